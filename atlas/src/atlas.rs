@@ -0,0 +1,798 @@
+//! Atlas pages, glyph entries, and eviction.
+//!
+//! A [`GlyphAtlas`] owns one or more fixed-size pages. Each inserted glyph is
+//! copied into a page at a spot chosen by the page's packer, and the caller
+//! gets back an [`AtlasEntry`] with both pixel and normalized UV coordinates.
+//!
+//! Packers cannot free individual rectangles, so eviction works per page: when
+//! every page is full and `max_pages` is reached, the least recently used page
+//! is wiped, the eviction callback fires for each glyph that lived on it, and
+//! packing starts over on that page.
+//!
+//! Padding only separates slots. For bilinear sampling at the quad edge to
+//! fade out instead of stopping hard, set [`AtlasConfig::outset`]: each glyph
+//! is stored with that many transparent pixels around its ink, and the entry's
+//! size, bearings, and UVs cover them. Straight-alpha color atlases can also
+//! turn on [`AtlasConfig::edge_bleed`] so those transparent texels carry the
+//! neighboring ink color instead of black.
+
+// this_file: atlas/src/atlas.rs
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use typf_core::types::BitmapFormat;
+
+use crate::packer::{Packer, PackerKind};
+use crate::AtlasError;
+
+/// Identifies one rasterized glyph: which font, which glyph, at what size and location.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlyphKey {
+    /// Hash of the font bytes.
+    pub font_id: u64,
+    /// Glyph ID within the font.
+    pub glyph_id: u32,
+    /// Font size in pixels, as `f32::to_bits`.
+    pub size_bits: u32,
+    /// Hash of the variation coordinates (0 for a static instance).
+    pub variations_hash: u64,
+}
+
+impl GlyphKey {
+    /// Build a key from the inputs that affect a glyph's pixels.
+    pub fn new(font_data: &[u8], glyph_id: u32, size: f32, variations: &[(String, f32)]) -> Self {
+        let mut hasher = DefaultHasher::new();
+        font_data.hash(&mut hasher);
+        let font_id = hasher.finish();
+
+        let variations_hash = if variations.is_empty() {
+            0
+        } else {
+            let mut hasher = DefaultHasher::new();
+            for (tag, value) in variations {
+                tag.hash(&mut hasher);
+                value.to_bits().hash(&mut hasher);
+            }
+            hasher.finish()
+        };
+
+        Self {
+            font_id,
+            glyph_id,
+            size_bits: size.to_bits(),
+            variations_hash,
+        }
+    }
+}
+
+/// A rasterized glyph ready to be copied into an atlas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphImage {
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+    /// Horizontal offset from the pen position to the left edge.
+    pub bearing_x: i32,
+    /// Vertical offset from the baseline up to the top edge.
+    pub bearing_y: i32,
+    /// Pixel layout of `data`; must match the atlas format.
+    pub format: BitmapFormat,
+    /// Row-major pixel data, tightly packed.
+    pub data: Vec<u8>,
+}
+
+impl GlyphImage {
+    /// Surround the image with `outset` transparent pixels on every side.
+    ///
+    /// Bearings move so the ink stays where it was relative to the pen. With
+    /// `edge_bleed`, transparent `Rgba8` pixels take the average color of
+    /// their inked neighbors, spreading outward one ring per outset pixel, so
+    /// filtering straight-alpha data never mixes in black. Other formats have
+    /// no separate color to bleed and only gain the transparent border.
+    pub fn with_outset(&self, outset: u32, edge_bleed: bool) -> GlyphImage {
+        let bpp = bytes_per_pixel(self.format);
+        let width = self.width + 2 * outset;
+        let height = self.height + 2 * outset;
+        let mut data = vec![0; width as usize * height as usize * bpp];
+
+        let src_row = self.width as usize * bpp;
+        for row in 0..self.height as usize {
+            let dst_start = ((row + outset as usize) * width as usize + outset as usize) * bpp;
+            data[dst_start..dst_start + src_row]
+                .copy_from_slice(&self.data[row * src_row..(row + 1) * src_row]);
+        }
+
+        if edge_bleed && self.format == BitmapFormat::Rgba8 {
+            bleed_rgba(&mut data, width as usize, height as usize, outset.max(1));
+        }
+
+        GlyphImage {
+            width,
+            height,
+            bearing_x: self.bearing_x - outset as i32,
+            bearing_y: self.bearing_y + outset as i32,
+            format: self.format,
+            data,
+        }
+    }
+}
+
+/// Copy the average color of inked neighbors into transparent pixels, `passes` rings deep.
+///
+/// Alpha is left at zero, so the bleed is invisible until a sampler blends it.
+fn bleed_rgba(data: &mut [u8], width: usize, height: usize, passes: u32) {
+    let mut filled: Vec<bool> = data.chunks_exact(4).map(|px| px[3] > 0).collect();
+    for _ in 0..passes {
+        let mut updates = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                if filled[y * width + x] {
+                    continue;
+                }
+                let (mut sum, mut count) = ([0u32; 3], 0u32);
+                for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                    for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                        let n = ny * width + nx;
+                        if filled[n] {
+                            for (acc, &channel) in sum.iter_mut().zip(&data[n * 4..n * 4 + 3]) {
+                                *acc += channel as u32;
+                            }
+                            count += 1;
+                        }
+                    }
+                }
+                if count > 0 {
+                    updates.push((y * width + x, sum.map(|c| (c / count) as u8)));
+                }
+            }
+        }
+        if updates.is_empty() {
+            break;
+        }
+        for (i, rgb) in updates {
+            data[i * 4..i * 4 + 3].copy_from_slice(&rgb);
+            filled[i] = true;
+        }
+    }
+}
+
+/// Normalized texture coordinates of a glyph within its page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvRect {
+    /// Left edge, 0.0..=1.0.
+    pub u0: f32,
+    /// Top edge, 0.0..=1.0.
+    pub v0: f32,
+    /// Right edge, 0.0..=1.0.
+    pub u1: f32,
+    /// Bottom edge, 0.0..=1.0.
+    pub v1: f32,
+}
+
+/// Where a glyph lives in the atlas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasEntry {
+    /// Index into [`GlyphAtlas::pages`].
+    pub page: usize,
+    /// Left edge on the page, in pixels.
+    pub x: u32,
+    /// Top edge on the page, in pixels.
+    pub y: u32,
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+    /// Horizontal offset from the pen position to the left edge.
+    pub bearing_x: i32,
+    /// Vertical offset from the baseline up to the top edge.
+    pub bearing_y: i32,
+    /// Normalized texture coordinates.
+    pub uv: UvRect,
+}
+
+/// Pixel rectangle on a page that changed since the last upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirtyRect {
+    /// Left edge.
+    pub x: u32,
+    /// Top edge.
+    pub y: u32,
+    /// Width.
+    pub width: u32,
+    /// Height.
+    pub height: u32,
+}
+
+impl DirtyRect {
+    fn union(self, other: DirtyRect) -> DirtyRect {
+        let x0 = self.x.min(other.x);
+        let y0 = self.y.min(other.y);
+        let x1 = (self.x + self.width).max(other.x + other.width);
+        let y1 = (self.y + self.height).max(other.y + other.height);
+        DirtyRect {
+            x: x0,
+            y: y0,
+            width: x1 - x0,
+            height: y1 - y0,
+        }
+    }
+}
+
+/// Settings for a [`GlyphAtlas`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasConfig {
+    /// Page width in pixels.
+    pub page_width: u32,
+    /// Page height in pixels.
+    pub page_height: u32,
+    /// Empty pixels kept around each glyph so bilinear sampling does not bleed.
+    pub padding: u32,
+    /// Transparent pixels added around each glyph's ink and included in its
+    /// quad and UVs, so edge texels filter against transparency (typically 1–2).
+    pub outset: u32,
+    /// Fill the outset of `Rgba8` glyphs with the neighboring ink color at zero
+    /// alpha; see [`GlyphImage::with_outset`]. Not needed for premultiplied data.
+    pub edge_bleed: bool,
+    /// Maximum number of pages before eviction kicks in.
+    pub max_pages: usize,
+    /// Pixel format of every page (`Gray8` for coverage, `Rgba8` for color).
+    pub format: BitmapFormat,
+    /// Packing algorithm for each page.
+    pub packer: PackerKind,
+}
+
+impl Default for AtlasConfig {
+    fn default() -> Self {
+        Self {
+            page_width: 1024,
+            page_height: 1024,
+            padding: 1,
+            outset: 0,
+            edge_bleed: false,
+            max_pages: 4,
+            format: BitmapFormat::Gray8,
+            packer: PackerKind::default(),
+        }
+    }
+}
+
+/// One texture-sized page of the atlas.
+#[derive(Debug, Clone)]
+pub struct AtlasPage {
+    width: u32,
+    height: u32,
+    format: BitmapFormat,
+    data: Vec<u8>,
+    packer: Packer,
+    dirty: Option<DirtyRect>,
+    generation: u64,
+    last_used: u64,
+}
+
+impl AtlasPage {
+    fn new(config: &AtlasConfig) -> Self {
+        let bpp = bytes_per_pixel(config.format);
+        Self {
+            width: config.page_width,
+            height: config.page_height,
+            format: config.format,
+            data: vec![0; config.page_width as usize * config.page_height as usize * bpp],
+            packer: Packer::new(config.packer, config.page_width, config.page_height),
+            dirty: None,
+            generation: 0,
+            last_used: 0,
+        }
+    }
+
+    /// Page width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Page height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Pixel format of [`data`](Self::data).
+    pub fn format(&self) -> BitmapFormat {
+        self.format
+    }
+
+    /// Row-major page pixels.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// How many times this page has been wiped by eviction.
+    ///
+    /// Cached UVs from an older generation point at stale pixels.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Region written since the last call, for partial texture uploads.
+    pub fn take_dirty(&mut self) -> Option<DirtyRect> {
+        self.dirty.take()
+    }
+
+    fn mark_dirty(&mut self, rect: DirtyRect) {
+        self.dirty = Some(match self.dirty {
+            Some(existing) => existing.union(rect),
+            None => rect,
+        });
+    }
+
+    fn clear(&mut self) {
+        self.data.fill(0);
+        self.packer.reset();
+        self.generation += 1;
+        self.mark_dirty(DirtyRect {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+        });
+    }
+
+    fn blit(&mut self, x: u32, y: u32, image: &GlyphImage) {
+        let bpp = bytes_per_pixel(self.format);
+        let row_bytes = image.width as usize * bpp;
+        for row in 0..image.height as usize {
+            let src = &image.data[row * row_bytes..(row + 1) * row_bytes];
+            let dst_start = ((y as usize + row) * self.width as usize + x as usize) * bpp;
+            self.data[dst_start..dst_start + row_bytes].copy_from_slice(src);
+        }
+        self.mark_dirty(DirtyRect {
+            x,
+            y,
+            width: image.width,
+            height: image.height,
+        });
+    }
+}
+
+/// Callback fired for every glyph removed when a page is evicted.
+pub type EvictionCallback = Box<dyn FnMut(&GlyphKey, &AtlasEntry) + Send>;
+
+/// Packs rasterized glyphs into one or more texture pages.
+pub struct GlyphAtlas {
+    config: AtlasConfig,
+    pages: Vec<AtlasPage>,
+    entries: HashMap<GlyphKey, AtlasEntry>,
+    on_evict: Option<EvictionCallback>,
+    clock: u64,
+    /// Pages eviction must skip, because quads already handed out use them
+    pinned: HashSet<usize>,
+}
+
+impl std::fmt::Debug for GlyphAtlas {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GlyphAtlas")
+            .field("config", &self.config)
+            .field("pages", &self.pages.len())
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
+impl GlyphAtlas {
+    /// Create an empty atlas. Pages are allocated lazily.
+    pub fn new(config: AtlasConfig) -> Self {
+        Self {
+            config,
+            pages: Vec::new(),
+            entries: HashMap::new(),
+            on_evict: None,
+            clock: 0,
+            pinned: HashSet::new(),
+        }
+    }
+
+    /// Register a callback for glyphs dropped by page eviction.
+    pub fn with_eviction_callback(
+        mut self,
+        callback: impl FnMut(&GlyphKey, &AtlasEntry) + Send + 'static,
+    ) -> Self {
+        self.on_evict = Some(Box::new(callback));
+        self
+    }
+
+    /// The settings this atlas was created with.
+    pub fn config(&self) -> &AtlasConfig {
+        &self.config
+    }
+
+    /// All allocated pages, indexed by [`AtlasEntry::page`].
+    pub fn pages(&self) -> &[AtlasPage] {
+        &self.pages
+    }
+
+    /// Mutable page access, mainly for [`AtlasPage::take_dirty`].
+    pub fn pages_mut(&mut self) -> &mut [AtlasPage] {
+        &mut self.pages
+    }
+
+    /// Number of glyphs currently stored.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// True when no glyphs are stored.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Look up a glyph, marking its page as recently used.
+    pub fn get(&mut self, key: &GlyphKey) -> Option<AtlasEntry> {
+        let entry = *self.entries.get(key)?;
+        self.touch(entry.page);
+        Some(entry)
+    }
+
+    /// Keep `page` from being evicted until [`unpin_all`](Self::unpin_all).
+    ///
+    /// A renderer pins the pages of the quads it has returned so far, so
+    /// glyphs inserted later in the same run cannot wipe them.
+    pub fn pin_page(&mut self, page: usize) {
+        self.pinned.insert(page);
+    }
+
+    /// Let every page be evicted again.
+    pub fn unpin_all(&mut self) {
+        self.pinned.clear();
+    }
+
+    /// Look up a glyph without affecting eviction order.
+    pub fn peek(&self, key: &GlyphKey) -> Option<&AtlasEntry> {
+        self.entries.get(key)
+    }
+
+    /// Copy a glyph into the atlas, or return its existing entry.
+    ///
+    /// The stored glyph grows by [`AtlasConfig::outset`] on every side, and the
+    /// returned entry's size and bearings describe the grown image. Tries
+    /// every page first, then opens a new page, and finally evicts the least
+    /// recently used unpinned page when `max_pages` is reached; with every
+    /// page pinned it fails with [`AtlasError::AllPagesPinned`].
+    pub fn insert(&mut self, key: GlyphKey, image: &GlyphImage) -> Result<AtlasEntry, AtlasError> {
+        if let Some(entry) = self.get(&key) {
+            return Ok(entry);
+        }
+
+        if image.format != self.config.format {
+            return Err(AtlasError::FormatMismatch {
+                expected: self.config.format,
+                found: image.format,
+            });
+        }
+        let expected_len =
+            image.width as usize * image.height as usize * bytes_per_pixel(image.format);
+        if image.data.len() != expected_len {
+            return Err(AtlasError::InvalidData {
+                expected: expected_len,
+                found: image.data.len(),
+            });
+        }
+
+        let outset_image;
+        let image = if self.config.outset > 0 || self.config.edge_bleed {
+            outset_image = image.with_outset(self.config.outset, self.config.edge_bleed);
+            &outset_image
+        } else {
+            image
+        };
+
+        let pad = self.config.padding;
+        let slot_w = image.width + 2 * pad;
+        let slot_h = image.height + 2 * pad;
+        let (page_width, page_height) = (self.config.page_width, self.config.page_height);
+        let too_large = || AtlasError::GlyphTooLarge {
+            width: image.width,
+            height: image.height,
+            page_width,
+            page_height,
+        };
+        if slot_w > page_width || slot_h > page_height {
+            return Err(too_large());
+        }
+
+        let slot = self
+            .pages
+            .iter_mut()
+            .enumerate()
+            .find_map(|(i, page)| page.packer.allocate(slot_w, slot_h).map(|pos| (i, pos)));
+
+        let (page_index, (x, y)) = match slot {
+            Some(found) => found,
+            None if self.pages.len() < self.config.max_pages.max(1) => {
+                self.pages.push(AtlasPage::new(&self.config));
+                let index = self.pages.len() - 1;
+                let pos = self.pages[index]
+                    .packer
+                    .allocate(slot_w, slot_h)
+                    .ok_or_else(too_large)?;
+                (index, pos)
+            },
+            None => {
+                let index = self.evict_lru_page().ok_or(AtlasError::AllPagesPinned {
+                    pages: self.pages.len(),
+                })?;
+                let pos = self.pages[index]
+                    .packer
+                    .allocate(slot_w, slot_h)
+                    .ok_or_else(too_large)?;
+                (index, pos)
+            },
+        };
+
+        let (gx, gy) = (x + pad, y + pad);
+        let page = &mut self.pages[page_index];
+        page.blit(gx, gy, image);
+
+        let (pw, ph) = (page.width as f32, page.height as f32);
+        let entry = AtlasEntry {
+            page: page_index,
+            x: gx,
+            y: gy,
+            width: image.width,
+            height: image.height,
+            bearing_x: image.bearing_x,
+            bearing_y: image.bearing_y,
+            uv: UvRect {
+                u0: gx as f32 / pw,
+                v0: gy as f32 / ph,
+                u1: (gx + image.width) as f32 / pw,
+                v1: (gy + image.height) as f32 / ph,
+            },
+        };
+        self.entries.insert(key, entry);
+        self.touch(page_index);
+        Ok(entry)
+    }
+
+    /// Drop every glyph and page, firing the eviction callback for each glyph.
+    pub fn clear(&mut self) {
+        let entries: Vec<_> = self.entries.drain().collect();
+        if let Some(callback) = self.on_evict.as_mut() {
+            for (key, entry) in &entries {
+                callback(key, entry);
+            }
+        }
+        self.pages.clear();
+        self.pinned.clear();
+    }
+
+    fn touch(&mut self, page: usize) {
+        self.clock += 1;
+        if let Some(p) = self.pages.get_mut(page) {
+            p.last_used = self.clock;
+        }
+    }
+
+    /// Wipe the least recently used unpinned page and return its index.
+    fn evict_lru_page(&mut self) -> Option<usize> {
+        let index = self
+            .pages
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.pinned.contains(i))
+            .min_by_key(|(_, p)| p.last_used)
+            .map(|(i, _)| i)?;
+
+        log::debug!("GlyphAtlas: evicting page {}", index);
+
+        let evicted: Vec<(GlyphKey, AtlasEntry)> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| e.page == index)
+            .map(|(k, e)| (*k, *e))
+            .collect();
+        for (key, _) in &evicted {
+            self.entries.remove(key);
+        }
+        if let Some(callback) = self.on_evict.as_mut() {
+            for (key, entry) in &evicted {
+                callback(key, entry);
+            }
+        }
+
+        self.pages[index].clear();
+        Some(index)
+    }
+}
+
+fn bytes_per_pixel(format: BitmapFormat) -> usize {
+    match format {
+        BitmapFormat::Rgba8 => 4,
+        BitmapFormat::Rgb8 => 3,
+        BitmapFormat::Gray8 | BitmapFormat::Gray1 => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn key(glyph_id: u32) -> GlyphKey {
+        GlyphKey::new(b"font", glyph_id, 16.0, &[])
+    }
+
+    fn image(width: u32, height: u32, value: u8) -> GlyphImage {
+        GlyphImage {
+            width,
+            height,
+            bearing_x: 1,
+            bearing_y: height as i32,
+            format: BitmapFormat::Gray8,
+            data: vec![value; (width * height) as usize],
+        }
+    }
+
+    fn small_config() -> AtlasConfig {
+        AtlasConfig {
+            page_width: 16,
+            page_height: 16,
+            padding: 1,
+            max_pages: 2,
+            ..AtlasConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_insert_when_new_glyph_then_pixels_and_uvs_match() {
+        let mut atlas = GlyphAtlas::new(small_config());
+        let entry = atlas.insert(key(1), &image(4, 2, 200)).unwrap();
+
+        assert_eq!(entry.page, 0);
+        assert_eq!((entry.x, entry.y), (1, 1), "padding offsets the glyph");
+        assert_eq!(entry.uv.u0, 1.0 / 16.0);
+        assert_eq!(entry.uv.v1, 3.0 / 16.0);
+
+        let page = &atlas.pages()[0];
+        assert_eq!(page.data()[16 + 1], 200);
+        assert_eq!(page.data()[0], 0, "padding stays empty");
+    }
+
+    #[test]
+    fn test_insert_when_key_exists_then_returns_same_entry() {
+        let mut atlas = GlyphAtlas::new(small_config());
+        let first = atlas.insert(key(1), &image(4, 4, 1)).unwrap();
+        let second = atlas.insert(key(1), &image(4, 4, 2)).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(atlas.len(), 1);
+    }
+
+    #[test]
+    fn test_insert_when_page_full_then_opens_second_page() {
+        let mut atlas = GlyphAtlas::new(small_config());
+        atlas.insert(key(1), &image(14, 14, 1)).unwrap();
+        let entry = atlas.insert(key(2), &image(14, 14, 1)).unwrap();
+        assert_eq!(entry.page, 1);
+        assert_eq!(atlas.pages().len(), 2);
+    }
+
+    #[test]
+    fn test_insert_when_max_pages_reached_then_evicts_lru_and_notifies() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&evicted);
+        let mut atlas = GlyphAtlas::new(small_config()).with_eviction_callback(move |k, _| {
+            sink.lock().unwrap().push(k.glyph_id);
+        });
+
+        atlas.insert(key(1), &image(14, 14, 1)).unwrap();
+        atlas.insert(key(2), &image(14, 14, 1)).unwrap();
+        // Touch glyph 1 so page 1 (glyph 2) becomes the eviction candidate.
+        atlas.get(&key(1)).unwrap();
+        let entry = atlas.insert(key(3), &image(14, 14, 1)).unwrap();
+
+        assert_eq!(entry.page, 1);
+        assert_eq!(*evicted.lock().unwrap(), vec![2]);
+        assert!(atlas.peek(&key(2)).is_none());
+        assert!(atlas.peek(&key(1)).is_some());
+        assert_eq!(atlas.pages()[1].generation(), 1);
+    }
+
+    #[test]
+    fn test_insert_when_lru_page_pinned_then_evicts_another() {
+        let mut atlas = GlyphAtlas::new(small_config());
+        atlas.insert(key(1), &image(14, 14, 1)).unwrap();
+        atlas.insert(key(2), &image(14, 14, 1)).unwrap();
+        atlas.get(&key(1)).unwrap();
+        atlas.pin_page(1);
+
+        let entry = atlas.insert(key(3), &image(14, 14, 1)).unwrap();
+        assert_eq!(entry.page, 0, "the pinned LRU page survives");
+        assert!(atlas.peek(&key(2)).is_some());
+
+        atlas.pin_page(0);
+        let err = atlas.insert(key(4), &image(14, 14, 1)).unwrap_err();
+        assert!(matches!(err, AtlasError::AllPagesPinned { pages: 2 }));
+
+        atlas.unpin_all();
+        assert!(atlas.insert(key(4), &image(14, 14, 1)).is_ok());
+    }
+
+    #[test]
+    fn test_insert_when_glyph_too_large_then_errors() {
+        let mut atlas = GlyphAtlas::new(small_config());
+        let err = atlas.insert(key(1), &image(15, 4, 1)).unwrap_err();
+        assert!(matches!(err, AtlasError::GlyphTooLarge { .. }));
+    }
+
+    #[test]
+    fn test_insert_when_format_differs_then_errors() {
+        let mut atlas = GlyphAtlas::new(small_config());
+        let mut rgba = image(2, 2, 1);
+        rgba.format = BitmapFormat::Rgba8;
+        rgba.data = vec![0; 16];
+        let err = atlas.insert(key(1), &rgba).unwrap_err();
+        assert!(matches!(err, AtlasError::FormatMismatch { .. }));
+    }
+
+    #[test]
+    fn test_take_dirty_when_glyphs_written_then_returns_union_once() {
+        let mut atlas = GlyphAtlas::new(small_config());
+        atlas.insert(key(1), &image(2, 2, 1)).unwrap();
+        atlas.insert(key(2), &image(2, 2, 1)).unwrap();
+        let dirty = atlas.pages_mut()[0].take_dirty().unwrap();
+        assert_eq!((dirty.x, dirty.y), (1, 1));
+        assert!(dirty.width >= 5);
+        assert!(atlas.pages_mut()[0].take_dirty().is_none());
+    }
+
+    #[test]
+    fn test_insert_when_outset_then_entry_grows_and_bearings_shift() {
+        let mut atlas = GlyphAtlas::new(AtlasConfig {
+            outset: 2,
+            ..small_config()
+        });
+        let entry = atlas.insert(key(1), &image(3, 2, 255)).unwrap();
+
+        assert_eq!((entry.width, entry.height), (7, 6));
+        assert_eq!((entry.bearing_x, entry.bearing_y), (-1, 4));
+        assert_eq!((entry.x, entry.y), (1, 1), "outset sits inside the padding");
+
+        let page = &atlas.pages()[0];
+        let at = |x: u32, y: u32| page.data()[(y * 16 + x) as usize];
+        assert_eq!(at(2, 2), 0, "outset ring is transparent");
+        assert_eq!(at(3, 3), 255, "ink starts after the outset");
+    }
+
+    #[test]
+    fn test_with_outset_when_edge_bleed_then_color_spreads_at_zero_alpha() {
+        let red = GlyphImage {
+            width: 1,
+            height: 1,
+            bearing_x: 0,
+            bearing_y: 1,
+            format: BitmapFormat::Rgba8,
+            data: vec![255, 0, 0, 255],
+        };
+        let out = red.with_outset(2, true);
+        assert_eq!((out.width, out.height), (5, 5));
+
+        let px = |x: usize, y: usize| &out.data[(y * 5 + x) * 4..(y * 5 + x) * 4 + 4];
+        assert_eq!(px(2, 2), &[255, 0, 0, 255]);
+        assert_eq!(px(1, 2), &[255, 0, 0, 0]);
+        assert_eq!(px(0, 0), &[255, 0, 0, 0], "second ring is reached too");
+
+        let plain = red.with_outset(2, false);
+        assert_eq!(&plain.data[0..4], &[0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_glyph_key_when_inputs_differ_then_keys_differ() {
+        let base = GlyphKey::new(b"font", 1, 16.0, &[]);
+        assert_ne!(base, GlyphKey::new(b"font", 1, 17.0, &[]));
+        assert_ne!(base, GlyphKey::new(b"other", 1, 16.0, &[]));
+        assert_ne!(
+            base,
+            GlyphKey::new(b"font", 1, 16.0, &[("wght".to_string(), 700.0)])
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! Glyph atlas packing for GPU text rendering.
+//!
+//! GPU text stacks rarely want one bitmap per string. They want every glyph
+//! rasterized once, packed into a few large textures, and referenced by UV
+//! rectangles. This crate provides that layer for Typf:
+//!
+//! - [`GlyphAtlas`] packs rasterized glyphs from any renderer into fixed-size
+//!   pages with a shelf or skyline packer, returns UV rects, tracks dirty
+//!   regions for partial uploads, and evicts whole pages when full.
+//! - [`AtlasRenderer`] (feature `opixa`, on by default) rasterizes shaped
+//!   text with Opixa straight into an atlas and returns one quad per glyph.
+//!
+//! ```
+//! use typf_atlas::{AtlasConfig, GlyphAtlas, GlyphImage, GlyphKey};
+//! use typf_core::types::BitmapFormat;
+//!
+//! let mut atlas = GlyphAtlas::new(AtlasConfig::default());
+//! let image = GlyphImage {
+//!     width: 8,
+//!     height: 10,
+//!     bearing_x: 1,
+//!     bearing_y: 10,
+//!     format: BitmapFormat::Gray8,
+//!     data: vec![255; 80],
+//! };
+//! let entry = atlas.insert(GlyphKey::new(b"font bytes", 42, 16.0, &[]), &image).unwrap();
+//! assert_eq!(entry.page, 0);
+//! ```
+
+// this_file: atlas/src/lib.rs
+
+pub mod atlas;
+pub mod packer;
+#[cfg(feature = "opixa")]
+pub mod renderer;
+
+pub use atlas::{
+    AtlasConfig, AtlasEntry, AtlasPage, DirtyRect, EvictionCallback, GlyphAtlas, GlyphImage,
+    GlyphKey, UvRect,
+};
+pub use packer::{Packer, PackerKind, ShelfPacker, SkylinePacker};
+#[cfg(feature = "opixa")]
+pub use renderer::{AtlasQuad, AtlasRenderer, AtlasText};
+
+use typf_core::error::{Classify, ErrorKind};
+use typf_core::types::BitmapFormat;
+
+/// Errors from inserting glyphs into an atlas.
+#[derive(Debug, thiserror::Error)]
+pub enum AtlasError {
+    #[error("Glyph {width}x{height} does not fit on a {page_width}x{page_height} atlas page")]
+    GlyphTooLarge {
+        width: u32,
+        height: u32,
+        page_width: u32,
+        page_height: u32,
+    },
+
+    #[error("Glyph format {found:?} does not match atlas format {expected:?}")]
+    FormatMismatch {
+        expected: BitmapFormat,
+        found: BitmapFormat,
+    },
+
+    #[error("Glyph data has {found} bytes, expected {expected}")]
+    InvalidData { expected: usize, found: usize },
+
+    #[error("All {pages} atlas pages are pinned by the current render; raise max_pages")]
+    AllPagesPinned { pages: usize },
+}
+
+impl Classify for AtlasError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            AtlasError::GlyphTooLarge { .. } | AtlasError::AllPagesPinned { .. } => {
+                ErrorKind::LimitExceeded
+            },
+            AtlasError::FormatMismatch { .. } | AtlasError::InvalidData { .. } => {
+                ErrorKind::InvalidInput
+            },
+        }
+    }
+}
@@ -0,0 +1,306 @@
+//! Rectangle packers that place glyphs on an atlas page.
+//!
+//! Two strategies are provided. The shelf packer is the simplest: glyphs fill
+//! horizontal rows whose height is set by the first glyph placed in them. It is
+//! fast and works well when glyph heights are similar, as they are for a
+//! single font at a single size. The skyline packer tracks the top edge of
+//! everything placed so far and drops each new rectangle into the lowest spot
+//! that fits, which wastes less space on mixed sizes and emoji.
+
+// this_file: atlas/src/packer.rs
+
+/// Which packing algorithm an atlas page uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PackerKind {
+    /// Row-based packing; cheapest and good for uniform glyph heights.
+    Shelf,
+    /// Bottom-left skyline packing; tighter for mixed sizes.
+    #[default]
+    Skyline,
+}
+
+/// A packer for one page, dispatching to the chosen algorithm.
+#[derive(Debug, Clone)]
+pub enum Packer {
+    /// Row-based packer.
+    Shelf(ShelfPacker),
+    /// Skyline packer.
+    Skyline(SkylinePacker),
+}
+
+impl Packer {
+    /// Create an empty packer of the given kind for a `width` x `height` page.
+    pub fn new(kind: PackerKind, width: u32, height: u32) -> Self {
+        match kind {
+            PackerKind::Shelf => Packer::Shelf(ShelfPacker::new(width, height)),
+            PackerKind::Skyline => Packer::Skyline(SkylinePacker::new(width, height)),
+        }
+    }
+
+    /// Reserve a `width` x `height` rectangle, returning its top-left corner.
+    pub fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        match self {
+            Packer::Shelf(p) => p.allocate(width, height),
+            Packer::Skyline(p) => p.allocate(width, height),
+        }
+    }
+
+    /// Forget every allocation so the page can be reused.
+    pub fn reset(&mut self) {
+        match self {
+            Packer::Shelf(p) => p.reset(),
+            Packer::Skyline(p) => p.reset(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    cursor_x: u32,
+}
+
+/// Packs rectangles into horizontal shelves.
+#[derive(Debug, Clone)]
+pub struct ShelfPacker {
+    width: u32,
+    height: u32,
+    shelves: Vec<Shelf>,
+}
+
+impl ShelfPacker {
+    /// Create an empty shelf packer.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            shelves: Vec::new(),
+        }
+    }
+
+    /// Reserve a rectangle, preferring the shortest shelf it fits on.
+    pub fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if width == 0 || height == 0 || width > self.width || height > self.height {
+            return None;
+        }
+
+        let page_width = self.width;
+        let best = self
+            .shelves
+            .iter_mut()
+            .filter(|s| s.height >= height && page_width - s.cursor_x >= width)
+            .min_by_key(|s| s.height);
+        if let Some(shelf) = best {
+            let pos = (shelf.cursor_x, shelf.y);
+            shelf.cursor_x += width;
+            return Some(pos);
+        }
+
+        let y = self.shelves.last().map(|s| s.y + s.height).unwrap_or(0);
+        if self.height - y < height {
+            return None;
+        }
+        self.shelves.push(Shelf {
+            y,
+            height,
+            cursor_x: width,
+        });
+        Some((0, y))
+    }
+
+    /// Drop all shelves.
+    pub fn reset(&mut self) {
+        self.shelves.clear();
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SkylineNode {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+/// Packs rectangles along a bottom-left skyline.
+#[derive(Debug, Clone)]
+pub struct SkylinePacker {
+    width: u32,
+    height: u32,
+    nodes: Vec<SkylineNode>,
+}
+
+impl SkylinePacker {
+    /// Create an empty skyline packer.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            nodes: vec![SkylineNode { x: 0, y: 0, width }],
+        }
+    }
+
+    /// Reserve a rectangle at the lowest (then leftmost) position that fits.
+    pub fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if width == 0 || height == 0 || width > self.width || height > self.height {
+            return None;
+        }
+
+        let mut best: Option<(usize, u32, u32)> = None;
+        for i in 0..self.nodes.len() {
+            if let Some(y) = self.fit(i, width, height) {
+                let better = match best {
+                    Some((_, best_y, best_w)) => {
+                        y < best_y || (y == best_y && self.nodes[i].width < best_w)
+                    },
+                    None => true,
+                };
+                if better {
+                    best = Some((i, y, self.nodes[i].width));
+                }
+            }
+        }
+
+        let (index, y, _) = best?;
+        let x = self.nodes[index].x;
+        self.insert_node(
+            index,
+            SkylineNode {
+                x,
+                y: y + height,
+                width,
+            },
+        );
+        Some((x, y))
+    }
+
+    /// Return to a single flat skyline.
+    pub fn reset(&mut self) {
+        self.nodes.clear();
+        self.nodes.push(SkylineNode {
+            x: 0,
+            y: 0,
+            width: self.width,
+        });
+    }
+
+    /// Height at which a `width` x `height` rectangle would rest if placed at node `index`.
+    fn fit(&self, index: usize, width: u32, height: u32) -> Option<u32> {
+        let x = self.nodes[index].x;
+        if x + width > self.width {
+            return None;
+        }
+
+        let mut remaining = width as i64;
+        let mut y = 0;
+        for node in &self.nodes[index..] {
+            if remaining <= 0 {
+                break;
+            }
+            y = y.max(node.y);
+            if y + height > self.height {
+                return None;
+            }
+            remaining -= node.width as i64;
+        }
+        Some(y)
+    }
+
+    fn insert_node(&mut self, index: usize, node: SkylineNode) {
+        self.nodes.insert(index, node);
+
+        // Trim or remove the nodes now covered by the new one.
+        let right = node.x + node.width;
+        let i = index + 1;
+        while i < self.nodes.len() {
+            let next = self.nodes[i];
+            if next.x >= right {
+                break;
+            }
+            let overlap = right - next.x;
+            if overlap >= next.width {
+                self.nodes.remove(i);
+            } else {
+                self.nodes[i].x += overlap;
+                self.nodes[i].width -= overlap;
+                break;
+            }
+        }
+
+        // Merge neighbours that ended up at the same height.
+        let mut i = 0;
+        while i + 1 < self.nodes.len() {
+            if self.nodes[i].y == self.nodes[i + 1].y {
+                self.nodes[i].width += self.nodes[i + 1].width;
+                self.nodes.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overlaps(a: (u32, u32, u32, u32), b: (u32, u32, u32, u32)) -> bool {
+        a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+    }
+
+    fn pack_many(kind: PackerKind) -> Vec<(u32, u32, u32, u32)> {
+        let mut packer = Packer::new(kind, 64, 64);
+        let mut placed = Vec::new();
+        for i in 0..40u32 {
+            let (w, h) = (4 + i % 5, 6 + i % 3);
+            if let Some((x, y)) = packer.allocate(w, h) {
+                placed.push((x, y, w, h));
+            }
+        }
+        placed
+    }
+
+    #[test]
+    fn test_packers_when_many_rects_then_no_overlap_and_in_bounds() {
+        for kind in [PackerKind::Shelf, PackerKind::Skyline] {
+            let placed = pack_many(kind);
+            assert!(placed.len() > 20, "{:?} placed only {}", kind, placed.len());
+            for (i, a) in placed.iter().enumerate() {
+                assert!(a.0 + a.2 <= 64 && a.1 + a.3 <= 64);
+                for b in &placed[i + 1..] {
+                    assert!(!overlaps(*a, *b), "{:?}: {:?} overlaps {:?}", kind, a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_packers_when_rect_too_large_then_none() {
+        for kind in [PackerKind::Shelf, PackerKind::Skyline] {
+            let mut packer = Packer::new(kind, 16, 16);
+            assert_eq!(packer.allocate(17, 4), None);
+            assert_eq!(packer.allocate(4, 17), None);
+            assert_eq!(packer.allocate(0, 4), None);
+        }
+    }
+
+    #[test]
+    fn test_packers_when_full_then_reset_frees_space() {
+        for kind in [PackerKind::Shelf, PackerKind::Skyline] {
+            let mut packer = Packer::new(kind, 8, 8);
+            assert_eq!(packer.allocate(8, 8), Some((0, 0)));
+            assert_eq!(packer.allocate(1, 1), None);
+            packer.reset();
+            assert_eq!(packer.allocate(8, 8), Some((0, 0)));
+        }
+    }
+
+    #[test]
+    fn test_skyline_when_step_then_fills_lowest_gap() {
+        let mut packer = SkylinePacker::new(10, 10);
+        assert_eq!(packer.allocate(4, 6), Some((0, 0)));
+        assert_eq!(packer.allocate(6, 2), Some((4, 0)));
+        // Lowest spot for a 6-wide rect is on top of the short one.
+        assert_eq!(packer.allocate(6, 2), Some((4, 2)));
+    }
+}
@@ -0,0 +1,290 @@
+//! Render shaped text as references into a glyph atlas.
+//!
+//! Instead of producing one bitmap per string, [`AtlasRenderer`] rasterizes
+//! each distinct glyph once with Opixa, stores it in a [`GlyphAtlas`], and
+//! returns a list of quads that point into the atlas pages. A GPU text stack
+//! uploads the dirty page regions and draws one textured quad per glyph.
+
+// this_file: atlas/src/renderer.rs
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use typf_core::{
+    error::{RenderError, Result},
+    traits::FontRef,
+    types::{BitmapFormat, ShapingResult},
+    GlyphSource, RenderParams,
+};
+use typf_render_opixa::{rasterizer::GlyphRasterizer, DropoutMode, FillRule};
+
+use crate::atlas::{AtlasConfig, GlyphAtlas, GlyphImage, GlyphKey, UvRect};
+
+/// Most empty glyphs remembered before the list starts over
+const MAX_EMPTY_GLYPHS: usize = 4096;
+
+/// One glyph of a rendered run, positioned relative to the run origin.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasQuad {
+    /// Glyph ID this quad draws.
+    pub glyph_id: u32,
+    /// Cluster index from shaping.
+    pub cluster: u32,
+    /// Atlas page holding the glyph.
+    pub page: usize,
+    /// Left edge of the quad, in pixels from the run origin.
+    pub x: f32,
+    /// Top edge of the quad, in pixels below the baseline (negative is above).
+    pub y: f32,
+    /// Quad width in pixels.
+    pub width: u32,
+    /// Quad height in pixels.
+    pub height: u32,
+    /// Texture coordinates on `page`.
+    pub uv: UvRect,
+}
+
+/// Text rendered as atlas references.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasText {
+    /// One quad per visible glyph, in shaping order.
+    pub quads: Vec<AtlasQuad>,
+    /// Total advance of the run in pixels.
+    pub advance_width: f32,
+}
+
+/// Renderer that writes glyphs into an atlas and returns quads.
+#[derive(Debug)]
+pub struct AtlasRenderer {
+    atlas: GlyphAtlas,
+    /// Glyphs that rasterized to nothing (spaces), so they are not retried;
+    /// at most [`MAX_EMPTY_GLYPHS`].
+    empty: HashSet<GlyphKey>,
+}
+
+impl AtlasRenderer {
+    /// Create a renderer with a fresh coverage (`Gray8`) atlas.
+    pub fn new(config: AtlasConfig) -> Self {
+        Self::with_atlas(GlyphAtlas::new(config))
+    }
+
+    /// Create a renderer around an existing atlas.
+    pub fn with_atlas(atlas: GlyphAtlas) -> Self {
+        Self {
+            atlas,
+            empty: HashSet::new(),
+        }
+    }
+
+    /// The atlas glyphs are written into.
+    pub fn atlas(&self) -> &GlyphAtlas {
+        &self.atlas
+    }
+
+    /// Mutable atlas access, for taking dirty regions after a render.
+    pub fn atlas_mut(&mut self) -> &mut GlyphAtlas {
+        &mut self.atlas
+    }
+
+    /// Rasterize any glyphs not yet in the atlas and lay out the run.
+    ///
+    /// Colors, background, and padding in `params` are ignored; only the
+    /// variation coordinates and glyph-source policy matter here. Pages
+    /// holding this run's glyphs are pinned while it renders, so every
+    /// returned quad stays valid; a run needing more than `max_pages` pages
+    /// fails.
+    pub fn render(
+        &mut self,
+        shaped: &ShapingResult,
+        font: Arc<dyn FontRef>,
+        params: &RenderParams,
+    ) -> Result<AtlasText> {
+        if self.atlas.config().format != BitmapFormat::Gray8 {
+            return Err(RenderError::FormatNotSupported(
+                "AtlasRenderer rasterizes coverage and needs a Gray8 atlas".into(),
+            )
+            .into());
+        }
+
+        let allows_outline = params
+            .glyph_sources
+            .effective_order()
+            .iter()
+            .any(|s| matches!(s, GlyphSource::Glyf | GlyphSource::Cff | GlyphSource::Cff2));
+        if !allows_outline {
+            return Err(RenderError::BackendError(
+                "atlas renderer requires outline glyph sources".to_string(),
+            )
+            .into());
+        }
+
+        let placed = self.place(shaped, font.data(), params);
+        self.atlas.unpin_all();
+        placed
+    }
+
+    /// Rasterize and lay out the glyphs of one run, pinning their pages
+    fn place(
+        &mut self,
+        shaped: &ShapingResult,
+        font_data: &[u8],
+        params: &RenderParams,
+    ) -> Result<AtlasText> {
+        let size = shaped.advance_height;
+        let mut rasterizer: Option<GlyphRasterizer<'_>> = None;
+        let mut quads = Vec::with_capacity(shaped.glyphs.len());
+        // Hash the font and variations once; glyphs differ only by ID
+        let run_key = GlyphKey::new(font_data, 0, size, &params.variations);
+
+        for glyph in &shaped.glyphs {
+            let key = GlyphKey {
+                glyph_id: glyph.id,
+                ..run_key
+            };
+            if self.empty.contains(&key) {
+                continue;
+            }
+
+            let entry = match self.atlas.get(&key) {
+                Some(entry) => entry,
+                None => {
+                    if rasterizer.is_none() {
+                        let mut r = GlyphRasterizer::new(font_data, size)
+                            .map_err(RenderError::BackendError)?;
+                        if !params.variations.is_empty() {
+                            if let Err(e) = r.set_variations(&params.variations) {
+                                log::warn!("Variable font setup failed: {}", e);
+                            }
+                        }
+                        rasterizer = Some(r);
+                    }
+                    let Some(rast) = rasterizer.as_mut() else {
+                        continue;
+                    };
+
+                    let bitmap = match rast.render_glyph(
+                        glyph.id,
+                        FillRule::NonZeroWinding,
+                        DropoutMode::None,
+                    ) {
+                        Ok(b) => b,
+                        Err(e) => {
+                            log::warn!("Glyph {} rasterization failed: {}", glyph.id, e);
+                            continue;
+                        },
+                    };
+                    if bitmap.width == 0 || bitmap.height == 0 {
+                        if self.empty.len() >= MAX_EMPTY_GLYPHS {
+                            self.empty.clear();
+                        }
+                        self.empty.insert(key);
+                        continue;
+                    }
+
+                    let image = GlyphImage {
+                        width: bitmap.width,
+                        height: bitmap.height,
+                        bearing_x: bitmap.left,
+                        bearing_y: bitmap.top,
+                        format: BitmapFormat::Gray8,
+                        data: bitmap.data,
+                    };
+                    self.atlas
+                        .insert(key, &image)
+                        .map_err(|e| RenderError::BackendError(e.to_string()))?
+                },
+            };
+            self.atlas.pin_page(entry.page);
+
+            quads.push(AtlasQuad {
+                glyph_id: glyph.id,
+                cluster: glyph.cluster,
+                page: entry.page,
+                x: glyph.x + entry.bearing_x as f32,
+                y: glyph.y - entry.bearing_y as f32,
+                width: entry.width,
+                height: entry.height,
+                uv: entry.uv,
+            });
+        }
+
+        Ok(AtlasText {
+            quads,
+            advance_width: shaped.advance_width,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use typf_core::types::{Direction, PositionedGlyph};
+
+    struct EmptyFont;
+
+    impl FontRef for EmptyFont {
+        fn data(&self) -> &[u8] {
+            &[]
+        }
+        fn units_per_em(&self) -> u16 {
+            1000
+        }
+        fn glyph_id(&self, _ch: char) -> Option<u32> {
+            Some(0)
+        }
+        fn advance_width(&self, _glyph_id: u32) -> f32 {
+            500.0
+        }
+    }
+
+    #[test]
+    fn test_render_when_no_glyphs_then_no_quads() {
+        let mut renderer = AtlasRenderer::new(AtlasConfig::default());
+        let shaped = ShapingResult {
+            glyphs: vec![],
+            advance_width: 0.0,
+            advance_height: 16.0,
+            direction: Direction::LeftToRight,
+        };
+        let text = renderer
+            .render(&shaped, Arc::new(EmptyFont), &RenderParams::default())
+            .unwrap();
+        assert!(text.quads.is_empty());
+        assert!(renderer.atlas().is_empty());
+    }
+
+    #[test]
+    fn test_render_when_font_unparseable_then_errors() {
+        let mut renderer = AtlasRenderer::new(AtlasConfig::default());
+        let shaped = ShapingResult {
+            glyphs: vec![PositionedGlyph {
+                id: 1,
+                x: 0.0,
+                y: 0.0,
+                advance: 10.0,
+                cluster: 0,
+            }],
+            advance_width: 10.0,
+            advance_height: 16.0,
+            direction: Direction::LeftToRight,
+        };
+        let result = renderer.render(&shaped, Arc::new(EmptyFont), &RenderParams::default());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_render_when_rgba_atlas_then_rejected() {
+        let mut renderer = AtlasRenderer::new(AtlasConfig {
+            format: BitmapFormat::Rgba8,
+            ..AtlasConfig::default()
+        });
+        let shaped = ShapingResult {
+            glyphs: vec![],
+            advance_width: 0.0,
+            advance_height: 16.0,
+            direction: Direction::LeftToRight,
+        };
+        let result = renderer.render(&shaped, Arc::new(EmptyFont), &RenderParams::default());
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,85 @@
+//! Integration tests for the atlas renderer with a real font.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use typf_atlas::{AtlasConfig, AtlasRenderer};
+use typf_core::{
+    traits::FontRef,
+    types::{Direction, PositionedGlyph, ShapingResult},
+    RenderParams,
+};
+
+struct TestFont {
+    data: Vec<u8>,
+}
+
+impl FontRef for TestFont {
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn units_per_em(&self) -> u16 {
+        1000
+    }
+
+    fn glyph_id(&self, _ch: char) -> Option<u32> {
+        Some(0)
+    }
+
+    fn advance_width(&self, _glyph_id: u32) -> f32 {
+        500.0
+    }
+}
+
+fn load_font(name: &str) -> Option<Arc<dyn FontRef>> {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.pop(); // root
+    path.push("test-fonts");
+    path.push(name);
+    let data = fs::read(&path).ok()?;
+    Some(Arc::new(TestFont { data }) as Arc<dyn FontRef>)
+}
+
+fn shaped(ids: &[u32]) -> ShapingResult {
+    let glyphs = ids
+        .iter()
+        .enumerate()
+        .map(|(i, &id)| PositionedGlyph {
+            id,
+            x: i as f32 * 20.0,
+            y: 0.0,
+            advance: 20.0,
+            cluster: i as u32,
+        })
+        .collect();
+    ShapingResult {
+        glyphs,
+        advance_width: ids.len() as f32 * 20.0,
+        advance_height: 32.0,
+        direction: Direction::LeftToRight,
+    }
+}
+
+#[test]
+fn test_atlas_renderer_reuses_repeated_glyphs() {
+    let Some(font) = load_font("NotoSans-Regular.ttf") else {
+        eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+        return;
+    };
+
+    let mut renderer = AtlasRenderer::new(AtlasConfig::default());
+    let text = renderer
+        .render(&shaped(&[36, 72, 36]), font, &RenderParams::default())
+        .expect("render should succeed");
+
+    assert_eq!(text.quads.len(), 3);
+    assert_eq!(renderer.atlas().len(), 2, "repeated glyph stored once");
+    assert_eq!(text.quads[0].uv, text.quads[2].uv);
+    assert!(text.quads[0].y < 0.0, "glyph sits above the baseline");
+
+    let page = &mut renderer.atlas_mut().pages_mut()[0];
+    assert!(page.take_dirty().is_some());
+    assert!(page.data().iter().any(|&v| v > 0));
+}
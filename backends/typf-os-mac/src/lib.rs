@@ -18,6 +18,15 @@
 //!
 //! The linra approach eliminates per-glyph overhead and allows CoreText
 //! to optimize internally (e.g., batch GPU operations).
+//!
+//! ## Fallback
+//!
+//! CoreText draws characters the font lacks with fonts from its cascade
+//! list, which defaults to the system's choice for the font's language.
+//! [`CoreTextLinraRenderer::with_cascade_list`] replaces it with an explicit
+//! order of [`CascadeFont`]s (font data, like `TextOptions::fallback_fonts`
+//! in the typf pipeline, or installed family names), so fallback follows the
+//! same chain on macOS as with the HarfBuzz backends.
 
 #![cfg(target_os = "macos")]
 
@@ -28,16 +37,17 @@ use std::ptr::{self, NonNull};
 use std::sync::Arc;
 
 use objc2_core_foundation::{
-    CFDictionary, CFMutableAttributedString, CFNumber, CFRange, CFRetained, CFString, CFType,
-    CGFloat, CGPoint, CGRect, CGSize,
+    CFArray, CFData, CFDictionary, CFMutableAttributedString, CFNumber, CFRange, CFRetained,
+    CFString, CFType, CGFloat, CGPoint, CGRect, CGSize,
 };
 use objc2_core_graphics::{
     CGBitmapContextCreate, CGColorSpace, CGContext, CGDataProvider, CGFont, CGImageAlphaInfo,
     CGTextDrawingMode,
 };
 use objc2_core_text::{
-    kCTFontAttributeName, kCTFontVariationAttribute, kCTKernAttributeName,
-    kCTLigatureAttributeName, CTFont, CTFontDescriptor, CTLine,
+    kCTFontAttributeName, kCTFontCascadeListAttribute, kCTFontVariationAttribute,
+    kCTKernAttributeName, kCTLigatureAttributeName, CTFont, CTFontDescriptor,
+    CTFontManagerCreateFontDescriptorFromData, CTLine,
 };
 
 use lru::LruCache;
@@ -46,7 +56,7 @@ use typf_core::{
     error::{RenderError, Result, TypfError},
     linra::{LinraRenderParams, LinraRenderer},
     traits::FontRef,
-    types::{BitmapData, BitmapFormat, RenderOutput},
+    types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput},
     Color,
 };
 
@@ -71,6 +81,8 @@ struct FontCacheKey {
     size: u32,
     /// Sorted variation string
     variations: String,
+    /// Hash of the renderer's cascade list (0 when it has none)
+    cascade: u64,
 }
 
 impl FontCacheKey {
@@ -96,8 +108,45 @@ impl FontCacheKey {
             font_hash,
             size: (size * 100.0) as u32,
             variations: var_str,
+            cascade: 0,
         }
     }
+
+    /// Key for the same font built with the cascade list hashed to `cascade`
+    fn with_cascade(mut self, cascade: u64) -> Self {
+        self.cascade = cascade;
+        self
+    }
+}
+
+/// One entry in a CoreText cascade (fallback) list
+#[derive(Clone)]
+pub enum CascadeFont {
+    /// A font given as data, loaded the same way as the primary font
+    Data(Arc<dyn FontRef>),
+    /// An installed font by family or PostScript name, e.g. `"Apple Color Emoji"`
+    Named(String),
+}
+
+impl std::fmt::Debug for CascadeFont {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CascadeFont::Data(font) => write!(f, "Data({} bytes)", font.data().len()),
+            CascadeFont::Named(name) => f.debug_tuple("Named").field(name).finish(),
+        }
+    }
+}
+
+impl From<Arc<dyn FontRef>> for CascadeFont {
+    fn from(font: Arc<dyn FontRef>) -> Self {
+        CascadeFont::Data(font)
+    }
+}
+
+impl From<&str> for CascadeFont {
+    fn from(name: &str) -> Self {
+        CascadeFont::Named(name.to_string())
+    }
 }
 
 /// Cached font entry that keeps font data alive
@@ -125,13 +174,49 @@ thread_local! {
 /// This renderer combines text shaping and rendering into a single CTLineDraw
 /// call for maximum performance on macOS.
 pub struct CoreTextLinraRenderer {
-    // No fields - cache is thread-local
+    /// Explicit fallback order; empty keeps CoreText's default cascade
+    cascade: Vec<CascadeFont>,
+    /// Hash of `cascade`, part of the font cache key (the cache is thread-local)
+    cascade_hash: u64,
 }
 
 impl CoreTextLinraRenderer {
     /// Creates a new linra renderer
     pub fn new() -> Self {
-        Self {}
+        Self {
+            cascade: Vec::new(),
+            cascade_hash: 0,
+        }
+    }
+
+    /// Fall back through `fonts`, in order, for characters the font lacks
+    ///
+    /// Sets `kCTFontCascadeListAttribute` on every font this renderer
+    /// creates. CoreText still falls back to the system for characters no
+    /// listed font covers.
+    pub fn with_cascade_list(mut self, fonts: impl IntoIterator<Item = CascadeFont>) -> Self {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        self.cascade = fonts.into_iter().collect();
+        self.cascade_hash = if self.cascade.is_empty() {
+            0
+        } else {
+            let mut hasher = DefaultHasher::new();
+            for entry in &self.cascade {
+                match entry {
+                    CascadeFont::Data(font) => (0u8, font.data()).hash(&mut hasher),
+                    CascadeFont::Named(name) => (1u8, name.as_bytes()).hash(&mut hasher),
+                }
+            }
+            hasher.finish().max(1)
+        };
+        self
+    }
+
+    /// The explicit fallback order, empty when CoreText picks
+    pub fn cascade_list(&self) -> &[CascadeFont] {
+        &self.cascade
     }
 
     /// Validate font data has valid TrueType/OpenType signature
@@ -226,16 +311,35 @@ impl CoreTextLinraRenderer {
         )
     }
 
-    /// Create CTFont with optional variation coordinates
+    /// Descriptor for one cascade entry, `None` if CoreText rejects it
+    fn cascade_descriptor(entry: &CascadeFont) -> Option<CFRetained<CTFontDescriptor>> {
+        match entry {
+            CascadeFont::Data(font) => {
+                Self::validate_font_data(font.data()).ok()?;
+                let data = CFData::from_bytes(font.data());
+                unsafe { CTFontManagerCreateFontDescriptorFromData(&data) }
+            },
+            CascadeFont::Named(name) => {
+                let name = CFString::from_str(name);
+                Some(unsafe { CTFontDescriptor::with_name_and_size(&name, 0.0) })
+            },
+        }
+    }
+
+    /// Create CTFont with optional variation coordinates and cascade list
     ///
     /// Takes Arc<[u8]> to ensure the same Arc flows through to CGDataProvider.
     fn create_ct_font(
         data: Arc<[u8]>,
         font_size: f64,
         variations: &[(String, f32)],
+        cascade: &[CascadeFont],
     ) -> Result<CFRetained<CTFont>> {
         let cg_font = Self::create_cg_font(data)?;
 
+        let mut attr_keys: Vec<&CFString> = Vec::new();
+        let mut attr_values: Vec<CFRetained<CFType>> = Vec::new();
+
         if !variations.is_empty() {
             // Build variation dictionary with numeric axis IDs
             let var_pairs: Vec<(CFRetained<CFNumber>, CFRetained<CFNumber>)> = variations
@@ -253,39 +357,45 @@ impl CoreTextLinraRenderer {
                 let values: Vec<&CFNumber> = var_pairs.iter().map(|(_, v)| v.as_ref()).collect();
                 let var_dict = CFDictionary::from_slices(&keys, &values);
 
-                // Create descriptor attributes dictionary with variation attribute
-                let var_key: &CFString = unsafe { kCTFontVariationAttribute };
-
-                // Cast dictionary to CFType for the attributes
-                let var_dict_type = unsafe { CFRetained::cast_unchecked::<CFType>(var_dict) };
-
-                let attr_keys: [&CFString; 1] = [var_key];
-                let attr_values: [&CFType; 1] = [&var_dict_type];
-                let attrs_dict = CFDictionary::from_slices(&attr_keys, &attr_values);
-
-                // Create font descriptor with variation attributes
-                let attrs_untyped =
-                    unsafe { CFRetained::cast_unchecked::<CFDictionary>(attrs_dict) };
-                let desc = unsafe { CTFontDescriptor::with_attributes(&attrs_untyped) };
-
-                // Create CTFont with the descriptor
-                return Ok(unsafe {
-                    CTFont::with_graphics_font(
-                        &cg_font,
-                        font_size as CGFloat,
-                        ptr::null(),
-                        Some(&desc),
-                    )
-                });
+                attr_keys.push(unsafe { kCTFontVariationAttribute });
+                attr_values.push(unsafe { CFRetained::cast_unchecked::<CFType>(var_dict) });
             }
         }
 
-        // No variations - create font without descriptor
-        Ok(
-            unsafe {
+        if !cascade.is_empty() {
+            let descriptors: Vec<CFRetained<CTFontDescriptor>> = cascade
+                .iter()
+                .filter_map(|entry| {
+                    let desc = Self::cascade_descriptor(entry);
+                    if desc.is_none() {
+                        log::warn!("CoreTextLinraRenderer: skipping cascade font {entry:?}");
+                    }
+                    desc
+                })
+                .collect();
+            let refs: Vec<&CTFontDescriptor> = descriptors.iter().map(|d| d.as_ref()).collect();
+            let list = CFArray::from_objects(&refs);
+
+            attr_keys.push(unsafe { kCTFontCascadeListAttribute });
+            attr_values.push(unsafe { CFRetained::cast_unchecked::<CFType>(list) });
+        }
+
+        if attr_keys.is_empty() {
+            // Nothing to apply - create font without descriptor
+            return Ok(unsafe {
                 CTFont::with_graphics_font(&cg_font, font_size as CGFloat, ptr::null(), None)
-            },
-        )
+            });
+        }
+
+        // Create font descriptor with the collected attributes
+        let values: Vec<&CFType> = attr_values.iter().map(|v| v.as_ref()).collect();
+        let attrs_dict = CFDictionary::from_slices(&attr_keys, &values);
+        let attrs_untyped = unsafe { CFRetained::cast_unchecked::<CFDictionary>(attrs_dict) };
+        let desc = unsafe { CTFontDescriptor::with_attributes(&attrs_untyped) };
+
+        Ok(unsafe {
+            CTFont::with_graphics_font(&cg_font, font_size as CGFloat, ptr::null(), Some(&desc))
+        })
     }
 
     /// Get or create a cached CTFont
@@ -293,12 +403,14 @@ impl CoreTextLinraRenderer {
     /// Uses thread-local cache since CTFont has thread affinity.
     /// Returns a reference to the CTFont via closure to avoid lifetime issues.
     fn with_ct_font<R>(
+        &self,
         font: &Arc<dyn FontRef>,
         params: &LinraRenderParams,
         f: impl FnOnce(&CTFont) -> Result<R>,
     ) -> Result<R> {
         let data = font.data();
-        let cache_key = FontCacheKey::new(data, params.size, &params.variations);
+        let cache_key = FontCacheKey::new(data, params.size, &params.variations)
+            .with_cascade(self.cascade_hash);
 
         FONT_CACHE.with(|cache| {
             let mut cache = cache.borrow_mut();
@@ -316,6 +428,7 @@ impl CoreTextLinraRenderer {
                 Arc::clone(&data_arc),
                 params.size as f64,
                 &params.variations,
+                &self.cascade,
             )?;
 
             // Wrap in CachedFont
@@ -470,12 +583,14 @@ impl LinraRenderer for CoreTextLinraRenderer {
                 width: 1,
                 height: 1,
                 format: BitmapFormat::Rgba8,
+                stride: 4,
+                layout: PixelLayout::RGBA,
                 data: vec![0, 0, 0, 0],
             }));
         }
 
         // Use closure-based approach to work with thread-local cache
-        Self::with_ct_font(&font, params, |ct_font| {
+        self.with_ct_font(&font, params, |ct_font| {
             // Create attributed string using the CTFont from cache
             let attr_string = Self::create_attributed_string(text, ct_font, params);
 
@@ -617,12 +732,17 @@ impl LinraRenderer for CoreTextLinraRenderer {
                 CGContext::restore_g_state(Some(&context));
             }
 
-            Ok(RenderOutput::Bitmap(BitmapData {
-                width,
-                height,
-                format: BitmapFormat::Rgba8,
-                data: buffer,
-            }))
+            Ok(RenderOutput::Bitmap(
+                BitmapData {
+                    width,
+                    height,
+                    format: BitmapFormat::Rgba8,
+                    stride: BitmapFormat::Rgba8.row_bytes(width),
+                    layout: PixelLayout::PREMULTIPLIED_RGBA,
+                    data: buffer,
+                }
+                .into_layout(params.pixel_layout),
+            ))
         })
     }
 
@@ -718,6 +838,54 @@ mod tests {
         assert_eq!(key1, key2);
     }
 
+    #[test]
+    fn test_with_cascade_list_when_set_then_cache_key_differs() {
+        let plain = CoreTextLinraRenderer::new();
+        let named = CoreTextLinraRenderer::new().with_cascade_list(["Apple Color Emoji".into()]);
+        let data = CoreTextLinraRenderer::new().with_cascade_list([CascadeFont::Data(Arc::new(
+            MockFont {
+                data: b"fallback".to_vec(),
+            },
+        ))]);
+
+        assert_eq!(plain.cascade_hash, 0);
+        assert_ne!(named.cascade_hash, 0);
+        assert_ne!(named.cascade_hash, data.cascade_hash);
+        assert_eq!(named.cascade_list().len(), 1);
+
+        let key = |r: &CoreTextLinraRenderer| {
+            FontCacheKey::new(b"font", 16.0, &[]).with_cascade(r.cascade_hash)
+        };
+        assert_ne!(key(&plain), key(&named));
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_render_text_when_cascade_font_covers_missing_char_then_drawn() {
+        use std::fs;
+
+        let (Ok(primary), Ok(fallback)) = (
+            fs::read("/System/Library/Fonts/Helvetica.ttc"),
+            fs::read("/System/Library/Fonts/Supplemental/Arial Unicode.ttf"),
+        ) else {
+            return;
+        };
+        let font = Arc::new(MockFont { data: primary });
+        let params = LinraRenderParams {
+            size: 24.0,
+            ..Default::default()
+        };
+
+        // Helvetica has no Hangul; the listed fallback does
+        let renderer = CoreTextLinraRenderer::new()
+            .with_cascade_list([CascadeFont::Data(Arc::new(MockFont { data: fallback }))]);
+        let result = renderer.render_text("\u{D55C}", font, &params);
+        let Ok(RenderOutput::Bitmap(bitmap)) = result else {
+            panic!("expected a bitmap, got {result:?}");
+        };
+        assert!(bitmap.data.chunks_exact(4).any(|px| px[3] > 0));
+    }
+
     #[test]
     #[cfg(target_os = "macos")]
     fn test_with_system_font() {
@@ -0,0 +1,265 @@
+//! Color glyphs through a custom DirectWrite text renderer
+//!
+//! `ID2D1RenderTarget::DrawTextLayout` paints every glyph as a monochrome
+//! outline, so emoji come out black. [`ColorTextRenderer`] receives the
+//! layout's glyph runs instead and asks `IDWriteFactory4::TranslateColorGlyphRun`
+//! to split each one into color runs, drawing each with the matching
+//! Direct2D call:
+//!
+//! - COLR layers (and plain outlines) with `DrawGlyphRun` and a brush set to
+//!   the layer's palette color;
+//! - PNG, JPEG, TIFF, and BGRA bitmaps (CBDT, sbix) with
+//!   `ID2D1DeviceContext4::DrawColorBitmapGlyphRun`;
+//! - SVG documents with `ID2D1DeviceContext4::DrawSvgGlyphRun`.
+//!
+//! Runs without color data (`DWRITE_E_NOCOLOR`) are drawn in the foreground
+//! color, exactly as `DrawTextLayout` would.
+
+use std::ffi::c_void;
+
+use windows::{
+    core::{implement, IUnknown, Interface, Result},
+    Win32::{
+        Foundation::{BOOL, FALSE},
+        Graphics::{
+            Direct2D::{
+                Common::{D2D1_COLOR_F, D2D_POINT_2F},
+                ID2D1DeviceContext4, ID2D1RenderTarget, ID2D1SolidColorBrush, ID2D1SvgGlyphStyle,
+                D2D1_COLOR_BITMAP_GLYPH_SNAP_OPTION_DEFAULT,
+            },
+            DirectWrite::{
+                IDWriteFactory, IDWriteFactory4, IDWriteInlineObject, IDWritePixelSnapping_Impl,
+                IDWriteTextRenderer, IDWriteTextRenderer_Impl, DWRITE_E_NOCOLOR,
+                DWRITE_GLYPH_IMAGE_FORMATS, DWRITE_GLYPH_IMAGE_FORMATS_CFF,
+                DWRITE_GLYPH_IMAGE_FORMATS_COLR, DWRITE_GLYPH_IMAGE_FORMATS_JPEG,
+                DWRITE_GLYPH_IMAGE_FORMATS_PNG, DWRITE_GLYPH_IMAGE_FORMATS_PREMULTIPLIED_B8G8R8A8,
+                DWRITE_GLYPH_IMAGE_FORMATS_SVG, DWRITE_GLYPH_IMAGE_FORMATS_TIFF,
+                DWRITE_GLYPH_IMAGE_FORMATS_TRUETYPE, DWRITE_GLYPH_RUN,
+                DWRITE_GLYPH_RUN_DESCRIPTION, DWRITE_MATRIX, DWRITE_MEASURING_MODE,
+                DWRITE_STRIKETHROUGH, DWRITE_UNDERLINE,
+            },
+        },
+    },
+};
+
+/// CPAL entry index meaning "the text foreground color"
+const FOREGROUND_PALETTE_INDEX: u16 = 0xFFFF;
+
+/// Glyph image formats the renderer knows how to draw
+fn drawable_formats() -> DWRITE_GLYPH_IMAGE_FORMATS {
+    DWRITE_GLYPH_IMAGE_FORMATS_TRUETYPE
+        | DWRITE_GLYPH_IMAGE_FORMATS_CFF
+        | DWRITE_GLYPH_IMAGE_FORMATS_COLR
+        | DWRITE_GLYPH_IMAGE_FORMATS_SVG
+        | DWRITE_GLYPH_IMAGE_FORMATS_PNG
+        | DWRITE_GLYPH_IMAGE_FORMATS_JPEG
+        | DWRITE_GLYPH_IMAGE_FORMATS_TIFF
+        | DWRITE_GLYPH_IMAGE_FORMATS_PREMULTIPLIED_B8G8R8A8
+}
+
+/// `IDWriteTextRenderer` that draws color glyph runs into a Direct2D target
+#[implement(IDWriteTextRenderer)]
+pub(crate) struct ColorTextRenderer {
+    factory: IDWriteFactory4,
+    target: ID2D1RenderTarget,
+    context: ID2D1DeviceContext4,
+    /// Text color, for outlines and foreground-colored COLR layers
+    foreground: ID2D1SolidColorBrush,
+    /// Recolored for each COLR layer
+    layer_brush: ID2D1SolidColorBrush,
+    palette_index: u32,
+}
+
+impl ColorTextRenderer {
+    /// A renderer drawing into `target`, or `None` before Windows 10
+    /// Anniversary Update, where the color interfaces are missing
+    pub(crate) fn new(
+        factory: &IDWriteFactory,
+        target: &ID2D1RenderTarget,
+        foreground: D2D1_COLOR_F,
+        palette_index: u16,
+    ) -> Option<IDWriteTextRenderer> {
+        let factory = factory.cast::<IDWriteFactory4>().ok()?;
+        let context = target.cast::<ID2D1DeviceContext4>().ok()?;
+        let (foreground, layer_brush) = unsafe {
+            (
+                target.CreateSolidColorBrush(&foreground, None).ok()?,
+                target.CreateSolidColorBrush(&foreground, None).ok()?,
+            )
+        };
+        Some(
+            Self {
+                factory,
+                target: target.clone(),
+                context,
+                foreground,
+                layer_brush,
+                palette_index: u32::from(palette_index),
+            }
+            .into(),
+        )
+    }
+}
+
+impl IDWritePixelSnapping_Impl for ColorTextRenderer_Impl {
+    fn IsPixelSnappingDisabled(&self, _context: *const c_void) -> Result<BOOL> {
+        Ok(FALSE)
+    }
+
+    fn GetCurrentTransform(
+        &self,
+        _context: *const c_void,
+        transform: *mut DWRITE_MATRIX,
+    ) -> Result<()> {
+        // The target draws at 96 DPI without a world transform
+        unsafe {
+            *transform = DWRITE_MATRIX {
+                m11: 1.0,
+                m12: 0.0,
+                m21: 0.0,
+                m22: 1.0,
+                dx: 0.0,
+                dy: 0.0,
+            };
+        }
+        Ok(())
+    }
+
+    fn GetPixelsPerDip(&self, _context: *const c_void) -> Result<f32> {
+        Ok(1.0)
+    }
+}
+
+impl IDWriteTextRenderer_Impl for ColorTextRenderer_Impl {
+    fn DrawGlyphRun(
+        &self,
+        _context: *const c_void,
+        baseline_x: f32,
+        baseline_y: f32,
+        measuring_mode: DWRITE_MEASURING_MODE,
+        glyph_run: *const DWRITE_GLYPH_RUN,
+        description: *const DWRITE_GLYPH_RUN_DESCRIPTION,
+        _effect: Option<&IUnknown>,
+    ) -> Result<()> {
+        let origin = D2D_POINT_2F {
+            x: baseline_x,
+            y: baseline_y,
+        };
+        let runs = unsafe {
+            self.factory.TranslateColorGlyphRun(
+                origin,
+                glyph_run,
+                Some(description),
+                drawable_formats(),
+                measuring_mode,
+                None,
+                self.palette_index,
+            )
+        };
+        let runs = match runs {
+            Ok(runs) => runs,
+            Err(e) if e.code() == DWRITE_E_NOCOLOR => {
+                unsafe {
+                    self.target
+                        .DrawGlyphRun(origin, glyph_run, &self.foreground, measuring_mode)
+                };
+                return Ok(());
+            },
+            Err(e) => return Err(e),
+        };
+
+        while unsafe { runs.MoveNext()? }.as_bool() {
+            let run = unsafe { &*runs.GetCurrentRun2()? };
+            let origin = D2D_POINT_2F {
+                x: run.Base.baselineOriginX,
+                y: run.Base.baselineOriginY,
+            };
+            let glyph_run = &run.Base.glyphRun;
+            let format = run.glyphImageFormat;
+
+            if format == DWRITE_GLYPH_IMAGE_FORMATS_PNG
+                || format == DWRITE_GLYPH_IMAGE_FORMATS_JPEG
+                || format == DWRITE_GLYPH_IMAGE_FORMATS_TIFF
+                || format == DWRITE_GLYPH_IMAGE_FORMATS_PREMULTIPLIED_B8G8R8A8
+            {
+                unsafe {
+                    self.context.DrawColorBitmapGlyphRun(
+                        format,
+                        origin,
+                        glyph_run,
+                        run.measuringMode,
+                        D2D1_COLOR_BITMAP_GLYPH_SNAP_OPTION_DEFAULT,
+                    )
+                };
+            } else if format == DWRITE_GLYPH_IMAGE_FORMATS_SVG {
+                unsafe {
+                    self.context.DrawSvgGlyphRun(
+                        origin,
+                        glyph_run,
+                        &self.foreground,
+                        None::<&ID2D1SvgGlyphStyle>,
+                        self.palette_index,
+                        run.measuringMode,
+                    )
+                };
+            } else {
+                // A COLR layer, or the outline of a glyph without color data
+                let brush = if run.Base.paletteIndex == FOREGROUND_PALETTE_INDEX {
+                    &self.foreground
+                } else {
+                    let c = run.Base.runColor;
+                    unsafe {
+                        self.layer_brush.SetColor(&D2D1_COLOR_F {
+                            r: c.r,
+                            g: c.g,
+                            b: c.b,
+                            a: c.a,
+                        })
+                    };
+                    &self.layer_brush
+                };
+                unsafe {
+                    self.target
+                        .DrawGlyphRun(origin, glyph_run, brush, run.measuringMode)
+                };
+            }
+        }
+        Ok(())
+    }
+
+    fn DrawUnderline(
+        &self,
+        _context: *const c_void,
+        _baseline_x: f32,
+        _baseline_y: f32,
+        _underline: *const DWRITE_UNDERLINE,
+        _effect: Option<&IUnknown>,
+    ) -> Result<()> {
+        // Layouts here never set underline ranges
+        Ok(())
+    }
+
+    fn DrawStrikethrough(
+        &self,
+        _context: *const c_void,
+        _baseline_x: f32,
+        _baseline_y: f32,
+        _strikethrough: *const DWRITE_STRIKETHROUGH,
+        _effect: Option<&IUnknown>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn DrawInlineObject(
+        &self,
+        _context: *const c_void,
+        _origin_x: f32,
+        _origin_y: f32,
+        _object: Option<&IDWriteInlineObject>,
+        _is_sideways: BOOL,
+        _is_right_to_left: BOOL,
+        _effect: Option<&IUnknown>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
@@ -18,11 +18,36 @@
 //!
 //! The linra approach eliminates per-glyph overhead and allows DirectWrite
 //! to optimize internally (e.g., batch GPU operations).
+//!
+//! ## Fonts
+//!
+//! Fonts come from [`FontRef::data`], not the system collection: the bytes
+//! go through DirectWrite's in-memory font file loader into a one-font
+//! collection, so any font file renders as itself whether or not it is
+//! installed. This needs `IDWriteFactory5` (Windows 10 Creators Update).
+//!
+//! ## Color glyphs
+//!
+//! Glyph runs go through a custom text renderer that splits them with
+//! `IDWriteFactory4::TranslateColorGlyphRun`, so COLR, SVG, and bitmap
+//! (CBDT, sbix) emoji such as Segoe UI Emoji render in color, using the CPAL
+//! palette from [`LinraRenderParams::color_palette`]. Windows releases
+//! without `ID2D1DeviceContext4` fall back to `DrawTextLayout` with color
+//! fonts enabled.
+//!
+//! ## Startup
+//!
+//! Only the DirectWrite factory is created up front. The Direct2D and WIC
+//! factories (and COM initialization for WIC) wait for the first
+//! [`render_text`](LinraRenderer::render_text) call, so servers that only
+//! call [`DirectWriteLinraRenderer::measure_text`] never pay for them.
 
 #![cfg(windows)]
 
+mod color;
+
 use std::num::NonZeroUsize;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use lru::LruCache;
 use parking_lot::RwLock;
@@ -31,12 +56,14 @@ use typf_core::{
     error::{RenderError, Result, TypfError},
     linra::{LinraRenderParams, LinraRenderer},
     traits::FontRef,
-    types::{BitmapData, BitmapFormat, RenderOutput},
+    types::{BitmapData, BitmapFormat, FontStretch, FontStyle, PixelLayout, RenderOutput},
     Color,
 };
 
+use crate::color::ColorTextRenderer;
+
 use windows::{
-    core::PCWSTR,
+    core::{IUnknown, Interface, PCWSTR},
     Win32::{
         Foundation::{BOOL, FALSE, TRUE},
         Graphics::{
@@ -47,10 +74,14 @@ use windows::{
                 D2D1_RENDER_TARGET_TYPE_DEFAULT, D2D1_RENDER_TARGET_USAGE_NONE,
             },
             DirectWrite::{
-                DWriteCreateFactory, IDWriteFactory, IDWriteFontFace, IDWriteTextFormat,
-                IDWriteTextLayout, DWRITE_FACTORY_TYPE_SHARED, DWRITE_FONT_STRETCH_NORMAL,
-                DWRITE_FONT_STYLE_NORMAL, DWRITE_FONT_WEIGHT_NORMAL, DWRITE_MEASURING_MODE_NATURAL,
-                DWRITE_TEXT_METRICS,
+                DWriteCreateFactory, IDWriteFactory, IDWriteFactory5, IDWriteFontCollection1,
+                IDWriteFontFace, IDWriteInMemoryFontFileLoader, IDWriteTextFormat,
+                IDWriteTextLayout, IDWriteTextLayout4, DWRITE_FACTORY_TYPE_SHARED,
+                DWRITE_FONT_AXIS_TAG, DWRITE_FONT_AXIS_VALUE, DWRITE_FONT_FACE_TYPE,
+                DWRITE_FONT_FILE_TYPE, DWRITE_FONT_SIMULATIONS_NONE, DWRITE_FONT_STRETCH,
+                DWRITE_FONT_STYLE, DWRITE_FONT_STYLE_ITALIC, DWRITE_FONT_STYLE_NORMAL,
+                DWRITE_FONT_STYLE_OBLIQUE, DWRITE_FONT_WEIGHT_NORMAL,
+                DWRITE_MEASURING_MODE_NATURAL, DWRITE_TEXT_METRICS, DWRITE_TEXT_RANGE,
             },
             Dxgi::Common::DXGI_FORMAT_R8G8B8A8_UNORM,
             Imaging::{CLSID_WICImagingFactory, IWICImagingFactory, WICBitmapCacheOnLoad},
@@ -97,12 +128,17 @@ impl FontCacheKey {
     }
 }
 
-/// Cached font entry that keeps font data alive
+/// Cached font entry: the caller's font as DirectWrite sees it
+///
+/// The in-memory loader copies the font bytes, so the entry owns everything
+/// DirectWrite reads.
 struct CachedFont {
     /// The DirectWrite font face
     font_face: IDWriteFontFace,
-    /// Font data kept alive to prevent use-after-free
-    _data: Arc<[u8]>,
+    /// Collection holding only this font, for text formats
+    collection: IDWriteFontCollection1,
+    /// Family name within `collection`, NUL-terminated UTF-16
+    family: Vec<u16>,
 }
 
 /// Single-pass text renderer using Windows DirectWrite
@@ -112,10 +148,12 @@ struct CachedFont {
 pub struct DirectWriteLinraRenderer {
     /// DirectWrite factory
     dwrite_factory: IDWriteFactory,
-    /// Direct2D factory
-    d2d_factory: ID2D1Factory,
-    /// WIC factory for bitmap creation
-    wic_factory: IWICImagingFactory,
+    /// Direct2D factory, created on first render
+    d2d_factory: OnceLock<ID2D1Factory>,
+    /// WIC factory for bitmap creation, created on first render
+    wic_factory: OnceLock<IWICImagingFactory>,
+    /// Loader for font files held in memory, registered on first use
+    memory_loader: OnceLock<IDWriteInMemoryFontFileLoader>,
     /// Font cache to avoid expensive font creation
     font_cache: RwLock<LruCache<FontCacheKey, Arc<CachedFont>>>,
 }
@@ -123,13 +161,13 @@ pub struct DirectWriteLinraRenderer {
 impl DirectWriteLinraRenderer {
     /// Creates a new linra renderer
     ///
+    /// Only the DirectWrite factory is created here; the graphics factories
+    /// are deferred until the first render.
+    ///
     /// # Errors
-    /// Returns an error if COM initialization or factory creation fails.
+    /// Returns an error if DirectWrite factory creation fails.
     pub fn new() -> Result<Self> {
         unsafe {
-            // Initialize COM
-            CoInitializeEx(None, COINIT_MULTITHREADED).ok();
-
             // Create DirectWrite factory
             let dwrite_factory: IDWriteFactory = DWriteCreateFactory(DWRITE_FACTORY_TYPE_SHARED)
                 .map_err(|e| {
@@ -138,33 +176,109 @@ impl DirectWriteLinraRenderer {
                     )))
                 })?;
 
-            // Create Direct2D factory
-            let d2d_factory: ID2D1Factory =
-                D2D1CreateFactory(D2D1_FACTORY_TYPE_SINGLE_THREADED, None).map_err(|e| {
-                    TypfError::RenderingFailed(RenderError::BackendError(format!(
-                        "Failed to create Direct2D factory: {e}"
-                    )))
-                })?;
-
-            // Create WIC factory for bitmap handling
-            let wic_factory: IWICImagingFactory =
-                CoCreateInstance(&CLSID_WICImagingFactory, None, CLSCTX_INPROC_SERVER).map_err(
-                    |e| {
-                        TypfError::RenderingFailed(RenderError::BackendError(format!(
-                            "Failed to create WIC factory: {e}"
-                        )))
-                    },
-                )?;
-
             Ok(Self {
                 dwrite_factory,
-                d2d_factory,
-                wic_factory,
+                d2d_factory: OnceLock::new(),
+                wic_factory: OnceLock::new(),
+                memory_loader: OnceLock::new(),
                 font_cache: RwLock::new(LruCache::new(NonZeroUsize::new(100).unwrap())),
             })
         }
     }
 
+    /// Direct2D factory, created on first use
+    fn d2d_factory(&self) -> Result<&ID2D1Factory> {
+        if let Some(factory) = self.d2d_factory.get() {
+            return Ok(factory);
+        }
+
+        let factory: ID2D1Factory =
+            unsafe { D2D1CreateFactory(D2D1_FACTORY_TYPE_SINGLE_THREADED, None) }.map_err(|e| {
+                TypfError::RenderingFailed(RenderError::BackendError(format!(
+                    "Failed to create Direct2D factory: {e}"
+                )))
+            })?;
+        Ok(self.d2d_factory.get_or_init(|| factory))
+    }
+
+    /// WIC factory, created (with COM initialized on this thread) on first use
+    fn wic_factory(&self) -> Result<&IWICImagingFactory> {
+        if let Some(factory) = self.wic_factory.get() {
+            return Ok(factory);
+        }
+
+        let factory: IWICImagingFactory = unsafe {
+            CoInitializeEx(None, COINIT_MULTITHREADED).ok();
+            CoCreateInstance(&CLSID_WICImagingFactory, None, CLSCTX_INPROC_SERVER)
+        }
+        .map_err(|e| {
+            TypfError::RenderingFailed(RenderError::BackendError(format!(
+                "Failed to create WIC factory: {e}"
+            )))
+        })?;
+        Ok(self.wic_factory.get_or_init(|| factory))
+    }
+
+    /// `IDWriteFactory5`, for in-memory fonts and font sets (Windows 10 1703+)
+    fn dwrite_factory5(&self) -> Result<IDWriteFactory5> {
+        self.dwrite_factory.cast().map_err(|e| {
+            TypfError::RenderingFailed(RenderError::BackendError(format!(
+                "DirectWrite is too old to load font data (needs IDWriteFactory5): {e}"
+            )))
+        })
+    }
+
+    /// In-memory font file loader, created and registered on first use
+    fn memory_loader(&self) -> Result<&IDWriteInMemoryFontFileLoader> {
+        if let Some(loader) = self.memory_loader.get() {
+            return Ok(loader);
+        }
+
+        let loader = unsafe {
+            let loader = self.dwrite_factory5()?.CreateInMemoryFontFileLoader();
+            loader.and_then(|loader| {
+                self.dwrite_factory.RegisterFontFileLoader(&loader)?;
+                Ok(loader)
+            })
+        }
+        .map_err(|e| {
+            TypfError::RenderingFailed(RenderError::BackendError(format!(
+                "Failed to register in-memory font loader: {e}"
+            )))
+        })?;
+
+        // A racing thread may have registered its own; that one stays
+        // registered until drop, this one is unregistered now
+        let winner = self.memory_loader.get_or_init(|| loader.clone());
+        if winner != &loader {
+            unsafe {
+                let _ = self.dwrite_factory.UnregisterFontFileLoader(&loader);
+            }
+        }
+        Ok(winner)
+    }
+
+    /// Lay out `text` and return its `(width, height)` in pixels without rendering
+    ///
+    /// Uses DirectWrite alone, so it never creates the Direct2D or WIC factories.
+    pub fn measure_text(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        params: &LinraRenderParams,
+    ) -> Result<(f32, f32)> {
+        if text.is_empty() {
+            return Ok((0.0, 0.0));
+        }
+
+        let cached_font = self.get_font_face(&font, params)?;
+        let text_format = self.create_text_format(&cached_font, params)?;
+        let layout = self.create_text_layout(text, &text_format, 10000.0, 10000.0)?;
+        Self::apply_variations(&layout, text, &params.variations)?;
+        let metrics = self.get_text_metrics(&layout)?;
+        Ok((metrics.width, metrics.height))
+    }
+
     /// Validate font data has valid TrueType/OpenType signature
     fn validate_font_data(data: &[u8]) -> Result<()> {
         if data.len() < 12 {
@@ -196,47 +310,92 @@ impl DirectWriteLinraRenderer {
         Ok(())
     }
 
-    /// Create IDWriteFontFace from font data
-    fn create_font_face(
-        &self,
-        data: Arc<[u8]>,
-        _variations: &[(String, f32)],
-    ) -> Result<IDWriteFontFace> {
-        Self::validate_font_data(&data)?;
+    /// Load font data through the in-memory loader
+    ///
+    /// Builds the face (for glyph metrics) and a one-font collection (for
+    /// text formats), so fonts that are not installed render as themselves
+    /// instead of falling back to a system family.
+    fn create_font_face(&self, data: &[u8], _variations: &[(String, f32)]) -> Result<CachedFont> {
+        Self::validate_font_data(data)?;
+
+        let backend_error = |what: &str, e: windows::core::Error| {
+            TypfError::RenderingFailed(RenderError::BackendError(format!("{what}: {e}")))
+        };
 
         unsafe {
-            // Create font file from memory
+            // The loader copies the bytes when no owner object is given
             let font_file = self
-                .dwrite_factory
-                .CreateFontFileReference(
-                    PCWSTR::null(), // We use in-memory font
-                    None,
+                .memory_loader()?
+                .CreateInMemoryFontFileReference(
+                    &self.dwrite_factory,
+                    data.as_ptr().cast(),
+                    data.len() as u32,
+                    None::<&IUnknown>,
                 )
-                .map_err(|e| {
-                    TypfError::RenderingFailed(RenderError::BackendError(format!(
-                        "Failed to create font file reference: {e}"
-                    )))
-                })?;
+                .map_err(|e| backend_error("Failed to create font file reference", e))?;
+
+            let mut supported = FALSE;
+            let mut file_type = DWRITE_FONT_FILE_TYPE::default();
+            let mut face_type = DWRITE_FONT_FACE_TYPE::default();
+            let mut face_count = 0u32;
+            font_file
+                .Analyze(
+                    &mut supported,
+                    &mut file_type,
+                    Some(&mut face_type),
+                    &mut face_count,
+                )
+                .map_err(|e| backend_error("Failed to analyze font file", e))?;
+            if !supported.as_bool() || face_count == 0 {
+                return Err(TypfError::RenderingFailed(RenderError::BackendError(
+                    "DirectWrite does not support this font file".to_string(),
+                )));
+            }
 
-            // Create font face
             let font_face = self
                 .dwrite_factory
                 .CreateFontFace(
-                    windows::Win32::Graphics::DirectWrite::DWRITE_FONT_FACE_TYPE_TRUETYPE,
-                    &[font_file],
+                    face_type,
+                    &[Some(font_file.clone())],
                     0,
-                    windows::Win32::Graphics::DirectWrite::DWRITE_FONT_SIMULATIONS_NONE,
+                    DWRITE_FONT_SIMULATIONS_NONE,
                 )
-                .map_err(|e| {
-                    TypfError::RenderingFailed(RenderError::BackendError(format!(
-                        "Failed to create font face: {e}"
-                    )))
-                })?;
-
-            // TODO: Apply variable font variations using IDWriteFontFace3::GetFontAxisValues
-            // and IDWriteFontFace5::CreateFontFaceReference
-
-            Ok(font_face)
+                .map_err(|e| backend_error("Failed to create font face", e))?;
+
+            // A collection with just this font, so text layouts can name it
+            let factory5 = self.dwrite_factory5()?;
+            let builder = factory5
+                .CreateFontSetBuilder()
+                .map_err(|e| backend_error("Failed to create font set builder", e))?;
+            builder
+                .AddFontFile(&font_file)
+                .map_err(|e| backend_error("Failed to add font file", e))?;
+            let font_set = builder
+                .CreateFontSet()
+                .map_err(|e| backend_error("Failed to create font set", e))?;
+            let collection = factory5
+                .CreateFontCollectionFromFontSet(&font_set)
+                .map_err(|e| backend_error("Failed to create font collection", e))?;
+
+            let names = collection
+                .GetFontFamily(0)
+                .and_then(|family| family.GetFamilyNames())
+                .map_err(|e| backend_error("Failed to read font family name", e))?;
+            let len = names
+                .GetStringLength(0)
+                .map_err(|e| backend_error("Failed to read font family name", e))?;
+            let mut family = vec![0u16; len as usize + 1];
+            names
+                .GetString(0, &mut family)
+                .map_err(|e| backend_error("Failed to read font family name", e))?;
+
+            // Variations are set per layout, see `apply_variations`
+
+            Ok(CachedFont {
+                font_face,
+                collection,
+                family,
+            })
         }
     }
 
@@ -257,13 +416,7 @@ impl DirectWriteLinraRenderer {
             }
         }
 
-        let data_arc: Arc<[u8]> = Arc::from(data);
-        let font_face = self.create_font_face(Arc::clone(&data_arc), &params.variations)?;
-
-        let cached_font = Arc::new(CachedFont {
-            font_face,
-            _data: data_arc,
-        });
+        let cached_font = Arc::new(self.create_font_face(data, &params.variations)?);
 
         {
             let mut cache = self.font_cache.write();
@@ -274,18 +427,21 @@ impl DirectWriteLinraRenderer {
     }
 
     /// Create text format for the given parameters
-    fn create_text_format(&self, params: &LinraRenderParams) -> Result<IDWriteTextFormat> {
+    fn create_text_format(
+        &self,
+        font: &CachedFont,
+        params: &LinraRenderParams,
+    ) -> Result<IDWriteTextFormat> {
         unsafe {
-            // Use default system font family - actual font face is applied via SetFontCollection
-            let font_family: Vec<u16> = "Segoe UI\0".encode_utf16().collect();
-
+            // The caller's font, by name, from its own collection; characters it
+            // lacks still fall back to system fonts
             self.dwrite_factory
                 .CreateTextFormat(
-                    PCWSTR(font_family.as_ptr()),
-                    None,
+                    PCWSTR(font.family.as_ptr()),
+                    &font.collection,
                     DWRITE_FONT_WEIGHT_NORMAL,
-                    DWRITE_FONT_STYLE_NORMAL,
-                    DWRITE_FONT_STRETCH_NORMAL,
+                    Self::dwrite_style(params.style),
+                    Self::dwrite_stretch(params.stretch),
                     params.size,
                     PCWSTR::null(),
                 )
@@ -297,6 +453,24 @@ impl DirectWriteLinraRenderer {
         }
     }
 
+    /// DirectWrite posture for `style`
+    ///
+    /// Font matching slants the upright face (`DWRITE_FONT_SIMULATIONS_OBLIQUE`)
+    /// when the family has no italic or oblique face, so a lone upright font
+    /// still renders slanted.
+    fn dwrite_style(style: FontStyle) -> DWRITE_FONT_STYLE {
+        match style {
+            FontStyle::Normal => DWRITE_FONT_STYLE_NORMAL,
+            FontStyle::Italic => DWRITE_FONT_STYLE_ITALIC,
+            FontStyle::Oblique => DWRITE_FONT_STYLE_OBLIQUE,
+        }
+    }
+
+    /// DirectWrite stretch for `stretch`; both number the OS/2 width classes 1-9
+    fn dwrite_stretch(stretch: FontStretch) -> DWRITE_FONT_STRETCH {
+        DWRITE_FONT_STRETCH(i32::from(stretch.width_class()))
+    }
+
     /// Create text layout for the given text and parameters
     fn create_text_layout(
         &self,
@@ -318,6 +492,44 @@ impl DirectWriteLinraRenderer {
         }
     }
 
+    /// Instance the layout's font at `variations`, so advances and line
+    /// metrics (including `MVAR` deltas) follow the selected axes
+    fn apply_variations(
+        layout: &IDWriteTextLayout,
+        text: &str,
+        variations: &[(String, f32)],
+    ) -> Result<()> {
+        if variations.is_empty() {
+            return Ok(());
+        }
+        let values: Vec<DWRITE_FONT_AXIS_VALUE> = variations
+            .iter()
+            .filter_map(|(tag, value)| {
+                let tag: [u8; 4] = tag.as_bytes().try_into().ok()?;
+                Some(DWRITE_FONT_AXIS_VALUE {
+                    // DWRITE_MAKE_FONT_AXIS_TAG puts the first character lowest
+                    axisTag: DWRITE_FONT_AXIS_TAG(u32::from_le_bytes(tag)),
+                    value: *value,
+                })
+            })
+            .collect();
+        let range = DWRITE_TEXT_RANGE {
+            startPosition: 0,
+            length: text.encode_utf16().count() as u32,
+        };
+
+        unsafe {
+            layout
+                .cast::<IDWriteTextLayout4>()
+                .and_then(|layout| layout.SetFontAxisValues(&values, range))
+                .map_err(|e| {
+                    TypfError::RenderingFailed(RenderError::BackendError(format!(
+                        "Failed to apply font variations: {e}"
+                    )))
+                })
+        }
+    }
+
     /// Get text metrics from layout
     fn get_text_metrics(&self, layout: &IDWriteTextLayout) -> Result<DWRITE_TEXT_METRICS> {
         let mut metrics = DWRITE_TEXT_METRICS::default();
@@ -342,6 +554,17 @@ impl DirectWriteLinraRenderer {
     }
 }
 
+impl Drop for DirectWriteLinraRenderer {
+    fn drop(&mut self) {
+        // The shared factory outlives us; take our loader back out of it
+        if let Some(loader) = self.memory_loader.get() {
+            unsafe {
+                let _ = self.dwrite_factory.UnregisterFontFileLoader(loader);
+            }
+        }
+    }
+}
+
 impl Default for DirectWriteLinraRenderer {
     fn default() -> Self {
         Self::new().expect("Failed to create DirectWriteLinraRenderer")
@@ -371,18 +594,21 @@ impl LinraRenderer for DirectWriteLinraRenderer {
                 width: 1,
                 height: 1,
                 format: BitmapFormat::Rgba8,
+                stride: 4,
+                layout: PixelLayout::RGBA,
                 data: vec![0, 0, 0, 0],
             }));
         }
 
-        // Get or create cached font (currently unused due to DirectWrite complexity)
-        let _cached_font = self.get_font_face(&font, params)?;
+        // Get or create cached font
+        let cached_font = self.get_font_face(&font, params)?;
 
-        // Create text format
-        let text_format = self.create_text_format(params)?;
+        // Create text format naming the cached font
+        let text_format = self.create_text_format(&cached_font, params)?;
 
         // Create text layout with large max dimensions for initial measurement
         let layout = self.create_text_layout(text, &text_format, 10000.0, 10000.0)?;
+        Self::apply_variations(&layout, text, &params.variations)?;
 
         // Get metrics to determine canvas size
         let metrics = self.get_text_metrics(&layout)?;
@@ -404,7 +630,7 @@ impl LinraRenderer for DirectWriteLinraRenderer {
         unsafe {
             // Create WIC bitmap
             let wic_bitmap = self
-                .wic_factory
+                .wic_factory()?
                 .CreateBitmap(
                     width,
                     height,
@@ -431,7 +657,7 @@ impl LinraRenderer for DirectWriteLinraRenderer {
             };
 
             let render_target: ID2D1RenderTarget = self
-                .d2d_factory
+                .d2d_factory()?
                 .CreateWicBitmapRenderTarget(&wic_bitmap, &render_target_props)
                 .map_err(|e| {
                     TypfError::RenderingFailed(RenderError::BackendError(format!(
@@ -464,16 +690,34 @@ impl LinraRenderer for DirectWriteLinraRenderer {
                     )))
                 })?;
 
-            // THE KEY OPERATION: DrawTextLayout shapes AND renders in one call
-            render_target.DrawTextLayout(
-                windows::Win32::Graphics::Direct2D::Common::D2D_POINT_2F {
-                    x: padding,
-                    y: padding,
-                },
-                &layout,
-                &brush,
-                windows::Win32::Graphics::Direct2D::D2D1_DRAW_TEXT_OPTIONS_NONE,
-            );
+            // THE KEY OPERATION: the layout shapes AND renders in one call,
+            // handing glyph runs to a renderer that paints color glyphs
+            let foreground = Self::color_to_d2d(&params.foreground);
+            if let Some(renderer) = ColorTextRenderer::new(
+                &self.dwrite_factory,
+                &render_target,
+                foreground,
+                params.color_palette.resolve(font.data(), font.face_index()),
+            ) {
+                layout
+                    .Draw(None, &renderer, padding, padding)
+                    .map_err(|e| {
+                        TypfError::RenderingFailed(RenderError::BackendError(format!(
+                            "Failed to draw text layout: {e}"
+                        )))
+                    })?;
+            } else {
+                // Older Windows: Direct2D still draws COLR v0 glyphs itself
+                render_target.DrawTextLayout(
+                    windows::Win32::Graphics::Direct2D::Common::D2D_POINT_2F {
+                        x: padding,
+                        y: padding,
+                    },
+                    &layout,
+                    &brush,
+                    windows::Win32::Graphics::Direct2D::D2D1_DRAW_TEXT_OPTIONS_ENABLE_COLOR_FONT,
+                );
+            }
 
             // End drawing
             render_target.EndDraw(None, None).map_err(|e| {
@@ -521,12 +765,18 @@ impl LinraRenderer for DirectWriteLinraRenderer {
             }
         }
 
-        Ok(RenderOutput::Bitmap(BitmapData {
-            width,
-            height,
-            format: BitmapFormat::Rgba8,
-            data: buffer,
-        }))
+        // The WIC bitmap holds Direct2D's premultiplied BGRA
+        Ok(RenderOutput::Bitmap(
+            BitmapData {
+                width,
+                height,
+                format: BitmapFormat::Rgba8,
+                stride: BitmapFormat::Rgba8.row_bytes(width),
+                layout: PixelLayout::PREMULTIPLIED_BGRA,
+                data: buffer,
+            }
+            .into_layout(params.pixel_layout),
+        ))
     }
 
     fn clear_cache(&self) {
@@ -568,6 +818,30 @@ mod tests {
         }
     }
 
+    /// A font from `test-fonts/`, or `None` when they are not checked out
+    fn test_font(name: &str) -> Option<Arc<MockFont>> {
+        let mut path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.pop(); // typf-os-win
+        path.pop(); // backends
+        path.push("test-fonts");
+        path.push(name);
+        std::fs::read(path)
+            .ok()
+            .map(|data| Arc::new(MockFont { data }))
+    }
+
+    fn has_ink(bitmap: &BitmapData) -> bool {
+        bitmap.data.chunks_exact(4).any(|px| px[3] > 0)
+    }
+
+    fn has_color(bitmap: &BitmapData) -> bool {
+        // Black text antialiases to grays; only color glyphs differ per channel
+        bitmap
+            .data
+            .chunks_exact(4)
+            .any(|px| px[3] > 0 && (px[0] != px[1] || px[1] != px[2]))
+    }
+
     #[test]
     fn test_renderer_creation() {
         let renderer = DirectWriteLinraRenderer::new();
@@ -602,6 +876,141 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_new_when_created_then_graphics_factories_deferred() {
+        if let Ok(renderer) = DirectWriteLinraRenderer::new() {
+            assert!(renderer.d2d_factory.get().is_none());
+            assert!(renderer.wic_factory.get().is_none());
+
+            let font = Arc::new(MockFont { data: vec![] });
+            let size = renderer.measure_text("", font, &LinraRenderParams::default());
+            assert_eq!(size.ok(), Some((0.0, 0.0)));
+            assert!(renderer.d2d_factory.get().is_none());
+        }
+    }
+
+    #[test]
+    fn test_render_text_when_font_not_installed_then_loaded_from_data() {
+        let (Ok(renderer), Some(font)) = (
+            DirectWriteLinraRenderer::new(),
+            test_font("NotoSans-Regular.ttf"),
+        ) else {
+            return;
+        };
+        let params = LinraRenderParams {
+            size: 32.0,
+            ..Default::default()
+        };
+
+        let result = renderer.render_text("Hello", font.clone(), &params);
+        let Ok(RenderOutput::Bitmap(bitmap)) = result else {
+            panic!("expected a bitmap, got {result:?}");
+        };
+        assert!(has_ink(&bitmap));
+
+        let cached = renderer.get_font_face(&(font as Arc<dyn FontRef>), &params);
+        let family = cached.map(|c| String::from_utf16_lossy(&c.family));
+        assert_eq!(family.ok().as_deref(), Some("Noto Sans\0"));
+    }
+
+    #[test]
+    fn test_render_text_when_italic_without_italic_face_then_slanted() {
+        let (Ok(renderer), Some(font)) = (
+            DirectWriteLinraRenderer::new(),
+            test_font("NotoSans-Regular.ttf"),
+        ) else {
+            return;
+        };
+        let render = |style| {
+            let params = LinraRenderParams {
+                size: 32.0,
+                style,
+                ..Default::default()
+            };
+            match renderer.render_text("Il", font.clone(), &params) {
+                Ok(RenderOutput::Bitmap(bitmap)) => Some(bitmap.data),
+                _ => None,
+            }
+        };
+
+        let upright = render(FontStyle::Normal);
+        let italic = render(FontStyle::Italic);
+        assert!(upright.is_some());
+        assert_ne!(upright, italic, "italic request left glyphs upright");
+    }
+
+    #[test]
+    fn test_dwrite_stretch_when_mapped_then_matches_width_class() {
+        use windows::Win32::Graphics::DirectWrite::{
+            DWRITE_FONT_STRETCH_CONDENSED, DWRITE_FONT_STRETCH_NORMAL,
+            DWRITE_FONT_STRETCH_ULTRA_EXPANDED,
+        };
+        assert_eq!(
+            DirectWriteLinraRenderer::dwrite_stretch(FontStretch::Normal),
+            DWRITE_FONT_STRETCH_NORMAL
+        );
+        assert_eq!(
+            DirectWriteLinraRenderer::dwrite_stretch(FontStretch::Condensed),
+            DWRITE_FONT_STRETCH_CONDENSED
+        );
+        assert_eq!(
+            DirectWriteLinraRenderer::dwrite_stretch(FontStretch::UltraExpanded),
+            DWRITE_FONT_STRETCH_ULTRA_EXPANDED
+        );
+    }
+
+    #[test]
+    fn test_render_text_when_data_invalid_then_error() {
+        if let Ok(renderer) = DirectWriteLinraRenderer::new() {
+            let font = Arc::new(MockFont {
+                data: b"not a font, just twelve+ bytes".to_vec(),
+            });
+            let result = renderer.render_text("Hello", font, &LinraRenderParams::default());
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_render_text_when_emoji_then_drawn_in_color() {
+        let (Ok(renderer), Some(font)) = (
+            DirectWriteLinraRenderer::new(),
+            test_font("NotoSans-Regular.ttf"),
+        ) else {
+            return;
+        };
+        let params = LinraRenderParams {
+            size: 48.0,
+            ..Default::default()
+        };
+
+        // NotoSans has no emoji, so font fallback picks Segoe UI Emoji
+        let result = renderer.render_text("\u{1F600}", font, &params);
+        let Ok(RenderOutput::Bitmap(bitmap)) = result else {
+            panic!("expected a bitmap, got {result:?}");
+        };
+        assert!(has_color(&bitmap), "emoji rendered without color");
+    }
+
+    #[test]
+    fn test_render_text_when_cbdt_font_then_drawn_in_color() {
+        let (Ok(renderer), Some(font)) = (
+            DirectWriteLinraRenderer::new(),
+            test_font("Nabla-Regular-CBDT.ttf"),
+        ) else {
+            return;
+        };
+        let params = LinraRenderParams {
+            size: 64.0,
+            ..Default::default()
+        };
+
+        let result = renderer.render_text("A", font, &params);
+        let Ok(RenderOutput::Bitmap(bitmap)) = result else {
+            panic!("expected a bitmap, got {result:?}");
+        };
+        assert!(has_color(&bitmap), "CBDT glyph rendered without color");
+    }
+
     #[test]
     fn test_font_cache_key() {
         let key1 = FontCacheKey::new(b"font1", 16.0, &[]);
@@ -34,7 +34,7 @@ pub use typf_core::linra::{LinraRenderParams, LinraRenderer};
 
 // Platform-specific implementations
 #[cfg(target_os = "macos")]
-pub use typf_os_mac::CoreTextLinraRenderer;
+pub use typf_os_mac::{CascadeFont, CoreTextLinraRenderer};
 
 // Type alias for the current platform's renderer
 #[cfg(target_os = "macos")]
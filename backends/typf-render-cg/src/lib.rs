@@ -12,18 +12,21 @@ use std::sync::Arc;
 use typf_core::{
     error::{RenderError, Result, TypfError},
     traits::{FontRef, Renderer},
-    types::{BitmapData, BitmapFormat, RenderOutput, ShapingResult},
+    types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput, ShapingResult},
     Color, RenderParams,
 };
 
 use objc2_core_foundation::{
-    CFDictionary, CFNumber, CFRetained, CFString, CGAffineTransform, CGPoint, CGRect, CGSize,
+    CFData, CFDictionary, CFNumber, CFRetained, CFString, CGAffineTransform, CGPoint, CGRect,
+    CGSize,
 };
 use objc2_core_graphics::{
     CGBitmapContextCreate, CGColorSpace, CGContext, CGDataProvider, CGFont, CGGlyph,
     CGTextDrawingMode,
 };
-use objc2_core_text::{kCTFontVariationAttribute, CTFont, CTFontDescriptor};
+use objc2_core_text::{
+    kCTFontVariationAttribute, CTFont, CTFontDescriptor, CTFontManagerCreateFontDescriptorFromData,
+};
 use std::ffi::c_void;
 use std::ptr;
 
@@ -182,95 +185,136 @@ impl CoreGraphicsRenderer {
         )
     }
 
+    /// Builds the `kCTFontVariationAttribute` dictionary for a font descriptor
+    ///
+    /// Returns None when no variation tag maps to a CoreText axis identifier.
+    fn variation_attributes(variations: &[(String, f32)]) -> Option<CFRetained<CFDictionary>> {
+        if variations.is_empty() {
+            return None;
+        }
+        log::debug!(
+            "CoreGraphicsRenderer: Applying {} variation coordinates",
+            variations.len()
+        );
+
+        // CoreText's variation dictionary requires:
+        // - Keys: CFNumber representing axis identifier (32-bit integer from 4-char tag)
+        // - Values: CFNumber representing axis value
+        let var_pairs: Vec<(CFRetained<CFNumber>, CFRetained<CFNumber>)> = variations
+            .iter()
+            .filter_map(|(tag, value)| {
+                Self::tag_to_axis_id(tag).map(|axis_id| {
+                    log::debug!(
+                        "CoreGraphicsRenderer: axis '{}' -> id {} = {}",
+                        tag,
+                        axis_id,
+                        value
+                    );
+                    let key = CFNumber::new_i64(axis_id);
+                    let val = CFNumber::new_f64(*value as f64);
+                    (key, val)
+                })
+            })
+            .collect();
+        if var_pairs.is_empty() {
+            return None;
+        }
+
+        // Create variation dictionary
+        let keys: Vec<&CFNumber> = var_pairs.iter().map(|(k, _)| k.as_ref()).collect();
+        let values: Vec<&CFNumber> = var_pairs.iter().map(|(_, v)| v.as_ref()).collect();
+
+        let var_dict: CFRetained<CFDictionary<CFNumber, CFNumber>> =
+            CFDictionary::from_slices(&keys, &values);
+
+        // kCTFontVariationAttribute is a *const CFString, we need to retain it
+        let var_key_ptr: *const CFString = unsafe { kCTFontVariationAttribute };
+        let var_key = unsafe { CFRetained::retain(NonNull::new(var_key_ptr as *mut CFString)?) };
+        let keys_for_attrs: Vec<&CFString> = vec![&var_key];
+        // Cast var_dict to untyped CFDictionary for use as attribute value
+        let var_dict_untyped: CFRetained<CFDictionary> =
+            unsafe { CFRetained::cast_unchecked(var_dict) };
+        let values_for_attrs: Vec<&CFDictionary> = vec![&var_dict_untyped];
+
+        let attrs: CFRetained<CFDictionary<CFString, CFDictionary>> =
+            CFDictionary::from_slices(&keys_for_attrs, &values_for_attrs);
+
+        // Cast to untyped dictionary for CTFontDescriptor
+        Some(unsafe { CFRetained::cast_unchecked(attrs) })
+    }
+
     /// Creates a CTFont from font data with optional variation coordinates applied.
     ///
     /// For variable fonts, this applies the specified axis values to produce
     /// the correct glyph outlines. Without this, variable fonts would always
     /// render at their default axis values regardless of shaping parameters.
+    ///
+    /// The font comes from `CTFontManagerCreateFontDescriptorFromData` when
+    /// possible: a CTFont built on a bare CGFont draws sbix, COLR, and SVG
+    /// glyphs blank or as black outlines, so color emoji need the descriptor
+    /// path. The CGFont path remains for data CoreText will not describe.
     fn create_ct_font_with_variations(
         data: &[u8],
         font_size: f64,
         variations: &[(String, f32)],
     ) -> Result<CFRetained<CTFont>> {
-        // Create CGFont from raw data - this keeps our loaded font data
-        let cg_font = Self::create_cg_font(data)?;
-
-        // Apply variation coordinates if specified
-        if !variations.is_empty() {
-            log::debug!(
-                "CoreGraphicsRenderer: Applying {} variation coordinates",
-                variations.len()
-            );
-
-            // CoreText's variation dictionary requires:
-            // - Keys: CFNumber representing axis identifier (32-bit integer from 4-char tag)
-            // - Values: CFNumber representing axis value
-            let var_pairs: Vec<(CFRetained<CFNumber>, CFRetained<CFNumber>)> = variations
-                .iter()
-                .filter_map(|(tag, value)| {
-                    Self::tag_to_axis_id(tag).map(|axis_id| {
-                        log::debug!(
-                            "CoreGraphicsRenderer: axis '{}' -> id {} = {}",
-                            tag,
-                            axis_id,
-                            value
-                        );
-                        let key = CFNumber::new_i64(axis_id);
-                        let val = CFNumber::new_f64(*value as f64);
-                        (key, val)
-                    })
-                })
-                .collect();
-
-            if !var_pairs.is_empty() {
-                // Create variation dictionary
-                let keys: Vec<&CFNumber> = var_pairs.iter().map(|(k, _)| k.as_ref()).collect();
-                let values: Vec<&CFNumber> = var_pairs.iter().map(|(_, v)| v.as_ref()).collect();
-
-                let var_dict: CFRetained<CFDictionary<CFNumber, CFNumber>> =
-                    CFDictionary::from_slices(&keys, &values);
-
-                // Create font descriptor with variations attribute
-                // kCTFontVariationAttribute is a *const CFString, we need to retain it
-                let var_key_ptr: *const CFString = unsafe { kCTFontVariationAttribute };
-                let var_key = unsafe {
-                    CFRetained::retain(NonNull::new(var_key_ptr as *mut CFString).unwrap())
-                };
-                let keys_for_attrs: Vec<&CFString> = vec![&var_key];
-                // Cast var_dict to untyped CFDictionary for use as attribute value
-                let var_dict_untyped: CFRetained<CFDictionary> =
-                    unsafe { CFRetained::cast_unchecked(var_dict) };
-                let values_for_attrs: Vec<&CFDictionary> = vec![&var_dict_untyped];
-
-                let attrs: CFRetained<CFDictionary<CFString, CFDictionary>> =
-                    CFDictionary::from_slices(&keys_for_attrs, &values_for_attrs);
-
-                // Cast to untyped dictionary for CTFontDescriptor
-                let attrs_untyped: CFRetained<CFDictionary> =
-                    unsafe { CFRetained::cast_unchecked(attrs) };
-
-                let desc = unsafe { CTFontDescriptor::with_attributes(&attrs_untyped) };
-
-                let ct_font = unsafe {
-                    CTFont::with_graphics_font(
-                        &cg_font,
-                        font_size,
-                        ptr::null::<CGAffineTransform>(),
-                        Some(&desc),
-                    )
-                };
-
-                return Ok(ct_font);
-            }
+        let attrs = Self::variation_attributes(variations);
+
+        let cf_data = CFData::from_bytes(data);
+        if let Some(desc) = unsafe { CTFontManagerCreateFontDescriptorFromData(&cf_data) } {
+            let desc = match &attrs {
+                Some(attrs) => unsafe { desc.copy_with_attributes(attrs) },
+                None => desc,
+            };
+            let ct_font = unsafe {
+                CTFont::with_font_descriptor(&desc, font_size, ptr::null::<CGAffineTransform>())
+            };
+            return Ok(ct_font);
         }
+        log::debug!("CoreGraphicsRenderer: No font descriptor for data, using CGFont");
 
-        // No variations - create CTFont directly from CGFont
+        // Create CGFont from raw data - this keeps our loaded font data
+        let cg_font = Self::create_cg_font(data)?;
+        let desc = attrs.map(|attrs| unsafe { CTFontDescriptor::with_attributes(&attrs) });
         let ct_font = unsafe {
-            CTFont::with_graphics_font(&cg_font, font_size, ptr::null::<CGAffineTransform>(), None)
+            CTFont::with_graphics_font(
+                &cg_font,
+                font_size,
+                ptr::null::<CGAffineTransform>(),
+                desc.as_deref(),
+            )
         };
 
         Ok(ct_font)
     }
+
+    /// Fills glyph outlines in the current fill color, skipping color tables
+    ///
+    /// CTFontDrawGlyphs always draws a glyph's color representation when it
+    /// has one; outlines are how CoreText honors a monochrome request. Glyphs
+    /// that exist only as bitmaps (Apple Color Emoji) have no outline and are
+    /// left blank.
+    fn fill_glyph_outlines(
+        context: &CGContext,
+        ct_font: &CTFont,
+        glyph_ids: &[CGGlyph],
+        positions: &[CGPoint],
+    ) {
+        for (&glyph, pos) in glyph_ids.iter().zip(positions) {
+            let transform = CGAffineTransform {
+                a: 1.0,
+                b: 0.0,
+                c: 0.0,
+                d: 1.0,
+                tx: pos.x,
+                ty: pos.y,
+            };
+            if let Some(path) = unsafe { ct_font.path_for_glyph(glyph, &transform) } {
+                CGContext::add_path(Some(context), Some(&path));
+            }
+        }
+        CGContext::fill_path(Some(context));
+    }
 }
 
 impl Default for CoreGraphicsRenderer {
@@ -295,6 +339,15 @@ impl Renderer for CoreGraphicsRenderer {
             shaped.glyphs.len()
         );
 
+        // The canvas here is sized from heuristic line metrics, which cannot
+        // follow a rotated or sheared run
+        if params.active_transform()?.is_some() {
+            return Err(TypfError::RenderingFailed(RenderError::BackendError(
+                "Text transforms are not supported by CoreGraphicsRenderer; use skia, zeno, or opixa"
+                    .to_string(),
+            )));
+        }
+
         // Handle empty glyph case
         if shaped.glyphs.is_empty() {
             log::debug!("CoreGraphicsRenderer: No glyphs to render");
@@ -302,6 +355,8 @@ impl Renderer for CoreGraphicsRenderer {
                 width: 1,
                 height: 1,
                 format: BitmapFormat::Rgba8,
+                stride: 4,
+                layout: PixelLayout::RGBA,
                 data: vec![0, 0, 0, 255], // Transparent pixel
             }));
         }
@@ -445,30 +500,46 @@ impl Renderer for CoreGraphicsRenderer {
             );
         }
 
-        // Render glyphs using CTFont
+        // Render glyphs using CTFont, raised by the baseline shift (y is up here)
         CGContext::save_g_state(Some(&context));
-        CGContext::translate_ctm(Some(&context), params.padding as f64, baseline_y);
+        CGContext::translate_ctm(
+            Some(&context),
+            params.padding as f64,
+            baseline_y + params.baseline_shift as f64,
+        );
         CGContext::set_text_drawing_mode(Some(&context), CGTextDrawingMode::Fill);
 
-        // Draw glyphs using CTFont::draw_glyphs
-        unsafe {
-            ct_font.draw_glyphs(
-                NonNull::new_unchecked(glyph_ids.as_ptr() as *mut CGGlyph),
-                NonNull::new_unchecked(glyph_positions.as_ptr() as *mut CGPoint),
-                glyph_ids.len(),
-                &context,
-            );
+        if params.glyph_sources.allows_color() {
+            // CTFontDrawGlyphs paints sbix, COLR, and SVG glyphs in their own
+            // colors and everything else in the fill color
+            unsafe {
+                ct_font.draw_glyphs(
+                    NonNull::new_unchecked(glyph_ids.as_ptr() as *mut CGGlyph),
+                    NonNull::new_unchecked(glyph_positions.as_ptr() as *mut CGPoint),
+                    glyph_ids.len(),
+                    &context,
+                );
+            }
+        } else {
+            Self::fill_glyph_outlines(&context, &ct_font, &glyph_ids, &glyph_positions);
         }
 
         CGContext::restore_g_state(Some(&context));
-
-        // Return bitmap data
-        Ok(RenderOutput::Bitmap(BitmapData {
-            width,
-            height,
-            format: BitmapFormat::Rgba8,
-            data: buffer,
-        }))
+        drop(context);
+
+        // CoreGraphics draws premultiplied; a half-covered red emoji edge
+        // would read as dark red if handed over as is
+        Ok(RenderOutput::Bitmap(
+            BitmapData {
+                width,
+                height,
+                format: BitmapFormat::Rgba8,
+                stride: BitmapFormat::Rgba8.row_bytes(width),
+                layout: PixelLayout::PREMULTIPLIED_RGBA,
+                data: buffer,
+            }
+            .into_layout(params.pixel_layout),
+        ))
     }
 
     fn supports_format(&self, format: &str) -> bool {
@@ -522,6 +593,91 @@ mod tests {
         assert!(!renderer.supports_format("svg"));
     }
 
+    /// Shape `text` one glyph per char from a `test-fonts/` file, or `None`
+    /// when the test fonts are not checked out
+    fn shape_test_font(name: &str, text: &str) -> Option<(Arc<dyn FontRef>, ShapingResult)> {
+        let mut path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.pop(); // typf-render-cg
+        path.pop(); // backends
+        path.push("test-fonts");
+        path.push(name);
+        let font: Arc<dyn FontRef> = Arc::new(typf_fontdb::TypfFontFace::from_file(&path).ok()?);
+
+        let size = 64.0;
+        let scale = size / f32::from(font.units_per_em());
+        let mut x = 0.0;
+        let mut glyphs = Vec::new();
+        for (cluster, ch) in text.char_indices() {
+            let id = font.glyph_id(ch)?;
+            glyphs.push(typf_core::types::PositionedGlyph {
+                id,
+                x,
+                y: 0.0,
+                advance: font.advance_width(id) * scale,
+                cluster: cluster as u32,
+            });
+            x += font.advance_width(id) * scale;
+        }
+        let shaped = ShapingResult {
+            glyphs,
+            advance_width: x,
+            advance_height: size,
+            direction: Direction::LeftToRight,
+        };
+        Some((font, shaped))
+    }
+
+    fn has_color(bitmap: &BitmapData) -> bool {
+        // Black text antialiases to grays; only color glyphs differ per channel
+        bitmap
+            .data
+            .chunks_exact(4)
+            .any(|px| px[3] > 0 && (px[0] != px[1] || px[1] != px[2]))
+    }
+
+    fn render_bitmap(
+        font: Arc<dyn FontRef>,
+        shaped: &ShapingResult,
+        params: &RenderParams,
+    ) -> BitmapData {
+        match CoreGraphicsRenderer::new().render(shaped, font, params) {
+            Ok(RenderOutput::Bitmap(bitmap)) => bitmap,
+            other => panic!("expected a bitmap, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_render_when_sbix_font_then_drawn_in_color() {
+        let Some((font, shaped)) = shape_test_font("Nabla-Regular-sbix.ttf", "AB") else {
+            return;
+        };
+        let bitmap = render_bitmap(font, &shaped, &RenderParams::default());
+        assert!(has_color(&bitmap), "sbix glyphs rendered without color");
+    }
+
+    #[test]
+    fn test_render_when_colr_font_then_drawn_in_color() {
+        let Some((font, shaped)) = shape_test_font("Nabla-Regular-COLR.ttf", "AB") else {
+            return;
+        };
+        let bitmap = render_bitmap(font, &shaped, &RenderParams::default());
+        assert!(has_color(&bitmap), "COLR glyphs rendered without color");
+    }
+
+    #[test]
+    fn test_render_when_monochrome_then_color_font_drawn_as_outlines() {
+        let Some((font, shaped)) = shape_test_font("Nabla-Regular-COLR.ttf", "AB") else {
+            return;
+        };
+        let params = RenderParams {
+            glyph_sources: typf_core::GlyphSourcePreference::monochrome(),
+            ..Default::default()
+        };
+        let bitmap = render_bitmap(font, &shaped, &params);
+        assert!(!has_color(&bitmap), "monochrome request still drew color");
+        assert!(bitmap.data.chunks_exact(4).any(|px| px[3] > 0));
+    }
+
     #[test]
     fn test_empty_glyphs() {
         let renderer = CoreGraphicsRenderer::new();
@@ -12,14 +12,27 @@
 //! Bitmap fonts store glyphs at specific pixel sizes (strikes). When rendering at
 //! a different size, we scale the bitmap using bilinear interpolation and flip
 //! vertically to convert from bitmap coordinates (Y-down) to font coordinates (Y-up).
+//!
+//! Pixel-art fonts blur under bilinear filtering. [`BitmapOptions`] chooses
+//! which strike to use ([`StrikeSelection`]) and how to scale it
+//! ([`BitmapScaling`]); nearest-neighbor and whole-number scaling keep every
+//! source pixel a hard-edged block.
+//!
+//! # Placement
+//!
+//! An sbix bitmap sits at the glyph's outline bounds (left side bearing and
+//! `yMin`, in font units) moved by the glyph record's `originOffsetX/Y`, in
+//! strike pixels, the way CoreText places Apple Color Emoji. [`SbixOrigin`]
+//! can drop the offsets for fonts that set them for another rasterizer.
 
-use skrifa::bitmap::{BitmapData, BitmapFormat, BitmapStrikes, Origin};
+use skrifa::bitmap::{BitmapData, BitmapStrikes, Origin};
 use skrifa::instance::{Location, Size};
 use skrifa::outline::{DrawSettings, OutlinePen};
 use skrifa::raw::TableProvider;
 use skrifa::GlyphId;
 use skrifa::MetadataProvider;
 use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, Transform};
+use typf_core::error::{Classify, ErrorKind, RenderError, TypfError};
 
 /// Error type for bitmap glyph rendering
 #[derive(Debug)]
@@ -40,6 +53,13 @@ pub enum BitmapRenderError {
     OutlineRenderFailed,
     /// No glyph available (neither bitmap nor outline)
     NoGlyphAvailable,
+    /// Decoded or scaled bitmap would pass [`MAX_BITMAP_GLYPH_DIMENSION`]
+    TooLarge {
+        /// Width the bitmap would have
+        width: u32,
+        /// Height the bitmap would have
+        height: u32,
+    },
 }
 
 impl std::fmt::Display for BitmapRenderError {
@@ -53,12 +73,56 @@ impl std::fmt::Display for BitmapRenderError {
             Self::UnsupportedFormat => write!(f, "unsupported bitmap format"),
             Self::OutlineRenderFailed => write!(f, "failed to render outline fallback"),
             Self::NoGlyphAvailable => write!(f, "no glyph available (bitmap or outline)"),
+            Self::TooLarge { width, height } => write!(
+                f,
+                "bitmap glyph of {width}x{height} exceeds the {MAX_BITMAP_GLYPH_DIMENSION}px limit"
+            ),
         }
     }
 }
 
 impl std::error::Error for BitmapRenderError {}
 
+impl Classify for BitmapRenderError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::FontParseFailed | Self::PngDecodeFailed => ErrorKind::InvalidFont,
+            Self::NoBitmapTable | Self::GlyphNotFound | Self::NoGlyphAvailable => {
+                ErrorKind::NotFound
+            },
+            Self::UnsupportedFormat => ErrorKind::Unsupported,
+            Self::PixmapCreationFailed | Self::OutlineRenderFailed => ErrorKind::Backend,
+            Self::TooLarge { .. } => ErrorKind::LimitExceeded,
+        }
+    }
+}
+
+impl From<BitmapRenderError> for RenderError {
+    fn from(e: BitmapRenderError) -> Self {
+        RenderError::classified(&e)
+    }
+}
+
+impl From<BitmapRenderError> for TypfError {
+    fn from(e: BitmapRenderError) -> Self {
+        TypfError::RenderingFailed(e.into())
+    }
+}
+
+/// Longest side, in pixels, of a bitmap glyph that is decoded or scaled
+///
+/// Sizes come straight from the font: a malformed PNG header or a strike
+/// claiming one pixel per em can ask for gigabytes before any pixel is read.
+pub const MAX_BITMAP_GLYPH_DIMENSION: u32 = 4096;
+
+/// Refuse bitmaps past [`MAX_BITMAP_GLYPH_DIMENSION`] before allocating them
+fn check_bitmap_size(width: u32, height: u32) -> Result<(), BitmapRenderError> {
+    if width > MAX_BITMAP_GLYPH_DIMENSION || height > MAX_BITMAP_GLYPH_DIMENSION {
+        return Err(BitmapRenderError::TooLarge { width, height });
+    }
+    Ok(())
+}
+
 /// A scaled bitmap glyph with positioning information
 #[derive(Debug, Clone)]
 pub struct ScaledBitmapGlyph {
@@ -68,12 +132,133 @@ pub struct ScaledBitmapGlyph {
     pub bearing_x: f32,
     /// Vertical offset from baseline to top edge (in pixels, positive = above baseline)
     pub bearing_y: f32,
+    /// sbix `originOffsetX` at the requested size; zero for other formats
+    /// and under [`SbixOrigin::Ignore`]
+    pub origin_offset_x: f32,
+    /// sbix `originOffsetY` at the requested size, positive = up; zero for
+    /// other formats and under [`SbixOrigin::Ignore`]
+    pub origin_offset_y: f32,
     /// The native ppem of the source bitmap strike
     pub native_ppem: f32,
     /// Whether the bitmap was scaled from a different strike size
     pub was_scaled: bool,
 }
 
+/// Policy for picking a bitmap strike for the requested size
+///
+/// Only strikes that contain the glyph are considered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrikeSelection {
+    /// Exact size, else the nearest larger strike, else the nearest smaller one
+    #[default]
+    Nearest,
+    /// Largest strike at or below the requested size; glyphs are only scaled up
+    SmallerOnly,
+    /// Only a strike whose ppem matches the requested size; nothing is scaled
+    ExactOnly,
+}
+
+impl StrikeSelection {
+    /// Index into `ppems` of the strike this policy picks for `requested`
+    ///
+    /// Returns `None` when no strike qualifies.
+    pub fn select(self, ppems: &[f32], requested: f32) -> Option<usize> {
+        let exact = ppems
+            .iter()
+            .position(|&ppem| (ppem - requested).abs() < STRIKE_SIZE_TOLERANCE);
+        let smaller = || {
+            ppems
+                .iter()
+                .enumerate()
+                .filter(|(_, &ppem)| ppem <= requested)
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(index, _)| index)
+        };
+
+        match self {
+            Self::ExactOnly => exact,
+            Self::SmallerOnly => exact.or_else(smaller),
+            Self::Nearest => exact
+                .or_else(|| {
+                    ppems
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, &ppem)| ppem > requested)
+                        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                        .map(|(index, _)| index)
+                })
+                .or_else(smaller),
+        }
+    }
+}
+
+/// Strike sizes closer than this to the request count as an exact match
+const STRIKE_SIZE_TOLERANCE: f32 = 0.01;
+
+/// How a strike is resized to the requested ppem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BitmapScaling {
+    /// Bilinear filtering to the exact requested size; smooth, but blurs pixel art
+    #[default]
+    Bilinear,
+    /// Nearest-neighbor sampling to the exact requested size
+    NearestNeighbor,
+    /// Nearest-neighbor by the largest whole factor that fits the requested size
+    ///
+    /// Every source pixel becomes an `n` x `n` block, so pixel fonts stay on
+    /// their grid. The glyph may come out smaller than requested, and strikes
+    /// larger than the request are drawn at their native size.
+    Integer,
+}
+
+impl BitmapScaling {
+    /// Horizontal and vertical scale from a strike of `native_ppem` to `ppem`
+    fn factors(self, ppem: f32, native_ppem_x: f32, native_ppem_y: f32) -> (f32, f32) {
+        match self {
+            Self::Integer => {
+                let factor = (ppem / native_ppem_x).floor().max(1.0);
+                (factor, factor)
+            },
+            Self::Bilinear | Self::NearestNeighbor => (ppem / native_ppem_x, ppem / native_ppem_y),
+        }
+    }
+}
+
+/// Whether sbix glyphs honor their per-glyph origin offsets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SbixOrigin {
+    /// Move the bitmap by `originOffsetX/Y`, as CoreText does
+    #[default]
+    Respect,
+    /// Put the bitmap's bottom-left corner at the outline bounds
+    Ignore,
+}
+
+/// Strike selection, scaling, and placement for bitmap glyphs
+///
+/// The default matches [`render_bitmap_glyph_scaled`]: nearest strike,
+/// bilinear scaling, sbix origin offsets applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BitmapOptions {
+    /// Which strike to draw from.
+    pub strike: StrikeSelection,
+    /// How to resize the strike to the requested size.
+    pub scaling: BitmapScaling,
+    /// How to place sbix bitmaps against the pen position.
+    pub sbix_origin: SbixOrigin,
+}
+
+impl BitmapOptions {
+    /// Settings that keep pixel-art fonts crisp: never downscale, integer zoom
+    pub fn pixel_art() -> Self {
+        Self {
+            strike: StrikeSelection::SmallerOnly,
+            scaling: BitmapScaling::Integer,
+            ..Self::default()
+        }
+    }
+}
+
 /// Check if a font has bitmap glyphs (sbix, CBDT/CBLC, or EBDT/EBLC tables)
 pub fn has_bitmap_glyphs(font_data: &[u8]) -> bool {
     if let Ok(font) = skrifa::FontRef::new(font_data) {
@@ -110,7 +295,16 @@ pub fn render_bitmap_glyph(
     ppem: f32,
 ) -> Result<Pixmap, BitmapRenderError> {
     let font = skrifa::FontRef::new(font_data).map_err(|_| BitmapRenderError::FontParseFailed)?;
-    let strikes = BitmapStrikes::new(&font);
+    render_font_bitmap_glyph(&font, glyph_id, ppem)
+}
+
+/// [`render_bitmap_glyph`] for an already parsed font, e.g. one face of a collection
+pub fn render_font_bitmap_glyph(
+    font: &skrifa::FontRef<'_>,
+    glyph_id: u32,
+    ppem: f32,
+) -> Result<Pixmap, BitmapRenderError> {
+    let strikes = BitmapStrikes::new(font);
 
     if strikes.is_empty() {
         return Err(BitmapRenderError::NoBitmapTable);
@@ -155,20 +349,52 @@ pub fn render_bitmap_glyph_scaled(
     font_data: &[u8],
     glyph_id: u32,
     ppem: f32,
+) -> Result<ScaledBitmapGlyph, BitmapRenderError> {
+    render_bitmap_glyph_scaled_with_options(font_data, glyph_id, ppem, &BitmapOptions::default())
+}
+
+/// Render a bitmap glyph with an explicit strike policy and scaling filter
+///
+/// Behaves like [`render_bitmap_glyph_scaled`], but picks the strike with
+/// `options.strike` and resizes it with `options.scaling`. Returns
+/// [`BitmapRenderError::GlyphNotFound`] when the policy rules out every strike
+/// that holds the glyph.
+pub fn render_bitmap_glyph_scaled_with_options(
+    font_data: &[u8],
+    glyph_id: u32,
+    ppem: f32,
+    options: &BitmapOptions,
 ) -> Result<ScaledBitmapGlyph, BitmapRenderError> {
     let font = skrifa::FontRef::new(font_data).map_err(|_| BitmapRenderError::FontParseFailed)?;
-    let strikes = BitmapStrikes::new(&font);
+    render_font_bitmap_scaled(&font, glyph_id, ppem, options)
+}
+
+/// [`render_bitmap_glyph_scaled_with_options`] for an already parsed font
+pub(crate) fn render_font_bitmap_scaled(
+    font: &skrifa::FontRef<'_>,
+    glyph_id: u32,
+    ppem: f32,
+    options: &BitmapOptions,
+) -> Result<ScaledBitmapGlyph, BitmapRenderError> {
+    let strikes = BitmapStrikes::new(font);
 
     if strikes.is_empty() {
         return Err(BitmapRenderError::NoBitmapTable);
     }
 
     let glyph_id_obj = GlyphId::new(glyph_id);
-    let size = Size::new(ppem);
 
-    // Get best matching glyph for the requested size
-    let bitmap_glyph = strikes
-        .glyph_for_size(size, glyph_id_obj)
+    // Pick among the strikes that actually hold this glyph
+    let candidates: Vec<_> = strikes
+        .iter()
+        .filter_map(|strike| strike.get(glyph_id_obj).map(|glyph| (strike.ppem(), glyph)))
+        .collect();
+    let ppems: Vec<f32> = candidates.iter().map(|(ppem, _)| *ppem).collect();
+    let bitmap_glyph = options
+        .strike
+        .select(&ppems, ppem)
+        .and_then(|index| candidates.into_iter().nth(index))
+        .map(|(_, glyph)| glyph)
         .ok_or(BitmapRenderError::GlyphNotFound)?;
 
     // Decode the bitmap at its native size
@@ -187,12 +413,12 @@ pub fn render_bitmap_glyph_scaled(
     let native_ppem_y = bitmap_glyph.ppem_y;
 
     // Calculate scale factors
-    let scale_x = ppem / native_ppem_x;
-    let scale_y = ppem / native_ppem_y;
+    let (scale_x, scale_y) = options.scaling.factors(ppem, native_ppem_x, native_ppem_y);
 
     // Calculate target dimensions
     let target_width = ((native_pixmap.width() as f32 * scale_x).round() as u32).max(1);
     let target_height = ((native_pixmap.height() as f32 * scale_y).round() as u32).max(1);
+    check_bitmap_size(target_width, target_height)?;
 
     // Check if scaling is needed
     let needs_scaling =
@@ -200,7 +426,14 @@ pub fn render_bitmap_glyph_scaled(
 
     // Scale the bitmap if needed
     let scaled_pixmap = if needs_scaling {
-        scale_pixmap_bilinear(&native_pixmap, target_width, target_height)?
+        match options.scaling {
+            BitmapScaling::Bilinear => {
+                scale_pixmap_bilinear(&native_pixmap, target_width, target_height)?
+            },
+            BitmapScaling::NearestNeighbor | BitmapScaling::Integer => {
+                scale_pixmap_nearest(&native_pixmap, target_width, target_height)?
+            },
+        }
     } else {
         native_pixmap
     };
@@ -218,46 +451,56 @@ pub fn render_bitmap_glyph_scaled(
     // Calculate bearings in the scaled coordinate space
     // bearing_x: horizontal offset from origin to left edge
     // bearing_y: vertical offset from baseline to top edge (positive = above baseline)
-    //
-    // For sbix, bearings may be zero and need special handling (CoreText adds 100 unit offset)
-    // For CBDT/CBLC, bearings come from the bitmap metrics
-    let bearing_x = bitmap_glyph.bearing_x * scale_x;
-
-    // Handle sbix special case: if bearing_y is 0 and this is sbix, use a default offset
-    let bearing_y = if bitmap_glyph.bearing_y == 0.0 && strikes.format() == Some(BitmapFormat::Sbix)
-    {
-        // CoreText uses 100 font units offset for sbix
-        let upem = font
-            .head()
-            .map(|h| h.units_per_em() as f32)
-            .unwrap_or(1000.0);
-        100.0 * ppem / upem
-    } else {
-        bitmap_glyph.bearing_y * scale_y
-    };
-
-    // Handle inner bearings based on placement origin
-    let (final_bearing_x, final_bearing_y) = match bitmap_glyph.placement_origin {
-        Origin::TopLeft => {
-            // Inner bearings are in pixels, apply them
-            (
-                bearing_x - bitmap_glyph.inner_bearing_x * scale_x,
-                bearing_y - bitmap_glyph.inner_bearing_y * scale_y,
-            )
-        },
-        Origin::BottomLeft => {
-            // Adjust for bottom-left origin
-            (
-                bearing_x - bitmap_glyph.inner_bearing_x * scale_x,
-                bearing_y - bitmap_glyph.inner_bearing_y * scale_y + output_pixmap.height() as f32,
-            )
-        },
-    };
+    let (final_bearing_x, final_bearing_y, origin_offset_x, origin_offset_y) =
+        match bitmap_glyph.placement_origin {
+            Origin::TopLeft => {
+                // CBDT/EBDT: inner bearings are the strike's pixel metrics
+                (
+                    -bitmap_glyph.inner_bearing_x * scale_x,
+                    -bitmap_glyph.inner_bearing_y * scale_y,
+                    0.0,
+                    0.0,
+                )
+            },
+            Origin::BottomLeft => {
+                // sbix: bearings are the outline's lsb and yMin in font units,
+                // inner bearings the glyph's originOffsetX/Y in strike pixels
+                let upem = font
+                    .head()
+                    .map(|h| h.units_per_em() as f32)
+                    .unwrap_or(1000.0);
+                let units_x = native_ppem_x / upem;
+                let units_y = native_ppem_y / upem;
+                // Glyphs without outline bounds sit 100 units up, as in CoreText
+                let y_min = if bitmap_glyph.bearing_y == 0.0 {
+                    100.0
+                } else {
+                    bitmap_glyph.bearing_y
+                };
+                let (offset_x, offset_y) = match options.sbix_origin {
+                    SbixOrigin::Respect => (
+                        bitmap_glyph.inner_bearing_x * scale_x,
+                        bitmap_glyph.inner_bearing_y * scale_y,
+                    ),
+                    SbixOrigin::Ignore => (0.0, 0.0),
+                };
+                let left = bitmap_glyph.bearing_x * units_x * scale_x + offset_x;
+                let bottom = y_min * units_y * scale_y + offset_y;
+                (
+                    left,
+                    bottom + output_pixmap.height() as f32,
+                    offset_x,
+                    offset_y,
+                )
+            },
+        };
 
     Ok(ScaledBitmapGlyph {
         pixmap: output_pixmap,
         bearing_x: final_bearing_x,
         bearing_y: final_bearing_y,
+        origin_offset_x,
+        origin_offset_y,
         native_ppem: native_ppem_x, // Typically x and y are the same
         was_scaled: needs_scaling,
     })
@@ -318,6 +561,39 @@ fn scale_pixmap_bilinear(
     .ok_or(BitmapRenderError::PixmapCreationFailed)
 }
 
+/// Scale a pixmap by picking the nearest source pixel, without filtering
+fn scale_pixmap_nearest(
+    src: &Pixmap,
+    target_width: u32,
+    target_height: u32,
+) -> Result<Pixmap, BitmapRenderError> {
+    let src_width = src.width() as usize;
+    let src_height = src.height() as usize;
+    let dst_width = target_width as usize;
+    let dst_height = target_height as usize;
+
+    let mut dst_data = vec![0u8; dst_width * dst_height * 4];
+    let src_data = src.data();
+
+    for dst_y in 0..dst_height {
+        // Sample at the destination pixel center
+        let src_y = ((dst_y * 2 + 1) * src_height / (dst_height * 2)).min(src_height - 1);
+        for dst_x in 0..dst_width {
+            let src_x = ((dst_x * 2 + 1) * src_width / (dst_width * 2)).min(src_width - 1);
+            let src_idx = (src_y * src_width + src_x) * 4;
+            let dst_idx = (dst_y * dst_width + dst_x) * 4;
+            dst_data[dst_idx..dst_idx + 4].copy_from_slice(&src_data[src_idx..src_idx + 4]);
+        }
+    }
+
+    Pixmap::from_vec(
+        dst_data,
+        tiny_skia::IntSize::from_wh(target_width, target_height)
+            .ok_or(BitmapRenderError::PixmapCreationFailed)?,
+    )
+    .ok_or(BitmapRenderError::PixmapCreationFailed)
+}
+
 /// Flip a pixmap vertically (Y-axis mirror)
 fn flip_pixmap_vertical(src: &Pixmap) -> Result<Pixmap, BitmapRenderError> {
     let width = src.width() as usize;
@@ -362,16 +638,27 @@ pub fn render_bitmap_glyph_or_outline(
     width: u32,
     height: u32,
     ppem: f32,
+) -> Result<(Pixmap, bool), BitmapRenderError> {
+    let font = skrifa::FontRef::new(font_data).map_err(|_| BitmapRenderError::FontParseFailed)?;
+    render_font_bitmap_or_outline(&font, glyph_id, width, height, ppem)
+}
+
+/// [`render_bitmap_glyph_or_outline`] for an already parsed font
+pub(crate) fn render_font_bitmap_or_outline(
+    font: &skrifa::FontRef<'_>,
+    glyph_id: u32,
+    width: u32,
+    height: u32,
+    ppem: f32,
 ) -> Result<(Pixmap, bool), BitmapRenderError> {
     // Try scaled bitmap first (this handles scaling and vertical flip)
-    match render_bitmap_glyph_scaled(font_data, glyph_id, ppem) {
+    match render_font_bitmap_scaled(font, glyph_id, ppem, &BitmapOptions::default()) {
         Ok(scaled) => Ok((scaled.pixmap, true)),
         Err(BitmapRenderError::NoBitmapTable)
         | Err(BitmapRenderError::GlyphNotFound)
         | Err(BitmapRenderError::UnsupportedFormat) => {
             // Fall back to outline rendering
-            render_outline_glyph(font_data, glyph_id, width, height, ppem)
-                .map(|pixmap| (pixmap, false))
+            render_outline_glyph(font, glyph_id, width, height, ppem).map(|pixmap| (pixmap, false))
         },
         Err(e) => Err(e),
     }
@@ -379,13 +666,12 @@ pub fn render_bitmap_glyph_or_outline(
 
 /// Render a glyph outline to a pixmap
 fn render_outline_glyph(
-    font_data: &[u8],
+    font: &skrifa::FontRef<'_>,
     glyph_id: u32,
     width: u32,
     height: u32,
     ppem: f32,
 ) -> Result<Pixmap, BitmapRenderError> {
-    let font = skrifa::FontRef::new(font_data).map_err(|_| BitmapRenderError::FontParseFailed)?;
     let glyph_id = GlyphId::new(glyph_id);
 
     // Get the glyph outline
@@ -474,6 +760,8 @@ fn decode_png_to_pixmap(png_data: &[u8]) -> Result<Pixmap, BitmapRenderError> {
     let mut reader = decoder
         .read_info()
         .map_err(|_| BitmapRenderError::PngDecodeFailed)?;
+    let header = reader.info();
+    check_bitmap_size(header.width, header.height)?;
 
     let mut buf = vec![0; reader.output_buffer_size()];
     let info = reader
@@ -620,6 +908,178 @@ mod tests {
         );
     }
 
+    /// A 4x6 opaque PNG
+    fn tiny_png() -> Vec<u8> {
+        let mut png_bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut png_bytes, 4, 6);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().expect("write_header");
+            writer
+                .write_image_data(&[255; 4 * 6 * 4])
+                .expect("write_image_data");
+        }
+        png_bytes
+    }
+
+    /// A 1000-unit font whose glyph 1 is a 20 ppem sbix PNG
+    ///
+    /// `outline` gives the glyph's lsb and yMin; without it the glyph has no
+    /// `glyf` data, like Apple Color Emoji.
+    fn sbix_font(outline: Option<(i16, i16)>, origin_offset: (i16, i16)) -> Vec<u8> {
+        let mut glyf = Vec::new();
+        if let Some((lsb, y_min)) = outline {
+            // Zero contours, bbox, no instructions
+            for value in [0, lsb, y_min, lsb + 200, y_min + 300, 0] {
+                glyf.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        let mut head = vec![0u8; 54];
+        head[0..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        head[12..16].copy_from_slice(&0x5F0F_3CF5u32.to_be_bytes());
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        let mut hhea = vec![0u8; 36];
+        hhea[0..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        hhea[34..36].copy_from_slice(&2u16.to_be_bytes());
+        let mut maxp = 0x0000_5000u32.to_be_bytes().to_vec();
+        maxp.extend_from_slice(&2u16.to_be_bytes());
+        let lsb = outline.map_or(0, |(lsb, _)| lsb);
+        let mut hmtx = Vec::new();
+        for value in [500, 0, 1000, lsb] {
+            hmtx.extend_from_slice(&value.to_be_bytes());
+        }
+        let mut loca = Vec::new();
+        for value in [0u16, 0, (glyf.len() / 2) as u16] {
+            loca.extend_from_slice(&value.to_be_bytes());
+        }
+
+        let png = tiny_png();
+        let mut glyph = Vec::new();
+        glyph.extend_from_slice(&origin_offset.0.to_be_bytes());
+        glyph.extend_from_slice(&origin_offset.1.to_be_bytes());
+        glyph.extend_from_slice(b"png ");
+        glyph.extend_from_slice(&png);
+        let mut strike = Vec::new();
+        strike.extend_from_slice(&20u16.to_be_bytes()); // ppem
+        strike.extend_from_slice(&72u16.to_be_bytes()); // ppi
+        let data_start = 4 + 4 * 3;
+        for offset in [data_start, data_start, data_start + glyph.len()] {
+            strike.extend_from_slice(&(offset as u32).to_be_bytes());
+        }
+        strike.extend_from_slice(&glyph);
+        let mut sbix = Vec::new();
+        sbix.extend_from_slice(&1u16.to_be_bytes()); // version
+        sbix.extend_from_slice(&1u16.to_be_bytes()); // flags
+        sbix.extend_from_slice(&1u32.to_be_bytes()); // numStrikes
+        sbix.extend_from_slice(&12u32.to_be_bytes());
+        sbix.extend_from_slice(&strike);
+
+        let tables: [(&[u8; 4], Vec<u8>); 7] = [
+            (b"glyf", glyf),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"hmtx", hmtx),
+            (b"loca", loca),
+            (b"maxp", maxp),
+            (b"sbix", sbix),
+        ];
+        let mut font = Vec::new();
+        font.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        font.extend_from_slice(&(tables.len() as u16).to_be_bytes());
+        font.extend_from_slice(&[0; 6]);
+        let mut offset = 12 + 16 * tables.len();
+        let mut body = Vec::new();
+        for (tag, data) in &tables {
+            font.extend_from_slice(*tag);
+            font.extend_from_slice(&0u32.to_be_bytes());
+            font.extend_from_slice(&(offset as u32).to_be_bytes());
+            font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            body.extend_from_slice(data);
+            while body.len() % 4 != 0 {
+                body.push(0);
+            }
+            offset = 12 + 16 * tables.len() + body.len();
+        }
+        font.extend_from_slice(&body);
+        font
+    }
+
+    #[test]
+    fn test_sbix_placement_when_origin_offset_then_bitmap_moves() {
+        // lsb 100 and yMin -200 units are 2 and -4 px at 20 ppem
+        let font = sbix_font(Some((100, -200)), (2, 3));
+
+        let glyph = render_bitmap_glyph_scaled(&font, 1, 20.0).expect("render");
+        assert_eq!((glyph.pixmap.width(), glyph.pixmap.height()), (4, 6));
+        assert_eq!((glyph.bearing_x, glyph.bearing_y), (4.0, 5.0));
+        assert_eq!((glyph.origin_offset_x, glyph.origin_offset_y), (2.0, 3.0));
+
+        let doubled = render_bitmap_glyph_scaled(&font, 1, 40.0).expect("render");
+        assert_eq!((doubled.bearing_x, doubled.bearing_y), (8.0, 10.0));
+    }
+
+    #[test]
+    fn test_sbix_placement_when_origin_ignored_then_outline_bounds_only() {
+        let font = sbix_font(Some((100, -200)), (2, 3));
+        let options = BitmapOptions {
+            sbix_origin: SbixOrigin::Ignore,
+            ..BitmapOptions::default()
+        };
+
+        let glyph =
+            render_bitmap_glyph_scaled_with_options(&font, 1, 20.0, &options).expect("render");
+        assert_eq!((glyph.bearing_x, glyph.bearing_y), (2.0, 2.0));
+        assert_eq!((glyph.origin_offset_x, glyph.origin_offset_y), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_sbix_placement_when_no_outline_then_apple_emoji_baseline() {
+        // Apple Color Emoji style: empty glyf, bitmap lowered below the
+        // baseline by a negative originOffsetY
+        let font = sbix_font(None, (0, -4));
+
+        let glyph = render_bitmap_glyph_scaled(&font, 1, 20.0).expect("render");
+        // 100 units up (2 px), 4 px down, 6 px tall
+        assert_eq!((glyph.bearing_x, glyph.bearing_y), (0.0, 4.0));
+    }
+
+    #[test]
+    fn test_strike_selection_when_between_strikes_then_policy_decides() {
+        let ppems = [16.0, 32.0, 64.0];
+
+        assert_eq!(StrikeSelection::Nearest.select(&ppems, 40.0), Some(2));
+        assert_eq!(StrikeSelection::Nearest.select(&ppems, 80.0), Some(2));
+        assert_eq!(StrikeSelection::SmallerOnly.select(&ppems, 40.0), Some(1));
+        assert_eq!(StrikeSelection::SmallerOnly.select(&ppems, 8.0), None);
+        assert_eq!(StrikeSelection::ExactOnly.select(&ppems, 40.0), None);
+        assert_eq!(StrikeSelection::ExactOnly.select(&ppems, 32.0), Some(1));
+        assert_eq!(StrikeSelection::Nearest.select(&[], 32.0), None);
+    }
+
+    #[test]
+    fn test_integer_scaling_when_request_not_a_multiple_then_floors_to_whole_factor() {
+        assert_eq!(BitmapScaling::Integer.factors(40.0, 16.0, 16.0), (2.0, 2.0));
+        assert_eq!(BitmapScaling::Integer.factors(12.0, 16.0, 16.0), (1.0, 1.0));
+        assert_eq!(
+            BitmapScaling::NearestNeighbor.factors(40.0, 16.0, 16.0),
+            (2.5, 2.5)
+        );
+    }
+
+    #[test]
+    fn test_scale_pixmap_nearest_when_doubled_then_pixels_become_blocks() {
+        let size = tiny_skia::IntSize::from_wh(2, 1).expect("size");
+        let src = Pixmap::from_vec(vec![255, 0, 0, 255, 0, 0, 255, 255], size).expect("pixmap");
+
+        let scaled = scale_pixmap_nearest(&src, 4, 2).expect("scale");
+
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let row = [red, red, blue, blue].concat();
+        assert_eq!(scaled.data(), [row.clone(), row].concat());
+    }
+
     #[test]
     fn test_has_bitmap_glyphs_regular_font() {
         // Regular fonts without bitmap tables should return false
@@ -0,0 +1,526 @@
+// this_file: backends/typf-render-color/src/color_font.rs
+//! A color font parsed once and reused across glyphs
+//!
+//! The free functions in this crate take raw bytes and parse the font again on
+//! every call, which adds up when a renderer walks a whole string or when
+//! [`detect_color_font_types`](crate::detect_color_font_types) probes hundreds
+//! of glyphs. [`ColorFont`] parses the table directory once and remembers
+//! what it has already looked up: the color formats present, the CPAL
+//! palettes, and each glyph's COLR format. The free functions are now thin
+//! wrappers that build a `ColorFont` for a single call.
+//!
+//! The SVG and bitmap paths go through crate-internal helpers that take the
+//! parsed font, so a face picked with [`ColorFont::from_index`] stays selected.
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use skrifa::color::{ColorGlyph, ColorGlyphFormat, ColorPalettes};
+use skrifa::raw::TableProvider;
+use skrifa::{GlyphId, MetadataProvider};
+use typf_core::budget::MemoryBudget;
+
+use crate::{
+    paint_colr_glyph, ColorFontType, ColorRenderError, ColrRasterOptions, Pixmap, RenderMethod,
+    RenderResult,
+};
+
+/// Largest glyph ID probed when detecting COLR versions.
+const COLR_PROBE_LIMIT: u16 = 1000;
+
+/// A parsed font with cached color-table lookups
+///
+/// Borrowing the bytes keeps construction free of copies. The caches use
+/// interior locking, so a `ColorFont` can be shared across threads.
+pub struct ColorFont<'a> {
+    data: &'a [u8],
+    font: skrifa::FontRef<'a>,
+    upem: f32,
+    types: OnceLock<Vec<ColorFontType>>,
+    palettes: OnceLock<Vec<Vec<skrifa::color::Color>>>,
+    glyph_formats: RwLock<HashMap<u32, Option<ColorGlyphFormat>>>,
+    budget: Option<MemoryBudget>,
+}
+
+impl std::fmt::Debug for ColorFont<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ColorFont")
+            .field("len", &self.data.len())
+            .field("upem", &self.upem)
+            .finish()
+    }
+}
+
+impl<'a> ColorFont<'a> {
+    /// Parse `font_data`, failing only if it is not a font.
+    pub fn new(font_data: &'a [u8]) -> Result<Self, ColorRenderError> {
+        Self::from_index(font_data, 0)
+    }
+
+    /// Parse face `index` of `font_data`, which may be a TTC/OTC collection.
+    pub fn from_index(font_data: &'a [u8], index: u32) -> Result<Self, ColorRenderError> {
+        let font = skrifa::FontRef::from_index(font_data, index)
+            .map_err(|_| ColorRenderError::FontParseFailed)?;
+        let upem = font.head().map(|h| h.units_per_em()).unwrap_or(1000) as f32;
+        Ok(Self {
+            data: font_data,
+            font,
+            upem,
+            types: OnceLock::new(),
+            palettes: OnceLock::new(),
+            glyph_formats: RwLock::new(HashMap::new()),
+            budget: None,
+        })
+    }
+
+    /// Reserve COLR pixmaps and layers against `budget`
+    ///
+    /// Painting that would pass the limit fails with
+    /// [`ColorRenderError::BudgetExceeded`]. SVG and bitmap glyphs are not
+    /// reserved here; renderers account for the pixmaps they return.
+    pub fn with_memory_budget(mut self, budget: Option<MemoryBudget>) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// The raw font bytes.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The underlying skrifa font, for lookups this wrapper does not cover.
+    pub fn skrifa(&self) -> &skrifa::FontRef<'a> {
+        &self.font
+    }
+
+    /// Units per em from the `head` table (1000 if missing).
+    pub fn units_per_em(&self) -> f32 {
+        self.upem
+    }
+
+    /// True when the font has a COLR table.
+    pub fn has_colr(&self) -> bool {
+        self.font.colr().is_ok()
+    }
+
+    /// Color formats present, in preferred render order; computed once.
+    ///
+    /// COLR versions are detected by probing the first 1000 glyphs.
+    pub fn color_types(&self) -> &[ColorFontType] {
+        self.types.get_or_init(|| self.detect_types())
+    }
+
+    /// True when any color format is available.
+    pub fn has_any_color_support(&self) -> bool {
+        !self.color_types().is_empty()
+    }
+
+    /// The preferred color format for rendering.
+    pub fn best_color_type(&self) -> Option<ColorFontType> {
+        self.color_types().first().copied()
+    }
+
+    /// Colors of CPAL palette `index`, or `None` if the font has no such palette.
+    pub fn palette_colors(&self, index: u16) -> Option<&[skrifa::color::Color]> {
+        let palettes = self.palettes.get_or_init(|| {
+            let palettes = ColorPalettes::new(&self.font);
+            (0..palettes.len())
+                .filter_map(|i| palettes.get(i).map(|p| p.colors().to_vec()))
+                .collect()
+        });
+        palettes.get(index as usize).map(Vec::as_slice)
+    }
+
+    /// The COLR format of `glyph_id` (v1 preferred over v0), cached per glyph.
+    pub fn glyph_format(&self, glyph_id: u32) -> Option<ColorGlyphFormat> {
+        if let Some(format) = self
+            .glyph_formats
+            .read()
+            .ok()
+            .and_then(|formats| formats.get(&glyph_id).copied())
+        {
+            return format;
+        }
+
+        let gid = GlyphId::new(glyph_id);
+        let color_glyphs = self.font.color_glyphs();
+        let format = [ColorGlyphFormat::ColrV1, ColorGlyphFormat::ColrV0]
+            .into_iter()
+            .find(|&format| color_glyphs.get_with_format(gid, format).is_some());
+
+        if let Ok(mut formats) = self.glyph_formats.write() {
+            formats.insert(glyph_id, format);
+        }
+        format
+    }
+
+    /// Render one COLR glyph; see [`render_color_glyph_with_options`](crate::render_color_glyph_with_options).
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_colr(
+        &self,
+        glyph_id: u32,
+        width: u32,
+        height: u32,
+        size: f32,
+        palette_index: u16,
+        variations: &[(&str, f32)],
+        options: &ColrRasterOptions,
+    ) -> Result<Pixmap, ColorRenderError> {
+        let color_glyph = self
+            .font
+            .color_glyphs()
+            .get(GlyphId::new(glyph_id))
+            .ok_or(ColorRenderError::GlyphNotFound)?;
+        let colors = self
+            .palette_colors(palette_index)
+            .ok_or(ColorRenderError::NoPalette)?;
+
+        let location = self.font.axes().location(variations.iter().copied());
+        let scale = size / self.upem;
+
+        let colr_bbox = color_glyph.bounding_box(&location, skrifa::instance::Size::unscaled());
+
+        let (pix_width, pix_height, translate_x, translate_y) = if let Some(bbox) = colr_bbox {
+            let scaled_x0 = bbox.x_min * scale;
+            let scaled_y0 = bbox.y_min * scale;
+            let scaled_x1 = bbox.x_max * scale;
+            let scaled_y1 = bbox.y_max * scale;
+
+            let w = ((scaled_x1 - scaled_x0).ceil() as u32).max(1);
+            let h = ((scaled_y1 - scaled_y0).ceil() as u32).max(1);
+
+            (w, h, -scaled_x0, scaled_y1)
+        } else {
+            (width, height, 0.0, size)
+        };
+
+        let transform = tiny_skia::Transform::from_scale(scale, -scale)
+            .post_translate(translate_x, translate_y);
+
+        paint_colr_glyph(
+            &color_glyph,
+            &location,
+            colors,
+            &self.font,
+            size,
+            pix_width,
+            pix_height,
+            transform,
+            options,
+            self.budget.as_ref(),
+        )
+    }
+
+    /// Render a glyph with the best available source; see [`render_glyph`](crate::render_glyph).
+    pub fn render_glyph(
+        &self,
+        glyph_id: u32,
+        width: u32,
+        height: u32,
+        size: f32,
+        palette_index: u16,
+        variations: &[(&str, f32)],
+    ) -> Result<RenderResult, ColorRenderError> {
+        if let Some(format) = self.glyph_format(glyph_id) {
+            let pixmap = self.render_colr(
+                glyph_id,
+                width,
+                height,
+                size,
+                palette_index,
+                variations,
+                &ColrRasterOptions::default(),
+            )?;
+            let method = match format {
+                ColorGlyphFormat::ColrV1 => RenderMethod::ColrV1,
+                ColorGlyphFormat::ColrV0 => RenderMethod::ColrV0,
+            };
+            return Ok(RenderResult {
+                pixmap,
+                method,
+                bearing_x: None,
+                bearing_y: None,
+            });
+        }
+
+        #[cfg(feature = "svg")]
+        {
+            let palette_colors = self.palette_colors(palette_index).unwrap_or_default();
+            if let Ok(pixmap) =
+                crate::svg::render_font_svg_glyph(&self.font, glyph_id, palette_colors, size)
+            {
+                return Ok(RenderResult {
+                    pixmap,
+                    method: RenderMethod::Svg,
+                    bearing_x: None,
+                    bearing_y: None,
+                });
+            }
+        }
+
+        #[cfg(feature = "bitmap")]
+        {
+            use crate::bitmap::{self, BitmapRenderError};
+
+            match bitmap::render_font_bitmap_scaled(
+                &self.font,
+                glyph_id,
+                size,
+                &bitmap::BitmapOptions::default(),
+            ) {
+                Ok(scaled) => Ok(RenderResult {
+                    pixmap: scaled.pixmap,
+                    method: RenderMethod::Bitmap,
+                    bearing_x: Some(scaled.bearing_x),
+                    bearing_y: Some(scaled.bearing_y),
+                }),
+                Err(BitmapRenderError::NoBitmapTable)
+                | Err(BitmapRenderError::GlyphNotFound)
+                | Err(BitmapRenderError::UnsupportedFormat) => {
+                    let (pixmap, _used_bitmap) = bitmap::render_font_bitmap_or_outline(
+                        &self.font, glyph_id, width, height, size,
+                    )?;
+                    Ok(RenderResult {
+                        pixmap,
+                        method: RenderMethod::Outline,
+                        bearing_x: None,
+                        bearing_y: None,
+                    })
+                },
+                Err(e) => Err(e.into()),
+            }
+        }
+
+        #[cfg(not(feature = "bitmap"))]
+        Err(ColorRenderError::GlyphNotFound)
+    }
+
+    /// Render the first available source in `preference` order; see
+    /// [`render_glyph_with_preference`](crate::render_glyph_with_preference).
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_glyph_with_preference(
+        &self,
+        glyph_id: u32,
+        width: u32,
+        height: u32,
+        size: f32,
+        palette_index: u16,
+        variations: &[(&str, f32)],
+        preference: &typf_core::GlyphSourcePreference,
+    ) -> Result<(RenderResult, typf_core::GlyphSource), ColorRenderError> {
+        use typf_core::GlyphSource;
+
+        let gid = GlyphId::new(glyph_id);
+        // Size rules pick the order for this size, e.g. bitmaps when small
+        for source in preference.order_at(size) {
+            match source {
+                GlyphSource::Colr1 | GlyphSource::Colr0 => {
+                    let (format, method) = if source == GlyphSource::Colr1 {
+                        (ColorGlyphFormat::ColrV1, RenderMethod::ColrV1)
+                    } else {
+                        (ColorGlyphFormat::ColrV0, RenderMethod::ColrV0)
+                    };
+                    if let Some(color_glyph) = self.font.color_glyphs().get_with_format(gid, format)
+                    {
+                        let pixmap = self.paint_unscaled(
+                            &color_glyph,
+                            width,
+                            height,
+                            size,
+                            palette_index,
+                            variations,
+                        )?;
+                        return Ok((
+                            RenderResult {
+                                pixmap,
+                                method,
+                                bearing_x: None,
+                                bearing_y: None,
+                            },
+                            source,
+                        ));
+                    }
+                },
+                GlyphSource::Svg => {
+                    #[cfg(feature = "svg")]
+                    {
+                        let palette_colors = self.palette_colors(palette_index).unwrap_or_default();
+                        if let Ok(pixmap) = crate::svg::render_font_svg_glyph(
+                            &self.font,
+                            glyph_id,
+                            palette_colors,
+                            size,
+                        ) {
+                            return Ok((
+                                RenderResult {
+                                    pixmap,
+                                    method: RenderMethod::Svg,
+                                    bearing_x: Some(0.0),
+                                    bearing_y: Some(size),
+                                },
+                                GlyphSource::Svg,
+                            ));
+                        }
+                    }
+                },
+                GlyphSource::Sbix | GlyphSource::Cbdt | GlyphSource::Ebdt => {
+                    #[cfg(feature = "bitmap")]
+                    {
+                        use crate::bitmap::{self, BitmapRenderError};
+
+                        match bitmap::render_font_bitmap_scaled(
+                            &self.font,
+                            glyph_id,
+                            size,
+                            &bitmap::BitmapOptions::default(),
+                        ) {
+                            Ok(scaled) => {
+                                return Ok((
+                                    RenderResult {
+                                        pixmap: scaled.pixmap,
+                                        method: RenderMethod::Bitmap,
+                                        bearing_x: Some(scaled.bearing_x),
+                                        bearing_y: Some(scaled.bearing_y),
+                                    },
+                                    source,
+                                ));
+                            },
+                            Err(BitmapRenderError::NoBitmapTable)
+                            | Err(BitmapRenderError::GlyphNotFound)
+                            | Err(BitmapRenderError::UnsupportedFormat) => {},
+                            Err(e) => return Err(e.into()),
+                        }
+                    }
+                },
+                GlyphSource::Glyf | GlyphSource::Cff | GlyphSource::Cff2 => {
+                    continue;
+                },
+            }
+        }
+
+        Err(ColorRenderError::GlyphNotFound)
+    }
+
+    /// Paint a COLR glyph with an identity transform, as the preference path expects.
+    fn paint_unscaled(
+        &self,
+        color_glyph: &ColorGlyph<'_>,
+        width: u32,
+        height: u32,
+        size: f32,
+        palette_index: u16,
+        variations: &[(&str, f32)],
+    ) -> Result<Pixmap, ColorRenderError> {
+        let colors = self
+            .palette_colors(palette_index)
+            .ok_or(ColorRenderError::NoPalette)?;
+        let location = self.font.axes().location(variations.iter().copied());
+        paint_colr_glyph(
+            color_glyph,
+            &location,
+            colors,
+            &self.font,
+            size,
+            width,
+            height,
+            tiny_skia::Transform::identity(),
+            &ColrRasterOptions::default(),
+            self.budget.as_ref(),
+        )
+    }
+
+    fn detect_types(&self) -> Vec<ColorFontType> {
+        let mut types = Vec::new();
+
+        let num_glyphs = self.font.maxp().map(|m| m.num_glyphs()).unwrap_or(0);
+        let mut has_colr_v1 = false;
+        let mut has_colr_v0 = false;
+        for gid in 0..num_glyphs.min(COLR_PROBE_LIMIT) {
+            match self.glyph_format(gid as u32) {
+                Some(ColorGlyphFormat::ColrV1) => {
+                    has_colr_v1 = true;
+                    break;
+                },
+                Some(ColorGlyphFormat::ColrV0) => has_colr_v0 = true,
+                None => {},
+            }
+        }
+
+        if has_colr_v1 {
+            types.push(ColorFontType::ColrV1);
+        }
+        if has_colr_v0 {
+            types.push(ColorFontType::ColrV0);
+        }
+
+        #[cfg(feature = "svg")]
+        if self.font.svg().is_ok() {
+            types.push(ColorFontType::Svg);
+        }
+
+        #[cfg(feature = "bitmap")]
+        if !skrifa::bitmap::BitmapStrikes::new(&self.font).is_empty() {
+            types.push(ColorFontType::Bitmap);
+        }
+
+        types
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(name: &str) -> Option<Vec<u8>> {
+        let path = format!("../../test-fonts/{}", name);
+        let data = std::fs::read(&path).ok();
+        if data.is_none() {
+            eprintln!("Skipping test: font not found at {}", path);
+        }
+        data
+    }
+
+    #[test]
+    fn test_new_when_garbage_then_parse_error() {
+        assert!(matches!(
+            ColorFont::new(b"not a font"),
+            Err(ColorRenderError::FontParseFailed)
+        ));
+    }
+
+    #[test]
+    fn test_color_font_when_outline_font_then_no_color_and_cached_misses() {
+        let Some(data) = load("NotoSans-Regular.ttf") else {
+            return;
+        };
+        let font = ColorFont::new(&data).expect("parse");
+
+        assert!(font.color_types().is_empty());
+        assert_eq!(font.best_color_type(), None);
+        assert!(font.glyph_format(1).is_none());
+        assert!(
+            font.glyph_formats.read().expect("lock").contains_key(&1),
+            "the miss is remembered"
+        );
+    }
+
+    #[test]
+    fn test_color_font_when_colrv1_then_matches_free_functions() {
+        let Some(data) = load("Nabla-Regular-COLR.ttf") else {
+            return;
+        };
+        let font = ColorFont::new(&data).expect("parse");
+
+        assert_eq!(font.color_types(), crate::detect_color_font_types(&data));
+        assert_eq!(font.best_color_type(), Some(ColorFontType::ColrV1));
+        assert!(font.palette_colors(0).is_some_and(|c| !c.is_empty()));
+
+        let gid = crate::colr_base_glyphs(&data).expect("parse")[0].glyph_id;
+        let rendered = font
+            .render_glyph(gid, 64, 64, 64.0, 0, &[])
+            .expect("render");
+        let via_bytes = crate::render_glyph(&data, gid, 64, 64, 64.0, 0).expect("render");
+        assert_eq!(rendered.method, via_bytes.method);
+        assert_eq!(rendered.pixmap.data(), via_bytes.pixmap.data());
+    }
+}
@@ -11,10 +11,15 @@ pub mod bitmap;
 #[cfg(feature = "svg")]
 pub mod svg;
 
+mod color_font;
+pub mod limits;
+pub mod tables;
+
 #[cfg(feature = "bitmap")]
 pub use bitmap::{
     get_bitmap_sizes, has_bitmap_glyphs, render_bitmap_glyph, render_bitmap_glyph_or_outline,
-    render_bitmap_glyph_scaled, BitmapRenderError, ScaledBitmapGlyph,
+    render_bitmap_glyph_scaled, render_bitmap_glyph_scaled_with_options, BitmapOptions,
+    BitmapRenderError, BitmapScaling, SbixOrigin, ScaledBitmapGlyph, StrikeSelection,
 };
 
 #[cfg(feature = "svg")]
@@ -23,14 +28,23 @@ pub use svg::{
     render_svg_glyph_with_palette_and_ppem, SvgRenderError,
 };
 
+pub use color_font::ColorFont;
+pub use limits::{PaintGuard, MAX_COLR_LAYERS, MAX_COLR_NESTING, MAX_COLR_PAINT_OPS};
+
+pub use tables::{
+    bitmap_strikes, colr_base_glyphs, svg_document_ranges, BitmapGlyphFormat, BitmapGlyphRange,
+    BitmapStrikeRecord, ColrGlyphRecord, SvgDocumentRange,
+};
+
 use skrifa::color::{Brush, ColorPainter, ColorStop, CompositeMode, Extend, Transform};
 use skrifa::outline::{DrawSettings, OutlinePen};
-use skrifa::raw::TableProvider;
 use skrifa::{GlyphId, MetadataProvider};
 use tiny_skia::{
     BlendMode, Color, FillRule, GradientStop, LinearGradient, Mask, Paint, PathBuilder,
     PixmapPaint, Point, RadialGradient, SpreadMode,
 };
+use typf_core::budget::{self, MemoryBudget, Reservation};
+use typf_core::error::{Classify, ErrorKind, RenderError, TypfError};
 
 /// Outline pen that records glyph curves into a `tiny-skia` path.
 struct TinySkiaPathPen {
@@ -80,11 +94,20 @@ pub struct TinySkiaColorPainter<'a> {
     palette: &'a [skrifa::color::Color],
     font: &'a skrifa::FontRef<'a>,
     size: f32,
+    /// Variation instance used for `PaintGlyph` clip outlines
+    location: Location,
+    budget: Option<MemoryBudget>,
+    /// First failure; once set, every later callback is ignored
+    error: Option<ColorRenderError>,
+    /// Paint callbacks made so far, checked against the `MAX_COLR_*` limits
+    guard: PaintGuard,
 }
 
 struct LayerState {
     pixmap: Pixmap,
     composite_mode: CompositeMode,
+    /// Budget held for `pixmap`, returned when the layer is popped
+    _memory: Reservation,
 }
 
 impl<'a> TinySkiaColorPainter<'a> {
@@ -107,6 +130,10 @@ impl<'a> TinySkiaColorPainter<'a> {
             palette,
             font,
             size,
+            location: Location::default(),
+            budget: None,
+            error: None,
+            guard: PaintGuard::new(),
         }
     }
 
@@ -129,7 +156,51 @@ impl<'a> TinySkiaColorPainter<'a> {
             palette,
             font,
             size,
+            location: Location::default(),
+            budget: None,
+            error: None,
+            guard: PaintGuard::new(),
+        }
+    }
+
+    /// Draw clip glyph outlines at `location` rather than the default instance
+    ///
+    /// Pass the same location given to [`ColorGlyph::paint`] so the clips
+    /// follow the variable outlines they were designed against.
+    pub fn with_location(mut self, location: &Location) -> Self {
+        self.location = location.clone();
+        self
+    }
+
+    /// Reserve every layer pixmap against `budget`
+    ///
+    /// A layer that would pass the limit is not pushed and painting stops;
+    /// [`take_error`](Self::take_error) reports the failure.
+    pub fn with_memory_budget(mut self, budget: Option<MemoryBudget>) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Why painting stopped early, if it did
+    ///
+    /// `ColorPainter` callbacks cannot fail, so a layer past the memory
+    /// budget, or a paint graph past a [`limits`] bound, is recorded here
+    /// and every later callback is ignored. The pixmap then holds a partial
+    /// glyph.
+    pub fn take_error(&mut self) -> Option<ColorRenderError> {
+        self.error.take()
+    }
+
+    /// Count one callback that would nest `depth` deep; false once painting has stopped
+    fn admit(&mut self, depth: usize, layer: bool) -> bool {
+        if self.error.is_some() {
+            return false;
+        }
+        if let Err(e) = self.guard.admit(depth, layer) {
+            self.error = Some(e);
+            return false;
         }
+        true
     }
 
     fn current_transform(&self) -> tiny_skia::Transform {
@@ -210,8 +281,8 @@ impl<'a> TinySkiaColorPainter<'a> {
 
         let mut pen = TinySkiaPathPen::new();
 
-        let location = skrifa::instance::Location::default();
-        let settings = DrawSettings::unhinted(skrifa::instance::Size::new(self.size), &location);
+        let settings =
+            DrawSettings::unhinted(skrifa::instance::Size::new(self.size), &self.location);
         outline.draw(settings, &mut pen).ok()?;
 
         let path = pen.finish()?;
@@ -226,6 +297,27 @@ impl<'a> TinySkiaColorPainter<'a> {
         Some(mask)
     }
 
+    /// Rectangle that covers the whole `width` x `height` target in the current user space.
+    ///
+    /// Fills are bounded by the clip stack, so they must reach every device
+    /// pixel however the current transform scales, flips, or skews.
+    fn user_space_bounds(
+        width: u32,
+        height: u32,
+        transform: tiny_skia::Transform,
+    ) -> Option<tiny_skia::Rect> {
+        let inverse = transform.invert()?;
+        let (w, h) = (width as f32, height as f32);
+        let mut corners = [
+            Point::from_xy(0.0, 0.0),
+            Point::from_xy(w, 0.0),
+            Point::from_xy(0.0, h),
+            Point::from_xy(w, h),
+        ];
+        inverse.map_points(&mut corners);
+        tiny_skia::Rect::from_points(&corners)
+    }
+
     fn create_box_clip_mask(&self, clip_box: skrifa::raw::types::BoundingBox<f32>) -> Option<Mask> {
         let rect = tiny_skia::Rect::from_ltrb(
             clip_box.x_min,
@@ -249,18 +341,25 @@ impl<'a> TinySkiaColorPainter<'a> {
 
 impl ColorPainter for TinySkiaColorPainter<'_> {
     fn push_transform(&mut self, transform: Transform) {
+        // The bottom entry is the initial transform, not a nesting level
+        if !self.admit(self.transform_stack.len(), false) {
+            return;
+        }
         let current = self.current_transform();
         let new_transform = current.pre_concat(Self::convert_transform(transform));
         self.transform_stack.push(new_transform);
     }
 
     fn pop_transform(&mut self) {
-        if self.transform_stack.len() > 1 {
+        if self.error.is_none() && self.transform_stack.len() > 1 {
             self.transform_stack.pop();
         }
     }
 
     fn push_clip_glyph(&mut self, glyph_id: GlyphId) {
+        if !self.admit(self.clip_stack.len(), false) {
+            return;
+        }
         let mask = self.create_glyph_clip_mask(glyph_id);
         if mask.is_none() {
             log::debug!("push_clip_glyph: {:?} - failed to create mask", glyph_id);
@@ -269,6 +368,9 @@ impl ColorPainter for TinySkiaColorPainter<'_> {
     }
 
     fn push_clip_box(&mut self, clip_box: skrifa::raw::types::BoundingBox<f32>) {
+        if !self.admit(self.clip_stack.len(), false) {
+            return;
+        }
         let mask = self.create_box_clip_mask(clip_box);
         if mask.is_none() {
             log::debug!(
@@ -285,12 +387,15 @@ impl ColorPainter for TinySkiaColorPainter<'_> {
     }
 
     fn pop_clip(&mut self) {
-        if self.clip_stack.len() > 1 {
+        if self.error.is_none() && self.clip_stack.len() > 1 {
             self.clip_stack.pop();
         }
     }
 
     fn fill(&mut self, brush: Brush<'_>) {
+        if !self.admit(0, false) {
+            return;
+        }
         let transform = self.current_transform();
 
         let (width, height) = if let Some(layer) = self.layer_stack.last() {
@@ -309,7 +414,7 @@ impl ColorPainter for TinySkiaColorPainter<'_> {
                 paint.set_color(color);
                 paint.anti_alias = true;
 
-                let rect = tiny_skia::Rect::from_xywh(0.0, 0.0, width as f32, height as f32);
+                let rect = Self::user_space_bounds(width, height, transform);
                 if let Some(rect) = rect {
                     let clip_mask = self.clip_stack.iter().rev().find_map(|m| m.as_ref());
                     let target = if let Some(layer) = self.layer_stack.last_mut() {
@@ -336,9 +441,7 @@ impl ColorPainter for TinySkiaColorPainter<'_> {
                     let mut paint = Paint::default();
                     paint.set_color(color);
                     paint.anti_alias = true;
-                    if let Some(rect) =
-                        tiny_skia::Rect::from_xywh(0.0, 0.0, width as f32, height as f32)
-                    {
+                    if let Some(rect) = Self::user_space_bounds(width, height, transform) {
                         let clip_mask = self.clip_stack.iter().rev().find_map(|m| m.as_ref());
                         let target = if let Some(layer) = self.layer_stack.last_mut() {
                             &mut layer.pixmap
@@ -366,9 +469,7 @@ impl ColorPainter for TinySkiaColorPainter<'_> {
                         ..Default::default()
                     };
 
-                    if let Some(rect) =
-                        tiny_skia::Rect::from_xywh(0.0, 0.0, width as f32, height as f32)
-                    {
+                    if let Some(rect) = Self::user_space_bounds(width, height, transform) {
                         let path = PathBuilder::from_rect(rect);
                         let clip_mask = self.clip_stack.iter().rev().find_map(|m| m.as_ref());
                         let target = if let Some(layer) = self.layer_stack.last_mut() {
@@ -404,9 +505,7 @@ impl ColorPainter for TinySkiaColorPainter<'_> {
                     let mut paint = Paint::default();
                     paint.set_color(color);
                     paint.anti_alias = true;
-                    if let Some(rect) =
-                        tiny_skia::Rect::from_xywh(0.0, 0.0, width as f32, height as f32)
-                    {
+                    if let Some(rect) = Self::user_space_bounds(width, height, transform) {
                         let clip_mask = self.clip_stack.iter().rev().find_map(|m| m.as_ref());
                         let target = if let Some(layer) = self.layer_stack.last_mut() {
                             &mut layer.pixmap
@@ -439,9 +538,7 @@ impl ColorPainter for TinySkiaColorPainter<'_> {
                         ..Default::default()
                     };
 
-                    if let Some(rect) =
-                        tiny_skia::Rect::from_xywh(0.0, 0.0, width as f32, height as f32)
-                    {
+                    if let Some(rect) = Self::user_space_bounds(width, height, transform) {
                         let path = PathBuilder::from_rect(rect);
                         let clip_mask = self.clip_stack.iter().rev().find_map(|m| m.as_ref());
                         let target = if let Some(layer) = self.layer_stack.last_mut() {
@@ -498,9 +595,7 @@ impl ColorPainter for TinySkiaColorPainter<'_> {
                 paint.set_color(color);
                 paint.anti_alias = true;
 
-                if let Some(rect) =
-                    tiny_skia::Rect::from_xywh(0.0, 0.0, width as f32, height as f32)
-                {
+                if let Some(rect) = Self::user_space_bounds(width, height, transform) {
                     let clip_mask = self.clip_stack.iter().rev().find_map(|m| m.as_ref());
                     let target = if let Some(layer) = self.layer_stack.last_mut() {
                         &mut layer.pixmap
@@ -514,18 +609,32 @@ impl ColorPainter for TinySkiaColorPainter<'_> {
     }
 
     fn push_layer(&mut self, composite_mode: CompositeMode) {
+        if !self.admit(self.layer_stack.len() + 1, true) {
+            return;
+        }
         let width = self.pixmap.width();
         let height = self.pixmap.height();
 
+        let memory = match budget::reserve(self.budget.as_ref(), pixmap_bytes(width, height)) {
+            Ok(memory) => memory,
+            Err(e) => {
+                self.error = Some(ColorRenderError::BudgetExceeded(e));
+                return;
+            },
+        };
         if let Some(layer_pixmap) = Pixmap::new(width, height) {
             self.layer_stack.push(LayerState {
                 pixmap: layer_pixmap,
                 composite_mode,
+                _memory: memory,
             });
         }
     }
 
     fn pop_layer(&mut self) {
+        if self.error.is_some() {
+            return;
+        }
         if let Some(layer) = self.layer_stack.pop() {
             let blend_mode = Self::convert_composite_mode(layer.composite_mode);
             let paint = PixmapPaint {
@@ -552,6 +661,9 @@ impl ColorPainter for TinySkiaColorPainter<'_> {
     }
 
     fn pop_layer_with_mode(&mut self, composite_mode: CompositeMode) {
+        if self.error.is_some() {
+            return;
+        }
         if let Some(mut layer) = self.layer_stack.pop() {
             layer.composite_mode = composite_mode;
             self.layer_stack.push(layer);
@@ -579,6 +691,17 @@ pub enum ColorRenderError {
     PixmapCreationFailed,
     /// No color palette available
     NoPalette,
+    /// A pixmap would have passed the call's memory budget
+    BudgetExceeded(RenderError),
+    /// The paint graph refers back to a paint it is already inside
+    PaintCycle,
+    /// The paint graph passed one of the [`limits`]
+    LimitExceeded {
+        /// What was counted: `"nesting"`, `"layer"`, or `"paint operation"`
+        what: &'static str,
+        /// The limit that was passed
+        limit: usize,
+    },
     /// Bitmap rendering error
     #[cfg(feature = "bitmap")]
     BitmapError(bitmap::BitmapRenderError),
@@ -593,6 +716,11 @@ impl std::fmt::Display for ColorRenderError {
             Self::PaintError(e) => write!(f, "paint error: {:?}", e),
             Self::PixmapCreationFailed => write!(f, "failed to create pixmap"),
             Self::NoPalette => write!(f, "no color palette available"),
+            Self::BudgetExceeded(e) => write!(f, "{e}"),
+            Self::PaintCycle => write!(f, "COLR paint graph contains a cycle"),
+            Self::LimitExceeded { what, limit } => {
+                write!(f, "COLR paint graph passed the {what} limit of {limit}")
+            },
             #[cfg(feature = "bitmap")]
             Self::BitmapError(e) => write!(f, "bitmap error: {:?}", e),
         }
@@ -601,9 +729,46 @@ impl std::fmt::Display for ColorRenderError {
 
 impl std::error::Error for ColorRenderError {}
 
+impl Classify for ColorRenderError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::FontParseFailed | Self::PaintCycle => ErrorKind::InvalidFont,
+            Self::NoColrTable | Self::GlyphNotFound | Self::NoPalette => ErrorKind::NotFound,
+            Self::PaintError(_) | Self::PixmapCreationFailed => ErrorKind::Backend,
+            Self::BudgetExceeded(e) => e.kind(),
+            Self::LimitExceeded { .. } => ErrorKind::LimitExceeded,
+            #[cfg(feature = "bitmap")]
+            Self::BitmapError(e) => e.kind(),
+        }
+    }
+}
+
+impl From<ColorRenderError> for RenderError {
+    fn from(e: ColorRenderError) -> Self {
+        match e {
+            ColorRenderError::BudgetExceeded(e) => e,
+            e => RenderError::classified(&e),
+        }
+    }
+}
+
+impl From<ColorRenderError> for TypfError {
+    fn from(e: ColorRenderError) -> Self {
+        TypfError::RenderingFailed(e.into())
+    }
+}
+
 impl From<PaintError> for ColorRenderError {
     fn from(e: PaintError) -> Self {
-        Self::PaintError(e)
+        // skrifa's graph checks get the same errors as the painter's own
+        match e {
+            PaintError::PaintCycleDetected => Self::PaintCycle,
+            PaintError::DepthLimitExceeded => Self::LimitExceeded {
+                what: "nesting",
+                limit: MAX_COLR_NESTING,
+            },
+            e => Self::PaintError(e),
+        }
     }
 }
 
@@ -643,67 +808,219 @@ pub fn render_color_glyph_with_variations(
     palette_index: u16,
     variations: &[(&str, f32)],
 ) -> Result<Pixmap, ColorRenderError> {
-    let font = skrifa::FontRef::new(font_data).map_err(|_| ColorRenderError::FontParseFailed)?;
-    let glyph_id = GlyphId::new(glyph_id);
-
-    let color_glyph = font
-        .color_glyphs()
-        .get(glyph_id)
-        .ok_or(ColorRenderError::GlyphNotFound)?;
+    render_color_glyph_with_options(
+        font_data,
+        glyph_id,
+        width,
+        height,
+        size,
+        palette_index,
+        variations,
+        &ColrRasterOptions::default(),
+    )
+}
 
-    let palettes = ColorPalettes::new(&font);
-    let palette = palettes
-        .get(palette_index)
-        .ok_or(ColorRenderError::NoPalette)?;
-    let colors = palette.colors();
+/// Default longest side, in pixels, of the working pixmap used to paint COLR glyphs.
+///
+/// Each COLR layer allocates a pixmap as large as the working pixmap, so a
+/// 2048 px emoji with a dozen layers painted at full size can reach hundreds
+/// of megabytes. At this cap it paints at 1024 px and is scaled up.
+pub const DEFAULT_MAX_COLR_DIMENSION: u32 = 1024;
 
-    let location = font.axes().location(variations.iter().copied());
+/// Resolution limits for painting COLR glyphs.
+///
+/// Painting happens in a working pixmap whose longest side never exceeds
+/// `max_dimension`. Glyphs larger than that are painted at the capped
+/// resolution and scaled up to the requested size, trading sharpness for a
+/// bounded memory footprint. Glyphs that fit can instead be painted at
+/// `supersample` times the requested resolution and box-filtered down, which
+/// smooths gradients and clip edges at small sizes. The supersample factor is
+/// lowered automatically when it would push the working pixmap past the cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColrRasterOptions {
+    /// Longest side of the working pixmap, in pixels.
+    pub max_dimension: u32,
+    /// Paint at this multiple of the target resolution, then downscale (1 = off).
+    pub supersample: u32,
+}
 
-    let upem = font.head().map(|h| h.units_per_em()).unwrap_or(1000) as f32;
-    let scale = size / upem;
+impl Default for ColrRasterOptions {
+    fn default() -> Self {
+        Self {
+            max_dimension: DEFAULT_MAX_COLR_DIMENSION,
+            supersample: 1,
+        }
+    }
+}
 
-    let location_ref = skrifa::instance::LocationRef::new(&[]);
-    let colr_bbox = color_glyph.bounding_box(location_ref, skrifa::instance::Size::unscaled());
+impl ColrRasterOptions {
+    /// Scale factor from target pixels to working pixels for a `width` x `height` glyph.
+    fn working_scale(&self, width: u32, height: u32) -> f32 {
+        let longest = width.max(height).max(1);
+        let max_dimension = self.max_dimension.max(1);
+        if longest > max_dimension {
+            return max_dimension as f32 / longest as f32;
+        }
+        let supersample = self.supersample.clamp(1, max_dimension / longest);
+        supersample as f32
+    }
 
-    let (pix_width, pix_height, translate_x, translate_y) = if let Some(bbox) = colr_bbox {
-        let scaled_x0 = bbox.x_min * scale;
-        let scaled_y0 = bbox.y_min * scale;
-        let scaled_x1 = bbox.x_max * scale;
-        let scaled_y1 = bbox.y_max * scale;
+    /// Size of the working pixmap for a `width` x `height` glyph, painted at `k`
+    /// times the target resolution; never longer than `max_dimension`.
+    fn working_size(&self, width: u32, height: u32, k: f32) -> (u32, u32) {
+        let side = |len: u32| ((len as f32 * k).ceil() as u32).clamp(1, self.max_dimension.max(1));
+        (side(width), side(height))
+    }
+}
 
-        let w = ((scaled_x1 - scaled_x0).ceil() as u32).max(1);
-        let h = ((scaled_y1 - scaled_y0).ceil() as u32).max(1);
+/// Render one COLR glyph with variable-font coordinates and resolution limits.
+///
+/// Behaves like [`render_color_glyph_with_variations`], but paints through
+/// [`ColrRasterOptions`] so very large sizes stay within a memory budget and
+/// small sizes can be supersampled. The returned pixmap always has the same
+/// dimensions as the uncapped render would.
+#[allow(clippy::too_many_arguments)]
+pub fn render_color_glyph_with_options(
+    font_data: &[u8],
+    glyph_id: u32,
+    width: u32,
+    height: u32,
+    size: f32,
+    palette_index: u16,
+    variations: &[(&str, f32)],
+    options: &ColrRasterOptions,
+) -> Result<Pixmap, ColorRenderError> {
+    ColorFont::new(font_data)?.render_colr(
+        glyph_id,
+        width,
+        height,
+        size,
+        palette_index,
+        variations,
+        options,
+    )
+}
 
-        let tx = -scaled_x0;
-        let ty = scaled_y1;
+/// Paint a COLR glyph into a `width` x `height` pixmap, honouring `options`.
+///
+/// The glyph is painted at the working resolution chosen by
+/// [`ColrRasterOptions`] and resampled to the requested dimensions when that
+/// differs from 1:1.
+#[allow(clippy::too_many_arguments)]
+fn paint_colr_glyph(
+    color_glyph: &ColorGlyph<'_>,
+    location: &Location,
+    colors: &[skrifa::color::Color],
+    font: &skrifa::FontRef<'_>,
+    size: f32,
+    width: u32,
+    height: u32,
+    transform: tiny_skia::Transform,
+    options: &ColrRasterOptions,
+    budget: Option<&MemoryBudget>,
+) -> Result<Pixmap, ColorRenderError> {
+    let paint = |pixmap: &mut Pixmap, transform| -> Result<(), ColorRenderError> {
+        let mut painter =
+            TinySkiaColorPainter::with_transform(pixmap, colors, font, size, transform)
+                .with_location(location)
+                .with_memory_budget(budget.cloned());
+        color_glyph.paint(location, &mut painter)?;
+        painter.take_error().map_or(Ok(()), Err)
+    };
 
-        (w, h, tx, ty)
+    let k = options.working_scale(width, height);
+    // Held until the glyph is handed back; the caller accounts for the result
+    let mut memory = reserve_pixmap(budget, width, height)?;
+    if k == 1.0 {
+        let mut pixmap =
+            Pixmap::new(width, height).ok_or(ColorRenderError::PixmapCreationFailed)?;
+        paint(&mut pixmap, transform)?;
+        return Ok(pixmap);
+    }
+
+    let (work_width, work_height) = options.working_size(width, height, k);
+    memory
+        .grow(pixmap_bytes(work_width, work_height))
+        .map_err(ColorRenderError::BudgetExceeded)?;
+    let mut working =
+        Pixmap::new(work_width, work_height).ok_or(ColorRenderError::PixmapCreationFailed)?;
+    paint(&mut working, transform.post_scale(k, k))?;
+
+    if k > 1.0 {
+        downsample_box(&working, k as u32, width, height)
     } else {
-        (width, height, 0.0, size)
-    };
+        let mut pixmap =
+            Pixmap::new(width, height).ok_or(ColorRenderError::PixmapCreationFailed)?;
+        let paint = PixmapPaint {
+            quality: tiny_skia::FilterQuality::Bilinear,
+            ..PixmapPaint::default()
+        };
+        pixmap.draw_pixmap(
+            0,
+            0,
+            working.as_ref(),
+            &paint,
+            tiny_skia::Transform::from_scale(1.0 / k, 1.0 / k),
+            None,
+        );
+        Ok(pixmap)
+    }
+}
 
-    let mut pixmap =
-        Pixmap::new(pix_width, pix_height).ok_or(ColorRenderError::PixmapCreationFailed)?;
+/// Bytes of an RGBA pixmap
+fn pixmap_bytes(width: u32, height: u32) -> usize {
+    width as usize * height as usize * 4
+}
 
-    let transform =
-        tiny_skia::Transform::from_scale(scale, -scale).post_translate(translate_x, translate_y);
+/// Reserve a `width` x `height` pixmap against an optional budget
+fn reserve_pixmap(
+    budget: Option<&MemoryBudget>,
+    width: u32,
+    height: u32,
+) -> Result<Reservation, ColorRenderError> {
+    budget::reserve(budget, pixmap_bytes(width, height)).map_err(ColorRenderError::BudgetExceeded)
+}
 
-    {
-        let mut painter =
-            TinySkiaColorPainter::with_transform(&mut pixmap, colors, &font, size, transform);
-        color_glyph.paint(&location, &mut painter)?;
+/// Average `factor` x `factor` blocks of premultiplied pixels into a `width` x `height` pixmap.
+fn downsample_box(
+    src: &Pixmap,
+    factor: u32,
+    width: u32,
+    height: u32,
+) -> Result<Pixmap, ColorRenderError> {
+    let mut dst = Pixmap::new(width, height).ok_or(ColorRenderError::PixmapCreationFailed)?;
+    let src_width = src.width() as usize;
+    let src_height = src.height() as usize;
+    let src_data = src.data();
+    let factor = factor.max(1) as usize;
+
+    for (i, px) in dst.data_mut().chunks_exact_mut(4).enumerate() {
+        let x0 = (i % width as usize) * factor;
+        let y0 = (i / width as usize) * factor;
+        let mut sum = [0u32; 4];
+        let mut count = 0u32;
+        for sy in y0..(y0 + factor).min(src_height) {
+            for sx in x0..(x0 + factor).min(src_width) {
+                let idx = (sy * src_width + sx) * 4;
+                for c in 0..4 {
+                    sum[c] += src_data[idx + c] as u32;
+                }
+                count += 1;
+            }
+        }
+        for (out, total) in px.iter_mut().zip(sum) {
+            if let Some(avg) = (total + count / 2).checked_div(count) {
+                *out = avg as u8;
+            }
+        }
     }
 
-    Ok(pixmap)
+    Ok(dst)
 }
 
 /// Return true when the font exposes a COLR table.
 pub fn has_color_glyphs(font_data: &[u8]) -> bool {
-    if let Ok(font) = skrifa::FontRef::new(font_data) {
-        font.colr().is_ok()
-    } else {
-        false
-    }
+    ColorFont::new(font_data).is_ok_and(|font| font.has_colr())
 }
 
 /// Color-glyph formats supported by a font.
@@ -724,60 +1041,9 @@ pub enum ColorFontType {
 /// The returned order is the preferred render order: COLR v1, COLR v0, SVG,
 /// then bitmap data.
 pub fn detect_color_font_types(font_data: &[u8]) -> Vec<ColorFontType> {
-    let mut types = Vec::new();
-
-    let font = match skrifa::FontRef::new(font_data) {
-        Ok(f) => f,
-        Err(_) => return types,
-    };
-
-    let color_glyphs = font.color_glyphs();
-    let num_glyphs = font.maxp().map(|m| m.num_glyphs()).unwrap_or(0);
-
-    let mut has_colr_v1 = false;
-    let mut has_colr_v0 = false;
-
-    for gid in 0..num_glyphs.min(1000) {
-        let glyph_id = GlyphId::new(gid as u32);
-        if color_glyphs
-            .get_with_format(glyph_id, ColorGlyphFormat::ColrV1)
-            .is_some()
-        {
-            has_colr_v1 = true;
-            break;
-        }
-        if color_glyphs
-            .get_with_format(glyph_id, ColorGlyphFormat::ColrV0)
-            .is_some()
-        {
-            has_colr_v0 = true;
-        }
-    }
-
-    if has_colr_v1 {
-        types.push(ColorFontType::ColrV1);
-    }
-    if has_colr_v0 {
-        types.push(ColorFontType::ColrV0);
-    }
-
-    #[cfg(feature = "svg")]
-    {
-        if font.svg().is_ok() {
-            types.push(ColorFontType::Svg);
-        }
-    }
-
-    #[cfg(feature = "bitmap")]
-    {
-        use skrifa::bitmap::BitmapStrikes;
-        let strikes = BitmapStrikes::new(&font);
-        if !strikes.is_empty() {
-            types.push(ColorFontType::Bitmap);
-        }
-    }
-
-    types
+    ColorFont::new(font_data)
+        .map(|font| font.color_types().to_vec())
+        .unwrap_or_default()
 }
 
 /// Return true when any color-glyph format is available.
@@ -792,26 +1058,7 @@ pub fn get_best_color_type(font_data: &[u8]) -> Option<ColorFontType> {
 
 /// Return the COLR format used by a specific glyph, if any.
 pub fn get_color_glyph_format(font_data: &[u8], glyph_id: u32) -> Option<ColorGlyphFormat> {
-    let font = skrifa::FontRef::new(font_data).ok()?;
-    let glyph_id = GlyphId::new(glyph_id);
-
-    if font
-        .color_glyphs()
-        .get_with_format(glyph_id, ColorGlyphFormat::ColrV1)
-        .is_some()
-    {
-        return Some(ColorGlyphFormat::ColrV1);
-    }
-
-    if font
-        .color_glyphs()
-        .get_with_format(glyph_id, ColorGlyphFormat::ColrV0)
-        .is_some()
-    {
-        return Some(ColorGlyphFormat::ColrV0);
-    }
-
-    None
+    ColorFont::new(font_data).ok()?.glyph_format(glyph_id)
 }
 
 /// Output from glyph rendering plus method metadata.
@@ -932,106 +1179,14 @@ pub fn render_glyph_with_variations(
     palette_index: u16,
     variations: &[(&str, f32)],
 ) -> Result<RenderResult, ColorRenderError> {
-    let font = skrifa::FontRef::new(font_data).map_err(|_| ColorRenderError::FontParseFailed)?;
-    let gid = GlyphId::new(glyph_id);
-
-    if font
-        .color_glyphs()
-        .get_with_format(gid, ColorGlyphFormat::ColrV1)
-        .is_some()
-    {
-        let pixmap = render_color_glyph_with_variations(
-            font_data,
-            glyph_id,
-            width,
-            height,
-            size,
-            palette_index,
-            variations,
-        )?;
-        return Ok(RenderResult {
-            pixmap,
-            method: RenderMethod::ColrV1,
-            bearing_x: None,
-            bearing_y: None,
-        });
-    }
-
-    if font
-        .color_glyphs()
-        .get_with_format(gid, ColorGlyphFormat::ColrV0)
-        .is_some()
-    {
-        let pixmap = render_color_glyph_with_variations(
-            font_data,
-            glyph_id,
-            width,
-            height,
-            size,
-            palette_index,
-            variations,
-        )?;
-        return Ok(RenderResult {
-            pixmap,
-            method: RenderMethod::ColrV0,
-            bearing_x: None,
-            bearing_y: None,
-        });
-    }
-
-    #[cfg(feature = "svg")]
-    {
-        let palettes = ColorPalettes::new(&font);
-        let palette_colors: Vec<skrifa::color::Color> = palettes
-            .get(palette_index)
-            .map(|p| p.colors().to_vec())
-            .unwrap_or_default();
-
-        if let Ok(pixmap) = svg::render_svg_glyph_with_palette_and_ppem(
-            font_data,
-            glyph_id,
-            width,
-            height,
-            &palette_colors,
-            size,
-        ) {
-            return Ok(RenderResult {
-                pixmap,
-                method: RenderMethod::Svg,
-                bearing_x: None,
-                bearing_y: None,
-            });
-        }
-    }
-
-    #[cfg(feature = "bitmap")]
-    {
-        match bitmap::render_bitmap_glyph_scaled(font_data, glyph_id, size) {
-            Ok(scaled) => Ok(RenderResult {
-                pixmap: scaled.pixmap,
-                method: RenderMethod::Bitmap,
-                bearing_x: Some(scaled.bearing_x),
-                bearing_y: Some(scaled.bearing_y),
-            }),
-            Err(bitmap::BitmapRenderError::NoBitmapTable)
-            | Err(bitmap::BitmapRenderError::GlyphNotFound)
-            | Err(bitmap::BitmapRenderError::UnsupportedFormat) => {
-                let (pixmap, _used_bitmap) = bitmap::render_bitmap_glyph_or_outline(
-                    font_data, glyph_id, width, height, size,
-                )?;
-                Ok(RenderResult {
-                    pixmap,
-                    method: RenderMethod::Outline,
-                    bearing_x: None,
-                    bearing_y: None,
-                })
-            },
-            Err(e) => Err(e.into()),
-        }
-    }
-
-    #[cfg(not(feature = "bitmap"))]
-    Err(ColorRenderError::GlyphNotFound)
+    ColorFont::new(font_data)?.render_glyph(
+        glyph_id,
+        width,
+        height,
+        size,
+        palette_index,
+        variations,
+    )
 }
 
 /// Render a specific glyph source in the order provided by GlyphSourcePreference.
@@ -1050,133 +1205,21 @@ pub fn render_glyph_with_preference(
     variations: &[(&str, f32)],
     preference: &typf_core::GlyphSourcePreference,
 ) -> Result<(RenderResult, typf_core::GlyphSource), ColorRenderError> {
-    use typf_core::GlyphSource;
-
-    let gid = GlyphId::new(glyph_id);
-    let font = skrifa::FontRef::new(font_data).map_err(|_| ColorRenderError::FontParseFailed)?;
-    let orders = preference.effective_order();
-
-    for source in orders {
-        match source {
-            GlyphSource::Colr1 => {
-                if let Some(color_glyph) = font
-                    .color_glyphs()
-                    .get_with_format(gid, ColorGlyphFormat::ColrV1)
-                {
-                    let palettes = ColorPalettes::new(&font);
-                    let palette = palettes
-                        .get(palette_index)
-                        .ok_or(ColorRenderError::NoPalette)?;
-                    let colors = palette.colors();
-                    let location = font.axes().location(variations.iter().copied());
-
-                    let mut pixmap =
-                        Pixmap::new(width, height).ok_or(ColorRenderError::PixmapCreationFailed)?;
-                    let mut painter = TinySkiaColorPainter::new(&mut pixmap, colors, &font, size);
-                    color_glyph.paint(&location, &mut painter)?;
-
-                    return Ok((
-                        RenderResult {
-                            pixmap,
-                            method: RenderMethod::ColrV1,
-                            bearing_x: None,
-                            bearing_y: None,
-                        },
-                        GlyphSource::Colr1,
-                    ));
-                }
-            },
-            GlyphSource::Colr0 => {
-                if let Some(color_glyph) = font
-                    .color_glyphs()
-                    .get_with_format(gid, ColorGlyphFormat::ColrV0)
-                {
-                    let palettes = ColorPalettes::new(&font);
-                    let palette = palettes
-                        .get(palette_index)
-                        .ok_or(ColorRenderError::NoPalette)?;
-                    let colors = palette.colors();
-                    let location = font.axes().location(variations.iter().copied());
-
-                    let mut pixmap =
-                        Pixmap::new(width, height).ok_or(ColorRenderError::PixmapCreationFailed)?;
-                    let mut painter = TinySkiaColorPainter::new(&mut pixmap, colors, &font, size);
-                    color_glyph.paint(&location, &mut painter)?;
-
-                    return Ok((
-                        RenderResult {
-                            pixmap,
-                            method: RenderMethod::ColrV0,
-                            bearing_x: None,
-                            bearing_y: None,
-                        },
-                        GlyphSource::Colr0,
-                    ));
-                }
-            },
-            GlyphSource::Svg => {
-                #[cfg(feature = "svg")]
-                {
-                    let palettes = ColorPalettes::new(&font);
-                    let palette_colors: Vec<skrifa::color::Color> = palettes
-                        .get(palette_index)
-                        .map(|p| p.colors().to_vec())
-                        .unwrap_or_default();
-
-                    if let Ok(pixmap) = svg::render_svg_glyph_with_palette_and_ppem(
-                        font_data,
-                        glyph_id,
-                        width,
-                        height,
-                        &palette_colors,
-                        size,
-                    ) {
-                        return Ok((
-                            RenderResult {
-                                pixmap,
-                                method: RenderMethod::Svg,
-                                bearing_x: Some(0.0),
-                                bearing_y: Some(size),
-                            },
-                            GlyphSource::Svg,
-                        ));
-                    }
-                }
-            },
-            GlyphSource::Sbix | GlyphSource::Cbdt | GlyphSource::Ebdt => {
-                #[cfg(feature = "bitmap")]
-                {
-                    match bitmap::render_bitmap_glyph_scaled(font_data, glyph_id, size) {
-                        Ok(scaled) => {
-                            return Ok((
-                                RenderResult {
-                                    pixmap: scaled.pixmap,
-                                    method: RenderMethod::Bitmap,
-                                    bearing_x: Some(scaled.bearing_x),
-                                    bearing_y: Some(scaled.bearing_y),
-                                },
-                                source,
-                            ));
-                        },
-                        Err(bitmap::BitmapRenderError::NoBitmapTable)
-                        | Err(bitmap::BitmapRenderError::GlyphNotFound)
-                        | Err(bitmap::BitmapRenderError::UnsupportedFormat) => {},
-                        Err(e) => return Err(e.into()),
-                    }
-                }
-            },
-            GlyphSource::Glyf | GlyphSource::Cff | GlyphSource::Cff2 => {
-                continue;
-            },
-        }
-    }
-
-    Err(ColorRenderError::GlyphNotFound)
+    ColorFont::new(font_data)?.render_glyph_with_preference(
+        glyph_id,
+        width,
+        height,
+        size,
+        palette_index,
+        variations,
+        preference,
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use skrifa::raw::TableProvider;
 
     #[test]
     fn test_composite_mode_conversion() {
@@ -1190,6 +1233,93 @@ mod tests {
         );
     }
 
+    /// Layers past the budget are refused, and popped layers give their bytes back
+    #[test]
+    fn test_push_layer_respects_memory_budget() {
+        let font_path = "../../test-fonts/NotoSans-Regular.ttf";
+        let Ok(font_data) = std::fs::read(font_path) else {
+            eprintln!("Skipping test: font not found at {}", font_path);
+            return;
+        };
+        let font = skrifa::FontRef::new(&font_data).expect("Failed to parse font");
+        let mut pixmap = Pixmap::new(10, 10).expect("pixmap");
+
+        let budget = MemoryBudget::new(500);
+        let mut painter = TinySkiaColorPainter::new(&mut pixmap, &[], &font, 10.0)
+            .with_memory_budget(Some(budget.clone()));
+        painter.push_layer(CompositeMode::SrcOver);
+        assert_eq!(budget.in_use(), 400);
+        assert!(painter.take_error().is_none());
+
+        painter.push_layer(CompositeMode::SrcOver);
+        assert!(matches!(
+            painter.take_error(),
+            Some(ColorRenderError::BudgetExceeded(
+                RenderError::BudgetExceeded {
+                    requested: 400,
+                    in_use: 400,
+                    limit: 500
+                }
+            ))
+        ));
+
+        painter.pop_layer();
+        assert_eq!(budget.in_use(), 0);
+    }
+
+    /// Paint graphs past the nesting or step limits stop painting
+    #[test]
+    fn test_painter_when_paint_graph_too_large_then_limit_exceeded() {
+        let font_path = "../../test-fonts/NotoSans-Regular.ttf";
+        let Ok(font_data) = std::fs::read(font_path) else {
+            eprintln!("Skipping test: font not found at {}", font_path);
+            return;
+        };
+        let font = skrifa::FontRef::new(&font_data).expect("Failed to parse font");
+        let mut pixmap = Pixmap::new(4, 4).expect("pixmap");
+
+        let mut painter = TinySkiaColorPainter::new(&mut pixmap, &[], &font, 4.0);
+        for _ in 0..=MAX_COLR_NESTING {
+            painter.push_layer(CompositeMode::SrcOver);
+        }
+        assert_eq!(painter.layer_stack.len(), MAX_COLR_NESTING);
+        assert!(matches!(
+            painter.take_error(),
+            Some(ColorRenderError::LimitExceeded {
+                what: "nesting",
+                limit: MAX_COLR_NESTING
+            })
+        ));
+
+        let mut painter = TinySkiaColorPainter::new(&mut pixmap, &[], &font, 4.0);
+        for _ in 0..=MAX_COLR_PAINT_OPS {
+            painter.push_transform(Transform::default());
+            painter.pop_transform();
+        }
+        assert!(matches!(
+            painter.take_error(),
+            Some(ColorRenderError::LimitExceeded {
+                what: "paint operation",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_paint_error_when_graph_check_fails_then_clean_error() {
+        assert!(matches!(
+            ColorRenderError::from(PaintError::PaintCycleDetected),
+            ColorRenderError::PaintCycle
+        ));
+        assert!(matches!(
+            ColorRenderError::from(PaintError::DepthLimitExceeded),
+            ColorRenderError::LimitExceeded {
+                what: "nesting",
+                ..
+            }
+        ));
+    }
+
     /// Test COLR glyph detection with NotoColorEmojiCOLR font
     #[test]
     fn test_has_color_glyphs_noto_colr() {
@@ -1626,4 +1756,146 @@ mod tests {
             eprintln!("Skipping test: font not found at {}", font_path);
         }
     }
+
+    #[test]
+    fn test_colr_working_scale_when_within_cap_then_supersamples() {
+        let options = ColrRasterOptions {
+            max_dimension: 1024,
+            supersample: 4,
+        };
+        assert_eq!(options.working_scale(100, 50), 4.0);
+        // 300 * 4 would exceed the cap, so supersampling drops to 3x.
+        assert_eq!(options.working_scale(300, 10), 3.0);
+        assert_eq!(ColrRasterOptions::default().working_scale(300, 10), 1.0);
+    }
+
+    #[test]
+    fn test_colr_working_scale_when_over_cap_then_shrinks() {
+        let options = ColrRasterOptions {
+            max_dimension: 1000,
+            supersample: 4,
+        };
+        assert_eq!(options.working_scale(2000, 500), 0.5);
+    }
+
+    #[test]
+    fn test_downsample_box_averages_blocks() {
+        let mut src = Pixmap::new(2, 2).unwrap();
+        // One opaque white pixel out of four.
+        src.data_mut()[..4].copy_from_slice(&[255, 255, 255, 255]);
+        let dst = downsample_box(&src, 2, 1, 1).unwrap();
+        assert_eq!(dst.data(), &[64, 64, 64, 64]);
+    }
+
+    #[test]
+    fn test_render_color_glyph_with_options_caps_working_resolution() {
+        let font_path = "../../test-fonts/Nabla-Regular-COLR.ttf";
+        let Ok(font_data) = std::fs::read(font_path) else {
+            eprintln!("Skipping test: font not found at {}", font_path);
+            return;
+        };
+        let font = skrifa::FontRef::new(&font_data).expect("Failed to parse font");
+        let num_glyphs = font.maxp().expect("no maxp").num_glyphs() as u32;
+        let Some(gid) =
+            (0..num_glyphs).find(|&gid| font.color_glyphs().get(GlyphId::new(gid)).is_some())
+        else {
+            eprintln!("No color glyphs found");
+            return;
+        };
+
+        let uncapped = render_color_glyph(&font_data, gid, 0, 0, 2048.0, 0).unwrap();
+        let options = ColrRasterOptions {
+            max_dimension: 256,
+            supersample: 1,
+        };
+        let capped =
+            render_color_glyph_with_options(&font_data, gid, 0, 0, 2048.0, 0, &[], &options)
+                .unwrap();
+
+        assert_eq!(capped.width(), uncapped.width());
+        assert_eq!(capped.height(), uncapped.height());
+        assert!(capped.pixels().iter().any(|p| p.alpha() > 0));
+    }
+
+    #[test]
+    fn test_render_color_glyph_when_2048px_then_default_cap_clamps_working_pixmap() {
+        let font_path = "../../test-fonts/Nabla-Regular-COLR.ttf";
+        let Ok(font_data) = std::fs::read(font_path) else {
+            eprintln!("Skipping test: font not found at {}", font_path);
+            return;
+        };
+        let font = skrifa::FontRef::new(&font_data).expect("Failed to parse font");
+        let num_glyphs = font.maxp().expect("no maxp").num_glyphs() as u32;
+        // A glyph whose ink at 2048 px is longer than the default cap
+        let location = Location::default();
+        let Some(gid) = (0..num_glyphs).find(|&gid| {
+            let glyph = font.color_glyphs().get(GlyphId::new(gid));
+            let bbox =
+                glyph.and_then(|g| g.bounding_box(&location, skrifa::instance::Size::new(2048.0)));
+            bbox.is_some_and(|b| {
+                (b.x_max - b.x_min).max(b.y_max - b.y_min) > DEFAULT_MAX_COLR_DIMENSION as f32
+            })
+        }) else {
+            eprintln!("No large color glyphs found");
+            return;
+        };
+
+        let uncapped = ColrRasterOptions {
+            max_dimension: u32::MAX,
+            supersample: 1,
+        };
+        let full =
+            render_color_glyph_with_options(&font_data, gid, 0, 0, 2048.0, 0, &[], &uncapped)
+                .unwrap();
+        let (width, height) = (full.width(), full.height());
+        assert!(width.max(height) > DEFAULT_MAX_COLR_DIMENSION);
+
+        let options = ColrRasterOptions::default();
+        let k = options.working_scale(width, height);
+        assert!(k < 1.0);
+        let (work_width, work_height) = options.working_size(width, height, k);
+        assert!(work_width.max(work_height) <= DEFAULT_MAX_COLR_DIMENSION);
+
+        let pixmap =
+            render_color_glyph_with_options(&font_data, gid, 0, 0, 2048.0, 0, &[], &options)
+                .unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (width, height));
+        assert!(pixmap.pixels().iter().any(|p| p.alpha() > 0));
+    }
+
+    #[test]
+    fn test_clip_glyph_when_located_then_follows_variable_outline() {
+        let font_path = "../../test-fonts/Nabla-Regular-COLR.ttf";
+        let Ok(font_data) = std::fs::read(font_path) else {
+            eprintln!("Skipping test: font not found at {}", font_path);
+            return;
+        };
+        let font = skrifa::FontRef::new(&font_data).expect("Failed to parse font");
+        if font.axes().get_by_tag(skrifa::Tag::new(b"EDPT")).is_none() {
+            eprintln!("Skipping test: {} has no EDPT axis", font_path);
+            return;
+        }
+        let location = font.axes().location([("EDPT", 0.0)]);
+        let transform = tiny_skia::Transform::from_scale(0.1, -0.1).post_translate(20.0, 100.0);
+        let mut pixmap = Pixmap::new(128, 128).expect("pixmap");
+        let mut varied = Pixmap::new(128, 128).expect("pixmap");
+        let default =
+            TinySkiaColorPainter::with_transform(&mut pixmap, &[], &font, 1000.0, transform);
+        let located =
+            TinySkiaColorPainter::with_transform(&mut varied, &[], &font, 1000.0, transform)
+                .with_location(&location);
+
+        let num_glyphs = font.maxp().expect("no maxp").num_glyphs() as u32;
+        let moved = (0..num_glyphs).any(|gid| {
+            let gid = GlyphId::new(gid);
+            match (
+                default.create_glyph_clip_mask(gid),
+                located.create_glyph_clip_mask(gid),
+            ) {
+                (Some(a), Some(b)) => a.data() != b.data(),
+                _ => false,
+            }
+        });
+        assert!(moved, "no clip outline changed at EDPT=0");
+    }
 }
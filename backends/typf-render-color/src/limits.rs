@@ -0,0 +1,83 @@
+//! Bounds on COLR paint graphs.
+//!
+//! skrifa walks the paint graph and already refuses cycles and recursion past
+//! 64 `PaintColrGlyph`/`PaintColrLayers` levels; both surface as
+//! [`ColorRenderError::PaintCycle`] and [`ColorRenderError::LimitExceeded`].
+//! A graph can pass those checks and still be ruinous to paint: layers shared
+//! by reference repeat exponentially, and every layer a painter pushes is a
+//! glyph-sized pixmap or an SVG group. [`PaintGuard`] counts what a painter is
+//! asked to do and trips at the first limit, so the painter stops allocating
+//! and reports the limit instead.
+
+use crate::ColorRenderError;
+
+/// Deepest a COLR paint graph may nest transforms, clips, or layers
+pub const MAX_COLR_NESTING: usize = 64;
+
+/// Most layers a single COLR glyph may push, nested or not
+pub const MAX_COLR_LAYERS: usize = 1024;
+
+/// Most paint callbacks a single COLR glyph may make
+pub const MAX_COLR_PAINT_OPS: usize = 1 << 16;
+
+/// Counts one glyph's paint callbacks against the `MAX_COLR_*` limits
+#[derive(Debug, Default)]
+pub struct PaintGuard {
+    ops: usize,
+    layers: usize,
+}
+
+impl PaintGuard {
+    /// A guard for one glyph, nothing counted yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a callback that nests `depth` deep (0 for fills), and a layer
+    /// if `layer`; fails once any limit is passed
+    pub fn admit(&mut self, depth: usize, layer: bool) -> Result<(), ColorRenderError> {
+        self.ops += 1;
+        self.layers += usize::from(layer);
+        let (what, limit) = if self.ops > MAX_COLR_PAINT_OPS {
+            ("paint operation", MAX_COLR_PAINT_OPS)
+        } else if self.layers > MAX_COLR_LAYERS {
+            ("layer", MAX_COLR_LAYERS)
+        } else if depth > MAX_COLR_NESTING {
+            ("nesting", MAX_COLR_NESTING)
+        } else {
+            return Ok(());
+        };
+        Err(ColorRenderError::LimitExceeded { what, limit })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admit_when_layers_reused_past_limit_then_layer_limit() {
+        let mut guard = PaintGuard::new();
+        for _ in 0..MAX_COLR_LAYERS {
+            // Pushed and popped at depth 1: shallow, but each is a pixmap
+            assert!(guard.admit(1, true).is_ok());
+        }
+        assert!(matches!(
+            guard.admit(1, true),
+            Err(ColorRenderError::LimitExceeded { what: "layer", .. })
+        ));
+    }
+
+    #[test]
+    fn test_admit_when_too_deep_then_nesting_limit() {
+        let mut guard = PaintGuard::new();
+        assert!(guard.admit(MAX_COLR_NESTING, false).is_ok());
+        assert!(matches!(
+            guard.admit(MAX_COLR_NESTING + 1, false),
+            Err(ColorRenderError::LimitExceeded {
+                what: "nesting",
+                limit: MAX_COLR_NESTING
+            })
+        ));
+    }
+}
@@ -10,6 +10,7 @@ use skrifa::raw::TableProvider;
 use skrifa::GlyphId;
 use std::io::Read;
 use tiny_skia::Pixmap;
+use typf_core::error::{Classify, ErrorKind, RenderError, TypfError};
 
 /// Error type for SVG glyph rendering
 #[derive(Debug)]
@@ -46,6 +47,30 @@ impl std::fmt::Display for SvgRenderError {
 
 impl std::error::Error for SvgRenderError {}
 
+impl Classify for SvgRenderError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::FontParseFailed | Self::DecompressionFailed | Self::ParseFailed(_) => {
+                ErrorKind::InvalidFont
+            },
+            Self::NoSvgTable | Self::GlyphNotFound => ErrorKind::NotFound,
+            Self::RenderFailed | Self::PixmapCreationFailed => ErrorKind::Backend,
+        }
+    }
+}
+
+impl From<SvgRenderError> for RenderError {
+    fn from(e: SvgRenderError) -> Self {
+        RenderError::classified(&e)
+    }
+}
+
+impl From<SvgRenderError> for TypfError {
+    fn from(e: SvgRenderError) -> Self {
+        TypfError::RenderingFailed(e.into())
+    }
+}
+
 /// Check if a font has SVG glyphs (SVG table)
 pub fn has_svg_glyphs(font_data: &[u8]) -> bool {
     if let Ok(font) = skrifa::FontRef::new(font_data) {
@@ -58,7 +83,14 @@ pub fn has_svg_glyphs(font_data: &[u8]) -> bool {
 /// Get the raw SVG document for a glyph (decompressed if needed)
 pub fn get_svg_document(font_data: &[u8], glyph_id: u32) -> Result<String, SvgRenderError> {
     let font = skrifa::FontRef::new(font_data).map_err(|_| SvgRenderError::FontParseFailed)?;
+    font_svg_document(&font, glyph_id)
+}
 
+/// [`get_svg_document`] for an already parsed font, e.g. one face of a collection
+pub(crate) fn font_svg_document(
+    font: &skrifa::FontRef<'_>,
+    glyph_id: u32,
+) -> Result<String, SvgRenderError> {
     let svg_table = font.svg().map_err(|_| SvgRenderError::NoSvgTable)?;
     let doc_list = svg_table
         .svg_document_list()
@@ -367,11 +399,20 @@ pub fn render_svg_glyph_with_palette_and_ppem(
     ppem: f32,
 ) -> Result<Pixmap, SvgRenderError> {
     let font = skrifa::FontRef::new(font_data).map_err(|_| SvgRenderError::FontParseFailed)?;
+    render_font_svg_glyph(&font, glyph_id, palette_colors, ppem)
+}
 
+/// [`render_svg_glyph_with_palette_and_ppem`] for an already parsed font
+pub(crate) fn render_font_svg_glyph(
+    font: &skrifa::FontRef<'_>,
+    glyph_id: u32,
+    palette_colors: &[skrifa::color::Color],
+    ppem: f32,
+) -> Result<Pixmap, SvgRenderError> {
     // Get font's units per em - needed for proper viewBox in extracted SVG
     let upem = font.head().map(|h| h.units_per_em()).unwrap_or(1000);
 
-    let svg_document = get_svg_document(font_data, glyph_id)?;
+    let svg_document = font_svg_document(font, glyph_id)?;
 
     log::debug!(
         "SVG render: glyph_id={}, palette_colors={}, ppem={}, svg_len={}",
@@ -0,0 +1,264 @@
+// this_file: backends/typf-render-color/src/tables.rs
+//! Raw color-table enumeration for font QA
+//!
+//! Rendering asks "what does glyph N look like"; QA asks "which glyphs does
+//! this table cover at all". These helpers list the COLR base glyph records,
+//! the SVG document ranges, and each bitmap strike's glyph ranges straight from
+//! the tables, so a script can check coverage (say, that every emoji codepoint
+//! maps to a COLRv1 glyph) without parsing the tables itself.
+//!
+//! A font that lacks a table yields an empty list; only an unparseable font
+//! is an error.
+
+use skrifa::bitmap::{BitmapData, BitmapFormat, BitmapStrikes};
+use skrifa::raw::TableProvider;
+use skrifa::GlyphId;
+
+use crate::{ColorFontType, ColorRenderError};
+
+/// One base glyph record from the COLR table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColrGlyphRecord {
+    /// The base glyph ID.
+    pub glyph_id: u32,
+    /// [`ColorFontType::ColrV0`] for layer records, [`ColorFontType::ColrV1`] for paint records.
+    pub format: ColorFontType,
+}
+
+/// One record from the SVG document list
+///
+/// Each record maps an inclusive glyph range to a single SVG document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvgDocumentRange {
+    /// First glyph ID rendered by the document.
+    pub first_glyph: u32,
+    /// Last glyph ID rendered by the document (inclusive).
+    pub last_glyph: u32,
+    /// Byte offset of the document from the start of the SVG document list.
+    pub offset: u32,
+    /// Document length in bytes (possibly gzip-compressed).
+    pub length: u32,
+}
+
+/// Image encoding of a bitmap glyph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitmapGlyphFormat {
+    /// PNG image.
+    Png,
+    /// Uncompressed premultiplied BGRA.
+    Bgra,
+    /// 1-, 2-, 4-, or 8-bit alpha mask.
+    Mask,
+}
+
+/// A run of consecutive glyph IDs that share a bitmap encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitmapGlyphRange {
+    /// First glyph ID in the run.
+    pub first_glyph: u32,
+    /// Last glyph ID in the run (inclusive).
+    pub last_glyph: u32,
+    /// Encoding shared by every glyph in the run.
+    pub format: BitmapGlyphFormat,
+}
+
+/// One bitmap strike and the glyphs it holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitmapStrikeRecord {
+    /// Table the strike comes from (sbix, CBDT, or EBDT).
+    pub table: BitmapFormat,
+    /// Pixels per em of the strike.
+    pub ppem: f32,
+    /// Glyphs with bitmap data, in ascending order.
+    pub glyph_ranges: Vec<BitmapGlyphRange>,
+}
+
+/// List every COLR base glyph record, v0 layer records first
+pub fn colr_base_glyphs(font_data: &[u8]) -> Result<Vec<ColrGlyphRecord>, ColorRenderError> {
+    let font = skrifa::FontRef::new(font_data).map_err(|_| ColorRenderError::FontParseFailed)?;
+    let Ok(colr) = font.colr() else {
+        return Ok(Vec::new());
+    };
+
+    let mut records = Vec::new();
+    if let Some(Ok(base_glyphs)) = colr.base_glyph_records() {
+        records.extend(base_glyphs.iter().map(|record| ColrGlyphRecord {
+            glyph_id: record.glyph_id().to_u32(),
+            format: ColorFontType::ColrV0,
+        }));
+    }
+    if let Some(Ok(list)) = colr.base_glyph_list() {
+        records.extend(
+            list.base_glyph_paint_records()
+                .iter()
+                .map(|record| ColrGlyphRecord {
+                    glyph_id: record.glyph_id().to_u32(),
+                    format: ColorFontType::ColrV1,
+                }),
+        );
+    }
+    Ok(records)
+}
+
+/// List the SVG table's document records in table order
+pub fn svg_document_ranges(font_data: &[u8]) -> Result<Vec<SvgDocumentRange>, ColorRenderError> {
+    let font = skrifa::FontRef::new(font_data).map_err(|_| ColorRenderError::FontParseFailed)?;
+    let Ok(list) = font.svg().and_then(|svg| svg.svg_document_list()) else {
+        return Ok(Vec::new());
+    };
+
+    Ok(list
+        .document_records()
+        .iter()
+        .map(|record| SvgDocumentRange {
+            first_glyph: record.start_glyph_id().to_u32(),
+            last_glyph: record.end_glyph_id().to_u32(),
+            offset: record.svg_doc_offset(),
+            length: record.svg_doc_length(),
+        })
+        .collect())
+}
+
+/// List every strike of every bitmap table with the glyphs it covers
+///
+/// Unlike rendering, which uses only the preferred table, this walks sbix,
+/// CBDT, and EBDT in that order. Each strike is probed glyph by glyph, so the
+/// cost grows with glyph count times strike count.
+pub fn bitmap_strikes(font_data: &[u8]) -> Result<Vec<BitmapStrikeRecord>, ColorRenderError> {
+    let font = skrifa::FontRef::new(font_data).map_err(|_| ColorRenderError::FontParseFailed)?;
+    let num_glyphs = font
+        .maxp()
+        .map(|maxp| maxp.num_glyphs() as u32)
+        .unwrap_or(0);
+
+    let mut records = Vec::new();
+    for table in [BitmapFormat::Sbix, BitmapFormat::Cbdt, BitmapFormat::Ebdt] {
+        let Some(strikes) = BitmapStrikes::with_format(&font, table) else {
+            continue;
+        };
+        for strike in strikes.iter() {
+            let glyphs = (0..num_glyphs).filter_map(|gid| {
+                let glyph = strike.get(GlyphId::new(gid))?;
+                let format = match glyph.data {
+                    BitmapData::Png(_) => BitmapGlyphFormat::Png,
+                    BitmapData::Bgra(_) => BitmapGlyphFormat::Bgra,
+                    BitmapData::Mask(_) => BitmapGlyphFormat::Mask,
+                };
+                Some((gid, format))
+            });
+            records.push(BitmapStrikeRecord {
+                table,
+                ppem: strike.ppem(),
+                glyph_ranges: coalesce_ranges(glyphs),
+            });
+        }
+    }
+    Ok(records)
+}
+
+/// Merge ascending `(glyph, format)` pairs into runs of consecutive IDs
+fn coalesce_ranges(
+    glyphs: impl IntoIterator<Item = (u32, BitmapGlyphFormat)>,
+) -> Vec<BitmapGlyphRange> {
+    let mut ranges: Vec<BitmapGlyphRange> = Vec::new();
+    for (gid, format) in glyphs {
+        match ranges.last_mut() {
+            Some(last) if last.format == format && last.last_glyph + 1 == gid => {
+                last.last_glyph = gid;
+            },
+            _ => ranges.push(BitmapGlyphRange {
+                first_glyph: gid,
+                last_glyph: gid,
+                format,
+            }),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(name: &str) -> Option<Vec<u8>> {
+        let path = format!("../../test-fonts/{}", name);
+        let data = std::fs::read(&path).ok();
+        if data.is_none() {
+            eprintln!("Skipping test: font not found at {}", path);
+        }
+        data
+    }
+
+    #[test]
+    fn test_coalesce_ranges_when_gap_or_format_change_then_splits() {
+        use BitmapGlyphFormat::{Mask, Png};
+        let ranges = coalesce_ranges([(1, Png), (2, Png), (3, Mask), (5, Mask)]);
+
+        let range = |first_glyph, last_glyph, format| BitmapGlyphRange {
+            first_glyph,
+            last_glyph,
+            format,
+        };
+        assert_eq!(
+            ranges,
+            vec![range(1, 2, Png), range(3, 3, Mask), range(5, 5, Mask)]
+        );
+    }
+
+    #[test]
+    fn test_tables_when_plain_outline_font_then_empty() {
+        let Some(data) = load("NotoSans-Regular.ttf") else {
+            return;
+        };
+        assert!(colr_base_glyphs(&data).expect("parse").is_empty());
+        assert!(svg_document_ranges(&data).expect("parse").is_empty());
+        assert!(bitmap_strikes(&data).expect("parse").is_empty());
+    }
+
+    #[test]
+    fn test_tables_when_garbage_then_parse_error() {
+        assert!(matches!(
+            colr_base_glyphs(b"not a font"),
+            Err(ColorRenderError::FontParseFailed)
+        ));
+    }
+
+    #[test]
+    fn test_colr_base_glyphs_when_colrv1_font_then_lists_paint_records() {
+        let Some(data) = load("Nabla-Regular-COLR.ttf") else {
+            return;
+        };
+        let records = colr_base_glyphs(&data).expect("parse");
+        assert!(records
+            .iter()
+            .any(|record| record.format == ColorFontType::ColrV1));
+    }
+
+    #[test]
+    fn test_bitmap_strikes_when_cbdt_font_then_ranges_hold_png_glyphs() {
+        let Some(data) = load("Nabla-Regular-CBDT.ttf") else {
+            return;
+        };
+        let strikes = bitmap_strikes(&data).expect("parse");
+        assert!(!strikes.is_empty());
+        for strike in &strikes {
+            assert_eq!(strike.table, BitmapFormat::Cbdt);
+            assert!(strike
+                .glyph_ranges
+                .iter()
+                .all(|range| range.first_glyph <= range.last_glyph));
+        }
+    }
+
+    #[test]
+    fn test_svg_document_ranges_when_svg_font_then_ranges_are_ordered() {
+        let Some(data) = load("Nabla-Regular-SVG.ttf") else {
+            return;
+        };
+        let ranges = svg_document_ranges(&data).expect("parse");
+        assert!(!ranges.is_empty());
+        assert!(ranges
+            .iter()
+            .all(|range| range.first_glyph <= range.last_glyph && range.length > 0));
+    }
+}
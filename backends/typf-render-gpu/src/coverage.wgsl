@@ -0,0 +1,97 @@
+// Coverage pass: one invocation per output pixel.
+//
+// Each pixel takes samples x samples points, counts the ones that fall
+// inside any glyph of its bin (nonzero winding), and blends the foreground
+// over the background by that coverage. Output is straight (unpremultiplied)
+// RGBA, matching the CPU renderers.
+//
+// this_file: backends/typf-render-gpu/src/coverage.wgsl
+
+struct Params {
+    // Canvas position of this tile's top-left pixel.
+    origin: vec2<u32>,
+    // Full canvas size.
+    size: vec2<u32>,
+    // Bin grid size.
+    bins: vec2<u32>,
+    bin_size: u32,
+    samples: u32,
+    foreground: vec4<f32>,
+    // Alpha 0 means transparent background.
+    background: vec4<f32>,
+}
+
+struct Glyph {
+    seg_start: u32,
+    seg_count: u32,
+    pad: vec2<u32>,
+    bbox: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> segments: array<vec4<f32>>;
+@group(0) @binding(2) var<storage, read> glyphs: array<Glyph>;
+@group(0) @binding(3) var<storage, read> bins: array<vec2<u32>>;
+@group(0) @binding(4) var<storage, read> bin_glyphs: array<u32>;
+@group(0) @binding(5) var output: texture_storage_2d<rgba8unorm, write>;
+
+fn winding(p: vec2<f32>, glyph: Glyph) -> i32 {
+    var w = 0;
+    let end = glyph.seg_start + glyph.seg_count;
+    for (var i = glyph.seg_start; i < end; i++) {
+        let s = segments[i];
+        if (s.y <= p.y) != (s.w <= p.y) {
+            let t = (p.y - s.y) / (s.w - s.y);
+            if s.x + t * (s.z - s.x) > p.x {
+                w += select(-1, 1, s.w > s.y);
+            }
+        }
+    }
+    return w;
+}
+
+fn inside(p: vec2<f32>, bin: vec2<u32>) -> bool {
+    for (var k = 0u; k < bin.y; k++) {
+        let glyph = glyphs[bin_glyphs[bin.x + k]];
+        let b = glyph.bbox;
+        if p.x >= b.x && p.y >= b.y && p.x <= b.z && p.y <= b.w && winding(p, glyph) != 0 {
+            return true;
+        }
+    }
+    return false;
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let dims = textureDimensions(output);
+    if id.x >= dims.x || id.y >= dims.y {
+        return;
+    }
+    let pixel = params.origin + id.xy;
+    let bin_xy = min(pixel / params.bin_size, params.bins - vec2<u32>(1u));
+    let bin = bins[bin_xy.y * params.bins.x + bin_xy.x];
+
+    var covered = 0u;
+    if bin.y > 0u {
+        let spacing = 1.0 / f32(params.samples);
+        for (var sy = 0u; sy < params.samples; sy++) {
+            for (var sx = 0u; sx < params.samples; sx++) {
+                let offset = (vec2<f32>(f32(sx), f32(sy)) + 0.5) * spacing;
+                if inside(vec2<f32>(pixel) + offset, bin) {
+                    covered += 1u;
+                }
+            }
+        }
+    }
+
+    let coverage = f32(covered) / f32(params.samples * params.samples);
+    let fg = params.foreground;
+    let bg = params.background;
+    let a = fg.a * coverage;
+    let out_a = a + bg.a * (1.0 - a);
+    var rgb = vec3<f32>(0.0);
+    if out_a > 0.0 {
+        rgb = (fg.rgb * a + bg.rgb * bg.a * (1.0 - a)) / out_a;
+    }
+    textureStore(output, vec2<i32>(id.xy), vec4<f32>(rgb, out_a));
+}
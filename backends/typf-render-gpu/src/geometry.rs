@@ -0,0 +1,260 @@
+//! CPU-side scene preparation: flatten outlines, bin glyphs into tiles.
+//!
+//! The compute shader never sees curves. Every glyph outline is flattened to
+//! line segments in canvas pixel space, and the canvas is cut into square
+//! bins that each list the glyphs whose bounding boxes touch them. A pixel
+//! only tests the segments of the glyphs in its own bin.
+
+// this_file: backends/typf-render-gpu/src/geometry.rs
+
+use bytemuck::{Pod, Zeroable};
+use skrifa::outline::OutlinePen;
+use typf_core::TextTransform;
+
+/// Maximum flattening error in pixels.
+const TOLERANCE: f32 = 0.2;
+
+/// Upper bound on line segments per curve, whatever its size.
+const MAX_CURVE_STEPS: u32 = 32;
+
+/// One glyph's slice of the segment buffer and its pixel bounding box.
+///
+/// Layout matches `Glyph` in `coverage.wgsl`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub(crate) struct GpuGlyph {
+    pub seg_start: u32,
+    pub seg_count: u32,
+    pub _pad: [u32; 2],
+    /// min x, min y, max x, max y
+    pub bbox: [f32; 4],
+}
+
+/// Everything the coverage pass reads, ready for upload.
+#[derive(Debug, Default)]
+pub(crate) struct GpuScene {
+    /// `[x0, y0, x1, y1]` per line segment, y pointing down.
+    pub segments: Vec<[f32; 4]>,
+    pub glyphs: Vec<GpuGlyph>,
+    /// `[start, count]` into `bin_glyphs`, row-major.
+    pub bins: Vec<[u32; 2]>,
+    pub bin_glyphs: Vec<u32>,
+    pub bins_x: u32,
+    pub bins_y: u32,
+}
+
+impl GpuScene {
+    /// Append one flattened glyph. Glyphs without segments are dropped.
+    pub fn push_glyph(&mut self, segments: &[[f32; 4]]) {
+        if segments.is_empty() {
+            return;
+        }
+        let mut bbox = [f32::MAX, f32::MAX, f32::MIN, f32::MIN];
+        for s in segments {
+            bbox[0] = bbox[0].min(s[0]).min(s[2]);
+            bbox[1] = bbox[1].min(s[1]).min(s[3]);
+            bbox[2] = bbox[2].max(s[0]).max(s[2]);
+            bbox[3] = bbox[3].max(s[1]).max(s[3]);
+        }
+        self.glyphs.push(GpuGlyph {
+            seg_start: self.segments.len() as u32,
+            seg_count: segments.len() as u32,
+            _pad: [0; 2],
+            bbox,
+        });
+        self.segments.extend_from_slice(segments);
+    }
+
+    /// Build the bin table for a `width` x `height` canvas.
+    pub fn bin(&mut self, width: u32, height: u32, bin_size: u32) {
+        let bin_size = bin_size.max(1);
+        self.bins_x = width.div_ceil(bin_size).max(1);
+        self.bins_y = height.div_ceil(bin_size).max(1);
+
+        let mut lists = vec![Vec::new(); (self.bins_x * self.bins_y) as usize];
+        for (index, glyph) in self.glyphs.iter().enumerate() {
+            let [x0, y0, x1, y1] = glyph.bbox;
+            if x1 < 0.0 || y1 < 0.0 || x0 >= width as f32 || y0 >= height as f32 {
+                continue;
+            }
+            let bx0 = (x0.max(0.0) as u32 / bin_size).min(self.bins_x - 1);
+            let by0 = (y0.max(0.0) as u32 / bin_size).min(self.bins_y - 1);
+            let bx1 = (x1.max(0.0) as u32 / bin_size).min(self.bins_x - 1);
+            let by1 = (y1.max(0.0) as u32 / bin_size).min(self.bins_y - 1);
+            for by in by0..=by1 {
+                for bx in bx0..=bx1 {
+                    lists[(by * self.bins_x + bx) as usize].push(index as u32);
+                }
+            }
+        }
+
+        self.bins.clear();
+        self.bin_glyphs.clear();
+        for list in lists {
+            self.bins
+                .push([self.bin_glyphs.len() as u32, list.len() as u32]);
+            self.bin_glyphs.extend(list);
+        }
+    }
+}
+
+/// Outline pen that flattens into line segments in canvas space.
+///
+/// Skrifa draws y-up in pixels around the glyph origin; the pen moves the
+/// origin to `(x, baseline)` and flips y so segments share the texture's
+/// y-down coordinates.
+pub(crate) struct FlattenPen {
+    origin: (f32, f32),
+    transform: Option<TextTransform>,
+    start: (f32, f32),
+    current: (f32, f32),
+    pub segments: Vec<[f32; 4]>,
+}
+
+impl FlattenPen {
+    pub fn new(x: f32, baseline: f32) -> Self {
+        Self {
+            origin: (x, baseline),
+            transform: None,
+            start: (0.0, 0.0),
+            current: (0.0, 0.0),
+            segments: Vec::new(),
+        }
+    }
+
+    /// Apply a run-wide transform to the outline around the pen origin.
+    pub fn with_transform(mut self, transform: Option<TextTransform>) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    fn map(&self, x: f32, y: f32) -> (f32, f32) {
+        let (x, y) = match self.transform {
+            Some(t) => t.apply(x, y),
+            None => (x, y),
+        };
+        (self.origin.0 + x, self.origin.1 - y)
+    }
+
+    fn line(&mut self, to: (f32, f32)) {
+        let from = self.current;
+        // Horizontal segments never cross a scanline; skip them.
+        if from.1 != to.1 {
+            self.segments.push([from.0, from.1, to.0, to.1]);
+        }
+        self.current = to;
+    }
+
+    fn steps(control_dist: f32) -> u32 {
+        ((control_dist / TOLERANCE).sqrt().ceil() as u32).clamp(1, MAX_CURVE_STEPS)
+    }
+}
+
+impl OutlinePen for FlattenPen {
+    fn move_to(&mut self, x: f32, y: f32) {
+        if self.current != self.start {
+            self.line(self.start);
+        }
+        let p = self.map(x, y);
+        self.start = p;
+        self.current = p;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let p = self.map(x, y);
+        self.line(p);
+    }
+
+    fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) {
+        let p0 = self.current;
+        let p1 = self.map(cx, cy);
+        let p2 = self.map(x, y);
+        let dd = (p0.0 - 2.0 * p1.0 + p2.0).hypot(p0.1 - 2.0 * p1.1 + p2.1);
+        let n = Self::steps(dd * 0.25);
+        for i in 1..=n {
+            let t = i as f32 / n as f32;
+            let mt = 1.0 - t;
+            self.line((
+                mt * mt * p0.0 + 2.0 * mt * t * p1.0 + t * t * p2.0,
+                mt * mt * p0.1 + 2.0 * mt * t * p1.1 + t * t * p2.1,
+            ));
+        }
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        let p0 = self.current;
+        let p1 = self.map(cx0, cy0);
+        let p2 = self.map(cx1, cy1);
+        let p3 = self.map(x, y);
+        let dd = (p0.0 - 2.0 * p1.0 + p2.0)
+            .hypot(p0.1 - 2.0 * p1.1 + p2.1)
+            .max((p1.0 - 2.0 * p2.0 + p3.0).hypot(p1.1 - 2.0 * p2.1 + p3.1));
+        let n = Self::steps(dd * 0.75);
+        for i in 1..=n {
+            let t = i as f32 / n as f32;
+            let mt = 1.0 - t;
+            let (a, b, c, d) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+            self.line((
+                a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+                a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+            ));
+        }
+    }
+
+    fn close(&mut self) {
+        if self.current != self.start {
+            self.line(self.start);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(pen: &mut FlattenPen, size: f32) {
+        pen.move_to(0.0, 0.0);
+        pen.line_to(size, 0.0);
+        pen.line_to(size, size);
+        pen.line_to(0.0, size);
+        pen.close();
+    }
+
+    #[test]
+    fn test_flatten_pen_when_square_then_keeps_vertical_edges_flipped() {
+        let mut pen = FlattenPen::new(10.0, 20.0);
+        square(&mut pen, 5.0);
+
+        // Horizontal edges are dropped; y is flipped around the baseline.
+        assert_eq!(
+            pen.segments,
+            vec![[15.0, 20.0, 15.0, 15.0], [10.0, 15.0, 10.0, 20.0]]
+        );
+    }
+
+    #[test]
+    fn test_flatten_pen_when_curve_then_ends_on_target() {
+        let mut pen = FlattenPen::new(0.0, 0.0);
+        pen.move_to(0.0, 0.0);
+        pen.quad_to(50.0, 100.0, 100.0, 0.0);
+        pen.close();
+
+        assert!(pen.segments.len() > 4);
+        let last_curve = pen.segments[pen.segments.len() - 1];
+        assert_eq!([last_curve[2], last_curve[3]], [100.0, 0.0]);
+    }
+
+    #[test]
+    fn test_bin_when_glyph_spans_bins_then_listed_in_each() {
+        let mut scene = GpuScene::default();
+        scene.push_glyph(&[[4.0, 4.0, 40.0, 10.0]]);
+        scene.push_glyph(&[]);
+        scene.push_glyph(&[[70.0, 2.0, 70.0, 8.0]]);
+        scene.bin(80, 16, 32);
+
+        assert_eq!(scene.glyphs.len(), 2);
+        assert_eq!((scene.bins_x, scene.bins_y), (3, 1));
+        assert_eq!(scene.bins, vec![[0, 1], [1, 1], [2, 1]]);
+        assert_eq!(scene.bin_glyphs, vec![0, 0, 1]);
+    }
+}
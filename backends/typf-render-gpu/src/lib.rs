@@ -0,0 +1,852 @@
+//! Experimental wgpu compute renderer for typf.
+//!
+//! Glyph outlines are flattened to line segments on the CPU, binned into
+//! 32-pixel tiles, and uploaded once. A compute shader then resolves coverage
+//! for every pixel in parallel (nonzero winding, `samples` x `samples`
+//! supersampling) and blends the foreground over the background.
+//!
+//! The payoff is canvas size. CPU rasterizers spend their time per pixel; here
+//! a poster or 8K banner costs a handful of dispatches. Canvases larger than
+//! the device's texture limit are rendered in tiles and stitched on read-back,
+//! so [`Renderer::render`] accepts anything the core bitmap limits allow.
+//!
+//! Two outputs are available:
+//!
+//! - [`Renderer::render`] returns a read-back `Rgba8` [`BitmapData`].
+//! - [`GpuRenderer::render_to_texture`] leaves the result on the GPU as a
+//!   [`GpuTexture`] for callers that composite with wgpu themselves. Build the
+//!   renderer with [`GpuRenderer::with_device`] to share your device.
+//!
+//! # Limitations
+//!
+//! Outlines only, filled with the foreground color: color glyph tables
+//! (`COLR`, `SVG`, bitmap strikes) and render effects are ignored. Use the
+//! `skia` or `zeno` backends when those matter.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use typf_render_gpu::GpuRenderer;
+//! use typf_core::traits::Renderer;
+//!
+//! let renderer = GpuRenderer::new()?;
+//! let output = renderer.render(&shaped_result, font, &params)?;
+//! ```
+
+// this_file: backends/typf-render-gpu/src/lib.rs
+
+mod geometry;
+
+use std::sync::Arc;
+
+use bytemuck::{Pod, Zeroable};
+use skrifa::MetadataProvider;
+use thiserror::Error;
+use typf_core::{
+    error::{Classify, ErrorKind, RenderError, Result},
+    traits::{FontRef, Renderer},
+    types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput, ShapingResult},
+    Color, RenderParams,
+};
+use wgpu::util::DeviceExt;
+use wgpu::{Device, Queue};
+
+use geometry::{FlattenPen, GpuScene};
+
+/// Workgroup edge length in `coverage.wgsl`.
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Errors specific to the GPU renderer.
+#[derive(Error, Debug)]
+pub enum GpuError {
+    /// Failed to initialize wgpu adapter.
+    #[error("Failed to find a suitable GPU adapter")]
+    NoAdapter,
+    /// Failed to create wgpu device.
+    #[error("Failed to create GPU device: {0}")]
+    DeviceCreation(String),
+    /// The flattened outlines do not fit in one storage buffer.
+    #[error("Scene too large for GPU buffers: {0}")]
+    SceneTooLarge(String),
+    /// The canvas exceeds the device's texture size limit.
+    #[error("Texture {width}x{height} exceeds the device limit of {max} pixels per side")]
+    TextureTooLarge {
+        /// Requested width.
+        width: u32,
+        /// Requested height.
+        height: u32,
+        /// Device `max_texture_dimension_2d`.
+        max: u32,
+    },
+    /// GPU poll/readback failed.
+    #[error("GPU readback failed: {0}")]
+    ReadbackFailed(String),
+}
+
+impl Classify for GpuError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            GpuError::NoAdapter => ErrorKind::Unsupported,
+            GpuError::SceneTooLarge(_) | GpuError::TextureTooLarge { .. } => {
+                ErrorKind::LimitExceeded
+            },
+            GpuError::DeviceCreation(_) | GpuError::ReadbackFailed(_) => ErrorKind::Backend,
+        }
+    }
+}
+
+impl From<GpuError> for RenderError {
+    fn from(e: GpuError) -> Self {
+        RenderError::classified(&e)
+    }
+}
+
+impl From<GpuError> for typf_core::TypfError {
+    fn from(e: GpuError) -> Self {
+        typf_core::TypfError::RenderingFailed(e.into())
+    }
+}
+
+/// Configuration for the GPU renderer.
+#[derive(Debug, Clone)]
+pub struct GpuConfig {
+    /// Whether to request a software adapter (slower but more compatible).
+    pub use_cpu_fallback: bool,
+    /// Power preference for GPU adapter selection.
+    pub power_preference: wgpu::PowerPreference,
+    /// Samples per pixel along each axis; 4 gives 16 coverage levels. Default: 4.
+    ///
+    /// Forced to 1 when `RenderParams::antialias` is off.
+    pub samples: u32,
+    /// Largest tile edge rendered in one dispatch. Default: 4096.
+    ///
+    /// Clamped to the device's texture limit. Smaller tiles lower peak GPU
+    /// memory for huge canvases at the cost of more submissions.
+    pub tile_size: u32,
+    /// Edge length of the glyph bins in pixels. Default: 32.
+    pub bin_size: u32,
+}
+
+impl Default for GpuConfig {
+    fn default() -> Self {
+        Self {
+            use_cpu_fallback: false,
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            samples: 4,
+            tile_size: 4096,
+            bin_size: 32,
+        }
+    }
+}
+
+/// A rendered run left on the GPU.
+///
+/// The texture is `Rgba8Unorm` with straight alpha and usages
+/// `TEXTURE_BINDING | STORAGE_BINDING | COPY_SRC`. It belongs to
+/// [`GpuRenderer::device`].
+#[derive(Debug)]
+pub struct GpuTexture {
+    /// The rendered texture.
+    pub texture: wgpu::Texture,
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+}
+
+/// Uniform block for one tile; layout matches `Params` in `coverage.wgsl`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct GpuParams {
+    origin: [u32; 2],
+    size: [u32; 2],
+    bins: [u32; 2],
+    bin_size: u32,
+    samples: u32,
+    foreground: [f32; 4],
+    background: [f32; 4],
+}
+
+/// A laid-out run: flattened geometry plus canvas size and colors.
+struct Frame {
+    scene: GpuScene,
+    width: u32,
+    height: u32,
+    params: GpuParams,
+}
+
+/// Scene buffers shared by every tile of a frame.
+struct SceneBuffers {
+    segments: wgpu::Buffer,
+    glyphs: wgpu::Buffer,
+    bins: wgpu::Buffer,
+    bin_glyphs: wgpu::Buffer,
+}
+
+/// GPU context holding wgpu device, queue, and the coverage pipeline.
+struct GpuContext {
+    device: Device,
+    queue: Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuContext {
+    fn new(config: &GpuConfig) -> Result<Self, GpuError> {
+        pollster::block_on(Self::new_async(config))
+    }
+
+    async fn new_async(config: &GpuConfig) -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::default();
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: config.power_preference,
+                force_fallback_adapter: config.use_cpu_fallback,
+                compatible_surface: None,
+            })
+            .await
+            .map_err(|_| GpuError::NoAdapter)?;
+
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("typf-gpu"),
+                required_features: wgpu::Features::empty(),
+                required_limits: adapter.limits(),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| GpuError::DeviceCreation(e.to_string()))?;
+
+        Ok(Self::from_device(device, queue))
+    }
+
+    fn from_device(device: Device, queue: Queue) -> Self {
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("typf-gpu-coverage"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("coverage.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("typf-gpu-coverage"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+        Self {
+            device,
+            queue,
+            pipeline,
+        }
+    }
+}
+
+/// Experimental GPU compute renderer.
+///
+/// Creating one compiles the coverage shader; reuse the renderer across
+/// renders rather than building one per string.
+pub struct GpuRenderer {
+    config: GpuConfig,
+    gpu: GpuContext,
+    max_width: u32,
+    max_height: u32,
+}
+
+impl std::fmt::Debug for GpuRenderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GpuRenderer")
+            .field("config", &self.config)
+            .field("gpu", &"<GpuContext>")
+            .finish()
+    }
+}
+
+impl GpuRenderer {
+    /// Creates a GPU renderer with default configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if GPU initialization fails (no suitable adapter or device creation fails).
+    pub fn new() -> Result<Self, GpuError> {
+        Self::with_config(GpuConfig::default())
+    }
+
+    /// Creates a GPU renderer with custom configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if GPU initialization fails.
+    pub fn with_config(config: GpuConfig) -> Result<Self, GpuError> {
+        let gpu = GpuContext::new(&config)?;
+        Ok(Self::from_context(config, gpu))
+    }
+
+    /// Creates a GPU renderer on an existing device, so textures from
+    /// [`render_to_texture`](Self::render_to_texture) can be used directly.
+    ///
+    /// `power_preference` and `use_cpu_fallback` in `config` are ignored.
+    pub fn with_device(device: Device, queue: Queue, config: GpuConfig) -> Self {
+        Self::from_context(config, GpuContext::from_device(device, queue))
+    }
+
+    fn from_context(config: GpuConfig, gpu: GpuContext) -> Self {
+        Self {
+            config,
+            gpu,
+            max_width: typf_core::get_max_bitmap_width(),
+            max_height: typf_core::get_max_bitmap_height(),
+        }
+    }
+
+    /// The device textures and buffers are created on.
+    pub fn device(&self) -> &Device {
+        &self.gpu.device
+    }
+
+    /// The queue renders are submitted to.
+    pub fn queue(&self) -> &Queue {
+        &self.gpu.queue
+    }
+
+    /// Render to a texture and leave it on the GPU.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`GpuError::TextureTooLarge`] when the canvas exceeds the
+    /// device's texture limit; use [`Renderer::render`] for those, which tiles.
+    pub fn render_to_texture(
+        &self,
+        shaped: &ShapingResult,
+        font: &dyn FontRef,
+        params: &RenderParams,
+    ) -> Result<GpuTexture> {
+        let frame = self.layout(shaped, font, params)?;
+        let max = self.gpu.device.limits().max_texture_dimension_2d;
+        if frame.width > max || frame.height > max {
+            return Err(GpuError::TextureTooLarge {
+                width: frame.width,
+                height: frame.height,
+                max,
+            }
+            .into());
+        }
+
+        let buffers = self.upload(&frame.scene)?;
+        let texture = self.create_target(
+            frame.width,
+            frame.height,
+            wgpu::TextureUsages::TEXTURE_BINDING,
+        );
+        let mut encoder = self
+            .gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("typf-gpu-render"),
+            });
+        self.encode_tile(&mut encoder, &buffers, &frame, &texture, [0, 0]);
+        self.gpu.queue.submit([encoder.finish()]);
+
+        Ok(GpuTexture {
+            texture,
+            width: frame.width,
+            height: frame.height,
+        })
+    }
+
+    /// Convert typf color to normalized RGBA.
+    fn to_gpu_color(color: Color) -> [f32; 4] {
+        [color.r, color.g, color.b, color.a].map(|c| c as f32 / 255.0)
+    }
+
+    /// Size the canvas and flatten every glyph outline into it.
+    fn layout(
+        &self,
+        shaped: &ShapingResult,
+        font: &dyn FontRef,
+        params: &RenderParams,
+    ) -> Result<Frame> {
+        let padding = params.padding as f32;
+        let font_size = shaped.advance_height;
+
+        // Baseline standardization (metrics-first):
+        // - Prefer font ascent/descent (stable across strings)
+        // - Fall back to a heuristic if font metrics are unavailable.
+        let (ascent, descent) = font
+            .metrics_at(&params.variations)
+            .filter(|m| m.units_per_em > 0 && (m.ascent != 0 || m.descent != 0))
+            .map(|m| {
+                let scale = font_size / (m.units_per_em as f32);
+                let ascent = (m.ascent as f32).max(0.0) * scale;
+                let descent = (m.descent as f32).abs() * scale;
+                (ascent, descent)
+            })
+            .unwrap_or((font_size * 0.8, font_size * 0.2));
+
+        // Transformed runs size the canvas to the transformed line box
+        let transform = params.active_transform()?;
+        let (left, top, text_width, text_height) = match transform {
+            Some(t) => {
+                let (x0, y0, x1, y1) = t.bounds(0.0, -descent, shaped.advance_width, ascent);
+                (-x0, y1, x1 - x0, y1 - y0)
+            },
+            None => (0.0, ascent, shaped.advance_width, ascent + descent),
+        };
+        let width = (text_width + padding * 2.0).ceil() as u32;
+        let height = (text_height + padding * 2.0).ceil() as u32;
+
+        if width == 0 || height == 0 {
+            return Err(RenderError::ZeroDimensions { width, height }.into());
+        }
+        if width > self.max_width || height > self.max_height {
+            return Err(RenderError::DimensionsTooLarge {
+                width,
+                height,
+                max_width: self.max_width,
+                max_height: self.max_height,
+            }
+            .into());
+        }
+
+        let font_ref = skrifa::FontRef::from_index(font.data(), font.face_index())
+            .map_err(|_| RenderError::InvalidFont)?;
+        let outlines = font_ref.outline_glyphs();
+        let variations: Vec<(&str, f32)> = params
+            .variations
+            .iter()
+            .map(|(tag, value)| (tag.as_str(), *value))
+            .collect();
+        let location = font_ref.axes().location(variations);
+        let size = skrifa::instance::Size::new(font_size);
+
+        let baseline = padding + top;
+        let mut scene = GpuScene::default();
+        for glyph in &shaped.glyphs {
+            let Some(outline) = outlines.get(skrifa::GlyphId::new(glyph.id)) else {
+                continue;
+            };
+            // Pen offsets are y-down; flip them for the y-up transform. The
+            // baseline shift raises the pen before the transform.
+            let pen_y = glyph.y - params.baseline_shift;
+            let (pen_x, pen_y) = match transform {
+                Some(t) => {
+                    let (x, y) = t.apply(glyph.x, -pen_y);
+                    (x, -y)
+                },
+                None => (glyph.x, pen_y),
+            };
+            let mut pen =
+                FlattenPen::new(padding + left + pen_x, baseline + pen_y).with_transform(transform);
+            let settings = skrifa::outline::DrawSettings::unhinted(size, location.coords());
+            if outline.draw(settings, &mut pen).is_err() {
+                log::warn!("GpuRenderer: failed to draw glyph {}", glyph.id);
+                continue;
+            }
+            scene.push_glyph(&pen.segments);
+        }
+        scene.bin(width, height, self.config.bin_size);
+
+        let samples = if params.antialias {
+            self.config.samples.clamp(1, 16)
+        } else {
+            1
+        };
+        let gpu_params = GpuParams {
+            origin: [0, 0],
+            size: [width, height],
+            bins: [scene.bins_x, scene.bins_y],
+            bin_size: self.config.bin_size.max(1),
+            samples,
+            foreground: Self::to_gpu_color(params.foreground),
+            background: params.background.map_or([0.0; 4], Self::to_gpu_color),
+        };
+
+        Ok(Frame {
+            scene,
+            width,
+            height,
+            params: gpu_params,
+        })
+    }
+
+    /// Upload the scene, failing early if a buffer exceeds the binding limit.
+    fn upload(&self, scene: &GpuScene) -> Result<SceneBuffers, GpuError> {
+        let max = u64::from(self.gpu.device.limits().max_storage_buffer_binding_size);
+        let buffer = |label: &str, bytes: &[u8]| -> Result<wgpu::Buffer, GpuError> {
+            if bytes.len() as u64 > max {
+                return Err(GpuError::SceneTooLarge(format!(
+                    "{label} needs {} bytes, device allows {max}",
+                    bytes.len()
+                )));
+            }
+            // Zero-sized bindings are invalid; keep one dummy element.
+            let padded;
+            let contents = if bytes.is_empty() {
+                padded = [0u8; 16];
+                &padded[..]
+            } else {
+                bytes
+            };
+            Ok(self
+                .gpu
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(label),
+                    contents,
+                    usage: wgpu::BufferUsages::STORAGE,
+                }))
+        };
+
+        Ok(SceneBuffers {
+            segments: buffer("typf-gpu-segments", bytemuck::cast_slice(&scene.segments))?,
+            glyphs: buffer("typf-gpu-glyphs", bytemuck::cast_slice(&scene.glyphs))?,
+            bins: buffer("typf-gpu-bins", bytemuck::cast_slice(&scene.bins))?,
+            bin_glyphs: buffer(
+                "typf-gpu-bin-glyphs",
+                bytemuck::cast_slice(&scene.bin_glyphs),
+            )?,
+        })
+    }
+
+    fn create_target(&self, width: u32, height: u32, usage: wgpu::TextureUsages) -> wgpu::Texture {
+        self.gpu.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("typf-gpu-target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: usage | wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    /// Record the coverage pass for the tile of `frame` starting at `origin`.
+    fn encode_tile(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        buffers: &SceneBuffers,
+        frame: &Frame,
+        texture: &wgpu::Texture,
+        origin: [u32; 2],
+    ) {
+        let device = &self.gpu.device;
+        let uniform = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("typf-gpu-params"),
+            contents: bytemuck::bytes_of(&GpuParams {
+                origin,
+                ..frame.params
+            }),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("typf-gpu-bindings"),
+            layout: &self.gpu.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: buffers.segments.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: buffers.glyphs.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: buffers.bins.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: buffers.bin_glyphs.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+            ],
+        });
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("typf-gpu-coverage"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.gpu.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(
+            texture.width().div_ceil(WORKGROUP_SIZE),
+            texture.height().div_ceil(WORKGROUP_SIZE),
+            1,
+        );
+    }
+
+    /// Render every tile of `frame` and stitch the read-backs together.
+    fn render_to_bitmap(&self, frame: &Frame) -> Result<Vec<u8>, GpuError> {
+        let tile = self
+            .config
+            .tile_size
+            .min(self.gpu.device.limits().max_texture_dimension_2d)
+            .max(WORKGROUP_SIZE);
+        let buffers = self.upload(&frame.scene)?;
+        let (width, height) = (frame.width, frame.height);
+        let mut result = vec![0u8; (width as usize) * (height as usize) * 4];
+
+        for tile_y in (0..height).step_by(tile as usize) {
+            for tile_x in (0..width).step_by(tile as usize) {
+                let tile_w = tile.min(width - tile_x);
+                let tile_h = tile.min(height - tile_y);
+                let texture = self.create_target(tile_w, tile_h, wgpu::TextureUsages::empty());
+
+                let mut encoder =
+                    self.gpu
+                        .device
+                        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                            label: Some("typf-gpu-render"),
+                        });
+                self.encode_tile(&mut encoder, &buffers, frame, &texture, [tile_x, tile_y]);
+                let pixels = self.read_back(encoder, &texture)?;
+
+                let row_bytes = tile_w as usize * 4;
+                for (row, src) in pixels.chunks_exact(row_bytes).enumerate() {
+                    let dst = ((tile_y as usize + row) * width as usize + tile_x as usize) * 4;
+                    result[dst..dst + row_bytes].copy_from_slice(src);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Submit `encoder` with a copy of `texture` appended and return its pixels.
+    fn read_back(
+        &self,
+        mut encoder: wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) -> Result<Vec<u8>, GpuError> {
+        let device = &self.gpu.device;
+        let (width, height) = (texture.width(), texture.height());
+
+        // Create buffer for readback (with row padding to 256 bytes)
+        let bytes_per_row = (width * 4).next_multiple_of(256);
+        let buffer_size = (bytes_per_row as u64) * (height as u64);
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("typf-gpu-readback"),
+            size: buffer_size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &readback_buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        self.gpu.queue.submit([encoder.finish()]);
+
+        let buffer_slice = readback_buffer.slice(..);
+        buffer_slice.map_async(wgpu::MapMode::Read, |_| {});
+        device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .map_err(|e| GpuError::ReadbackFailed(format!("{:?}", e)))?;
+
+        // Read data and remove row padding
+        let mapped = buffer_slice.get_mapped_range();
+        let mut result = Vec::with_capacity((width * height * 4) as usize);
+        for row in mapped.chunks_exact(bytes_per_row as usize) {
+            result.extend_from_slice(&row[0..(width * 4) as usize]);
+        }
+        drop(mapped);
+        readback_buffer.unmap();
+
+        Ok(result)
+    }
+}
+
+impl Renderer for GpuRenderer {
+    fn name(&self) -> &'static str {
+        "gpu"
+    }
+
+    /// Render shaped glyphs on the GPU and read the result back as RGBA.
+    ///
+    /// Canvases larger than [`GpuConfig::tile_size`] (or the device limit)
+    /// are rendered tile by tile. Color glyph tables and effects are ignored;
+    /// see the crate docs.
+    fn render(
+        &self,
+        shaped: &ShapingResult,
+        font: Arc<dyn FontRef>,
+        params: &RenderParams,
+    ) -> Result<RenderOutput> {
+        log::debug!("GpuRenderer: Rendering {} glyphs", shaped.glyphs.len());
+
+        let frame = self.layout(shaped, font.as_ref(), params)?;
+        let rgba_data = self.render_to_bitmap(&frame)?;
+
+        Ok(RenderOutput::Bitmap(
+            BitmapData {
+                width: frame.width,
+                height: frame.height,
+                format: BitmapFormat::Rgba8,
+                stride: BitmapFormat::Rgba8.row_bytes(frame.width),
+                layout: PixelLayout::RGBA,
+                data: rgba_data,
+            }
+            .into_layout(params.pixel_layout),
+        ))
+    }
+
+    fn supports_format(&self, format: &str) -> bool {
+        matches!(format, "bitmap" | "rgba" | "rgb")
+    }
+
+    fn clear_cache(&self) {
+        // Scene buffers are rebuilt per render, no persistent cache
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use typf_core::types::{Direction, PositionedGlyph};
+
+    #[test]
+    fn test_config_default() {
+        let config = GpuConfig::default();
+        assert!(!config.use_cpu_fallback);
+        assert_eq!(config.samples, 4);
+        assert_eq!(config.tile_size, 4096);
+    }
+
+    #[test]
+    fn test_color_conversion() {
+        let color = GpuRenderer::to_gpu_color(Color::rgba(255, 0, 51, 102));
+        for (got, want) in color.iter().zip([1.0, 0.0, 0.2, 0.4]) {
+            assert!((got - want).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_params_layout_matches_shader() {
+        assert_eq!(std::mem::size_of::<GpuParams>(), 64);
+        assert_eq!(std::mem::size_of::<geometry::GpuGlyph>(), 32);
+    }
+
+    fn load_font() -> Option<Arc<dyn FontRef>> {
+        let path = "../../test-fonts/NotoSans-Regular.ttf";
+        match typf_fontdb::TypfFontFace::from_file(path) {
+            Ok(face) => Some(Arc::new(face)),
+            Err(_) => {
+                eprintln!("Skipping: test font not found at {}", path);
+                None
+            },
+        }
+    }
+
+    fn renderer(tile_size: u32) -> Option<GpuRenderer> {
+        match GpuRenderer::with_config(GpuConfig {
+            tile_size,
+            ..Default::default()
+        }) {
+            Ok(renderer) => Some(renderer),
+            Err(e) => {
+                eprintln!("Skipping: no GPU available ({})", e);
+                None
+            },
+        }
+    }
+
+    fn run(font: &Arc<dyn FontRef>, text: &str, size: f32) -> ShapingResult {
+        let mut x = 0.0;
+        let glyphs = text
+            .chars()
+            .enumerate()
+            .map(|(i, ch)| {
+                let id = font.glyph_id(ch).unwrap_or(0);
+                let advance = font.advance_width(id) * size / font.units_per_em() as f32;
+                let glyph = PositionedGlyph {
+                    id,
+                    x,
+                    y: 0.0,
+                    advance,
+                    cluster: i as u32,
+                };
+                x += advance;
+                glyph
+            })
+            .collect();
+        ShapingResult {
+            glyphs,
+            advance_width: x,
+            advance_height: size,
+            direction: Direction::LeftToRight,
+        }
+    }
+
+    fn bitmap(output: RenderOutput) -> BitmapData {
+        match output {
+            RenderOutput::Bitmap(bitmap) => bitmap,
+            _ => panic!("expected bitmap output"),
+        }
+    }
+
+    #[test]
+    fn test_render_when_tiled_then_matches_single_tile() {
+        let (Some(font), Some(whole), Some(tiled)) = (load_font(), renderer(4096), renderer(48))
+        else {
+            return;
+        };
+        let shaped = run(&font, "Tiles", 64.0);
+        let params = RenderParams::default();
+
+        let whole = bitmap(
+            whole
+                .render(&shaped, font.clone(), &params)
+                .expect("render"),
+        );
+        let tiled = bitmap(tiled.render(&shaped, font, &params).expect("render"));
+
+        assert!(whole.width > 48 && whole.height > 48);
+        assert!(whole.data.chunks_exact(4).any(|px| px[3] > 0));
+        assert_eq!(whole.data, tiled.data);
+    }
+
+    #[test]
+    fn test_render_to_texture_when_small_then_matches_canvas_size() {
+        let (Some(font), Some(renderer)) = (load_font(), renderer(4096)) else {
+            return;
+        };
+        let shaped = run(&font, "GPU", 32.0);
+        let texture = renderer
+            .render_to_texture(&shaped, font.as_ref(), &RenderParams::default())
+            .expect("render");
+
+        assert_eq!(texture.texture.width(), texture.width);
+        assert_eq!(texture.texture.format(), wgpu::TextureFormat::Rgba8Unorm);
+    }
+}
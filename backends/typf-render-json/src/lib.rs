@@ -10,7 +10,7 @@ use typf_core::{
     error::Result,
     traits::{FontRef, Renderer, Stage},
     types::{RenderOutput, ShapingResult},
-    RenderParams,
+    GlyphRun, RenderParams,
 };
 
 /// Individual glyph data that matches HarfBuzz's JSON output format
@@ -42,16 +42,37 @@ pub struct JsonOutput {
     pub advance: f32,             // Total width of the shaped text
 }
 
+/// Which JSON document the renderer writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonFormat {
+    /// HarfBuzz-compatible glyph info in 26.6 units, for diffing against `hb-shape`
+    #[default]
+    HarfBuzz,
+    /// Pixel-space glyph runs with a font id, for canvas front-ends and other
+    /// processes that draw glyphs themselves; see [`typf_core::glyph_run`]
+    GlyphRun,
+}
+
 /// The renderer that turns shaping results into structured data
 ///
 /// Unlike bitmap renderers, this doesn't create pixels—it creates insight.
 /// Perfect for testing, debugging, or feeding other rendering systems.
-pub struct JsonRenderer;
+pub struct JsonRenderer {
+    format: JsonFormat,
+}
 
 impl JsonRenderer {
     /// Creates a renderer that speaks JSON instead of pixels
     pub fn new() -> Self {
-        Self
+        Self {
+            format: JsonFormat::default(),
+        }
+    }
+
+    /// Switches the output document, e.g. to [`JsonFormat::GlyphRun`]
+    pub fn with_format(mut self, format: JsonFormat) -> Self {
+        self.format = format;
+        self
     }
 }
 
@@ -88,9 +109,14 @@ impl Renderer for JsonRenderer {
     fn render(
         &self,
         shaped: &ShapingResult,
-        _font: Arc<dyn FontRef>,
+        font: Arc<dyn FontRef>,
         _params: &RenderParams,
     ) -> Result<RenderOutput> {
+        if self.format == JsonFormat::GlyphRun {
+            let run = GlyphRun::new(shaped, GlyphRun::font_id(font.as_ref()));
+            return Ok(RenderOutput::Json(typf_core::glyph_runs_to_json(&[run])));
+        }
+
         // Transform Typf's PositionedGlyph into HarfBuzz-compatible format
         let glyphs: Vec<HbGlyphInfo> = shaped
             .glyphs
@@ -213,6 +239,39 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_glyph_run_format_when_rendered_then_pixel_positions_and_font_id() {
+        let renderer = JsonRenderer::new().with_format(JsonFormat::GlyphRun);
+        let shaped = ShapingResult {
+            glyphs: vec![PositionedGlyph {
+                id: 65,
+                x: 1.5,
+                y: 0.0,
+                advance: 10.0,
+                cluster: 0,
+            }],
+            advance_width: 10.0,
+            advance_height: 16.0,
+            direction: Direction::LeftToRight,
+        };
+
+        let font: Arc<dyn FontRef> = Arc::new(MockFont);
+        let result = renderer.render(&shaped, font.clone(), &RenderParams::default());
+        let Ok(RenderOutput::Json(json)) = result else {
+            unreachable!("JSON renderer returns JSON");
+        };
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["format"], "typf-glyph-runs");
+        let run = &value["runs"][0];
+        assert_eq!(run["font_id"], GlyphRun::font_id(font.as_ref()));
+        assert_eq!(run["font_size"], 16.0);
+        assert_eq!(run["direction"], "ltr");
+        assert_eq!(run["glyphs"][0]["id"], 65);
+        assert_eq!(run["glyphs"][0]["x"], 1.5);
+        assert_eq!(run["glyphs"][0]["cluster"], 0);
+    }
+
     #[test]
     fn test_supports_format() {
         let renderer = JsonRenderer::new();
@@ -0,0 +1,101 @@
+//! SIMD kernels versus their scalar twins on paragraph-size workloads
+//!
+//! A paragraph of body text renders to roughly a 1200×400 canvas. These
+//! benchmarks run the two hot loops over that much data: compositing every
+//! canvas row, and accumulating 4×4 oversampled coverage for every output
+//! row. Each group reports the scalar reference next to the runtime-selected
+//! kernel, so the speedup reads straight off the criterion report.
+//!
+//! Run with `cargo bench -p typf-render-opixa --bench simd` (add
+//! `--features avx512` on a toolchain and CPU that support it).
+
+// this_file: backends/typf-render-opixa/benches/simd.rs
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+mod paragraph {
+    use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+    use typf_render_opixa::simd;
+
+    const WIDTH: usize = 1200;
+    const HEIGHT: usize = 400;
+    const OVERSAMPLE: usize = 4;
+
+    /// Blend and coverage kernels share this shape
+    type Kernel = fn(&mut [u8], &[u8]);
+
+    /// Glyph-like pixels: runs of transparent, edge, and solid coverage
+    fn glyph_row(len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|i| match i % 11 {
+                0..=3 => 0,
+                4 => 96,
+                5..=8 => 255,
+                _ => 160,
+            })
+            .collect()
+    }
+
+    pub fn bench_blend(c: &mut Criterion) {
+        let row_bytes = WIDTH * 4;
+        let src = glyph_row(row_bytes);
+        let mut group = c.benchmark_group("blend_paragraph");
+        group.throughput(Throughput::Bytes((row_bytes * HEIGHT) as u64));
+
+        let kernels: [(&str, Kernel); 2] = [
+            ("scalar", simd::blend_over_scalar),
+            ("dispatch", simd::blend_over),
+        ];
+        for (name, kernel) in kernels {
+            let mut canvas = vec![255u8; row_bytes * HEIGHT];
+            group.bench_function(BenchmarkId::from_parameter(name), |b| {
+                b.iter(|| {
+                    for row in canvas.chunks_exact_mut(row_bytes) {
+                        kernel(row, black_box(&src));
+                    }
+                })
+            });
+        }
+        group.finish();
+    }
+
+    pub fn bench_coverage(c: &mut Criterion) {
+        let mono_width = WIDTH * OVERSAMPLE;
+        let mono: Vec<u8> = glyph_row(mono_width * OVERSAMPLE)
+            .into_iter()
+            .map(|v| (v > 128) as u8)
+            .collect();
+        let mut group = c.benchmark_group("coverage_paragraph");
+        group.throughput(Throughput::Bytes((mono.len() * HEIGHT) as u64));
+
+        let kernels: [(&str, Kernel); 2] = [
+            ("scalar", simd::accumulate_coverage_scalar),
+            ("dispatch", simd::accumulate_coverage),
+        ];
+        for (name, kernel) in kernels {
+            let mut columns = vec![0u8; mono_width];
+            group.bench_function(BenchmarkId::from_parameter(name), |b| {
+                b.iter(|| {
+                    for _ in 0..HEIGHT {
+                        columns.fill(0);
+                        for row in mono.chunks_exact(mono_width) {
+                            kernel(&mut columns, black_box(row));
+                        }
+                    }
+                    black_box(&columns);
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+criterion::criterion_group!(benches, paragraph::bench_blend, paragraph::bench_coverage);
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn no_simd(_: &mut criterion::Criterion) {}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+criterion::criterion_group!(benches, no_simd);
+
+criterion::criterion_main!(benches);
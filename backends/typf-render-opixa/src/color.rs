@@ -0,0 +1,190 @@
+//! Color glyphs (COLR, SVG, sbix, CBDT) through typf-render-color.
+//!
+//! Opixa rasterizes outlines itself; glyphs a font draws in color are
+//! painted by [`ColorFont::render_glyph_with_preference`], the same path the
+//! skia and zeno renderers take. The result keeps its premultiplied pixels
+//! for color targets and hands its alpha to everything that only wants
+//! coverage: distance fields, strokes, shadows, and gray formats.
+//!
+//! Needs the `color` feature, on by default.
+
+#[cfg(feature = "color")]
+use skrifa::{instance::Size, MetadataProvider, Tag};
+#[cfg(feature = "color")]
+use typf_core::{
+    diagnostics::{self, DiagnosticKind},
+    error::{ErrorContext, TypfError},
+};
+use typf_core::{error::Result, GlyphSource, RenderParams};
+#[cfg(feature = "color")]
+use typf_render_color::{ColorFont, ColorRenderError, Location};
+
+use crate::rasterizer::GlyphBitmap;
+
+/// Tables that hold color or bitmap glyphs
+#[cfg(feature = "color")]
+const COLOR_TABLES: [Tag; 5] = [
+    Tag::new(b"COLR"),
+    Tag::new(b"SVG "),
+    Tag::new(b"sbix"),
+    Tag::new(b"CBDT"),
+    Tag::new(b"EBDT"),
+];
+
+/// A glyph painted in color.
+pub(crate) struct ColorGlyph {
+    /// Placement, with the alpha channel as coverage
+    pub bitmap: GlyphBitmap,
+    /// Premultiplied RGBA, `bitmap.width` by `bitmap.height`, top row first
+    pub rgba: Vec<u8>,
+    /// Table the glyph was painted from
+    pub source: GlyphSource,
+}
+
+/// Color glyph source for one run, set up once per font.
+#[cfg(feature = "color")]
+pub(crate) struct ColorGlyphs<'a> {
+    font: ColorFont<'a>,
+    location: Location,
+    variations: Vec<(&'a str, f32)>,
+    /// CPAL palette resolved from `params.color_palette` for this font
+    palette: u16,
+    size: f32,
+    params: &'a RenderParams,
+}
+
+#[cfg(feature = "color")]
+impl<'a> ColorGlyphs<'a> {
+    /// None when `params` rule out color sources or the font has no color
+    /// tables, so plain fonts cost one table lookup per run.
+    pub fn new(
+        font_data: &'a [u8],
+        face_index: u32,
+        size: f32,
+        params: &'a RenderParams,
+    ) -> Option<Self> {
+        if !params.glyph_sources.allows_color() {
+            return None;
+        }
+        let font = ColorFont::from_index(font_data, face_index).ok()?;
+        let has_color = COLOR_TABLES
+            .iter()
+            .any(|&tag| font.skrifa().table_data(tag).is_some());
+        if !has_color {
+            return None;
+        }
+        let variations: Vec<(&str, f32)> = params
+            .variations
+            .iter()
+            .map(|(tag, value)| (tag.as_str(), *value))
+            .collect();
+        let location = font.skrifa().axes().location(variations.iter().copied());
+        Some(Self {
+            font: font.with_memory_budget(params.memory_budget.clone()),
+            location,
+            variations,
+            palette: params.color_palette.resolve(font_data, face_index),
+            size,
+            params,
+        })
+    }
+
+    /// Paint `glyph_id`; none when the font draws it only as an outline.
+    pub fn render(&self, glyph_id: u32) -> Result<Option<ColorGlyph>> {
+        // COLR paints at the glyph origin, y up, so the pixmap spans the ink
+        // above and right of it; SVG fills the em square
+        let bounds = self
+            .font
+            .skrifa()
+            .glyph_metrics(Size::new(self.size), &self.location)
+            .bounds(skrifa::GlyphId::new(glyph_id));
+        let (right, top) = bounds.map_or((0.0, 0.0), |b| (b.x_max, b.y_max));
+        let width = right.max(self.size).ceil().max(1.0) as u32 + 1;
+        let height = top.max(self.size).ceil().max(1.0) as u32 + 1;
+
+        let rendered = self.font.render_glyph_with_preference(
+            glyph_id,
+            width,
+            height,
+            self.size,
+            self.palette,
+            &self.variations,
+            &self.params.glyph_sources,
+        );
+        let (rendered, source) = match rendered {
+            Ok(found) => found,
+            Err(ColorRenderError::GlyphNotFound | ColorRenderError::NoColrTable) => {
+                return Ok(None)
+            },
+            Err(ColorRenderError::NoPalette) => {
+                // A color glyph without a palette to paint it; its outline stands in
+                diagnostics::warn(
+                    self.params.diagnostics.as_ref(),
+                    DiagnosticKind::Fallback,
+                    Some(glyph_id),
+                    format!("Opixa: no palette for color glyph {glyph_id}; drew its outline"),
+                );
+                return Ok(None);
+            },
+            Err(ColorRenderError::BudgetExceeded(e)) => return Err(e.into()),
+            Err(e) => return Err(TypfError::from(e).with_context(ErrorContext::glyph(glyph_id))),
+        };
+
+        let pixmap = rendered.pixmap;
+        let (width, height) = (pixmap.width(), pixmap.height());
+        let mut rgba = pixmap.take();
+        if rgba.chunks_exact(4).all(|px| px[3] == 0) {
+            // Nothing painted, as for spaces; let the outline stand in
+            return Ok(None);
+        }
+        log::debug!("Opixa: rendered glyph {glyph_id} via {source:?} into {width}x{height}");
+
+        // COLR and bitmap sources come out y-up; resvg already draws y-down
+        if source != GlyphSource::Svg {
+            let row = width as usize * 4;
+            for y in 0..height as usize / 2 {
+                let bottom = (height as usize - 1 - y) * row;
+                let (upper, lower) = rgba.split_at_mut(bottom);
+                upper[y * row..(y + 1) * row].swap_with_slice(&mut lower[..row]);
+            }
+        }
+        // Bitmap and SVG sources report their bearings; COLR pixmaps start
+        // at the glyph origin, so the top row sits `height` above it
+        let (left, top) = match (rendered.bearing_x, rendered.bearing_y) {
+            (Some(x), Some(y)) => (x.floor() as i32, y.ceil() as i32),
+            _ => (0, height as i32),
+        };
+        let coverage = rgba.chunks_exact(4).map(|px| px[3]).collect();
+        Ok(Some(ColorGlyph {
+            bitmap: GlyphBitmap {
+                width,
+                height,
+                left,
+                top,
+                data: coverage,
+            },
+            rgba,
+            source,
+        }))
+    }
+}
+
+/// Without the `color` feature every glyph is an outline.
+#[cfg(not(feature = "color"))]
+pub(crate) struct ColorGlyphs<'a>(std::marker::PhantomData<&'a ()>);
+
+#[cfg(not(feature = "color"))]
+impl<'a> ColorGlyphs<'a> {
+    pub fn new(
+        _font_data: &'a [u8],
+        _face_index: u32,
+        _size: f32,
+        _params: &'a RenderParams,
+    ) -> Option<Self> {
+        None
+    }
+
+    pub fn render(&self, _glyph_id: u32) -> Result<Option<ColorGlyph>> {
+        Ok(None)
+    }
+}
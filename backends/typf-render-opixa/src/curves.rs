@@ -44,13 +44,10 @@ pub fn compute_quadratic_flatness(
     // For simplicity, use Manhattan distance approximation:
     // flatness ≈ |x1 - (x0+x2)/2| + |y1 - (y0+y2)/2|
 
-    let mid_x = (x0 + x2).raw() / 2;
-    let mid_y = (y0 + y2).raw() / 2;
+    let dx = (x1 - x0.midpoint(x2)).abs();
+    let dy = (y1 - y0.midpoint(y2)).abs();
 
-    let dx = (x1.raw() - mid_x).abs();
-    let dy = (y1.raw() - mid_y).abs();
-
-    F26Dot6::from_raw(dx + dy)
+    dx + dy
 }
 
 /// The curve whisperer: transforms rebellious Béziers into obedient lines
@@ -99,14 +96,14 @@ pub fn subdivide_quadratic<F>(
     //   m12 = (p1 + p2) / 2
     //   m012 = (m01 + m12) / 2  (point on curve at t=0.5)
 
-    let m01_x = F26Dot6::from_raw((x0.raw() + x1.raw()) / 2);
-    let m01_y = F26Dot6::from_raw((y0.raw() + y1.raw()) / 2);
+    let m01_x = x0.midpoint(x1);
+    let m01_y = y0.midpoint(y1);
 
-    let m12_x = F26Dot6::from_raw((x1.raw() + x2.raw()) / 2);
-    let m12_y = F26Dot6::from_raw((y1.raw() + y2.raw()) / 2);
+    let m12_x = x1.midpoint(x2);
+    let m12_y = y1.midpoint(y2);
 
-    let m012_x = F26Dot6::from_raw((m01_x.raw() + m12_x.raw()) / 2);
-    let m012_y = F26Dot6::from_raw((m01_y.raw() + m12_y.raw()) / 2);
+    let m012_x = m01_x.midpoint(m12_x);
+    let m012_y = m01_y.midpoint(m12_y);
 
     // Recursively subdivide both halves
     subdivide_quadratic(x0, y0, m01_x, m01_y, m012_x, m012_y, output, depth + 1);
@@ -140,16 +137,11 @@ pub fn compute_cubic_flatness(
     // to the line connecting endpoints
 
     // Check distance from both control points
-    let dx1 = (x1.raw() - (x0.raw() + x3.raw()) / 2).abs();
-    let dy1 = (y1.raw() - (y0.raw() + y3.raw()) / 2).abs();
-
-    let dx2 = (x2.raw() - (x0.raw() + x3.raw()) / 2).abs();
-    let dy2 = (y2.raw() - (y0.raw() + y3.raw()) / 2).abs();
-
-    let dist1 = dx1 + dy1;
-    let dist2 = dx2 + dy2;
+    let (mid_x, mid_y) = (x0.midpoint(x3), y0.midpoint(y3));
+    let dist1 = (x1 - mid_x).abs() + (y1 - mid_y).abs();
+    let dist2 = (x2 - mid_x).abs() + (y2 - mid_y).abs();
 
-    F26Dot6::from_raw(dist1.max(dist2))
+    dist1.max(dist2)
 }
 
 /// Taming the wild cubic beast, one split at a time
@@ -198,25 +190,25 @@ pub fn subdivide_cubic<F>(
 
     // Subdivide using de Casteljau algorithm
     // First level midpoints:
-    let m01_x = F26Dot6::from_raw((x0.raw() + x1.raw()) / 2);
-    let m01_y = F26Dot6::from_raw((y0.raw() + y1.raw()) / 2);
+    let m01_x = x0.midpoint(x1);
+    let m01_y = y0.midpoint(y1);
 
-    let m12_x = F26Dot6::from_raw((x1.raw() + x2.raw()) / 2);
-    let m12_y = F26Dot6::from_raw((y1.raw() + y2.raw()) / 2);
+    let m12_x = x1.midpoint(x2);
+    let m12_y = y1.midpoint(y2);
 
-    let m23_x = F26Dot6::from_raw((x2.raw() + x3.raw()) / 2);
-    let m23_y = F26Dot6::from_raw((y2.raw() + y3.raw()) / 2);
+    let m23_x = x2.midpoint(x3);
+    let m23_y = y2.midpoint(y3);
 
     // Second level midpoints:
-    let m012_x = F26Dot6::from_raw((m01_x.raw() + m12_x.raw()) / 2);
-    let m012_y = F26Dot6::from_raw((m01_y.raw() + m12_y.raw()) / 2);
+    let m012_x = m01_x.midpoint(m12_x);
+    let m012_y = m01_y.midpoint(m12_y);
 
-    let m123_x = F26Dot6::from_raw((m12_x.raw() + m23_x.raw()) / 2);
-    let m123_y = F26Dot6::from_raw((m12_y.raw() + m23_y.raw()) / 2);
+    let m123_x = m12_x.midpoint(m23_x);
+    let m123_y = m12_y.midpoint(m23_y);
 
     // Third level midpoint (point on curve at t=0.5):
-    let m0123_x = F26Dot6::from_raw((m012_x.raw() + m123_x.raw()) / 2);
-    let m0123_y = F26Dot6::from_raw((m012_y.raw() + m123_y.raw()) / 2);
+    let m0123_x = m012_x.midpoint(m123_x);
+    let m0123_y = m012_y.midpoint(m123_y);
 
     // Recursively subdivide both halves
     subdivide_cubic(
@@ -4,6 +4,13 @@
 //! Our 26.6 format gives us 6 bits of fractional precision—enough to
 //! position text with surgical accuracy while keeping calculations fast.
 //! This is the secret sauce that makes rasterized text look smooth at any size.
+//!
+//! The 26 integer bits reach ±33 million pixels, far past any canvas, so a
+//! poster glyph at 10,000 px with 8x oversampling still has room to spare.
+//! Results that would leave the range anyway (a degenerate slope, a wild
+//! transform) saturate at [`F26Dot6::MIN`] or [`F26Dot6::MAX`] instead of
+//! wrapping around, which keeps an out-of-range edge off the canvas rather
+//! than teleporting it across.
 
 use std::ops::{Add, Neg, Sub};
 
@@ -42,18 +49,49 @@ impl F26Dot6 {
     /// The midpoint: exactly 0.5, perfect for rounding
     pub const HALF: F26Dot6 = F26Dot6(1 << (Self::FRAC_BITS - 1));
 
-    /// From whole number to fixed-point precision
+    /// The largest value, just under 33,554,432; overflow stops here
+    pub const MAX: F26Dot6 = F26Dot6(i32::MAX);
+
+    /// The smallest value, -33,554,432; underflow stops here
+    pub const MIN: F26Dot6 = F26Dot6(i32::MIN);
+
+    /// Clamp a wide intermediate back into range
+    #[inline]
+    const fn saturate(raw: i64) -> Self {
+        if raw > i32::MAX as i64 {
+            Self::MAX
+        } else if raw < i32::MIN as i64 {
+            Self::MIN
+        } else {
+            F26Dot6(raw as i32)
+        }
+    }
+
+    /// From whole number to fixed-point precision, saturating past ±33M
     #[inline]
     pub const fn from_int(x: i32) -> Self {
-        F26Dot6(x << Self::FRAC_BITS)
+        Self::saturate((x as i64) << Self::FRAC_BITS)
     }
 
-    /// Transform浮动点 precision into our fixed world
+    /// Transform floating-point precision into our fixed world
+    ///
+    /// Values past the range saturate; NaN becomes zero.
     #[inline]
     pub fn from_float(x: f32) -> Self {
         F26Dot6((x * 64.0) as i32)
     }
 
+    /// Snap to the nearest 1/64, halves away from zero
+    ///
+    /// [`from_float`](Self::from_float) truncates, so a coordinate one ulp
+    /// below a 1/64 boundary lands a whole step lower than one exactly on
+    /// it. Rounding only flips at half steps, which keeps last-bit float
+    /// differences between platforms out of the fixed-point result.
+    #[inline]
+    pub fn from_float_round(x: f32) -> Self {
+        F26Dot6((x * 64.0).round() as i32)
+    }
+
     /// Shed the fractional baggage: pure integer result
     #[inline]
     pub const fn to_int(self) -> i32 {
@@ -81,19 +119,35 @@ impl F26Dot6 {
     /// Precision multiplication: where fractions meet fractions
     #[inline]
     pub const fn mul(self, other: F26Dot6) -> F26Dot6 {
-        F26Dot6(((self.0 as i64 * other.0 as i64) >> Self::FRAC_BITS) as i32)
+        Self::saturate((self.0 as i64 * other.0 as i64) >> Self::FRAC_BITS)
     }
 
     /// Careful division: maintaining precision through the quotient
+    ///
+    /// Dividing by zero saturates toward the dividend's sign, as an
+    /// infinitely steep slope would.
     #[inline]
     pub const fn div(self, other: F26Dot6) -> F26Dot6 {
-        F26Dot6((((self.0 as i64) << Self::FRAC_BITS) / other.0 as i64) as i32)
+        if other.0 == 0 {
+            return match self.0 {
+                0 => Self::ZERO,
+                x if x > 0 => Self::MAX,
+                _ => Self::MIN,
+            };
+        }
+        Self::saturate(((self.0 as i64) << Self::FRAC_BITS) / other.0 as i64)
+    }
+
+    /// Halfway between two values, without overflowing on the way
+    #[inline]
+    pub const fn midpoint(self, other: F26Dot6) -> F26Dot6 {
+        F26Dot6(((self.0 as i64 + other.0 as i64) / 2) as i32)
     }
 
     /// Distance from zero: magnitude without direction
     #[inline]
     pub const fn abs(self) -> F26Dot6 {
-        F26Dot6(self.0.abs())
+        F26Dot6(self.0.saturating_abs())
     }
 
     /// Gravity's pull: always round down toward the earth
@@ -108,7 +162,7 @@ impl F26Dot6 {
         if self.0 & Self::FRAC_MASK == 0 {
             self
         } else {
-            F26Dot6((self.0 & !(Self::FRAC_MASK)) + Self::ONE.0)
+            F26Dot6((self.0 & !(Self::FRAC_MASK)).saturating_add(Self::ONE.0))
         }
     }
 
@@ -130,7 +184,7 @@ impl Add for F26Dot6 {
 
     #[inline]
     fn add(self, other: Self) -> Self {
-        F26Dot6(self.0 + other.0)
+        F26Dot6(self.0.saturating_add(other.0))
     }
 }
 
@@ -139,7 +193,7 @@ impl Sub for F26Dot6 {
 
     #[inline]
     fn sub(self, other: Self) -> Self {
-        F26Dot6(self.0 - other.0)
+        F26Dot6(self.0.saturating_sub(other.0))
     }
 }
 
@@ -148,7 +202,7 @@ impl Neg for F26Dot6 {
 
     #[inline]
     fn neg(self) -> Self {
-        F26Dot6(-self.0)
+        F26Dot6(self.0.saturating_neg())
     }
 }
 
@@ -192,6 +246,15 @@ mod tests {
         assert_eq!(F26Dot6::from_int(-3).raw(), -192);
     }
 
+    #[test]
+    fn test_from_float_round_when_just_below_boundary_then_snaps_up() {
+        let below = 6.0 / 64.0 - f32::EPSILON;
+        assert_eq!(F26Dot6::from_float(below).raw(), 5);
+        assert_eq!(F26Dot6::from_float_round(below).raw(), 6);
+        assert_eq!(F26Dot6::from_float_round(-2.5).raw(), -160);
+        assert_eq!(F26Dot6::from_float_round(-0.001).raw(), 0);
+    }
+
     #[test]
     fn test_from_float() {
         assert_eq!(F26Dot6::from_float(0.0).raw(), 0);
@@ -346,4 +409,37 @@ mod tests {
         let result = large.mul(small);
         assert_eq!(result.to_int(), 20000);
     }
+
+    #[test]
+    fn test_overflow_when_out_of_range_then_saturates() {
+        assert_eq!(F26Dot6::from_int(40_000_000), F26Dot6::MAX);
+        assert_eq!(F26Dot6::from_int(-40_000_000), F26Dot6::MIN);
+        assert_eq!(F26Dot6::from_float(1e12), F26Dot6::MAX);
+        assert_eq!(F26Dot6::from_float(f32::NAN), F26Dot6::ZERO);
+
+        assert_eq!(F26Dot6::MAX + F26Dot6::ONE, F26Dot6::MAX);
+        assert_eq!(F26Dot6::MIN - F26Dot6::ONE, F26Dot6::MIN);
+        assert_eq!(-F26Dot6::MIN, F26Dot6::MAX);
+        assert_eq!(F26Dot6::MIN.abs(), F26Dot6::MAX);
+        assert_eq!(F26Dot6::MAX.ceil(), F26Dot6::MAX);
+
+        let far = F26Dot6::from_int(1_000_000);
+        assert_eq!(far.mul(far), F26Dot6::MAX);
+        assert_eq!(far.mul(-far), F26Dot6::MIN);
+        // A near-horizontal slope: a million pixels across 1/64 of a row
+        assert_eq!(far.div(F26Dot6::from_raw(1)), F26Dot6::MAX);
+        assert_eq!(far.div(F26Dot6::ZERO), F26Dot6::MAX);
+        assert_eq!((-far).div(F26Dot6::ZERO), F26Dot6::MIN);
+        assert_eq!(F26Dot6::ZERO.div(F26Dot6::ZERO), F26Dot6::ZERO);
+    }
+
+    #[test]
+    fn test_midpoint_when_near_limits_then_exact() {
+        assert_eq!(F26Dot6::MAX.midpoint(F26Dot6::MAX), F26Dot6::MAX);
+        assert_eq!(F26Dot6::MIN.midpoint(F26Dot6::MIN), F26Dot6::MIN);
+        assert_eq!(
+            F26Dot6::from_int(3).midpoint(F26Dot6::from_int(5)),
+            F26Dot6::from_int(4)
+        );
+    }
 }
@@ -3,13 +3,20 @@
 //! When rendering text, the same glyph often appears multiple times. This cache
 //! stores rendered glyph bitmaps keyed by (font, glyph_id, size, variations) to
 //! avoid redundant rasterization work.
+//!
+//! Each entry remembers when it was last read, so long-running hosts can drop
+//! glyphs nobody has asked for lately with [`GlyphCache::trim_idle`].
 
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::RwLock;
+use std::time::Duration;
 
 use crate::rasterizer::GlyphBitmap;
+use typf_core::cache::{CacheClock, InvalidateFont};
+use typf_core::{AntialiasQuality, DropoutMode, Hinting, RenderParams, TextTransform};
 
 /// Cache key for rendered glyphs
 ///
@@ -18,22 +25,35 @@ use crate::rasterizer::GlyphBitmap;
 pub struct GlyphCacheKey {
     /// Hash of font data (identifies the font)
     pub font_id: u64,
+    /// Face within the font data, for TTC/OTC collections
+    pub face_index: u32,
     /// Glyph ID within the font
     pub glyph_id: u32,
     /// Size in fixed-point (size * 100 for hash stability)
     pub size: u32,
     /// Hash of variation coordinates
     pub variations_hash: u64,
+    /// Bit patterns of the outline transform matrix, zero when untransformed
+    pub transform: [u32; 4],
+    /// Oversampling level or analytic coverage
+    pub antialias_quality: AntialiasQuality,
+    /// Rasterized in deterministic mode
+    pub deterministic: bool,
+    /// Rasterized in strict mode, where oversized glyphs fail instead of clamping
+    pub strict: bool,
+    /// Rasterized without anti-aliasing
+    pub aliased: bool,
+    /// Hinting the outline was drawn with
+    pub hinting: Hinting,
+    /// Dropout control the outline was scan-converted with
+    pub dropout: DropoutMode,
+    /// Bit pattern of the gamma applied to the coverage
+    pub gamma: u32,
 }
 
 impl GlyphCacheKey {
     /// Create a new cache key
     pub fn new(font_data: &[u8], glyph_id: u32, size: f32, variations: &[(String, f32)]) -> Self {
-        // Hash font data
-        let mut hasher = DefaultHasher::new();
-        font_data.hash(&mut hasher);
-        let font_id = hasher.finish();
-
         // Hash variations
         let mut var_hasher = DefaultHasher::new();
         for (tag, val) in variations {
@@ -43,18 +63,88 @@ impl GlyphCacheKey {
         let variations_hash = var_hasher.finish();
 
         Self {
-            font_id,
+            font_id: font_id(font_data),
+            face_index: 0,
             glyph_id,
             size: (size * 100.0) as u32,
             variations_hash,
+            transform: [0; 4],
+            antialias_quality: AntialiasQuality::default(),
+            deterministic: false,
+            strict: false,
+            aliased: false,
+            hinting: Hinting::None,
+            dropout: DropoutMode::None,
+            gamma: 1.0f32.to_bits(),
         }
     }
+
+    /// Key a glyph of face `face_index` of a collection
+    pub fn with_face_index(mut self, face_index: u32) -> Self {
+        self.face_index = face_index;
+        self
+    }
+
+    /// Key a glyph rasterized under `transform`
+    pub fn with_transform(mut self, transform: Option<TextTransform>) -> Self {
+        self.transform = transform.map_or([0; 4], |t| {
+            [
+                t.xx.to_bits(),
+                t.yx.to_bits(),
+                t.xy.to_bits(),
+                t.yy.to_bits(),
+            ]
+        });
+        self
+    }
+
+    /// Key a glyph rasterized at `quality`
+    pub fn with_antialias_quality(mut self, quality: AntialiasQuality) -> Self {
+        self.antialias_quality = quality;
+        self
+    }
+
+    /// Key a glyph rasterized with or without deterministic mode
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Key a glyph rasterized with or without strict mode
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Key a glyph rasterized with `params`' anti-aliasing switch, hinting,
+    /// dropout control, and gamma
+    pub fn with_coverage_params(mut self, params: &RenderParams) -> Self {
+        self.aliased = !params.antialias;
+        self.hinting = params.hinting;
+        self.dropout = params.dropout;
+        self.gamma = params.gamma.to_bits();
+        self
+    }
+}
+
+/// Hash of font data, the `font_id` of its keys
+fn font_id(font_data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    font_data.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A cached bitmap and when it was last read, in milliseconds on the cache's clock
+struct CacheEntry {
+    bitmap: GlyphBitmap,
+    last_access: AtomicU64,
 }
 
 /// LRU-style glyph cache with configurable capacity
 pub struct GlyphCache {
-    cache: RwLock<HashMap<GlyphCacheKey, GlyphBitmap>>,
+    cache: RwLock<HashMap<GlyphCacheKey, CacheEntry>>,
     capacity: usize,
+    clock: CacheClock,
     hits: RwLock<u64>,
     misses: RwLock<u64>,
 }
@@ -65,19 +155,29 @@ impl GlyphCache {
         Self {
             cache: RwLock::new(HashMap::with_capacity(capacity)),
             capacity,
+            clock: CacheClock::default(),
             hits: RwLock::new(0),
             misses: RwLock::new(0),
         }
     }
 
+    /// Read idle times from `clock` instead of the process clock
+    pub fn with_clock(mut self, clock: CacheClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Get a cached glyph bitmap if available
     pub fn get(&self, key: &GlyphCacheKey) -> Option<GlyphBitmap> {
         let cache = self.cache.read().ok()?;
-        if let Some(bitmap) = cache.get(key) {
+        if let Some(entry) = cache.get(key) {
             if let Ok(mut hits) = self.hits.write() {
                 *hits += 1;
             }
-            Some(bitmap.clone())
+            entry
+                .last_access
+                .store(self.now_millis(), Ordering::Relaxed);
+            Some(entry.bitmap.clone())
         } else {
             if let Ok(mut misses) = self.misses.write() {
                 *misses += 1;
@@ -101,7 +201,29 @@ impl GlyphCache {
             }
         }
 
-        cache.insert(key, bitmap);
+        let entry = CacheEntry {
+            bitmap,
+            last_access: AtomicU64::new(self.now_millis()),
+        };
+        cache.insert(key, entry);
+    }
+
+    /// Drop glyphs not read or inserted within `max_age`, returning how many were removed
+    pub fn trim_idle(&self, max_age: Duration) -> usize {
+        let Ok(mut cache) = self.cache.write() else {
+            return 0;
+        };
+        let now = self.now_millis();
+        let max_age = max_age.as_millis().min(u64::MAX as u128) as u64;
+        let before = cache.len();
+        cache.retain(|_, entry| {
+            now.saturating_sub(entry.last_access.load(Ordering::Relaxed)) < max_age
+        });
+        before - cache.len()
+    }
+
+    fn now_millis(&self) -> u64 {
+        self.clock.now_millis()
     }
 
     /// Get cache hit rate (0.0 to 1.0)
@@ -135,6 +257,18 @@ impl GlyphCache {
     }
 }
 
+impl InvalidateFont for GlyphCache {
+    fn invalidate_font(&self, font_data: &[u8]) -> usize {
+        let Ok(mut cache) = self.cache.write() else {
+            return 0;
+        };
+        let font_id = font_id(font_data);
+        let before = cache.len();
+        cache.retain(|key, _| key.font_id != font_id);
+        before - cache.len()
+    }
+}
+
 /// Glyph cache statistics
 #[derive(Debug, Clone)]
 pub struct GlyphCacheStats {
@@ -169,6 +303,17 @@ mod tests {
         assert_ne!(key1, key3);
     }
 
+    #[test]
+    fn test_transform_affects_key() {
+        let plain = GlyphCacheKey::new(b"font", 65, 16.0, &[]);
+        let rotated = plain
+            .clone()
+            .with_transform(Some(TextTransform::rotate(30.0)));
+
+        assert_ne!(plain, rotated);
+        assert_eq!(plain, plain.clone().with_transform(None));
+    }
+
     #[test]
     fn test_variations_affect_key() {
         let key1 = GlyphCacheKey::new(b"font", 65, 16.0, &[("wght".to_string(), 400.0)]);
@@ -254,4 +399,48 @@ mod tests {
         assert_eq!(stats.hits, 2);
         assert!(stats.hit_rate() > 0.6);
     }
+
+    #[test]
+    fn test_trim_idle_when_entry_stale_then_removed_and_fresh_kept() {
+        let clock = CacheClock::manual();
+        let cache = GlyphCache::new(100).with_clock(clock.clone());
+        let bitmap = GlyphBitmap {
+            width: 1,
+            height: 1,
+            left: 0,
+            top: 1,
+            data: vec![255],
+        };
+        let stale = GlyphCacheKey::new(b"font", 1, 16.0, &[]);
+        let fresh = GlyphCacheKey::new(b"font", 2, 16.0, &[]);
+        cache.insert(stale.clone(), bitmap.clone());
+        cache.insert(fresh.clone(), bitmap);
+
+        clock.advance(Duration::from_millis(40));
+        assert!(cache.get(&fresh).is_some());
+
+        assert_eq!(cache.trim_idle(Duration::from_millis(20)), 1);
+        assert!(cache.get(&stale).is_none());
+        assert!(cache.get(&fresh).is_some());
+    }
+
+    #[test]
+    fn test_invalidate_font_when_font_replaced_then_only_its_glyphs_dropped() {
+        let cache = GlyphCache::new(100);
+        let bitmap = GlyphBitmap {
+            width: 1,
+            height: 1,
+            left: 0,
+            top: 1,
+            data: vec![255],
+        };
+        let stale = GlyphCacheKey::new(b"old font", 1, 16.0, &[]);
+        let other = GlyphCacheKey::new(b"other font", 1, 16.0, &[]);
+        cache.insert(stale.clone(), bitmap.clone());
+        cache.insert(other.clone(), bitmap);
+
+        assert_eq!(cache.invalidate_font(b"old font"), 1);
+        assert!(cache.get(&stale).is_none());
+        assert!(cache.get(&other).is_some());
+    }
 }
@@ -5,7 +5,9 @@
 //! to create smooth edges that please the eye. This module transforms
 //! monochrome precision into 256 levels of visual perfection.
 
-use crate::scan_converter::ScanConverter;
+use crate::curves::{subdivide_cubic, subdivide_quadratic};
+use crate::fixed::F26Dot6;
+use crate::scan_converter::{PathSink, ScanConverter, Span};
 
 /// The quality spectrum: how smooth do you want your text?
 ///
@@ -19,6 +21,8 @@ pub enum GrayscaleLevel {
     Level4x4 = 4,
     /// Perfectionist: 8x8 oversampling for magazine-quality text
     Level8x8 = 8,
+    /// Low-DPI polish: 16x16 oversampling, every step of gray an edge can take
+    Level16x16 = 16,
 }
 
 impl GrayscaleLevel {
@@ -34,8 +38,15 @@ impl GrayscaleLevel {
     }
 
     /// The highest alpha value achievable at this quality level
+    ///
+    /// The sample count, saturating at 255 for 16x16.
     pub const fn max_alpha(self) -> u8 {
-        self.samples_per_pixel() as u8
+        let samples = self.samples_per_pixel();
+        if samples > u8::MAX as usize {
+            u8::MAX
+        } else {
+            samples as u8
+        }
     }
 }
 
@@ -136,10 +147,59 @@ fn downsample_to_grayscale_simd(
     output
 }
 
+/// Wide downsampling: count samples a row at a time with the SIMD kernels
+///
+/// Instead of visiting every `factor x factor` block separately, we add the
+/// `factor` oversampled rows of an output row into per-column counts with
+/// [`crate::simd::accumulate_coverage`], then sum `factor` neighbouring
+/// counts per pixel. The result is identical to the scalar path.
+#[cfg(all(
+    any(target_arch = "x86_64", target_arch = "aarch64"),
+    not(target_feature = "simd128")
+))]
+fn downsample_to_grayscale_wide(
+    mono: &[u8],
+    mono_width: usize,
+    mono_height: usize,
+    out_width: usize,
+    out_height: usize,
+    level: GrayscaleLevel,
+) -> Vec<u8> {
+    let factor = level.factor();
+    let max_coverage = level.samples_per_pixel() as u32;
+
+    let mut output = vec![0u8; out_width * out_height];
+    let mut columns = vec![0u8; mono_width];
+
+    for (out_y, out_row) in output.chunks_exact_mut(out_width.max(1)).enumerate() {
+        columns.fill(0);
+        for y in (out_y * factor..(out_y + 1) * factor).take_while(|&y| y < mono_height) {
+            let row = &mono[y * mono_width..(y + 1) * mono_width];
+            crate::simd::accumulate_coverage(&mut columns, row);
+        }
+
+        for (out_x, alpha) in out_row.iter_mut().enumerate() {
+            let start = (out_x * factor).min(mono_width);
+            let end = (start + factor).min(mono_width);
+            let coverage: u32 = columns[start..end].iter().map(|&c| c as u32).sum();
+            *alpha = ((coverage * 255) / max_coverage) as u8;
+        }
+    }
+
+    output
+}
+
 /// The reliable workhorse: pixel-by-pixel grayscale transformation
 ///
 /// When SIMD isn't available, we fall back to careful scalar processing.
 /// Slower, but compatible with every CPU and equally precise.
+#[cfg_attr(
+    all(
+        any(target_arch = "x86_64", target_arch = "aarch64"),
+        not(target_feature = "simd128")
+    ),
+    allow(dead_code)
+)]
 fn downsample_to_grayscale_scalar(
     mono: &[u8],
     mono_width: usize,
@@ -183,8 +243,9 @@ fn downsample_to_grayscale_scalar(
 
 /// Choose your weapon: SIMD or scalar, automatically selected
 ///
-/// We detect CPU capabilities at compile time and choose the fastest
-/// available implementation. No configuration needed—just performance.
+/// WebAssembly SIMD is chosen at compile time; on x86_64 and aarch64 the
+/// wide path picks its kernel at runtime. No configuration needed—just
+/// performance.
 #[inline]
 fn downsample_to_grayscale(
     mono: &[u8],
@@ -198,7 +259,18 @@ fn downsample_to_grayscale(
     {
         downsample_to_grayscale_simd(mono, mono_width, mono_height, out_width, out_height, level)
     }
-    #[cfg(not(target_feature = "simd128"))]
+    #[cfg(all(
+        any(target_arch = "x86_64", target_arch = "aarch64"),
+        not(target_feature = "simd128")
+    ))]
+    {
+        downsample_to_grayscale_wide(mono, mono_width, mono_height, out_width, out_height, level)
+    }
+    #[cfg(not(any(
+        target_feature = "simd128",
+        target_arch = "x86_64",
+        target_arch = "aarch64"
+    )))]
     {
         downsample_to_grayscale_scalar(mono, mono_width, mono_height, out_width, out_height, level)
     }
@@ -241,6 +313,242 @@ pub fn render_grayscale_direct(
     )
 }
 
+/// Anti-aliased coverage spans, without a mask
+///
+/// Like [`render_grayscale_direct`], but hands `emit` runs of equal,
+/// nonzero coverage in output pixels instead of returning a bitmap, top row
+/// first and left to right. Only one row of per-pixel sample counts is held
+/// at a time, so memory stays at `width` words however tall the glyph is.
+/// The coverage values match [`render_grayscale_direct`] exactly.
+pub fn render_grayscale_spans(
+    width: usize,
+    height: usize,
+    level: GrayscaleLevel,
+    build_outline: impl FnOnce(&mut ScanConverter),
+    mut emit: impl FnMut(Span),
+) {
+    let factor = level.factor();
+    let max_coverage = level.samples_per_pixel() as u32;
+
+    let mut sc = ScanConverter::new(width * factor, height * factor);
+    build_outline(&mut sc);
+
+    // Samples per output pixel on the row being gathered
+    let mut counts = vec![0u16; width];
+    let mut row: Option<u32> = None;
+    let mut flush = |row: u32, counts: &mut [u16]| {
+        let mut x = 0;
+        while x < counts.len() {
+            let alpha = ((counts[x] as u32 * 255) / max_coverage) as u8;
+            let start = x;
+            while x < counts.len() && ((counts[x] as u32 * 255) / max_coverage) as u8 == alpha {
+                x += 1;
+            }
+            if alpha > 0 {
+                emit(Span {
+                    y: row,
+                    x: start as u32,
+                    len: (x - start) as u32,
+                    coverage: alpha,
+                });
+            }
+        }
+        counts.fill(0);
+    };
+
+    sc.render_spans(|span| {
+        let out_y = span.y / factor as u32;
+        if let Some(previous) = row.filter(|&r| r != out_y) {
+            flush(previous, &mut counts);
+        }
+        row = Some(out_y);
+        for x in span.x..span.x + span.len {
+            if let Some(count) = counts.get_mut(x as usize / factor) {
+                *count += 1;
+            }
+        }
+    });
+    if let Some(last) = row {
+        flush(last, &mut counts);
+    }
+}
+
+/// Exact coverage: the area of each pixel an outline encloses
+///
+/// Oversampling counts which of a grid of points fall inside; this adds up
+/// the signed area every line segment sweeps across each pixel and
+/// integrates along the row, so a pixel half covered reads 128 however the
+/// edge crosses it. One `f32` per pixel replaces `n * n` samples. Overlaps
+/// count as nonzero winding: coverage is the absolute winding area, capped
+/// at one.
+#[derive(Debug)]
+pub struct AreaCoverage {
+    width: usize,
+    height: usize,
+    /// Signed area deltas; their running sum along a row is the coverage
+    deltas: Vec<f32>,
+    current: (F26Dot6, F26Dot6),
+    contour_start: (F26Dot6, F26Dot6),
+}
+
+impl AreaCoverage {
+    /// An empty `width` x `height` canvas.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            // A segment ending on the right edge writes one slot past its row
+            deltas: vec![0.0; width * height + 2],
+            current: (F26Dot6::ZERO, F26Dot6::ZERO),
+            contour_start: (F26Dot6::ZERO, F26Dot6::ZERO),
+        }
+    }
+
+    /// Add the area line `(x0, y0)`-`(x1, y1)` sweeps to its left.
+    fn add_line(&mut self, from: (F26Dot6, F26Dot6), to: (F26Dot6, F26Dot6)) {
+        let (x0, y0) = (from.0.to_float(), from.1.to_float());
+        let (x1, y1) = (to.0.to_float(), to.1.to_float());
+        if y0 == y1 {
+            return;
+        }
+        let (dir, x0, y0, x1, y1) = if y0 < y1 {
+            (1.0, x0, y0, x1, y1)
+        } else {
+            (-1.0, x1, y1, x0, y0)
+        };
+        let (width, height) = (self.width as f32, self.height as f32);
+        if y1 <= 0.0 || y0 >= height {
+            return;
+        }
+        let dxdy = (x1 - x0) / (y1 - y0);
+        let top = y0.max(0.0);
+        let mut x = x0 + (top - y0) * dxdy;
+        for row in top as usize..(y1.ceil().min(height) as usize) {
+            let dy = ((row + 1) as f32).min(y1) - (row as f32).max(y0);
+            let x_next = x + dxdy * dy;
+            let d = dy * dir;
+            // Ink left of the canvas lands in its first column, as a
+            // running sum from the left edge would count it
+            let (a, b) = (x.clamp(0.0, width), x_next.clamp(0.0, width));
+            let (left, right) = if a < b { (a, b) } else { (b, a) };
+            let start = row * self.width;
+            let left_floor = left.floor();
+            let left_col = left_floor as usize;
+            let right_ceil = right.ceil();
+            let right_col = right_ceil as usize;
+            if right_col <= left_col + 1 {
+                // Within one pixel: split by where the segment's middle sits
+                let mid = 0.5 * (a + b) - left_floor;
+                self.deltas[start + left_col] += d - d * mid;
+                self.deltas[start + left_col + 1] += d * mid;
+            } else {
+                // Across several pixels: a ramp from the first to the last
+                let slope = (right - left).recip();
+                let left_frac = left - left_floor;
+                let first = 0.5 * slope * (1.0 - left_frac) * (1.0 - left_frac);
+                let right_frac = right - right_ceil + 1.0;
+                let last = 0.5 * slope * right_frac * right_frac;
+                self.deltas[start + left_col] += d * first;
+                if right_col == left_col + 2 {
+                    self.deltas[start + left_col + 1] += d * (1.0 - first - last);
+                } else {
+                    let second = slope * (1.5 - left_frac);
+                    self.deltas[start + left_col + 1] += d * (second - first);
+                    for col in left_col + 2..right_col - 1 {
+                        self.deltas[start + col] += d * slope;
+                    }
+                    let before_last = second + (right_col - left_col - 3) as f32 * slope;
+                    self.deltas[start + right_col - 1] += d * (1.0 - before_last - last);
+                }
+                self.deltas[start + right_col] += d * last;
+            }
+            x = x_next;
+        }
+    }
+
+    /// Coverage per pixel, 0 to 255, top row first.
+    pub fn into_coverage(self) -> Vec<u8> {
+        let mut sum = 0.0f32;
+        self.deltas[..self.width * self.height]
+            .iter()
+            .map(|&delta| {
+                sum += delta;
+                (sum.abs().min(1.0) * 255.0 + 0.5) as u8
+            })
+            .collect()
+    }
+}
+
+impl PathSink for AreaCoverage {
+    fn move_to(&mut self, x: F26Dot6, y: F26Dot6) {
+        self.current = (x, y);
+        self.contour_start = (x, y);
+    }
+
+    fn line_to(&mut self, x: F26Dot6, y: F26Dot6) {
+        self.add_line(self.current, (x, y));
+        self.current = (x, y);
+    }
+
+    fn quadratic_to(&mut self, x1: F26Dot6, y1: F26Dot6, x2: F26Dot6, y2: F26Dot6) {
+        let (x0, y0) = self.current;
+        subdivide_quadratic(
+            x0,
+            y0,
+            x1,
+            y1,
+            x2,
+            y2,
+            &mut |x, y| PathSink::line_to(self, x, y),
+            0,
+        );
+    }
+
+    fn cubic_to(
+        &mut self,
+        x1: F26Dot6,
+        y1: F26Dot6,
+        x2: F26Dot6,
+        y2: F26Dot6,
+        x3: F26Dot6,
+        y3: F26Dot6,
+    ) {
+        let (x0, y0) = self.current;
+        subdivide_cubic(
+            x0,
+            y0,
+            x1,
+            y1,
+            x2,
+            y2,
+            x3,
+            y3,
+            &mut |x, y| PathSink::line_to(self, x, y),
+            0,
+        );
+    }
+
+    fn close(&mut self) {
+        if self.current != self.contour_start {
+            self.add_line(self.current, self.contour_start);
+        }
+        self.current = self.contour_start;
+    }
+}
+
+/// Exact-area anti-aliasing, the analytic counterpart of [`render_grayscale_direct`]
+///
+/// The outline is drawn at output resolution; no oversampling.
+pub fn render_analytic(
+    width: usize,
+    height: usize,
+    build_outline: impl FnOnce(&mut AreaCoverage),
+) -> Vec<u8> {
+    let mut coverage = AreaCoverage::new(width, height);
+    build_outline(&mut coverage);
+    coverage.into_coverage()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -321,6 +629,43 @@ mod tests {
         assert!(gray[0] < 50, "Corner alpha = {}", gray[0]);
     }
 
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    #[test]
+    fn test_downsample_when_wide_path_then_matches_scalar() {
+        // Odd widths leave a partial last block and a SIMD tail
+        let (mono_width, mono_height) = (203usize, 37usize);
+        let mono: Vec<u8> = (0..mono_width * mono_height)
+            .map(|i| ((i * 7919) % 13 < 6) as u8)
+            .collect();
+
+        for level in [
+            GrayscaleLevel::Level2x2,
+            GrayscaleLevel::Level4x4,
+            GrayscaleLevel::Level8x8,
+        ] {
+            let factor = level.factor();
+            let (out_width, out_height) =
+                (mono_width.div_ceil(factor), mono_height.div_ceil(factor));
+            let scalar = downsample_to_grayscale_scalar(
+                &mono,
+                mono_width,
+                mono_height,
+                out_width,
+                out_height,
+                level,
+            );
+            let wide = downsample_to_grayscale(
+                &mono,
+                mono_width,
+                mono_height,
+                out_width,
+                out_height,
+                level,
+            );
+            assert_eq!(wide, scalar, "level {:?}", level);
+        }
+    }
+
     #[test]
     fn test_render_grayscale_levels() {
         // Test different oversampling levels
@@ -350,4 +695,85 @@ mod tests {
             assert!(filled > 0, "Level {:?} has no filled pixels", level);
         }
     }
+
+    #[test]
+    fn test_render_grayscale_spans_match_direct() {
+        for level in [
+            GrayscaleLevel::Level2x2,
+            GrayscaleLevel::Level4x4,
+            GrayscaleLevel::Level8x8,
+        ] {
+            let factor = level.factor() as i32;
+            // A slanted triangle, so edges land on partial coverage
+            let triangle = |sc: &mut ScanConverter| {
+                sc.move_to(F26Dot6::from_int(factor), F26Dot6::from_int(factor));
+                sc.line_to(F26Dot6::from_int(9 * factor), F26Dot6::from_int(2 * factor));
+                sc.line_to(F26Dot6::from_int(4 * factor), F26Dot6::from_int(9 * factor));
+                sc.close();
+            };
+            let gray = render_grayscale_direct(10, 10, level, triangle);
+
+            let mut painted = vec![0u8; 100];
+            let mut last = None;
+            render_grayscale_spans(10, 10, level, triangle, |span| {
+                assert!(span.coverage > 0);
+                assert!(last < Some((span.y, span.x)), "spans out of order");
+                last = Some((span.y, span.x));
+                let start = (span.y * 10 + span.x) as usize;
+                painted[start..start + span.len as usize].fill(span.coverage);
+            });
+            assert_eq!(painted, gray, "{level:?}");
+            assert!(gray.iter().any(|&a| a > 0 && a < 255), "{level:?}");
+        }
+    }
+
+    #[test]
+    fn test_render_analytic_when_edges_split_pixels_then_exact_area() {
+        // x from 1.25 to 3.5, y from 0.5 to 2: partial columns and rows
+        let gray = render_analytic(5, 3, |area| {
+            area.move_to(F26Dot6::from_raw(80), F26Dot6::from_raw(32));
+            area.line_to(F26Dot6::from_raw(224), F26Dot6::from_raw(32));
+            area.line_to(F26Dot6::from_raw(224), F26Dot6::from_int(2));
+            area.line_to(F26Dot6::from_raw(80), F26Dot6::from_int(2));
+            area.close();
+        });
+        #[rustfmt::skip]
+        let expected = [
+            0, 96, 128, 64, 0,
+            0, 191, 255, 128, 0,
+            0, 0, 0, 0, 0,
+        ];
+        assert_eq!(gray, expected);
+    }
+
+    #[test]
+    fn test_render_analytic_when_slanted_then_matches_fine_oversampling() {
+        let gray = render_analytic(10, 10, |area| {
+            area.move_to(F26Dot6::from_raw(70), F26Dot6::from_raw(40));
+            area.line_to(F26Dot6::from_raw(600), F26Dot6::from_raw(150));
+            area.line_to(F26Dot6::from_raw(250), F26Dot6::from_raw(610));
+            area.close();
+        });
+        let level = GrayscaleLevel::Level16x16;
+        let sampled = render_grayscale_direct(10, 10, level, |sc| {
+            sc.move_to(F26Dot6::from_raw(70 * 16), F26Dot6::from_raw(40 * 16));
+            sc.line_to(F26Dot6::from_raw(600 * 16), F26Dot6::from_raw(150 * 16));
+            sc.line_to(F26Dot6::from_raw(250 * 16), F26Dot6::from_raw(610 * 16));
+            sc.close();
+        });
+        for (i, (&a, &b)) in gray.iter().zip(&sampled).enumerate() {
+            assert!(a.abs_diff(b) <= 24, "pixel {i}: analytic {a} vs 16x16 {b}");
+        }
+        assert!(gray.iter().any(|&a| a > 0 && a < 255));
+    }
+
+    #[test]
+    fn test_level16x16_when_fully_covered_then_opaque() {
+        let level = GrayscaleLevel::Level16x16;
+        assert_eq!(level.samples_per_pixel(), 256);
+        assert_eq!(level.max_alpha(), 255);
+        let mono = vec![1u8; 32 * 32];
+        let gray = downsample_to_grayscale(&mono, 32, 32, 2, 2, level);
+        assert_eq!(gray, [255; 4]);
+    }
 }
@@ -1,13 +1,15 @@
 //! Monochrome rasterizer for Typf.
 //!
 //! Opixa is the pure-Rust renderer that turns shaped glyph outlines into pixel
-//! coverage data. It is focused on predictable outline rasterization rather than
-//! color-glyph support. The submodules divide that work into fixed-point math,
-//! curve flattening, edge handling, scan conversion, and optional SIMD or
-//! parallel acceleration.
+//! coverage data. It is focused on predictable outline rasterization; color
+//! glyphs (COLR, SVG, sbix, CBDT) are painted by typf-render-color under the
+//! default `color` feature. The submodules divide that work into fixed-point
+//! math, curve flattening, edge handling, scan conversion, and optional SIMD
+//! or parallel acceleration.
 
 use std::sync::Arc;
 
+mod color;
 pub mod curves;
 pub mod edge;
 pub mod fixed;
@@ -25,30 +27,53 @@ pub enum FillRule {
     EvenOdd,
 }
 
-/// Strategy for preserving very thin strokes.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum DropoutMode {
-    /// Do not apply dropout handling.
-    None,
-    /// Basic protection for thin strokes that might otherwise disappear.
-    Simple,
-    /// More expensive dropout handling for extreme small-size rendering.
-    Smart,
-}
+/// Strategy for preserving very thin strokes, from
+/// [`RenderParams::dropout`].
+pub use typf_core::DropoutMode;
 
 use typf_core::{
+    budget::{self, Reservation},
+    cancel, color_span,
+    diagnostics::{self, DiagnosticKind},
+    effects::CoverageMask,
     error::{RenderError, Result},
+    render_target::{self, BufferTarget, ClipTarget, DirtyRect, GlyphCull, RenderTarget},
+    source_log,
     traits::{FontRef, Renderer},
-    types::{BitmapData, BitmapFormat, RenderOutput, ShapingResult},
-    Color, GlyphSource, RenderParams,
+    types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput, ShapingResult},
+    Color, GlyphSource, Insets, RenderMode, RenderParams, TextTransform,
 };
 
 #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
-mod simd;
+pub mod simd;
 
 #[cfg(feature = "parallel")]
 pub mod parallel;
 
+/// One glyph from [`OpixaRenderer::rasterize_glyph`], placed relative to
+/// its pen position on the baseline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlyphMask {
+    pub width: u32,
+    pub height: u32,
+    /// Columns from the pen to the left edge
+    pub left: i32,
+    /// Rows from the baseline up to the top edge
+    pub top: i32,
+    /// Coverage, `width` by `height`, top row first; for color glyphs, the
+    /// alpha of `color`
+    pub coverage: Vec<u8>,
+    /// Premultiplied RGBA when the font paints the glyph in color
+    pub color: Option<Vec<u8>>,
+}
+
+impl GlyphMask {
+    /// Whether the glyph has no ink, like a space.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
 /// Renderer that rasterizes outline glyphs into bitmaps.
 ///
 /// It applies scan conversion to glyph outlines and composites the resulting
@@ -59,6 +84,7 @@ pub struct OpixaRenderer {
     max_height: u32,
     max_pixels: u64,
     cache: Option<Arc<glyph_cache::GlyphCache>>,
+    default_padding: u32,
 }
 
 impl OpixaRenderer {
@@ -68,6 +94,7 @@ impl OpixaRenderer {
             max_height: typf_core::get_max_bitmap_height(),
             max_pixels: typf_core::get_max_bitmap_pixels(),
             cache: None,
+            default_padding: 0,
         }
     }
 
@@ -81,9 +108,22 @@ impl OpixaRenderer {
             max_height: typf_core::get_max_bitmap_height(),
             max_pixels: typf_core::get_max_bitmap_pixels(),
             cache: Some(Arc::new(glyph_cache::GlyphCache::new(capacity))),
+            default_padding: 0,
         }
     }
 
+    /// Minimum padding on every side, used when `RenderParams::padding` is smaller.
+    pub fn with_default_padding(mut self, padding: u32) -> Self {
+        self.default_padding = padding;
+        self
+    }
+
+    /// The glyph cache, if any, e.g. to subscribe it to a font database's
+    /// reloads so replaced fonts don't keep serving old bitmaps
+    pub fn glyph_cache(&self) -> Option<Arc<glyph_cache::GlyphCache>> {
+        self.cache.clone()
+    }
+
     pub fn cache_stats(&self) -> Option<glyph_cache::GlyphCacheStats> {
         self.cache.as_ref().map(|c| c.stats())
     }
@@ -104,6 +144,110 @@ impl OpixaRenderer {
         parallel::ParallelRenderer::new()
     }
 
+    /// Rasterize one glyph at `size` pixels per em, without a run or canvas.
+    ///
+    /// For glyph atlases and other callers that place glyphs themselves. The
+    /// params' glyph sources, variations, transform, cancellation, and memory
+    /// budget apply, and outline glyphs go through the renderer's cache;
+    /// padding, colors, effects, and the output format do not. A glyph with
+    /// no ink, like a space, comes back as an empty mask.
+    pub fn rasterize_glyph(
+        &self,
+        font: &dyn FontRef,
+        glyph_id: u32,
+        size: f32,
+        params: &RenderParams,
+    ) -> Result<GlyphMask> {
+        check_glyph_sources(params)?;
+        cancel::check(params.cancel.as_ref())?;
+        let font_data = font.data();
+        let transform = params.active_transform()?;
+
+        let painted = match color::ColorGlyphs::new(font_data, font.face_index(), size, params) {
+            Some(source) => source.render(glyph_id)?,
+            None => None,
+        };
+        let (bitmap, color) = match painted {
+            Some(painted) => (painted.bitmap, Some(painted.rgba)),
+            None if !allows_outline(params) => {
+                return Err(RenderError::BackendError(format!(
+                    "outline glyph sources disabled and glyph {glyph_id} has no color glyph"
+                ))
+                .into())
+            },
+            None => {
+                let rasterizer =
+                    glyph_rasterizer(font_data, font.face_index(), size, params, transform)?;
+                let outline = self.outline_glyph(
+                    rasterizer.as_ref(),
+                    font,
+                    glyph_id,
+                    size,
+                    transform,
+                    params,
+                );
+                match outline {
+                    Ok(bitmap) => (bitmap, None),
+                    Err(e) => {
+                        cancel::check(params.cancel.as_ref())?;
+                        return Err(RenderError::BackendError(format!(
+                            "Glyph {glyph_id} rasterization failed: {e}"
+                        ))
+                        .into());
+                    },
+                }
+            },
+        };
+        budget::reserve(
+            params.memory_budget.as_ref(),
+            bitmap.data.len() + color.as_ref().map_or(0, Vec::len),
+        )?;
+        Ok(GlyphMask {
+            width: bitmap.width,
+            height: bitmap.height,
+            left: bitmap.left,
+            top: bitmap.top,
+            coverage: bitmap.data,
+            color,
+        })
+    }
+
+    /// Rasterize an outline glyph, through the cache when there is one.
+    ///
+    /// Errors are the glyph's own, for the caller to skip or report.
+    fn outline_glyph(
+        &self,
+        rasterizer: Option<&rasterizer::GlyphRasterizer<'_>>,
+        font: &dyn FontRef,
+        glyph_id: u32,
+        size: f32,
+        transform: Option<TextTransform>,
+        params: &RenderParams,
+    ) -> std::result::Result<rasterizer::GlyphBitmap, String> {
+        let cache_key = self.cache.as_ref().map(|_| {
+            glyph_cache::GlyphCacheKey::new(font.data(), glyph_id, size, &params.variations)
+                .with_face_index(font.face_index())
+                .with_transform(transform)
+                .with_antialias_quality(params.antialias_quality)
+                .with_deterministic(params.deterministic)
+                .with_strict(params.strict)
+                .with_coverage_params(params)
+        });
+        if let (Some(cache), Some(key)) = (&self.cache, &cache_key) {
+            if let Some(cached) = cache.get(key) {
+                return Ok(cached);
+            }
+        }
+        let rasterizer = rasterizer.ok_or("no rasterizer available")?;
+        let mut bitmap =
+            rasterizer.render_glyph(glyph_id, FillRule::NonZeroWinding, params.dropout)?;
+        apply_gamma(&mut bitmap.data, params.gamma);
+        if let (Some(cache), Some(key)) = (&self.cache, cache_key) {
+            cache.insert(key, bitmap.clone());
+        }
+        Ok(bitmap)
+    }
+
     /// Blend one rasterized glyph bitmap onto the destination canvas.
     fn composite_glyph(
         &self,
@@ -198,139 +342,154 @@ impl OpixaRenderer {
             }
         }
     }
-}
-
-impl Default for OpixaRenderer {
-    fn default() -> Self {
-        Self::new()
-    }
-}
 
-impl Renderer for OpixaRenderer {
-    fn name(&self) -> &'static str {
-        "opixa"
-    }
-
-    fn render(
+    /// Rasterize every glyph and size the canvas.
+    ///
+    /// Shared by `render`, which composites into a fresh canvas, and
+    /// `render_into`, which composites into a caller's target.
+    fn layout(
         &self,
         shaped: &ShapingResult,
-        font: Arc<dyn FontRef>,
+        font: &Arc<dyn FontRef>,
         params: &RenderParams,
-    ) -> Result<RenderOutput> {
-        log::debug!("OpixaRenderer: Rendering {} glyphs", shaped.glyphs.len());
-
-        let allows_outline = params
-            .glyph_sources
-            .effective_order()
-            .iter()
-            .any(|s| matches!(s, GlyphSource::Glyf | GlyphSource::Cff | GlyphSource::Cff2));
-        if !allows_outline {
-            return Err(RenderError::BackendError(
-                "opixa renderer requires outline glyph sources".to_string(),
-            )
-            .into());
-        }
-
+        cull: Option<&GlyphCull>,
+    ) -> Result<Layout> {
         let font_data = font.data();
-        let padding = params.padding as f32;
+        let insets = params
+            .canvas_insets(self.default_padding)
+            .grow(Insets::uniform(sdf_spread(params).unwrap_or(0)));
+        let pad_top = insets.top as f32;
         let glyph_size = shaped.advance_height;
+        let transform = params.active_transform()?;
 
         let mut rendered_glyphs: Vec<RenderedGlyph> = Vec::new();
+        let mut memory = budget::reserve(params.memory_budget.as_ref(), 0)?;
+        let mut culled = 0usize;
         let mut min_y: f32 = 0.0;
         let mut max_y: f32 = 0.0;
+        // Horizontal ink extent, only needed to fit transformed runs
+        let mut min_x = f32::MAX;
+        let mut max_x = f32::MIN;
 
-        let mut rasterizer = if !shaped.glyphs.is_empty() {
-            match rasterizer::GlyphRasterizer::new(font_data, glyph_size) {
-                Ok(mut r) => {
-                    if !params.variations.is_empty() {
-                        if let Err(e) = r.set_variations(&params.variations) {
-                            log::warn!("Variable font setup failed: {}", e);
-                        }
-                    }
-                    Some(r)
-                },
-                Err(e) => {
-                    log::warn!("Failed to create rasterizer: {}", e);
-                    None
-                },
-            }
+        let color_glyphs =
+            color::ColorGlyphs::new(font_data, font.face_index(), glyph_size, params);
+        let outline_allowed = allows_outline(params);
+        diagnostics::check_variations(params.diagnostics.as_ref(), &**font, &params.variations);
+
+        let rasterizer = if !shaped.glyphs.is_empty() {
+            glyph_rasterizer(font_data, font.face_index(), glyph_size, params, transform)?
         } else {
             None
         };
 
-        for glyph in &shaped.glyphs {
-            let glyph_bitmap = if let Some(ref cache) = self.cache {
-                let cache_key = glyph_cache::GlyphCacheKey::new(
-                    font_data,
+        for (index, glyph) in shaped.glyphs.iter().enumerate() {
+            cancel::check(params.cancel.as_ref())?;
+            // Transformed pens only map to canvas columns after layout, so
+            // clipped redraws of transformed runs keep every glyph.
+            let skip = cull.filter(|c| {
+                transform.is_none() && !c.keeps(insets.left as f32 + glyph.x, glyph.advance)
+            });
+            if let Some(c) = skip {
+                // Measured instead of drawn, so the line box matches a full render
+                let extent = c.skipped_extent(&**font, glyph.id, glyph_size, &params.variations);
+                if let Some((top, bottom)) = extent {
+                    let lift = glyph.y + params.baseline_shift;
+                    max_y = max_y.max(lift + top);
+                    min_y = min_y.min(lift + bottom);
+                }
+                culled += 1;
+                continue;
+            }
+            let painted = match &color_glyphs {
+                Some(source) => source.render(glyph.id)?,
+                None => None,
+            };
+            let (glyph_bitmap, color, source) = match painted {
+                Some(painted) => (painted.bitmap, Some(painted.rgba), painted.source),
+                None if !outline_allowed => {
+                    return Err(RenderError::BackendError(
+                        "outline glyph sources disabled and no color glyph available".to_string(),
+                    )
+                    .into())
+                },
+                None => match self.outline_glyph(
+                    rasterizer.as_ref(),
+                    &**font,
                     glyph.id,
                     glyph_size,
-                    &params.variations,
-                );
-
-                if let Some(cached) = cache.get(&cache_key) {
-                    cached
-                } else {
-                    let Some(ref mut rast) = rasterizer else {
-                        log::warn!("Skipping glyph {} (no rasterizer available)", glyph.id);
-                        continue;
-                    };
-
-                    let bitmap = match rast.render_glyph(
-                        glyph.id,
-                        FillRule::NonZeroWinding,
-                        DropoutMode::None,
-                    ) {
-                        Ok(b) => b,
-                        Err(e) => {
-                            log::warn!("Glyph {} rasterization failed: {}", glyph.id, e);
-                            continue;
-                        },
-                    };
-
-                    cache.insert(cache_key, bitmap.clone());
-                    bitmap
-                }
-            } else {
-                let Some(ref mut rast) = rasterizer else {
-                    log::warn!("Skipping glyph {} (no rasterizer available)", glyph.id);
-                    continue;
-                };
-
-                match rast.render_glyph(glyph.id, FillRule::NonZeroWinding, DropoutMode::None) {
-                    Ok(bitmap) => bitmap,
+                    transform,
+                    params,
+                ) {
+                    Ok(bitmap) => (
+                        bitmap,
+                        None,
+                        source_log::outline_source(font_data, font.face_index()),
+                    ),
                     Err(e) => {
-                        log::warn!("Glyph {} rasterization failed: {}", glyph.id, e);
+                        // A cancelled glyph fails too; report that, not the glyph
+                        cancel::check(params.cancel.as_ref())?;
+                        glyph_failed(glyph.id, &e, params.strict)?;
+                        diagnostics::warn(
+                            params.diagnostics.as_ref(),
+                            DiagnosticKind::GlyphSkipped,
+                            Some(glyph.id),
+                            format!("skipped: {e}"),
+                        );
                         continue;
                     },
-                }
+                },
             };
 
             if glyph_bitmap.width == 0 || glyph_bitmap.height == 0 {
                 continue;
             }
+            memory.grow(glyph_bitmap.data.len() + color.as_ref().map_or(0, Vec::len))?;
+            if let Some(log) = &params.glyph_source_log {
+                log.record(index, glyph.id, source);
+            }
 
-            let glyph_top = glyph.y + glyph_bitmap.top as f32;
-            let glyph_bottom = glyph.y + glyph_bitmap.top as f32 - glyph_bitmap.height as f32;
+            // Compositing treats `glyph.y` as a downward offset; flip it into
+            // y-up for the matrix and back. The baseline shift raises the pen
+            // before the transform, so rotated runs shift along their normal.
+            let shift = params.baseline_shift;
+            let (glyph_x, glyph_y) = match transform {
+                Some(t) => {
+                    let (x, y) = t.apply(glyph.x, shift - glyph.y);
+                    (x, -y)
+                },
+                None => (glyph.x, glyph.y - shift),
+            };
+
+            // Transformed runs measure from the pen they are composited at
+            let lift = if transform.is_some() {
+                -glyph_y
+            } else {
+                glyph.y + shift
+            };
+            let glyph_top = lift + glyph_bitmap.top as f32;
+            let glyph_bottom = lift + glyph_bitmap.top as f32 - glyph_bitmap.height as f32;
 
             max_y = max_y.max(glyph_top);
             min_y = min_y.min(glyph_bottom);
+            let glyph_left = glyph_x + glyph_bitmap.left as f32;
+            min_x = min_x.min(glyph_left);
+            max_x = max_x.max(glyph_left + glyph_bitmap.width as f32);
 
             rendered_glyphs.push(RenderedGlyph {
                 bitmap: glyph_bitmap,
-                glyph_x: glyph.x,
-                glyph_y: glyph.y,
+                color,
+                glyph_x,
+                glyph_y,
+                cluster: glyph.cluster,
             });
         }
 
-        let min_width = if shaped.glyphs.is_empty() && shaped.advance_width == 0.0 {
-            1
-        } else {
-            (shaped.advance_width + padding * 2.0).ceil() as u32
-        };
-        let width = min_width.max(1);
+        // Glyphs skipped by `cull` still count as content, so a clipped
+        // redraw keeps the same canvas height as a full render.
+        let empty = rendered_glyphs.is_empty() && culled == 0;
 
         let (metrics_ascent, metrics_descent) = font
-            .metrics()
+            .metrics_at(&params.variations)
             .filter(|m| m.units_per_em > 0 && (m.ascent != 0 || m.descent != 0))
             .map(|m| {
                 let scale = glyph_size / (m.units_per_em as f32);
@@ -342,15 +501,32 @@ impl Renderer for OpixaRenderer {
 
         let glyph_top = max_y.max(0.0);
         let glyph_bottom = (-min_y).max(0.0);
-        let top = glyph_top.max(metrics_ascent);
-        let bottom = glyph_bottom.max(metrics_descent);
+        let mut top = glyph_top.max(metrics_ascent);
+        let mut bottom = glyph_bottom.max(metrics_descent);
+        let mut pad_left = insets.left as f32;
+        // A clipped or ellipsized line ends at the layout width
+        let mut text_width = params.layout.visible_width(shaped.advance_width);
+
+        // Transformed runs: fit the transformed line box and the ink
+        if let Some(t) = transform {
+            let (x0, y0, x1, y1) =
+                t.bounds(0.0, -metrics_descent, shaped.advance_width, metrics_ascent);
+            let (x0, x1) = (x0.min(min_x), x1.max(max_x));
+            top = glyph_top.max(y1);
+            bottom = glyph_bottom.max(-y0);
+            pad_left -= x0;
+            text_width = x1 - x0;
+        }
 
-        let content_height = if rendered_glyphs.is_empty() {
-            16.0
+        let min_width = if shaped.glyphs.is_empty() && shaped.advance_width == 0.0 {
+            1
         } else {
-            top + bottom
+            (text_width + insets.horizontal() as f32).ceil() as u32
         };
-        let height = (content_height + padding * 2.0).ceil() as u32;
+        let width = min_width.max(1);
+
+        let content_height = if empty { 16.0 } else { top + bottom };
+        let height = (content_height + insets.vertical() as f32).ceil() as u32;
 
         if width == 0 || height == 0 {
             return Err(RenderError::ZeroDimensions { width, height }.into());
@@ -377,6 +553,308 @@ impl Renderer for OpixaRenderer {
             .into());
         }
 
+        let baseline_y = if empty { pad_top } else { pad_top + top };
+
+        let text_box = (transform.is_none() && !empty).then_some([
+            pad_left,
+            baseline_y - top,
+            pad_left + text_width,
+            baseline_y + bottom,
+        ]);
+
+        Ok(Layout {
+            width,
+            height,
+            pad_left,
+            baseline_y,
+            text_box,
+            glyphs: rendered_glyphs,
+            memory,
+        })
+    }
+
+    /// Composite a run into `target`, skipping glyphs `cull` rules out.
+    fn draw_into(
+        &self,
+        shaped: &ShapingResult,
+        font: &Arc<dyn FontRef>,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+        cull: Option<&GlyphCull>,
+    ) -> Result<Option<DirtyRect>> {
+        let layout = self.layout(shaped, font, params, cull)?;
+        self.draw_layout(layout, shaped, params, target, origin)
+    }
+
+    /// Render into a fresh canvas of `params.format` with aligned rows.
+    ///
+    /// Coverage formats and padded rows go through the render target path;
+    /// only packed `Rgba8` canvases take the renderer's own compositor.
+    fn render_to_buffer(
+        &self,
+        mut layout: Layout,
+        shaped: &ShapingResult,
+        params: &RenderParams,
+    ) -> Result<RenderOutput> {
+        let (width, height, format) = (layout.width, layout.height, params.format);
+        let stride = format.stride(width, params.row_alignment);
+        layout.memory.grow(stride * height as usize)?;
+        let mut data = vec![0u8; stride * height as usize];
+        let mut target = BufferTarget::new(&mut data, width, height, stride, format)?;
+        self.draw_layout(layout, shaped, params, &mut target, (0, 0))?;
+        Ok(RenderOutput::Bitmap(
+            BitmapData {
+                width,
+                height,
+                format,
+                stride,
+                layout: PixelLayout::RGBA,
+                data,
+            }
+            .into_layout(params.pixel_layout),
+        ))
+    }
+
+    /// Composite a laid-out run into `target`.
+    fn draw_layout(
+        &self,
+        mut layout: Layout,
+        shaped: &ShapingResult,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+    ) -> Result<Option<DirtyRect>> {
+        let effects = layout.effect_mask(params)?;
+        let (ox, oy) = origin;
+
+        let mut dirty = None;
+        if let Some(bg) = params.background {
+            dirty = render_target::fill_rect(target, ox, oy, layout.width, layout.height, bg)?;
+        }
+        if let Some((plate, [left, top, right, bottom])) =
+            params.effects.background_box.zip(layout.text_box)
+        {
+            let (x, y) = (ox as f32, oy as f32);
+            let filled = plate.fill(target, x + left, y + top, x + right, y + bottom)?;
+            dirty = DirtyRect::merge(dirty, filled);
+        }
+        let spans = color_span::fill_span_backgrounds(
+            target,
+            shaped,
+            params,
+            ox as f32 + layout.pad_left,
+            oy,
+            layout.height,
+        )?;
+        dirty = DirtyRect::merge(dirty, spans);
+        if let Some(mask) = effects {
+            let drawn = mask.draw_effects(&params.effects, target, origin)?;
+            dirty = DirtyRect::merge(dirty, drawn);
+        }
+
+        for rg in &layout.glyphs {
+            let bitmap = &rg.bitmap;
+            let x = ox + (rg.glyph_x + layout.pad_left) as i32 + bitmap.left;
+            let y = oy + (layout.baseline_y + rg.glyph_y) as i32 - bitmap.top;
+            let touched = rg.blend(target, x, y, params)?;
+            dirty = DirtyRect::merge(dirty, touched);
+        }
+
+        Ok(dirty)
+    }
+}
+
+impl Default for OpixaRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether an outline glyph source is allowed.
+fn allows_outline(params: &RenderParams) -> bool {
+    params
+        .glyph_sources
+        .effective_order()
+        .iter()
+        .any(|s| matches!(s, GlyphSource::Glyf | GlyphSource::Cff | GlyphSource::Cff2))
+}
+
+/// Errors unless an outline glyph source, or a color one the `color`
+/// feature can paint, is allowed.
+fn check_glyph_sources(params: &RenderParams) -> Result<()> {
+    let allows_color = cfg!(feature = "color") && params.glyph_sources.allows_color();
+    if !allows_outline(params) && !allows_color {
+        return Err(RenderError::BackendError(
+            "opixa renderer requires outline or color glyph sources".to_string(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Fail the call for a glyph that did not rasterize, when `strict`
+fn glyph_failed(glyph_id: u32, error: &str, strict: bool) -> Result<()> {
+    if strict {
+        return Err(RenderError::BackendError(format!(
+            "Glyph {glyph_id} rasterization failed: {error}"
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Outline rasterizer for one run; none when the font will not load and
+/// the params are not strict.
+fn glyph_rasterizer<'a>(
+    font_data: &'a [u8],
+    face_index: u32,
+    size: f32,
+    params: &RenderParams,
+    transform: Option<TextTransform>,
+) -> Result<Option<rasterizer::GlyphRasterizer<'a>>> {
+    let rasterizer = match rasterizer::GlyphRasterizer::from_index(font_data, face_index, size) {
+        Ok(r) => r,
+        Err(e) if params.strict => {
+            return Err(
+                RenderError::BackendError(format!("Failed to create rasterizer: {e}")).into(),
+            )
+        },
+        Err(e) => {
+            diagnostics::warn(
+                params.diagnostics.as_ref(),
+                DiagnosticKind::FontUnusable,
+                None,
+                format!("Failed to create rasterizer: {e}"),
+            );
+            return Ok(None);
+        },
+    };
+    let rasterizer = if params.antialias {
+        rasterizer.with_antialias_quality(params.antialias_quality)
+    } else {
+        rasterizer.with_oversample(1)
+    };
+    let mut rasterizer = rasterizer
+        .with_deterministic(params.deterministic)
+        .with_cancel(params.cancel.clone())
+        .with_strict(params.strict)
+        .with_diagnostics(params.diagnostics.clone());
+    if !params.variations.is_empty() {
+        if let Err(e) = rasterizer.set_variations(&params.variations) {
+            diagnostics::warn(
+                params.diagnostics.as_ref(),
+                DiagnosticKind::VariationsIgnored,
+                None,
+                format!("Variable font setup failed: {e}"),
+            );
+        }
+    }
+    if let Err(e) = rasterizer.set_hinting(params.hinting) {
+        diagnostics::warn(
+            params.diagnostics.as_ref(),
+            DiagnosticKind::FontUnusable,
+            None,
+            format!("Drawing unhinted outlines: {e}"),
+        );
+    }
+    rasterizer.set_transform(transform);
+    Ok(Some(rasterizer))
+}
+
+/// Raise each coverage value to the power `1 / gamma`; 1.0 leaves it as is.
+fn apply_gamma(coverage: &mut [u8], gamma: f32) {
+    if gamma == 1.0 || !gamma.is_finite() || gamma <= 0.0 {
+        return;
+    }
+    let mut table = [0u8; 256];
+    for (value, entry) in table.iter_mut().enumerate() {
+        *entry = ((value as f32 / 255.0).powf(1.0 / gamma) * 255.0).round() as u8;
+    }
+    for value in coverage {
+        *value = table[*value as usize];
+    }
+}
+
+/// Distance fields fall off `spread` pixels beyond the outline, so the
+/// canvas needs that much extra room on every side.
+fn sdf_spread(params: &RenderParams) -> Option<u32> {
+    match params.output {
+        RenderMode::DistanceField { spread } => Some(spread.max(1)),
+        _ => None,
+    }
+}
+
+impl Renderer for OpixaRenderer {
+    fn name(&self) -> &'static str {
+        "opixa"
+    }
+
+    fn render(
+        &self,
+        shaped: &ShapingResult,
+        font: Arc<dyn FontRef>,
+        params: &RenderParams,
+    ) -> Result<RenderOutput> {
+        log::debug!("OpixaRenderer: Rendering {} glyphs", shaped.glyphs.len());
+
+        check_glyph_sources(params)?;
+
+        let mut layout = self.layout(shaped, &font, params, None)?;
+        // The compositor below writes packed Rgba8 rows only
+        let stride = params.format.stride(layout.width, params.row_alignment);
+        let packed_rgba =
+            params.format == BitmapFormat::Rgba8 && stride == layout.width as usize * 4;
+        if !packed_rgba && sdf_spread(params).is_none() {
+            return self.render_to_buffer(layout, shaped, params);
+        }
+        let effects = layout.effect_mask(params)?;
+        let Layout {
+            width,
+            height,
+            pad_left,
+            baseline_y,
+            text_box,
+            glyphs: rendered_glyphs,
+            mut memory,
+        } = layout;
+
+        if let Some(spread) = sdf_spread(params) {
+            memory.grow((width * height) as usize)?;
+            let mut canvas = vec![0u8; (width * height) as usize];
+            for rg in rendered_glyphs {
+                let bitmap = &rg.bitmap;
+                let field = typf_core::sdf::coverage_to_distance_field(
+                    &bitmap.data,
+                    bitmap.width,
+                    bitmap.height,
+                    spread,
+                );
+                let x = (rg.glyph_x + pad_left) as i32 + bitmap.left - spread as i32;
+                let y = (baseline_y + rg.glyph_y) as i32 - bitmap.top - spread as i32;
+                typf_core::sdf::blit_distance_field(
+                    &mut canvas,
+                    width,
+                    height,
+                    &field,
+                    bitmap.width + 2 * spread,
+                    bitmap.height + 2 * spread,
+                    x,
+                    y,
+                );
+            }
+
+            return Ok(RenderOutput::Bitmap(BitmapData {
+                width,
+                height,
+                format: BitmapFormat::Gray8,
+                stride: BitmapFormat::Gray8.row_bytes(width),
+                layout: PixelLayout::RGBA,
+                data: canvas,
+            }));
+        }
+
+        memory.grow((width * height * 4) as usize)?;
         let mut canvas = vec![0u8; (width * height * 4) as usize];
 
         if let Some(bg) = params.background {
@@ -387,37 +865,182 @@ impl Renderer for OpixaRenderer {
                 pixel[3] = bg.a;
             }
         }
-
-        let baseline_y = if rendered_glyphs.is_empty() {
-            padding
-        } else {
-            padding + top
-        };
+        let plate = params.effects.background_box.zip(text_box);
+        let has_color = rendered_glyphs.iter().any(|rg| rg.color.is_some());
+        if plate.is_some() || !params.color_spans.is_empty() || effects.is_some() || has_color {
+            let stride = width as usize * 4;
+            let mut target =
+                BufferTarget::new(&mut canvas, width, height, stride, BitmapFormat::Rgba8)?;
+            if let Some((plate, [left, top, right, bottom])) = plate {
+                plate.fill(&mut target, left, top, right, bottom)?;
+            }
+            color_span::fill_span_backgrounds(&mut target, shaped, params, pad_left, 0, height)?;
+            if let Some(mask) = effects {
+                mask.draw_effects(&params.effects, &mut target, (0, 0))?;
+            }
+            // The SIMD blend assumes a clear canvas and one-color glyphs, so
+            // glyphs drawn over fills and effects, and color glyphs, go
+            // through the render target blends instead
+            for rg in &rendered_glyphs {
+                let bitmap = &rg.bitmap;
+                rg.blend(
+                    &mut target,
+                    (rg.glyph_x + pad_left) as i32 + bitmap.left,
+                    (baseline_y + rg.glyph_y) as i32 - bitmap.top,
+                    params,
+                )?;
+            }
+            return Ok(RenderOutput::Bitmap(
+                BitmapData {
+                    width,
+                    height,
+                    format: BitmapFormat::Rgba8,
+                    stride: BitmapFormat::Rgba8.row_bytes(width),
+                    layout: PixelLayout::RGBA,
+                    data: canvas,
+                }
+                .into_layout(params.pixel_layout),
+            ));
+        }
 
         for rg in rendered_glyphs {
-            let x = (rg.glyph_x + padding) as i32;
+            let x = (rg.glyph_x + pad_left) as i32;
             let y = (baseline_y + rg.glyph_y) as i32;
+            let color = params.foreground_at(rg.cluster);
 
-            self.composite_glyph(&mut canvas, width, &rg.bitmap, x, y, params.foreground);
+            self.composite_glyph(&mut canvas, width, &rg.bitmap, x, y, color);
         }
 
-        Ok(RenderOutput::Bitmap(BitmapData {
-            width,
-            height,
-            format: BitmapFormat::Rgba8,
-            data: canvas,
-        }))
+        Ok(RenderOutput::Bitmap(
+            BitmapData {
+                width,
+                height,
+                format: BitmapFormat::Rgba8,
+                stride: BitmapFormat::Rgba8.row_bytes(width),
+                layout: PixelLayout::RGBA,
+                data: canvas,
+            }
+            .into_layout(params.pixel_layout),
+        ))
+    }
+
+    fn render_into(
+        &self,
+        shaped: &ShapingResult,
+        font: Arc<dyn FontRef>,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+    ) -> Result<Option<DirtyRect>> {
+        check_glyph_sources(params)?;
+        if !matches!(params.output, RenderMode::Bitmap) {
+            return render_target::render_via_bitmap(self, shaped, font, params, target, origin);
+        }
+
+        self.draw_into(shaped, &font, params, target, origin, None)
+    }
+
+    fn render_region(
+        &self,
+        shaped: &ShapingResult,
+        font: Arc<dyn FontRef>,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+        clip: DirtyRect,
+    ) -> Result<Option<DirtyRect>> {
+        check_glyph_sources(params)?;
+        if !matches!(params.output, RenderMode::Bitmap) {
+            return render_target::render_clipped(self, shaped, font, params, target, origin, clip);
+        }
+
+        // The font size bounds how far ink strays from the pen span.
+        let cull = GlyphCull::new(clip, origin, shaped.advance_height);
+        let mut clipped = ClipTarget::new(target, clip);
+        self.draw_into(shaped, &font, params, &mut clipped, origin, Some(&cull))
     }
 
     fn supports_format(&self, format: &str) -> bool {
-        matches!(format, "bitmap" | "rgba" | "rgb" | "gray")
+        matches!(format, "bitmap" | "rgba" | "rgb" | "gray" | "sdf")
+    }
+
+    fn trim_idle(&self, max_age: std::time::Duration) -> usize {
+        self.cache.as_ref().map_or(0, |c| c.trim_idle(max_age))
+    }
+}
+
+/// Rasterized glyphs and canvas geometry for one run.
+struct Layout {
+    width: u32,
+    height: u32,
+    pad_left: f32,
+    baseline_y: f32,
+    /// Untransformed text box as left, top, right, bottom; none when empty
+    text_box: Option<[f32; 4]>,
+    glyphs: Vec<RenderedGlyph>,
+    /// Budget held for the glyph bitmaps, and for the canvas once allocated
+    memory: Reservation,
+}
+
+impl Layout {
+    /// The run's coverage, when the params' effects need it.
+    fn effect_mask(&mut self, params: &RenderParams) -> Result<Option<CoverageMask>> {
+        let effects = &params.effects;
+        if effects.stroke.is_none() && effects.shadow.is_none() {
+            return Ok(None);
+        }
+        if sdf_spread(params).is_some() {
+            return Ok(None);
+        }
+        let mut mask = CoverageMask::new(self.width, self.height);
+        // The mask and one working copy for the stroke or the blur
+        self.memory.grow(2 * mask.byte_len())?;
+        for rg in &self.glyphs {
+            let bitmap = &rg.bitmap;
+            let x = (rg.glyph_x + self.pad_left) as i32 + bitmap.left;
+            let y = (self.baseline_y + rg.glyph_y) as i32 - bitmap.top;
+            mask.add(x, y, bitmap.width, bitmap.height, &bitmap.data);
+        }
+        Ok(Some(mask))
     }
 }
 
 struct RenderedGlyph {
+    /// Coverage; the alpha of `color` for color glyphs
     bitmap: rasterizer::GlyphBitmap,
+    /// Premultiplied RGBA of a color glyph, the size of `bitmap`
+    color: Option<Vec<u8>>,
     glyph_x: f32,
     glyph_y: f32,
+    /// Source byte the glyph came from, for [`RenderParams::foreground_at`]
+    cluster: u32,
+}
+
+impl RenderedGlyph {
+    /// Blend onto `target` with the bitmap's top-left at `(x, y)`.
+    fn blend(
+        &self,
+        target: &mut dyn RenderTarget,
+        x: i32,
+        y: i32,
+        params: &RenderParams,
+    ) -> Result<Option<DirtyRect>> {
+        let bitmap = &self.bitmap;
+        match &self.color {
+            Some(rgba) => {
+                render_target::blend_premul_rgba(target, x, y, bitmap.width, bitmap.height, rgba)
+            },
+            None => render_target::blend_mask(
+                target,
+                x,
+                y,
+                bitmap.width,
+                bitmap.height,
+                &bitmap.data,
+                params.foreground_at(self.cluster),
+            ),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -425,7 +1048,7 @@ mod tests {
     use super::*;
     use typf_core::{
         types::{Direction, PositionedGlyph},
-        GlyphSource, GlyphSourcePreference,
+        GlyphSource, GlyphSourcePreference, MemoryBudget, Overflow, TextLayout, TypfError,
     };
 
     #[test]
@@ -489,6 +1112,156 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_diagnostics_when_font_unreadable_then_font_and_glyph_recorded() {
+        struct EmptyFont;
+        impl FontRef for EmptyFont {
+            fn data(&self) -> &[u8] {
+                &[]
+            }
+            fn units_per_em(&self) -> u16 {
+                1000
+            }
+            fn glyph_id(&self, _ch: char) -> Option<u32> {
+                Some(0)
+            }
+            fn advance_width(&self, _glyph_id: u32) -> f32 {
+                500.0
+            }
+        }
+
+        let shaped = ShapingResult {
+            glyphs: vec![PositionedGlyph {
+                id: 72,
+                x: 0.0,
+                y: 0.0,
+                advance: 10.0,
+                cluster: 0,
+            }],
+            advance_width: 10.0,
+            advance_height: 16.0,
+            direction: Direction::LeftToRight,
+        };
+        let diagnostics = typf_core::diagnostics::Diagnostics::new();
+        let params = RenderParams {
+            diagnostics: Some(diagnostics.clone()),
+            ..RenderParams::default()
+        };
+
+        OpixaRenderer::new()
+            .render(&shaped, Arc::new(EmptyFont), &params)
+            .expect("unreadable fonts render blank outside strict mode");
+
+        let recorded = diagnostics.take();
+        let kinds: Vec<_> = recorded.iter().map(|d| d.kind).collect();
+        assert_eq!(
+            kinds,
+            [DiagnosticKind::FontUnusable, DiagnosticKind::GlyphSkipped]
+        );
+        assert_eq!(recorded[1].glyph_id, Some(72));
+    }
+
+    #[test]
+    fn test_memory_budget_when_canvas_too_large_then_budget_exceeded() {
+        struct EmptyFont;
+        impl FontRef for EmptyFont {
+            fn data(&self) -> &[u8] {
+                &[]
+            }
+            fn units_per_em(&self) -> u16 {
+                1000
+            }
+            fn glyph_id(&self, _ch: char) -> Option<u32> {
+                None
+            }
+            fn advance_width(&self, _glyph_id: u32) -> f32 {
+                0.0
+            }
+        }
+
+        let renderer = OpixaRenderer::new();
+        let shaped = ShapingResult {
+            glyphs: Vec::new(),
+            advance_width: 20.0,
+            advance_height: 16.0,
+            direction: Direction::LeftToRight,
+        };
+
+        let tight = MemoryBudget::new(64);
+        let params = RenderParams {
+            memory_budget: Some(tight.clone()),
+            ..RenderParams::default()
+        };
+        let result = renderer.render(&shaped, Arc::new(EmptyFont), &params);
+        assert!(matches!(
+            result,
+            Err(TypfError::RenderingFailed(RenderError::BudgetExceeded {
+                limit: 64,
+                ..
+            }))
+        ));
+        assert_eq!(tight.in_use(), 0);
+
+        let roomy = MemoryBudget::new(1 << 20);
+        let params = RenderParams {
+            memory_budget: Some(roomy.clone()),
+            ..RenderParams::default()
+        };
+        let Ok(RenderOutput::Bitmap(bitmap)) =
+            renderer.render(&shaped, Arc::new(EmptyFont), &params)
+        else {
+            panic!("Expected bitmap output");
+        };
+        assert_eq!(roomy.in_use(), 0);
+        assert_eq!(roomy.peak(), bitmap.data.len());
+    }
+
+    #[test]
+    fn test_strict_when_font_unreadable_then_error_instead_of_skipping() {
+        struct GarbageFont;
+        impl FontRef for GarbageFont {
+            fn data(&self) -> &[u8] {
+                b"not a font"
+            }
+            fn units_per_em(&self) -> u16 {
+                1000
+            }
+            fn glyph_id(&self, _ch: char) -> Option<u32> {
+                None
+            }
+            fn advance_width(&self, _glyph_id: u32) -> f32 {
+                0.0
+            }
+        }
+
+        let renderer = OpixaRenderer::new();
+        let shaped = ShapingResult {
+            glyphs: vec![PositionedGlyph {
+                id: 1,
+                x: 0.0,
+                y: 0.0,
+                advance: 10.0,
+                cluster: 0,
+            }],
+            advance_width: 10.0,
+            advance_height: 16.0,
+            direction: Direction::LeftToRight,
+        };
+
+        let lenient = renderer.render(&shaped, Arc::new(GarbageFont), &RenderParams::default());
+        assert!(lenient.is_ok(), "bad glyphs are skipped: {lenient:?}");
+
+        let params = RenderParams {
+            strict: true,
+            ..RenderParams::default()
+        };
+        let strict = renderer.render(&shaped, Arc::new(GarbageFont), &params);
+        assert!(matches!(
+            strict,
+            Err(TypfError::RenderingFailed(RenderError::BackendError(_)))
+        ));
+    }
+
     #[test]
     fn errors_when_outlines_denied() {
         let renderer = OpixaRenderer::new();
@@ -578,4 +1351,153 @@ mod tests {
             _ => panic!("Expected bitmap output"),
         }
     }
+
+    #[test]
+    fn test_layout_when_clipped_then_canvas_ends_at_max_width() {
+        let renderer = OpixaRenderer::new();
+        let shaped = ShapingResult {
+            glyphs: vec![],
+            advance_width: 100.0,
+            advance_height: 20.0,
+            direction: Direction::LeftToRight,
+        };
+
+        struct MockFont;
+        impl FontRef for MockFont {
+            fn data(&self) -> &[u8] {
+                &[]
+            }
+            fn units_per_em(&self) -> u16 {
+                1000
+            }
+            fn glyph_id(&self, _ch: char) -> Option<u32> {
+                Some(0)
+            }
+            fn advance_width(&self, _glyph_id: u32) -> f32 {
+                500.0
+            }
+        }
+
+        let width = |layout: TextLayout| {
+            let params = RenderParams {
+                layout,
+                ..RenderParams::default()
+            };
+            match renderer.render(&shaped, Arc::new(MockFont), &params) {
+                Ok(RenderOutput::Bitmap(bitmap)) => bitmap.width,
+                _ => panic!("Expected bitmap output"),
+            }
+        };
+
+        let visible = width(TextLayout::new(40.0));
+        let clipped = width(TextLayout::new(40.0).with_overflow(Overflow::Clip));
+        assert_eq!(visible - clipped, 60);
+    }
+
+    #[test]
+    fn test_distance_field_when_no_glyphs_then_gray8_canvas_grows_by_spread() {
+        let renderer = OpixaRenderer::new();
+
+        let shaped = ShapingResult {
+            glyphs: vec![],
+            advance_width: 100.0,
+            advance_height: 20.0,
+            direction: Direction::LeftToRight,
+        };
+
+        struct MockFont;
+        impl FontRef for MockFont {
+            fn data(&self) -> &[u8] {
+                &[]
+            }
+            fn units_per_em(&self) -> u16 {
+                1000
+            }
+            fn glyph_id(&self, _ch: char) -> Option<u32> {
+                Some(0)
+            }
+            fn advance_width(&self, _glyph_id: u32) -> f32 {
+                500.0
+            }
+        }
+
+        let font = Arc::new(MockFont);
+        let params = RenderParams {
+            output: RenderMode::DistanceField { spread: 4 },
+            ..Default::default()
+        };
+
+        match renderer.render(&shaped, font, &params).unwrap() {
+            RenderOutput::Bitmap(bitmap) => {
+                assert_eq!(bitmap.format, BitmapFormat::Gray8);
+                assert_eq!(bitmap.width, 108);
+                assert_eq!(bitmap.data.len(), (bitmap.width * bitmap.height) as usize);
+                assert!(bitmap.data.iter().all(|&v| v == 0));
+            },
+            _ => panic!("Expected bitmap output"),
+        }
+    }
+
+    #[test]
+    fn test_effects_when_shadow_and_default_padding_then_canvas_grows_per_side() {
+        let renderer = OpixaRenderer::new().with_default_padding(2);
+
+        let shaped = ShapingResult {
+            glyphs: vec![],
+            advance_width: 100.0,
+            advance_height: 20.0,
+            direction: Direction::LeftToRight,
+        };
+
+        struct MockFont;
+        impl FontRef for MockFont {
+            fn data(&self) -> &[u8] {
+                &[]
+            }
+            fn units_per_em(&self) -> u16 {
+                1000
+            }
+            fn glyph_id(&self, _ch: char) -> Option<u32> {
+                Some(0)
+            }
+            fn advance_width(&self, _glyph_id: u32) -> f32 {
+                500.0
+            }
+        }
+
+        let params = RenderParams {
+            effects: typf_core::RenderEffects {
+                shadow: Some(typf_core::DropShadow {
+                    offset_x: 3.0,
+                    offset_y: 0.0,
+                    blur_radius: 2.0,
+                    color: Color::black(),
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let insets = params.canvas_insets(2);
+        assert_eq!((insets.left, insets.right), (2, 7));
+
+        match renderer
+            .render(&shaped, Arc::new(MockFont), &params)
+            .unwrap()
+        {
+            RenderOutput::Bitmap(bitmap) => assert_eq!(bitmap.width, 109),
+            _ => panic!("Expected bitmap output"),
+        }
+
+        let fixed = RenderParams {
+            auto_padding: false,
+            ..params
+        };
+        match renderer
+            .render(&shaped, Arc::new(MockFont), &fixed)
+            .unwrap()
+        {
+            RenderOutput::Bitmap(bitmap) => assert_eq!(bitmap.width, 104),
+            _ => panic!("Expected bitmap output"),
+        }
+    }
 }
@@ -4,6 +4,10 @@
 //! splits your text across available threads, letting each one work on a portion
 //! of the glyphs. Rayon handles the thread management—we just reap the speed
 //! benefits.
+//!
+//! A single huge glyph is split differently: see
+//! [`GlyphRasterizer::with_tile_threshold`](crate::rasterizer::GlyphRasterizer::with_tile_threshold),
+//! which rasterizes bands of one glyph's scanlines on separate threads.
 
 use rayon::prelude::*;
 use typf_core::{
@@ -15,6 +19,8 @@ use typf_core::{
 pub struct ParallelRenderer {
     /// How many cores to enlist (0 = let Rayon decide optimally)
     thread_count: usize,
+    /// Composite in glyph order on one canvas, never per region
+    deterministic: bool,
 }
 
 impl ParallelRenderer {
@@ -22,12 +28,27 @@ impl ParallelRenderer {
     pub fn new() -> Self {
         Self {
             thread_count: 0, // Let Rayon's thread pool choose wisely
+            deterministic: false,
         }
     }
 
     /// Take manual control of thread allocation
     pub fn with_threads(thread_count: usize) -> Self {
-        Self { thread_count }
+        Self {
+            thread_count,
+            deterministic: false,
+        }
+    }
+
+    /// Make the canvas independent of how work is divided
+    ///
+    /// Regional rendering clips glyphs at strip boundaries, so its pixels
+    /// depend on the strip layout. In deterministic mode
+    /// [`render_regions`](Self::render_regions) composites every glyph in
+    /// text order like [`render_parallel`](Self::render_parallel) instead.
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
     }
 
     /// Parallel glyph rendering: faster when you have many cores
@@ -103,6 +124,17 @@ impl ParallelRenderer {
         glyph_renderer: impl Fn(&PositionedGlyph) -> Vec<u8> + Send + Sync,
         color: Color,
     ) -> Vec<u8> {
+        if self.deterministic {
+            return self.render_parallel(
+                &shaped.glyphs,
+                canvas_width,
+                canvas_height,
+                glyph_renderer,
+                color,
+                None,
+            );
+        }
+
         // Split canvas into sensible horizontal strips
         let region_height = 64; // Typical line height
         let num_regions = (canvas_height / region_height).max(1);
@@ -300,6 +332,34 @@ mod tests {
         assert_eq!(canvas.len(), 100 * 40 * 4);
     }
 
+    #[test]
+    fn test_region_rendering_when_deterministic_then_glyphs_not_clipped_at_strips() {
+        // One glyph straddling the boundary between the first two 64-row strips
+        let shaped = ShapingResult {
+            glyphs: vec![PositionedGlyph {
+                id: 42,
+                x: 0.0,
+                y: 60.0,
+                advance: 10.0,
+                cluster: 0,
+            }],
+            advance_width: 10.0,
+            advance_height: 128.0,
+            direction: Direction::LeftToRight,
+        };
+        let glyph_renderer = |_glyph: &PositionedGlyph| vec![255u8; 100];
+        let black = Color::rgba(0, 0, 0, 255);
+
+        let deterministic = ParallelRenderer::new().with_deterministic(true);
+        let canvas = deterministic.render_regions(&shaped, 10, 128, glyph_renderer, black);
+        let sequential =
+            deterministic.render_parallel(&shaped.glyphs, 10, 128, glyph_renderer, black, None);
+
+        assert_eq!(canvas, sequential);
+        // Row 66 lies past the strip boundary but inside the glyph
+        assert_eq!(canvas[(66 * 10) * 4 + 3], 255);
+    }
+
     #[test]
     fn test_parallel_stats() {
         let stats = ParallelStats {
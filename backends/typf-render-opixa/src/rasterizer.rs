@@ -6,14 +6,28 @@
 //! bitmaps that humans can read.
 
 use crate::fixed::F26Dot6;
-use crate::grayscale::GrayscaleLevel;
-use crate::scan_converter::ScanConverter;
+use crate::grayscale::{AreaCoverage, GrayscaleLevel};
+use crate::scan_converter::{PathSink, ScanConverter};
 use crate::{DropoutMode, FillRule};
 
 use read_fonts::FontRef as ReadFontsRef;
 use skrifa::instance::Size;
-use skrifa::outline::DrawSettings;
+use skrifa::outline::{DrawSettings, HintingInstance, SmoothMode, Target};
 use skrifa::{GlyphId as SkrifaGlyphId, MetadataProvider};
+use typf_core::{
+    diagnostics::Diagnostics, sanitize, AntialiasQuality, CancelToken, Hinting, TextTransform,
+};
+
+/// Glyph height, in output pixels, from which one glyph is split into bands
+/// of scanlines rasterized on separate threads (with the `parallel` feature).
+pub const DEFAULT_TILE_THRESHOLD: usize = 256;
+
+/// Fewest output rows a band gets, so per-band outline setup stays cheap.
+const MIN_TILE_ROWS: usize = 16;
+
+/// Most bytes of mono bitmap (or analytic area) one band works in at a time;
+/// poster-size glyphs are cut into as many bands as it takes.
+const MAX_BAND_BYTES: usize = 64 * 1024 * 1024;
 
 /// Your personal glyph artist: turning outlines into masterpieces
 ///
@@ -36,8 +50,24 @@ pub struct GlyphRasterizer<'a> {
     size: f32,
     /// Our smoothing level: 1=crisp, 4=balanced, 8=perfect
     oversample: u8,
+    /// Exact area coverage instead of oversampling
+    analytic: bool,
     /// Variable font coordinates for infinite font variation
     location: skrifa::instance::Location,
+    /// Glyph height (output pixels) at which scanline bands go parallel
+    tile_threshold: Option<usize>,
+    /// Rotation, shear, or scale applied to outlines before scan conversion
+    transform: Option<TextTransform>,
+    /// Single-pass scan conversion with rounded fixed-point coordinates
+    deterministic: bool,
+    /// Abandon scan conversion between scanlines once this trips
+    cancel: Option<CancelToken>,
+    /// Fail on oversized glyphs instead of clamping their bounds
+    strict: bool,
+    /// Where clamped bounds are noted
+    diagnostics: Option<Diagnostics>,
+    /// Grid-fitting for this size and location; none draws unhinted outlines
+    hinting: Option<HintingInstance>,
 }
 
 impl<'a> GlyphRasterizer<'a> {
@@ -55,14 +85,27 @@ impl<'a> GlyphRasterizer<'a> {
     ///
     /// A ready-to-use rasterizer or a helpful error message
     pub fn new(font_data: &'a [u8], size: f32) -> Result<Self, String> {
-        let font =
-            ReadFontsRef::new(font_data).map_err(|e| format!("Failed to parse font: {}", e))?;
+        Self::from_index(font_data, 0, size)
+    }
+
+    /// Like [`new`](Self::new), for face `face_index` of a TTC/OTC collection
+    pub fn from_index(font_data: &'a [u8], face_index: u32, size: f32) -> Result<Self, String> {
+        let font = ReadFontsRef::from_index(font_data, face_index)
+            .map_err(|e| format!("Failed to parse font: {}", e))?;
 
         Ok(Self {
             font,
             size,
             oversample: 4, // 4x oversampling by default
+            analytic: false,
             location: skrifa::instance::Location::default(),
+            tile_threshold: Some(DEFAULT_TILE_THRESHOLD),
+            transform: None,
+            deterministic: false,
+            cancel: None,
+            strict: false,
+            diagnostics: None,
+            hinting: None,
         })
     }
 
@@ -92,6 +135,15 @@ impl<'a> GlyphRasterizer<'a> {
         Ok(())
     }
 
+    /// Rotate, shear, or scale every outline before rasterizing
+    ///
+    /// The transform works in y-up pixel space around the glyph origin, and
+    /// the returned bitmap's `left`/`top` describe the transformed ink.
+    /// `None` draws outlines as designed.
+    pub fn set_transform(&mut self, transform: Option<TextTransform>) {
+        self.transform = transform;
+    }
+
     /// Choose your smoothness: from razor-sharp to buttery-smooth
     ///
     /// Anti-aliasing is the art of compromise between speed and beauty.
@@ -99,9 +151,178 @@ impl<'a> GlyphRasterizer<'a> {
     /// and processing time. Pick your sweet spot.
     pub fn with_oversample(mut self, oversample: u8) -> Self {
         self.oversample = oversample.max(1);
+        self.analytic = false;
         self
     }
 
+    /// Pick oversampling or exact area coverage from a render quality
+    ///
+    /// Analytic coverage draws outlines at output resolution and measures
+    /// each pixel's covered area; the fill rule then counts as nonzero.
+    pub fn with_antialias_quality(self, quality: AntialiasQuality) -> Self {
+        match quality.oversample() {
+            Some(oversample) => self.with_oversample(oversample),
+            None => Self {
+                oversample: 1,
+                analytic: true,
+                ..self
+            },
+        }
+    }
+
+    /// Split huge glyphs into scanline bands rendered in parallel
+    ///
+    /// A display-size headline can be a single glyph a thousand pixels tall,
+    /// which no amount of per-glyph parallelism speeds up. Glyphs at least
+    /// `threshold` output pixels tall are cut into one band per worker
+    /// thread; the bands' coverage is stacked back together afterwards and
+    /// matches a single-pass render exactly. `None` always renders in one
+    /// pass. Only takes effect with the `parallel` feature.
+    pub fn with_tile_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.tile_threshold = threshold;
+        self
+    }
+
+    /// Same coverage on every machine and thread count
+    ///
+    /// Ignores the tile threshold, so band boundaries never depend on the
+    /// worker count (poster-size glyphs are still cut into bands, by size
+    /// alone), and rounds outline coordinates to the nearest 1/64 instead of
+    /// truncating them.
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Give up on a glyph between scanlines once `cancel` trips
+    ///
+    /// [`render_glyph`](Self::render_glyph) then fails with
+    /// "Rasterization cancelled" instead of returning partial coverage.
+    pub fn with_cancel(mut self, cancel: Option<CancelToken>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Refuse glyphs whose bounds pass the malformed-glyph limit
+    ///
+    /// By default such bounds are clamped by
+    /// [`sanitize::clamp_glyph_bounds`], drawing only the part of the glyph
+    /// inside them; strict rasterizers fail the glyph instead.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Note glyphs whose bounds get clamped in `diagnostics`
+    pub fn with_diagnostics(mut self, diagnostics: Option<Diagnostics>) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+
+    /// Grid-fit outlines with the font's hints, or the automatic hinter
+    ///
+    /// Hints are prepared for the current size and variations, so call this
+    /// after [`set_variations`](Self::set_variations). Full hinting targets
+    /// aliased output when `oversample` is 1. A font whose hints fail to
+    /// load keeps drawing unhinted outlines, and the error is returned.
+    pub fn set_hinting(&mut self, hinting: Hinting) -> Result<(), String> {
+        let target: Target = match hinting {
+            Hinting::None => {
+                self.hinting = None;
+                return Ok(());
+            },
+            Hinting::Light => SmoothMode::Light.into(),
+            Hinting::Full if self.oversample == 1 && !self.analytic => Target::Mono,
+            Hinting::Full => SmoothMode::Normal.into(),
+        };
+        self.hinting = None;
+        let instance = HintingInstance::new(
+            &self.font.outline_glyphs(),
+            Size::new(self.size),
+            self.location.coords(),
+            target,
+        )
+        .map_err(|e| format!("Failed to load hints: {e}"))?;
+        self.hinting = Some(instance);
+        Ok(())
+    }
+
+    /// How outlines are scaled to pixels, hinted when hinting is set up
+    fn draw_settings(&self) -> DrawSettings<'_> {
+        match &self.hinting {
+            Some(instance) => DrawSettings::hinted(instance, false),
+            None => DrawSettings::unhinted(Size::new(self.size), self.location.coords()),
+        }
+    }
+
+    /// Row ranges `(first_row, rows)` to rasterize independently
+    fn tile_bands(&self, out_width: usize, out_height: usize) -> Vec<(usize, usize)> {
+        let workers = Self::worker_count();
+        let split = match self.tile_threshold {
+            Some(_) if self.deterministic => false,
+            Some(threshold) => workers > 1 && out_height >= threshold.max(1),
+            None => false,
+        };
+        let rows = if split {
+            out_height.div_ceil(workers).max(MIN_TILE_ROWS)
+        } else {
+            out_height
+        };
+        // However the work is split, one band's working buffer stays bounded
+        let bytes_per_row = if self.analytic {
+            out_width * std::mem::size_of::<f32>()
+        } else {
+            out_width * (self.oversample as usize).pow(2)
+        };
+        let rows = rows.min(MAX_BAND_BYTES / bytes_per_row.max(1)).max(1);
+        if rows >= out_height {
+            return vec![(0, out_height)];
+        }
+
+        (0..out_height)
+            .step_by(rows)
+            .map(|start| (start, rows.min(out_height - start)))
+            .collect()
+    }
+
+    #[cfg(feature = "parallel")]
+    fn worker_count() -> usize {
+        rayon::current_num_threads()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn worker_count() -> usize {
+        1
+    }
+
+    /// Rasterize every band and stack their rows into one coverage buffer
+    fn rasterize_bands(
+        &self,
+        bands: &[(usize, usize)],
+        capacity: usize,
+        rasterize_band: impl Fn(usize, usize) -> Result<Vec<u8>, String> + Sync,
+    ) -> Result<Vec<u8>, String> {
+        #[cfg(feature = "parallel")]
+        let parts: Vec<Vec<u8>> = {
+            use rayon::prelude::*;
+            bands
+                .par_iter()
+                .map(|&(start, rows)| rasterize_band(start, rows))
+                .collect::<Result<_, _>>()?
+        };
+        #[cfg(not(feature = "parallel"))]
+        let parts: Vec<Vec<u8>> = bands
+            .iter()
+            .map(|&(start, rows)| rasterize_band(start, rows))
+            .collect::<Result<_, _>>()?;
+
+        let mut coverage = Vec::with_capacity(capacity);
+        for part in parts {
+            coverage.extend_from_slice(&part);
+        }
+        Ok(coverage)
+    }
+
     /// The moment of truth: curves become pixels
     ///
     /// This is where the magic happens. We take everything you've configured—
@@ -140,6 +361,7 @@ impl<'a> GlyphRasterizer<'a> {
         // Bounds detection: how much canvas do we really need?
         // We'll draw into a temporary pen to find the glyph's natural size
         struct BoundsCalculator {
+            transform: Option<TextTransform>,
             x_min: f32,
             y_min: f32,
             x_max: f32,
@@ -148,8 +370,9 @@ impl<'a> GlyphRasterizer<'a> {
         }
 
         impl BoundsCalculator {
-            fn new() -> Self {
+            fn new(transform: Option<TextTransform>) -> Self {
                 Self {
+                    transform,
                     x_min: f32::MAX,
                     y_min: f32::MAX,
                     x_max: f32::MIN,
@@ -159,6 +382,10 @@ impl<'a> GlyphRasterizer<'a> {
             }
 
             fn update(&mut self, x: f32, y: f32) {
+                let (x, y) = match self.transform {
+                    Some(t) => t.apply(x, y),
+                    None => (x, y),
+                };
                 self.x_min = self.x_min.min(x);
                 self.y_min = self.y_min.min(y);
                 self.x_max = self.x_max.max(x);
@@ -190,13 +417,10 @@ impl<'a> GlyphRasterizer<'a> {
             fn close(&mut self) {}
         }
 
-        let size_setting = Size::new(self.size);
+        // Variable font location and hints come from our stored settings
+        let draw_settings = self.draw_settings();
 
-        // Variable font location comes from our stored coordinates
-        let location_ref = self.location.coords();
-        let draw_settings = DrawSettings::unhinted(size_setting, location_ref);
-
-        let mut bounds_calc = BoundsCalculator::new();
+        let mut bounds_calc = BoundsCalculator::new(self.transform);
         glyph
             .draw(draw_settings, &mut bounds_calc)
             .map_err(|e| format!("Failed to calculate bounds: {:?}", e))?;
@@ -212,34 +436,52 @@ impl<'a> GlyphRasterizer<'a> {
             });
         }
 
-        // DrawSettings already scaled from font units to pixels
+        // DrawSettings already scaled from font units to pixels; malformed
+        // glyphs are clamped (or refused) before they size the canvas
+        let [x_min, y_min, x_max, y_max] = sanitize::clamp_glyph_bounds(
+            glyph_id,
+            [
+                bounds_calc.x_min,
+                bounds_calc.y_min,
+                bounds_calc.x_max,
+                bounds_calc.y_max,
+            ],
+            self.size,
+            self.strict,
+            self.diagnostics.as_ref(),
+        )
+        .map_err(|e| e.to_string())?;
+
         // Now we convert to integer pixel coordinates
-        let x_min = bounds_calc.x_min.floor() as i32;
-        let y_min = bounds_calc.y_min.floor() as i32;
-        let x_max = bounds_calc.x_max.ceil() as i32;
-        let y_max = bounds_calc.y_max.ceil() as i32;
+        let x_min = x_min.floor() as i32;
+        let y_min = y_min.floor() as i32;
+        let x_max = x_max.ceil() as i32;
+        let y_max = y_max.ceil() as i32;
 
         // Calculate OUTPUT dimensions first (ensuring at least 1x1), then derive
-        // oversampled dimensions. This ensures render_grayscale's mono_bitmap size
-        // matches the scan_converter dimensions.
+        // the oversampled width; each band oversamples its own rows. This ensures
+        // render_grayscale's mono_bitmap size matches the scan_converter dimensions.
         let out_width = ((x_max - x_min) as usize).max(1);
         let out_height = ((y_max - y_min) as usize).max(1);
         let width = out_width * self.oversample as usize;
-        let height = out_height * self.oversample as usize;
 
-        // Guard against memory bombs (malicious fonts or giant sizes)
-        if width > 4096 || height > 4096 {
+        // Malformed outlines were clamped above; what is left is the size
+        // asked for, held to the same limits as a whole canvas. Scan
+        // conversion works in bands, so only the coverage is this big.
+        let (max_width, max_height) = (
+            typf_core::get_max_bitmap_width() as usize,
+            typf_core::get_max_bitmap_height() as usize,
+        );
+        let pixels = out_width as u64 * out_height as u64;
+        if out_width > max_width
+            || out_height > max_height
+            || pixels > typf_core::get_max_bitmap_pixels()
+        {
             return Err(format!(
-                "Glyph bitmap too large: {}x{} (max 4096x4096)",
-                width, height
+                "Glyph bitmap too large: {out_width}x{out_height} (max {max_width}x{max_height})"
             ));
         }
 
-        // Prepare our canvas, oversized for smooth anti-aliasing
-        let mut scan_converter = ScanConverter::new(width, height);
-        scan_converter.set_fill_rule(fill_rule);
-        scan_converter.set_dropout_mode(dropout_mode);
-
         // Transform magic: where do pixels go in our oversized canvas?
         // skrifa handled font units → pixels, now we apply oversampling
         let oversample_scale = self.oversample as f32;
@@ -249,55 +491,50 @@ impl<'a> GlyphRasterizer<'a> {
 
         // Our coordinate transformer: shapes the canvas for the scan converter
         struct TransformPen<'p> {
-            inner: &'p mut ScanConverter,
+            inner: &'p mut dyn PathSink,
+            transform: Option<TextTransform>,
             scale: f32,
             x_offset: f32,
             y_offset: f32,
+            /// First oversampled scanline of the band being drawn
+            band_offset: f32,
+        }
+
+        impl<'p> TransformPen<'p> {
+            fn map(&self, x: f32, y: f32) -> (F26Dot6, F26Dot6) {
+                let (x, y) = match self.transform {
+                    Some(t) => t.apply(x, y),
+                    None => (x, y),
+                };
+                let tx = x * self.scale + self.x_offset;
+                // Flip Y for bitmap coordinates, then shift into the band
+                let ty = (-y * self.scale + self.y_offset) - self.band_offset;
+                (F26Dot6::from_float(tx), F26Dot6::from_float(ty))
+            }
         }
 
         impl<'p> skrifa::outline::OutlinePen for TransformPen<'p> {
             fn move_to(&mut self, x: f32, y: f32) {
-                let tx = x * self.scale + self.x_offset;
-                let ty = -y * self.scale + self.y_offset; // Flip Y for bitmap coordinates
-                self.inner
-                    .move_to(F26Dot6::from_float(tx), F26Dot6::from_float(ty));
+                let (tx, ty) = self.map(x, y);
+                self.inner.move_to(tx, ty);
             }
 
             fn line_to(&mut self, x: f32, y: f32) {
-                let tx = x * self.scale + self.x_offset;
-                let ty = -y * self.scale + self.y_offset; // Flip Y
-                self.inner
-                    .line_to(F26Dot6::from_float(tx), F26Dot6::from_float(ty));
+                let (tx, ty) = self.map(x, y);
+                self.inner.line_to(tx, ty);
             }
 
             fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
-                let tx1 = x1 * self.scale + self.x_offset;
-                let ty1 = -y1 * self.scale + self.y_offset; // Flip Y
-                let tx = x * self.scale + self.x_offset;
-                let ty = -y * self.scale + self.y_offset; // Flip Y
-                self.inner.quadratic_to(
-                    F26Dot6::from_float(tx1),
-                    F26Dot6::from_float(ty1),
-                    F26Dot6::from_float(tx),
-                    F26Dot6::from_float(ty),
-                );
+                let (tx1, ty1) = self.map(x1, y1);
+                let (tx, ty) = self.map(x, y);
+                self.inner.quadratic_to(tx1, ty1, tx, ty);
             }
 
             fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
-                let tx1 = x1 * self.scale + self.x_offset;
-                let ty1 = -y1 * self.scale + self.y_offset; // Flip Y
-                let tx2 = x2 * self.scale + self.x_offset;
-                let ty2 = -y2 * self.scale + self.y_offset; // Flip Y
-                let tx = x * self.scale + self.x_offset;
-                let ty = -y * self.scale + self.y_offset; // Flip Y
-                self.inner.cubic_to(
-                    F26Dot6::from_float(tx1),
-                    F26Dot6::from_float(ty1),
-                    F26Dot6::from_float(tx2),
-                    F26Dot6::from_float(ty2),
-                    F26Dot6::from_float(tx),
-                    F26Dot6::from_float(ty),
-                );
+                let (tx1, ty1) = self.map(x1, y1);
+                let (tx2, ty2) = self.map(x2, y2);
+                let (tx, ty) = self.map(x, y);
+                self.inner.cubic_to(tx1, ty1, tx2, ty2, tx, ty);
             }
 
             fn close(&mut self) {
@@ -305,39 +542,84 @@ impl<'a> GlyphRasterizer<'a> {
             }
         }
 
-        let mut transform_pen = TransformPen {
-            inner: &mut scan_converter,
-            scale: oversample_scale,
-            x_offset,
-            y_offset,
-        };
-
-        // Draw the glyph outline
-        let size_setting = Size::new(self.size);
-        let location_ref = self.location.coords(); // Use stored variations
-        let draw_settings = DrawSettings::unhinted(size_setting, location_ref);
-
-        glyph
-            .draw(draw_settings, &mut transform_pen)
-            .map_err(|e| format!("Failed to draw outline: {:?}", e))?;
-
         // The final touch: smooth those crisp pixels into beauty
         // This downsampling creates the anti-aliased effect readers love
         let grayscale_level = match self.oversample {
             2 => GrayscaleLevel::Level2x2,
             4 => GrayscaleLevel::Level4x4,
             8 => GrayscaleLevel::Level8x8,
+            16 => GrayscaleLevel::Level16x16,
             _ => GrayscaleLevel::Level4x4, // Default to 4x4
         };
 
-        // Use the grayscale module's downsample function
-        // out_width and out_height were calculated at the top of this function
-        let gray_bitmap = crate::grayscale::render_grayscale(
-            &mut scan_converter,
-            out_width,
-            out_height,
-            grayscale_level,
-        );
+        // Scan-convert output rows `row_start..row_start + rows` on their own.
+        // Each band re-draws the whole outline into a converter that only
+        // covers its scanlines; edges above or below the band are clipped or
+        // advanced by the converter, so bands stitch together seamlessly.
+        let rasterize_band = |row_start: usize, rows: usize| -> Result<Vec<u8>, String> {
+            let draw_settings = self.draw_settings();
+
+            if self.analytic {
+                let mut area = AreaCoverage::new(out_width, rows);
+                let mut transform_pen = TransformPen {
+                    inner: &mut area,
+                    transform: self.transform,
+                    scale: 1.0,
+                    x_offset,
+                    y_offset,
+                    band_offset: row_start as f32,
+                };
+                glyph
+                    .draw(draw_settings, &mut transform_pen)
+                    .map_err(|e| format!("Failed to draw outline: {:?}", e))?;
+                if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+                    return Err("Rasterization cancelled".to_string());
+                }
+                return Ok(area.into_coverage());
+            }
+
+            let mut scan_converter = ScanConverter::new(width, rows * self.oversample as usize);
+            scan_converter.set_fill_rule(fill_rule);
+            scan_converter.set_dropout_mode(dropout_mode);
+            scan_converter.set_round_coordinates(self.deterministic);
+            scan_converter.set_cancel(self.cancel.clone());
+
+            let mut transform_pen = TransformPen {
+                inner: &mut scan_converter,
+                transform: self.transform,
+                scale: oversample_scale,
+                x_offset,
+                y_offset,
+                band_offset: (row_start * self.oversample as usize) as f32,
+            };
+
+            glyph
+                .draw(draw_settings, &mut transform_pen)
+                .map_err(|e| format!("Failed to draw outline: {:?}", e))?;
+
+            // Without oversampling every pixel is either inked or not
+            let coverage = if self.oversample == 1 {
+                scan_converter.rasterize()
+            } else {
+                crate::grayscale::render_grayscale(
+                    &mut scan_converter,
+                    out_width,
+                    rows,
+                    grayscale_level,
+                )
+            };
+            if scan_converter.is_cancelled() {
+                return Err("Rasterization cancelled".to_string());
+            }
+            Ok(coverage)
+        };
+
+        let bands = self.tile_bands(out_width, out_height);
+        let gray_bitmap = if bands.len() <= 1 {
+            rasterize_band(0, out_height)?
+        } else {
+            self.rasterize_bands(&bands, out_width * out_height, rasterize_band)?
+        };
 
         Ok(GlyphBitmap {
             width: out_width as u32,
@@ -11,6 +11,7 @@ use crate::fixed::F26Dot6;
 use crate::{DropoutMode, FillRule};
 
 use skrifa::outline::OutlinePen;
+use typf_core::CancelToken;
 
 /// Your scanline conductor: orchestrating the rasterization symphony
 ///
@@ -41,6 +42,12 @@ pub struct ScanConverter {
     /// The canvas dimensions we're working with
     width: usize,
     height: usize,
+
+    /// Round float coordinates to the nearest 1/64 instead of truncating
+    round_coordinates: bool,
+
+    /// Stop filling scanlines once this trips
+    cancel: Option<CancelToken>,
 }
 
 impl ScanConverter {
@@ -76,6 +83,8 @@ impl ScanConverter {
             dropout_mode: DropoutMode::None,
             width,
             height,
+            round_coordinates: false,
+            cancel: None,
         }
     }
 
@@ -89,6 +98,32 @@ impl ScanConverter {
         self.dropout_mode = mode;
     }
 
+    /// Round outline coordinates to the nearest 1/64 (see
+    /// [`F26Dot6::from_float_round`]) rather than truncating them.
+    pub fn set_round_coordinates(&mut self, round: bool) {
+        self.round_coordinates = round;
+    }
+
+    /// Check `cancel` before every scanline; once it trips the remaining
+    /// rows stay empty and [`is_cancelled`](Self::is_cancelled) reports it.
+    pub fn set_cancel(&mut self, cancel: Option<CancelToken>) {
+        self.cancel = cancel;
+    }
+
+    /// Whether the cancel token has tripped
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+    }
+
+    /// Float pixel coordinate to fixed point, as configured
+    fn fixed(&self, value: f32) -> F26Dot6 {
+        if self.round_coordinates {
+            F26Dot6::from_float_round(value)
+        } else {
+            F26Dot6::from_float(value)
+        }
+    }
+
     /// Get the current fill rule.
     pub fn fill_rule(&self) -> FillRule {
         self.fill_rule
@@ -285,14 +320,35 @@ impl ScanConverter {
         // Fill with white (0)
         bitmap.fill(0);
 
-        // Scanline loop
+        let width = self.width;
+        self.render_spans(|span| {
+            let start = span.y as usize * width + span.x as usize;
+            // Use slice::fill() which the compiler optimizes to memset
+            if let Some(run) = bitmap.get_mut(start..start + span.len as usize) {
+                run.fill(1);
+            }
+        });
+    }
+
+    /// Render outline as coverage spans instead of a bitmap.
+    ///
+    /// Hands `emit` every filled run, top row first and left to right within
+    /// a row, clipped to the canvas. Runs on one row never overlap, so a
+    /// custom blitter can draw each straight to its target without an
+    /// intermediate mask. Every span is fully covered; see
+    /// [`render_grayscale_spans`](crate::grayscale::render_grayscale_spans)
+    /// for anti-aliased ones.
+    pub fn render_spans(&mut self, mut emit: impl FnMut(Span)) {
         for y in 0..self.height {
-            self.scan_line_mono(y as i32, bitmap);
+            if self.is_cancelled() {
+                return;
+            }
+            self.scan_line(y as i32, &mut emit);
         }
     }
 
-    /// Process one scanline for monochrome rendering.
-    fn scan_line_mono(&mut self, y: i32, bitmap: &mut [u8]) {
+    /// Process one scanline.
+    fn scan_line(&mut self, y: i32, emit: &mut impl FnMut(Span)) {
         if y < 0 || y >= self.height as i32 {
             return;
         }
@@ -312,8 +368,8 @@ impl ScanConverter {
 
         // Fill spans based on fill rule
         match self.fill_rule {
-            FillRule::NonZeroWinding => self.fill_nonzero_winding(y, bitmap),
-            FillRule::EvenOdd => self.fill_even_odd(y, bitmap),
+            FillRule::NonZeroWinding => self.fill_nonzero_winding(y, emit),
+            FillRule::EvenOdd => self.fill_even_odd(y, emit),
         }
 
         // Step all edges to next scanline
@@ -321,12 +377,12 @@ impl ScanConverter {
     }
 
     /// Fill spans using non-zero winding rule.
-    fn fill_nonzero_winding(&self, y: i32, bitmap: &mut [u8]) {
+    fn fill_nonzero_winding(&self, y: i32, emit: &mut impl FnMut(Span)) {
         let mut winding = 0i32;
-        let mut fill_start: Option<i32> = None;
+        let mut fill_start: Option<F26Dot6> = None;
 
         for edge in self.active_edges.iter() {
-            let x = edge.x.to_int();
+            let x = edge.x;
             let old_winding = winding;
 
             // Update winding number
@@ -339,7 +395,7 @@ impl ScanConverter {
             } else if old_winding != 0 && winding == 0 {
                 // End fill span (leaving filled region)
                 if let Some(start) = fill_start {
-                    self.fill_span(start, x, y, bitmap);
+                    self.fill_span(start, x, y, emit);
                     fill_start = None;
                 }
             }
@@ -347,16 +403,16 @@ impl ScanConverter {
     }
 
     /// Fill spans using even-odd rule.
-    fn fill_even_odd(&self, y: i32, bitmap: &mut [u8]) {
+    fn fill_even_odd(&self, y: i32, emit: &mut impl FnMut(Span)) {
         let mut inside = false;
-        let mut fill_start = 0i32;
+        let mut fill_start = F26Dot6::ZERO;
 
         for edge in self.active_edges.iter() {
-            let x = edge.x.to_int();
+            let x = edge.x;
 
             if inside {
                 // End span
-                self.fill_span(fill_start, x, y, bitmap);
+                self.fill_span(fill_start, x, y, emit);
                 inside = false;
             } else {
                 // Start span
@@ -366,28 +422,109 @@ impl ScanConverter {
         }
     }
 
-    /// Fill a horizontal span of pixels.
-    fn fill_span(&self, x1: i32, x2: i32, y: i32, bitmap: &mut [u8]) {
+    /// Clip the pixels between edges `from` and `to` to the canvas and emit them.
+    ///
+    /// A span that starts and ends inside one pixel covers no pixel of its
+    /// own; with dropout control on it still lights one.
+    fn fill_span(&self, from: F26Dot6, to: F26Dot6, y: i32, emit: &mut impl FnMut(Span)) {
+        let (mut x1, mut x2) = (from.to_int(), to.to_int());
+        if x1 >= x2 && from < to {
+            x1 = match self.dropout_mode {
+                DropoutMode::None => return,
+                DropoutMode::Simple => x1,
+                DropoutMode::Smart => from.midpoint(to).to_int(),
+            };
+            x2 = x1 + 1;
+        }
+
         // Early return for invalid spans
         if y < 0 || y >= self.height as i32 || x1 >= x2 {
             return;
         }
 
-        let x_start = x1.max(0).min(self.width as i32) as usize;
-        let x_end = x2.max(0).min(self.width as i32) as usize;
+        let x_start = x1.max(0).min(self.width as i32);
+        let x_end = x2.max(0).min(self.width as i32);
 
         if x_start >= x_end {
             return;
         }
 
-        let row_offset = y as usize * self.width;
-        let span_start = row_offset + x_start;
-        let span_end = row_offset + x_end;
+        emit(Span {
+            y: y as u32,
+            x: x_start as u32,
+            len: (x_end - x_start) as u32,
+            coverage: 255,
+        });
+    }
+}
 
-        // Use slice::fill() which the compiler optimizes to memset
-        if let Some(span) = bitmap.get_mut(span_start..span_end) {
-            span.fill(1);
-        }
+/// A horizontal run of pixels sharing one coverage value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// Row, from the top of the canvas
+    pub y: u32,
+    /// First column of the run
+    pub x: u32,
+    /// Pixels in the run, at least one
+    pub len: u32,
+    /// 0 = empty, 255 = fully covered
+    pub coverage: u8,
+}
+
+/// Anything that takes outlines in 26.6 pixel coordinates, y down
+///
+/// The scan converter and [`AreaCoverage`](crate::grayscale::AreaCoverage)
+/// both accept the same path commands, so the rasterizer draws into
+/// whichever one the anti-aliasing quality calls for.
+pub trait PathSink {
+    /// Start a new contour at `(x, y)`.
+    fn move_to(&mut self, x: F26Dot6, y: F26Dot6);
+    /// Straight line to `(x, y)`.
+    fn line_to(&mut self, x: F26Dot6, y: F26Dot6);
+    /// Quadratic Bézier through control point `(x1, y1)` to `(x2, y2)`.
+    fn quadratic_to(&mut self, x1: F26Dot6, y1: F26Dot6, x2: F26Dot6, y2: F26Dot6);
+    /// Cubic Bézier through two control points to `(x3, y3)`.
+    #[allow(clippy::too_many_arguments)]
+    fn cubic_to(
+        &mut self,
+        x1: F26Dot6,
+        y1: F26Dot6,
+        x2: F26Dot6,
+        y2: F26Dot6,
+        x3: F26Dot6,
+        y3: F26Dot6,
+    );
+    /// Line back to the contour's start.
+    fn close(&mut self);
+}
+
+impl PathSink for ScanConverter {
+    fn move_to(&mut self, x: F26Dot6, y: F26Dot6) {
+        ScanConverter::move_to(self, x, y);
+    }
+
+    fn line_to(&mut self, x: F26Dot6, y: F26Dot6) {
+        ScanConverter::line_to(self, x, y);
+    }
+
+    fn quadratic_to(&mut self, x1: F26Dot6, y1: F26Dot6, x2: F26Dot6, y2: F26Dot6) {
+        ScanConverter::quadratic_to(self, x1, y1, x2, y2);
+    }
+
+    fn cubic_to(
+        &mut self,
+        x1: F26Dot6,
+        y1: F26Dot6,
+        x2: F26Dot6,
+        y2: F26Dot6,
+        x3: F26Dot6,
+        y3: F26Dot6,
+    ) {
+        ScanConverter::cubic_to(self, x1, y1, x2, y2, x3, y3);
+    }
+
+    fn close(&mut self) {
+        ScanConverter::close(self);
     }
 }
 
@@ -399,22 +536,22 @@ impl OutlinePen for ScanConverter {
     fn move_to(&mut self, x: f32, y: f32) {
         // Y-flip: font space (Y-up) → graphics space (Y-down)
         let y_flipped = self.height as f32 - y;
-        self.move_to(F26Dot6::from_float(x), F26Dot6::from_float(y_flipped));
+        self.move_to(self.fixed(x), self.fixed(y_flipped));
     }
 
     fn line_to(&mut self, x: f32, y: f32) {
         let y_flipped = self.height as f32 - y;
-        self.line_to(F26Dot6::from_float(x), F26Dot6::from_float(y_flipped));
+        self.line_to(self.fixed(x), self.fixed(y_flipped));
     }
 
     fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
         let y1_flipped = self.height as f32 - y1;
         let y_flipped = self.height as f32 - y;
         self.quadratic_to(
-            F26Dot6::from_float(x1),
-            F26Dot6::from_float(y1_flipped),
-            F26Dot6::from_float(x),
-            F26Dot6::from_float(y_flipped),
+            self.fixed(x1),
+            self.fixed(y1_flipped),
+            self.fixed(x),
+            self.fixed(y_flipped),
         );
     }
 
@@ -423,12 +560,12 @@ impl OutlinePen for ScanConverter {
         let y2_flipped = self.height as f32 - y2;
         let y_flipped = self.height as f32 - y;
         self.cubic_to(
-            F26Dot6::from_float(x1),
-            F26Dot6::from_float(y1_flipped),
-            F26Dot6::from_float(x2),
-            F26Dot6::from_float(y2_flipped),
-            F26Dot6::from_float(x),
-            F26Dot6::from_float(y_flipped),
+            self.fixed(x1),
+            self.fixed(y1_flipped),
+            self.fixed(x2),
+            self.fixed(y2_flipped),
+            self.fixed(x),
+            self.fixed(y_flipped),
         );
     }
 
@@ -508,6 +645,25 @@ mod tests {
         assert_eq!(bitmap[4 * 10 + 9], 0, "Pixel (9, 4) should be white");
     }
 
+    #[test]
+    fn test_render_mono_when_cancelled_then_rows_left_empty() {
+        let mut sc = ScanConverter::new(10, 10);
+        sc.move_to(F26Dot6::from_int(2), F26Dot6::from_int(2));
+        sc.line_to(F26Dot6::from_int(8), F26Dot6::from_int(2));
+        sc.line_to(F26Dot6::from_int(8), F26Dot6::from_int(8));
+        sc.line_to(F26Dot6::from_int(2), F26Dot6::from_int(8));
+        sc.close();
+
+        let token = CancelToken::new();
+        token.cancel();
+        sc.set_cancel(Some(token));
+
+        let mut bitmap = vec![1u8; 100];
+        sc.render_mono(&mut bitmap);
+        assert!(sc.is_cancelled());
+        assert!(bitmap.iter().all(|&p| p == 0));
+    }
+
     #[test]
     fn test_render_triangle() {
         let mut sc = ScanConverter::new(20, 20);
@@ -533,6 +689,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_render_mono_when_stem_thinner_than_pixel_then_dropout_lights_it() {
+        let thin_stem = |mode: DropoutMode| {
+            let mut sc = ScanConverter::new(8, 4);
+            sc.set_dropout_mode(mode);
+            // 0.5 px wide, inside column 3
+            sc.move_to(F26Dot6::from_float(3.2), F26Dot6::from_int(0));
+            sc.line_to(F26Dot6::from_float(3.7), F26Dot6::from_int(0));
+            sc.line_to(F26Dot6::from_float(3.7), F26Dot6::from_int(4));
+            sc.line_to(F26Dot6::from_float(3.2), F26Dot6::from_int(4));
+            sc.close();
+            let mut bitmap = vec![0u8; 8 * 4];
+            sc.render_mono(&mut bitmap);
+            bitmap
+        };
+
+        assert!(thin_stem(DropoutMode::None).iter().all(|&p| p == 0));
+        for mode in [DropoutMode::Simple, DropoutMode::Smart] {
+            let bitmap = thin_stem(mode);
+            for row in bitmap.chunks(8) {
+                assert_eq!(row, [0, 0, 0, 1, 0, 0, 0, 0], "{mode:?}");
+            }
+        }
+    }
+
     #[test]
     fn test_even_odd_fill_rule() {
         let mut sc = ScanConverter::new(10, 10);
@@ -564,4 +745,61 @@ mod tests {
         assert_eq!(sc.current_x.to_int(), 0);
         assert_eq!(sc.current_y.to_int(), 0);
     }
+
+    #[test]
+    fn test_render_spans_match_mono_bitmap() {
+        let triangle = |sc: &mut ScanConverter| {
+            sc.move_to(F26Dot6::from_int(-3), F26Dot6::from_int(5));
+            sc.line_to(F26Dot6::from_int(15), F26Dot6::from_int(5));
+            sc.line_to(F26Dot6::from_int(10), F26Dot6::from_int(25));
+            sc.close();
+        };
+        let mut sc = ScanConverter::new(20, 20);
+        triangle(&mut sc);
+        let mut bitmap = vec![0u8; 400];
+        sc.render_mono(&mut bitmap);
+
+        let mut sc = ScanConverter::new(20, 20);
+        triangle(&mut sc);
+        let mut spans = Vec::new();
+        sc.render_spans(|span| spans.push(span));
+
+        assert!(!spans.is_empty());
+        // Top row first, left to right, clipped to the canvas
+        assert!(spans
+            .windows(2)
+            .all(|w| (w[0].y, w[0].x) < (w[1].y, w[1].x)));
+        let mut painted = vec![0u8; 400];
+        for span in &spans {
+            assert_eq!(span.coverage, 255);
+            assert!(span.len > 0 && span.x + span.len <= 20 && span.y < 20);
+            let start = (span.y * 20 + span.x) as usize;
+            painted[start..start + span.len as usize].fill(1);
+        }
+        assert_eq!(painted, bitmap);
+    }
+
+    #[test]
+    fn test_render_when_outline_reaches_fixed_limits_then_edges_stay_put() {
+        // The top edge runs from x = 5 to the end of the fixed-point range
+        // while climbing 1/32 of a row; its slope saturates rather than
+        // wrapping to zero, so row 3 still fills out to the right
+        let mut sc = ScanConverter::new(10, 10);
+        sc.move_to(F26Dot6::from_int(5), F26Dot6::from_raw(3 * 64 - 1));
+        sc.line_to(F26Dot6::MAX, F26Dot6::from_raw(3 * 64 + 1));
+        sc.line_to(F26Dot6::MAX, F26Dot6::from_int(20));
+        sc.line_to(F26Dot6::from_int(5), F26Dot6::from_int(20));
+        sc.close();
+
+        let mut bitmap = vec![0u8; 100];
+        sc.render_mono(&mut bitmap);
+        for (y, row) in bitmap.chunks_exact(10).enumerate() {
+            let expected: &[u8] = if y < 3 {
+                &[0; 10]
+            } else {
+                &[0, 0, 0, 0, 0, 1, 1, 1, 1, 1]
+            };
+            assert_eq!(row, expected, "row {y}");
+        }
+    }
 }
@@ -7,9 +7,10 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use typf_core::{
+    render_target::{BufferTarget, DirtyRect},
     traits::{FontRef, Renderer},
     types::{BitmapFormat, Direction, PositionedGlyph, RenderOutput, ShapingResult},
-    RenderParams,
+    Color, ColorSpan, DropShadow, RenderEffects, RenderMode, RenderParams, TextTransform,
 };
 use typf_render_opixa::OpixaRenderer;
 
@@ -217,3 +218,862 @@ fn test_opixa_supports_format() {
     assert!(!renderer.supports_format("svg")); // Raster renderer doesn't support SVG
     assert!(!renderer.supports_format("png")); // PNG encoding is done by exporter, not renderer
 }
+
+#[test]
+fn test_opixa_render_distance_field() {
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let renderer = OpixaRenderer::new();
+    let shaped = simple_shaping_result();
+    let params = RenderParams {
+        output: RenderMode::DistanceField { spread: 8 },
+        ..Default::default()
+    };
+
+    let result = renderer.render(&shaped, font, &params);
+    if let Ok(RenderOutput::Bitmap(bitmap)) = result {
+        assert_eq!(bitmap.format, BitmapFormat::Gray8);
+        assert_eq!(
+            bitmap.data.len(),
+            (bitmap.width * bitmap.height) as usize,
+            "Distance field should be one byte per pixel"
+        );
+        // Ink pixels sit above the edge value, background below it.
+        assert!(bitmap.data.iter().any(|&v| v > 128));
+        assert!(bitmap.data.iter().any(|&v| v < 128));
+    } else {
+        panic!("Expected bitmap output");
+    }
+}
+
+#[test]
+fn test_opixa_tiled_glyph_matches_single_pass() {
+    use typf_render_opixa::{rasterizer::GlyphRasterizer, DropoutMode, FillRule};
+
+    let path = test_font_path("NotoSans-Regular.ttf");
+    let Ok(data) = fs::read(&path) else {
+        eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+        return;
+    };
+
+    // Tall enough that every worker gets a band of its own.
+    let single = GlyphRasterizer::new(&data, 600.0)
+        .unwrap()
+        .with_tile_threshold(None)
+        .render_glyph(36, FillRule::NonZeroWinding, DropoutMode::None)
+        .unwrap();
+    let tiled = GlyphRasterizer::new(&data, 600.0)
+        .unwrap()
+        .with_tile_threshold(Some(1))
+        .render_glyph(36, FillRule::NonZeroWinding, DropoutMode::None)
+        .unwrap();
+
+    assert!(single.height > 200);
+    assert_eq!((tiled.width, tiled.height), (single.width, single.height));
+    assert_eq!((tiled.left, tiled.top), (single.left, single.top));
+    assert!(tiled.data == single.data, "band seams changed coverage");
+}
+
+#[test]
+fn test_opixa_preset_when_eink_mono_then_coverage_aliased() {
+    use typf_core::{Hinting, RenderPreset};
+
+    let Some(font) = load_font("NotoSans-Regular.ttf") else {
+        eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+        return;
+    };
+    let renderer = OpixaRenderer::new();
+    let shaped = simple_shaping_result();
+    let coverage = |params: &RenderParams| {
+        let params = RenderParams {
+            format: BitmapFormat::Gray8,
+            ..params.clone()
+        };
+        match renderer.render(&shaped, font.clone(), &params) {
+            Ok(RenderOutput::Bitmap(bitmap)) => bitmap.data,
+            other => panic!("Expected bitmap output, got {other:?}"),
+        }
+    };
+
+    let eink = coverage(&RenderParams::preset(RenderPreset::EinkMono));
+    assert!(eink.contains(&255));
+    assert!(
+        eink.iter().all(|&v| v == 0 || v == 255),
+        "aliased output has no gray levels"
+    );
+
+    let smooth = coverage(&RenderParams::default());
+    assert!(smooth.iter().any(|&v| v != 0 && v != 255));
+    let darker = coverage(&RenderParams {
+        gamma: 2.0,
+        ..RenderParams::default()
+    });
+    assert!(
+        darker.iter().map(|&v| v as u64).sum::<u64>()
+            > smooth.iter().map(|&v| v as u64).sum::<u64>(),
+        "gamma above 1 darkens edges"
+    );
+    let hinted = coverage(&RenderParams {
+        hinting: Hinting::Full,
+        ..RenderParams::default()
+    });
+    assert_ne!(hinted, smooth, "hinting moves the outlines");
+}
+
+#[test]
+fn test_opixa_render_into_matches_render() {
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let renderer = OpixaRenderer::new();
+    let shaped = simple_shaping_result();
+    let params = RenderParams::default();
+
+    let Ok(RenderOutput::Bitmap(expected)) = renderer.render(&shaped, font.clone(), &params) else {
+        panic!("Expected bitmap output");
+    };
+
+    let (width, height) = (expected.width, expected.height);
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+    let mut target = BufferTarget::new(
+        &mut pixels,
+        width,
+        height,
+        width as usize * 4,
+        BitmapFormat::Rgba8,
+    )
+    .expect("buffer should fit");
+    let dirty = renderer
+        .render_into(&shaped, font, &params, &mut target, (0, 0))
+        .expect("render_into should succeed");
+
+    assert!(dirty.is_some(), "Glyphs should mark a dirty region");
+    // Direct blending rounds differently from the SIMD canvas path.
+    for (got, want) in pixels.iter().zip(&expected.data) {
+        assert!(got.abs_diff(*want) <= 2, "{got} vs {want}");
+    }
+}
+
+#[test]
+fn test_opixa_render_region_redraws_only_clip() {
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let renderer = OpixaRenderer::new();
+    let shaped = simple_shaping_result();
+    let params = RenderParams::default();
+
+    let Ok(RenderOutput::Bitmap(full)) = renderer.render(&shaped, font.clone(), &params) else {
+        panic!("Expected bitmap output");
+    };
+    let (width, height) = (full.width, full.height);
+    let stride = width as usize * 4;
+
+    let mut full_pixels = vec![0u8; stride * height as usize];
+    let mut target =
+        BufferTarget::new(&mut full_pixels, width, height, stride, BitmapFormat::Rgba8)
+            .expect("buffer should fit");
+    renderer
+        .render_into(&shaped, font.clone(), &params, &mut target, (0, 0))
+        .expect("render_into should succeed");
+
+    // Repaint the right half, where only the second glyph lands.
+    let clip = DirtyRect {
+        x: width / 2,
+        y: 0,
+        width: width - width / 2,
+        height,
+    };
+    let mut region_pixels = vec![0u8; stride * height as usize];
+    let mut target = BufferTarget::new(
+        &mut region_pixels,
+        width,
+        height,
+        stride,
+        BitmapFormat::Rgba8,
+    )
+    .expect("buffer should fit");
+    let dirty = renderer
+        .render_region(&shaped, font, &params, &mut target, (0, 0), clip)
+        .expect("render_region should succeed")
+        .expect("second glyph should be drawn");
+
+    assert!(dirty.x >= clip.x, "Dirty region must stay inside the clip");
+    for y in 0..height as usize {
+        for x in 0..width as usize {
+            let at = y * stride + x * 4;
+            let got = &region_pixels[at..at + 4];
+            if x < clip.x as usize {
+                assert_eq!(got, &[0, 0, 0, 0], "pixel ({x}, {y}) is outside the clip");
+            } else {
+                assert_eq!(got, &full_pixels[at..at + 4], "pixel ({x}, {y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_opixa_render_region_when_raised_glyph_culled_then_baseline_matches_full_render() {
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let renderer = OpixaRenderer::new();
+    let mut shaped = simple_shaping_result();
+    // Raise the first glyph like a superscript so it alone sets the line top
+    shaped.glyphs[0].y = -40.0;
+    let params = RenderParams::default();
+
+    let Ok(RenderOutput::Bitmap(full)) = renderer.render(&shaped, font.clone(), &params) else {
+        panic!("Expected bitmap output");
+    };
+    let (width, height) = (full.width, full.height);
+    let stride = width as usize * 4;
+
+    let mut full_pixels = vec![0u8; stride * height as usize];
+    let mut target =
+        BufferTarget::new(&mut full_pixels, width, height, stride, BitmapFormat::Rgba8)
+            .expect("buffer should fit");
+    renderer
+        .render_into(&shaped, font.clone(), &params, &mut target, (0, 0))
+        .expect("render_into should succeed");
+
+    // The clip excludes the raised glyph, which is measured but not drawn
+    let clip = DirtyRect {
+        x: width / 2,
+        y: 0,
+        width: width - width / 2,
+        height,
+    };
+    let mut region_pixels = vec![0u8; stride * height as usize];
+    let mut target = BufferTarget::new(
+        &mut region_pixels,
+        width,
+        height,
+        stride,
+        BitmapFormat::Rgba8,
+    )
+    .expect("buffer should fit");
+    renderer
+        .render_region(&shaped, font, &params, &mut target, (0, 0), clip)
+        .expect("render_region should succeed")
+        .expect("second glyph should be drawn");
+
+    for y in 0..height as usize {
+        for x in clip.x as usize..width as usize {
+            let at = y * stride + x * 4;
+            assert_eq!(
+                &region_pixels[at..at + 4],
+                &full_pixels[at..at + 4],
+                "pixel ({x}, {y})"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_opixa_render_rotated_run_is_upright_canvas() {
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let renderer = OpixaRenderer::new();
+    let shaped = simple_shaping_result();
+    let render = |transform| {
+        let params = RenderParams {
+            transform,
+            ..RenderParams::default()
+        };
+        match renderer.render(&shaped, font.clone(), &params) {
+            Ok(RenderOutput::Bitmap(bitmap)) => bitmap,
+            other => panic!("Expected bitmap output, got {:?}", other.map(|_| ())),
+        }
+    };
+
+    let plain = render(None);
+    let identity = render(Some(TextTransform::IDENTITY));
+    assert_eq!(
+        plain.data, identity.data,
+        "Identity must match no transform"
+    );
+
+    // A quarter turn stands the run on end
+    let rotated = render(Some(TextTransform::rotate(90.0)));
+    assert!(plain.width > plain.height);
+    assert!(
+        rotated.height > rotated.width,
+        "rotated canvas should be tall: {}x{}",
+        rotated.width,
+        rotated.height
+    );
+    assert!(rotated.data.chunks(4).any(|px| px[3] > 0), "Ink expected");
+}
+
+#[test]
+fn test_opixa_render_degenerate_transform_errors() {
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let params = RenderParams {
+        transform: Some(TextTransform::scale(0.0, 1.0)),
+        ..RenderParams::default()
+    };
+    let result = OpixaRenderer::new().render(&simple_shaping_result(), font, &params);
+    assert!(result.is_err(), "Degenerate transform should be rejected");
+}
+
+#[test]
+fn test_opixa_render_baseline_shift_raises_ink_off_baseline() {
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    // Rows below the lowest ink, i.e. how far the ink sits above the baseline
+    let blank_rows_below_ink = |params: &RenderParams| {
+        let output = OpixaRenderer::new()
+            .render(&simple_shaping_result(), font.clone(), params)
+            .expect("Rendering should succeed");
+        let RenderOutput::Bitmap(bitmap) = output else {
+            panic!("Expected bitmap output");
+        };
+        let row_bytes = (bitmap.width * 4) as usize;
+        bitmap
+            .data
+            .chunks(row_bytes)
+            .rev()
+            .take_while(|row| row.chunks(4).all(|px| px[3] == 0))
+            .count()
+    };
+
+    let flat = blank_rows_below_ink(&RenderParams::default());
+    let raised = blank_rows_below_ink(&RenderParams {
+        baseline_shift: 20.0,
+        ..RenderParams::default()
+    });
+    // Descender overshoot of 'e' eats a few of the 20 rows
+    assert!(
+        (15..=20).contains(&(raised - flat)),
+        "ink should rise about 20px off the baseline: {flat} -> {raised}"
+    );
+}
+
+#[test]
+fn test_opixa_color_spans_color_each_glyph() {
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let renderer = OpixaRenderer::new();
+    let shaped = simple_shaping_result();
+    let yellow = Color::rgba(255, 255, 0, 255);
+    let params = RenderParams {
+        color_spans: vec![
+            ColorSpan::foreground(0..1, Color::rgba(255, 0, 0, 255)),
+            ColorSpan::foreground(1..2, Color::rgba(0, 0, 255, 255)).with_background(yellow),
+        ],
+        ..RenderParams::default()
+    };
+
+    let Ok(RenderOutput::Bitmap(bitmap)) = renderer.render(&shaped, font, &params) else {
+        panic!("Expected bitmap output");
+    };
+    let pixels: Vec<&[u8]> = bitmap.data.chunks_exact(4).collect();
+    let top_row = &pixels[..bitmap.width as usize];
+
+    assert!(pixels.iter().any(|p| p[0] > 200 && p[2] < 50 && p[3] > 200));
+    assert!(pixels.iter().any(|p| p[2] > 200 && p[0] < 50));
+    assert_ne!(
+        top_row[0],
+        &[255, 255, 0, 255][..],
+        "first glyph has no fill"
+    );
+    assert!(
+        top_row.iter().any(|p| *p == [255, 255, 0, 255]),
+        "second glyph's fill spans the canvas height"
+    );
+}
+
+#[test]
+fn test_opixa_drop_shadow_casts_blurred_offset_copy() {
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let renderer = OpixaRenderer::new();
+    let shaped = simple_shaping_result();
+    let params = RenderParams {
+        effects: RenderEffects {
+            shadow: Some(DropShadow {
+                offset_x: 6.0,
+                offset_y: 4.0,
+                blur_radius: 3.0,
+                color: Color::rgba(255, 0, 0, 255),
+            }),
+            ..RenderEffects::default()
+        },
+        ..RenderParams::default()
+    };
+
+    let Ok(RenderOutput::Bitmap(bitmap)) = renderer.render(&shaped, font, &params) else {
+        panic!("Expected bitmap output");
+    };
+    let pixels: Vec<&[u8]> = bitmap.data.chunks_exact(4).collect();
+
+    assert!(
+        pixels.iter().any(|p| p[0] > 200 && p[3] > 200),
+        "solid shadow"
+    );
+    assert!(
+        pixels.iter().any(|p| p[0] > 0 && p[3] > 0 && p[3] < 64),
+        "blurred shadow edge"
+    );
+    assert!(
+        pixels.iter().any(|p| p[0] < 20 && p[3] > 200),
+        "text drawn over its shadow"
+    );
+}
+
+#[test]
+fn test_opixa_subtitle_draws_shadow_border_then_fill() {
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let renderer = OpixaRenderer::new();
+    let shaped = simple_shaping_result();
+    let params = RenderParams::subtitle(shaped.advance_height);
+
+    let Ok(RenderOutput::Bitmap(bitmap)) = renderer.render(&shaped, font, &params) else {
+        panic!("Expected bitmap output");
+    };
+    let pixels: Vec<&[u8]> = bitmap.data.chunks_exact(4).collect();
+
+    assert!(
+        pixels.iter().any(|p| p[0] > 250 && p[3] > 250),
+        "white fill"
+    );
+    assert!(
+        pixels.iter().any(|p| p[0] < 5 && p[3] > 250),
+        "black border"
+    );
+    assert!(
+        pixels.iter().any(|p| p[3] > 0 && p[3] < 160),
+        "translucent shadow"
+    );
+
+    // Reading each row from the left, the border comes before the fill
+    for row in bitmap.data.chunks_exact(bitmap.width as usize * 4) {
+        let mut opaque = row.chunks_exact(4).filter(|p| p[3] > 250);
+        if row.chunks_exact(4).any(|p| p[0] > 250 && p[3] > 250) {
+            let first = opaque.next().unwrap();
+            assert!(first[0] < 128, "fill drawn over its border: {first:?}");
+        }
+    }
+}
+
+#[test]
+fn test_opixa_coverage_formats_match_rgba_alpha() {
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let renderer = OpixaRenderer::new();
+    // Apart, so no pixel takes coverage from two glyphs
+    let mut shaped = simple_shaping_result();
+    shaped.glyphs[1].x = 200.0;
+    shaped.advance_width = 280.0;
+    let render = |format| {
+        let params = RenderParams {
+            format,
+            ..RenderParams::default()
+        };
+        match renderer.render(&shaped, font.clone(), &params) {
+            Ok(RenderOutput::Bitmap(bitmap)) => bitmap,
+            other => panic!("Expected bitmap output, got {other:?}"),
+        }
+    };
+
+    let rgba = render(BitmapFormat::Rgba8);
+    let gray = render(BitmapFormat::Gray8);
+    let mono = render(BitmapFormat::Gray1);
+    assert_eq!(gray.format, BitmapFormat::Gray8);
+    assert_eq!(mono.format, BitmapFormat::Gray1);
+    assert_eq!((gray.width, gray.height), (rgba.width, rgba.height));
+    assert_eq!(gray.data.len(), (gray.width * gray.height) as usize);
+    let stride = (mono.width as usize).div_ceil(8);
+    assert_eq!(mono.data.len(), stride * mono.height as usize);
+
+    let mut covered = 0;
+    for (i, (&coverage, px)) in gray.data.iter().zip(rgba.data.chunks_exact(4)).enumerate() {
+        assert!(
+            coverage.abs_diff(px[3]) <= 1,
+            "pixel {i}: {coverage} vs {}",
+            px[3]
+        );
+        let (x, y) = (i % gray.width as usize, i / gray.width as usize);
+        let bit = mono.data[y * stride + x / 8] & (0x80 >> (x % 8)) != 0;
+        assert_eq!(bit, coverage >= 128, "pixel ({x}, {y})");
+        covered += bit as usize;
+    }
+    assert!(covered > 0, "some pixels should be set");
+
+    let params = RenderParams {
+        format: BitmapFormat::Rgb8,
+        ..RenderParams::default()
+    };
+    assert!(renderer.render(&shaped, font, &params).is_err());
+}
+
+#[test]
+fn test_opixa_row_alignment_pads_rows_without_changing_pixels() {
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let renderer = OpixaRenderer::new();
+    let shaped = simple_shaping_result();
+    for format in [
+        BitmapFormat::Rgba8,
+        BitmapFormat::Gray8,
+        BitmapFormat::Gray1,
+    ] {
+        let render = |row_alignment| {
+            let params = RenderParams {
+                format,
+                row_alignment,
+                ..RenderParams::default()
+            };
+            match renderer.render(&shaped, font.clone(), &params) {
+                Ok(RenderOutput::Bitmap(bitmap)) => bitmap,
+                other => panic!("Expected bitmap output, got {other:?}"),
+            }
+        };
+
+        let packed = render(1);
+        let aligned = render(256);
+        assert!(packed.is_packed(), "{format:?}");
+        assert_eq!(aligned.stride % 256, 0, "{format:?}");
+        assert!(aligned.stride >= format.row_bytes(aligned.width));
+        assert_eq!(aligned.data.len(), aligned.stride * aligned.height as usize);
+        for y in 0..packed.height {
+            let (a, b) = (packed.row(y).unwrap(), aligned.row(y).unwrap());
+            // The padded path blends in straight alpha rather than SIMD
+            let close = a.iter().zip(b).all(|(p, q)| p.abs_diff(*q) <= 2);
+            assert!(close, "{format:?} row {y} differs");
+        }
+    }
+}
+
+/// First glyph Nabla paints through COLR, found by walking the glyph ids
+#[cfg(feature = "color")]
+fn first_colr_glyph(data: &[u8]) -> Option<u32> {
+    use skrifa::{color::ColorGlyphFormat, raw::TableProvider, GlyphId, MetadataProvider};
+
+    let font = skrifa::FontRef::new(data).ok()?;
+    let color_glyphs = font.color_glyphs();
+    let count = font.maxp().ok()?.num_glyphs() as u32;
+    (0..count).find(|&gid| {
+        let glyph_id = GlyphId::new(gid);
+        [ColorGlyphFormat::ColrV1, ColorGlyphFormat::ColrV0]
+            .into_iter()
+            .any(|format| color_glyphs.get_with_format(glyph_id, format).is_some())
+    })
+}
+
+#[cfg(feature = "color")]
+#[test]
+fn test_opixa_colr_glyph_paints_palette_colors_without_outlines() {
+    use typf_core::{GlyphSource, GlyphSourcePreference};
+
+    let font = match load_font("Nabla-Regular-COLR.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: Nabla-Regular-COLR.ttf not found");
+            return;
+        },
+    };
+    let glyph_id = first_colr_glyph(font.data()).expect("Nabla has COLR glyphs");
+
+    let shaped = ShapingResult {
+        glyphs: vec![PositionedGlyph {
+            id: glyph_id,
+            x: 0.0,
+            y: 0.0,
+            advance: 40.0,
+            cluster: 0,
+        }],
+        advance_width: 40.0,
+        advance_height: 48.0,
+        direction: Direction::LeftToRight,
+    };
+    let params = RenderParams {
+        foreground: Color::rgba(0, 0, 0, 255),
+        glyph_sources: GlyphSourcePreference::from_parts(
+            vec![GlyphSource::Colr1, GlyphSource::Colr0],
+            [GlyphSource::Glyf, GlyphSource::Cff, GlyphSource::Cff2],
+        ),
+        ..RenderParams::default()
+    };
+
+    let renderer = OpixaRenderer::new();
+    let bitmap = match renderer.render(&shaped, font.clone(), &params) {
+        Ok(RenderOutput::Bitmap(bitmap)) => bitmap,
+        other => panic!("Expected bitmap output, got {other:?}"),
+    };
+    // Black foreground, so any hue comes from the palette
+    let colored = bitmap
+        .data
+        .chunks_exact(4)
+        .any(|px| px[3] > 0 && px[..3].iter().any(|&c| c > 0));
+    assert!(colored, "COLR glyph should paint palette colors");
+
+    let mask = renderer
+        .rasterize_glyph(font.as_ref(), glyph_id, 48.0, &params)
+        .unwrap();
+    let rgba = mask.color.expect("COLR glyph comes back in color");
+    assert_eq!(rgba.len(), mask.coverage.len() * 4);
+}
+
+#[test]
+fn test_opixa_rasterize_glyph_matches_rendered_run() {
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let renderer = OpixaRenderer::with_cache();
+    let params = RenderParams::default();
+    let mask = renderer
+        .rasterize_glyph(font.as_ref(), 36, 48.0, &params)
+        .unwrap();
+    assert!(!mask.is_empty());
+    assert!(mask.color.is_none());
+    assert_eq!(mask.coverage.len(), (mask.width * mask.height) as usize);
+    assert!(mask.top > 0, "'H' sits above the baseline");
+
+    // The same glyph drawn as a run covers the same pixels
+    let shaped = ShapingResult {
+        glyphs: vec![PositionedGlyph {
+            id: 36,
+            x: 0.0,
+            y: 0.0,
+            advance: 40.0,
+            cluster: 0,
+        }],
+        advance_width: 40.0,
+        advance_height: 48.0,
+        direction: Direction::LeftToRight,
+    };
+    let bitmap = match renderer.render(&shaped, font.clone(), &params) {
+        Ok(RenderOutput::Bitmap(bitmap)) => bitmap,
+        other => panic!("Expected bitmap output, got {other:?}"),
+    };
+    let rendered: u64 = bitmap.data.chunks_exact(4).map(|px| px[3] as u64).sum();
+    let rasterized: u64 = mask.coverage.iter().map(|&c| c as u64).sum();
+    assert!(rendered.abs_diff(rasterized) <= rasterized / 100);
+
+    // Both calls went through the cache; the run hit the glyph's entry
+    let stats = renderer.cache_stats().unwrap();
+    assert!(stats.hits >= 1, "{stats:?}");
+}
+
+#[test]
+fn test_opixa_rasterize_glyph_errors_without_glyph_sources() {
+    use typf_core::{GlyphSource, GlyphSourcePreference};
+
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let params = RenderParams {
+        glyph_sources: GlyphSourcePreference::from_parts(
+            vec![GlyphSource::Colr1],
+            [GlyphSource::Glyf, GlyphSource::Cff, GlyphSource::Cff2],
+        ),
+        ..RenderParams::default()
+    };
+    let renderer = OpixaRenderer::new();
+    assert!(renderer
+        .rasterize_glyph(font.as_ref(), 36, 48.0, &params)
+        .is_err());
+}
+
+#[test]
+fn test_opixa_rasterize_glyph_at_poster_size_keeps_its_shape() {
+    use skrifa::MetadataProvider;
+
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+    let o = skrifa::FontRef::new(font.data())
+        .unwrap()
+        .charmap()
+        .map('o')
+        .unwrap()
+        .to_u32();
+
+    let renderer = OpixaRenderer::new();
+    let params = RenderParams::default();
+    let small = renderer
+        .rasterize_glyph(font.as_ref(), o, 100.0, &params)
+        .unwrap();
+    let poster = renderer
+        .rasterize_glyph(font.as_ref(), o, 10_000.0, &params)
+        .unwrap();
+
+    // A hundred times the size: a hundred times the extent...
+    let scaled = |small: u32, big: u32| big.abs_diff(small * 100) <= 100;
+    assert!(
+        scaled(small.width, poster.width),
+        "{} vs {}",
+        small.width,
+        poster.width
+    );
+    assert!(
+        scaled(small.height, poster.height),
+        "{} vs {}",
+        small.height,
+        poster.height
+    );
+    assert!((poster.top - small.top * 100).abs() <= 100);
+
+    // ...the same share of ink...
+    let ink = |mask: &typf_render_opixa::GlyphMask| {
+        let sum: u64 = mask.coverage.iter().map(|&c| c as u64).sum();
+        sum as f64 / (255.0 * mask.width as f64 * mask.height as f64)
+    };
+    let (small_ink, poster_ink) = (ink(&small), ink(&poster));
+    assert!(
+        (small_ink - poster_ink).abs() < 0.02,
+        "ink {small_ink:.3} at 100 px vs {poster_ink:.3} at 10,000 px"
+    );
+
+    // ...and no stray edges: solid on the left stroke, empty in the counter
+    let at = |x: u32, y: u32| poster.coverage[(y * poster.width + x) as usize];
+    let mid = poster.height / 2;
+    assert_eq!(at(poster.width / 2, mid), 0, "counter should be empty");
+    assert_eq!(at(poster.width / 20, mid), 255, "stroke should be solid");
+    assert_eq!(at(0, 0), 0, "corner outside the bowl should be empty");
+}
+
+#[test]
+fn test_opixa_antialias_quality_levels_agree_on_ink() {
+    use typf_core::AntialiasQuality;
+
+    let font = match load_font("NotoSans-Regular.ttf") {
+        Some(f) => f,
+        None => {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        },
+    };
+
+    let renderer = OpixaRenderer::with_cache();
+    let mask = |quality| {
+        let params = RenderParams {
+            antialias_quality: quality,
+            ..RenderParams::default()
+        };
+        renderer
+            .rasterize_glyph(font.as_ref(), 36, 24.0, &params)
+            .unwrap()
+    };
+    let fine = mask(AntialiasQuality::Oversample16x);
+    let gray_levels = |m: &typf_render_opixa::GlyphMask| {
+        let mut seen = [false; 256];
+        m.coverage.iter().for_each(|&c| seen[c as usize] = true);
+        seen.iter().filter(|&&s| s).count()
+    };
+
+    for quality in [
+        AntialiasQuality::Oversample4x,
+        AntialiasQuality::Oversample8x,
+        AntialiasQuality::Analytic,
+    ] {
+        let m = mask(quality);
+        // Same placement; each pixel within a 4x4 grid step (a quarter
+        // pixel of edge) of 16x16, and the same total ink within 3%
+        assert_eq!(
+            (m.width, m.height, m.left, m.top),
+            (fine.width, fine.height, fine.left, fine.top)
+        );
+        for (i, (&a, &b)) in m.coverage.iter().zip(&fine.coverage).enumerate() {
+            assert!(a.abs_diff(b) <= 64, "{quality:?} pixel {i}: {a} vs {b}");
+        }
+        let ink =
+            |m: &typf_render_opixa::GlyphMask| m.coverage.iter().map(|&c| c as i64).sum::<i64>();
+        let (got, want) = (ink(&m), ink(&fine));
+        assert!(
+            (got - want).abs() * 100 <= want * 3,
+            "{quality:?} ink {got} vs {want}"
+        );
+    }
+    // Finer grids reach more shades along the edges
+    let coarse = mask(AntialiasQuality::Oversample4x);
+    assert!(gray_levels(&coarse) <= 17);
+    assert!(gray_levels(&fine) > gray_levels(&coarse));
+}
@@ -17,16 +17,29 @@
 
 use kurbo::Shape;
 use skrifa::MetadataProvider;
+use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::Arc;
 use typf_core::{
-    error::{RenderError, Result},
+    budget::{self, Reservation},
+    cancel, color_span,
+    diagnostics::{self, DiagnosticKind, Diagnostics},
+    effects::CoverageMask,
+    error::{ErrorContext, RenderError, Result, TypfError},
+    outline::BezPathPen,
+    render_target::{self, BufferTarget, ClipTarget, DirtyRect, GlyphCull, RenderTarget},
+    sanitize, source_log,
     traits::{FontRef, Renderer},
-    types::{BitmapData, BitmapFormat, RenderOutput, ShapingResult, VectorFormat},
-    GlyphSource, GlyphSourcePreference, RenderMode, RenderParams,
+    types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput, ShapingResult, VectorFormat},
+    GlyphSource, GlyphSourcePreference, RenderMode, RenderParams, SubpixelPositioning,
+    TextTransform,
 };
-use typf_render_color::{compute_content_bounds, render_glyph_with_preference};
+use typf_render_color::{compute_content_bounds, ColorFont, ColorRenderError};
 use typf_render_svg::SvgRenderer;
 
+mod pixmap_target;
+pub use pixmap_target::PixmapTarget;
+
 /// tiny-skia powered renderer for pristine glyph output
 ///
 /// This isn't just another bitmap renderer—it's a precision instrument
@@ -37,6 +50,8 @@ pub struct SkiaRenderer {
     max_width: u32,
     /// Maximum canvas height to prevent memory exhaustion
     max_height: u32,
+    /// Minimum padding on every side of the canvas
+    default_padding: u32,
 }
 
 impl SkiaRenderer {
@@ -45,14 +60,26 @@ impl SkiaRenderer {
         Self {
             max_width: typf_core::get_max_bitmap_width(),
             max_height: typf_core::get_max_bitmap_height(),
+            default_padding: 0,
         }
     }
 
+    /// Sets the minimum padding, used when `RenderParams::padding` is smaller
+    pub fn with_default_padding(mut self, padding: u32) -> Self {
+        self.default_padding = padding;
+        self
+    }
+
     /// Converts a single glyph from outline to bitmap with surgical precision
     ///
     /// This method extracts the glyph outline using skrifa, builds a path,
     /// and renders it with tiny-skia's advanced anti-aliasing. The result
     /// is a clean alpha bitmap ready for compositing.
+    ///
+    /// With a `shift`, the outline keeps its fractional position, moved
+    /// right by `shift` pixels, instead of snapping its left edge to the
+    /// bitmap's; see [`SubpixelPositioning`].
+    #[allow(clippy::too_many_arguments)]
     fn render_glyph(
         &self,
         font: &Arc<dyn FontRef>,
@@ -60,13 +87,16 @@ impl SkiaRenderer {
         font_size: f32,
         location: &skrifa::instance::Location,
         params: &RenderParams,
+        transform: Option<TextTransform>,
+        shift: Option<f32>,
     ) -> Result<GlyphBitmap> {
         use kurbo::{BezPath, PathEl};
         use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, Transform};
 
         // Pull raw font data for skrifa to parse
         let font_data = font.data();
-        let font_ref = skrifa::FontRef::new(font_data).map_err(|_| RenderError::InvalidFont)?;
+        let font_ref = skrifa::FontRef::from_index(font_data, font.face_index())
+            .map_err(|_| RenderError::InvalidFont)?;
         let color_allowed = allows_color_sources(&params.glyph_sources);
         log::debug!("Skia: color_allowed={}", color_allowed);
 
@@ -106,11 +136,8 @@ impl SkiaRenderer {
         // Build a kurbo path from the glyph's outline data
         let mut path = BezPath::new();
         // skrifa's DrawSettings handles the tricky font-unit-to-pixel scaling
-        // for us, so our PathPen can stay simple and focused
-        let mut pen = PathPen {
-            path: &mut path,
-            scale: 1.0, // skrifa does the heavy lifting on scaling
-        };
+        // for us, so the pen only has to copy points
+        let mut pen = BezPathPen::new(&mut path);
 
         // Request unhinted outlines at the exact size we need
         let size = skrifa::instance::Size::new(font_size);
@@ -122,19 +149,17 @@ impl SkiaRenderer {
             .draw(settings, &mut pen)
             .map_err(|_| RenderError::OutlineExtractionFailed)?;
 
-        // Figure out how much canvas space this glyph needs
-        let mut bbox = path.bounding_box();
+        // Figure out how much canvas space this glyph needs; malformed
+        // glyphs are clamped (or refused, when strict) before they size a pixmap
+        let mut bbox = sanitize_bbox(
+            glyph_id.to_u32(),
+            path.bounding_box(),
+            font_size,
+            params.strict,
+            params.diagnostics.as_ref(),
+        )?;
 
         let outline_empty = bbox.width() == 0.0 || bbox.height() == 0.0;
-
-        // Guard against malformed glyphs that could crash the renderer
-        if bbox.x0.is_infinite()
-            || bbox.y0.is_infinite()
-            || bbox.x1.is_infinite()
-            || bbox.y1.is_infinite()
-        {
-            return Err(RenderError::PathBuildingFailed.into());
-        }
         if outline_empty && color_allowed {
             let fallback = font_size.max(1.0) as f64;
             bbox = kurbo::Rect::new(0.0, 0.0, fallback, fallback);
@@ -208,6 +233,42 @@ impl SkiaRenderer {
             .into());
         }
 
+        // Transform the outline only now: color glyphs above keep the
+        // upright bounds their renderer expects
+        let (width, height) = match transform {
+            Some(t) if !outline_empty => {
+                path.apply_affine(kurbo::Affine::new([
+                    t.xx as f64,
+                    t.yx as f64,
+                    t.xy as f64,
+                    t.yy as f64,
+                    0.0,
+                    0.0,
+                ]));
+                bbox = sanitize_bbox(
+                    glyph_id.to_u32(),
+                    path.bounding_box(),
+                    font_size,
+                    params.strict,
+                    params.diagnostics.as_ref(),
+                )?;
+                (
+                    (bbox.width().ceil() as u32).max(1),
+                    (bbox.height().ceil() as u32).max(1),
+                )
+            },
+            _ => (width, height),
+        };
+        let (left, width) = match shift {
+            Some(shift) if !outline_empty => {
+                path.apply_affine(kurbo::Affine::translate((shift as f64, 0.0)));
+                bbox = bbox + kurbo::Vec2::new(shift as f64, 0.0);
+                let left = bbox.x0.floor();
+                (left, ((bbox.x1 - left).ceil() as u32).max(1))
+            },
+            _ => (bbox.x0, width),
+        };
+
         // Translate kurbo's path format into tiny-skia's native format
         let mut builder = PathBuilder::new();
         for element in path.elements() {
@@ -231,7 +292,12 @@ impl SkiaRenderer {
 
         let skia_path = builder.finish().ok_or(RenderError::PathBuildingFailed)?;
 
-        // Create our rendering surface
+        // Create our rendering surface; the coverage copied out of it is
+        // reserved by the caller with the rest of the glyph bitmaps
+        let _scratch = budget::reserve(
+            params.memory_budget.as_ref(),
+            width as usize * height as usize * 4,
+        )?;
         let mut pixmap = Pixmap::new(width, height).ok_or(RenderError::PixmapCreationFailed)?;
 
         // Set up painter with anti-aliasing for smooth edges
@@ -244,7 +310,7 @@ impl SkiaRenderer {
         // 1. Flip Y (fonts use y-up, bitmaps use y-down)
         // 2. Shift so bbox fits perfectly in our pixmap
         let transform =
-            Transform::from_scale(1.0, -1.0).post_translate(-bbox.x0 as f32, bbox.y1 as f32);
+            Transform::from_scale(1.0, -1.0).post_translate(-left as f32, bbox.y1 as f32);
 
         // Render the filled path to our pixmap
         pixmap.fill_path(&skia_path, &paint, FillRule::Winding, transform, None);
@@ -263,8 +329,9 @@ impl SkiaRenderer {
             width,
             height,
             data: GlyphBitmapData::Mask(alpha),
-            bearing_x: bbox.x0.floor() as i32,
+            bearing_x: left.floor() as i32,
             bearing_y: bbox.y1.ceil() as i32,
+            source: source_log::outline_source(font_data, font.face_index()),
         })
     }
 
@@ -300,16 +367,21 @@ impl SkiaRenderer {
             width,
             height
         );
-        match render_glyph_with_preference(
-            font.data(),
-            glyph_id,
-            width,
-            height,
-            font_size,
-            params.color_palette,
-            &variations,
-            &params.glyph_sources,
-        ) {
+        let rendered =
+            ColorFont::from_index(font.data(), font.face_index()).and_then(|color_font| {
+                color_font
+                    .with_memory_budget(params.memory_budget.clone())
+                    .render_glyph_with_preference(
+                        glyph_id,
+                        width,
+                        height,
+                        font_size,
+                        params.color_palette.resolve(font.data(), font.face_index()),
+                        &variations,
+                        &params.glyph_sources,
+                    )
+            });
+        match rendered {
             Ok((rendered, source_used)) => {
                 log::debug!(
                     "Skia try_color_glyph: success via {:?}, pixmap={}x{}",
@@ -408,9 +480,10 @@ impl SkiaRenderer {
                     data: GlyphBitmapData::RgbaPremul(rgba_data),
                     bearing_x,
                     bearing_y,
+                    source: source_used,
                 }))
             },
-            Err(typf_render_color::ColorRenderError::GlyphNotFound) => {
+            Err(ColorRenderError::GlyphNotFound) => {
                 // No color glyph available - allow outline fallback
                 log::debug!(
                     "Skia: no color glyph for {}, falling back to outline",
@@ -418,28 +491,364 @@ impl SkiaRenderer {
                 );
                 Ok(None)
             },
-            Err(typf_render_color::ColorRenderError::NoColrTable) => {
+            Err(ColorRenderError::NoColrTable) => {
                 // Font has no COLR table - allow outline fallback
                 Ok(None)
             },
-            Err(typf_render_color::ColorRenderError::NoPalette) => {
-                // No palette available - allow outline fallback
-                log::debug!(
-                    "Skia: no palette for glyph {}, falling back to outline",
-                    glyph_id
+            Err(ColorRenderError::NoPalette) => {
+                // A color glyph without a palette to paint it; its outline stands in
+                diagnostics::warn(
+                    params.diagnostics.as_ref(),
+                    DiagnosticKind::Fallback,
+                    Some(glyph_id),
+                    format!("Skia: no palette for color glyph {glyph_id}; drew its outline"),
                 );
                 Ok(None)
             },
+            // Budget errors stay bare so the run stops on them
+            Err(ColorRenderError::BudgetExceeded(e)) => Err(e.into()),
             Err(err) => {
                 // Actual rendering error (pixmap creation failed, paint error, etc.)
-                Err(RenderError::BackendError(format!(
-                    "color glyph {} render failed: {:?}",
-                    glyph_id, err
-                ))
-                .into())
+                Err(TypfError::from(err).with_context(ErrorContext::glyph(glyph_id)))
             },
         }
     }
+
+    /// Phases 1 and 2 of rendering: rasterize every glyph and size the canvas.
+    ///
+    /// Shared by `render`, which composites into a fresh canvas, and
+    /// `render_into`, which composites into a caller's target.
+    fn layout(
+        &self,
+        shaped: &ShapingResult,
+        font: &Arc<dyn FontRef>,
+        params: &RenderParams,
+        cull: Option<&GlyphCull>,
+    ) -> Result<Layout> {
+        // Explicit padding plus room for effects, so strokes and shadows are never clipped
+        let insets = params.canvas_insets(self.default_padding);
+        let pad_top = insets.top as f32;
+        let glyph_size = shaped.advance_height;
+
+        // Build variable font location from params.variations
+        let location = build_location(font, &params.variations);
+        diagnostics::check_variations(params.diagnostics.as_ref(), &**font, &params.variations);
+        let transform = params.active_transform()?;
+
+        // Phase 1: Render all glyphs first to get accurate bounds
+        // This ensures we don't clip tall glyphs (emoji, Thai marks, Arabic diacritics)
+        let mut rendered_glyphs: Vec<RenderedGlyph> = Vec::new();
+        let mut memory = budget::reserve(params.memory_budget.as_ref(), 0)?;
+        let mut culled = 0usize;
+        let mut min_y: f32 = 0.0; // Relative to baseline
+        let mut max_y: f32 = 0.0; // Relative to baseline
+
+        // Horizontal ink extent, only needed to fit transformed runs
+        let mut min_x = f32::MAX;
+        let mut max_x = f32::MIN;
+        let mut last_error: Option<String> = None;
+        // Repeated glyphs at the same phase share one bitmap
+        let mut rasterized: HashMap<(u32, u32), Rc<GlyphBitmap>> = HashMap::new();
+
+        for (index, glyph) in shaped.glyphs.iter().enumerate() {
+            cancel::check(params.cancel.as_ref())?;
+            // Transformed pens only map to canvas columns after layout, so
+            // clipped redraws of transformed runs keep every glyph.
+            let skip = cull.filter(|c| {
+                transform.is_none() && !c.keeps(insets.left as f32 + glyph.x, glyph.advance)
+            });
+            if let Some(c) = skip {
+                // Measured instead of drawn, so the line box matches a full render
+                let extent = c.skipped_extent(&**font, glyph.id, glyph_size, &params.variations);
+                if let Some((top, bottom)) = extent {
+                    let lift = glyph.y + params.baseline_shift;
+                    max_y = max_y.max(lift + top);
+                    min_y = min_y.min(lift + bottom);
+                }
+                culled += 1;
+                continue;
+            }
+            // Compositing treats `glyph.y` as a downward offset; flip it into
+            // y-up for the matrix and back. The baseline shift raises the pen
+            // before the transform, so rotated runs shift along their normal.
+            let shift = params.baseline_shift;
+            let (glyph_x, glyph_y) = match transform {
+                Some(t) => {
+                    let (x, y) = t.apply(glyph.x, shift - glyph.y);
+                    (x, -y)
+                },
+                None => (glyph.x, glyph.y - shift),
+            };
+            // Transformed pens land on fractional canvas columns anyway
+            let (glyph_x, phase) = match params.subpixel {
+                SubpixelPositioning::Off => (glyph_x, None),
+                _ if transform.is_some() => (glyph_x, None),
+                subpixel => {
+                    let (whole, phase) = subpixel.split(glyph_x);
+                    (whole, Some(phase))
+                },
+            };
+            let key = (glyph.id, phase.unwrap_or(0));
+            let bitmap = match rasterized.get(&key) {
+                Some(bitmap) => Ok(Rc::clone(bitmap)),
+                None => self
+                    .render_glyph(
+                        font,
+                        glyph.id,
+                        glyph_size,
+                        &location,
+                        params,
+                        transform,
+                        phase.map(|phase| params.subpixel.offset(phase)),
+                    )
+                    .map(|bitmap| Rc::clone(rasterized.entry(key).or_insert(Rc::new(bitmap)))),
+            };
+            match bitmap {
+                Ok(bitmap) => {
+                    if let Some(log) = &params.glyph_source_log {
+                        log.record(index, glyph.id, bitmap.source);
+                    }
+                    // bearing_y is distance from baseline to top of glyph (positive = above baseline)
+                    // glyph top relative to baseline = glyph.y + bearing_y
+                    // glyph bottom relative to baseline = glyph.y + bearing_y - height
+                    // Transformed runs measure from the pen they are composited at
+                    let lift = if transform.is_some() {
+                        -glyph_y
+                    } else {
+                        glyph.y + shift
+                    };
+                    let glyph_top = lift + bitmap.bearing_y as f32;
+                    let glyph_bottom = lift + bitmap.bearing_y as f32 - bitmap.height as f32;
+
+                    max_y = max_y.max(glyph_top);
+                    min_y = min_y.min(glyph_bottom);
+                    let glyph_left = glyph_x + bitmap.bearing_x as f32;
+                    min_x = min_x.min(glyph_left);
+                    max_x = max_x.max(glyph_left + bitmap.width as f32);
+
+                    memory.grow(bitmap.data.byte_len())?;
+                    rendered_glyphs.push(RenderedGlyph {
+                        bitmap,
+                        glyph_x,
+                        glyph_y,
+                        cluster: glyph.cluster,
+                    });
+                },
+                // Running out of budget ends the run; other failures skip a glyph
+                Err(e @ TypfError::RenderingFailed(RenderError::BudgetExceeded { .. })) => {
+                    return Err(e)
+                },
+                Err(e) if params.strict => return Err(e),
+                Err(e) => {
+                    diagnostics::warn(
+                        params.diagnostics.as_ref(),
+                        DiagnosticKind::GlyphSkipped,
+                        Some(glyph.id),
+                        format!("Skia: failed to render: {e}"),
+                    );
+                    last_error = Some(e.to_string());
+                },
+            }
+        }
+
+        // Glyphs skipped by `cull` still count as content, so a clipped
+        // redraw keeps the same canvas height as a full render.
+        let empty = rendered_glyphs.is_empty() && culled == 0;
+
+        if empty && !shaped.glyphs.is_empty() {
+            if let Some(err) = last_error {
+                return Err(RenderError::BackendError(err).into());
+            }
+            return Err(RenderError::BackendError("no glyphs rendered".into()).into());
+        }
+
+        // Phase 2: Calculate canvas dimensions.
+        //
+        // Baseline standardization (metrics-first):
+        // - Prefer font ascent/descent (stable across strings)
+        // - Expand to include any glyph bounds that exceed the metrics (effects, extreme accents)
+        let (metrics_ascent, metrics_descent) = font
+            .metrics_at(&params.variations)
+            .filter(|m| m.units_per_em > 0 && (m.ascent != 0 || m.descent != 0))
+            .map(|m| {
+                let scale = glyph_size / (m.units_per_em as f32);
+                let ascent = (m.ascent as f32).max(0.0) * scale;
+                let descent = (m.descent as f32).abs() * scale;
+                (ascent, descent)
+            })
+            .unwrap_or((0.0, 0.0));
+
+        let glyph_top = max_y.max(0.0);
+        let glyph_bottom = (-min_y).max(0.0);
+        let mut top = glyph_top.max(metrics_ascent);
+        let mut bottom = glyph_bottom.max(metrics_descent);
+        let mut pad_left = insets.left as f32;
+        // A clipped or ellipsized line ends at the layout width
+        let mut text_width = params.layout.visible_width(shaped.advance_width);
+
+        // Transformed runs: fit the transformed line box and the ink
+        if let Some(t) = transform {
+            let (x0, y0, x1, y1) =
+                t.bounds(0.0, -metrics_descent, shaped.advance_width, metrics_ascent);
+            let (x0, x1) = (x0.min(min_x), x1.max(max_x));
+            top = glyph_top.max(y1);
+            bottom = glyph_bottom.max(-y0);
+            pad_left -= x0;
+            text_width = x1 - x0;
+        }
+        let width = (text_width + insets.horizontal() as f32).ceil() as u32;
+
+        let content_height = if empty {
+            16.0 // Default minimum for empty text
+        } else {
+            top + bottom
+        };
+        let height = (content_height + insets.vertical() as f32).ceil() as u32;
+
+        // Validate dimensions
+        if width == 0 || height == 0 {
+            return Err(RenderError::ZeroDimensions { width, height }.into());
+        }
+
+        if width > self.max_width || height > self.max_height {
+            return Err(RenderError::DimensionsTooLarge {
+                width,
+                height,
+                max_width: self.max_width,
+                max_height: self.max_height,
+            }
+            .into());
+        }
+
+        // Baseline position: top inset + distance from top to baseline
+        let baseline_y = if empty { pad_top } else { pad_top + top };
+
+        let text_box = (transform.is_none() && !empty).then_some([
+            pad_left,
+            baseline_y - top,
+            pad_left + text_width,
+            baseline_y + bottom,
+        ]);
+
+        Ok(Layout {
+            width,
+            height,
+            pad_left,
+            baseline_y,
+            text_box,
+            glyphs: rendered_glyphs,
+            memory,
+        })
+    }
+
+    /// Composite a run into `target`, skipping glyphs `cull` rules out.
+    fn draw_into(
+        &self,
+        shaped: &ShapingResult,
+        font: &Arc<dyn FontRef>,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+        cull: Option<&GlyphCull>,
+    ) -> Result<Option<DirtyRect>> {
+        let layout = self.layout(shaped, font, params, cull)?;
+        self.draw_layout(layout, shaped, params, target, origin)
+    }
+
+    /// Render into a fresh canvas of `params.format` with aligned rows.
+    ///
+    /// Coverage formats and padded rows go through the render target path;
+    /// only packed `Rgba8` canvases take the renderer's own compositor.
+    fn render_to_buffer(
+        &self,
+        mut layout: Layout,
+        shaped: &ShapingResult,
+        params: &RenderParams,
+    ) -> Result<RenderOutput> {
+        let (width, height, format) = (layout.width, layout.height, params.format);
+        let stride = format.stride(width, params.row_alignment);
+        layout.memory.grow(stride * height as usize)?;
+        let mut data = vec![0u8; stride * height as usize];
+        let mut target = BufferTarget::new(&mut data, width, height, stride, format)?;
+        self.draw_layout(layout, shaped, params, &mut target, (0, 0))?;
+        Ok(RenderOutput::Bitmap(
+            BitmapData {
+                width,
+                height,
+                format,
+                stride,
+                layout: PixelLayout::RGBA,
+                data,
+            }
+            .into_layout(params.pixel_layout),
+        ))
+    }
+
+    /// Composite a laid-out run into `target`.
+    fn draw_layout(
+        &self,
+        mut layout: Layout,
+        shaped: &ShapingResult,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+    ) -> Result<Option<DirtyRect>> {
+        let effects = layout.effect_mask(params)?;
+        let (ox, oy) = origin;
+
+        let mut dirty = None;
+        if let Some(bg) = params.background {
+            dirty = render_target::fill_rect(target, ox, oy, layout.width, layout.height, bg)?;
+        }
+        if let Some((plate, [left, top, right, bottom])) =
+            params.effects.background_box.zip(layout.text_box)
+        {
+            let (x, y) = (ox as f32, oy as f32);
+            let filled = plate.fill(target, x + left, y + top, x + right, y + bottom)?;
+            dirty = DirtyRect::merge(dirty, filled);
+        }
+        let spans = color_span::fill_span_backgrounds(
+            target,
+            shaped,
+            params,
+            ox as f32 + layout.pad_left,
+            oy,
+            layout.height,
+        )?;
+        dirty = DirtyRect::merge(dirty, spans);
+        if let Some(mask) = effects {
+            let drawn = mask.draw_effects(&params.effects, target, origin)?;
+            dirty = DirtyRect::merge(dirty, drawn);
+        }
+
+        for rg in &layout.glyphs {
+            let bitmap = &rg.bitmap;
+            let x = ox + (rg.glyph_x + layout.pad_left) as i32 + bitmap.bearing_x;
+            let y = oy + (layout.baseline_y + rg.glyph_y) as i32 - bitmap.bearing_y;
+            let touched = match &bitmap.data {
+                GlyphBitmapData::Mask(mask) => render_target::blend_mask(
+                    target,
+                    x,
+                    y,
+                    bitmap.width,
+                    bitmap.height,
+                    mask,
+                    params.foreground_at(rg.cluster),
+                )?,
+                GlyphBitmapData::RgbaPremul(rgba) => render_target::blend_premul_rgba(
+                    target,
+                    x,
+                    y,
+                    bitmap.width,
+                    bitmap.height,
+                    rgba,
+                )?,
+            };
+            dirty = DirtyRect::merge(dirty, touched);
+        }
+
+        Ok(dirty)
+    }
 }
 
 impl Default for SkiaRenderer {
@@ -464,6 +873,29 @@ fn flip_vertical_rgba(data: &mut [u8], width: u32, height: u32) {
 }
 
 /// Check if a premultiplied RGBA buffer is fully transparent
+/// `bbox` checked by [`sanitize::clamp_glyph_bounds`]
+fn sanitize_bbox(
+    glyph_id: u32,
+    bbox: kurbo::Rect,
+    font_size: f32,
+    strict: bool,
+    diagnostics: Option<&Diagnostics>,
+) -> Result<kurbo::Rect> {
+    let [x0, y0, x1, y1] = sanitize::clamp_glyph_bounds(
+        glyph_id,
+        [
+            bbox.x0 as f32,
+            bbox.y0 as f32,
+            bbox.x1 as f32,
+            bbox.y1 as f32,
+        ],
+        font_size,
+        strict,
+        diagnostics,
+    )?;
+    Ok(kurbo::Rect::new(x0 as f64, y0 as f64, x1 as f64, y1 as f64))
+}
+
 fn is_fully_transparent(data: &[u8]) -> bool {
     data.chunks_exact(4).all(|px| px[3] == 0)
 }
@@ -484,6 +916,22 @@ fn allows_color_sources(pref: &GlyphSourcePreference) -> bool {
 }
 
 /// Build variation location from params
+/// Errors unless at least one outline or color glyph source is allowed
+fn check_glyph_sources(params: &RenderParams) -> Result<()> {
+    let allows_outline = params
+        .glyph_sources
+        .effective_order()
+        .iter()
+        .any(|s| matches!(s, GlyphSource::Glyf | GlyphSource::Cff | GlyphSource::Cff2));
+    if !allows_outline && !allows_color_sources(&params.glyph_sources) {
+        return Err(RenderError::BackendError(
+            "skia renderer requires outline or color glyph sources".to_string(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
 fn build_location(
     font: &Arc<dyn FontRef>,
     variations: &[(String, f32)],
@@ -493,7 +941,7 @@ fn build_location(
     }
 
     let font_data = font.data();
-    let font_ref = match skrifa::FontRef::new(font_data) {
+    let font_ref = match skrifa::FontRef::from_index(font_data, font.face_index()) {
         Ok(f) => f,
         Err(_) => return skrifa::instance::Location::default(),
     };
@@ -518,18 +966,7 @@ impl Renderer for SkiaRenderer {
         font: Arc<dyn FontRef>,
         params: &RenderParams,
     ) -> Result<RenderOutput> {
-        let allows_outline = params
-            .glyph_sources
-            .effective_order()
-            .iter()
-            .any(|s| matches!(s, GlyphSource::Glyf | GlyphSource::Cff | GlyphSource::Cff2));
-        let allows_color = allows_color_sources(&params.glyph_sources);
-        if !allows_outline && !allows_color {
-            return Err(RenderError::BackendError(
-                "skia renderer requires outline or color glyph sources".to_string(),
-            )
-            .into());
-        }
+        check_glyph_sources(params)?;
 
         // Vector mode: delegate to the SVG renderer for path extraction
         if let RenderMode::Vector(vector_format) = params.output {
@@ -545,101 +982,34 @@ impl Renderer for SkiaRenderer {
             }
         }
 
-        let padding = params.padding as f32;
-        let glyph_size = shaped.advance_height;
-
-        // Build variable font location from params.variations
-        let location = build_location(&font, &params.variations);
-
-        // Phase 1: Render all glyphs first to get accurate bounds
-        // This ensures we don't clip tall glyphs (emoji, Thai marks, Arabic diacritics)
-        let mut rendered_glyphs: Vec<(RenderedGlyph, f32, f32)> = Vec::new();
-        let mut min_y: f32 = 0.0; // Relative to baseline
-        let mut max_y: f32 = 0.0; // Relative to baseline
-        let mut last_error: Option<String> = None;
-
-        for glyph in shaped.glyphs.iter() {
-            match self.render_glyph(&font, glyph.id, glyph_size, &location, params) {
-                Ok(bitmap) => {
-                    // bearing_y is distance from baseline to top of glyph (positive = above baseline)
-                    // glyph top relative to baseline = glyph.y + bearing_y
-                    // glyph bottom relative to baseline = glyph.y + bearing_y - height
-                    let glyph_top = glyph.y + bitmap.bearing_y as f32;
-                    let glyph_bottom = glyph.y + bitmap.bearing_y as f32 - bitmap.height as f32;
-
-                    max_y = max_y.max(glyph_top);
-                    min_y = min_y.min(glyph_bottom);
-
-                    rendered_glyphs.push((
-                        RenderedGlyph {
-                            bitmap,
-                            glyph_x: glyph.x,
-                            glyph_y: glyph.y,
-                        },
-                        glyph_top,
-                        glyph_bottom,
-                    ));
-                },
-                Err(e) => {
-                    log::warn!("Skia: Failed to render glyph {}: {:?}", glyph.id, e);
-                    last_error = Some(e.to_string());
-                },
-            }
-        }
-
-        if rendered_glyphs.is_empty() && !shaped.glyphs.is_empty() {
-            if let Some(err) = last_error {
-                return Err(RenderError::BackendError(err).into());
-            }
-            return Err(RenderError::BackendError("no glyphs rendered".into()).into());
-        }
-
-        // Phase 2: Calculate canvas dimensions.
-        //
-        // Baseline standardization (metrics-first):
-        // - Prefer font ascent/descent (stable across strings)
-        // - Expand to include any glyph bounds that exceed the metrics (effects, extreme accents)
-        let width = (shaped.advance_width + padding * 2.0).ceil() as u32;
-
-        let (metrics_ascent, metrics_descent) = font
-            .metrics()
-            .filter(|m| m.units_per_em > 0 && (m.ascent != 0 || m.descent != 0))
-            .map(|m| {
-                let scale = glyph_size / (m.units_per_em as f32);
-                let ascent = (m.ascent as f32).max(0.0) * scale;
-                let descent = (m.descent as f32).abs() * scale;
-                (ascent, descent)
-            })
-            .unwrap_or((0.0, 0.0));
-
-        let glyph_top = max_y.max(0.0);
-        let glyph_bottom = (-min_y).max(0.0);
-        let top = glyph_top.max(metrics_ascent);
-        let bottom = glyph_bottom.max(metrics_descent);
-
-        let content_height = if rendered_glyphs.is_empty() {
-            16.0 // Default minimum for empty text
-        } else {
-            top + bottom
-        };
-        let height = (content_height + padding * 2.0).ceil() as u32;
-
-        // Validate dimensions
-        if width == 0 || height == 0 {
-            return Err(RenderError::ZeroDimensions { width, height }.into());
+        if let RenderMode::DistanceField { .. } = params.output {
+            return Err(RenderError::FormatNotSupported(
+                "Skia renderer does not support distance-field output; use opixa or zeno".into(),
+            )
+            .into());
         }
 
-        if width > self.max_width || height > self.max_height {
-            return Err(RenderError::DimensionsTooLarge {
-                width,
-                height,
-                max_width: self.max_width,
-                max_height: self.max_height,
-            }
-            .into());
+        let mut layout = self.layout(shaped, &font, params, None)?;
+        // The compositor below writes packed Rgba8 rows only
+        let stride = params.format.stride(layout.width, params.row_alignment);
+        let packed_rgba =
+            params.format == BitmapFormat::Rgba8 && stride == layout.width as usize * 4;
+        if !packed_rgba {
+            return self.render_to_buffer(layout, shaped, params);
         }
+        let effects = layout.effect_mask(params)?;
+        let Layout {
+            width,
+            height,
+            pad_left,
+            baseline_y,
+            text_box,
+            glyphs: rendered_glyphs,
+            mut memory,
+        } = layout;
 
         // Create premultiplied RGBA canvas
+        memory.grow((width * height * 4) as usize)?;
         let mut canvas = vec![0u8; (width * height * 4) as usize];
 
         // Fill background if specified (premultiplied)
@@ -655,24 +1025,32 @@ impl Renderer for SkiaRenderer {
                 pixel[3] = a as u8;
             }
         }
-
-        // Baseline position: padding + distance from top to baseline
-        let baseline_y = if rendered_glyphs.is_empty() {
-            padding
-        } else {
-            padding + top
-        };
+        let plate = params.effects.background_box.zip(text_box);
+        if plate.is_some() || !params.color_spans.is_empty() || effects.is_some() {
+            let stride = width as usize * 4;
+            let mut target =
+                BufferTarget::new(&mut canvas, width, height, stride, BitmapFormat::Rgba8)?
+                    .with_premultiplied(true);
+            if let Some((plate, [left, top, right, bottom])) = plate {
+                plate.fill(&mut target, left, top, right, bottom)?;
+            }
+            color_span::fill_span_backgrounds(&mut target, shaped, params, pad_left, 0, height)?;
+            if let Some(mask) = effects {
+                mask.draw_effects(&params.effects, &mut target, (0, 0))?;
+            }
+        }
 
         // Phase 3: Composite pre-rendered glyphs onto canvas
-        for (rg, _top, _bottom) in rendered_glyphs {
+        for rg in rendered_glyphs {
             let bitmap = &rg.bitmap;
 
             // Position glyph on canvas
-            // X: glyph.x + padding + bearing_x
+            // X: glyph.x + left inset + bearing_x
             // Y: baseline_y + glyph.y - bearing_y (convert from baseline-relative to top-origin)
-            let x = (rg.glyph_x + padding) as i32 + bitmap.bearing_x;
+            let x = (rg.glyph_x + pad_left) as i32 + bitmap.bearing_x;
             let y = (baseline_y + rg.glyph_y) as i32 - bitmap.bearing_y;
 
+            let fg = params.foreground_at(rg.cluster);
             match &bitmap.data {
                 GlyphBitmapData::Mask(mask) => {
                     for gy in 0..bitmap.height {
@@ -697,7 +1075,6 @@ impl Renderer for SkiaRenderer {
                             }
 
                             // Apply coverage to foreground color, creating premultiplied values
-                            let fg = &params.foreground;
                             let src_a = coverage * fg.a as u32 / 255;
                             let src_r = fg.r as u32 * src_a / 255;
                             let src_g = fg.g as u32 * src_a / 255;
@@ -765,28 +1142,54 @@ impl Renderer for SkiaRenderer {
             }
         }
 
-        // Convert premultiplied canvas back to straight RGBA for output
-        let mut output = canvas;
-        for px in output.chunks_exact_mut(4) {
-            let a = px[3];
-            if a == 0 {
-                px[0] = 0;
-                px[1] = 0;
-                px[2] = 0;
-                continue;
+        // The canvas is premultiplied; hand it over in the requested layout
+        Ok(RenderOutput::Bitmap(
+            BitmapData {
+                width,
+                height,
+                format: BitmapFormat::Rgba8,
+                stride: BitmapFormat::Rgba8.row_bytes(width),
+                layout: PixelLayout::PREMULTIPLIED_RGBA,
+                data: canvas,
             }
-            let a_u = a as u32;
-            px[0] = ((px[0] as u32 * 255 + a_u / 2) / a_u).min(255) as u8;
-            px[1] = ((px[1] as u32 * 255 + a_u / 2) / a_u).min(255) as u8;
-            px[2] = ((px[2] as u32 * 255 + a_u / 2) / a_u).min(255) as u8;
+            .into_layout(params.pixel_layout),
+        ))
+    }
+
+    fn render_into(
+        &self,
+        shaped: &ShapingResult,
+        font: Arc<dyn FontRef>,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+    ) -> Result<Option<DirtyRect>> {
+        check_glyph_sources(params)?;
+        if !matches!(params.output, RenderMode::Bitmap) {
+            return render_target::render_via_bitmap(self, shaped, font, params, target, origin);
         }
 
-        Ok(RenderOutput::Bitmap(BitmapData {
-            width,
-            height,
-            format: BitmapFormat::Rgba8,
-            data: output,
-        }))
+        self.draw_into(shaped, &font, params, target, origin, None)
+    }
+
+    fn render_region(
+        &self,
+        shaped: &ShapingResult,
+        font: Arc<dyn FontRef>,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+        clip: DirtyRect,
+    ) -> Result<Option<DirtyRect>> {
+        check_glyph_sources(params)?;
+        if !matches!(params.output, RenderMode::Bitmap) {
+            return render_target::render_clipped(self, shaped, font, params, target, origin, clip);
+        }
+
+        // The font size bounds how far ink strays from the pen span.
+        let cull = GlyphCull::new(clip, origin, shaped.advance_height);
+        let mut clipped = ClipTarget::new(target, clip);
+        self.draw_into(shaped, &font, params, &mut clipped, origin, Some(&cull))
     }
 
     fn supports_format(&self, format: &str) -> bool {
@@ -795,11 +1198,53 @@ impl Renderer for SkiaRenderer {
     }
 }
 
+/// Rasterized glyphs and canvas geometry for one run
+struct Layout {
+    width: u32,
+    height: u32,
+    pad_left: f32,
+    baseline_y: f32,
+    /// Untransformed text box as left, top, right, bottom; none when empty
+    text_box: Option<[f32; 4]>,
+    glyphs: Vec<RenderedGlyph>,
+    /// Budget held for the glyph bitmaps, and for the canvas once allocated
+    memory: Reservation,
+}
+
+impl Layout {
+    /// The run's coverage, when the params' effects need it.
+    fn effect_mask(&mut self, params: &RenderParams) -> Result<Option<CoverageMask>> {
+        let effects = &params.effects;
+        if effects.stroke.is_none() && effects.shadow.is_none() {
+            return Ok(None);
+        }
+        let mut mask = CoverageMask::new(self.width, self.height);
+        // The mask and one working copy for the stroke or the blur
+        self.memory.grow(2 * mask.byte_len())?;
+        for rg in &self.glyphs {
+            let bitmap = &rg.bitmap;
+            let x = (self.pad_left + rg.glyph_x) as i32 + bitmap.bearing_x;
+            let y = (self.baseline_y + rg.glyph_y) as i32 - bitmap.bearing_y;
+            match &bitmap.data {
+                GlyphBitmapData::Mask(coverage) => {
+                    mask.add(x, y, bitmap.width, bitmap.height, coverage)
+                },
+                GlyphBitmapData::RgbaPremul(rgba) => {
+                    mask.add_rgba(x, y, bitmap.width, bitmap.height, rgba)
+                },
+            }
+        }
+        Ok(Some(mask))
+    }
+}
+
 /// A rendered glyph ready for compositing
 struct RenderedGlyph {
-    bitmap: GlyphBitmap,
+    bitmap: Rc<GlyphBitmap>,
     glyph_x: f32,
     glyph_y: f32,
+    /// Source byte the glyph came from, for [`RenderParams::foreground_at`]
+    cluster: u32,
 }
 
 /// Stored glyph data for compositing
@@ -810,6 +1255,15 @@ enum GlyphBitmapData {
     RgbaPremul(Vec<u8>),
 }
 
+impl GlyphBitmapData {
+    /// Bytes of pixel data held
+    fn byte_len(&self) -> usize {
+        match self {
+            Self::Mask(data) | Self::RgbaPremul(data) => data.len(),
+        }
+    }
+}
+
 /// A rendered glyph with everything needed for proper positioning
 struct GlyphBitmap {
     width: u32,            // Pixel width of the glyph bitmap
@@ -817,60 +1271,7 @@ struct GlyphBitmap {
     data: GlyphBitmapData, // Coverage or color data
     bearing_x: i32,        // Horizontal offset from origin to left edge
     bearing_y: i32,        // Vertical offset from baseline to top edge
-}
-
-/// Bridge between skrifa's outline commands and kurbo's path format
-///
-/// This pen receives drawing commands from skrifa and translates them
-/// into kurbo's path representation, handling scaling along the way.
-struct PathPen<'a> {
-    path: &'a mut kurbo::BezPath,
-    scale: f32,
-}
-
-impl skrifa::outline::OutlinePen for PathPen<'_> {
-    fn move_to(&mut self, x: f32, y: f32) {
-        // Start a new subpath at this position
-        self.path
-            .move_to((x as f64 * self.scale as f64, y as f64 * self.scale as f64));
-    }
-
-    fn line_to(&mut self, x: f32, y: f32) {
-        // Draw a straight line to this point
-        self.path
-            .line_to((x as f64 * self.scale as f64, y as f64 * self.scale as f64));
-    }
-
-    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
-        // Draw a quadratic Bézier curve with one control point
-        self.path.quad_to(
-            (
-                cx0 as f64 * self.scale as f64,
-                cy0 as f64 * self.scale as f64,
-            ),
-            (x as f64 * self.scale as f64, y as f64 * self.scale as f64),
-        );
-    }
-
-    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
-        // Draw a cubic Bézier curve with two control points
-        self.path.curve_to(
-            (
-                cx0 as f64 * self.scale as f64,
-                cy0 as f64 * self.scale as f64,
-            ),
-            (
-                cx1 as f64 * self.scale as f64,
-                cy1 as f64 * self.scale as f64,
-            ),
-            (x as f64 * self.scale as f64, y as f64 * self.scale as f64),
-        );
-    }
-
-    fn close(&mut self) {
-        // Close the current subpath, connecting back to the start
-        self.path.close_path();
-    }
+    source: GlyphSource,   // Table the bitmap was drawn from
 }
 
 #[cfg(test)]
@@ -883,6 +1284,7 @@ mod tests {
         types::{BitmapFormat, Direction},
         Color, GlyphSource, GlyphSourcePreference,
     };
+    use typf_render_color::render_glyph_with_preference;
 
     #[test]
     fn test_renderer_creation() {
@@ -950,6 +1352,53 @@ mod tests {
         assert!(result.is_err(), "denying all sources should error");
     }
 
+    #[test]
+    fn subpixel_positioning_moves_ink_by_pen_fraction() {
+        let renderer = SkiaRenderer::new();
+        let font = load_test_font();
+        let glyph_id = font.glyph_id('l').unwrap_or(0);
+
+        // Horizontal center of the ink for a glyph whose pen sits at `x`
+        let ink_center = |x: f32, subpixel: SubpixelPositioning| {
+            let shaped = ShapingResult {
+                glyphs: vec![typf_core::types::PositionedGlyph {
+                    id: glyph_id,
+                    x,
+                    y: 0.0,
+                    advance: 10.0,
+                    cluster: 0,
+                }],
+                advance_width: 12.0,
+                advance_height: 32.0,
+                direction: Direction::LeftToRight,
+            };
+            let params = RenderParams {
+                subpixel,
+                ..RenderParams::default()
+            };
+            let Ok(RenderOutput::Bitmap(bitmap)) = renderer.render(&shaped, font.clone(), &params)
+            else {
+                panic!("expected bitmap output");
+            };
+            let (mut sum, mut total) = (0.0, 0.0);
+            for (i, pixel) in bitmap.data.chunks_exact(4).enumerate() {
+                let column = (i % bitmap.width as usize) as f32;
+                sum += column * pixel[3] as f32;
+                total += pixel[3] as f32;
+            }
+            sum / total
+        };
+
+        let off = SubpixelPositioning::Off;
+        assert_eq!(ink_center(0.0, off), ink_center(0.5, off));
+
+        let quarter = SubpixelPositioning::Quarter;
+        let moved = ink_center(0.5, quarter) - ink_center(0.0, quarter);
+        assert!((moved - 0.5).abs() < 0.1, "moved {moved}");
+        let moved = ink_center(1.25, quarter) - ink_center(0.0, quarter);
+        assert!((moved - 1.25).abs() < 0.1, "moved {moved}");
+    }
+
     fn load_test_font() -> Arc<dyn FontRef> {
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         path.pop(); // typf-render-skia
@@ -1117,6 +1566,66 @@ mod tests {
         }
     }
 
+    #[test]
+    fn glyph_source_log_reports_color_then_outline() {
+        let renderer = SkiaRenderer::new();
+        let sources_used = |font: Arc<dyn FontRef>, glyph_id: u32, prefer: Vec<GlyphSource>| {
+            let shaped = ShapingResult {
+                glyphs: vec![typf_core::types::PositionedGlyph {
+                    id: glyph_id,
+                    x: 0.0,
+                    y: 0.0,
+                    advance: 32.0,
+                    cluster: 0,
+                }],
+                advance_width: 32.0,
+                advance_height: 32.0,
+                direction: Direction::LeftToRight,
+            };
+            let log = source_log::GlyphSourceLog::new();
+            let params = RenderParams {
+                glyph_sources: GlyphSourcePreference::from_parts(prefer, []),
+                glyph_source_log: Some(log.clone()),
+                ..RenderParams::default()
+            };
+            renderer
+                .render(&shaped, font, &params)
+                .expect("render should succeed");
+            log.take()
+        };
+
+        let (color_font, bytes) = load_color_font("Nabla-Regular-COLR.ttf");
+        let colr_id = first_colr_glyph(&bytes).expect("color glyph should exist");
+        let used = sources_used(
+            color_font,
+            colr_id,
+            vec![GlyphSource::Colr1, GlyphSource::Colr0, GlyphSource::Glyf],
+        );
+        assert_eq!(used.len(), 1);
+        assert_eq!(used[0].glyph_id, colr_id);
+        assert!(matches!(
+            used[0].source,
+            GlyphSource::Colr1 | GlyphSource::Colr0
+        ));
+
+        // A plain font falls through the color sources to its outline
+        let font = load_test_font();
+        let glyph_id = font.glyph_id('l').unwrap_or(0);
+        let used = sources_used(
+            font,
+            glyph_id,
+            vec![GlyphSource::Colr1, GlyphSource::Svg, GlyphSource::Glyf],
+        );
+        assert_eq!(
+            used,
+            vec![source_log::GlyphSourceRecord {
+                index: 0,
+                glyph_id,
+                source: GlyphSource::Glyf,
+            }]
+        );
+    }
+
     #[test]
     fn renders_svg_glyph_when_outlines_denied() {
         let renderer = SkiaRenderer::new();
@@ -0,0 +1,68 @@
+//! tiny-skia pixmaps as render targets
+//!
+//! tiny-skia stores premultiplied RGBA with no row padding, so a pixmap can
+//! take text straight from `render_into` and keep drawing on top of it.
+//
+// this_file: backends/typf-render-skia/src/pixmap_target.rs
+
+use typf_core::{
+    render_target::{DirtyRect, RenderTarget},
+    types::BitmapFormat,
+};
+
+/// Borrows a tiny-skia pixmap so any renderer can draw text into it
+///
+/// ```ignore
+/// let mut pixmap = tiny_skia::Pixmap::new(800, 600).unwrap();
+/// let mut target = PixmapTarget::new(pixmap.as_mut());
+/// renderer.render_into(&shaped, font, &params, &mut target, (20, 40))?;
+/// ```
+pub struct PixmapTarget<'a> {
+    pixmap: tiny_skia::PixmapMut<'a>,
+    dirty: Option<DirtyRect>,
+}
+
+impl<'a> PixmapTarget<'a> {
+    /// Wraps a mutable pixmap view
+    pub fn new(pixmap: tiny_skia::PixmapMut<'a>) -> Self {
+        Self {
+            pixmap,
+            dirty: None,
+        }
+    }
+
+    /// Union of every region drawn so far
+    pub fn dirty(&self) -> Option<DirtyRect> {
+        self.dirty
+    }
+}
+
+impl RenderTarget for PixmapTarget<'_> {
+    fn width(&self) -> u32 {
+        self.pixmap.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.pixmap.height()
+    }
+
+    fn stride(&self) -> usize {
+        self.pixmap.width() as usize * 4
+    }
+
+    fn format(&self) -> BitmapFormat {
+        BitmapFormat::Rgba8
+    }
+
+    fn premultiplied(&self) -> bool {
+        true
+    }
+
+    fn pixels_mut(&mut self) -> &mut [u8] {
+        self.pixmap.data_mut()
+    }
+
+    fn mark_dirty(&mut self, rect: DirtyRect) {
+        self.dirty = DirtyRect::merge(self.dirty, Some(rect));
+    }
+}
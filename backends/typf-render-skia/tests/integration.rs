@@ -2,13 +2,15 @@
 //!
 //! Community project by FontLab - https://www.fontlab.org/
 
+use std::path::PathBuf;
 use std::sync::Arc;
 use typf_core::{
+    render_target::{BufferTarget, DirtyRect},
     traits::{FontRef, Renderer},
-    types::{BitmapFormat, Direction, GlyphId, RenderOutput, ShapingResult},
-    RenderParams,
+    types::{BitmapFormat, Direction, GlyphId, PositionedGlyph, RenderOutput, ShapingResult},
+    Color, RenderParams, TextTransform,
 };
-use typf_render_skia::SkiaRenderer;
+use typf_render_skia::{PixmapTarget, SkiaRenderer};
 
 /// Stub font for testing
 struct StubFont {
@@ -72,3 +74,206 @@ fn test_empty_rendering() {
         assert!(bitmap.height > 0);
     }
 }
+
+fn empty_run() -> ShapingResult {
+    ShapingResult {
+        glyphs: vec![],
+        advance_width: 10.0,
+        advance_height: 20.0,
+        direction: Direction::LeftToRight,
+    }
+}
+
+/// Load a font from the repo's test-fonts directory, if it is there
+fn load_font(name: &str) -> Option<Arc<dyn FontRef>> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../test-fonts")
+        .join(name);
+    let font = typf_fontdb::TypfFontFace::from_file(path).ok()?;
+    Some(Arc::new(font))
+}
+
+/// "He" in `font` at 32px, set with the font's advances
+fn he_run(font: &Arc<dyn FontRef>) -> ShapingResult {
+    let size = 32.0;
+    let scale = size / font.units_per_em() as f32;
+    let mut x = 0.0;
+    let glyphs = "He"
+        .chars()
+        .enumerate()
+        .map(|(cluster, ch)| {
+            let id = font.glyph_id(ch).unwrap_or(0);
+            let advance = font.advance_width(id) * scale;
+            let glyph = PositionedGlyph {
+                id,
+                x,
+                y: 0.0,
+                advance,
+                cluster: cluster as u32,
+            };
+            x += advance;
+            glyph
+        })
+        .collect();
+    ShapingResult {
+        glyphs,
+        advance_width: x,
+        advance_height: size,
+        direction: Direction::LeftToRight,
+    }
+}
+
+#[test]
+fn test_render_into_when_buffer_then_matches_render() {
+    let Some(font) = load_font("NotoSans-Regular.ttf") else {
+        eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+        return;
+    };
+    let renderer = SkiaRenderer::new();
+    let shaped = he_run(&font);
+    let params = RenderParams {
+        background: Some(Color::rgba(10, 20, 30, 255)),
+        ..RenderParams::default()
+    };
+
+    let Ok(RenderOutput::Bitmap(expected)) = renderer.render(&shaped, font.clone(), &params) else {
+        panic!("expected bitmap output");
+    };
+    assert!(
+        expected
+            .data
+            .chunks_exact(4)
+            .any(|px| px[..3] != [10, 20, 30]),
+        "glyphs should leave ink on the background"
+    );
+
+    let (width, height) = (expected.width, expected.height);
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+    let mut target = BufferTarget::new(
+        &mut pixels,
+        width,
+        height,
+        width as usize * 4,
+        BitmapFormat::Rgba8,
+    )
+    .unwrap();
+    let dirty = renderer
+        .render_into(&shaped, font, &params, &mut target, (0, 0))
+        .unwrap();
+
+    assert_eq!(
+        dirty,
+        Some(DirtyRect {
+            x: 0,
+            y: 0,
+            width,
+            height
+        })
+    );
+    // Blending into the target rounds differently from the canvas path
+    for (got, want) in pixels.iter().zip(&expected.data) {
+        assert!(got.abs_diff(*want) <= 2, "{got} vs {want}");
+    }
+}
+
+#[test]
+fn test_render_into_when_pixmap_then_draws_at_origin() {
+    let Some(font) = load_font("NotoSans-Regular.ttf") else {
+        eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+        return;
+    };
+    let renderer = SkiaRenderer::new();
+    let shaped = he_run(&font);
+    let params = RenderParams {
+        background: Some(Color::rgba(255, 255, 255, 255)),
+        ..RenderParams::default()
+    };
+
+    let mut pixmap = tiny_skia::Pixmap::new(128, 64).unwrap();
+    let mut target = PixmapTarget::new(pixmap.as_mut());
+    let dirty = renderer
+        .render_into(&shaped, font, &params, &mut target, (5, 7))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!((dirty.x, dirty.y), (5, 7));
+    assert_eq!(target.dirty(), Some(dirty));
+    assert_eq!(pixmap.pixel(4, 7).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(5, 7).unwrap().alpha(), 255);
+    let inked = (dirty.y..dirty.y + dirty.height)
+        .flat_map(|y| (dirty.x..dirty.x + dirty.width).map(move |x| (x, y)))
+        .filter_map(|(x, y)| pixmap.pixel(x, y))
+        .any(|px| px.red() < 128);
+    assert!(inked, "glyphs should be drawn inside the dirty rect");
+}
+
+#[test]
+fn test_render_region_when_clip_then_leaves_outside_untouched() {
+    let renderer = SkiaRenderer::new();
+    let font = Arc::new(StubFont { data: vec![] }) as Arc<dyn FontRef>;
+    let params = RenderParams {
+        background: Some(Color::rgba(255, 255, 255, 255)),
+        ..RenderParams::default()
+    };
+    let clip = DirtyRect {
+        x: 2,
+        y: 0,
+        width: 3,
+        height: 2,
+    };
+
+    let mut pixels = vec![0u8; 16 * 16 * 4];
+    let mut target = BufferTarget::new(&mut pixels, 16, 16, 16 * 4, BitmapFormat::Rgba8).unwrap();
+    let dirty = renderer
+        .render_region(&empty_run(), font, &params, &mut target, (0, 0), clip)
+        .unwrap();
+
+    assert_eq!(dirty, Some(clip));
+    assert_eq!(target.dirty(), Some(clip));
+    let painted = pixels.chunks_exact(4).filter(|px| px[3] == 255).count();
+    assert_eq!(painted, 6);
+}
+
+#[test]
+fn test_render_when_rotated_quarter_turn_then_canvas_stands_up() {
+    let renderer = SkiaRenderer::new();
+    let shaped = ShapingResult {
+        glyphs: vec![],
+        advance_width: 100.0,
+        advance_height: 20.0,
+        direction: Direction::LeftToRight,
+    };
+    let font = Arc::new(StubFont { data: vec![] }) as Arc<dyn FontRef>;
+    let size = |transform| {
+        let params = RenderParams {
+            transform,
+            ..RenderParams::default()
+        };
+        match renderer.render(&shaped, font.clone(), &params) {
+            Ok(RenderOutput::Bitmap(bitmap)) => (bitmap.width, bitmap.height),
+            other => panic!("expected bitmap, got {:?}", other.map(|_| ())),
+        }
+    };
+
+    let (width, height) = size(None);
+    let (rot_width, rot_height) = size(Some(TextTransform::rotate(90.0)));
+    assert!(width > height);
+    assert!(rot_height > rot_width, "{rot_width}x{rot_height}");
+}
+
+#[test]
+fn test_render_when_degenerate_transform_then_errors() {
+    let renderer = SkiaRenderer::new();
+    let font = Arc::new(StubFont { data: vec![] }) as Arc<dyn FontRef>;
+    let params = RenderParams {
+        transform: Some(TextTransform::scale(1.0, 0.0)),
+        ..RenderParams::default()
+    };
+    let shaped = ShapingResult {
+        glyphs: vec![],
+        advance_width: 100.0,
+        advance_height: 20.0,
+        direction: Direction::LeftToRight,
+    };
+    assert!(renderer.render(&shaped, font, &params).is_err());
+}
@@ -0,0 +1,161 @@
+//! Canonical form for deterministic SVG output
+//!
+//! The renderer writes coordinates at two decimals but COLR paints and
+//! transforms at full `f32` precision, where the last digits follow the
+//! platform's float math. With [`RenderParams::deterministic`] set, the
+//! finished document is rewritten so equal inputs give equal bytes:
+//!
+//! - attributes of every element are sorted by name;
+//! - every decimal number in an attribute value is rounded to three
+//!   places, trailing zeros dropped, and `-0` written as `0`.
+//!
+//! Text content, `<style>` rules, and integers (ids, colors, base64 data)
+//! pass through unchanged.
+//!
+//! [`RenderParams::deterministic`]: typf_core::RenderParams::deterministic
+
+/// Decimal places kept by [`canonicalize`]
+const PRECISION: usize = 3;
+
+/// `svg` with sorted attributes and fixed-precision numbers
+pub(crate) fn canonicalize(svg: &str) -> String {
+    let mut out = String::with_capacity(svg.len());
+    let mut rest = svg;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let is_element = rest[1..].starts_with(|c: char| c.is_ascii_alphabetic());
+        let Some(end) = tag_end(rest) else {
+            break;
+        };
+        if is_element {
+            canonical_tag(&rest[..=end], &mut out);
+        } else {
+            out.push_str(&rest[..=end]);
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Index of the `>` closing the tag at the start of `s`, skipping quoted values
+fn tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(i),
+            _ => {},
+        }
+    }
+    None
+}
+
+/// Write one start or empty-element tag with its attributes sorted
+fn canonical_tag(tag: &str, out: &mut String) {
+    let self_closing = tag.ends_with("/>");
+    let inner = &tag[1..tag.len() - if self_closing { 2 } else { 1 }];
+    let name_end = inner
+        .find(|c: char| c.is_ascii_whitespace())
+        .unwrap_or(inner.len());
+
+    let mut attributes = Vec::new();
+    let mut rest = inner[name_end..].trim_start();
+    while let Some(eq) = rest.find('=') {
+        let name = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let Some(quote) = value.chars().next().filter(|&c| c == '"' || c == '\'') else {
+            break;
+        };
+        let Some(close) = value[1..].find(quote) else {
+            break;
+        };
+        attributes.push((name, quote, &value[1..=close]));
+        rest = value[close + 2..].trim_start();
+    }
+    attributes.sort_by(|a, b| a.0.cmp(b.0));
+
+    out.push('<');
+    out.push_str(&inner[..name_end]);
+    for (name, quote, value) in attributes {
+        out.push(' ');
+        out.push_str(name);
+        out.push('=');
+        out.push(quote);
+        round_numbers(value, out);
+        out.push(quote);
+    }
+    out.push_str(if self_closing { "/>" } else { ">" });
+}
+
+/// Copy `value`, rewriting each decimal number at [`PRECISION`]
+fn round_numbers(value: &str, out: &mut String) {
+    let bytes = value.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let starts = bytes[i].is_ascii_digit()
+            || (bytes[i] == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit));
+        if starts {
+            let mut end = i + 1;
+            while end < bytes.len() && (bytes[end].is_ascii_digit() || bytes[end] == b'.') {
+                end += 1;
+            }
+            // Integers are ids, colors, and sizes; only decimals vary
+            let number = &value[i..end];
+            match number.parse::<f64>() {
+                Ok(n) if number.contains('.') => push_number(n, out),
+                _ => out.push_str(number),
+            }
+            i = end;
+        } else {
+            // Multi-byte characters are copied whole
+            let len = value[i..].chars().next().map_or(1, char::len_utf8);
+            out.push_str(&value[i..i + len]);
+            i += len;
+        }
+    }
+}
+
+fn push_number(n: f64, out: &mut String) {
+    let text = format!("{:.*}", PRECISION, n);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        out.push('0');
+    } else {
+        out.push_str(text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize_when_attributes_unsorted_then_sorted() {
+        let svg = r#"<path d="M0.10,-0.0004L2.125,3Z" fill="red" clip-path="url(#c1)"/>"#;
+        assert_eq!(
+            canonicalize(svg),
+            r#"<path clip-path="url(#c1)" d="M0.1,0L2.125,3Z" fill="red"/>"#
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_when_numbers_vary_then_fixed_precision() {
+        let svg = r##"<use href="#typf-c5-g2" transform="translate(-0.00,12.50) scale(0.0123456,-0.0123456)"/>"##;
+        assert_eq!(
+            canonicalize(svg),
+            r##"<use href="#typf-c5-g2" transform="translate(0,12.5) scale(0.012,-0.012)"/>"##
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_when_text_and_declarations_then_unchanged() {
+        let svg = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<text y=\"1.50\" x=\"0.25\">3.14159 &lt; 4</text>\n";
+        assert_eq!(
+            canonicalize(svg),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<text x=\"0.25\" y=\"1.5\">3.14159 &lt; 4</text>\n"
+        );
+    }
+}
@@ -0,0 +1,513 @@
+//! COLR glyphs as native SVG
+//!
+//! Instead of rasterizing color glyphs into embedded PNGs, [`SvgColorPainter`]
+//! replays the COLR paint graph as SVG: glyph clips become `<clipPath>`s,
+//! gradients become `<linearGradient>`/`<radialGradient>` definitions, and
+//! composite layers become groups with `mix-blend-mode`. Everything is
+//! written in font units with y up; the caller places the result with a
+//! `scale(s, -s)` transform.
+
+use std::fmt::Write as FmtWrite;
+
+use skrifa::color::{Brush, ColorGlyphFormat, ColorPainter, ColorStop, CompositeMode, Extend};
+use skrifa::instance::{Location, Size};
+use skrifa::outline::{DrawSettings, OutlinePen};
+use skrifa::raw::types::BoundingBox;
+use skrifa::{GlyphId, MetadataProvider};
+use typf_core::{Color, GlyphSource};
+use typf_render_color::{ColorRenderError, PaintGuard};
+
+/// Palette index COLR uses for "the text foreground color"
+const FOREGROUND_PALETTE_INDEX: u16 = 0xFFFF;
+
+/// Half-size of the rectangle painted by unbounded fills, in font units.
+/// Fills are always clipped, so it only has to cover any glyph.
+const FILL_EXTENT: f32 = 32768.0;
+
+/// One painted color glyph: shared definitions plus the drawing itself
+pub(crate) struct ColrSvg {
+    /// Gradients and clip paths, to be placed inside `<defs>`
+    pub defs: String,
+    /// Group content drawing the glyph in font units, y up
+    pub body: String,
+}
+
+/// Paint `glyph_id` from the COLR table as SVG, or `None` when the glyph has
+/// no COLR data for the requested `source` or painting fails.
+///
+/// `id_prefix` keeps element ids unique when several glyphs share a document.
+pub(crate) fn paint_colr_glyph(
+    font: &skrifa::FontRef,
+    glyph_id: u32,
+    source: GlyphSource,
+    location: &Location,
+    palette_index: u16,
+    foreground: Color,
+    id_prefix: &str,
+) -> Option<ColrSvg> {
+    let format = match source {
+        GlyphSource::Colr0 => ColorGlyphFormat::ColrV0,
+        GlyphSource::Colr1 => ColorGlyphFormat::ColrV1,
+        _ => return None,
+    };
+    let color_glyph = font
+        .color_glyphs()
+        .get_with_format(GlyphId::new(glyph_id), format)?;
+    let palettes = font.color_palettes();
+    let palette: Vec<skrifa::color::Color> = palettes
+        .get(palette_index)
+        .or_else(|| palettes.get(0))
+        .map(|p| p.colors().to_vec())
+        .unwrap_or_default();
+
+    let mut painter = SvgColorPainter {
+        font,
+        location,
+        palette: &palette,
+        foreground,
+        id_prefix,
+        next_id: 0,
+        defs: String::new(),
+        body: String::new(),
+        open_groups: Vec::new(),
+        guard: PaintGuard::new(),
+        error: None,
+    };
+    if let Err(e) = color_glyph.paint(location, &mut painter) {
+        log::debug!("SvgColorPainter: glyph {} failed: {:?}", glyph_id, e);
+        return None;
+    }
+    if let Some(e) = painter.error.take() {
+        log::warn!("SvgColorPainter: glyph {} stopped: {}", glyph_id, e);
+        return None;
+    }
+    Some(painter.finish())
+}
+
+/// `ColorPainter` that writes SVG markup instead of pixels
+pub(crate) struct SvgColorPainter<'a> {
+    font: &'a skrifa::FontRef<'a>,
+    location: &'a Location,
+    palette: &'a [skrifa::color::Color],
+    foreground: Color,
+    id_prefix: &'a str,
+    next_id: u32,
+    defs: String,
+    body: String,
+    /// Kind of every open `<g>`, so pops close only what they opened
+    open_groups: Vec<GroupKind>,
+    /// Bounds the markup a hostile paint graph can make us write
+    guard: PaintGuard,
+    /// First limit passed; once set, every later callback is ignored
+    error: Option<ColorRenderError>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GroupKind {
+    Transform,
+    Clip,
+    Layer,
+}
+
+impl SvgColorPainter<'_> {
+    fn finish(mut self) -> ColrSvg {
+        for _ in 0..self.open_groups.len() {
+            self.body.push_str("</g>");
+        }
+        ColrSvg {
+            defs: self.defs,
+            body: self.body,
+        }
+    }
+
+    fn new_id(&mut self, kind: &str) -> String {
+        self.next_id += 1;
+        format!("{}{}{}", self.id_prefix, kind, self.next_id)
+    }
+
+    /// Count one callback that would nest `depth` deep; false once painting has stopped
+    fn admit(&mut self, depth: usize, layer: bool) -> bool {
+        if self.error.is_some() {
+            return false;
+        }
+        if let Err(e) = self.guard.admit(depth, layer) {
+            self.error = Some(e);
+            return false;
+        }
+        true
+    }
+
+    fn open_group(&mut self, kind: GroupKind, attributes: &str) {
+        let _ = write!(self.body, "<g {}>", attributes);
+        self.open_groups.push(kind);
+    }
+
+    fn close_group(&mut self, kind: GroupKind) {
+        if self.error.is_none() && self.open_groups.last() == Some(&kind) {
+            self.open_groups.pop();
+            self.body.push_str("</g>");
+        }
+    }
+
+    /// Outline of `glyph_id` in font units, y up
+    fn glyph_path(&self, glyph_id: GlyphId) -> Option<String> {
+        let outline = self.font.outline_glyphs().get(glyph_id)?;
+        let mut pen = FontUnitsPen::default();
+        let settings = DrawSettings::unhinted(Size::unscaled(), self.location.coords());
+        outline.draw(settings, &mut pen).ok()?;
+        Some(pen.commands)
+    }
+
+    /// `(rgb, opacity)` for a palette entry scaled by `alpha`
+    fn palette_color(&self, palette_index: u16, alpha: f32) -> (String, f32) {
+        let (r, g, b, a) = if palette_index == FOREGROUND_PALETTE_INDEX {
+            let c = self.foreground;
+            (c.r, c.g, c.b, c.a)
+        } else if let Some(c) = self.palette.get(palette_index as usize) {
+            (c.red, c.green, c.blue, c.alpha)
+        } else {
+            (0, 0, 0, 255)
+        };
+        (
+            format!("rgb({},{},{})", r, g, b),
+            a as f32 / 255.0 * alpha.clamp(0.0, 1.0),
+        )
+    }
+
+    fn write_stops(&mut self, stops: &[ColorStop]) {
+        for stop in stops {
+            let (color, opacity) = self.palette_color(stop.palette_index, stop.alpha);
+            let _ = write!(
+                self.defs,
+                r#"<stop offset="{}" stop-color="{}" stop-opacity="{}"/>"#,
+                stop.offset, color, opacity
+            );
+        }
+    }
+
+    /// Fill attributes for `brush`, defining a gradient when needed.
+    /// `brush_transform` maps gradient space into the filled shape's space.
+    fn paint_attributes(&mut self, brush: Brush<'_>, brush_transform: Option<&str>) -> String {
+        let gradient_transform = brush_transform
+            .map(|t| format!(r#" gradientTransform="{}""#, t))
+            .unwrap_or_default();
+        match brush {
+            Brush::Solid {
+                palette_index,
+                alpha,
+            } => {
+                let (color, opacity) = self.palette_color(palette_index, alpha);
+                format!(r#"fill="{}" fill-opacity="{}""#, color, opacity)
+            },
+            Brush::LinearGradient {
+                p0,
+                p1,
+                color_stops,
+                extend,
+            } if color_stops.len() > 1 => {
+                let id = self.new_id("lg");
+                let _ = write!(
+                    self.defs,
+                    r#"<linearGradient id="{}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}" spreadMethod="{}"{}>"#,
+                    id,
+                    p0.x,
+                    p0.y,
+                    p1.x,
+                    p1.y,
+                    spread_method(extend),
+                    gradient_transform
+                );
+                self.write_stops(color_stops);
+                self.defs.push_str("</linearGradient>");
+                format!(r#"fill="url(#{})""#, id)
+            },
+            Brush::RadialGradient {
+                c0,
+                r0,
+                c1,
+                r1,
+                color_stops,
+                extend,
+            } if color_stops.len() > 1 => {
+                // COLRv1's two circles map onto SVG's focal circle (fx, fy,
+                // fr) and end circle (cx, cy, r)
+                let id = self.new_id("rg");
+                let _ = write!(
+                    self.defs,
+                    r#"<radialGradient id="{}" gradientUnits="userSpaceOnUse" fx="{}" fy="{}" fr="{}" cx="{}" cy="{}" r="{}" spreadMethod="{}"{}>"#,
+                    id,
+                    c0.x,
+                    c0.y,
+                    r0,
+                    c1.x,
+                    c1.y,
+                    r1,
+                    spread_method(extend),
+                    gradient_transform
+                );
+                self.write_stops(color_stops);
+                self.defs.push_str("</radialGradient>");
+                format!(r#"fill="url(#{})""#, id)
+            },
+            // SVG has no sweep gradient; like the raster painter, use the
+            // middle stop. Single-stop gradients are solid fills.
+            Brush::LinearGradient { color_stops, .. }
+            | Brush::RadialGradient { color_stops, .. }
+            | Brush::SweepGradient { color_stops, .. } => {
+                match color_stops.get(color_stops.len() / 2) {
+                    Some(stop) => {
+                        let (color, opacity) = self.palette_color(stop.palette_index, stop.alpha);
+                        format!(r#"fill="{}" fill-opacity="{}""#, color, opacity)
+                    },
+                    None => r#"fill="none""#.to_string(),
+                }
+            },
+        }
+    }
+}
+
+impl ColorPainter for SvgColorPainter<'_> {
+    fn push_transform(&mut self, t: skrifa::color::Transform) {
+        if !self.admit(self.open_groups.len() + 1, false) {
+            return;
+        }
+        let attributes = format!(
+            r#"transform="matrix({},{},{},{},{},{})""#,
+            t.xx, t.yx, t.xy, t.yy, t.dx, t.dy
+        );
+        self.open_group(GroupKind::Transform, &attributes);
+    }
+
+    fn pop_transform(&mut self) {
+        self.close_group(GroupKind::Transform);
+    }
+
+    fn push_clip_glyph(&mut self, glyph_id: GlyphId) {
+        if !self.admit(self.open_groups.len() + 1, false) {
+            return;
+        }
+        let path = self.glyph_path(glyph_id).unwrap_or_default();
+        let id = self.new_id("clip");
+        let _ = write!(
+            self.defs,
+            r#"<clipPath id="{}"><path d="{}"/></clipPath>"#,
+            id, path
+        );
+        self.open_group(GroupKind::Clip, &format!(r#"clip-path="url(#{})""#, id));
+    }
+
+    fn push_clip_box(&mut self, clip_box: BoundingBox<f32>) {
+        if !self.admit(self.open_groups.len() + 1, false) {
+            return;
+        }
+        let id = self.new_id("clip");
+        let _ = write!(
+            self.defs,
+            r#"<clipPath id="{}"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath>"#,
+            id,
+            clip_box.x_min,
+            clip_box.y_min,
+            clip_box.x_max - clip_box.x_min,
+            clip_box.y_max - clip_box.y_min
+        );
+        self.open_group(GroupKind::Clip, &format!(r#"clip-path="url(#{})""#, id));
+    }
+
+    fn pop_clip(&mut self) {
+        self.close_group(GroupKind::Clip);
+    }
+
+    fn fill(&mut self, brush: Brush<'_>) {
+        if !self.admit(0, false) {
+            return;
+        }
+        let paint = self.paint_attributes(brush, None);
+        let _ = write!(
+            self.body,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+            -FILL_EXTENT,
+            -FILL_EXTENT,
+            FILL_EXTENT * 2.0,
+            FILL_EXTENT * 2.0,
+            paint
+        );
+    }
+
+    fn fill_glyph(
+        &mut self,
+        glyph_id: GlyphId,
+        brush_transform: Option<skrifa::color::Transform>,
+        brush: Brush<'_>,
+    ) {
+        if !self.admit(0, false) {
+            return;
+        }
+        // A glyph clip filled once is just the glyph path
+        let Some(path) = self.glyph_path(glyph_id) else {
+            return;
+        };
+        let brush_transform = brush_transform.map(|t| {
+            format!(
+                "matrix({},{},{},{},{},{})",
+                t.xx, t.yx, t.xy, t.yy, t.dx, t.dy
+            )
+        });
+        let paint = self.paint_attributes(brush, brush_transform.as_deref());
+        let _ = write!(self.body, r#"<path d="{}" {}/>"#, path, paint);
+    }
+
+    fn push_layer(&mut self, composite_mode: CompositeMode) {
+        if !self.admit(self.open_groups.len() + 1, true) {
+            return;
+        }
+        let attributes = match blend_mode(composite_mode) {
+            Some(mode) => format!(r#"style="isolation:isolate;mix-blend-mode:{}""#, mode),
+            None => {
+                log::debug!(
+                    "SvgColorPainter: {:?} has no SVG equivalent; drawing normally",
+                    composite_mode
+                );
+                r#"style="isolation:isolate""#.to_string()
+            },
+        };
+        self.open_group(GroupKind::Layer, &attributes);
+    }
+
+    fn pop_layer(&mut self) {
+        self.close_group(GroupKind::Layer);
+    }
+}
+
+fn spread_method(extend: Extend) -> &'static str {
+    match extend {
+        Extend::Pad | Extend::Unknown => "pad",
+        Extend::Repeat => "repeat",
+        Extend::Reflect => "reflect",
+    }
+}
+
+/// CSS `mix-blend-mode` for a COLR composite mode. Porter-Duff modes other
+/// than source-over cannot be expressed and return `None`.
+fn blend_mode(mode: CompositeMode) -> Option<&'static str> {
+    Some(match mode {
+        CompositeMode::SrcOver => "normal",
+        CompositeMode::Plus => "plus-lighter",
+        CompositeMode::Screen => "screen",
+        CompositeMode::Overlay => "overlay",
+        CompositeMode::Darken => "darken",
+        CompositeMode::Lighten => "lighten",
+        CompositeMode::ColorDodge => "color-dodge",
+        CompositeMode::ColorBurn => "color-burn",
+        CompositeMode::HardLight => "hard-light",
+        CompositeMode::SoftLight => "soft-light",
+        CompositeMode::Difference => "difference",
+        CompositeMode::Exclusion => "exclusion",
+        CompositeMode::Multiply => "multiply",
+        CompositeMode::HslHue => "hue",
+        CompositeMode::HslSaturation => "saturation",
+        CompositeMode::HslColor => "color",
+        CompositeMode::HslLuminosity => "luminosity",
+        _ => return None,
+    })
+}
+
+/// Outline pen writing path data in font units, without flipping y
+#[derive(Default)]
+struct FontUnitsPen {
+    commands: String,
+}
+
+impl OutlinePen for FontUnitsPen {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let _ = write!(self.commands, "M{},{}", x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let _ = write!(self.commands, "L{},{}", x, y);
+    }
+
+    fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) {
+        let _ = write!(self.commands, "Q{},{} {},{}", cx, cy, x, y);
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        let _ = write!(
+            self.commands,
+            "C{},{} {},{} {},{}",
+            cx0, cy0, cx1, cy1, x, y
+        );
+    }
+
+    fn close(&mut self) {
+        self.commands.push('Z');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use typf_render_color::MAX_COLR_NESTING;
+
+    #[test]
+    fn test_blend_mode_when_porter_duff_then_none() {
+        assert_eq!(blend_mode(CompositeMode::Multiply), Some("multiply"));
+        assert_eq!(blend_mode(CompositeMode::SrcOver), Some("normal"));
+        assert_eq!(blend_mode(CompositeMode::DestIn), None);
+    }
+
+    #[test]
+    fn test_font_units_pen_keeps_y_up() {
+        let mut pen = FontUnitsPen::default();
+        pen.move_to(0.0, 700.0);
+        pen.line_to(10.5, -200.0);
+        pen.close();
+        assert_eq!(pen.commands, "M0,700L10.5,-200Z");
+    }
+
+    #[test]
+    fn test_painter_when_nested_past_limit_then_stops_writing() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../test-fonts/NotoSans-Regular.ttf"
+        );
+        let Ok(data) = std::fs::read(path) else {
+            eprintln!("skipping; missing {path}");
+            return;
+        };
+        let Ok(font) = skrifa::FontRef::new(&data) else {
+            panic!("unreadable test font at {path}");
+        };
+        let location = Location::default();
+        let mut painter = SvgColorPainter {
+            font: &font,
+            location: &location,
+            palette: &[],
+            foreground: Color::rgba(0, 0, 0, 255),
+            id_prefix: "t-",
+            next_id: 0,
+            defs: String::new(),
+            body: String::new(),
+            open_groups: Vec::new(),
+            guard: PaintGuard::new(),
+            error: None,
+        };
+
+        for _ in 0..2 * MAX_COLR_NESTING {
+            painter.push_clip_box(BoundingBox {
+                x_min: 0.0,
+                y_min: 0.0,
+                x_max: 1.0,
+                y_max: 1.0,
+            });
+        }
+        assert_eq!(painter.open_groups.len(), MAX_COLR_NESTING);
+        assert_eq!(painter.defs.matches("<clipPath").count(), MAX_COLR_NESTING);
+        assert!(matches!(
+            painter.error,
+            Some(ColorRenderError::LimitExceeded {
+                what: "nesting",
+                ..
+            })
+        ));
+    }
+}
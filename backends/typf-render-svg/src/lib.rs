@@ -16,18 +16,64 @@
 //! Uses two-phase rendering to ensure proper viewBox dimensions:
 //! - Phase 1: Extract all glyph paths, track actual bounds
 //! - Phase 2: Generate SVG with accurate viewBox from bounds
+//!
+//! ## Glyph Modes
+//!
+//! [`SvgGlyphMode`] picks how outlines land in the document: inline paths,
+//! shared `<defs>` referenced by `<use>`, or `<text>` set in the font itself,
+//! embedded as WOFF. The last two keep long texts small.
+//!
+//! COLR color glyphs are written as native SVG (clip paths, gradients, and
+//! blended groups), defined once per glyph and placed with `<use>`. Other
+//! color formats are embedded as PNG images.
+//!
+//! With [`RenderParams::deterministic`] set, the document is written in a
+//! canonical form: sorted attributes and numbers at a fixed precision.
+
+mod canonical;
+mod colr;
+mod woff;
 
-use skrifa::MetadataProvider;
+use skrifa::{raw::TableProvider, MetadataProvider};
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write as FmtWrite;
 use std::sync::Arc;
 use typf_core::{
+    color_span,
+    diagnostics::{self, DiagnosticKind, Diagnostics},
     error::{RenderError, Result},
+    source_log,
     traits::{FontRef, Renderer},
-    types::{BitmapData, BitmapFormat, RenderOutput, ShapingResult, VectorData, VectorFormat},
-    GlyphSource, GlyphSourcePreference, RenderParams,
+    types::{
+        BitmapData, BitmapFormat, PixelLayout, RenderOutput, ShapingResult, VectorData,
+        VectorFormat,
+    },
+    Color, GlyphSource, GlyphSourcePreference, RenderParams, TextTransform,
 };
 use typf_export::png::encode_bitmap_to_png;
-use typf_render_color::render_glyph_with_preference;
+use typf_render_color::ColorFont;
+
+/// Font family name used for the embedded `@font-face`
+const EMBEDDED_FAMILY: &str = "typf-embedded";
+
+/// OS/2 `fsType` bit forbidding embedding without the owner's permission
+const FS_TYPE_RESTRICTED: u16 = 0x0002;
+
+/// How glyph outlines are written into the SVG
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SvgGlyphMode {
+    /// One `<path>` per glyph occurrence
+    #[default]
+    Inline,
+    /// Each distinct glyph is defined once in `<defs>` and placed with `<use>`
+    Defs,
+    /// Glyphs are set as `<text>` in the font, embedded as WOFF through
+    /// `@font-face`. The whole font is embedded, so this pays off for long
+    /// texts. Glyphs without a character in `cmap` (ligatures, alternates)
+    /// fall back to `<defs>`, as does everything when the font is a
+    /// collection or its `fsType` restricts embedding.
+    EmbeddedFont,
+}
 
 /// SVG vector renderer
 ///
@@ -37,12 +83,17 @@ use typf_render_color::render_glyph_with_preference;
 pub struct SvgRenderer {
     /// SVG canvas padding
     padding: f32,
+    /// How outlines are emitted
+    glyph_mode: SvgGlyphMode,
 }
 
 impl SvgRenderer {
     /// Create a new SVG renderer with default padding
     pub fn new() -> Self {
-        Self { padding: 10.0 }
+        Self {
+            padding: 10.0,
+            glyph_mode: SvgGlyphMode::Inline,
+        }
     }
 
     /// Set the padding around the SVG canvas
@@ -51,6 +102,66 @@ impl SvgRenderer {
         self
     }
 
+    /// Choose how glyph outlines are written (default: inline paths)
+    pub fn with_glyph_mode(mut self, mode: SvgGlyphMode) -> Self {
+        self.glyph_mode = mode;
+        self
+    }
+
+    /// Build the `@font-face` rule for [`SvgGlyphMode::EmbeddedFont`], or
+    /// `None` when the font cannot or may not be embedded.
+    fn embedded_font_face(
+        font_ref: &skrifa::FontRef,
+        data: &[u8],
+        diagnostics: Option<&Diagnostics>,
+    ) -> Option<String> {
+        let restricted = font_ref
+            .os2()
+            .is_ok_and(|os2| os2.fs_type() & FS_TYPE_RESTRICTED != 0);
+        if restricted {
+            diagnostics::warn(
+                diagnostics,
+                DiagnosticKind::Fallback,
+                None,
+                "SvgRenderer: font restricts embedding; using <defs> outlines",
+            );
+            return None;
+        }
+
+        let Some(woff) = woff::encode_woff(data) else {
+            diagnostics::warn(
+                diagnostics,
+                DiagnosticKind::Fallback,
+                None,
+                "SvgRenderer: cannot pack font as WOFF; using <defs> outlines",
+            );
+            return None;
+        };
+
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+        Some(format!(
+            r#"@font-face{{font-family:"{}";src:url(data:font/woff;base64,{}) format("woff");}}"#,
+            EMBEDDED_FAMILY,
+            STANDARD.encode(woff)
+        ))
+    }
+
+    /// First character mapping to each wanted glyph, skipping controls that
+    /// XML cannot carry
+    fn chars_for_glyphs(font_ref: &skrifa::FontRef, wanted: &HashSet<u32>) -> HashMap<u32, char> {
+        let mut chars = HashMap::new();
+        for (codepoint, gid) in font_ref.charmap().mappings() {
+            let gid = gid.to_u32();
+            if !wanted.contains(&gid) || chars.contains_key(&gid) {
+                continue;
+            }
+            if let Some(ch) = char::from_u32(codepoint).filter(|c| !c.is_control()) {
+                chars.insert(gid, ch);
+            }
+        }
+        chars
+    }
+
     /// Extract glyph outline as SVG path string with bounds
     ///
     /// Returns (path_string, min_y, max_y) where min_y/max_y are in scaled
@@ -63,7 +174,8 @@ impl SvgRenderer {
         location: &skrifa::instance::Location,
     ) -> Result<GlyphPath> {
         let font_data = font.data();
-        let font_ref = skrifa::FontRef::new(font_data).map_err(|_| RenderError::InvalidFont)?;
+        let font_ref = skrifa::FontRef::from_index(font_data, font.face_index())
+            .map_err(|_| RenderError::InvalidFont)?;
 
         let outlines = font_ref.outline_glyphs();
         // Use GlyphId::new to support full u32 range (>65k glyph IDs)
@@ -112,7 +224,7 @@ impl SvgRenderer {
         }
 
         let font_data = font.data();
-        let font_ref = match skrifa::FontRef::new(font_data) {
+        let font_ref = match skrifa::FontRef::from_index(font_data, font.face_index()) {
             Ok(f) => f,
             Err(_) => return skrifa::instance::Location::default(),
         };
@@ -151,22 +263,26 @@ impl SvgRenderer {
 
         let preference = GlyphSourcePreference::from_parts(vec![source], []);
 
-        let (render_result, _) = render_glyph_with_preference(
-            font.data(),
-            glyph_id,
-            width,
-            height,
-            glyph_size,
-            params.color_palette,
-            &variations,
-            &preference,
-        )
-        .ok()?;
+        let (render_result, _) = ColorFont::from_index(font.data(), font.face_index())
+            .and_then(|color_font| {
+                color_font.render_glyph_with_preference(
+                    glyph_id,
+                    width,
+                    height,
+                    glyph_size,
+                    params.color_palette.resolve(font.data(), font.face_index()),
+                    &variations,
+                    &preference,
+                )
+            })
+            .ok()?;
 
         let bitmap = BitmapData {
             width,
             height,
             format: BitmapFormat::Rgba8,
+            stride: BitmapFormat::Rgba8.row_bytes(width),
+            layout: PixelLayout::RGBA,
             data: render_result.pixmap.data().to_vec(),
         };
 
@@ -200,6 +316,8 @@ struct GlyphPath {
 
 enum GlyphRenderKind {
     Path(String),
+    /// COLR glyph painted as SVG, defined in `<defs>` by glyph id
+    ColorVector,
     ColorImage {
         data_base64: String,
         width: u32,
@@ -214,6 +332,8 @@ struct ColorImage {
 }
 
 struct PreparedGlyph {
+    id: u32,
+    cluster: u32,
     x: f32,
     y: f32,
     bounds: GlyphBounds,
@@ -236,13 +356,22 @@ impl Renderer for SvgRenderer {
             shaped.glyphs.len()
         );
 
-        let padding = params.padding as f32;
+        let plate = params.effects.background_box;
+        // Auto padding makes room for the box, as in the bitmap renderers
+        let padding = match plate {
+            Some(plate) if params.auto_padding && plate.padding.is_finite() => {
+                params.padding as f32 + plate.padding.max(0.0).ceil()
+            },
+            _ => params.padding as f32,
+        };
         let foreground = params.foreground;
         let scale = shaped.advance_height / font.units_per_em() as f32;
         let glyph_size = shaped.advance_height;
+        let transform = params.active_transform()?;
 
         // Build variable font location from params.variations
         let location = Self::build_location(&font, &params.variations);
+        diagnostics::check_variations(params.diagnostics.as_ref(), &*font, &params.variations);
 
         // Phase 1: Extract all glyph paths and compute actual bounds
         // min_y/max_y are in SVG coordinates relative to baseline (y=0)
@@ -250,30 +379,61 @@ impl Renderer for SvgRenderer {
         let mut min_y: f32 = 0.0; // Below baseline (positive in SVG coords)
         let mut max_y: f32 = 0.0; // Above baseline (negative in SVG coords, but we track magnitude)
         let source_order = params.glyph_sources.effective_order();
+        let colr_font = skrifa::FontRef::from_index(font.data(), font.face_index()).ok();
+        let palette = params.color_palette.resolve(font.data(), font.face_index());
+        // Painted COLR glyphs in first-use order, each defined once
+        let mut colr_glyphs: Vec<(u32, colr::ColrSvg)> = Vec::new();
 
-        for glyph in &shaped.glyphs {
+        for (index, glyph) in shaped.glyphs.iter().enumerate() {
             let glyph_path =
                 self.extract_glyph_path_with_bounds(&font, glyph.id, scale, &location)?;
 
-            let bounds = match glyph_path.bounds {
-                Some(b) => b,
-                None => continue,
+            // COLR glyphs may have no base outline; their paint bounds stand in
+            let colr_bounds = || {
+                let colr_allowed = source_order
+                    .iter()
+                    .any(|s| matches!(s, GlyphSource::Colr0 | GlyphSource::Colr1));
+                let color_glyph = colr_font
+                    .as_ref()
+                    .filter(|_| colr_allowed)?
+                    .color_glyphs()
+                    .get(skrifa::GlyphId::new(glyph.id))?;
+                let bbox =
+                    color_glyph.bounding_box(&location, skrifa::instance::Size::new(glyph_size))?;
+                Some(GlyphBounds {
+                    min_x: bbox.x_min,
+                    max_x: bbox.x_max,
+                    min_y: bbox.y_min,
+                    max_y: bbox.y_max,
+                })
+            };
+            let (bounds, min_y_svg, max_y_svg) = match glyph_path.bounds {
+                Some(b) => (b, glyph_path.min_y_svg, glyph_path.max_y_svg),
+                None => match colr_bounds() {
+                    Some(b) => (b, -b.max_y, -b.min_y),
+                    None => continue,
+                },
             };
 
             // Glyph bounds relative to baseline at this position
             // glyph.y is the vertical offset from baseline (usually 0 for base glyphs)
-            let glyph_min_y = glyph_path.min_y_svg + glyph.y;
-            let glyph_max_y = glyph_path.max_y_svg + glyph.y;
+            // SVG y points down, so raising the run lowers its y
+            let glyph_y = glyph.y - params.baseline_shift;
+            let glyph_min_y = min_y_svg + glyph_y;
+            let glyph_max_y = max_y_svg + glyph_y;
 
             min_y = min_y.min(glyph_min_y);
             max_y = max_y.max(glyph_max_y);
 
             let mut chosen_kind: Option<GlyphRenderKind> = None;
+            let mut chosen_source = GlyphSource::Glyf;
             for source in &source_order {
                 match source {
                     GlyphSource::Glyf | GlyphSource::Cff | GlyphSource::Cff2 => {
                         if !glyph_path.path.is_empty() {
                             chosen_kind = Some(GlyphRenderKind::Path(glyph_path.path.clone()));
+                            chosen_source =
+                                source_log::outline_source(font.data(), font.face_index());
                             break;
                         }
                     },
@@ -283,6 +443,30 @@ impl Renderer for SvgRenderer {
                     | GlyphSource::Sbix
                     | GlyphSource::Cbdt
                     | GlyphSource::Ebdt => {
+                        // COLR glyphs become native SVG, painted once per glyph
+                        if matches!(source, GlyphSource::Colr0 | GlyphSource::Colr1) {
+                            let painted = colr_glyphs.iter().any(|(id, _)| *id == glyph.id)
+                                || colr_font
+                                    .as_ref()
+                                    .and_then(|f| {
+                                        colr::paint_colr_glyph(
+                                            f,
+                                            glyph.id,
+                                            *source,
+                                            &location,
+                                            palette,
+                                            foreground,
+                                            &format!("typf-c{}-", glyph.id),
+                                        )
+                                    })
+                                    .map(|svg| colr_glyphs.push((glyph.id, svg)))
+                                    .is_some();
+                            if painted {
+                                chosen_kind = Some(GlyphRenderKind::ColorVector);
+                                chosen_source = *source;
+                                break;
+                            }
+                        }
                         if let Some(img) = self.render_color_image(
                             &font, glyph.id, &bounds, glyph_size, params, *source,
                         ) {
@@ -291,6 +475,7 @@ impl Renderer for SvgRenderer {
                                 width: img.width,
                                 height: img.height,
                             });
+                            chosen_source = *source;
                             break;
                         }
                     },
@@ -300,17 +485,24 @@ impl Renderer for SvgRenderer {
             let Some(kind) = chosen_kind else {
                 continue;
             };
+            if let Some(log) = &params.glyph_source_log {
+                log.record(index, glyph.id, chosen_source);
+            }
 
             prepared_glyphs.push(PreparedGlyph {
+                id: glyph.id,
+                cluster: glyph.cluster,
                 x: glyph.x,
-                y: glyph.y,
+                y: glyph_y,
                 bounds,
                 kind,
             });
         }
 
         // Phase 2: Calculate viewBox from actual content bounds
-        let width = shaped.advance_width + padding * 2.0;
+        // A clipped or ellipsized line ends at the layout width
+        let text_width = params.layout.visible_width(shaped.advance_width);
+        let mut width = text_width + padding * 2.0;
 
         // In SVG coords: min_y is topmost (most negative), max_y is bottommost (most positive)
         // Content height spans from min_y to max_y
@@ -319,12 +511,28 @@ impl Renderer for SvgRenderer {
         } else {
             max_y - min_y
         };
-        let height = content_height + padding * 2.0;
+        let mut height = content_height + padding * 2.0;
 
         // Baseline position: distance from top of viewBox to baseline
         // min_y is the topmost point (most negative in SVG), so baseline is at:
         // padding + |min_y| = padding - min_y (since min_y is typically negative for ascenders)
-        let baseline_y = padding - min_y;
+        let mut baseline_y = padding - min_y;
+        let mut origin_x = padding;
+
+        // Transformed runs: fit the viewBox to the transformed content box
+        // (y-up for the matrix) and move the run origin to match
+        if let Some(t) = transform {
+            let top = if prepared_glyphs.is_empty() {
+                shaped.advance_height
+            } else {
+                -min_y
+            };
+            let (x0, y0, x1, y1) = t.bounds(0.0, -max_y, shaped.advance_width, top);
+            width = x1 - x0 + padding * 2.0;
+            height = y1 - y0 + padding * 2.0;
+            origin_x = padding - x0;
+            baseline_y = padding + y1;
+        }
 
         let mut svg = String::new();
 
@@ -339,23 +547,190 @@ impl Renderer for SvgRenderer {
         )
         .map_err(|_| RenderError::PathBuildingFailed)?;
 
+        let fill = fill_attr(foreground);
+
+        // Embedded font mode: glyphs with a cmap character become text set
+        // in the embedded face; the rest share <defs> outlines. The single
+        // <text> element has one fill, so per-glyph colors use the outlines.
+        let mut glyph_mode = self.glyph_mode;
+        if glyph_mode == SvgGlyphMode::EmbeddedFont && params.has_foreground_spans() {
+            glyph_mode = SvgGlyphMode::Defs;
+        }
+        let mut font_face = None;
+        let mut glyph_chars = HashMap::new();
+        if glyph_mode == SvgGlyphMode::EmbeddedFont {
+            let font_ref = skrifa::FontRef::from_index(font.data(), font.face_index())
+                .map_err(|_| RenderError::InvalidFont)?;
+            font_face =
+                Self::embedded_font_face(&font_ref, font.data(), params.diagnostics.as_ref());
+            if font_face.is_some() {
+                let wanted = prepared_glyphs
+                    .iter()
+                    .filter(|g| matches!(g.kind, GlyphRenderKind::Path(_)))
+                    .map(|g| g.id)
+                    .collect();
+                glyph_chars = Self::chars_for_glyphs(&font_ref, &wanted);
+            } else {
+                glyph_mode = SvgGlyphMode::Defs;
+            }
+        }
+
+        // Shared outlines and color glyphs: each distinct glyph is written once
+        if glyph_mode != SvgGlyphMode::Inline || !colr_glyphs.is_empty() {
+            writeln!(&mut svg, "  <defs>").map_err(|_| RenderError::PathBuildingFailed)?;
+            if let Some(face) = &font_face {
+                writeln!(&mut svg, "    <style>{}</style>", face)
+                    .map_err(|_| RenderError::PathBuildingFailed)?;
+            }
+            for (id, colr) in &colr_glyphs {
+                writeln!(
+                    &mut svg,
+                    r#"    {}<g id="{}">{}</g>"#,
+                    colr.defs,
+                    colr_ref_id(*id),
+                    colr.body
+                )
+                .map_err(|_| RenderError::PathBuildingFailed)?;
+            }
+            let mut defined = HashSet::new();
+            for glyph in &prepared_glyphs {
+                if glyph_mode == SvgGlyphMode::Inline {
+                    break;
+                }
+                if let GlyphRenderKind::Path(path) = &glyph.kind {
+                    if glyph_chars.contains_key(&glyph.id) || !defined.insert(glyph.id) {
+                        continue;
+                    }
+                    writeln!(
+                        &mut svg,
+                        r#"    <path id="{}" d="{}"/>"#,
+                        glyph_ref_id(glyph.id),
+                        path
+                    )
+                    .map_err(|_| RenderError::PathBuildingFailed)?;
+                }
+            }
+            writeln!(&mut svg, "  </defs>").map_err(|_| RenderError::PathBuildingFailed)?;
+        }
+
+        // One <text> carries every glyph the embedded face can spell, each
+        // pinned to its shaped position so the viewer does not re-space it
+        let text_glyphs: Vec<&PreparedGlyph> = prepared_glyphs
+            .iter()
+            .filter(|g| glyph_chars.contains_key(&g.id))
+            .collect();
+        if !text_glyphs.is_empty() {
+            let mut xs = Vec::with_capacity(text_glyphs.len());
+            let mut ys = Vec::with_capacity(text_glyphs.len());
+            let mut content = String::new();
+            for glyph in &text_glyphs {
+                xs.push(format!("{:.2}", glyph.x));
+                ys.push(format!("{:.2}", glyph.y));
+                if let Some(&ch) = glyph_chars.get(&glyph.id) {
+                    push_xml_escaped(&mut content, ch);
+                }
+            }
+            let mut style =
+                String::from("font-kerning:none;font-variant-ligatures:none;white-space:pre");
+            if !params.variations.is_empty() {
+                let settings: Vec<String> = params
+                    .variations
+                    .iter()
+                    .map(|(tag, value)| format!("'{}' {}", tag, value))
+                    .collect();
+                let _ = write!(style, ";font-variation-settings:{}", settings.join(","));
+            }
+
+            writeln!(
+                &mut svg,
+                r#"  <text font-family="{}" font-size="{:.2}" {} style="{}" transform="{}" x="{}" y="{}">{}</text>"#,
+                EMBEDDED_FAMILY,
+                glyph_size,
+                fill,
+                style,
+                placement(transform, origin_x, baseline_y),
+                xs.join(" "),
+                ys.join(" "),
+                content
+            )
+            .map_err(|_| RenderError::PathBuildingFailed)?;
+        }
+
+        // The box hugs the ink, which is what the viewBox is fitted to
+        let outline = plate
+            .filter(|_| transform.is_none() && !prepared_glyphs.is_empty())
+            .and_then(|plate| {
+                let right = origin_x + text_width;
+                let edges = plate.outline(origin_x, baseline_y + min_y, right, baseline_y + max_y);
+                edges.map(|edges| (plate.color, edges))
+            });
+        if let Some((color, ([x0, y0, x1, y1], radius))) = outline {
+            writeln!(
+                &mut svg,
+                r#"  <rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" rx="{:.2}" {}/>"#,
+                x0,
+                y0,
+                x1 - x0,
+                y1 - y0,
+                radius,
+                fill_attr(color)
+            )
+            .map_err(|_| RenderError::PathBuildingFailed)?;
+        }
+
+        for span in color_span::span_backgrounds(shaped, params) {
+            writeln!(
+                &mut svg,
+                r#"  <rect x="{:.2}" y="0" width="{:.2}" height="{:.2}" {}/>"#,
+                origin_x + span.x,
+                span.width,
+                height,
+                fill_attr(span.color)
+            )
+            .map_err(|_| RenderError::PathBuildingFailed)?;
+        }
+
         // Phase 3: Render each glyph with correct positioning
         for glyph in &prepared_glyphs {
+            let (pen_x, pen_y) = pen_position(transform, glyph.x, glyph.y);
             match &glyph.kind {
                 GlyphRenderKind::Path(path) => {
-                    let x = padding + glyph.x;
-                    let y = baseline_y + glyph.y;
-
+                    if glyph_chars.contains_key(&glyph.id) {
+                        continue;
+                    }
+                    let placement = placement(transform, origin_x + pen_x, baseline_y + pen_y);
+                    let fill = match params.foreground_at(glyph.cluster) {
+                        color if color == foreground => fill.clone(),
+                        color => fill_attr(color),
+                    };
+
+                    if glyph_mode == SvgGlyphMode::Inline {
+                        writeln!(
+                            &mut svg,
+                            r#"  <path d="{}" {} transform="{}"/>"#,
+                            path, fill, placement
+                        )
+                    } else {
+                        writeln!(
+                            &mut svg,
+                            r##"  <use href="#{}" {} transform="{}"/>"##,
+                            glyph_ref_id(glyph.id),
+                            fill,
+                            placement
+                        )
+                    }
+                    .map_err(|_| RenderError::PathBuildingFailed)?;
+                },
+                GlyphRenderKind::ColorVector => {
+                    // Font units, y up, scaled onto the upright pen
                     writeln!(
                         &mut svg,
-                        r#"  <path d="{}" fill="rgb({},{},{})" fill-opacity="{:.2}" transform="translate({:.2},{:.2})"/>"#,
-                        path,
-                        foreground.r,
-                        foreground.g,
-                        foreground.b,
-                        foreground.a as f32 / 255.0,
-                        x,
-                        y
+                        r##"  <use href="#{}" transform="translate({:.2},{:.2}) scale({},{})"/>"##,
+                        colr_ref_id(glyph.id),
+                        origin_x + pen_x,
+                        baseline_y + pen_y,
+                        scale,
+                        -scale
                     )
                     .map_err(|_| RenderError::PathBuildingFailed)?;
                 },
@@ -364,8 +739,9 @@ impl Renderer for SvgRenderer {
                     width,
                     height,
                 } => {
-                    let x = padding + glyph.x + glyph.bounds.min_x;
-                    let y = baseline_y + glyph.y - glyph.bounds.max_y;
+                    // Color images stay upright on the transformed pen
+                    let x = origin_x + pen_x + glyph.bounds.min_x;
+                    let y = baseline_y + pen_y - glyph.bounds.max_y;
 
                     writeln!(
                         &mut svg,
@@ -384,6 +760,10 @@ impl Renderer for SvgRenderer {
         // SVG footer
         writeln!(&mut svg, "</svg>").map_err(|_| RenderError::PathBuildingFailed)?;
 
+        if params.deterministic {
+            svg = canonical::canonicalize(&svg);
+        }
+
         Ok(RenderOutput::Vector(VectorData {
             format: VectorFormat::Svg,
             data: svg,
@@ -395,6 +775,60 @@ impl Renderer for SvgRenderer {
     }
 }
 
+/// `fill` and `fill-opacity` attributes painting `color`.
+fn fill_attr(color: Color) -> String {
+    format!(
+        r#"fill="rgb({},{},{})" fill-opacity="{:.2}""#,
+        color.r,
+        color.g,
+        color.b,
+        color.a as f32 / 255.0
+    )
+}
+
+/// SVG transform putting an outline's origin at `(x, y)`. Transformed runs
+/// carry the matrix flipped into SVG's y-down space.
+fn placement(transform: Option<TextTransform>, x: f32, y: f32) -> String {
+    match transform {
+        Some(t) => format!(
+            "matrix({:.6},{:.6},{:.6},{:.6},{:.2},{:.2})",
+            t.xx, -t.yx, -t.xy, t.yy, x, y
+        ),
+        None => format!("translate({:.2},{:.2})", x, y),
+    }
+}
+
+/// Element id of a glyph outline in `<defs>`
+fn glyph_ref_id(glyph_id: u32) -> String {
+    format!("typf-g{}", glyph_id)
+}
+
+/// Element id of a painted COLR glyph in `<defs>`
+fn colr_ref_id(glyph_id: u32) -> String {
+    format!("typf-colr{}", glyph_id)
+}
+
+/// Append a character, escaped for XML text content
+fn push_xml_escaped(out: &mut String, ch: char) {
+    match ch {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        _ => out.push(ch),
+    }
+}
+
+/// Pen offset from the run origin in SVG (y-down) coordinates
+fn pen_position(transform: Option<TextTransform>, x: f32, y: f32) -> (f32, f32) {
+    match transform {
+        Some(t) => {
+            let (tx, ty) = t.apply(x, -y);
+            (tx, -ty)
+        },
+        None => (x, y),
+    }
+}
+
 /// SVG path builder implementing skrifa's OutlinePen
 ///
 /// Tracks vertical bounds while building the path for proper viewBox sizing.
@@ -644,9 +1078,149 @@ mod tests {
         };
 
         assert!(
-            svg.contains("<image"),
-            "color glyph should be embedded when COLR is preferred"
+            svg.contains("<use href=\"#typf-colr"),
+            "color glyph should be drawn when COLR is preferred"
+        );
+    }
+
+    #[test]
+    fn rotated_run_uses_matrix_and_tall_viewbox() {
+        let renderer = SvgRenderer::new();
+        let Some(font) = load_font("NotoSans-Regular.ttf") else {
+            return;
+        };
+        let mut shaped = shaped_for_char(&font, 'l', 64.0);
+        shaped.advance_width = 200.0;
+
+        let params = RenderParams {
+            output: RenderMode::Vector(VectorFormat::Svg),
+            transform: Some(TextTransform::rotate(90.0)),
+            ..RenderParams::default()
+        };
+
+        let output = renderer.render(&shaped, font, &params).unwrap();
+        let svg = match output {
+            RenderOutput::Vector(v) => v.data,
+            other => panic!("expected vector output, got {:?}", other),
+        };
+
+        assert!(svg.contains("transform=\"matrix("), "{svg}");
+        let view_box: Vec<f32> = svg
+            .split("viewBox=\"")
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .map(|v| v.split(' ').filter_map(|n| n.parse().ok()).collect())
+            .unwrap_or_default();
+        assert!(view_box[3] > view_box[2], "viewBox {view_box:?}");
+    }
+
+    fn shaped_repeat(font: &Arc<dyn FontRef>, text: &str, size: f32) -> ShapingResult {
+        let glyphs: Vec<PositionedGlyph> = text
+            .chars()
+            .enumerate()
+            .map(|(i, ch)| PositionedGlyph {
+                id: font.glyph_id(ch).unwrap_or(0),
+                x: i as f32 * size * 0.6,
+                y: 0.0,
+                advance: size * 0.6,
+                cluster: i as u32,
+            })
+            .collect();
+        ShapingResult {
+            advance_width: glyphs.len() as f32 * size * 0.6,
+            advance_height: size,
+            glyphs,
+            direction: Direction::LeftToRight,
+        }
+    }
+
+    fn render_svg(
+        renderer: &SvgRenderer,
+        shaped: &ShapingResult,
+        font: Arc<dyn FontRef>,
+    ) -> String {
+        let params = RenderParams {
+            output: RenderMode::Vector(VectorFormat::Svg),
+            ..RenderParams::default()
+        };
+        match renderer.render(shaped, font, &params).unwrap() {
+            RenderOutput::Vector(v) => v.data,
+            other => panic!("expected vector output, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_render_when_defs_mode_then_each_outline_written_once() {
+        let Some(font) = load_font("NotoSans-Regular.ttf") else {
+            return;
+        };
+        let shaped = shaped_repeat(&font, "abababab", 32.0);
+
+        let inline = render_svg(&SvgRenderer::new(), &shaped, font.clone());
+        let defs = render_svg(
+            &SvgRenderer::new().with_glyph_mode(SvgGlyphMode::Defs),
+            &shaped,
+            font,
+        );
+
+        assert_eq!(inline.matches("<path d=").count(), 8);
+        assert_eq!(defs.matches("<path id=").count(), 2, "{defs}");
+        assert_eq!(defs.matches("<use href=\"#typf-g").count(), 8);
+        assert!(defs.len() < inline.len());
+    }
+
+    #[test]
+    fn test_render_when_embedded_font_mode_then_text_set_in_woff_face() {
+        let Some(font) = load_font("NotoSans-Regular.ttf") else {
+            return;
+        };
+        let shaped = shaped_repeat(&font, "a<b", 32.0);
+
+        let svg = render_svg(
+            &SvgRenderer::new().with_glyph_mode(SvgGlyphMode::EmbeddedFont),
+            &shaped,
+            font,
+        );
+
+        assert!(
+            svg.contains("src:url(data:font/woff;base64,d09G"),
+            "WOFF face expected"
+        );
+        assert!(
+            svg.contains(">a&lt;b</text>"),
+            "{}",
+            &svg[svg.len().saturating_sub(400)..]
         );
+        assert!(!svg.contains("<use"), "every glyph maps to a character");
+    }
+
+    #[test]
+    fn colr_glyph_is_written_as_native_svg_once() {
+        let Some(font) = load_font("Nabla-Regular-COLR.ttf") else {
+            return;
+        };
+        // Glyph 8 is a COLRv1 glyph without a base outline in this test font
+        let mut shaped = shaped_for_char(&font, 'A', 64.0);
+        shaped.glyphs[0].id = 8;
+        let mut second = shaped.glyphs[0].clone();
+        second.x = 64.0;
+        shaped.glyphs.push(second);
+        shaped.advance_width = 128.0;
+
+        let params = RenderParams {
+            output: RenderMode::Vector(VectorFormat::Svg),
+            glyph_sources: GlyphSourcePreference::from_parts(vec![GlyphSource::Colr1], []),
+            ..RenderParams::default()
+        };
+        let svg = match SvgRenderer::new().render(&shaped, font, &params).unwrap() {
+            RenderOutput::Vector(v) => v.data,
+            other => panic!("expected vector output, got {:?}", other),
+        };
+
+        assert!(!svg.contains("<image"), "COLR should not be rasterized");
+        assert_eq!(svg.matches("<g id=\"typf-colr8\">").count(), 1, "{svg}");
+        assert_eq!(svg.matches("<use href=\"#typf-colr8\"").count(), 2);
+        assert!(svg.contains("clip-path=") || svg.contains("<path d="));
     }
 
     #[test]
@@ -0,0 +1,162 @@
+//! WOFF 1.0 packing for fonts embedded in SVG `@font-face` rules
+//!
+//! WOFF is the sfnt table set with each table zlib-compressed on its own.
+//! Browsers accept it everywhere SVG is shown, and it is usually about half
+//! the size of the raw font, which matters once it is base64-encoded.
+
+use std::io::Write;
+
+use flate2::{write::ZlibEncoder, Compression};
+
+const WOFF_SIGNATURE: u32 = 0x774F_4646; // 'wOFF'
+const WOFF_HEADER_LEN: usize = 44;
+const WOFF_ENTRY_LEN: usize = 20;
+const SFNT_HEADER_LEN: usize = 12;
+const SFNT_ENTRY_LEN: usize = 16;
+
+struct Table<'a> {
+    tag: u32,
+    checksum: u32,
+    data: &'a [u8],
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn pad4(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+/// Pack a single-face sfnt (TrueType or CFF) as WOFF.
+///
+/// Returns `None` for collections or malformed table directories.
+pub(crate) fn encode_woff(sfnt: &[u8]) -> Option<Vec<u8>> {
+    let flavor = read_u32(sfnt, 0)?;
+    if flavor == u32::from_be_bytes(*b"ttcf") {
+        return None;
+    }
+    let num_tables = read_u16(sfnt, 4)? as usize;
+
+    let mut tables = Vec::with_capacity(num_tables);
+    for i in 0..num_tables {
+        let record = SFNT_HEADER_LEN + i * SFNT_ENTRY_LEN;
+        let offset = read_u32(sfnt, record + 8)? as usize;
+        let length = read_u32(sfnt, record + 12)? as usize;
+        tables.push(Table {
+            tag: read_u32(sfnt, record)?,
+            checksum: read_u32(sfnt, record + 4)?,
+            data: sfnt.get(offset..offset.checked_add(length)?)?,
+        });
+    }
+    // The WOFF directory must be sorted by tag
+    tables.sort_by_key(|t| t.tag);
+
+    let mut body = Vec::new();
+    let mut directory = Vec::with_capacity(num_tables * WOFF_ENTRY_LEN);
+    let mut sfnt_size = SFNT_HEADER_LEN + num_tables * SFNT_ENTRY_LEN;
+    let data_start = WOFF_HEADER_LEN + num_tables * WOFF_ENTRY_LEN;
+
+    for table in &tables {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(table.data).ok()?;
+        let compressed = encoder.finish().ok()?;
+        // Tables that do not shrink are stored as-is
+        let stored = if compressed.len() < table.data.len() {
+            &compressed[..]
+        } else {
+            table.data
+        };
+
+        let offset = data_start + body.len();
+        directory.extend_from_slice(&table.tag.to_be_bytes());
+        directory.extend_from_slice(&(offset as u32).to_be_bytes());
+        directory.extend_from_slice(&(stored.len() as u32).to_be_bytes());
+        directory.extend_from_slice(&(table.data.len() as u32).to_be_bytes());
+        directory.extend_from_slice(&table.checksum.to_be_bytes());
+
+        body.extend_from_slice(stored);
+        body.resize(pad4(body.len()), 0);
+        sfnt_size += pad4(table.data.len());
+    }
+
+    let total = data_start + body.len();
+    let mut woff = Vec::with_capacity(total);
+    woff.extend_from_slice(&WOFF_SIGNATURE.to_be_bytes());
+    woff.extend_from_slice(&flavor.to_be_bytes());
+    woff.extend_from_slice(&(total as u32).to_be_bytes());
+    woff.extend_from_slice(&(num_tables as u16).to_be_bytes());
+    woff.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    woff.extend_from_slice(&(sfnt_size as u32).to_be_bytes());
+    woff.extend_from_slice(&1u16.to_be_bytes()); // major version
+    woff.extend_from_slice(&0u16.to_be_bytes()); // minor version
+    woff.extend_from_slice(&[0u8; 20]); // no metadata or private block
+    woff.extend_from_slice(&directory);
+    woff.extend_from_slice(&body);
+    Some(woff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+
+    /// Two-table sfnt: one repetitive (compressible), one tiny (stored raw)
+    fn sample_sfnt() -> Vec<u8> {
+        let big = vec![7u8; 400];
+        let small = vec![1u8, 2, 3];
+        let mut sfnt = Vec::new();
+        sfnt.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        sfnt.extend_from_slice(&2u16.to_be_bytes());
+        sfnt.extend_from_slice(&[0u8; 6]);
+        let first = (SFNT_HEADER_LEN + 2 * SFNT_ENTRY_LEN) as u32;
+        let second = first + pad4(big.len()) as u32;
+        for (tag, offset, len) in [(b"zzzz", first, big.len()), (b"aaaa", second, small.len())] {
+            sfnt.extend_from_slice(tag);
+            sfnt.extend_from_slice(&0u32.to_be_bytes());
+            sfnt.extend_from_slice(&offset.to_be_bytes());
+            sfnt.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+        sfnt.extend_from_slice(&big);
+        sfnt.extend_from_slice(&small);
+        sfnt.push(0);
+        sfnt
+    }
+
+    #[test]
+    fn test_encode_woff_when_sfnt_then_tables_round_trip() {
+        let woff = encode_woff(&sample_sfnt()).unwrap();
+
+        assert_eq!(read_u32(&woff, 0), Some(WOFF_SIGNATURE));
+        assert_eq!(read_u32(&woff, 8), Some(woff.len() as u32));
+        assert_eq!(read_u16(&woff, 12), Some(2));
+
+        // Sorted directory: 'aaaa' (stored) comes before 'zzzz' (compressed)
+        let entry = |i: usize| WOFF_HEADER_LEN + i * WOFF_ENTRY_LEN;
+        assert_eq!(&woff[entry(0)..entry(0) + 4], b"aaaa");
+        let at = read_u32(&woff, entry(0) + 4).unwrap() as usize;
+        assert_eq!(&woff[at..at + 3], &[1, 2, 3]);
+
+        let at = read_u32(&woff, entry(1) + 4).unwrap() as usize;
+        let comp_len = read_u32(&woff, entry(1) + 8).unwrap() as usize;
+        assert!(comp_len < 400);
+        let mut table = Vec::new();
+        ZlibDecoder::new(&woff[at..at + comp_len])
+            .read_to_end(&mut table)
+            .unwrap();
+        assert_eq!(table, vec![7u8; 400]);
+    }
+
+    #[test]
+    fn test_encode_woff_when_collection_or_truncated_then_none() {
+        assert!(encode_woff(b"ttcf\0\0\0\0").is_none());
+        let mut sfnt = sample_sfnt();
+        sfnt.truncate(40);
+        assert!(encode_woff(&sfnt).is_none());
+    }
+}
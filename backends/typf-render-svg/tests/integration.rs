@@ -11,7 +11,7 @@ use std::sync::Arc;
 use typf_core::{
     traits::{FontRef, Renderer},
     types::{Direction, PositionedGlyph, RenderOutput, ShapingResult, VectorFormat},
-    GlyphSource, GlyphSourcePreference, RenderParams,
+    GlyphSource, GlyphSourcePreference, PaletteSelection, RenderParams,
 };
 use typf_fontdb::TypfFontFace;
 use typf_render_svg::SvgRenderer;
@@ -112,13 +112,6 @@ fn first_color_glyph(font: &Arc<dyn FontRef>) -> Option<u32> {
     (0..glyph_count).find(|gid| get_color_glyph_format(data, *gid).is_some())
 }
 
-/// Extract the base64 payload from a data URI inside the SVG
-fn extract_base64_image(svg: &str) -> Option<String> {
-    let start = svg.find("base64,")? + "base64,".len();
-    let end = svg[start..].find('"')? + start;
-    Some(svg[start..end].to_string())
-}
-
 #[test]
 fn test_svg_renderer_creation() {
     let renderer = SvgRenderer::new();
@@ -288,7 +281,7 @@ fn test_svg_consistency() {
 }
 
 #[test]
-fn test_svg_embeds_colr_glyph_as_image() {
+fn test_svg_writes_colr_glyph_as_native_svg() {
     let font = match load_real_font("Nabla-Regular-COLR.ttf") {
         Some(f) => f,
         None => {
@@ -333,12 +326,14 @@ fn test_svg_embeds_colr_glyph_as_image() {
 
     if let Ok(RenderOutput::Vector(vector)) = result {
         assert!(
-            vector.data.contains("<image"),
-            "SVG should embed color glyph as image"
+            vector
+                .data
+                .contains(&format!("<use href=\"#typf-colr{}\"", glyph_id)),
+            "SVG should place the color glyph definition"
         );
         assert!(
-            vector.data.contains("data:image/png;base64,"),
-            "SVG should embed PNG data for color glyph"
+            !vector.data.contains("data:image/png;base64,"),
+            "COLR glyphs should not be rasterized to PNG"
         );
     } else {
         panic!("Expected vector output");
@@ -387,7 +382,7 @@ fn test_svg_color_palette_affects_output() {
     let renderer = SvgRenderer::new();
 
     let params0 = RenderParams {
-        color_palette: 0,
+        color_palette: PaletteSelection::Index(0),
         glyph_sources: GlyphSourcePreference::from_parts(
             vec![GlyphSource::Colr1, GlyphSource::Glyf],
             [],
@@ -400,29 +395,20 @@ fn test_svg_color_palette_affects_output() {
     };
 
     let mut params1 = params0.clone();
-    params1.color_palette = 1;
+    params1.color_palette = PaletteSelection::Index(1);
     let svg1 = match renderer.render(&shaped, font, &params1) {
         Ok(RenderOutput::Vector(v)) => v.data,
         _ => unreachable!("Expected vector output for palette 1"),
     };
 
-    let img0 = match extract_base64_image(&svg0) {
-        Some(img) => img,
-        None => unreachable!("Palette 0 image should exist"),
-    };
-    let img1 = match extract_base64_image(&svg1) {
-        Some(img) => img,
-        None => unreachable!("Palette 1 image should exist"),
-    };
-
-    if img0 == img1 {
-        eprintln!("Skipping palette diff: palettes render identical PNG for this font");
+    if svg0 == svg1 {
+        eprintln!("Skipping palette diff: palettes render identical colors for this font");
         return;
     }
 
-    assert_ne!(
-        img0, img1,
-        "Different palettes should change embedded image data"
+    assert!(
+        svg0.contains("typf-colr") && svg1.contains("typf-colr"),
+        "Both palettes should draw the color glyph"
     );
 }
 
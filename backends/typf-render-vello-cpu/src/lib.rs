@@ -29,10 +29,11 @@ use std::sync::Arc;
 use skrifa::MetadataProvider;
 use thiserror::Error;
 use typf_core::{
-    error::{RenderError, Result},
+    color_span,
+    error::{Classify, ErrorKind, RenderError, Result},
     traits::{FontRef, Renderer},
-    types::{BitmapData, BitmapFormat, RenderOutput, ShapingResult},
-    Color, RenderParams,
+    types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput, ShapingResult},
+    Color, RenderParams, TextTransform,
 };
 use vello_common::glyph::Glyph as VelloGlyph;
 use vello_common::peniko::{Blob, FontData};
@@ -52,9 +53,18 @@ pub enum VelloCpuError {
     RenderingFailed(String),
 }
 
+impl Classify for VelloCpuError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            VelloCpuError::FontDataError(_) => ErrorKind::InvalidFont,
+            VelloCpuError::RenderingFailed(_) => ErrorKind::Backend,
+        }
+    }
+}
+
 impl From<VelloCpuError> for RenderError {
     fn from(e: VelloCpuError) -> Self {
-        RenderError::BackendError(e.to_string())
+        RenderError::classified(&e)
     }
 }
 
@@ -131,13 +141,14 @@ type VelloNormalizedCoord = i16;
 /// uses the F2Dot14 wrapper type. We convert by extracting the raw bits.
 fn build_normalized_coords(
     font_data: &[u8],
+    face_index: u32,
     variations: &[(String, f32)],
 ) -> Vec<VelloNormalizedCoord> {
     if variations.is_empty() {
         return Vec::new();
     }
 
-    let font_ref = match skrifa::FontRef::new(font_data) {
+    let font_ref = match skrifa::FontRef::from_index(font_data, face_index) {
         Ok(f) => f,
         Err(_) => return Vec::new(),
     };
@@ -159,6 +170,61 @@ impl Default for VelloCpuRenderer {
     }
 }
 
+/// Line box of a run, after the optional run-wide transform
+struct TextBox {
+    transform: Option<TextTransform>,
+    /// Distance from the box's left edge to the run origin
+    left: f32,
+    /// Distance from the box's top edge to the baseline
+    top: f32,
+    width: f32,
+    height: f32,
+}
+
+impl TextBox {
+    fn new(transform: Option<TextTransform>, advance: f32, ascent: f32, descent: f32) -> Self {
+        match transform {
+            Some(t) => {
+                let (x0, y0, x1, y1) = t.bounds(0.0, -descent, advance, ascent);
+                Self {
+                    transform,
+                    left: -x0,
+                    top: y1,
+                    width: x1 - x0,
+                    height: y1 - y0,
+                }
+            },
+            None => Self {
+                transform,
+                left: 0.0,
+                top: ascent,
+                width: advance,
+                height: ascent + descent,
+            },
+        }
+    }
+
+    /// Scene transform placing the run origin at `(padding + left, baseline_y)`.
+    /// The y-up text transform is flipped into the y-down canvas.
+    fn origin(&self, padding: f32, baseline_y: f32) -> Affine {
+        let translate = Affine::translate(((padding + self.left) as f64, baseline_y as f64));
+        match self.transform {
+            Some(t) => {
+                translate
+                    * Affine::new([
+                        t.xx as f64,
+                        -t.yx as f64,
+                        -t.xy as f64,
+                        t.yy as f64,
+                        0.0,
+                        0.0,
+                    ])
+            },
+            None => translate,
+        }
+    }
+}
+
 impl Renderer for VelloCpuRenderer {
     fn name(&self) -> &'static str {
         "vello-cpu"
@@ -185,7 +251,7 @@ impl Renderer for VelloCpuRenderer {
         // Fall back to skrifa parsing, then to an approximation if parsing fails.
         let font_bytes = font.data();
         let (ascent, descent) = font
-            .metrics()
+            .metrics_at(&params.variations)
             .filter(|m| m.units_per_em > 0 && (m.ascent != 0 || m.descent != 0))
             .map(|m| {
                 let scale = font_size / (m.units_per_em as f32);
@@ -194,19 +260,23 @@ impl Renderer for VelloCpuRenderer {
                 (ascent, descent)
             })
             .or_else(|| {
-                skrifa::FontRef::new(font_bytes).ok().map(|font_ref| {
-                    let size = skrifa::instance::Size::new(font_size);
-                    let location = skrifa::instance::LocationRef::default();
-                    let metrics = font_ref.metrics(size, location);
-                    (metrics.ascent, metrics.descent.abs())
-                })
+                skrifa::FontRef::from_index(font_bytes, font.face_index())
+                    .ok()
+                    .map(|font_ref| {
+                        let size = skrifa::instance::Size::new(font_size);
+                        let location = skrifa::instance::LocationRef::default();
+                        let metrics = font_ref.metrics(size, location);
+                        (metrics.ascent, metrics.descent.abs())
+                    })
             })
             .unwrap_or((font_size * 0.8, font_size * 0.2));
 
         // Calculate canvas dimensions using actual font metrics
-        let width = (shaped.advance_width + padding * 2.0).ceil() as u32;
+        let transform = params.active_transform()?;
+        let text_box = TextBox::new(transform, shaped.advance_width, ascent, descent);
+        let width = (text_box.width + padding * 2.0).ceil() as u32;
         // Height covers full ascent + descent + padding on both sides
-        let height = (ascent + descent + padding * 2.0).ceil() as u32;
+        let height = (text_box.height + padding * 2.0).ceil() as u32;
 
         // Sanity check dimensions
         if width == 0 || height == 0 {
@@ -219,10 +289,11 @@ impl Renderer for VelloCpuRenderer {
         } else {
             font.data().to_vec().into()
         };
-        let font_data = FontData::new(font_blob, 0);
+        let font_data = FontData::new(font_blob, font.face_index());
 
         // Build normalized variation coordinates for variable fonts
-        let normalized_coords = build_normalized_coords(font.data(), &params.variations);
+        let normalized_coords =
+            build_normalized_coords(font.data(), font.face_index(), &params.variations);
 
         // Create render context
         let mut context = RenderContext::new(width as u16, height as u16);
@@ -232,51 +303,77 @@ impl Renderer for VelloCpuRenderer {
             context.set_paint(Self::to_vello_color(bg));
             context.fill_rect(&Rect::new(0.0, 0.0, width as f64, height as f64));
         }
-
-        // Set foreground color
-        context.set_paint(Self::to_vello_color(params.foreground));
+        for fill in color_span::span_backgrounds(shaped, params) {
+            let x = (padding + text_box.left + fill.x) as f64;
+            context.set_paint(Self::to_vello_color(fill.color));
+            context.fill_rect(&Rect::new(x, 0.0, x + fill.width as f64, height as f64));
+        }
 
         // Calculate baseline position using actual font ascent
         // Baseline is at padding + ascent (top of canvas + space for ascenders)
-        let baseline_y = padding + ascent;
+        let baseline_y = padding + text_box.top;
 
-        // Set transform for glyph positioning
-        context.set_transform(Affine::translate((padding as f64, baseline_y as f64)));
+        // Set transform for glyph positioning, raised off the line's baseline along the
+        // run's own normal
+        let raise = Affine::translate((0.0, -params.baseline_shift as f64));
+        context.set_transform(text_box.origin(padding, baseline_y) * raise);
 
         // Convert glyphs
         let glyphs = Self::convert_glyphs(shaped);
+        let colors: Vec<Color> = shaped
+            .glyphs
+            .iter()
+            .map(|g| params.foreground_at(g.cluster))
+            .collect();
 
-        // Build and render glyph run using RenderContext's built-in glyph support
-        let mut glyph_run = context
-            .glyph_run(&font_data)
-            .font_size(font_size)
-            .hint(self.config.hinting);
-
-        // Apply variable font coordinates if specified
-        if !normalized_coords.is_empty() {
-            glyph_run = glyph_run.normalized_coords(&normalized_coords);
+        // One glyph run per stretch of glyphs sharing a foreground color
+        let mut start = 0;
+        while start < glyphs.len() {
+            let color = colors[start];
+            let end = colors[start..]
+                .iter()
+                .position(|c| *c != color)
+                .map_or(glyphs.len(), |n| start + n);
+            context.set_paint(Self::to_vello_color(color));
+
+            // Build and render glyph run using RenderContext's built-in glyph support
+            let mut glyph_run = context
+                .glyph_run(&font_data)
+                .font_size(font_size)
+                .hint(self.config.hinting);
+
+            // Apply variable font coordinates if specified
+            if !normalized_coords.is_empty() {
+                glyph_run = glyph_run.normalized_coords(&normalized_coords);
+            }
+
+            glyph_run.fill_glyphs(glyphs[start..end].iter().cloned());
+            start = end;
         }
 
-        glyph_run.fill_glyphs(glyphs.into_iter());
-
         // Flush and render to pixmap
         context.flush();
         let mut pixmap = Pixmap::new(width as u16, height as u16);
         context.render_to_pixmap(&mut pixmap);
 
-        // Convert pixmap to RGBA8 bitmap data
+        // Convert pixmap to RGBA8 bitmap data; vello_cpu keeps it premultiplied
         let rgba_data: Vec<u8> = pixmap
             .data()
             .iter()
             .flat_map(|pixel| [pixel.r, pixel.g, pixel.b, pixel.a])
             .collect();
 
-        Ok(RenderOutput::Bitmap(BitmapData {
-            width,
-            height,
-            format: BitmapFormat::Rgba8,
-            data: rgba_data,
-        }))
+        Ok(RenderOutput::Bitmap(
+            BitmapData {
+                width,
+                height,
+                format: BitmapFormat::Rgba8,
+                stride: BitmapFormat::Rgba8.row_bytes(width),
+                layout: PixelLayout::PREMULTIPLIED_RGBA,
+                data: rgba_data,
+            }
+            .into_layout(params.pixel_layout),
+        ))
     }
 
     fn supports_format(&self, format: &str) -> bool {
@@ -20,10 +20,10 @@ use std::sync::Arc;
 
 use thiserror::Error;
 use typf_core::{
-    error::{RenderError, Result},
+    error::{Classify, ErrorKind, RenderError, Result},
     traits::{FontRef, Renderer},
-    types::{BitmapData, BitmapFormat, RenderOutput, ShapingResult},
-    Color, RenderParams,
+    types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput, ShapingResult},
+    Color, RenderParams, TextTransform,
 };
 use vello_common::{
     glyph::Glyph,
@@ -50,15 +50,26 @@ pub enum VelloError {
     ReadbackFailed(String),
 }
 
+impl Classify for VelloError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            VelloError::NoAdapter => ErrorKind::Unsupported,
+            VelloError::DeviceCreation(_)
+            | VelloError::RenderFailed(_)
+            | VelloError::ReadbackFailed(_) => ErrorKind::Backend,
+        }
+    }
+}
+
 impl From<VelloError> for RenderError {
     fn from(e: VelloError) -> Self {
-        RenderError::BackendError(e.to_string())
+        RenderError::classified(&e)
     }
 }
 
 impl From<VelloError> for typf_core::TypfError {
     fn from(e: VelloError) -> Self {
-        typf_core::TypfError::RenderingFailed(RenderError::BackendError(e.to_string()))
+        typf_core::TypfError::RenderingFailed(e.into())
     }
 }
 
@@ -289,6 +300,61 @@ impl VelloRenderer {
     }
 }
 
+/// Line box of a run, after the optional run-wide transform
+struct TextBox {
+    transform: Option<TextTransform>,
+    /// Distance from the box's left edge to the run origin
+    left: f32,
+    /// Distance from the box's top edge to the baseline
+    top: f32,
+    width: f32,
+    height: f32,
+}
+
+impl TextBox {
+    fn new(transform: Option<TextTransform>, advance: f32, ascent: f32, descent: f32) -> Self {
+        match transform {
+            Some(t) => {
+                let (x0, y0, x1, y1) = t.bounds(0.0, -descent, advance, ascent);
+                Self {
+                    transform,
+                    left: -x0,
+                    top: y1,
+                    width: x1 - x0,
+                    height: y1 - y0,
+                }
+            },
+            None => Self {
+                transform,
+                left: 0.0,
+                top: ascent,
+                width: advance,
+                height: ascent + descent,
+            },
+        }
+    }
+
+    /// Scene transform placing the run origin at `(padding + left, baseline_y)`.
+    /// The y-up text transform is flipped into the y-down canvas.
+    fn origin(&self, padding: f32, baseline_y: f32) -> Affine {
+        let translate = Affine::translate(((padding + self.left) as f64, baseline_y as f64));
+        match self.transform {
+            Some(t) => {
+                translate
+                    * Affine::new([
+                        t.xx as f64,
+                        -t.yx as f64,
+                        -t.xy as f64,
+                        t.yy as f64,
+                        0.0,
+                        0.0,
+                    ])
+            },
+            None => translate,
+        }
+    }
+}
+
 impl Renderer for VelloRenderer {
     fn name(&self) -> &'static str {
         "vello"
@@ -317,7 +383,7 @@ impl Renderer for VelloRenderer {
         // - Prefer font ascent/descent (stable across strings)
         // - Fall back to a heuristic if font metrics are unavailable.
         let (ascent, descent) = font
-            .metrics()
+            .metrics_at(&params.variations)
             .filter(|m| m.units_per_em > 0 && (m.ascent != 0 || m.descent != 0))
             .map(|m| {
                 let scale = font_size / (m.units_per_em as f32);
@@ -328,8 +394,10 @@ impl Renderer for VelloRenderer {
             .unwrap_or((font_size * 0.8, font_size * 0.2));
 
         // Calculate canvas dimensions
-        let width = (shaped.advance_width + padding * 2.0).ceil() as u32;
-        let height = (ascent + descent + padding * 2.0).ceil() as u32;
+        let transform = params.active_transform()?;
+        let text_box = TextBox::new(transform, shaped.advance_width, ascent, descent);
+        let width = (text_box.width + padding * 2.0).ceil() as u32;
+        let height = (text_box.height + padding * 2.0).ceil() as u32;
 
         // Sanity check dimensions
         if width == 0 || height == 0 {
@@ -363,17 +431,19 @@ impl Renderer for VelloRenderer {
         } else {
             font.data().to_vec().into()
         };
-        let font_data = FontData::new(font_blob, 0);
+        let font_data = FontData::new(font_blob, font.face_index());
 
         // Convert glyphs
         let glyphs = Self::convert_glyphs(shaped);
 
         if !glyphs.is_empty() {
             // Baseline position: padding + ascent (top of canvas + ascender space)
-            let baseline_y = padding + ascent;
+            let baseline_y = padding + text_box.top;
 
-            // Set transform for positioning
-            scene.set_transform(Affine::translate((padding as f64, baseline_y as f64)));
+            // Set transform for positioning, raised off the line's baseline along the
+            // run's own normal
+            let raise = Affine::translate((0.0, -params.baseline_shift as f64));
+            scene.set_transform(text_box.origin(padding, baseline_y) * raise);
 
             // Render glyphs using glyph_run builder
             scene
@@ -385,12 +455,17 @@ impl Renderer for VelloRenderer {
         // Render scene to bitmap
         let rgba_data = self.render_to_bitmap(&scene, width, height)?;
 
-        Ok(RenderOutput::Bitmap(BitmapData {
-            width,
-            height,
-            format: BitmapFormat::Rgba8,
-            data: rgba_data,
-        }))
+        Ok(RenderOutput::Bitmap(
+            BitmapData {
+                width,
+                height,
+                format: BitmapFormat::Rgba8,
+                stride: BitmapFormat::Rgba8.row_bytes(width),
+                layout: PixelLayout::RGBA,
+                data: rgba_data,
+            }
+            .into_layout(params.pixel_layout),
+        ))
     }
 
     fn supports_format(&self, format: &str) -> bool {
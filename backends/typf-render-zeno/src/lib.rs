@@ -33,12 +33,18 @@ use kurbo::Shape;
 use skrifa::MetadataProvider;
 use std::sync::Arc;
 use typf_core::{
-    error::{RenderError, Result},
+    budget::{self, Reservation},
+    cancel, color_span,
+    diagnostics::{self, DiagnosticKind},
+    effects::CoverageMask,
+    error::{ErrorContext, RenderError, Result, TypfError},
+    render_target::{self, BufferTarget, ClipTarget, DirtyRect, GlyphCull, RenderTarget},
+    sanitize, source_log,
     traits::{FontRef, Renderer},
-    types::{BitmapData, BitmapFormat, RenderOutput, ShapingResult, VectorFormat},
-    GlyphSource, GlyphSourcePreference, RenderMode, RenderParams,
+    types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput, ShapingResult, VectorFormat},
+    GlyphSource, GlyphSourcePreference, Insets, RenderMode, RenderParams, TextTransform,
 };
-use typf_render_color::{compute_content_bounds, render_glyph_with_preference};
+use typf_render_color::{compute_content_bounds, ColorFont, ColorRenderError};
 use typf_render_svg::SvgRenderer;
 
 /// Pure Rust renderer that punches above its weight
@@ -51,6 +57,8 @@ pub struct ZenoRenderer {
     max_width: u32,
     /// Safety net to prevent runaway memory allocation (height)
     max_height: u32,
+    /// Minimum padding on every side of the canvas
+    default_padding: u32,
 }
 
 impl ZenoRenderer {
@@ -59,9 +67,16 @@ impl ZenoRenderer {
         Self {
             max_width: typf_core::get_max_bitmap_width(),
             max_height: typf_core::get_max_bitmap_height(),
+            default_padding: 0,
         }
     }
 
+    /// Sets the minimum padding, used when `RenderParams::padding` is smaller
+    pub fn with_default_padding(mut self, padding: u32) -> Self {
+        self.default_padding = padding;
+        self
+    }
+
     /// Turns a single glyph outline into a beautiful bitmap
     ///
     /// This is where Zeno's magic shines: we extract the glyph outline,
@@ -74,12 +89,14 @@ impl ZenoRenderer {
         font_size: f32,
         location: &skrifa::instance::Location,
         params: &RenderParams,
+        transform: Option<TextTransform>,
     ) -> Result<GlyphBitmap> {
         use zeno::Mask;
 
         // Grab the font data for skrifa to parse
         let font_data = font.data();
-        let font_ref = skrifa::FontRef::new(font_data).map_err(|_| RenderError::InvalidFont)?;
+        let font_ref = skrifa::FontRef::from_index(font_data, font.face_index())
+            .map_err(|_| RenderError::InvalidFont)?;
         let color_allowed = allows_color_sources(&params.glyph_sources);
 
         // Navigate to the glyph collection
@@ -148,6 +165,7 @@ impl ZenoRenderer {
                 data: GlyphBitmapData::Mask(Vec::new()),
                 bearing_x: 0,
                 bearing_y: 0,
+                source: source_log::outline_source(font_data, font.face_index()),
             });
         }
 
@@ -157,6 +175,14 @@ impl ZenoRenderer {
         let mut min_y = bbox.y0 as f32;
         let mut max_x = bbox.x1 as f32;
         let mut max_y = bbox.y1 as f32;
+        // Malformed glyphs are clamped (or refused, when strict) before they size a canvas
+        [min_x, min_y, max_x, max_y] = sanitize::clamp_glyph_bounds(
+            glyph_id.to_u32(),
+            [min_x, min_y, max_x, max_y],
+            font_size,
+            params.strict,
+            params.diagnostics.as_ref(),
+        )?;
 
         // Track if the outline is empty (zero-area bbox)
         let outline_empty = max_x - min_x == 0.0 || max_y - min_y == 0.0;
@@ -220,6 +246,37 @@ impl ZenoRenderer {
             .into());
         }
 
+        // Transform the outline only now: color glyphs above keep the
+        // upright bounds their renderer expects
+        let (path_data, width, height) = match transform {
+            Some(t) if !outline_empty => {
+                let mut builder = ZenoPathBuilder::new(1.0).with_transform(t);
+                let settings = skrifa::outline::DrawSettings::unhinted(size, location.coords());
+                glyph
+                    .draw(settings, &mut builder)
+                    .map_err(|_| RenderError::OutlineExtractionFailed)?;
+                let (path_data, kurbo_path) = builder.finish();
+                let bbox = kurbo_path.bounding_box();
+                min_x = bbox.x0 as f32;
+                min_y = bbox.y0 as f32;
+                max_x = bbox.x1 as f32;
+                max_y = bbox.y1 as f32;
+                [min_x, min_y, max_x, max_y] = sanitize::clamp_glyph_bounds(
+                    glyph_id.to_u32(),
+                    [min_x, min_y, max_x, max_y],
+                    font_size,
+                    params.strict,
+                    params.diagnostics.as_ref(),
+                )?;
+                (
+                    path_data,
+                    ((max_x - min_x).ceil() as u32).max(1),
+                    ((max_y - min_y).ceil() as u32).max(1) + 1,
+                )
+            },
+            _ => (path_data, width, height),
+        };
+
         // Create our rendering canvas
         let mut mask = vec![0u8; (width * height) as usize];
 
@@ -248,6 +305,7 @@ impl ZenoRenderer {
             data: GlyphBitmapData::Mask(mask),
             bearing_x: min_x.floor() as i32,
             bearing_y: max_y.ceil() as i32, // Distance from baseline to top edge (use ceil for proper alignment)
+            source: source_log::outline_source(font_data, font.face_index()),
         })
     }
 
@@ -277,16 +335,21 @@ impl ZenoRenderer {
             .map(|(tag, value)| (tag.as_str(), *value))
             .collect();
 
-        match render_glyph_with_preference(
-            font.data(),
-            glyph_id,
-            width,
-            height,
-            font_size,
-            params.color_palette,
-            &variations,
-            &params.glyph_sources,
-        ) {
+        let rendered =
+            ColorFont::from_index(font.data(), font.face_index()).and_then(|color_font| {
+                color_font
+                    .with_memory_budget(params.memory_budget.clone())
+                    .render_glyph_with_preference(
+                        glyph_id,
+                        width,
+                        height,
+                        font_size,
+                        params.color_palette.resolve(font.data(), font.face_index()),
+                        &variations,
+                        &params.glyph_sources,
+                    )
+            });
+        match rendered {
             Ok((rendered, source_used)) => {
                 let pixmap = rendered.pixmap;
                 let pixmap_data = pixmap.data();
@@ -372,9 +435,10 @@ impl ZenoRenderer {
                     data: GlyphBitmapData::RgbaPremul(rgba_data),
                     bearing_x,
                     bearing_y,
+                    source: source_used,
                 }))
             },
-            Err(typf_render_color::ColorRenderError::GlyphNotFound) => {
+            Err(ColorRenderError::GlyphNotFound) => {
                 // No color glyph available - allow outline fallback
                 log::debug!(
                     "Zeno: no color glyph for {}, falling back to outline",
@@ -382,28 +446,345 @@ impl ZenoRenderer {
                 );
                 Ok(None)
             },
-            Err(typf_render_color::ColorRenderError::NoColrTable) => {
+            Err(ColorRenderError::NoColrTable) => {
                 // Font has no COLR table - allow outline fallback
                 Ok(None)
             },
-            Err(typf_render_color::ColorRenderError::NoPalette) => {
-                // No palette available - allow outline fallback
-                log::debug!(
-                    "Zeno: no palette for glyph {}, falling back to outline",
-                    glyph_id
+            Err(ColorRenderError::NoPalette) => {
+                // A color glyph without a palette to paint it; its outline stands in
+                diagnostics::warn(
+                    params.diagnostics.as_ref(),
+                    DiagnosticKind::Fallback,
+                    Some(glyph_id),
+                    format!("Zeno: no palette for color glyph {glyph_id}; drew its outline"),
                 );
                 Ok(None)
             },
+            // Budget errors stay bare so the run stops on them
+            Err(ColorRenderError::BudgetExceeded(e)) => Err(e.into()),
             Err(err) => {
                 // Actual rendering error (pixmap creation failed, paint error, etc.)
-                Err(RenderError::BackendError(format!(
-                    "color glyph {} render failed: {:?}",
-                    glyph_id, err
-                ))
-                .into())
+                Err(TypfError::from(err).with_context(ErrorContext::glyph(glyph_id)))
             },
         }
     }
+
+    /// Phases 1 and 2 of rendering: rasterize every glyph and size the canvas.
+    ///
+    /// Shared by `render`, which composites into a fresh canvas, and
+    /// `render_into`, which composites into a caller's target.
+    fn layout(
+        &self,
+        shaped: &ShapingResult,
+        font: &Arc<dyn FontRef>,
+        params: &RenderParams,
+        cull: Option<&GlyphCull>,
+    ) -> Result<Layout> {
+        // Explicit padding plus room for effects, so strokes and shadows are never clipped
+        let insets = params
+            .canvas_insets(self.default_padding)
+            .grow(Insets::uniform(sdf_spread(params).unwrap_or(0)));
+        let pad_top = insets.top as f32;
+        let glyph_size = shaped.advance_height;
+
+        // Build variable font location from params.variations
+        let location = build_location(font, &params.variations);
+        diagnostics::check_variations(params.diagnostics.as_ref(), &**font, &params.variations);
+        let transform = params.active_transform()?;
+
+        // Phase 1: Render all glyphs first to get accurate bounds
+        // This ensures we don't clip tall glyphs (emoji, Thai marks, Arabic diacritics)
+        let mut rendered_glyphs: Vec<RenderedGlyph> = Vec::new();
+        let mut memory = budget::reserve(params.memory_budget.as_ref(), 0)?;
+        let mut culled = 0usize;
+        let mut min_y: f32 = 0.0; // Relative to baseline
+        let mut max_y: f32 = 0.0; // Relative to baseline
+
+        // Horizontal ink extent, only needed to fit transformed runs
+        let mut min_x = f32::MAX;
+        let mut max_x = f32::MIN;
+        let mut last_error: Option<String> = None;
+
+        for (index, glyph) in shaped.glyphs.iter().enumerate() {
+            cancel::check(params.cancel.as_ref())?;
+            // Transformed pens only map to canvas columns after layout, so
+            // clipped redraws of transformed runs keep every glyph.
+            let skip = cull.filter(|c| {
+                transform.is_none() && !c.keeps(insets.left as f32 + glyph.x, glyph.advance)
+            });
+            if let Some(c) = skip {
+                // Measured instead of drawn, so the line box matches a full render
+                let extent = c.skipped_extent(&**font, glyph.id, glyph_size, &params.variations);
+                if let Some((top, bottom)) = extent {
+                    let lift = glyph.y + params.baseline_shift;
+                    max_y = max_y.max(lift + top);
+                    min_y = min_y.min(lift + bottom);
+                }
+                culled += 1;
+                continue;
+            }
+            // Compositing treats `glyph.y` as a downward offset; flip it into
+            // y-up for the matrix and back. The baseline shift raises the pen
+            // before the transform, so rotated runs shift along their normal.
+            let shift = params.baseline_shift;
+            let (glyph_x, glyph_y) = match transform {
+                Some(t) => {
+                    let (x, y) = t.apply(glyph.x, shift - glyph.y);
+                    (x, -y)
+                },
+                None => (glyph.x, glyph.y - shift),
+            };
+            match self.render_glyph(font, glyph.id, glyph_size, &location, params, transform) {
+                Ok(bitmap) => {
+                    // Skip empty glyphs (like spaces)
+                    if bitmap.width == 0 || bitmap.height == 0 {
+                        continue;
+                    }
+                    if let Some(log) = &params.glyph_source_log {
+                        log.record(index, glyph.id, bitmap.source);
+                    }
+
+                    // bearing_y is distance from baseline to top of glyph (positive = above baseline)
+                    // glyph top relative to baseline = glyph.y + bearing_y
+                    // glyph bottom relative to baseline = glyph.y + bearing_y - height
+                    // Transformed runs measure from the pen they are composited at
+                    let lift = if transform.is_some() {
+                        -glyph_y
+                    } else {
+                        glyph.y + shift
+                    };
+                    let glyph_top = lift + bitmap.bearing_y as f32;
+                    let glyph_bottom = lift + bitmap.bearing_y as f32 - bitmap.height as f32;
+
+                    max_y = max_y.max(glyph_top);
+                    min_y = min_y.min(glyph_bottom);
+                    let glyph_left = glyph_x + bitmap.bearing_x as f32;
+                    min_x = min_x.min(glyph_left);
+                    max_x = max_x.max(glyph_left + bitmap.width as f32);
+
+                    memory.grow(bitmap.data.byte_len())?;
+                    rendered_glyphs.push(RenderedGlyph {
+                        bitmap,
+                        glyph_x,
+                        glyph_y,
+                        cluster: glyph.cluster,
+                    });
+                },
+                // Running out of budget ends the run; other failures skip a glyph
+                Err(e @ TypfError::RenderingFailed(RenderError::BudgetExceeded { .. })) => {
+                    return Err(e)
+                },
+                Err(e) if params.strict => return Err(e),
+                Err(e) => {
+                    diagnostics::warn(
+                        params.diagnostics.as_ref(),
+                        DiagnosticKind::GlyphSkipped,
+                        Some(glyph.id),
+                        format!("Zeno: failed to render: {e}"),
+                    );
+                    last_error = Some(e.to_string());
+                },
+            }
+        }
+
+        // Glyphs skipped by `cull` still count as content, so a clipped
+        // redraw keeps the same canvas height as a full render.
+        let empty = rendered_glyphs.is_empty() && culled == 0;
+
+        if empty && !shaped.glyphs.is_empty() {
+            if let Some(err) = last_error {
+                return Err(RenderError::BackendError(err).into());
+            }
+            return Err(RenderError::BackendError("no glyphs rendered".into()).into());
+        }
+
+        // Phase 2: Calculate canvas dimensions.
+        //
+        // Baseline standardization (metrics-first):
+        // - Prefer font ascent/descent (stable across strings)
+        // - Expand to include any glyph bounds that exceed the metrics (effects, extreme accents)
+        let (metrics_ascent, metrics_descent) = font
+            .metrics_at(&params.variations)
+            .filter(|m| m.units_per_em > 0 && (m.ascent != 0 || m.descent != 0))
+            .map(|m| {
+                let scale = glyph_size / (m.units_per_em as f32);
+                let ascent = (m.ascent as f32).max(0.0) * scale;
+                let descent = (m.descent as f32).abs() * scale;
+                (ascent, descent)
+            })
+            .unwrap_or((0.0, 0.0));
+
+        let glyph_top = max_y.max(0.0);
+        let glyph_bottom = (-min_y).max(0.0);
+        let mut top = glyph_top.max(metrics_ascent);
+        let mut bottom = glyph_bottom.max(metrics_descent);
+        let mut pad_left = insets.left as f32;
+        // A clipped or ellipsized line ends at the layout width
+        let mut text_width = params.layout.visible_width(shaped.advance_width);
+
+        // Transformed runs: fit the transformed line box and the ink
+        if let Some(t) = transform {
+            let (x0, y0, x1, y1) =
+                t.bounds(0.0, -metrics_descent, shaped.advance_width, metrics_ascent);
+            let (x0, x1) = (x0.min(min_x), x1.max(max_x));
+            top = glyph_top.max(y1);
+            bottom = glyph_bottom.max(-y0);
+            pad_left -= x0;
+            text_width = x1 - x0;
+        }
+        let width = (text_width + insets.horizontal() as f32).ceil() as u32;
+
+        let content_height = if empty {
+            16.0 // Default minimum for empty text
+        } else {
+            top + bottom
+        };
+        let height = (content_height + insets.vertical() as f32).ceil() as u32;
+
+        // Validate dimensions
+        if width == 0 || height == 0 {
+            return Err(RenderError::ZeroDimensions { width, height }.into());
+        }
+
+        if width > self.max_width || height > self.max_height {
+            return Err(RenderError::DimensionsTooLarge {
+                width,
+                height,
+                max_width: self.max_width,
+                max_height: self.max_height,
+            }
+            .into());
+        }
+
+        // Baseline position: top inset + distance from top to baseline
+        let baseline_y = if empty { pad_top } else { pad_top + top };
+
+        let text_box = (transform.is_none() && !empty).then_some([
+            pad_left,
+            baseline_y - top,
+            pad_left + text_width,
+            baseline_y + bottom,
+        ]);
+
+        Ok(Layout {
+            width,
+            height,
+            pad_left,
+            baseline_y,
+            text_box,
+            glyphs: rendered_glyphs,
+            memory,
+        })
+    }
+
+    /// Composite a run into `target`, skipping glyphs `cull` rules out.
+    fn draw_into(
+        &self,
+        shaped: &ShapingResult,
+        font: &Arc<dyn FontRef>,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+        cull: Option<&GlyphCull>,
+    ) -> Result<Option<DirtyRect>> {
+        let layout = self.layout(shaped, font, params, cull)?;
+        self.draw_layout(layout, shaped, params, target, origin)
+    }
+
+    /// Render into a fresh canvas of `params.format` with aligned rows.
+    ///
+    /// Coverage formats and padded rows go through the render target path;
+    /// only packed `Rgba8` canvases take the renderer's own compositor.
+    fn render_to_buffer(
+        &self,
+        mut layout: Layout,
+        shaped: &ShapingResult,
+        params: &RenderParams,
+    ) -> Result<RenderOutput> {
+        let (width, height, format) = (layout.width, layout.height, params.format);
+        let stride = format.stride(width, params.row_alignment);
+        layout.memory.grow(stride * height as usize)?;
+        let mut data = vec![0u8; stride * height as usize];
+        let mut target = BufferTarget::new(&mut data, width, height, stride, format)?;
+        self.draw_layout(layout, shaped, params, &mut target, (0, 0))?;
+        Ok(RenderOutput::Bitmap(
+            BitmapData {
+                width,
+                height,
+                format,
+                stride,
+                layout: PixelLayout::RGBA,
+                data,
+            }
+            .into_layout(params.pixel_layout),
+        ))
+    }
+
+    /// Composite a laid-out run into `target`.
+    fn draw_layout(
+        &self,
+        mut layout: Layout,
+        shaped: &ShapingResult,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+    ) -> Result<Option<DirtyRect>> {
+        let effects = layout.effect_mask(params)?;
+        let (ox, oy) = origin;
+
+        let mut dirty = None;
+        if let Some(bg) = params.background {
+            dirty = render_target::fill_rect(target, ox, oy, layout.width, layout.height, bg)?;
+        }
+        if let Some((plate, [left, top, right, bottom])) =
+            params.effects.background_box.zip(layout.text_box)
+        {
+            let (x, y) = (ox as f32, oy as f32);
+            let filled = plate.fill(target, x + left, y + top, x + right, y + bottom)?;
+            dirty = DirtyRect::merge(dirty, filled);
+        }
+        let spans = color_span::fill_span_backgrounds(
+            target,
+            shaped,
+            params,
+            ox as f32 + layout.pad_left,
+            oy,
+            layout.height,
+        )?;
+        dirty = DirtyRect::merge(dirty, spans);
+        if let Some(mask) = effects {
+            let drawn = mask.draw_effects(&params.effects, target, origin)?;
+            dirty = DirtyRect::merge(dirty, drawn);
+        }
+
+        for rg in &layout.glyphs {
+            let bitmap = &rg.bitmap;
+            let x = ox + (layout.pad_left + rg.glyph_x) as i32 + bitmap.bearing_x;
+            let y = oy + (layout.baseline_y + rg.glyph_y) as i32 - bitmap.bearing_y;
+            let touched = match &bitmap.data {
+                GlyphBitmapData::Mask(mask) => render_target::blend_mask(
+                    target,
+                    x,
+                    y,
+                    bitmap.width,
+                    bitmap.height,
+                    mask,
+                    params.foreground_at(rg.cluster),
+                )?,
+                GlyphBitmapData::RgbaPremul(rgba) => render_target::blend_premul_rgba(
+                    target,
+                    x,
+                    y,
+                    bitmap.width,
+                    bitmap.height,
+                    rgba,
+                )?,
+            };
+            dirty = DirtyRect::merge(dirty, touched);
+        }
+
+        Ok(dirty)
+    }
 }
 
 impl Default for ZenoRenderer {
@@ -448,6 +829,31 @@ fn allows_color_sources(pref: &GlyphSourcePreference) -> bool {
 }
 
 /// Build variation location from params
+/// Errors unless at least one outline or color glyph source is allowed
+fn check_glyph_sources(params: &RenderParams) -> Result<()> {
+    let allows_outline = params
+        .glyph_sources
+        .effective_order()
+        .iter()
+        .any(|s| matches!(s, GlyphSource::Glyf | GlyphSource::Cff | GlyphSource::Cff2));
+    if !allows_outline && !allows_color_sources(&params.glyph_sources) {
+        return Err(RenderError::BackendError(
+            "zeno renderer requires outline or color glyph sources".to_string(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Distance fields fall off `spread` pixels beyond the outline, so the
+/// canvas needs that much extra room on every side.
+fn sdf_spread(params: &RenderParams) -> Option<u32> {
+    match params.output {
+        RenderMode::DistanceField { spread } => Some(spread.max(1)),
+        _ => None,
+    }
+}
+
 fn build_location(
     font: &Arc<dyn FontRef>,
     variations: &[(String, f32)],
@@ -457,7 +863,7 @@ fn build_location(
     }
 
     let font_data = font.data();
-    let font_ref = match skrifa::FontRef::new(font_data) {
+    let font_ref = match skrifa::FontRef::from_index(font_data, font.face_index()) {
         Ok(f) => f,
         Err(_) => return skrifa::instance::Location::default(),
     };
@@ -482,18 +888,7 @@ impl Renderer for ZenoRenderer {
         font: Arc<dyn FontRef>,
         params: &RenderParams,
     ) -> Result<RenderOutput> {
-        let allows_outline = params
-            .glyph_sources
-            .effective_order()
-            .iter()
-            .any(|s| matches!(s, GlyphSource::Glyf | GlyphSource::Cff | GlyphSource::Cff2));
-        let allows_color = allows_color_sources(&params.glyph_sources);
-        if !allows_outline && !allows_color {
-            return Err(RenderError::BackendError(
-                "zeno renderer requires outline or color glyph sources".to_string(),
-            )
-            .into());
-        }
+        check_glyph_sources(params)?;
 
         // Vector mode: delegate to SVG renderer for path extraction
         if let RenderMode::Vector(vector_format) = params.output {
@@ -509,102 +904,69 @@ impl Renderer for ZenoRenderer {
             }
         }
 
-        let padding = params.padding as f32;
-        let glyph_size = shaped.advance_height;
-
-        // Build variable font location from params.variations
-        let location = build_location(&font, &params.variations);
-
-        // Phase 1: Render all glyphs first to get accurate bounds
-        // This ensures we don't clip tall glyphs (emoji, Thai marks, Arabic diacritics)
-        let mut rendered_glyphs: Vec<RenderedGlyph> = Vec::new();
-        let mut min_y: f32 = 0.0; // Relative to baseline
-        let mut max_y: f32 = 0.0; // Relative to baseline
-        let mut last_error: Option<String> = None;
-
-        for glyph in &shaped.glyphs {
-            match self.render_glyph(&font, glyph.id, glyph_size, &location, params) {
-                Ok(bitmap) => {
-                    // Skip empty glyphs (like spaces)
-                    if bitmap.width == 0 || bitmap.height == 0 {
-                        continue;
-                    }
-
-                    // bearing_y is distance from baseline to top of glyph (positive = above baseline)
-                    // glyph top relative to baseline = glyph.y + bearing_y
-                    // glyph bottom relative to baseline = glyph.y + bearing_y - height
-                    let glyph_top = glyph.y + bitmap.bearing_y as f32;
-                    let glyph_bottom = glyph.y + bitmap.bearing_y as f32 - bitmap.height as f32;
-
-                    max_y = max_y.max(glyph_top);
-                    min_y = min_y.min(glyph_bottom);
-
-                    rendered_glyphs.push(RenderedGlyph {
-                        bitmap,
-                        glyph_x: glyph.x,
-                        glyph_y: glyph.y,
-                    });
-                },
-                Err(e) => {
-                    log::warn!("Zeno: Failed to render glyph {}: {:?}", glyph.id, e);
-                    last_error = Some(e.to_string());
-                },
-            }
+        let mut layout = self.layout(shaped, &font, params, None)?;
+        // The compositor below writes packed Rgba8 rows only
+        let stride = params.format.stride(layout.width, params.row_alignment);
+        let packed_rgba =
+            params.format == BitmapFormat::Rgba8 && stride == layout.width as usize * 4;
+        if !packed_rgba && sdf_spread(params).is_none() {
+            return self.render_to_buffer(layout, shaped, params);
         }
-
-        if rendered_glyphs.is_empty() && !shaped.glyphs.is_empty() {
-            if let Some(err) = last_error {
-                return Err(RenderError::BackendError(err).into());
+        let effects = layout.effect_mask(params)?;
+        let Layout {
+            width,
+            height,
+            pad_left,
+            baseline_y,
+            text_box,
+            glyphs: rendered_glyphs,
+            mut memory,
+        } = layout;
+
+        if let Some(spread) = sdf_spread(params) {
+            memory.grow((width * height) as usize)?;
+            let mut canvas = vec![0u8; (width * height) as usize];
+            for rg in rendered_glyphs {
+                let bitmap = &rg.bitmap;
+                // Color glyphs contribute their alpha channel as coverage
+                let coverage = match &bitmap.data {
+                    GlyphBitmapData::Mask(mask) => mask.clone(),
+                    GlyphBitmapData::RgbaPremul(rgba) => {
+                        rgba.chunks_exact(4).map(|px| px[3]).collect()
+                    },
+                };
+                let field = typf_core::sdf::coverage_to_distance_field(
+                    &coverage,
+                    bitmap.width,
+                    bitmap.height,
+                    spread,
+                );
+                let x = (pad_left + rg.glyph_x) as i32 + bitmap.bearing_x - spread as i32;
+                let y = (baseline_y + rg.glyph_y) as i32 - bitmap.bearing_y - spread as i32;
+                typf_core::sdf::blit_distance_field(
+                    &mut canvas,
+                    width,
+                    height,
+                    &field,
+                    bitmap.width + 2 * spread,
+                    bitmap.height + 2 * spread,
+                    x,
+                    y,
+                );
             }
-            return Err(RenderError::BackendError("no glyphs rendered".into()).into());
-        }
-
-        // Phase 2: Calculate canvas dimensions.
-        //
-        // Baseline standardization (metrics-first):
-        // - Prefer font ascent/descent (stable across strings)
-        // - Expand to include any glyph bounds that exceed the metrics (effects, extreme accents)
-        let width = (shaped.advance_width + padding * 2.0).ceil() as u32;
-
-        let (metrics_ascent, metrics_descent) = font
-            .metrics()
-            .filter(|m| m.units_per_em > 0 && (m.ascent != 0 || m.descent != 0))
-            .map(|m| {
-                let scale = glyph_size / (m.units_per_em as f32);
-                let ascent = (m.ascent as f32).max(0.0) * scale;
-                let descent = (m.descent as f32).abs() * scale;
-                (ascent, descent)
-            })
-            .unwrap_or((0.0, 0.0));
-
-        let glyph_top = max_y.max(0.0);
-        let glyph_bottom = (-min_y).max(0.0);
-        let top = glyph_top.max(metrics_ascent);
-        let bottom = glyph_bottom.max(metrics_descent);
-
-        let content_height = if rendered_glyphs.is_empty() {
-            16.0 // Default minimum for empty text
-        } else {
-            top + bottom
-        };
-        let height = (content_height + padding * 2.0).ceil() as u32;
 
-        // Validate dimensions
-        if width == 0 || height == 0 {
-            return Err(RenderError::ZeroDimensions { width, height }.into());
-        }
-
-        if width > self.max_width || height > self.max_height {
-            return Err(RenderError::DimensionsTooLarge {
+            return Ok(RenderOutput::Bitmap(BitmapData {
                 width,
                 height,
-                max_width: self.max_width,
-                max_height: self.max_height,
-            }
-            .into());
+                format: BitmapFormat::Gray8,
+                stride: BitmapFormat::Gray8.row_bytes(width),
+                layout: PixelLayout::RGBA,
+                data: canvas,
+            }));
         }
 
         // Create premultiplied RGBA canvas
+        memory.grow((width * height * 4) as usize)?;
         let mut canvas = vec![0u8; (width * height * 4) as usize];
 
         // Fill background if specified (premultiplied)
@@ -620,22 +982,30 @@ impl Renderer for ZenoRenderer {
                 pixel[3] = a as u8;
             }
         }
-
-        // Baseline position: padding + distance from top to baseline
-        let baseline_y = if rendered_glyphs.is_empty() {
-            padding
-        } else {
-            padding + top
-        };
+        let plate = params.effects.background_box.zip(text_box);
+        if plate.is_some() || !params.color_spans.is_empty() || effects.is_some() {
+            let stride = width as usize * 4;
+            let mut target =
+                BufferTarget::new(&mut canvas, width, height, stride, BitmapFormat::Rgba8)?
+                    .with_premultiplied(true);
+            if let Some((plate, [left, top, right, bottom])) = plate {
+                plate.fill(&mut target, left, top, right, bottom)?;
+            }
+            color_span::fill_span_backgrounds(&mut target, shaped, params, pad_left, 0, height)?;
+            if let Some(mask) = effects {
+                mask.draw_effects(&params.effects, &mut target, (0, 0))?;
+            }
+        }
 
         // Phase 3: Composite pre-rendered glyphs onto canvas
         for rg in rendered_glyphs {
             let bitmap = &rg.bitmap;
 
             // Position glyph on canvas
-            let x = (padding + rg.glyph_x) as i32 + bitmap.bearing_x;
+            let x = (pad_left + rg.glyph_x) as i32 + bitmap.bearing_x;
             let y = (baseline_y + rg.glyph_y) as i32 - bitmap.bearing_y;
 
+            let fg = params.foreground_at(rg.cluster);
             match &bitmap.data {
                 GlyphBitmapData::Mask(mask) => {
                     for gy in 0..bitmap.height {
@@ -655,7 +1025,6 @@ impl Renderer for ZenoRenderer {
 
                             let canvas_idx = ((py as u32 * width + px as u32) * 4) as usize;
 
-                            let fg = &params.foreground;
                             let src_a = coverage * fg.a as u32 / 255;
                             let src_r = fg.r as u32 * src_a / 255;
                             let src_g = fg.g as u32 * src_a / 255;
@@ -717,33 +1086,102 @@ impl Renderer for ZenoRenderer {
             }
         }
 
-        // Convert premultiplied canvas back to straight RGBA for output
-        let mut output = canvas;
-        for px in output.chunks_exact_mut(4) {
-            let a = px[3];
-            if a == 0 {
-                px[0] = 0;
-                px[1] = 0;
-                px[2] = 0;
-                continue;
+        // The canvas is premultiplied; hand it over in the requested layout
+        Ok(RenderOutput::Bitmap(
+            BitmapData {
+                width,
+                height,
+                format: BitmapFormat::Rgba8,
+                stride: BitmapFormat::Rgba8.row_bytes(width),
+                layout: PixelLayout::PREMULTIPLIED_RGBA,
+                data: canvas,
             }
-            let a_u = a as u32;
-            px[0] = ((px[0] as u32 * 255 + a_u / 2) / a_u).min(255) as u8;
-            px[1] = ((px[1] as u32 * 255 + a_u / 2) / a_u).min(255) as u8;
-            px[2] = ((px[2] as u32 * 255 + a_u / 2) / a_u).min(255) as u8;
+            .into_layout(params.pixel_layout),
+        ))
+    }
+
+    fn render_into(
+        &self,
+        shaped: &ShapingResult,
+        font: Arc<dyn FontRef>,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+    ) -> Result<Option<DirtyRect>> {
+        check_glyph_sources(params)?;
+        if !matches!(params.output, RenderMode::Bitmap) {
+            return render_target::render_via_bitmap(self, shaped, font, params, target, origin);
         }
 
-        Ok(RenderOutput::Bitmap(BitmapData {
-            width,
-            height,
-            format: BitmapFormat::Rgba8,
-            data: output,
-        }))
+        self.draw_into(shaped, &font, params, target, origin, None)
+    }
+
+    fn render_region(
+        &self,
+        shaped: &ShapingResult,
+        font: Arc<dyn FontRef>,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+        clip: DirtyRect,
+    ) -> Result<Option<DirtyRect>> {
+        check_glyph_sources(params)?;
+        if !matches!(params.output, RenderMode::Bitmap) {
+            return render_target::render_clipped(self, shaped, font, params, target, origin, clip);
+        }
+
+        // The font size bounds how far ink strays from the pen span.
+        let cull = GlyphCull::new(clip, origin, shaped.advance_height);
+        let mut clipped = ClipTarget::new(target, clip);
+        self.draw_into(shaped, &font, params, &mut clipped, origin, Some(&cull))
     }
 
     fn supports_format(&self, format: &str) -> bool {
         let f = format.to_ascii_lowercase();
-        matches!(f.as_str(), "bitmap" | "rgba" | "svg" | "vector")
+        matches!(f.as_str(), "bitmap" | "rgba" | "svg" | "vector" | "sdf")
+    }
+}
+
+/// Rasterized glyphs and canvas geometry for one run
+struct Layout {
+    width: u32,
+    height: u32,
+    pad_left: f32,
+    baseline_y: f32,
+    /// Untransformed text box as left, top, right, bottom; none when empty
+    text_box: Option<[f32; 4]>,
+    glyphs: Vec<RenderedGlyph>,
+    /// Budget held for the glyph bitmaps, and for the canvas once allocated
+    memory: Reservation,
+}
+
+impl Layout {
+    /// The run's coverage, when the params' effects need it.
+    fn effect_mask(&mut self, params: &RenderParams) -> Result<Option<CoverageMask>> {
+        let effects = &params.effects;
+        if effects.stroke.is_none() && effects.shadow.is_none() {
+            return Ok(None);
+        }
+        if sdf_spread(params).is_some() {
+            return Ok(None);
+        }
+        let mut mask = CoverageMask::new(self.width, self.height);
+        // The mask and one working copy for the stroke or the blur
+        self.memory.grow(2 * mask.byte_len())?;
+        for rg in &self.glyphs {
+            let bitmap = &rg.bitmap;
+            let x = (self.pad_left + rg.glyph_x) as i32 + bitmap.bearing_x;
+            let y = (self.baseline_y + rg.glyph_y) as i32 - bitmap.bearing_y;
+            match &bitmap.data {
+                GlyphBitmapData::Mask(coverage) => {
+                    mask.add(x, y, bitmap.width, bitmap.height, coverage)
+                },
+                GlyphBitmapData::RgbaPremul(rgba) => {
+                    mask.add_rgba(x, y, bitmap.width, bitmap.height, rgba)
+                },
+            }
+        }
+        Ok(Some(mask))
     }
 }
 
@@ -752,6 +1190,8 @@ struct RenderedGlyph {
     bitmap: GlyphBitmap,
     glyph_x: f32,
     glyph_y: f32,
+    /// Source byte the glyph came from, for [`RenderParams::foreground_at`]
+    cluster: u32,
 }
 
 /// A rendered glyph complete with positioning for perfect layout
@@ -761,6 +1201,7 @@ struct GlyphBitmap {
     data: GlyphBitmapData, // Coverage or premultiplied color data
     bearing_x: i32,        // Horizontal offset from origin to left edge
     bearing_y: i32,        // Vertical offset from baseline to top edge
+    source: GlyphSource,   // Table the bitmap was drawn from
 }
 
 /// Stored glyph data for compositing
@@ -769,6 +1210,15 @@ enum GlyphBitmapData {
     RgbaPremul(Vec<u8>),
 }
 
+impl GlyphBitmapData {
+    /// Bytes of pixel data held
+    fn byte_len(&self) -> usize {
+        match self {
+            Self::Mask(data) | Self::RgbaPremul(data) => data.len(),
+        }
+    }
+}
+
 /// Dual-output path builder that feeds two masters at once
 ///
 /// This clever builder creates both:
@@ -777,9 +1227,10 @@ enum GlyphBitmapData {
 ///
 /// No parsing, no approximation—just the best of both worlds.
 struct ZenoPathBuilder {
-    commands: Vec<String>,      // SVG commands for Zeno
-    kurbo_path: kurbo::BezPath, // Path for kurbo's bounds calculation
-    scale: f32,                 // Scaling factor for coordinate transformation
+    commands: Vec<String>,            // SVG commands for Zeno
+    kurbo_path: kurbo::BezPath,       // Path for kurbo's bounds calculation
+    scale: f32,                       // Scaling factor for coordinate transformation
+    transform: Option<TextTransform>, // Applied after scaling
 }
 
 impl ZenoPathBuilder {
@@ -788,6 +1239,20 @@ impl ZenoPathBuilder {
             commands: Vec::new(),
             kurbo_path: kurbo::BezPath::new(),
             scale,
+            transform: None,
+        }
+    }
+
+    fn with_transform(mut self, transform: TextTransform) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+
+    fn map(&self, x: f32, y: f32) -> (f32, f32) {
+        let (x, y) = (x * self.scale, y * self.scale);
+        match self.transform {
+            Some(t) => t.apply(x, y),
+            None => (x, y),
         }
     }
 
@@ -798,24 +1263,20 @@ impl ZenoPathBuilder {
 
 impl skrifa::outline::OutlinePen for ZenoPathBuilder {
     fn move_to(&mut self, x: f32, y: f32) {
-        let x = x * self.scale;
-        let y = y * self.scale;
+        let (x, y) = self.map(x, y);
         self.commands.push(format!("M {:.2},{:.2}", x, y));
         self.kurbo_path.move_to((x as f64, y as f64));
     }
 
     fn line_to(&mut self, x: f32, y: f32) {
-        let x = x * self.scale;
-        let y = y * self.scale;
+        let (x, y) = self.map(x, y);
         self.commands.push(format!("L {:.2},{:.2}", x, y));
         self.kurbo_path.line_to((x as f64, y as f64));
     }
 
     fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) {
-        let cx = cx * self.scale;
-        let cy = cy * self.scale;
-        let x = x * self.scale;
-        let y = y * self.scale;
+        let (cx, cy) = self.map(cx, cy);
+        let (x, y) = self.map(x, y);
         self.commands
             .push(format!("Q {:.2},{:.2} {:.2},{:.2}", cx, cy, x, y));
         self.kurbo_path
@@ -823,12 +1284,9 @@ impl skrifa::outline::OutlinePen for ZenoPathBuilder {
     }
 
     fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
-        let cx0 = cx0 * self.scale;
-        let cy0 = cy0 * self.scale;
-        let cx1 = cx1 * self.scale;
-        let cy1 = cy1 * self.scale;
-        let x = x * self.scale;
-        let y = y * self.scale;
+        let (cx0, cy0) = self.map(cx0, cy0);
+        let (cx1, cy1) = self.map(cx1, cy1);
+        let (x, y) = self.map(x, y);
         self.commands.push(format!(
             "C {:.2},{:.2} {:.2},{:.2} {:.2},{:.2}",
             cx0, cy0, cx1, cy1, x, y
@@ -1176,6 +1634,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_distance_field_mode_returns_gray8() {
+        let renderer = ZenoRenderer::new();
+        let font = load_test_font();
+
+        let glyph_id = font.glyph_id('Z').unwrap_or(0);
+        let shaped = ShapingResult {
+            glyphs: vec![typf_core::types::PositionedGlyph {
+                id: glyph_id,
+                x: 0.0,
+                y: 0.0,
+                advance: 64.0,
+                cluster: 0,
+            }],
+            advance_width: 64.0,
+            advance_height: 64.0,
+            direction: Direction::LeftToRight,
+        };
+
+        let params = RenderParams {
+            output: RenderMode::DistanceField { spread: 6 },
+            ..RenderParams::default()
+        };
+
+        match renderer.render(&shaped, font, &params).unwrap() {
+            RenderOutput::Bitmap(bitmap) => {
+                assert_eq!(bitmap.format, BitmapFormat::Gray8);
+                assert_eq!(bitmap.width, 64 + 12);
+                assert_eq!(bitmap.data.len(), (bitmap.width * bitmap.height) as usize);
+                assert!(bitmap.data.iter().any(|&v| v > 128), "glyph interior");
+                assert!(bitmap.data.iter().any(|&v| v < 128), "glyph exterior");
+            },
+            other => panic!("expected bitmap output, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_calculate_bounds_space_separated_commands() {
         // Regression test for Round 28 fix: SVG paths with space-separated commands
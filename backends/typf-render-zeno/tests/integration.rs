@@ -4,9 +4,10 @@
 
 use std::sync::Arc;
 use typf_core::{
+    render_target::{BufferTarget, DirtyRect},
     traits::{FontRef, Renderer},
-    types::{BitmapFormat, Direction, GlyphId, RenderOutput, ShapingResult},
-    RenderParams,
+    types::{BitmapFormat, Direction, GlyphId, PixelLayout, RenderOutput, ShapingResult},
+    Color, RenderParams, TextTransform,
 };
 use typf_render_zeno::ZenoRenderer;
 
@@ -72,3 +73,119 @@ fn test_empty_rendering() {
         assert!(bitmap.height > 0);
     }
 }
+
+#[test]
+fn test_render_into_when_offset_then_fills_background_at_origin() {
+    let renderer = ZenoRenderer::new();
+    let font = Arc::new(StubFont { data: vec![] }) as Arc<dyn FontRef>;
+    let shaped = ShapingResult {
+        glyphs: vec![],
+        advance_width: 10.0,
+        advance_height: 20.0,
+        direction: Direction::LeftToRight,
+    };
+    let params = RenderParams {
+        background: Some(Color::rgba(10, 20, 30, 255)),
+        ..RenderParams::default()
+    };
+
+    let Ok(RenderOutput::Bitmap(expected)) = renderer.render(&shaped, font.clone(), &params) else {
+        panic!("expected bitmap output");
+    };
+
+    let mut pixels = vec![0u8; 64 * 64 * 4];
+    let mut target = BufferTarget::new(&mut pixels, 64, 64, 64 * 4, BitmapFormat::Rgba8).unwrap();
+    let dirty = renderer
+        .render_into(&shaped, font, &params, &mut target, (3, 4))
+        .unwrap();
+
+    assert_eq!(
+        dirty,
+        Some(DirtyRect {
+            x: 3,
+            y: 4,
+            width: expected.width,
+            height: expected.height
+        })
+    );
+    assert_eq!(&pixels[(4 * 64 + 3) * 4..][..4], &[10, 20, 30, 255]);
+    assert_eq!(&pixels[(4 * 64 + 2) * 4..][..4], &[0, 0, 0, 0]);
+}
+
+#[test]
+fn test_render_when_premultiplied_bgra_then_canvas_handed_over_unconverted() {
+    let renderer = ZenoRenderer::new();
+    let font = Arc::new(StubFont { data: vec![] }) as Arc<dyn FontRef>;
+    let shaped = ShapingResult {
+        glyphs: vec![],
+        advance_width: 10.0,
+        advance_height: 20.0,
+        direction: Direction::LeftToRight,
+    };
+    let straight_params = RenderParams {
+        background: Some(Color::rgba(200, 100, 50, 128)),
+        ..RenderParams::default()
+    };
+    let bgra_params = RenderParams {
+        pixel_layout: PixelLayout::PREMULTIPLIED_BGRA,
+        ..straight_params.clone()
+    };
+
+    let Ok(RenderOutput::Bitmap(straight)) =
+        renderer.render(&shaped, font.clone(), &straight_params)
+    else {
+        panic!("expected bitmap output");
+    };
+    let Ok(RenderOutput::Bitmap(bgra)) = renderer.render(&shaped, font, &bgra_params) else {
+        panic!("expected bitmap output");
+    };
+
+    assert_eq!(straight.layout, PixelLayout::RGBA);
+    assert_eq!(bgra.layout, PixelLayout::PREMULTIPLIED_BGRA);
+    assert_eq!(&bgra.data[..4], &[25, 50, 100, 128]);
+    assert_eq!(bgra.to_layout(PixelLayout::RGBA).data, straight.data);
+}
+
+#[test]
+fn test_render_when_rotated_quarter_turn_then_canvas_stands_up() {
+    let renderer = ZenoRenderer::new();
+    let shaped = ShapingResult {
+        glyphs: vec![],
+        advance_width: 100.0,
+        advance_height: 20.0,
+        direction: Direction::LeftToRight,
+    };
+    let font = Arc::new(StubFont { data: vec![] }) as Arc<dyn FontRef>;
+    let size = |transform| {
+        let params = RenderParams {
+            transform,
+            ..RenderParams::default()
+        };
+        match renderer.render(&shaped, font.clone(), &params) {
+            Ok(RenderOutput::Bitmap(bitmap)) => (bitmap.width, bitmap.height),
+            other => panic!("expected bitmap, got {:?}", other.map(|_| ())),
+        }
+    };
+
+    let (width, height) = size(None);
+    let (rot_width, rot_height) = size(Some(TextTransform::rotate(90.0)));
+    assert!(width > height);
+    assert!(rot_height > rot_width, "{rot_width}x{rot_height}");
+}
+
+#[test]
+fn test_render_when_degenerate_transform_then_errors() {
+    let renderer = ZenoRenderer::new();
+    let font = Arc::new(StubFont { data: vec![] }) as Arc<dyn FontRef>;
+    let params = RenderParams {
+        transform: Some(TextTransform::scale(1.0, 0.0)),
+        ..RenderParams::default()
+    };
+    let shaped = ShapingResult {
+        glyphs: vec![],
+        advance_width: 100.0,
+        advance_height: 20.0,
+        direction: Direction::LeftToRight,
+    };
+    assert!(renderer.render(&shaped, font, &params).is_err());
+}
@@ -13,9 +13,12 @@ use std::{
     cell::RefCell,
     ffi::c_void,
     ptr::{self, NonNull},
-    sync::Arc,
+    sync::{Arc, OnceLock},
 };
 use typf_core::{
+    cancel,
+    case::apply_case,
+    digits::substitute_digits,
     error::{Result, ShapingError, TypfError},
     traits::{FontRef, Shaper},
     types::{PositionedGlyph, ShapingResult},
@@ -71,7 +74,10 @@ pub struct CoreTextShaper {
     /// Note: font_cache is thread-local (FONT_CACHE) to ensure CTFont objects
     /// are always destroyed on the same thread they were created, avoiding
     /// memory corruption in CoreText's OTL lookup tables.
-    shape_cache: Option<RwLock<LruCache<ShapeCacheKey, Arc<ShapingResult>>>>,
+    ///
+    /// The LRU preallocates its full capacity, so it is built on the first
+    /// insert rather than in the constructor.
+    shape_cache: Option<OnceLock<RwLock<LruCache<ShapeCacheKey, Arc<ShapingResult>>>>>,
 }
 
 impl CoreTextShaper {
@@ -82,15 +88,9 @@ impl CoreTextShaper {
 
     /// Creates a new shaper with optional shape caching
     pub fn with_cache(enabled: bool) -> Self {
-        let cache = if enabled {
-            Some(RwLock::new(LruCache::new(
-                std::num::NonZeroUsize::new(1000).unwrap(),
-            )))
-        } else {
-            None
-        };
-
-        Self { shape_cache: cache }
+        Self {
+            shape_cache: enabled.then(OnceLock::new),
+        }
     }
 
     /// Makes a unique key for caching fonts with their settings
@@ -514,12 +514,21 @@ impl Shaper for CoreTextShaper {
         params: &ShapingParams,
     ) -> Result<ShapingResult> {
         log::debug!("CoreTextShaper: Shaping {} chars", text.chars().count());
+        cancel::check(params.cancel.as_ref())?;
+        // Case changes first, so small caps see the font's smcp forms
+        if let Some(cased) = apply_case(text, font.as_ref(), params) {
+            return cased.shape(params, |text, params| self.shape(text, font, params));
+        }
+        // Native digits go in before shaping, so the font's forms for them apply
+        if let Some(digits) = substitute_digits(text, params) {
+            return digits.shape(params, |text, params| self.shape(text, font, params));
+        }
 
         // Create cache key
         let cache_key = Self::shape_cache_key(text, &font, params);
 
         // Check shape cache
-        if let Some(cache_lock) = &self.shape_cache {
+        if let Some(cache_lock) = self.shape_cache.as_ref().and_then(OnceLock::get) {
             let cache = cache_lock.read();
             if let Some(cached) = cache.peek(&cache_key) {
                 log::debug!("CoreTextShaper: Shape cache hit");
@@ -558,9 +567,11 @@ impl Shaper for CoreTextShaper {
         };
 
         // Cache the result
-        if let Some(cache_lock) = &self.shape_cache {
-            let mut cache = cache_lock.write();
-            cache.put(cache_key, Arc::new(result.clone()));
+        if let Some(cache_cell) = &self.shape_cache {
+            let cache_lock = cache_cell.get_or_init(|| {
+                RwLock::new(LruCache::new(std::num::NonZeroUsize::new(1000).unwrap()))
+            });
+            cache_lock.write().put(cache_key, Arc::new(result.clone()));
         }
 
         Ok(result)
@@ -576,7 +587,7 @@ impl Shaper for CoreTextShaper {
         // Clear thread-local font cache
         FONT_CACHE.with(|cache| cache.borrow_mut().clear());
         // Clear shared shape cache
-        if let Some(cache) = &self.shape_cache {
+        if let Some(cache) = self.shape_cache.as_ref().and_then(OnceLock::get) {
             cache.write().clear();
         }
     }
@@ -681,6 +692,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_with_cache_when_created_then_lru_deferred_until_insert() {
+        let shaper = CoreTextShaper::with_cache(true);
+        let cell = shaper.shape_cache.as_ref().expect("cache enabled");
+        assert!(cell.get().is_none());
+        shaper.clear_cache();
+        assert!(cell.get().is_none());
+        assert!(CoreTextShaper::with_cache(false).shape_cache.is_none());
+    }
+
     #[test]
     fn test_cache_clearing() {
         let shaper = CoreTextShaper::new();
@@ -0,0 +1,100 @@
+//! Shaping many short runs with and without the kept HarfBuzz face
+//!
+//! A UI frame shapes thousands of one- or two-word runs in the same font and
+//! settings. Fonts that share their bytes keep their HarfBuzz face, and with
+//! it HarfBuzz's cached shape plans; fonts that only lend their bytes get a
+//! fresh face, and fresh plans, for every run. Both cases go through the
+//! same `HarfBuzzShaper`, so the difference in the criterion report is the
+//! face reuse alone.
+//!
+//! Run with `cargo bench -p typf-shape-hb --bench ui_runs`. Needs
+//! `test-fonts/NotoSans-Regular.ttf`.
+
+// this_file: backends/typf-shape-hb/benches/ui_runs.rs
+
+use std::path::Path;
+use std::sync::Arc;
+
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+use typf_core::{
+    traits::{FontRef, Shaper},
+    ShapingParams,
+};
+use typf_shape_hb::HarfBuzzShaper;
+
+/// Labels and button captions, the runs a UI frame is made of
+const WORDS: [&str; 8] = [
+    "OK",
+    "Cancel",
+    "Settings",
+    "Open file",
+    "Save",
+    "Typography",
+    "Help",
+    "Quit",
+];
+const RUNS: usize = 1000;
+
+/// NotoSans, optionally sharing its bytes with the shaper
+struct BenchFont {
+    data: Arc<Vec<u8>>,
+    shared: bool,
+}
+
+impl FontRef for BenchFont {
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn data_shared(&self) -> Option<Arc<dyn AsRef<[u8]> + Send + Sync>> {
+        self.shared
+            .then(|| self.data.clone() as Arc<dyn AsRef<[u8]> + Send + Sync>)
+    }
+
+    fn units_per_em(&self) -> u16 {
+        1000
+    }
+
+    fn glyph_id(&self, _ch: char) -> Option<u32> {
+        None
+    }
+
+    fn advance_width(&self, _glyph_id: u32) -> f32 {
+        0.0
+    }
+}
+
+fn bench_ui_runs(c: &mut Criterion) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test-fonts/NotoSans-Regular.ttf");
+    let Ok(data) = std::fs::read(path) else {
+        eprintln!("Skipping benchmark: NotoSans-Regular.ttf not found");
+        return;
+    };
+    let data = Arc::new(data);
+    let params = ShapingParams {
+        size: 14.0,
+        features: vec![("kern".to_string(), 1), ("liga".to_string(), 1)],
+        ..ShapingParams::default()
+    };
+
+    let mut group = c.benchmark_group("ui_runs");
+    group.throughput(Throughput::Elements(RUNS as u64));
+    for (name, shared) in [("fresh_face", false), ("kept_face", true)] {
+        let shaper = HarfBuzzShaper::new();
+        let font: Arc<dyn FontRef> = Arc::new(BenchFont {
+            data: data.clone(),
+            shared,
+        });
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                for word in WORDS.iter().cycle().take(RUNS) {
+                    black_box(shaper.shape(black_box(word), font.clone(), &params).ok());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion::criterion_group!(benches, bench_ui_runs);
+criterion::criterion_main!(benches);
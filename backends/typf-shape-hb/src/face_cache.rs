@@ -0,0 +1,65 @@
+//! HarfBuzz faces kept across runs.
+//!
+//! HarfBuzz caches shape plans, and the GSUB/GPOS accelerators they run on,
+//! on the `hb_face_t`. Building a new face for every run threw all of that
+//! away, so UI text arriving as thousands of short runs per frame compiled
+//! the same plans over and over. [`FaceCache`] keeps one face per font and
+//! face index, so every run after the first shapes with HarfBuzz's cached
+//! plan for its script, direction, language, and features.
+//!
+//! Only fonts that share their bytes ([`FontRef::data_shared`]) are kept.
+//! Each face holds a clone of those bytes, so their address, which keys the
+//! cache, cannot be reused while the face lives.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use harfbuzz_rs::{Blob, Face, Shared};
+use parking_lot::RwLock;
+
+use typf_core::traits::FontRef;
+
+/// Faces kept before the cache starts over; UIs use a handful of fonts
+const MAX_FACES: usize = 64;
+
+/// Address and length of a font's shared bytes, and the face index
+type FaceKey = (usize, usize, u32);
+
+/// Shared HarfBuzz faces, one per font and face index
+#[derive(Default)]
+pub(crate) struct FaceCache {
+    faces: RwLock<HashMap<FaceKey, Shared<Face<'static>>>>,
+}
+
+impl FaceCache {
+    /// Face `index` of `font`, built on first use
+    ///
+    /// Returns `None` for fonts without shared bytes; the caller builds a
+    /// face over [`FontRef::data`] for that run instead.
+    pub(crate) fn get(&self, font: &dyn FontRef, index: u32) -> Option<Shared<Face<'static>>> {
+        let bytes: Arc<dyn AsRef<[u8]> + Send + Sync> = font.data_shared()?;
+        let data = (*bytes).as_ref();
+        let key = (data.as_ptr() as usize, data.len(), index);
+        if let Some(face) = self.faces.read().get(&key) {
+            return Some(face.clone());
+        }
+
+        let blob = Blob::with_bytes_owned(bytes, |bytes| (**bytes).as_ref());
+        let face: Shared<Face<'static>> = Face::new(blob, index).into();
+        let mut faces = self.faces.write();
+        if faces.len() >= MAX_FACES && !faces.contains_key(&key) {
+            faces.clear();
+        }
+        Some(faces.entry(key).or_insert(face).clone())
+    }
+
+    /// Number of faces held
+    pub(crate) fn len(&self) -> usize {
+        self.faces.read().len()
+    }
+
+    /// Drop every face, and with them HarfBuzz's cached plans
+    pub(crate) fn clear(&self) {
+        self.faces.write().clear();
+    }
+}
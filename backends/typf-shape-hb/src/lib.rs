@@ -6,15 +6,22 @@
 //! character does not map cleanly to one painted glyph. This crate delegates
 //! that work to HarfBuzz and translates Typf's neutral API into HarfBuzz calls.
 
+mod face_cache;
+
 use std::str::FromStr;
 use std::sync::Arc;
 
+use face_cache::FaceCache;
 use harfbuzz_rs::{Direction as HbDirection, Face, Feature, Font as HbFont, Tag, UnicodeBuffer};
-
 use typf_core::{
+    cache::TrimIdle,
+    cancel,
+    case::apply_case,
+    digits::substitute_digits,
     error::Result,
     traits::{FontRef, Shaper, Stage},
     types::{Direction, PositionedGlyph, ShapingResult},
+    whitespace::expand_whitespace,
     ShapingParams,
 };
 
@@ -23,27 +30,37 @@ pub use typf_core::shaping_cache::{CacheStats, ShapingCache, ShapingCacheKey, Sh
 /// Text shaper backed by HarfBuzz.
 ///
 /// It optionally caches shaping results so repeated requests for the same text,
-/// font, language, and feature set do not pay the shaping cost again.
+/// font, language, and feature set do not pay the shaping cost again. The
+/// HarfBuzz face of every font with shared bytes is always kept, so runs
+/// reuse the shape plans HarfBuzz caches on it.
 pub struct HarfBuzzShaper {
     cache: Option<SharedShapingCache>,
+    faces: FaceCache,
 }
 
 impl HarfBuzzShaper {
     /// Create a HarfBuzz shaper without an internal cache.
     pub fn new() -> Self {
-        Self { cache: None }
+        Self {
+            cache: None,
+            faces: FaceCache::default(),
+        }
     }
 
     /// Create a HarfBuzz shaper with its own default cache.
     pub fn with_cache() -> Self {
         Self {
             cache: Some(Arc::new(std::sync::RwLock::new(ShapingCache::new()))),
+            faces: FaceCache::default(),
         }
     }
 
     /// Create a HarfBuzz shaper that reuses an existing shared cache.
     pub fn with_shared_cache(cache: SharedShapingCache) -> Self {
-        Self { cache: Some(cache) }
+        Self {
+            cache: Some(cache),
+            faces: FaceCache::default(),
+        }
     }
 
     pub fn cache_stats(&self) -> Option<CacheStats> {
@@ -60,6 +77,11 @@ impl HarfBuzzShaper {
             .map(|c| c.hit_rate())
     }
 
+    /// Number of HarfBuzz faces held, each with its cached shape plans.
+    pub fn face_count(&self) -> usize {
+        self.faces.len()
+    }
+
     /// Convert Typf's direction enum into HarfBuzz's direction enum.
     fn to_hb_direction(dir: Direction) -> HbDirection {
         match dir {
@@ -69,33 +91,9 @@ impl HarfBuzzShaper {
             Direction::BottomToTop => HbDirection::Btt,
         }
     }
-}
 
-impl Default for HarfBuzzShaper {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl Stage for HarfBuzzShaper {
-    fn name(&self) -> &'static str {
-        "HarfBuzz"
-    }
-
-    fn process(
-        &self,
-        ctx: typf_core::context::PipelineContext,
-    ) -> Result<typf_core::context::PipelineContext> {
-        Ok(ctx)
-    }
-}
-
-impl Shaper for HarfBuzzShaper {
-    fn name(&self) -> &'static str {
-        "HarfBuzz"
-    }
-
-    fn shape(
+    /// Shape `text`; results are cached before the whitespace pass
+    fn shape_glyphs(
         &self,
         text: &str,
         font: Arc<dyn FontRef>,
@@ -122,7 +120,8 @@ impl Shaper for HarfBuzzShaper {
                 params.script.clone(),
                 params.features.clone(),
                 params.variations.clone(),
-            );
+            )
+            .with_face_index(font.face_index());
             if let Some(ref cache) = self.cache {
                 if let Ok(cache_guard) = cache.read() {
                     if let Some(result) = cache_guard.get(&key) {
@@ -161,7 +160,7 @@ impl Shaper for HarfBuzzShaper {
 
             if let Some(key) = cache_key {
                 if let Some(ref cache) = self.cache {
-                    if let Ok(cache_guard) = cache.write() {
+                    if let Ok(cache_guard) = cache.read() {
                         cache_guard.insert(key, result.clone());
                     }
                 }
@@ -170,8 +169,10 @@ impl Shaper for HarfBuzzShaper {
             return Ok(result);
         }
 
-        let face = Face::from_bytes(font_data, 0);
-        let mut hb_font = HbFont::new(face);
+        let mut hb_font = match self.faces.get(font.as_ref(), font.face_index()) {
+            Some(face) => HbFont::new(face),
+            None => HbFont::new(Face::from_bytes(font_data, font.face_index())),
+        };
 
         let scale = (params.size * 64.0) as i32;
         hb_font.set_scale(scale, scale);
@@ -208,39 +209,17 @@ impl Shaper for HarfBuzzShaper {
             }
         }
 
-        if let Some(ref script_str) = params.script {
-            if script_str.len() == 4 {
-                let bytes = script_str.as_bytes();
-                let tag = Tag::new(
-                    bytes[0] as char,
-                    bytes[1] as char,
-                    bytes[2] as char,
-                    bytes[3] as char,
-                );
-                buffer = buffer.set_script(tag);
-            }
+        if let Some(script) = params.script.as_deref().and_then(parse_tag) {
+            buffer = buffer.set_script(script);
         }
 
-        let hb_features: Vec<Feature> = params
+        // Features cover the whole buffer
+        let features: Vec<Feature> = params
             .features
             .iter()
-            .filter_map(|(name, value)| {
-                if name.len() == 4 {
-                    let bytes = name.as_bytes();
-                    let tag = Tag::new(
-                        bytes[0] as char,
-                        bytes[1] as char,
-                        bytes[2] as char,
-                        bytes[3] as char,
-                    );
-                    Some(Feature::new(tag, *value, 0..text.len()))
-                } else {
-                    None
-                }
-            })
+            .filter_map(|(name, value)| Some(Feature::new(parse_tag(name)?, *value, ..)))
             .collect();
-
-        let output = harfbuzz_rs::shape(&hb_font, buffer, &hb_features);
+        let output = harfbuzz_rs::shape(&hb_font, buffer, &features);
 
         let mut glyphs = Vec::new();
         let mut x_offset = 0.0;
@@ -272,7 +251,9 @@ impl Shaper for HarfBuzzShaper {
 
         if let Some(key) = cache_key {
             if let Some(ref cache) = self.cache {
-                if let Ok(cache_guard) = cache.write() {
+                // Inserts take `&self`; a shared guard keeps parallel runs from
+                // queueing behind each other
+                if let Ok(cache_guard) = cache.read() {
                     cache_guard.insert(key, result.clone());
                 }
             }
@@ -280,6 +261,78 @@ impl Shaper for HarfBuzzShaper {
 
         Ok(result)
     }
+}
+
+/// A four-letter OpenType tag, or `None` for anything else
+fn parse_tag(tag: &str) -> Option<Tag> {
+    match tag.as_bytes() {
+        &[a, b, c, d] => Some(Tag::new(a as char, b as char, c as char, d as char)),
+        _ => None,
+    }
+}
+
+impl Default for HarfBuzzShaper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stage for HarfBuzzShaper {
+    fn name(&self) -> &'static str {
+        "HarfBuzz"
+    }
+
+    fn process(
+        &self,
+        ctx: typf_core::context::PipelineContext,
+    ) -> Result<typf_core::context::PipelineContext> {
+        Ok(ctx)
+    }
+}
+
+impl Shaper for HarfBuzzShaper {
+    fn name(&self) -> &'static str {
+        "HarfBuzz"
+    }
+
+    fn shape(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        params: &ShapingParams,
+    ) -> Result<ShapingResult> {
+        cancel::check(params.cancel.as_ref())?;
+        // Case changes first, so small caps see the font's smcp forms
+        if let Some(cased) = apply_case(text, font.as_ref(), params) {
+            return cased.shape(params, |text, params| self.shape(text, font, params));
+        }
+        // Native digits go in before shaping, so the font's forms for them apply
+        if let Some(digits) = substitute_digits(text, params) {
+            return digits.shape(params, |text, params| self.shape(text, font, params));
+        }
+        let mut result = self.shape_glyphs(text, font.clone(), params)?;
+        // Shaping itself cannot stop midway; drop its result if time ran out
+        cancel::check(params.cancel.as_ref())?;
+        // Tabs reach their stops; unmapped spaces get their widths
+        expand_whitespace(text, &mut result, font.as_ref(), params);
+        Ok(result)
+    }
+
+    /// With the `parallel` feature, runs are shaped on the rayon pool.
+    /// Each run builds its own HarfBuzz font, and faces and cached results
+    /// are shared behind read locks, so threads rarely wait on each other.
+    #[cfg(feature = "parallel")]
+    fn shape_all(
+        &self,
+        runs: &[(&str, ShapingParams)],
+        font: Arc<dyn FontRef>,
+    ) -> Result<Vec<ShapingResult>> {
+        use rayon::prelude::*;
+
+        runs.par_iter()
+            .map(|(text, params)| self.shape(text, font.clone(), params))
+            .collect()
+    }
 
     fn supports_script(&self, _script: &str) -> bool {
         true
@@ -291,6 +344,14 @@ impl Shaper for HarfBuzzShaper {
                 *cache_guard = ShapingCache::new();
             }
         }
+        self.faces.clear();
+    }
+
+    fn trim_idle(&self, max_age: std::time::Duration) -> usize {
+        self.cache
+            .as_ref()
+            .and_then(|c| c.read().ok())
+            .map_or(0, |c| c.trim_idle(max_age))
     }
 }
 
@@ -331,6 +392,44 @@ mod tests {
         assert_eq!(result.advance_width, 0.0);
     }
 
+    #[test]
+    fn test_shape_all_when_runs_given_then_results_in_run_order() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../test-fonts/NotoSans-Regular.ttf");
+        let Ok(data) = std::fs::read(&path) else {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        };
+        let shaper = HarfBuzzShaper::with_cache();
+        let font: Arc<dyn FontRef> = Arc::new(TestFont { data });
+        let texts = ["Hi", "", "There", "office", "Hi"];
+        let runs: Vec<_> = texts
+            .iter()
+            .enumerate()
+            .map(|(i, &text)| {
+                let params = ShapingParams {
+                    size: 10.0 + i as f32,
+                    ..Default::default()
+                };
+                (text, params)
+            })
+            .collect();
+
+        let shaped = shaper.shape_all(&runs, font.clone()).unwrap();
+        assert_eq!(shaped.len(), texts.len());
+        for ((text, params), result) in runs.iter().zip(&shaped) {
+            let alone = HarfBuzzShaper::new()
+                .shape(text, font.clone(), params)
+                .unwrap();
+            assert_eq!(result.advance_height, params.size);
+            assert_eq!(result.advance_width, alone.advance_width);
+            let placed =
+                |r: &ShapingResult| r.glyphs.iter().map(|g| (g.id, g.x)).collect::<Vec<_>>();
+            assert_eq!(placed(result), placed(&alone), "{text:?}");
+            assert!(result.glyphs.iter().all(|glyph| glyph.id != 0));
+        }
+    }
+
     #[test]
     fn test_simple_text_no_font_data() {
         let shaper = HarfBuzzShaper::new();
@@ -708,6 +807,112 @@ mod tests {
         );
     }
 
+    /// A font that shares its bytes, so the shaper keeps its face
+    struct SharedFont {
+        data: Arc<Vec<u8>>,
+    }
+
+    impl FontRef for SharedFont {
+        fn data(&self) -> &[u8] {
+            &self.data
+        }
+
+        fn data_shared(&self) -> Option<Arc<dyn AsRef<[u8]> + Send + Sync>> {
+            Some(self.data.clone())
+        }
+
+        fn units_per_em(&self) -> u16 {
+            1000
+        }
+
+        fn glyph_id(&self, ch: char) -> Option<u32> {
+            Some(ch as u32)
+        }
+
+        fn advance_width(&self, _: u32) -> f32 {
+            500.0
+        }
+    }
+
+    #[test]
+    fn test_face_count_when_runs_share_a_font_then_one_face_kept() {
+        let shaper = HarfBuzzShaper::new();
+        // HarfBuzz shapes against an empty face when the bytes don't parse,
+        // which is enough to go through the face cache
+        let font = Arc::new(SharedFont {
+            data: Arc::new(b"not a font".to_vec()),
+        });
+        let params = ShapingParams {
+            features: vec![("liga".to_string(), 0)],
+            ..ShapingParams::default()
+        };
+
+        for text in ["One", "two", "three"] {
+            shaper.shape(text, font.clone(), &params).unwrap();
+        }
+        let arabic = ShapingParams {
+            direction: Direction::RightToLeft,
+            script: Some("arab".to_string()),
+            ..params.clone()
+        };
+        shaper.shape("سلام", font.clone(), &arabic).unwrap();
+        assert_eq!(shaper.face_count(), 1);
+
+        // Borrowed bytes have no stable identity, so their face is not kept
+        let borrowed = Arc::new(TestFont {
+            data: b"not a font".to_vec(),
+        });
+        shaper.shape("One", borrowed, &params).unwrap();
+        assert_eq!(shaper.face_count(), 1);
+
+        shaper.clear_cache();
+        assert_eq!(shaper.face_count(), 0);
+    }
+
+    #[test]
+    fn test_face_cache_when_real_font_reshaped_then_output_matches_fresh_face() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../test-fonts/NotoSans-Regular.ttf");
+        let Ok(data) = std::fs::read(&path) else {
+            eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+            return;
+        };
+        let shared = Arc::new(SharedFont {
+            data: Arc::new(data.clone()),
+        });
+        let params = ShapingParams {
+            size: 24.0,
+            features: vec![("kern".to_string(), 1), ("liga".to_string(), 1)],
+            ..ShapingParams::default()
+        };
+
+        let shaper = HarfBuzzShaper::new();
+        let first = shaper
+            .shape("AVATAR office", shared.clone(), &params)
+            .unwrap();
+        shaper.shape("Typography", shared.clone(), &params).unwrap();
+        let again = shaper
+            .shape("AVATAR office", shared.clone(), &params)
+            .unwrap();
+        assert_eq!(shaper.face_count(), 1, "one face serves every run");
+
+        // TestFont has no shared bytes, so this run builds a fresh face
+        let fresh = HarfBuzzShaper::new()
+            .shape("AVATAR office", Arc::new(TestFont { data }), &params)
+            .unwrap();
+        for result in [&first, &again] {
+            assert_eq!(result.glyphs.len(), fresh.glyphs.len());
+            assert_eq!(result.advance_width, fresh.advance_width);
+            for (got, want) in result.glyphs.iter().zip(&fresh.glyphs) {
+                assert_eq!(
+                    (got.id, got.x, got.cluster),
+                    (want.id, want.x, want.cluster)
+                );
+            }
+        }
+        assert!(fresh.glyphs.iter().all(|glyph| glyph.id != 0));
+    }
+
     #[test]
     fn test_shaper_without_cache() {
         let shaper = HarfBuzzShaper::new();
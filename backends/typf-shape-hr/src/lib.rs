@@ -16,9 +16,14 @@ use harfrust::{
 };
 
 use typf_core::{
+    cache::TrimIdle,
+    cancel,
+    case::apply_case,
+    digits::substitute_digits,
     error::Result,
     traits::{FontRef, Shaper, Stage},
     types::{Direction, PositionedGlyph, ShapingResult},
+    whitespace::expand_whitespace,
     ShapingParams,
 };
 
@@ -98,11 +103,17 @@ impl HarfrustShaper {
         font: Arc<dyn FontRef>,
         params: &ShapingParams,
     ) -> ShapingResult {
-        let mut glyphs = Vec::new();
+        let mut glyphs: Vec<PositionedGlyph> = Vec::new();
         let mut x_offset = 0.0;
+        let kerning = params.kerning();
 
         for ch in text.chars() {
             if let Some(glyph_id) = font.glyph_id(ch) {
+                // No GPOS here, so legacy kern pairs are all the kerning we get
+                if let Some(previous) = glyphs.last().filter(|_| kerning) {
+                    x_offset += font.kerning(previous.id, glyph_id) * params.size
+                        / font.units_per_em() as f32;
+                }
                 let advance = font.advance_width(glyph_id);
                 glyphs.push(PositionedGlyph {
                     id: glyph_id,
@@ -146,34 +157,9 @@ impl HarfrustShaper {
 
         (glyphs, x_offset)
     }
-}
-
-impl Default for HarfrustShaper {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl Stage for HarfrustShaper {
-    fn name(&self) -> &'static str {
-        "Harfrust"
-    }
-
-    fn process(
-        &self,
-        ctx: typf_core::context::PipelineContext,
-    ) -> Result<typf_core::context::PipelineContext> {
-        // Harfrust doesn't process pipeline context directly
-        Ok(ctx)
-    }
-}
-
-impl Shaper for HarfrustShaper {
-    fn name(&self) -> &'static str {
-        "Harfrust"
-    }
 
-    fn shape(
+    /// Shape `text`; results are cached before the whitespace pass
+    fn shape_glyphs(
         &self,
         text: &str,
         font: Arc<dyn FontRef>,
@@ -202,7 +188,8 @@ impl Shaper for HarfrustShaper {
                 params.script.clone(),
                 params.features.clone(),
                 params.variations.clone(),
-            );
+            )
+            .with_face_index(font.face_index());
             // Try to get from cache
             if let Some(ref cache) = self.cache {
                 if let Ok(cache_guard) = cache.read() {
@@ -233,7 +220,7 @@ impl Shaper for HarfrustShaper {
         }
 
         // Create harfrust FontRef from font data
-        let hr_font = match HrFontRef::new(font_data) {
+        let hr_font = match HrFontRef::from_index(font_data, font.face_index()) {
             Ok(f) => f,
             Err(_) => {
                 // Font data couldn't be parsed, fall back to basic shaping
@@ -334,6 +321,55 @@ impl Shaper for HarfrustShaper {
 
         Ok(result)
     }
+}
+
+impl Default for HarfrustShaper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stage for HarfrustShaper {
+    fn name(&self) -> &'static str {
+        "Harfrust"
+    }
+
+    fn process(
+        &self,
+        ctx: typf_core::context::PipelineContext,
+    ) -> Result<typf_core::context::PipelineContext> {
+        // Harfrust doesn't process pipeline context directly
+        Ok(ctx)
+    }
+}
+
+impl Shaper for HarfrustShaper {
+    fn name(&self) -> &'static str {
+        "Harfrust"
+    }
+
+    fn shape(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        params: &ShapingParams,
+    ) -> Result<ShapingResult> {
+        cancel::check(params.cancel.as_ref())?;
+        // Case changes first, so small caps see the font's smcp forms
+        if let Some(cased) = apply_case(text, font.as_ref(), params) {
+            return cased.shape(params, |text, params| self.shape(text, font, params));
+        }
+        // Native digits go in before shaping, so the font's forms for them apply
+        if let Some(digits) = substitute_digits(text, params) {
+            return digits.shape(params, |text, params| self.shape(text, font, params));
+        }
+        let mut result = self.shape_glyphs(text, font.clone(), params)?;
+        // Shaping itself cannot stop midway; drop its result if time ran out
+        cancel::check(params.cancel.as_ref())?;
+        // Tabs reach their stops; unmapped spaces get their widths
+        expand_whitespace(text, &mut result, font.as_ref(), params);
+        Ok(result)
+    }
 
     fn supports_script(&self, _script: &str) -> bool {
         // Harfrust knows how to shape every script that HarfBuzz supports
@@ -347,6 +383,13 @@ impl Shaper for HarfrustShaper {
             }
         }
     }
+
+    fn trim_idle(&self, max_age: std::time::Duration) -> usize {
+        self.cache
+            .as_ref()
+            .and_then(|c| c.read().ok())
+            .map_or(0, |c| c.trim_idle(max_age))
+    }
 }
 
 #[cfg(test)]
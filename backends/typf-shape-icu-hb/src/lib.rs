@@ -9,9 +9,14 @@ use harfbuzz_rs::{Direction as HbDirection, Face, Feature, Font as HbFont, Tag,
 use std::str::FromStr;
 use std::sync::Arc;
 use typf_core::{
+    cache::TrimIdle,
+    cancel,
+    case::apply_case,
+    digits::substitute_digits,
     error::Result,
     traits::{FontRef, Shaper, Stage},
     types::{Direction, PositionedGlyph, ShapingResult},
+    whitespace::expand_whitespace,
     ShapingParams,
 };
 use unicode_normalization::UnicodeNormalization;
@@ -80,40 +85,15 @@ impl IcuHarfBuzzShaper {
             Direction::BottomToTop => HbDirection::Btt,
         }
     }
-}
-
-impl Default for IcuHarfBuzzShaper {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl Stage for IcuHarfBuzzShaper {
-    fn name(&self) -> &'static str {
-        "ICU-HarfBuzz"
-    }
-
-    fn process(
-        &self,
-        ctx: typf_core::context::PipelineContext,
-    ) -> Result<typf_core::context::PipelineContext> {
-        // ICU-HB doesn't process pipeline context directly
-        Ok(ctx)
-    }
-}
-
-impl Shaper for IcuHarfBuzzShaper {
-    fn name(&self) -> &'static str {
-        "ICU-HarfBuzz"
-    }
 
-    fn shape(
+    /// Shape NFC-normalized text; results are cached before the whitespace pass
+    fn shape_normalized(
         &self,
-        text: &str,
+        normalized: &str,
         font: Arc<dyn FontRef>,
         params: &ShapingParams,
     ) -> Result<ShapingResult> {
-        if text.is_empty() {
+        if normalized.is_empty() {
             return Ok(ShapingResult {
                 glyphs: Vec::new(),
                 advance_width: 0.0,
@@ -122,16 +102,13 @@ impl Shaper for IcuHarfBuzzShaper {
             });
         }
 
-        // Step 1: Normalize the text (fix é vs e + ´ and similar issues)
-        let normalized: String = text.nfc().collect();
-
         // Step 2: Get the font data for HarfBuzz
         let font_data = font.data();
 
         // Check cache if enabled (use normalized text for key)
         let cache_key = if self.cache.is_some() {
             let key = ShapingCacheKey::new(
-                &normalized,
+                normalized,
                 Shaper::name(self),
                 font_data,
                 params.size,
@@ -139,7 +116,8 @@ impl Shaper for IcuHarfBuzzShaper {
                 params.script.clone(),
                 params.features.clone(),
                 params.variations.clone(),
-            );
+            )
+            .with_face_index(font.face_index());
             // Try to get from cache
             if let Some(ref cache) = self.cache {
                 if let Ok(cache_guard) = cache.read() {
@@ -192,7 +170,7 @@ impl Shaper for IcuHarfBuzzShaper {
         }
 
         // Step 3: Load the font into HarfBuzz
-        let hb_face = Face::from_bytes(font_data, 0);
+        let hb_face = Face::from_bytes(font_data, font.face_index());
         let mut hb_font = HbFont::new(hb_face);
 
         // HarfBuzz uses 26.6 fixed-point for font coordinates
@@ -201,7 +179,7 @@ impl Shaper for IcuHarfBuzzShaper {
 
         // Step 4: Set up HarfBuzz's text buffer with our normalized text
         let mut buffer = UnicodeBuffer::new()
-            .add_str(&normalized)
+            .add_str(normalized)
             .set_direction(Self::to_hb_direction(params.direction));
 
         // Tell HarfBuzz which language rules to use
@@ -293,6 +271,57 @@ impl Shaper for IcuHarfBuzzShaper {
 
         Ok(result)
     }
+}
+
+impl Default for IcuHarfBuzzShaper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stage for IcuHarfBuzzShaper {
+    fn name(&self) -> &'static str {
+        "ICU-HarfBuzz"
+    }
+
+    fn process(
+        &self,
+        ctx: typf_core::context::PipelineContext,
+    ) -> Result<typf_core::context::PipelineContext> {
+        // ICU-HB doesn't process pipeline context directly
+        Ok(ctx)
+    }
+}
+
+impl Shaper for IcuHarfBuzzShaper {
+    fn name(&self) -> &'static str {
+        "ICU-HarfBuzz"
+    }
+
+    fn shape(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        params: &ShapingParams,
+    ) -> Result<ShapingResult> {
+        // Case changes first, so small caps see the font's smcp forms
+        if let Some(cased) = apply_case(text, font.as_ref(), params) {
+            return cased.shape(params, |text, params| self.shape(text, font, params));
+        }
+        // Native digits go in before shaping, so the font's forms for them apply
+        if let Some(digits) = substitute_digits(text, params) {
+            return digits.shape(params, |text, params| self.shape(text, font, params));
+        }
+        // Step 1: Normalize the text (fix é vs e + ´ and similar issues)
+        let normalized: String = text.nfc().collect();
+        cancel::check(params.cancel.as_ref())?;
+        let mut result = self.shape_normalized(&normalized, font.clone(), params)?;
+        // Shaping itself cannot stop midway; drop its result if time ran out
+        cancel::check(params.cancel.as_ref())?;
+        // Tabs reach their stops; unmapped spaces get their widths
+        expand_whitespace(&normalized, &mut result, font.as_ref(), params);
+        Ok(result)
+    }
 
     fn clear_cache(&self) {
         if let Some(ref cache) = self.cache {
@@ -301,6 +330,13 @@ impl Shaper for IcuHarfBuzzShaper {
             }
         }
     }
+
+    fn trim_idle(&self, max_age: std::time::Duration) -> usize {
+        self.cache
+            .as_ref()
+            .and_then(|c| c.read().ok())
+            .map_or(0, |c| c.trim_idle(max_age))
+    }
 }
 
 #[cfg(test)]
@@ -1,15 +1,20 @@
 //! When you just need characters laid out: the simplest possible shaper
 //!
-//! This is our "just the basics" shaper. No ligatures, no kerning, no complex
+//! This is our "just the basics" shaper. No ligatures, no GPOS, no complex
 //! script support. Just takes your text, finds the matching glyphs, and lays
-//! them out left-to-right. Perfect for ASCII, debugging, or when you don't
-//! want the complexity of HarfBuzz.
+//! them out left-to-right, with the font's legacy `kern` pairs if it has any
+//! (turn them off with `("kern", 0)`). Perfect for ASCII, debugging, or when
+//! you don't want the complexity of HarfBuzz.
 
 use std::sync::Arc;
 use typf_core::{
+    cancel,
+    case::apply_case,
+    digits::substitute_digits,
     error::Result,
     traits::{FontRef, Shaper},
     types::{PositionedGlyph, ShapingResult},
+    whitespace::expand_whitespace,
     ShapingParams,
 };
 
@@ -41,16 +46,33 @@ impl Shaper for NoneShaper {
         params: &ShapingParams,
     ) -> Result<ShapingResult> {
         log::debug!("NoneShaper: Shaping {} chars", text.chars().count());
+        // Case changes first, so small caps see the font's smcp forms
+        if let Some(cased) = apply_case(text, font.as_ref(), params) {
+            return cased.shape(params, |text, params| self.shape(text, font, params));
+        }
+        // Native digits go in before shaping, so the font's forms for them apply
+        if let Some(digits) = substitute_digits(text, params) {
+            return digits.shape(params, |text, params| self.shape(text, font, params));
+        }
 
-        let mut glyphs = Vec::new();
+        let mut glyphs: Vec<PositionedGlyph> = Vec::new();
         let mut x_advance = 0.0;
         let scale = params.size / font.units_per_em() as f32;
+        let kerning = params.kerning();
 
         // One character becomes one glyph, positioned sequentially
         for (cluster, ch) in text.char_indices() {
+            cancel::check(params.cancel.as_ref())?;
             // Find which glyph draws this character
             let glyph_id = font.glyph_id(ch).unwrap_or(0); // Use .notdef (0) if not found
 
+            // Legacy kern pairs widen or tighten the previous glyph's advance
+            if let Some(previous) = glyphs.last_mut().filter(|_| kerning) {
+                let kern = font.kerning(previous.id, glyph_id) * scale;
+                previous.advance += kern;
+                x_advance += kern;
+            }
+
             // Get the glyph's width and scale it to our display size
             let advance_unscaled = font.advance_width(glyph_id);
             let advance = advance_unscaled * scale + params.letter_spacing;
@@ -68,12 +90,15 @@ impl Shaper for NoneShaper {
             x_advance += advance;
         }
 
-        Ok(ShapingResult {
+        let mut result = ShapingResult {
             glyphs,
             advance_width: x_advance,
             advance_height: params.size,
             direction: params.direction,
-        })
+        };
+        // Tabs reach their stops; unmapped spaces get their widths
+        expand_whitespace(text, &mut result, font.as_ref(), params);
+        Ok(result)
     }
 
     fn supports_script(&self, _script: &str) -> bool {
@@ -111,6 +136,15 @@ mod tests {
         fn advance_width(&self, _glyph_id: u32) -> f32 {
             500.0 // Fixed advance for simplicity
         }
+
+        fn kerning(&self, left: u32, right: u32) -> f32 {
+            // "AV" is the one kerned pair
+            if (left, right) == ('A' as u32, 'V' as u32) {
+                -100.0
+            } else {
+                0.0
+            }
+        }
     }
 
     #[test]
@@ -144,4 +178,96 @@ mod tests {
         assert_eq!(result.glyphs.len(), 0);
         assert_eq!(result.advance_width, 0.0);
     }
+
+    #[test]
+    fn test_cancelled_token_stops_shaping() {
+        let shaper = NoneShaper::new();
+        let font = Arc::new(MockFont);
+        let token = typf_core::CancelToken::new();
+        token.cancel();
+        let params = ShapingParams {
+            cancel: Some(token),
+            ..Default::default()
+        };
+
+        let result = shaper.shape("Hello", font, &params);
+
+        assert!(matches!(result, Err(typf_core::TypfError::Cancelled)));
+    }
+
+    #[test]
+    fn test_tab_advances_to_next_stop() {
+        let shaper = NoneShaper::new();
+        let font = Arc::new(MockFont);
+        let params = ShapingParams {
+            size: 16.0,
+            tab_stops: typf_core::TabStops::every(4.0),
+            ..Default::default()
+        };
+
+        // Every glyph is 8px wide, so stops fall every 32px
+        let result = shaper.shape("ab\tc", font, &params).unwrap();
+
+        assert_eq!(result.glyphs[2].id, ' ' as u32);
+        assert_eq!(result.glyphs[3].x, 32.0);
+        assert_eq!(result.advance_width, 40.0);
+    }
+
+    #[test]
+    fn test_kern_pairs_applied_unless_disabled() {
+        let shaper = NoneShaper::new();
+        let font = Arc::new(MockFont);
+        let params = ShapingParams {
+            size: 16.0,
+            ..Default::default()
+        };
+
+        // 500 units at 16px is 8px; the AV pair takes 1.6px off
+        let result = shaper.shape("AVA", font.clone(), &params).unwrap();
+        assert!((result.glyphs[0].advance - 6.4).abs() < 1e-4);
+        assert!((result.glyphs[1].x - 6.4).abs() < 1e-4);
+        assert!((result.advance_width - 22.4).abs() < 1e-4);
+
+        let params = ShapingParams {
+            features: vec![("kern".to_string(), 0)],
+            ..params
+        };
+        let result = shaper.shape("AVA", font, &params).unwrap();
+        assert_eq!(result.glyphs[1].x, 8.0);
+        assert_eq!(result.advance_width, 24.0);
+    }
+
+    #[test]
+    fn test_number_substitution_shapes_native_digits() {
+        let shaper = NoneShaper::new();
+        let font = Arc::new(MockFont);
+        let params = ShapingParams {
+            language: Some("ar".to_string()),
+            number_substitution: typf_core::NumberSubstitution::National,
+            ..Default::default()
+        };
+
+        // MockFont maps ASCII only, so native digits come out as .notdef
+        let result = shaper.shape("x12", font, &params).unwrap();
+        let ids: Vec<u32> = result.glyphs.iter().map(|g| g.id).collect();
+        let clusters: Vec<u32> = result.glyphs.iter().map(|g| g.cluster).collect();
+        assert_eq!(ids, ['x' as u32, 0, 0]);
+        assert_eq!(clusters, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_uppercase_case_shapes_capitals_with_original_clusters() {
+        let shaper = NoneShaper::new();
+        let font = Arc::new(MockFont);
+        let params = ShapingParams {
+            case: typf_core::TextCase::Uppercase,
+            ..Default::default()
+        };
+
+        let result = shaper.shape("aßb", font, &params).unwrap();
+        let ids: Vec<u32> = result.glyphs.iter().map(|g| g.id).collect();
+        let clusters: Vec<u32> = result.glyphs.iter().map(|g| g.cluster).collect();
+        assert_eq!(ids, ['A' as u32, 'S' as u32, 'S' as u32, 'B' as u32]);
+        assert_eq!(clusters, [0, 1, 1, 3]);
+    }
 }
@@ -0,0 +1,403 @@
+//! Pure Rust text shaping backend using rustybuzz
+//!
+//! Rustybuzz is the long-standing Rust port of HarfBuzz's shaping engine,
+//! built on `ttf-parser`. It has no C dependencies and no build script, so
+//! this backend is the easy choice for `wasm32` and static musl builds where
+//! linking the HarfBuzz C library is painful.
+//!
+//! Complex-script output (Arabic, Devanagari) matches the `typf-shape-hr`
+//! backend glyph for glyph; see `tests/parity.rs`.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use rustybuzz::{
+    ttf_parser::Tag, Direction as RbDirection, Face, Feature, GlyphBuffer, Language, Script,
+    UnicodeBuffer, Variation,
+};
+
+use typf_core::{
+    cache::TrimIdle,
+    cancel,
+    case::apply_case,
+    digits::substitute_digits,
+    error::Result,
+    traits::{FontRef, Shaper, Stage},
+    types::{Direction, PositionedGlyph, ShapingResult},
+    whitespace::expand_whitespace,
+    ShapingParams,
+};
+
+// Re-export shared shaping cache from typf-core
+pub use typf_core::shaping_cache::{CacheStats, ShapingCache, ShapingCacheKey, SharedShapingCache};
+
+/// Pure Rust text shaping powered by rustybuzz
+///
+/// Optionally caches shaping results to avoid expensive re-shaping of identical text.
+pub struct RustybuzzShaper {
+    /// Optional shaping cache for performance
+    cache: Option<SharedShapingCache>,
+}
+
+impl RustybuzzShaper {
+    /// Creates a new rustybuzz shaper ready to handle any script
+    pub fn new() -> Self {
+        Self { cache: None }
+    }
+
+    /// Creates a new rustybuzz shaper with caching enabled
+    ///
+    /// Uses default cache capacities (L1: 100, L2: 500 entries)
+    pub fn with_cache() -> Self {
+        Self {
+            cache: Some(Arc::new(std::sync::RwLock::new(ShapingCache::new()))),
+        }
+    }
+
+    /// Creates a new rustybuzz shaper with a custom cache
+    ///
+    /// Useful for sharing a cache across multiple shapers
+    pub fn with_shared_cache(cache: SharedShapingCache) -> Self {
+        Self { cache: Some(cache) }
+    }
+
+    /// Returns cache statistics if caching is enabled
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache
+            .as_ref()
+            .and_then(|c| c.read().ok())
+            .map(|c| c.stats())
+    }
+
+    /// Translates our direction enum to rustybuzz's format
+    fn to_rb_direction(dir: Direction) -> RbDirection {
+        match dir {
+            Direction::LeftToRight => RbDirection::LeftToRight,
+            Direction::RightToLeft => RbDirection::RightToLeft,
+            Direction::TopToBottom => RbDirection::TopToBottom,
+            Direction::BottomToTop => RbDirection::BottomToTop,
+        }
+    }
+
+    /// Parse a 4-character tag string into a rustybuzz Tag
+    fn parse_tag(tag_str: &str) -> Option<Tag> {
+        let bytes: [u8; 4] = tag_str.as_bytes().try_into().ok()?;
+        Some(Tag::from_bytes(&bytes))
+    }
+
+    /// Perform basic fallback shaping when font data is unavailable
+    fn fallback_shape(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        params: &ShapingParams,
+    ) -> ShapingResult {
+        let mut glyphs: Vec<PositionedGlyph> = Vec::new();
+        let mut x_offset = 0.0;
+        let kerning = params.kerning();
+
+        for ch in text.chars() {
+            if let Some(glyph_id) = font.glyph_id(ch) {
+                // No GPOS here, so legacy kern pairs are all the kerning we get
+                if let Some(previous) = glyphs.last().filter(|_| kerning) {
+                    x_offset += font.kerning(previous.id, glyph_id) * params.size
+                        / font.units_per_em() as f32;
+                }
+                let advance = font.advance_width(glyph_id);
+                glyphs.push(PositionedGlyph {
+                    id: glyph_id,
+                    x: x_offset,
+                    y: 0.0,
+                    advance,
+                    cluster: 0,
+                });
+                x_offset += advance * params.size / font.units_per_em() as f32;
+            }
+        }
+
+        ShapingResult {
+            glyphs,
+            advance_width: x_offset,
+            advance_height: params.size,
+            direction: params.direction,
+        }
+    }
+
+    /// Extract positioned glyphs from rustybuzz's GlyphBuffer
+    fn extract_glyphs(buffer: &GlyphBuffer, ppem: f32, upem: u16) -> (Vec<PositionedGlyph>, f32) {
+        let mut glyphs = Vec::with_capacity(buffer.len());
+        let mut x_offset = 0.0;
+        let scale = ppem / upem as f32;
+
+        for (info, pos) in buffer
+            .glyph_infos()
+            .iter()
+            .zip(buffer.glyph_positions().iter())
+        {
+            glyphs.push(PositionedGlyph {
+                id: info.glyph_id,
+                x: x_offset + (pos.x_offset as f32 * scale),
+                y: pos.y_offset as f32 * scale,
+                advance: pos.x_advance as f32 * scale,
+                cluster: info.cluster,
+            });
+
+            x_offset += pos.x_advance as f32 * scale;
+        }
+
+        (glyphs, x_offset)
+    }
+
+    /// Store a result in the cache, if there is one
+    fn remember(&self, key: Option<ShapingCacheKey>, result: &ShapingResult) {
+        if let (Some(key), Some(cache)) = (key, self.cache.as_ref()) {
+            if let Ok(cache_guard) = cache.write() {
+                cache_guard.insert(key, result.clone());
+            }
+        }
+    }
+
+    /// Shape `text`; results are cached before the whitespace pass
+    fn shape_glyphs(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        params: &ShapingParams,
+    ) -> Result<ShapingResult> {
+        if text.is_empty() {
+            return Ok(ShapingResult {
+                glyphs: Vec::new(),
+                advance_width: 0.0,
+                advance_height: params.size,
+                direction: params.direction,
+            });
+        }
+
+        let font_data = font.data();
+
+        let cache_key = if let Some(ref cache) = self.cache {
+            let key = ShapingCacheKey::new(
+                text,
+                Shaper::name(self),
+                font_data,
+                params.size,
+                params.language.clone(),
+                params.script.clone(),
+                params.features.clone(),
+                params.variations.clone(),
+            )
+            .with_face_index(font.face_index());
+            if let Ok(cache_guard) = cache.read() {
+                if let Some(result) = cache_guard.get(&key) {
+                    return Ok(result);
+                }
+            }
+            Some(key)
+        } else {
+            None
+        };
+
+        // No font data, or data rustybuzz can't parse: fall back to cmap lookups
+        let mut face = match Face::from_slice(font_data, font.face_index()) {
+            Some(face) => face,
+            None => {
+                if !font_data.is_empty() {
+                    log::warn!("rustybuzz could not parse font data; using fallback shaping");
+                }
+                let result = self.fallback_shape(text, font, params);
+                self.remember(cache_key, &result);
+                return Ok(result);
+            },
+        };
+
+        if !params.variations.is_empty() {
+            let variations: Vec<Variation> = params
+                .variations
+                .iter()
+                .filter_map(|(tag_str, value)| {
+                    Self::parse_tag(tag_str).map(|tag| Variation { tag, value: *value })
+                })
+                .collect();
+            face.set_variations(&variations);
+        }
+        face.set_points_per_em(Some(params.size));
+
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(text);
+        buffer.set_direction(Self::to_rb_direction(params.direction));
+
+        if let Some(ref lang) = params.language {
+            if let Ok(language) = Language::from_str(lang) {
+                buffer.set_language(language);
+            }
+        }
+
+        if let Some(ref script_str) = params.script {
+            if let Some(script) = Self::parse_tag(script_str).and_then(Script::from_iso15924_tag) {
+                buffer.set_script(script);
+            }
+        }
+
+        let features: Vec<Feature> = params
+            .features
+            .iter()
+            .filter_map(|(name, value)| {
+                Self::parse_tag(name).map(|tag| Feature::new(tag, *value, ..))
+            })
+            .collect();
+
+        let output = rustybuzz::shape(&face, &features, buffer);
+
+        let upem = font.units_per_em();
+        let (glyphs, advance_width) = Self::extract_glyphs(&output, params.size, upem);
+
+        let result = ShapingResult {
+            glyphs,
+            advance_width,
+            advance_height: params.size,
+            direction: params.direction,
+        };
+        self.remember(cache_key, &result);
+
+        Ok(result)
+    }
+}
+
+impl Default for RustybuzzShaper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stage for RustybuzzShaper {
+    fn name(&self) -> &'static str {
+        "Rustybuzz"
+    }
+
+    fn process(
+        &self,
+        ctx: typf_core::context::PipelineContext,
+    ) -> Result<typf_core::context::PipelineContext> {
+        // Rustybuzz doesn't process pipeline context directly
+        Ok(ctx)
+    }
+}
+
+impl Shaper for RustybuzzShaper {
+    fn name(&self) -> &'static str {
+        "Rustybuzz"
+    }
+
+    fn shape(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        params: &ShapingParams,
+    ) -> Result<ShapingResult> {
+        cancel::check(params.cancel.as_ref())?;
+        // Case changes first, so small caps see the font's smcp forms
+        if let Some(cased) = apply_case(text, font.as_ref(), params) {
+            return cased.shape(params, |text, params| self.shape(text, font, params));
+        }
+        // Native digits go in before shaping, so the font's forms for them apply
+        if let Some(digits) = substitute_digits(text, params) {
+            return digits.shape(params, |text, params| self.shape(text, font, params));
+        }
+        let mut result = self.shape_glyphs(text, font.clone(), params)?;
+        // Shaping itself cannot stop midway; drop its result if time ran out
+        cancel::check(params.cancel.as_ref())?;
+        // Tabs reach their stops; unmapped spaces get their widths
+        expand_whitespace(text, &mut result, font.as_ref(), params);
+        Ok(result)
+    }
+
+    fn supports_script(&self, _script: &str) -> bool {
+        // Rustybuzz implements every HarfBuzz complex shaper
+        true
+    }
+
+    fn clear_cache(&self) {
+        if let Some(ref cache) = self.cache {
+            if let Ok(mut cache_guard) = cache.write() {
+                *cache_guard = ShapingCache::new();
+            }
+        }
+    }
+
+    fn trim_idle(&self, max_age: std::time::Duration) -> usize {
+        self.cache
+            .as_ref()
+            .and_then(|c| c.read().ok())
+            .map_or(0, |c| c.trim_idle(max_age))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestFont {
+        data: Vec<u8>,
+    }
+
+    impl FontRef for TestFont {
+        fn data(&self) -> &[u8] {
+            &self.data
+        }
+
+        fn units_per_em(&self) -> u16 {
+            1000
+        }
+
+        fn glyph_id(&self, ch: char) -> Option<u32> {
+            Some(ch as u32)
+        }
+
+        fn advance_width(&self, _: u32) -> f32 {
+            500.0
+        }
+    }
+
+    #[test]
+    fn test_empty_text() {
+        let shaper = RustybuzzShaper::new();
+        let font = Arc::new(TestFont { data: vec![] });
+
+        let result = shaper.shape("", font, &ShapingParams::default()).unwrap();
+        assert!(result.glyphs.is_empty());
+        assert_eq!(result.advance_width, 0.0);
+    }
+
+    #[test]
+    fn test_simple_text_no_font_data() {
+        let shaper = RustybuzzShaper::new();
+        let font = Arc::new(TestFont { data: vec![] });
+
+        let result = shaper.shape("Hi", font, &ShapingParams::default()).unwrap();
+        assert_eq!(result.glyphs.len(), 2);
+        assert!(result.advance_width > 0.0);
+    }
+
+    #[test]
+    fn test_parse_tag() {
+        assert!(RustybuzzShaper::parse_tag("liga").is_some());
+        assert!(RustybuzzShaper::parse_tag("lig").is_none());
+        assert!(RustybuzzShaper::parse_tag("ligat").is_none());
+    }
+
+    #[test]
+    fn test_shaper_with_cache() {
+        let _guard = typf_core::cache_config::scoped_caching_enabled(true);
+
+        let shaper = RustybuzzShaper::with_cache();
+        let font = Arc::new(TestFont { data: vec![] });
+        let params = ShapingParams::default();
+
+        let first = shaper.shape("Hello", font.clone(), &params).unwrap();
+        let second = shaper.shape("Hello", font, &params).unwrap();
+        assert_eq!(first.advance_width, second.advance_width);
+
+        let stats = shaper.cache_stats().unwrap();
+        assert!(stats.hits >= 1);
+    }
+}
@@ -0,0 +1,27 @@
+//! Regenerates `include/typf.h` when the `header` feature is on.
+//!
+//! The checked-in header is what C users include; run
+//! `cargo build -p typf-capi --features header` after changing the ABI.
+
+fn main() {
+    #[cfg(feature = "header")]
+    generate_header();
+}
+
+#[cfg(feature = "header")]
+fn generate_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".into());
+    let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml"))
+        .unwrap_or_else(|e| panic!("cbindgen.toml: {e}"));
+
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=../../core/src/ffi.rs");
+
+    match cbindgen::generate_with_config(&crate_dir, config) {
+        Ok(bindings) => {
+            bindings.write_to_file(format!("{crate_dir}/include/typf.h"));
+        },
+        Err(e) => panic!("failed to generate typf.h: {e}"),
+    }
+}
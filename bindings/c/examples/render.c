@@ -0,0 +1,80 @@
+/*
+ * Shape and render a line of text through the Typf C API.
+ *
+ *   cargo build -p typf-capi --release
+ *   cc bindings/c/examples/render.c -Ibindings/c/include \
+ *      -Ltarget/release -ltypf_capi -o typf-render
+ *   ./typf-render font.ttf "Hello, Typf" out.pam
+ *
+ * this_file: bindings/c/examples/render.c
+ */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "typf.h"
+
+static int fail(const char *what) {
+    fprintf(stderr, "%s: %s\n", what, typf_last_error());
+    return 1;
+}
+
+int main(int argc, char **argv) {
+    if (argc != 4) {
+        fprintf(stderr, "usage: %s FONT TEXT OUT.pam\n", argv[0]);
+        return 2;
+    }
+    const char *text = argv[2];
+    size_t len = strlen(text);
+
+    TypfFont *font = NULL;
+    TypfShaper *shaper = NULL;
+    TypfRenderer *renderer = NULL;
+    if (typf_font_load_file(argv[1], 0, &font) != TYPF_STATUS_OK) return fail("font");
+    if (typf_shaper_new("harfrust", &shaper) != TYPF_STATUS_OK) return fail("shaper");
+    if (typf_renderer_new("opixa", &renderer) != TYPF_STATUS_OK) return fail("renderer");
+
+    TypfSegments segments;
+    if (typf_segment((const uint8_t *)text, len, NULL, &segments) != TYPF_STATUS_OK)
+        return fail("segment");
+    for (size_t i = 0; i < segments.count; i++) {
+        const TypfSegment *s = &segments.segments[i];
+        printf("run %zu: bytes %zu..%zu script %.4s %s\n", i, s->start, s->end,
+               (const char *)s->script,
+               s->direction == TYPF_DIRECTION_RIGHT_TO_LEFT ? "rtl" : "ltr");
+    }
+    typf_segments_free(&segments);
+
+    TypfShapeOptions shape = typf_shape_options_default();
+    shape.size = 48.0f;
+    TypfShapingResult shaped;
+    if (typf_shape(shaper, font, (const uint8_t *)text, len, &shape, &shaped) != TYPF_STATUS_OK)
+        return fail("shape");
+    for (uint32_t i = 0; i < shaped.glyph_count; i++) {
+        const TypfGlyph *g = &shaped.glyphs[i];
+        printf("glyph %u at (%.1f, %.1f) advance %.1f cluster %u\n", g->glyph_id, g->x, g->y,
+               g->advance, g->cluster);
+    }
+
+    TypfRenderOptions render = typf_render_options_default();
+    render.padding = 8;
+    TypfBitmap bitmap;
+    if (typf_render(renderer, &shaped, font, &render, &bitmap) != TYPF_STATUS_OK)
+        return fail("render");
+
+    FILE *out = fopen(argv[3], "wb");
+    if (out && bitmap.format == TYPF_BITMAP_FORMAT_RGBA8) {
+        fprintf(out, "P7\nWIDTH %u\nHEIGHT %u\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n",
+                bitmap.width, bitmap.height);
+        for (uint32_t y = 0; y < bitmap.height; y++)
+            fwrite(bitmap.data + (size_t)y * bitmap.stride, 4, bitmap.width, out);
+    }
+    if (out) fclose(out);
+
+    typf_bitmap_free(&bitmap);
+    typf_shaping_result_free(&shaped);
+    typf_renderer_free(renderer);
+    typf_shaper_free(shaper);
+    typf_font_free(font);
+    return 0;
+}
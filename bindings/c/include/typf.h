@@ -0,0 +1,383 @@
+/* Typf C API. SPDX-License-Identifier: Apache-2.0 */
+
+#ifndef TYPF_H
+#define TYPF_H
+
+/* Generated by cbindgen from bindings/c; do not edit by hand. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// Outcome of every fallible call
+typedef enum TypfStatus {
+  // The call succeeded and its outputs are filled in
+  TYPF_STATUS_OK = 0,
+  // A required pointer argument was null
+  TYPF_STATUS_NULL_POINTER = 1,
+  // An argument was malformed, e.g. text that is not UTF-8
+  TYPF_STATUS_INVALID_ARGUMENT = 2,
+  // The font could not be read or parsed
+  TYPF_STATUS_FONT_ERROR = 3,
+  // Shaping failed
+  TYPF_STATUS_SHAPING_ERROR = 4,
+  // Rendering failed
+  TYPF_STATUS_RENDER_ERROR = 5,
+  // The backend or output kind is not available in this build
+  TYPF_STATUS_UNSUPPORTED = 6,
+  // Typf panicked; the message is in `typf_last_error`
+  TYPF_STATUS_PANIC = 7,
+} TypfStatus;
+
+// Text direction as a C-compatible enum.
+//
+// Values match common conventions:
+// - 0: Left-to-right (Latin, Cyrillic)
+// - 1: Right-to-left (Arabic, Hebrew)
+// - 2: Top-to-bottom (Traditional Chinese, Japanese)
+// - 3: Bottom-to-top (rare)
+enum TypfDirection
+#if defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+  : uint8_t
+#endif // defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+ {
+  TYPF_DIRECTION_LEFT_TO_RIGHT = 0,
+  TYPF_DIRECTION_RIGHT_TO_LEFT = 1,
+  TYPF_DIRECTION_TOP_TO_BOTTOM = 2,
+  TYPF_DIRECTION_BOTTOM_TO_TOP = 3,
+};
+#ifndef __cplusplus
+#if __STDC_VERSION__ >= 202311L
+typedef enum TypfDirection TypfDirection;
+#else
+typedef uint8_t TypfDirection;
+#endif // __STDC_VERSION__ >= 202311L
+#endif // __cplusplus
+
+// Pixel layout of a [`TypfBitmap`]
+typedef enum TypfBitmapFormat {
+  // 4 bytes per pixel, red first
+  TYPF_BITMAP_FORMAT_RGBA8 = 0,
+  // 3 bytes per pixel, red first
+  TYPF_BITMAP_FORMAT_RGB8 = 1,
+  // 1 byte of coverage per pixel
+  TYPF_BITMAP_FORMAT_GRAY8 = 2,
+  // 1 bit per pixel, most significant bit first
+  TYPF_BITMAP_FORMAT_GRAY1 = 3,
+} TypfBitmapFormat;
+
+// A loaded font face. Opaque to C; release with `typf_font_free`.
+typedef struct TypfFont TypfFont;
+
+// A rendering backend. Opaque to C; release with `typf_renderer_free`.
+typedef struct TypfRenderer TypfRenderer;
+
+// A shaping backend. Opaque to C; release with `typf_shaper_free`.
+typedef struct TypfShaper TypfShaper;
+
+// A variable font axis setting such as `{"wght", 700.0f}`
+typedef struct TypfVariation {
+  // Four-byte axis tag
+  uint8_t tag[4];
+  // Axis value in user units
+  float value;
+} TypfVariation;
+
+// Rendering settings; start from `typf_render_options_default`
+typedef struct TypfRenderOptions {
+  // Text color as RGBA
+  uint8_t foreground[4];
+  // Canvas color as RGBA; alpha 0 leaves the canvas transparent
+  uint8_t background[4];
+  // Space around the text, in pixels
+  uint32_t padding;
+  // Smooth glyph edges
+  bool antialias;
+  // CPAL palette used for color glyphs
+  uint16_t color_palette;
+  // Axis settings; should match the ones used for shaping
+  const struct TypfVariation *variations;
+  // Number of entries in `variations`
+  size_t variation_count;
+} TypfRenderOptions;
+
+// C-ABI compatible positioned glyph.
+//
+// This struct matches the layout expected by external renderers like Cairo:
+// - `glyph_id`: Index into the font's glyph table
+// - `x`, `y`: Position in user space (typically pixels)
+// - `advance`: Horizontal advance width
+// - `cluster`: Cluster index for text segmentation (useful for cursor positioning)
+//
+// # Size and Alignment
+//
+// This struct is 20 bytes with 4-byte alignment on all platforms.
+typedef struct TypfGlyph {
+  // Glyph index in the font (maps to `cairo_glyph_t.index`)
+  uint32_t glyph_id;
+  // Horizontal position in user space
+  float x;
+  // Vertical position in user space
+  float y;
+  // Horizontal advance width
+  float advance;
+  // Cluster index (maps to original text position)
+  uint32_t cluster;
+} TypfGlyph;
+
+// C-ABI compatible shaping result.
+//
+// Contains a pointer to an array of positioned glyphs plus metadata.
+//
+// # Memory Ownership
+//
+// When returned from FFI functions, the caller owns the memory and must call
+// `typf_shaping_result_free()` to release it. The `glyphs` pointer is valid
+// until freed.
+//
+// # Null Safety
+//
+// - `glyphs` may be null if `glyph_count` is 0
+// - Always check `glyph_count` before dereferencing `glyphs`
+typedef struct TypfShapingResult {
+  // Pointer to array of positioned glyphs (owned)
+  struct TypfGlyph *glyphs;
+  // Number of glyphs in the array
+  uint32_t glyph_count;
+  // Total horizontal advance width
+  float advance_width;
+  // Total vertical advance height
+  float advance_height;
+  // Text direction
+  TypfDirection direction;
+  // Reserved for future use (padding)
+  uint8_t _reserved[3];
+} TypfShapingResult;
+
+// Pixels written by `typf_render`; release with `typf_bitmap_free`
+typedef struct TypfBitmap {
+  // Top row first; null when `len` is 0
+  uint8_t *data;
+  // Number of bytes in `data`
+  size_t len;
+  // Width in pixels
+  uint32_t width;
+  // Height in pixels
+  uint32_t height;
+  // Bytes from the start of one row to the next
+  uint32_t stride;
+  // Pixel layout
+  enum TypfBitmapFormat format;
+} TypfBitmap;
+
+// One run of text with a single script and direction
+typedef struct TypfSegment {
+  // Byte offset of the run in the text passed to `typf_segment`
+  size_t start;
+  // Byte offset one past the end of the run
+  size_t end;
+  // Resolved bidi direction
+  TypfDirection direction;
+  // ISO 15924 script tag such as `Latn` or `Arab`; `Zyyy` when unknown
+  uint8_t script[4];
+} TypfSegment;
+
+// Runs written by `typf_segment`; release with `typf_segments_free`
+typedef struct TypfSegments {
+  // Runs in logical order; null when `count` is 0
+  struct TypfSegment *segments;
+  // Number of runs
+  size_t count;
+} TypfSegments;
+
+// An OpenType feature setting such as `{"liga", 0}`
+typedef struct TypfFeature {
+  // Four-byte feature tag
+  uint8_t tag[4];
+  // 0 disables the feature, 1 enables it, larger values pick alternates
+  uint32_t value;
+} TypfFeature;
+
+// Shaping settings; start from `typf_shape_options_default`
+typedef struct TypfShapeOptions {
+  // Font size in pixels
+  float size;
+  // Run direction
+  TypfDirection direction;
+  // BCP 47 language tag, or null
+  const char *language;
+  // ISO 15924 script tag, or null to let the shaper guess
+  const char *script;
+  // Feature settings; may be null when `feature_count` is 0
+  const struct TypfFeature *features;
+  // Number of entries in `features`
+  size_t feature_count;
+  // Axis settings; may be null when `variation_count` is 0
+  const struct TypfVariation *variations;
+  // Number of entries in `variations`
+  size_t variation_count;
+  // Extra space added after every glyph, in pixels
+  float letter_spacing;
+} TypfShapeOptions;
+
+
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Message for the most recent failure on this thread, or null if none.
+//
+// Successful calls leave the message alone. The pointer stays valid until
+// the next failing Typf call on the same thread.
+const char *typf_last_error(void);
+
+// Typf version as a static NUL-terminated string, e.g. `"5.0.16"`
+const char *typf_version(void);
+
+// Loads face `face_index` of the font file at `path`.
+//
+// # Safety
+//
+// `path` must be a NUL-terminated string and `out` a valid pointer.
+enum TypfStatus typf_font_load_file(const char *path, uint32_t face_index, struct TypfFont **out);
+
+// Loads face `face_index` from font bytes in memory. The bytes are copied,
+// so the caller may free them as soon as this returns.
+//
+// # Safety
+//
+// `data` must point to `len` readable bytes and `out` must be valid.
+enum TypfStatus typf_font_load_data(const uint8_t *data,
+                                    size_t len,
+                                    uint32_t face_index,
+                                    struct TypfFont **out);
+
+// Design units per em of the font, or 0 for a null handle
+//
+// # Safety
+//
+// `font` must be null or a handle from `typf_font_load_*`.
+uint16_t typf_font_units_per_em(const struct TypfFont *font);
+
+// Releases a font. Null is ignored.
+//
+// Shaping results and bitmaps made with the font stay valid.
+//
+// # Safety
+//
+// `font` must be null or a handle from `typf_font_load_*` not yet freed.
+void typf_font_free(struct TypfFont *font);
+
+// Creates a renderer by backend name.
+//
+// Always available: `"opixa"`. Depending on build features: `"skia"` and
+// `"zeno"`.
+//
+// # Safety
+//
+// `name` must be NUL-terminated and `out` valid.
+enum TypfStatus typf_renderer_new(const char *name, struct TypfRenderer **out);
+
+// Releases a renderer. Null is ignored.
+//
+// # Safety
+//
+// `renderer` must be null or a handle from `typf_renderer_new` not yet
+// freed.
+void typf_renderer_free(struct TypfRenderer *renderer);
+
+// Default rendering settings: black text on transparent, antialiased
+struct TypfRenderOptions typf_render_options_default(void);
+
+// Renders a shaping result into a new bitmap.
+//
+// `options` may be null for the defaults. Release the bitmap with
+// `typf_bitmap_free`.
+//
+// # Safety
+//
+// `renderer` and `font` must be live handles, `shaped` must come from
+// `typf_shape`, `options` must be null or valid, and `out` must be valid.
+enum TypfStatus typf_render(const struct TypfRenderer *renderer,
+                            const struct TypfShapingResult *shaped,
+                            const struct TypfFont *font,
+                            const struct TypfRenderOptions *options,
+                            struct TypfBitmap *out);
+
+// Releases the pixels of a bitmap and resets it. Null is ignored.
+//
+// # Safety
+//
+// `bitmap` must be null or filled in by `typf_render` and not freed since.
+void typf_bitmap_free(struct TypfBitmap *bitmap);
+
+// Splits UTF-8 `text` into runs of one script and one bidi direction.
+//
+// Offsets refer to the text as given. `language` is an optional BCP 47 tag
+// and may be null.
+//
+// # Safety
+//
+// `text` must point to `len` bytes, `language` must be null or
+// NUL-terminated, and `out` must be valid.
+enum TypfStatus typf_segment(const uint8_t *text,
+                             size_t len,
+                             const char *language,
+                             struct TypfSegments *out);
+
+// Releases the runs and resets `segments` to empty. Null is ignored.
+//
+// # Safety
+//
+// `segments` must be null or filled in by `typf_segment`.
+void typf_segments_free(struct TypfSegments *segments);
+
+// Creates a shaper by backend name.
+//
+// Always available: `"none"`. Depending on build features: `"harfrust"`
+// (alias `"hr"`) and `"harfbuzz"` (alias `"hb"`).
+//
+// # Safety
+//
+// `name` must be NUL-terminated and `out` valid.
+enum TypfStatus typf_shaper_new(const char *name, struct TypfShaper **out);
+
+// Releases a shaper. Null is ignored.
+//
+// # Safety
+//
+// `shaper` must be null or a handle from `typf_shaper_new` not yet freed.
+void typf_shaper_free(struct TypfShaper *shaper);
+
+// Default shaping settings: 16 px, left to right, no features
+struct TypfShapeOptions typf_shape_options_default(void);
+
+// Shapes UTF-8 `text` with `font` and writes positioned glyphs to `out`.
+//
+// `options` may be null for the defaults. Release the result with
+// `typf_shaping_result_free`.
+//
+// # Safety
+//
+// `shaper` and `font` must be live handles, `text` must point to `len`
+// bytes, `options` must be null or valid, and `out` must be valid.
+enum TypfStatus typf_shape(const struct TypfShaper *shaper,
+                           const struct TypfFont *font,
+                           const uint8_t *text,
+                           size_t len,
+                           const struct TypfShapeOptions *options,
+                           struct TypfShapingResult *out);
+
+// Releases the glyphs of a shaping result and resets it. Null is ignored.
+//
+// # Safety
+//
+// `result` must be null or filled in by `typf_shape` and not freed since.
+void typf_shaping_result_free(struct TypfShapingResult *result);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TYPF_H */
@@ -0,0 +1,95 @@
+//! Font handles
+//
+// this_file: bindings/c/src/font.rs
+
+use std::ffi::c_char;
+use std::sync::Arc;
+
+use typf_core::traits::FontRef;
+use typf_fontdb::TypfFontFace;
+
+use crate::{c_str, guard, slice, Failure, TypfStatus};
+
+/// A loaded font face. Opaque to C; release with `typf_font_free`.
+pub struct TypfFont {
+    pub(crate) face: Arc<dyn FontRef>,
+}
+
+fn store(face: TypfFontFace, out: *mut *mut TypfFont) {
+    let font = Box::new(TypfFont {
+        face: Arc::new(face),
+    });
+    // SAFETY: callers checked `out` for null
+    unsafe { *out = Box::into_raw(font) };
+}
+
+/// Loads face `face_index` of the font file at `path`.
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn typf_font_load_file(
+    path: *const c_char,
+    face_index: u32,
+    out: *mut *mut TypfFont,
+) -> TypfStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(Failure::null("out"));
+        }
+        let path = c_str(path, "path")?;
+        store(TypfFontFace::from_file_index(path, face_index)?, out);
+        Ok(())
+    })
+}
+
+/// Loads face `face_index` from font bytes in memory. The bytes are copied,
+/// so the caller may free them as soon as this returns.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes and `out` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn typf_font_load_data(
+    data: *const u8,
+    len: usize,
+    face_index: u32,
+    out: *mut *mut TypfFont,
+) -> TypfStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(Failure::null("out"));
+        }
+        if data.is_null() {
+            return Err(Failure::null("data"));
+        }
+        let bytes = slice(data, len).to_vec();
+        store(TypfFontFace::from_data_index(bytes, face_index)?, out);
+        Ok(())
+    })
+}
+
+/// Design units per em of the font, or 0 for a null handle
+///
+/// # Safety
+///
+/// `font` must be null or a handle from `typf_font_load_*`.
+#[no_mangle]
+pub unsafe extern "C" fn typf_font_units_per_em(font: *const TypfFont) -> u16 {
+    font.as_ref().map_or(0, |font| font.face.units_per_em())
+}
+
+/// Releases a font. Null is ignored.
+///
+/// Shaping results and bitmaps made with the font stay valid.
+///
+/// # Safety
+///
+/// `font` must be null or a handle from `typf_font_load_*` not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn typf_font_free(font: *mut TypfFont) {
+    if !font.is_null() {
+        drop(Box::from_raw(font));
+    }
+}
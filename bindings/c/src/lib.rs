@@ -0,0 +1,219 @@
+//! C bindings for Typf
+//!
+//! Segments text into runs, shapes them, and renders them to bitmaps behind a
+//! plain C ABI, so C, C++, and Swift applications can embed Typf without a
+//! Rust toolchain of their own. The header lives in `include/typf.h`.
+//!
+//! # Conventions
+//!
+//! - Fonts, shapers, and renderers are opaque handles created by a `*_new` or
+//!   `*_load` function and released with the matching `*_free`. Handles are
+//!   thread-safe and may be shared between threads.
+//! - Results are written into caller-provided structs. Whatever Typf
+//!   allocated inside them is released with the matching `*_free`, which also
+//!   resets the struct so a second call is harmless.
+//! - Every fallible function returns a [`TypfStatus`]. On failure,
+//!   [`typf_last_error`] describes what went wrong on the calling thread.
+//! - Text is UTF-8 with an explicit byte length and need not be
+//!   NUL-terminated. Other strings are NUL-terminated.
+//! - Panics never cross the boundary; they surface as
+//!   [`TypfStatus::Panic`].
+//!
+//! ```c
+//! TypfFont *font = NULL;
+//! TypfShaper *shaper = NULL;
+//! typf_font_load_file("NotoSans-Regular.ttf", 0, &font);
+//! typf_shaper_new("harfrust", &shaper);
+//!
+//! TypfShapeOptions options = typf_shape_options_default();
+//! options.size = 32.0f;
+//! TypfShapingResult shaped;
+//! if (typf_shape(shaper, font, "Hello", 5, &options, &shaped) != TYPF_STATUS_OK) {
+//!     fprintf(stderr, "%s\n", typf_last_error());
+//! }
+//! typf_shaping_result_free(&shaped);
+//! typf_shaper_free(shaper);
+//! typf_font_free(font);
+//! ```
+//
+// this_file: bindings/c/src/lib.rs
+
+#![allow(unsafe_code)]
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use typf_core::TypfError;
+
+mod font;
+mod render;
+mod segment;
+mod shape;
+
+pub use font::*;
+pub use render::*;
+pub use segment::*;
+pub use shape::*;
+
+/// Outcome of every fallible call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypfStatus {
+    /// The call succeeded and its outputs are filled in
+    Ok = 0,
+    /// A required pointer argument was null
+    NullPointer = 1,
+    /// An argument was malformed, e.g. text that is not UTF-8
+    InvalidArgument = 2,
+    /// The font could not be read or parsed
+    FontError = 3,
+    /// Shaping failed
+    ShapingError = 4,
+    /// Rendering failed
+    RenderError = 5,
+    /// The backend or output kind is not available in this build
+    Unsupported = 6,
+    /// Typf panicked; the message is in `typf_last_error`
+    Panic = 7,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: &str) {
+    // Interior NULs would truncate the C string, so drop them
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = Some(message));
+}
+
+/// A failed call: the status to return and the message for `typf_last_error`
+pub(crate) struct Failure {
+    status: TypfStatus,
+    message: String,
+}
+
+impl Failure {
+    pub(crate) fn new(status: TypfStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn null(argument: &str) -> Self {
+        Self::new(TypfStatus::NullPointer, format!("`{argument}` is null"))
+    }
+}
+
+impl From<TypfError> for Failure {
+    fn from(error: TypfError) -> Self {
+        let status = match error.root() {
+            TypfError::FontLoad(_) | TypfError::Io(_) => TypfStatus::FontError,
+            TypfError::ShapingFailed(_) => TypfStatus::ShapingError,
+            TypfError::RenderingFailed(_) => TypfStatus::RenderError,
+            TypfError::NotImplemented(_)
+            | TypfError::FeatureNotCompiled(_)
+            | TypfError::UnsupportedBackendCombination(..) => TypfStatus::Unsupported,
+            TypfError::ConfigError(_) => TypfStatus::InvalidArgument,
+            _ => TypfStatus::RenderError,
+        };
+        Self::new(status, error.to_string())
+    }
+}
+
+/// Runs `body`, records any failure or panic, and returns the status
+pub(crate) fn guard(body: impl FnOnce() -> Result<(), Failure>) -> TypfStatus {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => TypfStatus::Ok,
+        Ok(Err(failure)) => {
+            set_last_error(&failure.message);
+            failure.status
+        },
+        Err(payload) => {
+            set_last_error(&format!("panic: {}", panic_message(payload.as_ref())));
+            TypfStatus::Panic
+        },
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "unknown"
+    }
+}
+
+/// Borrows a NUL-terminated UTF-8 string argument
+pub(crate) unsafe fn c_str<'a>(ptr: *const c_char, argument: &str) -> Result<&'a str, Failure> {
+    if ptr.is_null() {
+        return Err(Failure::null(argument));
+    }
+    CStr::from_ptr(ptr).to_str().map_err(|_| {
+        Failure::new(
+            TypfStatus::InvalidArgument,
+            format!("`{argument}` is not UTF-8"),
+        )
+    })
+}
+
+/// Borrows an optional NUL-terminated string; null means "not set"
+pub(crate) unsafe fn optional_c_str<'a>(
+    ptr: *const c_char,
+    argument: &str,
+) -> Result<Option<&'a str>, Failure> {
+    if ptr.is_null() {
+        Ok(None)
+    } else {
+        c_str(ptr, argument).map(Some)
+    }
+}
+
+/// Borrows `len` bytes of UTF-8 text; null is allowed when `len` is 0
+pub(crate) unsafe fn utf8<'a>(text: *const u8, len: usize) -> Result<&'a str, Failure> {
+    if len == 0 {
+        return Ok("");
+    }
+    if text.is_null() {
+        return Err(Failure::null("text"));
+    }
+    std::str::from_utf8(std::slice::from_raw_parts(text, len)).map_err(|e| {
+        Failure::new(
+            TypfStatus::InvalidArgument,
+            format!("text is not UTF-8: {e}"),
+        )
+    })
+}
+
+/// Borrows a caller array; null is allowed when `count` is 0
+pub(crate) unsafe fn slice<'a, T>(ptr: *const T, count: usize) -> &'a [T] {
+    if ptr.is_null() || count == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(ptr, count)
+    }
+}
+
+/// Message for the most recent failure on this thread, or null if none.
+///
+/// Successful calls leave the message alone. The pointer stays valid until
+/// the next failing Typf call on the same thread.
+#[no_mangle]
+pub extern "C" fn typf_last_error() -> *const c_char {
+    LAST_ERROR.with(|slot| {
+        slot.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Typf version as a static NUL-terminated string, e.g. `"5.0.16"`
+#[no_mangle]
+pub extern "C" fn typf_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
@@ -0,0 +1,248 @@
+//! Renderer handles and rasterization
+//
+// this_file: bindings/c/src/render.rs
+
+use std::ffi::c_char;
+use std::sync::Arc;
+
+use typf_core::{
+    ffi::ShapingResultC,
+    traits::Renderer,
+    types::{BitmapFormat, RenderOutput},
+    Color, RenderParams,
+};
+
+use crate::{c_str, guard, slice, variations, Failure, TypfFont, TypfStatus, TypfVariation};
+
+/// A rendering backend. Opaque to C; release with `typf_renderer_free`.
+pub struct TypfRenderer {
+    renderer: Arc<dyn Renderer>,
+}
+
+/// Pixel layout of a [`TypfBitmap`]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypfBitmapFormat {
+    /// 4 bytes per pixel, red first
+    Rgba8 = 0,
+    /// 3 bytes per pixel, red first
+    Rgb8 = 1,
+    /// 1 byte of coverage per pixel
+    Gray8 = 2,
+    /// 1 bit per pixel, most significant bit first
+    Gray1 = 3,
+}
+
+impl From<BitmapFormat> for TypfBitmapFormat {
+    fn from(format: BitmapFormat) -> Self {
+        match format {
+            BitmapFormat::Rgba8 => Self::Rgba8,
+            BitmapFormat::Rgb8 => Self::Rgb8,
+            BitmapFormat::Gray8 => Self::Gray8,
+            BitmapFormat::Gray1 => Self::Gray1,
+        }
+    }
+}
+
+/// Pixels written by `typf_render`; release with `typf_bitmap_free`
+#[repr(C)]
+#[derive(Debug)]
+pub struct TypfBitmap {
+    /// Top row first; null when `len` is 0
+    pub data: *mut u8,
+    /// Number of bytes in `data`
+    pub len: usize,
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Bytes from the start of one row to the next
+    pub stride: u32,
+    /// Pixel layout
+    pub format: TypfBitmapFormat,
+}
+
+impl TypfBitmap {
+    const EMPTY: Self = Self {
+        data: std::ptr::null_mut(),
+        len: 0,
+        width: 0,
+        height: 0,
+        stride: 0,
+        format: TypfBitmapFormat::Rgba8,
+    };
+}
+
+/// Rendering settings; start from `typf_render_options_default`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TypfRenderOptions {
+    /// Text color as RGBA
+    pub foreground: [u8; 4],
+    /// Canvas color as RGBA; alpha 0 leaves the canvas transparent
+    pub background: [u8; 4],
+    /// Space around the text, in pixels
+    pub padding: u32,
+    /// Smooth glyph edges
+    pub antialias: bool,
+    /// CPAL palette used for color glyphs
+    pub color_palette: u16,
+    /// Axis settings; should match the ones used for shaping
+    pub variations: *const TypfVariation,
+    /// Number of entries in `variations`
+    pub variation_count: usize,
+}
+
+/// Creates a renderer by backend name.
+///
+/// Always available: `"opixa"`. Depending on build features: `"skia"` and
+/// `"zeno"`.
+///
+/// # Safety
+///
+/// `name` must be NUL-terminated and `out` valid.
+#[no_mangle]
+pub unsafe extern "C" fn typf_renderer_new(
+    name: *const c_char,
+    out: *mut *mut TypfRenderer,
+) -> TypfStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(Failure::null("out"));
+        }
+        let renderer: Arc<dyn Renderer> = match c_str(name, "name")? {
+            "opixa" => Arc::new(typf_render_opixa::OpixaRenderer::new()),
+            #[cfg(feature = "render-skia")]
+            "skia" => Arc::new(typf_render_skia::SkiaRenderer::new()),
+            #[cfg(feature = "render-zeno")]
+            "zeno" => Arc::new(typf_render_zeno::ZenoRenderer::new()),
+            other => {
+                return Err(Failure::new(
+                    TypfStatus::Unsupported,
+                    format!("renderer `{other}` is not available in this build"),
+                ))
+            },
+        };
+        *out = Box::into_raw(Box::new(TypfRenderer { renderer }));
+        Ok(())
+    })
+}
+
+/// Releases a renderer. Null is ignored.
+///
+/// # Safety
+///
+/// `renderer` must be null or a handle from `typf_renderer_new` not yet
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn typf_renderer_free(renderer: *mut TypfRenderer) {
+    if !renderer.is_null() {
+        drop(Box::from_raw(renderer));
+    }
+}
+
+/// Default rendering settings: black text on transparent, antialiased
+#[no_mangle]
+pub extern "C" fn typf_render_options_default() -> TypfRenderOptions {
+    let defaults = RenderParams::default();
+    let fg = defaults.foreground;
+    TypfRenderOptions {
+        foreground: [fg.r, fg.g, fg.b, fg.a],
+        background: [0; 4],
+        padding: defaults.padding,
+        antialias: defaults.antialias,
+        color_palette: 0,
+        variations: std::ptr::null(),
+        variation_count: 0,
+    }
+}
+
+/// Renders a shaping result into a new bitmap.
+///
+/// `options` may be null for the defaults. Release the bitmap with
+/// `typf_bitmap_free`.
+///
+/// # Safety
+///
+/// `renderer` and `font` must be live handles, `shaped` must come from
+/// `typf_shape`, `options` must be null or valid, and `out` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn typf_render(
+    renderer: *const TypfRenderer,
+    shaped: *const ShapingResultC,
+    font: *const TypfFont,
+    options: *const TypfRenderOptions,
+    out: *mut TypfBitmap,
+) -> TypfStatus {
+    guard(|| {
+        let out = out.as_mut().ok_or_else(|| Failure::null("out"))?;
+        *out = TypfBitmap::EMPTY;
+        let renderer = renderer.as_ref().ok_or_else(|| Failure::null("renderer"))?;
+        let shaped = shaped.as_ref().ok_or_else(|| Failure::null("shaped"))?;
+        let font = font.as_ref().ok_or_else(|| Failure::null("font"))?;
+        let options = options
+            .as_ref()
+            .copied()
+            .unwrap_or_else(|| typf_render_options_default());
+
+        let [r, g, b, a] = options.foreground;
+        let background = options.background;
+        let params = RenderParams {
+            foreground: Color::rgba(r, g, b, a),
+            background: (background[3] > 0).then(|| {
+                let [r, g, b, a] = background;
+                Color::rgba(r, g, b, a)
+            }),
+            padding: options.padding,
+            antialias: options.antialias,
+            color_palette: options.color_palette.into(),
+            variations: variations(slice(options.variations, options.variation_count))?,
+            ..RenderParams::default()
+        };
+
+        let output = renderer
+            .renderer
+            .render(&shaped.to_rust(), font.face.clone(), &params)?;
+        let RenderOutput::Bitmap(bitmap) = output else {
+            return Err(Failure::new(
+                TypfStatus::Unsupported,
+                format!("{} did not produce a bitmap", renderer.renderer.name()),
+            ));
+        };
+
+        let stride = bitmap.stride;
+        let pixels = bitmap.data.into_boxed_slice();
+        *out = TypfBitmap {
+            len: pixels.len(),
+            data: if pixels.is_empty() {
+                std::ptr::null_mut()
+            } else {
+                Box::into_raw(pixels).cast()
+            },
+            width: bitmap.width,
+            height: bitmap.height,
+            stride: stride as u32,
+            format: bitmap.format.into(),
+        };
+        Ok(())
+    })
+}
+
+/// Releases the pixels of a bitmap and resets it. Null is ignored.
+///
+/// # Safety
+///
+/// `bitmap` must be null or filled in by `typf_render` and not freed since.
+#[no_mangle]
+pub unsafe extern "C" fn typf_bitmap_free(bitmap: *mut TypfBitmap) {
+    let Some(bitmap) = bitmap.as_mut() else {
+        return;
+    };
+    if !bitmap.data.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+            bitmap.data,
+            bitmap.len,
+        )));
+    }
+    *bitmap = TypfBitmap::EMPTY;
+}
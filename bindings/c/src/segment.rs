@@ -0,0 +1,118 @@
+//! Splitting text into script and direction runs
+//
+// this_file: bindings/c/src/segment.rs
+
+use std::ffi::c_char;
+
+use icu_properties::{props::Script, PropertyNamesShort};
+use typf_core::ffi::DirectionC;
+use typf_unicode::{UnicodeOptions, UnicodeProcessor};
+
+use crate::{guard, optional_c_str, utf8, Failure, TypfStatus};
+
+/// One run of text with a single script and direction
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TypfSegment {
+    /// Byte offset of the run in the text passed to `typf_segment`
+    pub start: usize,
+    /// Byte offset one past the end of the run
+    pub end: usize,
+    /// Resolved bidi direction
+    pub direction: DirectionC,
+    /// ISO 15924 script tag such as `Latn` or `Arab`; `Zyyy` when unknown
+    pub script: [u8; 4],
+}
+
+/// Runs written by `typf_segment`; release with `typf_segments_free`
+#[repr(C)]
+#[derive(Debug)]
+pub struct TypfSegments {
+    /// Runs in logical order; null when `count` is 0
+    pub segments: *mut TypfSegment,
+    /// Number of runs
+    pub count: usize,
+}
+
+impl TypfSegments {
+    const EMPTY: Self = Self {
+        segments: std::ptr::null_mut(),
+        count: 0,
+    };
+}
+
+fn script_tag(script: Script) -> [u8; 4] {
+    PropertyNamesShort::<Script>::new()
+        .get_locale_script(script)
+        .and_then(|tag| tag.as_str().as_bytes().try_into().ok())
+        .unwrap_or(*b"Zyyy")
+}
+
+/// Splits UTF-8 `text` into runs of one script and one bidi direction.
+///
+/// Offsets refer to the text as given. `language` is an optional BCP 47 tag
+/// and may be null.
+///
+/// # Safety
+///
+/// `text` must point to `len` bytes, `language` must be null or
+/// NUL-terminated, and `out` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn typf_segment(
+    text: *const u8,
+    len: usize,
+    language: *const c_char,
+    out: *mut TypfSegments,
+) -> TypfStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(Failure::null("out"));
+        }
+        *out = TypfSegments::EMPTY;
+        let text = utf8(text, len)?;
+        let options = UnicodeOptions {
+            detect_scripts: true,
+            // Normalizing would shift offsets away from the caller's text
+            normalize: false,
+            bidi_resolve: true,
+            language: optional_c_str(language, "language")?.map(str::to_string),
+        };
+        let runs = UnicodeProcessor::new().process(text, &options)?;
+
+        let segments: Box<[TypfSegment]> = runs
+            .iter()
+            .map(|run| TypfSegment {
+                start: run.start,
+                end: run.end,
+                direction: run.direction.into(),
+                script: script_tag(run.script),
+            })
+            .collect();
+        if !segments.is_empty() {
+            *out = TypfSegments {
+                count: segments.len(),
+                segments: Box::into_raw(segments).cast(),
+            };
+        }
+        Ok(())
+    })
+}
+
+/// Releases the runs and resets `segments` to empty. Null is ignored.
+///
+/// # Safety
+///
+/// `segments` must be null or filled in by `typf_segment`.
+#[no_mangle]
+pub unsafe extern "C" fn typf_segments_free(segments: *mut TypfSegments) {
+    let Some(segments) = segments.as_mut() else {
+        return;
+    };
+    if !segments.segments.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+            segments.segments,
+            segments.count,
+        )));
+    }
+    *segments = TypfSegments::EMPTY;
+}
@@ -0,0 +1,213 @@
+//! Shaper handles and shaping
+//
+// this_file: bindings/c/src/shape.rs
+
+use std::ffi::c_char;
+use std::sync::Arc;
+
+use typf_core::{
+    ffi::{DirectionC, ShapingResultC},
+    traits::Shaper,
+    types::{Direction, ShapingResult},
+    ShapingParams,
+};
+
+use crate::{c_str, guard, optional_c_str, slice, utf8, Failure, TypfFont, TypfStatus};
+
+/// A shaping backend. Opaque to C; release with `typf_shaper_free`.
+pub struct TypfShaper {
+    shaper: Arc<dyn Shaper>,
+}
+
+/// An OpenType feature setting such as `{"liga", 0}`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypfFeature {
+    /// Four-byte feature tag
+    pub tag: [u8; 4],
+    /// 0 disables the feature, 1 enables it, larger values pick alternates
+    pub value: u32,
+}
+
+/// A variable font axis setting such as `{"wght", 700.0f}`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TypfVariation {
+    /// Four-byte axis tag
+    pub tag: [u8; 4],
+    /// Axis value in user units
+    pub value: f32,
+}
+
+/// Shaping settings; start from `typf_shape_options_default`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TypfShapeOptions {
+    /// Font size in pixels
+    pub size: f32,
+    /// Run direction
+    pub direction: DirectionC,
+    /// BCP 47 language tag, or null
+    pub language: *const c_char,
+    /// ISO 15924 script tag, or null to let the shaper guess
+    pub script: *const c_char,
+    /// Feature settings; may be null when `feature_count` is 0
+    pub features: *const TypfFeature,
+    /// Number of entries in `features`
+    pub feature_count: usize,
+    /// Axis settings; may be null when `variation_count` is 0
+    pub variations: *const TypfVariation,
+    /// Number of entries in `variations`
+    pub variation_count: usize,
+    /// Extra space added after every glyph, in pixels
+    pub letter_spacing: f32,
+}
+
+pub(crate) fn tag_string(tag: [u8; 4]) -> Result<String, Failure> {
+    if tag.iter().all(|b| (0x20..0x7f).contains(b)) {
+        Ok(tag.iter().map(|&b| b as char).collect())
+    } else {
+        Err(Failure::new(
+            TypfStatus::InvalidArgument,
+            format!("tag {tag:?} is not printable ASCII"),
+        ))
+    }
+}
+
+pub(crate) fn variations(settings: &[TypfVariation]) -> Result<Vec<(String, f32)>, Failure> {
+    settings
+        .iter()
+        .map(|v| Ok((tag_string(v.tag)?, v.value)))
+        .collect()
+}
+
+/// Creates a shaper by backend name.
+///
+/// Always available: `"none"`. Depending on build features: `"harfrust"`
+/// (alias `"hr"`) and `"harfbuzz"` (alias `"hb"`).
+///
+/// # Safety
+///
+/// `name` must be NUL-terminated and `out` valid.
+#[no_mangle]
+pub unsafe extern "C" fn typf_shaper_new(
+    name: *const c_char,
+    out: *mut *mut TypfShaper,
+) -> TypfStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(Failure::null("out"));
+        }
+        let shaper: Arc<dyn Shaper> = match c_str(name, "name")? {
+            "none" => Arc::new(typf_shape_none::NoneShaper::new()),
+            #[cfg(feature = "shaping-hr")]
+            "harfrust" | "hr" => Arc::new(typf_shape_hr::HarfrustShaper::new()),
+            #[cfg(feature = "shaping-hb")]
+            "harfbuzz" | "hb" => Arc::new(typf_shape_hb::HarfBuzzShaper::new()),
+            other => {
+                return Err(Failure::new(
+                    TypfStatus::Unsupported,
+                    format!("shaper `{other}` is not available in this build"),
+                ))
+            },
+        };
+        *out = Box::into_raw(Box::new(TypfShaper { shaper }));
+        Ok(())
+    })
+}
+
+/// Releases a shaper. Null is ignored.
+///
+/// # Safety
+///
+/// `shaper` must be null or a handle from `typf_shaper_new` not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn typf_shaper_free(shaper: *mut TypfShaper) {
+    if !shaper.is_null() {
+        drop(Box::from_raw(shaper));
+    }
+}
+
+/// Default shaping settings: 16 px, left to right, no features
+#[no_mangle]
+pub extern "C" fn typf_shape_options_default() -> TypfShapeOptions {
+    let defaults = ShapingParams::default();
+    TypfShapeOptions {
+        size: defaults.size,
+        direction: defaults.direction.into(),
+        language: std::ptr::null(),
+        script: std::ptr::null(),
+        features: std::ptr::null(),
+        feature_count: 0,
+        variations: std::ptr::null(),
+        variation_count: 0,
+        letter_spacing: defaults.letter_spacing,
+    }
+}
+
+/// Shapes UTF-8 `text` with `font` and writes positioned glyphs to `out`.
+///
+/// `options` may be null for the defaults. Release the result with
+/// `typf_shaping_result_free`.
+///
+/// # Safety
+///
+/// `shaper` and `font` must be live handles, `text` must point to `len`
+/// bytes, `options` must be null or valid, and `out` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn typf_shape(
+    shaper: *const TypfShaper,
+    font: *const TypfFont,
+    text: *const u8,
+    len: usize,
+    options: *const TypfShapeOptions,
+    out: *mut ShapingResultC,
+) -> TypfStatus {
+    guard(|| {
+        let out = out.as_mut().ok_or_else(|| Failure::null("out"))?;
+        // Leave a freeable empty result behind if anything below fails
+        *out = ShapingResultC::from_rust(&ShapingResult {
+            glyphs: Vec::new(),
+            advance_width: 0.0,
+            advance_height: 0.0,
+            direction: Direction::LeftToRight,
+        });
+        let shaper = shaper.as_ref().ok_or_else(|| Failure::null("shaper"))?;
+        let font = font.as_ref().ok_or_else(|| Failure::null("font"))?;
+        let text = utf8(text, len)?;
+        let options = options
+            .as_ref()
+            .copied()
+            .unwrap_or_else(|| typf_shape_options_default());
+
+        let params = ShapingParams {
+            size: options.size,
+            direction: options.direction.into(),
+            language: optional_c_str(options.language, "language")?.map(str::to_string),
+            script: optional_c_str(options.script, "script")?.map(str::to_string),
+            features: slice(options.features, options.feature_count)
+                .iter()
+                .map(|f| Ok((tag_string(f.tag)?, f.value)))
+                .collect::<Result<_, Failure>>()?,
+            variations: variations(slice(options.variations, options.variation_count))?,
+            letter_spacing: options.letter_spacing,
+            ..ShapingParams::default()
+        };
+
+        let shaped = shaper.shaper.shape(text, font.face.clone(), &params)?;
+        *out = ShapingResultC::from_rust(&shaped);
+        Ok(())
+    })
+}
+
+/// Releases the glyphs of a shaping result and resets it. Null is ignored.
+///
+/// # Safety
+///
+/// `result` must be null or filled in by `typf_shape` and not freed since.
+#[no_mangle]
+pub unsafe extern "C" fn typf_shaping_result_free(result: *mut ShapingResultC) {
+    if let Some(result) = result.as_mut() {
+        result.free();
+    }
+}
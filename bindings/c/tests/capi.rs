@@ -0,0 +1,169 @@
+//! Drives the C ABI the way a C caller would
+//
+// this_file: bindings/c/tests/capi.rs
+
+#![allow(clippy::unwrap_used)]
+
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use typf_capi::*;
+use typf_core::ffi::{DirectionC, ShapingResultC};
+
+fn test_font_path() -> Option<CString> {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../test-fonts/NotoSans-Regular.ttf"
+    );
+    if std::path::Path::new(path).exists() {
+        CString::new(path).ok()
+    } else {
+        eprintln!("skipping: {path} not found");
+        None
+    }
+}
+
+fn last_error() -> String {
+    let message = typf_last_error();
+    assert!(!message.is_null());
+    unsafe { CStr::from_ptr(message) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn version_matches_crate() {
+    let version = unsafe { CStr::from_ptr(typf_version()) };
+    assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn segment_splits_scripts_and_directions() {
+    let text = "abc שלום";
+    let mut segments = TypfSegments {
+        segments: ptr::null_mut(),
+        count: 0,
+    };
+    let status = unsafe { typf_segment(text.as_ptr(), text.len(), ptr::null(), &mut segments) };
+    assert_eq!(status, TypfStatus::Ok);
+
+    let runs = unsafe { std::slice::from_raw_parts(segments.segments, segments.count) };
+    let last = runs.last().unwrap();
+    assert_eq!(runs[0].start, 0);
+    assert_eq!(&runs[0].script, b"Latn");
+    assert_eq!(last.end, text.len());
+    assert_eq!(&last.script, b"Hebr");
+    assert_eq!(last.direction, DirectionC::RightToLeft);
+
+    unsafe { typf_segments_free(&mut segments) };
+    assert!(segments.segments.is_null());
+    assert_eq!(segments.count, 0);
+    // Freeing twice is harmless
+    unsafe { typf_segments_free(&mut segments) };
+}
+
+#[test]
+fn shape_and_render_round_trip() {
+    let Some(path) = test_font_path() else {
+        return;
+    };
+    unsafe {
+        let mut font = ptr::null_mut();
+        assert_eq!(
+            typf_font_load_file(path.as_ptr(), 0, &mut font),
+            TypfStatus::Ok
+        );
+        assert!(typf_font_units_per_em(font) > 0);
+
+        let mut shaper = ptr::null_mut();
+        let name = CString::new("none").unwrap();
+        assert_eq!(typf_shaper_new(name.as_ptr(), &mut shaper), TypfStatus::Ok);
+
+        let mut options = typf_shape_options_default();
+        options.size = 32.0;
+        let text = "Typf";
+        let mut shaped: ShapingResultC = std::mem::zeroed();
+        let status = typf_shape(
+            shaper,
+            font,
+            text.as_ptr(),
+            text.len(),
+            &options,
+            &mut shaped,
+        );
+        assert_eq!(status, TypfStatus::Ok, "{}", last_error());
+        assert_eq!(shaped.glyph_count, 4);
+        assert_eq!(shaped.advance_height, 32.0);
+
+        let mut renderer = ptr::null_mut();
+        let name = CString::new("opixa").unwrap();
+        assert_eq!(
+            typf_renderer_new(name.as_ptr(), &mut renderer),
+            TypfStatus::Ok
+        );
+
+        let mut bitmap: TypfBitmap = std::mem::zeroed();
+        let status = typf_render(renderer, &shaped, font, ptr::null(), &mut bitmap);
+        assert_eq!(status, TypfStatus::Ok, "{}", last_error());
+        assert!(bitmap.width > 0 && bitmap.height > 0);
+        assert_eq!(bitmap.len, bitmap.stride as usize * bitmap.height as usize);
+        let pixels = std::slice::from_raw_parts(bitmap.data, bitmap.len);
+        assert!(pixels.iter().any(|&b| b != 0), "text left no ink");
+
+        typf_bitmap_free(&mut bitmap);
+        assert!(bitmap.data.is_null());
+        typf_shaping_result_free(&mut shaped);
+        assert!(shaped.glyphs.is_null());
+        typf_renderer_free(renderer);
+        typf_shaper_free(shaper);
+        typf_font_free(font);
+    }
+}
+
+#[test]
+fn failures_report_status_and_message() {
+    unsafe {
+        let mut shaper = ptr::null_mut();
+        let name = CString::new("no-such-shaper").unwrap();
+        assert_eq!(
+            typf_shaper_new(name.as_ptr(), &mut shaper),
+            TypfStatus::Unsupported
+        );
+        assert!(shaper.is_null());
+        assert!(last_error().contains("no-such-shaper"));
+
+        let mut font = ptr::null_mut();
+        let garbage = [0u8; 16];
+        assert_eq!(
+            typf_font_load_data(garbage.as_ptr(), garbage.len(), 0, &mut font),
+            TypfStatus::FontError
+        );
+        assert_eq!(
+            typf_font_load_file(ptr::null(), 0, &mut font),
+            TypfStatus::NullPointer
+        );
+
+        let bad_utf8 = [0xffu8, 0xfe];
+        let mut segments = TypfSegments {
+            segments: ptr::null_mut(),
+            count: 0,
+        };
+        assert_eq!(
+            typf_segment(bad_utf8.as_ptr(), 2, ptr::null(), &mut segments),
+            TypfStatus::InvalidArgument
+        );
+
+        // A failed shape still leaves a result that can be freed
+        let mut shaped: ShapingResultC = std::mem::zeroed();
+        let status = typf_shape(
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            0,
+            ptr::null(),
+            &mut shaped,
+        );
+        assert_eq!(status, TypfStatus::NullPointer);
+        typf_shaping_result_free(&mut shaped);
+    }
+}
@@ -20,6 +20,8 @@ result = typf.render_text("Hello, World!", "font.ttf", size=48)
 
 # Import the compiled Rust extension that brings the power
 from .typf import (
+    Font,           # Fonts loaded once and reused across calls
+    FontDatabase,   # Font collections with per-file caching
     FontInfo,       # Font inspection and metadata
     Typf,          # Main rendering pipeline
     __version__,   # Version information
@@ -34,13 +36,22 @@ except ImportError:
     TypfLinra = None
     __linra_available__ = False
 
+# NumPy conversion exists when built with the numpy-interop feature
+try:
+    from .typf import to_numpy
+except ImportError:
+    to_numpy = None
+
 # What we expose to the Python world
 __all__ = [
     "Typf",
     "TypfLinra",
+    "Font",
+    "FontDatabase",
     "FontInfo",
     "render_simple",
     "export_image",
+    "to_numpy",
     "__version__",
     "__linra_available__",
 ]
@@ -6,20 +6,24 @@
 
 #![allow(clippy::useless_conversion)]
 
-use pyo3::exceptions::{PyIOError, PyRuntimeError, PyValueError};
+use pyo3::exceptions::{PyIOError, PyRuntimeError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::{PyBytes, PyDict};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 #[cfg(feature = "linra")]
 use typf_core::linra::{LinraRenderParams, LinraRenderer};
+use typf_core::traits::FontRef as TypfFontRef;
 use typf_core::{
     traits::{Exporter, Renderer, Shaper},
-    types::{BitmapData, Direction, RenderOutput},
+    types::{
+        BitmapData, BitmapFormat, Direction, FontStretch, FontStyle, PixelLayout, RenderOutput,
+    },
     Color, RenderParams, ShapingParams,
 };
 use typf_export::PnmExporter;
-use typf_fontdb::TypfFontFace;
+use typf_fontdb::{EmbeddingLevel, TypfFontFace};
 use typf_unicode::{UnicodeOptions, UnicodeProcessor};
 
 /// Workspace version (injected at build time)
@@ -71,7 +75,10 @@ fn parse_direction(dir_str: &str, text: &str, language: Option<&str>) -> PyResul
 }
 
 /// Load font with optional TTC index
-fn load_font(font_path: &str, face_index: u32) -> PyResult<Arc<dyn typf_core::traits::FontRef>> {
+fn load_font(
+    font_path: impl AsRef<Path>,
+    face_index: u32,
+) -> PyResult<Arc<dyn typf_core::traits::FontRef>> {
     let font = if face_index == 0 {
         TypfFontFace::from_file(font_path)
     } else {
@@ -82,6 +89,50 @@ fn load_font(font_path: &str, face_index: u32) -> PyResult<Arc<dyn typf_core::tr
     Ok(Arc::new(font) as Arc<dyn typf_core::traits::FontRef>)
 }
 
+/// Accept either a loaded `Font` or a path (str or os.PathLike)
+///
+/// `face_index` only applies to paths; a `Font` already knows its face.
+fn resolve_font(
+    font: &Bound<'_, PyAny>,
+    face_index: u32,
+) -> PyResult<Arc<dyn typf_core::traits::FontRef>> {
+    if let Ok(font) = font.downcast::<Font>() {
+        return Ok(font.get().face.clone());
+    }
+    let path: PathBuf = font
+        .extract()
+        .map_err(|_| PyTypeError::new_err("font must be a typf.Font or a path to a font file"))?;
+    load_font(path, face_index)
+}
+
+/// Package a bitmap as a dict with width, height, format, shape, and data
+///
+/// `shape` is `(height, width, channels)` for byte-per-channel formats, so
+/// `numpy.frombuffer(d["data"], numpy.uint8).reshape(d["shape"])` gives an
+/// image array without copying. It is None for 1-bit bitmaps.
+fn bitmap_to_dict(py: Python, bitmap: &BitmapData) -> PyResult<PyObject> {
+    let result = PyDict::new_bound(py);
+    result.set_item("width", bitmap.width)?;
+    result.set_item("height", bitmap.height)?;
+    result.set_item("format", format!("{:?}", bitmap.format))?;
+    result.set_item(
+        "shape",
+        bitmap_channels(bitmap.format)
+            .map(|channels| (bitmap.height as usize, bitmap.width as usize, channels)),
+    )?;
+    result.set_item("data", PyBytes::new_bound(py, &bitmap.to_packed().data))?;
+    Ok(result.into())
+}
+
+fn bitmap_channels(format: BitmapFormat) -> Option<usize> {
+    match format {
+        BitmapFormat::Rgba8 => Some(4),
+        BitmapFormat::Rgb8 => Some(3),
+        BitmapFormat::Gray8 => Some(1),
+        BitmapFormat::Gray1 => None,
+    }
+}
+
 // Note: Skia and Zeno renderers not yet available in workspace
 
 /// The main Typf interface that Python developers will love
@@ -158,7 +209,7 @@ impl Typf {
     ///
     /// Args:
     ///     text: The text to render
-    ///     font_path: Path to the font file
+    ///     font_path: Path to the font file, or a Font from FontDatabase
     ///     size: Font size in pixels (default: 16.0)
     ///     color: Foreground color as (r, g, b, a) tuple (default: black)
     ///     background: Background color as (r, g, b, a) tuple (default: transparent)
@@ -174,7 +225,7 @@ impl Typf {
         &self,
         py: Python,
         text: &str,
-        font_path: &str,
+        font_path: &Bound<'_, PyAny>,
         size: f32,
         color: Option<(u8, u8, u8, u8)>,
         background: Option<(u8, u8, u8, u8)>,
@@ -185,7 +236,7 @@ impl Typf {
         face_index: u32,
     ) -> PyResult<PyObject> {
         // Load font with optional TTC index
-        let font_arc = load_font(font_path, face_index)?;
+        let font_arc = resolve_font(font_path, face_index)?;
 
         let mut variation_vec: Vec<(String, f32)> =
             variations.unwrap_or_default().into_iter().collect();
@@ -230,14 +281,7 @@ impl Typf {
 
         // Package the result for Python consumption
         match rendered {
-            RenderOutput::Bitmap(bitmap) => {
-                let result = PyDict::new_bound(py);
-                result.set_item("width", bitmap.width)?;
-                result.set_item("height", bitmap.height)?;
-                result.set_item("format", format!("{:?}", bitmap.format))?;
-                result.set_item("data", PyBytes::new_bound(py, &bitmap.data))?;
-                Ok(result.into()) // Return as a dict with metadata
-            },
+            RenderOutput::Bitmap(bitmap) => bitmap_to_dict(py, &bitmap),
             RenderOutput::Json(json_str) => {
                 // JSON renderers get special handling - return the raw string
                 Ok(json_str.into_py(py))
@@ -265,7 +309,7 @@ impl Typf {
     ///
     /// Args:
     ///     text: The text to shape
-    ///     font_path: Path to the font file
+    ///     font_path: Path to the font file, or a Font from FontDatabase
     ///     size: Font size in pixels (default: 16.0)
     ///     direction: Text direction - "auto", "ltr", "rtl", "ttb", "btt" (default: "auto")
     ///     language: Language hint for direction detection
@@ -277,14 +321,14 @@ impl Typf {
         &self,
         py: Python,
         text: &str,
-        font_path: &str,
+        font_path: &Bound<'_, PyAny>,
         size: f32,
         direction: &str,
         language: Option<&str>,
         face_index: u32,
     ) -> PyResult<PyObject> {
         // Load font with optional TTC index
-        let font_arc = load_font(font_path, face_index)?;
+        let font_arc = resolve_font(font_path, face_index)?;
 
         // Auto-detect or parse direction
         let resolved_direction = parse_direction(direction, text, language)?;
@@ -316,7 +360,7 @@ impl Typf {
     ///
     /// Args:
     ///     text: The text to shape
-    ///     font_path: Path to the font file
+    ///     font_path: Path to the font file, or a Font from FontDatabase
     ///     size: Font size in pixels (default: 16.0)
     ///     direction: Text direction - "auto", "ltr", "rtl", "ttb", "btt" (default: "auto")
     ///     language: Language hint for direction detection
@@ -339,7 +383,7 @@ impl Typf {
     fn shape_glyphs(
         &self,
         text: &str,
-        font_path: &str,
+        font_path: &Bound<'_, PyAny>,
         size: f32,
         direction: &str,
         language: Option<&str>,
@@ -347,7 +391,7 @@ impl Typf {
         variations: Option<HashMap<String, f32>>,
     ) -> PyResult<ShapedGlyphs> {
         // Load font with optional TTC index
-        let font_arc = load_font(font_path, face_index)?;
+        let font_arc = resolve_font(font_path, face_index)?;
 
         let mut variation_vec: Vec<(String, f32)> =
             variations.unwrap_or_default().into_iter().collect();
@@ -382,7 +426,7 @@ impl Typf {
     ///
     /// Args:
     ///     text: The text to render
-    ///     font_path: Path to the font file
+    ///     font_path: Path to the font file, or a Font from FontDatabase
     ///     size: Font size in pixels (default: 16.0)
     ///     color: Foreground color as (r, g, b, a) tuple (default: black)
     ///     padding: Padding around rendered text in pixels (default: 10)
@@ -396,7 +440,7 @@ impl Typf {
     fn render_to_svg(
         &self,
         text: &str,
-        font_path: &str,
+        font_path: &Bound<'_, PyAny>,
         size: f32,
         color: Option<(u8, u8, u8, u8)>,
         padding: u32,
@@ -405,7 +449,7 @@ impl Typf {
         face_index: u32,
     ) -> PyResult<String> {
         // Load font with optional TTC index
-        let font_arc = load_font(font_path, face_index)?;
+        let font_arc = resolve_font(font_path, face_index)?;
 
         // Auto-detect or parse direction
         let resolved_direction = parse_direction(direction, text, language)?;
@@ -492,7 +536,7 @@ impl TypfLinra {
     ///
     /// Args:
     ///     text: The text to render
-    ///     font_path: Path to the font file
+    ///     font_path: Path to the font file, or a Font from FontDatabase
     ///     size: Font size in pixels (default: 16.0)
     ///     color: Foreground color as (r, g, b, a) tuple (default: black)
     ///     background: Background color as (r, g, b, a) tuple (default: transparent)
@@ -509,7 +553,7 @@ impl TypfLinra {
         &self,
         py: Python,
         text: &str,
-        font_path: &str,
+        font_path: &Bound<'_, PyAny>,
         size: f32,
         color: Option<(u8, u8, u8, u8)>,
         background: Option<(u8, u8, u8, u8)>,
@@ -522,7 +566,7 @@ impl TypfLinra {
         face_index: u32,
     ) -> PyResult<PyObject> {
         // Load font with optional TTC index
-        let font_arc = load_font(font_path, face_index)?;
+        let font_arc = resolve_font(font_path, face_index)?;
 
         // Parse colors
         let foreground = color
@@ -550,7 +594,10 @@ impl TypfLinra {
             script,
             antialias: true,
             letter_spacing: 0.0,
-            color_palette: 0,
+            color_palette: Default::default(),
+            style: FontStyle::Normal,
+            stretch: FontStretch::Normal,
+            pixel_layout: Default::default(),
         };
 
         // Render using linra (single-pass)
@@ -561,14 +608,7 @@ impl TypfLinra {
 
         // Package result
         match rendered {
-            RenderOutput::Bitmap(bitmap) => {
-                let result = PyDict::new_bound(py);
-                result.set_item("width", bitmap.width)?;
-                result.set_item("height", bitmap.height)?;
-                result.set_item("format", format!("{:?}", bitmap.format))?;
-                result.set_item("data", PyBytes::new_bound(py, &bitmap.data))?;
-                Ok(result.into())
-            },
+            RenderOutput::Bitmap(bitmap) => bitmap_to_dict(py, &bitmap),
             _ => Err(PyValueError::new_err("Unexpected render output format")),
         }
     }
@@ -1022,6 +1062,46 @@ impl VariationAxisInfo {
     }
 }
 
+/// What a font's license allows when embedding it in a PDF or SVG
+#[pyclass]
+#[derive(Clone)]
+struct EmbeddingPermissionsInfo {
+    /// Raw OS/2 fsType field
+    #[pyo3(get)]
+    fs_type: u16,
+    /// "installable", "restricted", "preview_and_print", or "editable"
+    #[pyo3(get)]
+    level: String,
+    /// Whether the whole font must be embedded rather than a subset
+    #[pyo3(get)]
+    no_subsetting: bool,
+    /// Whether only bitmap strikes may be embedded
+    #[pyo3(get)]
+    bitmap_only: bool,
+    /// Whether the outlines may be embedded at all
+    #[pyo3(get)]
+    allows_embedding: bool,
+    /// Whether a subset may be embedded
+    #[pyo3(get)]
+    allows_subsetting: bool,
+    #[pyo3(get)]
+    license: Option<String>,
+    #[pyo3(get)]
+    license_url: Option<String>,
+    #[pyo3(get)]
+    copyright: Option<String>,
+}
+
+#[pymethods]
+impl EmbeddingPermissionsInfo {
+    fn __repr__(&self) -> String {
+        format!(
+            "EmbeddingPermissionsInfo(level='{}', fs_type=0x{:04x}, no_subsetting={}, bitmap_only={})",
+            self.level, self.fs_type, self.no_subsetting, self.bitmap_only
+        )
+    }
+}
+
 /// Load a font and get information about it
 #[pyclass]
 struct FontInfo {
@@ -1103,6 +1183,34 @@ impl FontInfo {
         }))
     }
 
+    /// Embedding restrictions and license notices
+    ///
+    /// Check these before embedding a subset of the font in a PDF or SVG.
+    /// Returns an EmbeddingPermissionsInfo read from the OS/2 fsType field
+    /// and the name table's license, license URL, and copyright records.
+    fn embedding_permissions(&self) -> PyResult<EmbeddingPermissionsInfo> {
+        let face = TypfFontFace::from_file_index(&self.path, self.face_index)
+            .map_err(|e| PyIOError::new_err(format!("Failed to load font: {:?}", e)))?;
+        let permissions = face.embedding_permissions();
+        let level = match permissions.level {
+            EmbeddingLevel::Installable => "installable",
+            EmbeddingLevel::Restricted => "restricted",
+            EmbeddingLevel::PreviewAndPrint => "preview_and_print",
+            EmbeddingLevel::Editable => "editable",
+        };
+        Ok(EmbeddingPermissionsInfo {
+            fs_type: permissions.fs_type,
+            level: level.to_string(),
+            no_subsetting: permissions.no_subsetting,
+            bitmap_only: permissions.bitmap_only,
+            allows_embedding: permissions.allows_embedding(),
+            allows_subsetting: permissions.allows_subsetting(),
+            license: permissions.license,
+            license_url: permissions.license_url,
+            copyright: permissions.copyright,
+        })
+    }
+
     /// Calculate line height in font units
     ///
     /// This is the recommended line-to-line distance: ascent - descent + line_gap
@@ -1111,6 +1219,181 @@ impl FontInfo {
     }
 }
 
+/// A font face loaded once and shared by every call that uses it
+///
+/// Pass a Font wherever a `font_path` is accepted to skip re-reading the
+/// file. Fonts are immutable and safe to share between threads.
+///
+/// Example:
+///     >>> font = Font("NotoSans-Regular.ttf")
+///     >>> image = Typf().render_text("Hello", font, size=32)
+#[pyclass(frozen)]
+#[derive(Clone)]
+struct Font {
+    face: Arc<TypfFontFace>,
+}
+
+#[pymethods]
+impl Font {
+    /// Load a face from a font file or collection
+    ///
+    /// Args:
+    ///     path: Path to the font file (str or os.PathLike)
+    ///     face_index: TTC collection face index (default: 0)
+    #[new]
+    #[pyo3(signature = (path, face_index=0))]
+    fn new(path: PathBuf, face_index: u32) -> PyResult<Self> {
+        let face = TypfFontFace::from_file_index(&path, face_index)
+            .map_err(|e| PyIOError::new_err(format!("Failed to load font: {:?}", e)))?;
+        Ok(Self {
+            face: Arc::new(face),
+        })
+    }
+
+    /// Load a face from font bytes in memory
+    #[staticmethod]
+    #[pyo3(signature = (data, face_index=0))]
+    fn from_bytes(data: Vec<u8>, face_index: u32) -> PyResult<Self> {
+        let face = TypfFontFace::from_data_index(data, face_index)
+            .map_err(|e| PyValueError::new_err(format!("Invalid font data: {:?}", e)))?;
+        Ok(Self {
+            face: Arc::new(face),
+        })
+    }
+
+    /// File the font was loaded from, or None for in-memory fonts
+    #[getter]
+    fn path(&self) -> Option<String> {
+        self.face.path().map(|p| p.display().to_string())
+    }
+
+    /// Face index within a collection
+    #[getter]
+    fn face_index(&self) -> u32 {
+        self.face.face_index()
+    }
+
+    /// Design units per em
+    #[getter]
+    fn units_per_em(&self) -> u16 {
+        TypfFontRef::units_per_em(self.face.as_ref())
+    }
+
+    /// Whether the font has variation axes
+    #[getter]
+    fn is_variable(&self) -> bool {
+        TypfFontRef::is_variable(self.face.as_ref())
+    }
+
+    /// Glyph ID for a character, or None if the font lacks it
+    fn glyph_id(&self, ch: char) -> Option<u32> {
+        self.face.glyph_id(ch)
+    }
+
+    fn __repr__(&self) -> String {
+        match self.path() {
+            Some(path) => format!("Font({:?}, face_index={})", path, self.face_index()),
+            None => format!("Font(<bytes>, face_index={})", self.face_index()),
+        }
+    }
+}
+
+/// A set of loaded fonts that reuses faces loaded from the same file
+///
+/// Useful for regression scripts that render many strings with a handful of
+/// fonts: each file is read and parsed once.
+///
+/// Example:
+///     >>> db = FontDatabase()
+///     >>> sans = db.load_font("NotoSans-Regular.ttf")
+///     >>> again = db.load_font("NotoSans-Regular.ttf")  # reuses the face
+///     >>> len(db)
+///     1
+#[pyclass]
+struct FontDatabase {
+    db: Mutex<typf_fontdb::FontDatabase>,
+}
+
+impl FontDatabase {
+    fn with_db<T>(&self, f: impl FnOnce(&mut typf_fontdb::FontDatabase) -> T) -> T {
+        let mut db = self
+            .db
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&mut db)
+    }
+}
+
+#[pymethods]
+impl FontDatabase {
+    /// Create an empty database
+    #[new]
+    fn new() -> Self {
+        Self {
+            db: Mutex::new(typf_fontdb::FontDatabase::new()),
+        }
+    }
+
+    /// Load the first face of a font file, reusing it if already loaded
+    fn load_font(&self, path: PathBuf) -> PyResult<Font> {
+        self.with_db(|db| db.load_font(&path))
+            .map(|face| Font { face })
+            .map_err(|e| PyIOError::new_err(format!("Failed to load font: {:?}", e)))
+    }
+
+    /// Add a font from bytes in memory
+    fn load_font_data(&self, data: Vec<u8>) -> PyResult<Font> {
+        self.with_db(|db| db.load_font_data(data))
+            .map(|face| Font { face })
+            .map_err(|e| PyValueError::new_err(format!("Invalid font data: {:?}", e)))
+    }
+
+    /// The first font loaded, or None when empty
+    fn default_font(&self) -> Option<Font> {
+        self.with_db(|db| db.default_font())
+            .map(|face| Font { face })
+    }
+
+    /// Every loaded font in load order
+    fn fonts(&self) -> Vec<Font> {
+        self.with_db(|db| {
+            db.fonts()
+                .iter()
+                .map(|face| Font { face: face.clone() })
+                .collect()
+        })
+    }
+
+    fn __len__(&self) -> usize {
+        self.with_db(|db| db.fonts().len())
+    }
+}
+
+/// Convert a bitmap dict from render_text() into a NumPy array
+///
+/// Returns a uint8 array shaped (height, width, channels). 1-bit bitmaps
+/// are rejected.
+#[cfg(feature = "numpy-interop")]
+#[pyfunction]
+fn to_numpy<'py>(
+    py: Python<'py>,
+    image: &Bound<'py, PyDict>,
+) -> PyResult<Bound<'py, numpy::PyArray3<u8>>> {
+    use numpy::{PyArray1, PyArrayMethods};
+
+    let shape: Option<(usize, usize, usize)> = image
+        .get_item("shape")?
+        .ok_or_else(|| PyValueError::new_err("image dict has no 'shape'"))?
+        .extract()?;
+    let shape = shape
+        .ok_or_else(|| PyValueError::new_err("1-bit bitmaps cannot be viewed as a byte array"))?;
+    let data: Vec<u8> = image
+        .get_item("data")?
+        .ok_or_else(|| PyValueError::new_err("image dict has no 'data'"))?
+        .extract()?;
+    PyArray1::from_vec_bound(py, data).reshape([shape.0, shape.1, shape.2])
+}
+
 /// Export rendered output to various formats
 ///
 /// Accepts either raw bytes (data, width, height) or a dict from render_text()
@@ -1138,6 +1421,8 @@ fn export_image(py: Python, image_data: PyObject, format: &str) -> PyResult<PyOb
             width,
             height,
             format: typf_core::types::BitmapFormat::Rgba8,
+            stride: typf_core::types::BitmapFormat::Rgba8.row_bytes(width),
+            layout: typf_core::types::PixelLayout::RGBA,
             data,
         }
     } else {
@@ -1250,14 +1535,7 @@ fn render_simple(py: Python, text: &str, size: f32, direction: &str) -> PyResult
         .map_err(|e| PyRuntimeError::new_err(format!("Rendering failed: {:?}", e)))?;
 
     match rendered {
-        RenderOutput::Bitmap(bitmap) => {
-            let result = PyDict::new_bound(py);
-            result.set_item("width", bitmap.width)?;
-            result.set_item("height", bitmap.height)?;
-            result.set_item("format", format!("{:?}", bitmap.format))?;
-            result.set_item("data", PyBytes::new_bound(py, &bitmap.data))?;
-            Ok(result.into())
-        },
+        RenderOutput::Bitmap(bitmap) => bitmap_to_dict(py, &bitmap),
         _ => Err(PyValueError::new_err("Unexpected render output format")),
     }
 }
@@ -1304,7 +1582,10 @@ fn is_caching_enabled() -> bool {
 fn typf(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Typf>()?;
     m.add_class::<FontInfo>()?;
+    m.add_class::<Font>()?;
+    m.add_class::<FontDatabase>()?;
     m.add_class::<VariationAxisInfo>()?;
+    m.add_class::<EmbeddingPermissionsInfo>()?;
     m.add_class::<PathOp>()?;
     m.add_class::<GlyphPath>()?;
     m.add_class::<PositionedGlyph>()?;
@@ -1315,6 +1596,8 @@ fn typf(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(render_simple, m)?)?;
     m.add_function(wrap_pyfunction!(set_caching_enabled, m)?)?;
     m.add_function(wrap_pyfunction!(is_caching_enabled, m)?)?;
+    #[cfg(feature = "numpy-interop")]
+    m.add_function(wrap_pyfunction!(to_numpy, m)?)?;
     m.add("__version__", VERSION)?;
     m.add("__linra_available__", cfg!(feature = "linra"))?;
     m.add("__numpy_available__", cfg!(feature = "numpy-interop"))?;
@@ -0,0 +1,72 @@
+"""
+Tests for Font and FontDatabase, and for NumPy-ready bitmap output.
+"""
+# this_file: bindings/py/tests/test_font_database.py
+
+from pathlib import Path
+
+import pytest
+
+TEST_DATA_DIR = Path(__file__).resolve().parents[3] / "test-fonts"
+NOTO_SANS = TEST_DATA_DIR / "NotoSans-Regular.ttf"
+
+pytestmark = pytest.mark.skipif(not NOTO_SANS.exists(), reason="test font missing")
+
+
+def test_font_loads_from_path_and_bytes():
+    from typfpy import Font
+
+    from_path = Font(NOTO_SANS)
+    from_bytes = Font.from_bytes(NOTO_SANS.read_bytes())
+
+    assert from_path.path == str(NOTO_SANS)
+    assert from_bytes.path is None
+    assert from_path.units_per_em == from_bytes.units_per_em > 0
+    assert from_path.glyph_id("A") == from_bytes.glyph_id("A")
+
+
+def test_font_database_reuses_faces():
+    from typfpy import FontDatabase
+
+    db = FontDatabase()
+    assert db.default_font() is None
+
+    first = db.load_font(NOTO_SANS)
+    db.load_font(str(NOTO_SANS))
+
+    assert len(db) == 1
+    assert db.default_font().path == first.path
+    assert [f.path for f in db.fonts()] == [first.path]
+
+
+def test_render_text_accepts_font_objects():
+    from typfpy import Font, Typf
+
+    engine = Typf(shaper="none", renderer="opixa")
+    by_path = engine.render_text("Typf", str(NOTO_SANS), size=32)
+    by_font = engine.render_text("Typf", Font(NOTO_SANS), size=32)
+
+    assert by_font["data"] == by_path["data"]
+
+
+def test_render_text_rejects_non_font_argument():
+    from typfpy import Typf
+
+    with pytest.raises(TypeError):
+        Typf(shaper="none", renderer="opixa").render_text("Typf", 42)
+
+
+def test_bitmap_shape_matches_numpy_layout():
+    np = pytest.importorskip("numpy")
+    from typfpy import Font, Typf, to_numpy
+
+    image = Typf(shaper="none", renderer="opixa").render_text(
+        "Typf", Font(NOTO_SANS), size=32
+    )
+    height, width, channels = image["shape"]
+    assert (height, width) == (image["height"], image["width"])
+
+    pixels = np.frombuffer(image["data"], np.uint8).reshape(image["shape"])
+    assert pixels.shape == (height, width, channels)
+    if to_numpy is not None:
+        assert np.array_equal(to_numpy(image), pixels)
@@ -0,0 +1,9 @@
+//! WebAssembly package for Typf
+//!
+//! `wasm-pack` needs a `cdylib` to build from; this crate is that shell. The
+//! JavaScript API itself is [`typf::wasm`], re-exported here so everything
+//! marked `#[wasm_bindgen]` there lands in the package.
+//
+// this_file: bindings/wasm/src/lib.rs
+
+pub use typf::wasm::*;
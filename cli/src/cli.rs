@@ -26,6 +26,13 @@ pub enum Commands {
     #[command(alias = "r")]
     Render(Box<RenderArgs>),
 
+    /// Shape text and print the positioned glyphs
+    #[command(alias = "s")]
+    Shape(Box<ShapeArgs>),
+
+    /// Report what a font file contains: names, metrics, axes, color tables
+    Inspect(InspectArgs),
+
     /// Process multiple rendering jobs from a JSONL file
     Batch(BatchArgs),
 }
@@ -55,13 +62,17 @@ pub struct RenderArgs {
 
     // Font Face Options
     /// Font file path (.ttf, .otf, .ttc, .otc)
-    #[arg(short = 'f', long = "font-file")]
+    #[arg(short = 'f', long = "font-file", visible_alias = "font")]
     pub font_file: Option<PathBuf>,
 
     /// Face index for TTC/OTC collections
     #[arg(short = 'y', long = "face-index", default_value = "0")]
     pub face_index: u32,
 
+    /// PostScript name of the face in a TTC/OTC collection (overrides --face-index)
+    #[arg(long = "face-name")]
+    pub face_name: Option<String>,
+
     /// Named/dynamic instance spec
     #[arg(short = 'i', long = "instance")]
     pub instance: Option<String>,
@@ -81,8 +92,8 @@ pub struct RenderArgs {
     #[arg(long = "shaper", default_value = "auto")]
     pub shaper: String,
 
-    /// Rendering backend: auto, opixa, skia, zeno, vello, vello-cpu, mac, win, json, linra-mac, linra-win
-    /// vello uses GPU acceleration via wgpu; vello-cpu is CPU-only
+    /// Rendering backend: auto, opixa, skia, zeno, vello, vello-cpu, gpu, mac, win, json, linra-mac, linra-win
+    /// vello uses GPU acceleration via wgpu; vello-cpu is CPU-only; gpu is the experimental wgpu compute renderer
     /// Linra backends (linra-mac, linra-win) perform shaping AND rendering in one pass
     #[arg(long = "renderer", default_value = "auto")]
     pub renderer: String,
@@ -107,7 +118,12 @@ pub struct RenderArgs {
 
     // Size and Crop Options
     /// Font size in pixels (or 'em' for UPM)
-    #[arg(short = 's', long = "font-size", default_value = "200")]
+    #[arg(
+        short = 's',
+        long = "font-size",
+        visible_alias = "size",
+        default_value = "200"
+    )]
     pub font_size: String,
 
     /// Line height as % of font size
@@ -149,6 +165,10 @@ pub struct RenderArgs {
     ///   --glyph-source deny=colr0,colr1,svg     Disable all color sources
     ///   --glyph-source prefer=colr1,colr0,svg   Prefer COLR over SVG
     ///   --glyph-source deny=sbix,cbdt,ebdt      Disable bitmap sources
+    ///   --glyph-source preset=prefer-bitmap-at-small-sizes
+    ///                                           Bitmaps below 32 ppem, COLR above
+    ///
+    /// Presets: default, monochrome, vector-only, prefer-bitmap-at-small-sizes
     #[arg(long = "glyph-source", action = ArgAction::Append, verbatim_doc_comment)]
     pub glyph_source: Vec<String>,
 
@@ -160,9 +180,25 @@ pub struct RenderArgs {
     #[arg(long = "no-glyph-cache", action = ArgAction::SetTrue)]
     pub no_glyph_cache: bool,
 
+    /// Byte-identical output across runs, thread counts, and platforms
+    #[arg(long = "deterministic", action = ArgAction::SetTrue)]
+    pub deterministic: bool,
+
+    /// Fail on malformed glyphs instead of skipping them
+    #[arg(long = "strict", action = ArgAction::SetTrue)]
+    pub strict: bool,
+
+    /// Place glyphs at quarter-pixel offsets (skia renderer)
+    #[arg(long = "subpixel", action = ArgAction::SetTrue)]
+    pub subpixel: bool,
+
+    /// Anti-aliasing quality: 4x, 8x, 16x oversampling or analytic (opixa renderer)
+    #[arg(long = "aa-quality", default_value = "4x")]
+    pub aa_quality: AaQuality,
+
     // Output Options
     /// Output file path (stdout if omitted)
-    #[arg(short = 'o', long = "output-file")]
+    #[arg(short = 'o', long = "output-file", visible_alias = "out")]
     pub output_file: Option<PathBuf>,
 
     /// Output format: pbm, png1, pgm, png4, png8, png, svg
@@ -178,6 +214,97 @@ pub struct RenderArgs {
     pub verbose: bool,
 }
 
+/// Arguments for the shape command
+#[derive(Parser, Debug)]
+pub struct ShapeArgs {
+    /// Input text to shape (reads from stdin if omitted)
+    pub text: Option<String>,
+
+    /// Font file path (.ttf, .otf, .ttc, .otc)
+    #[arg(short = 'f', long = "font-file", visible_alias = "font")]
+    pub font_file: Option<PathBuf>,
+
+    /// Face index for TTC/OTC collections
+    #[arg(short = 'y', long = "face-index", default_value = "0")]
+    pub face_index: u32,
+
+    /// PostScript name of the face in a TTC/OTC collection (overrides --face-index)
+    #[arg(long = "face-name")]
+    pub face_name: Option<String>,
+
+    /// Named/dynamic instance spec
+    #[arg(short = 'i', long = "instance")]
+    pub instance: Option<String>,
+
+    /// Input text (alternative to positional argument)
+    #[arg(short = 't', long = "text", conflicts_with = "text_file")]
+    pub text_arg: Option<String>,
+
+    /// Read input text from file
+    #[arg(short = 'T', long = "text-file", conflicts_with = "text_arg")]
+    pub text_file: Option<PathBuf>,
+
+    /// Shaping backend: auto, none, hb, icu-hb, mac
+    #[arg(long = "shaper", default_value = "auto")]
+    pub shaper: String,
+
+    /// Text direction: auto, ltr, rtl, ttb, btt
+    #[arg(short = 'd', long = "direction", default_value = "auto")]
+    pub direction: String,
+
+    /// Language tag (BCP 47), e.g., en, ar, zh-Hans
+    #[arg(short = 'l', long = "language")]
+    pub language: Option<String>,
+
+    /// Script tag (ISO 15924), e.g., Latn, Arab, Hans
+    #[arg(short = 'S', long = "script", default_value = "auto")]
+    pub script: String,
+
+    /// Font feature settings (comma or space separated)
+    #[arg(short = 'F', long = "features")]
+    pub features: Option<String>,
+
+    /// Font size in pixels (or 'em' for UPM)
+    #[arg(
+        short = 's',
+        long = "font-size",
+        visible_alias = "size",
+        default_value = "em"
+    )]
+    pub font_size: String,
+
+    /// Print HarfBuzz-compatible JSON instead of `hb-shape` style text
+    #[arg(long = "json")]
+    pub json: bool,
+
+    /// Output file path (stdout if omitted)
+    #[arg(short = 'o', long = "output-file", visible_alias = "out")]
+    pub output_file: Option<PathBuf>,
+
+    /// Verbose output
+    #[arg(long = "verbose")]
+    pub verbose: bool,
+}
+
+/// Arguments for the inspect command
+#[derive(Parser, Debug)]
+pub struct InspectArgs {
+    /// Font file path (.ttf, .otf, .ttc, .otc)
+    pub font_file: PathBuf,
+
+    /// Face index for TTC/OTC collections
+    #[arg(short = 'y', long = "face-index", default_value = "0")]
+    pub face_index: u32,
+
+    /// PostScript name of the face in a TTC/OTC collection (overrides --face-index)
+    #[arg(long = "face-name")]
+    pub face_name: Option<String>,
+
+    /// Print JSON instead of text
+    #[arg(long = "json")]
+    pub json: bool,
+}
+
 /// Arguments for the batch command
 #[derive(Parser, Debug)]
 pub struct BatchArgs {
@@ -202,6 +329,22 @@ pub struct BatchArgs {
     pub verbose: bool,
 }
 
+/// Anti-aliasing quality levels
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum AaQuality {
+    /// 4x4 samples per pixel
+    #[value(name = "4x")]
+    X4,
+    /// 8x8 samples per pixel
+    #[value(name = "8x")]
+    X8,
+    /// 16x16 samples per pixel
+    #[value(name = "16x")]
+    X16,
+    /// Exact area coverage
+    Analytic,
+}
+
 /// Supported output formats
 #[derive(Debug, Clone, Copy, ValueEnum)]
 #[value(rename_all = "lowercase")]
@@ -3,7 +3,7 @@
 //! Processes multiple rendering jobs from a JSONL file.
 // this_file: crates/typf-cli/src/commands/batch.rs
 
-use crate::cli::{BatchArgs, OutputFormat};
+use crate::cli::{AaQuality, BatchArgs, OutputFormat};
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::{self, BufRead, BufReader};
@@ -257,6 +257,7 @@ fn process_job(job: &BatchJob, output_file: &Path, args: &BatchArgs) -> Result<(
         text: Some(job.text.clone()),
         font_file,
         face_index: 0,
+        face_name: None,
         instance: None,
         text_arg: None,
         text_file: None,
@@ -286,6 +287,10 @@ fn process_job(job: &BatchJob, output_file: &Path, args: &BatchArgs) -> Result<(
         glyph_source: Vec::new(),
         no_shaping_cache: false,
         no_glyph_cache: false,
+        deterministic: false,
+        strict: false,
+        subpixel: false,
+        aa_quality: AaQuality::X4,
         output_file: Some(output_file.to_path_buf()),
         format,
         quiet: args.quiet,
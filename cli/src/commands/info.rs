@@ -112,6 +112,9 @@ fn print_renderers() {
     #[cfg(feature = "render-vello")]
     println!("  vello             - Vello GPU (wgpu-accelerated, cross-platform)");
 
+    #[cfg(feature = "render-gpu")]
+    println!("  gpu               - wgpu compute coverage (experimental, huge canvases)");
+
     #[cfg(all(target_os = "macos", feature = "render-mac"))]
     println!("  mac               - CoreGraphics (macOS native)");
 
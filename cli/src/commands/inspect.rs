@@ -0,0 +1,307 @@
+//! Inspect command implementation
+//!
+//! Reports what a font file contains, so bug reports can say which color
+//! tables, outline format, and axes the font has without a separate tool.
+// this_file: crates/typf-cli/src/commands/inspect.rs
+
+use serde::Serialize;
+use skrifa::bitmap::BitmapStrikes;
+use skrifa::instance::{LocationRef, Size};
+use skrifa::raw::{types::Tag, TableProvider};
+use skrifa::{string::StringId, MetadataProvider};
+
+use crate::cli::InspectArgs;
+use crate::limits::{validate_file_size_limit, MAX_FONT_FILE_BYTES};
+use typf::error::{Result, TypfError};
+
+/// Everything `typf inspect` prints
+#[derive(Debug, Serialize)]
+struct FontReport {
+    path: String,
+    face_index: u32,
+    faces: Vec<FaceReport>,
+    family: Option<String>,
+    subfamily: Option<String>,
+    units_per_em: u16,
+    glyph_count: u16,
+    ascent: f32,
+    descent: f32,
+    line_gap: f32,
+    outlines: Vec<&'static str>,
+    axes: Vec<AxisReport>,
+    named_instances: usize,
+    color: ColorReport,
+    layout: Vec<&'static str>,
+}
+
+/// One face of the file; collections have several
+#[derive(Debug, Serialize)]
+struct FaceReport {
+    index: u32,
+    family: Option<String>,
+    postscript_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AxisReport {
+    tag: String,
+    min: f32,
+    default: f32,
+    max: f32,
+}
+
+/// Color glyph sources, `None`/`false` when the table is absent
+#[derive(Debug, Default, Serialize)]
+struct ColorReport {
+    colr_version: Option<u16>,
+    cpal_palettes: Option<u16>,
+    svg: bool,
+    sbix: bool,
+    cbdt: bool,
+    ebdt: bool,
+    bitmap_strikes: usize,
+}
+
+impl ColorReport {
+    fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(version) = self.colr_version {
+            parts.push(format!("COLRv{}", version));
+        }
+        if let Some(palettes) = self.cpal_palettes {
+            parts.push(format!("CPAL ({} palettes)", palettes));
+        }
+        for (present, name) in [
+            (self.svg, "SVG"),
+            (self.sbix, "sbix"),
+            (self.cbdt, "CBDT"),
+            (self.ebdt, "EBDT"),
+        ] {
+            if present {
+                parts.push(name.to_string());
+            }
+        }
+        if self.bitmap_strikes > 0 {
+            parts.push(format!("{} bitmap strikes", self.bitmap_strikes));
+        }
+        if parts.is_empty() {
+            "none".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+pub fn run(args: &InspectArgs) -> Result<()> {
+    validate_file_size_limit(&args.font_file, MAX_FONT_FILE_BYTES, "font file")
+        .map_err(TypfError::Other)?;
+    let data = std::fs::read(&args.font_file)?;
+    let report = inspect(&data, args)?;
+
+    if args.json {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| TypfError::Other(format!("Failed to serialize report: {}", e)))?;
+        println!("{}", json);
+    } else {
+        print_report(&report);
+    }
+    Ok(())
+}
+
+/// The family and PostScript name of `font`
+fn face_names(font: &skrifa::FontRef) -> (Option<String>, Option<String>) {
+    let name = |id: StringId| {
+        font.localized_strings(id)
+            .english_or_first()
+            .map(|s| s.to_string())
+    };
+    (name(StringId::FAMILY_NAME), name(StringId::POSTSCRIPT_NAME))
+}
+
+/// Every face in the file, in index order
+fn list_faces(data: &[u8]) -> Vec<FaceReport> {
+    (0..)
+        .map_while(|index| {
+            let font = skrifa::FontRef::from_index(data, index).ok()?;
+            let (family, postscript_name) = face_names(&font);
+            Some(FaceReport {
+                index,
+                family,
+                postscript_name,
+            })
+        })
+        .collect()
+}
+
+fn inspect(data: &[u8], args: &InspectArgs) -> Result<FontReport> {
+    let faces = list_faces(data);
+    let face_index = match &args.face_name {
+        Some(ps_name) => faces
+            .iter()
+            .find(|face| face.postscript_name.as_deref() == Some(ps_name.as_str()))
+            .map(|face| face.index)
+            .ok_or_else(|| {
+                TypfError::Other(format!(
+                    "No face with PostScript name '{}' in '{}'",
+                    ps_name,
+                    args.font_file.display()
+                ))
+            })?,
+        None => args.face_index,
+    };
+    let font = skrifa::FontRef::from_index(data, face_index).map_err(|error| {
+        TypfError::Other(format!(
+            "Failed to read font '{}' with face_index={}: {}",
+            args.font_file.display(),
+            face_index,
+            error
+        ))
+    })?;
+
+    let has = |tag: &[u8; 4]| font.table_data(Tag::new(tag)).is_some();
+    let name = |id: StringId| {
+        font.localized_strings(id)
+            .english_or_first()
+            .map(|s| s.to_string())
+    };
+    let metrics = font.metrics(Size::unscaled(), LocationRef::default());
+
+    let outlines = [(b"glyf", "glyf"), (b"CFF ", "CFF"), (b"CFF2", "CFF2")]
+        .into_iter()
+        .filter(|(tag, _)| has(tag))
+        .map(|(_, name)| name)
+        .collect();
+    let layout = [
+        (b"GSUB", "GSUB"),
+        (b"GPOS", "GPOS"),
+        (b"GDEF", "GDEF"),
+        (b"kern", "kern"),
+        (b"morx", "morx"),
+    ]
+    .into_iter()
+    .filter(|(tag, _)| has(tag))
+    .map(|(_, name)| name)
+    .collect();
+
+    let color = ColorReport {
+        colr_version: font.colr().ok().map(|colr| colr.version()),
+        cpal_palettes: font.cpal().ok().map(|cpal| cpal.num_palettes()),
+        svg: has(b"SVG "),
+        sbix: has(b"sbix"),
+        cbdt: has(b"CBDT"),
+        ebdt: has(b"EBDT"),
+        bitmap_strikes: BitmapStrikes::new(&font).len(),
+    };
+
+    Ok(FontReport {
+        path: args.font_file.display().to_string(),
+        face_index,
+        faces,
+        family: name(StringId::FAMILY_NAME),
+        subfamily: name(StringId::SUBFAMILY_NAME),
+        units_per_em: metrics.units_per_em,
+        glyph_count: metrics.glyph_count,
+        ascent: metrics.ascent,
+        descent: metrics.descent,
+        line_gap: metrics.leading,
+        outlines,
+        axes: font
+            .axes()
+            .iter()
+            .map(|axis| AxisReport {
+                tag: axis.tag().to_string(),
+                min: axis.min_value(),
+                default: axis.default_value(),
+                max: axis.max_value(),
+            })
+            .collect(),
+        named_instances: font.named_instances().len(),
+        color,
+        layout,
+    })
+}
+
+fn print_report(report: &FontReport) {
+    let or_none = |list: &[&str]| {
+        if list.is_empty() {
+            "none".to_string()
+        } else {
+            list.join(", ")
+        }
+    };
+
+    println!("Font: {} (face {})", report.path, report.face_index);
+    if report.faces.len() > 1 {
+        println!("  Collection:      {} faces", report.faces.len());
+        for face in &report.faces {
+            println!(
+                "    {}  {} ({})",
+                face.index,
+                face.family.as_deref().unwrap_or("(unnamed)"),
+                face.postscript_name
+                    .as_deref()
+                    .unwrap_or("no PostScript name")
+            );
+        }
+    }
+    println!(
+        "  Family:          {}",
+        report.family.as_deref().unwrap_or("(unnamed)")
+    );
+    println!(
+        "  Style:           {}",
+        report.subfamily.as_deref().unwrap_or("(unnamed)")
+    );
+    println!("  Units per em:    {}", report.units_per_em);
+    println!("  Glyphs:          {}", report.glyph_count);
+    println!(
+        "  Ascent/descent:  {} / {} (line gap {})",
+        report.ascent, report.descent, report.line_gap
+    );
+    println!("  Outlines:        {}", or_none(&report.outlines));
+    println!("  Color:           {}", report.color.summary());
+    println!("  Layout:          {}", or_none(&report.layout));
+    if report.axes.is_empty() {
+        println!("  Variable axes:   none");
+    } else {
+        println!("  Variable axes:");
+        for axis in &report.axes {
+            println!(
+                "    {}  {} .. {} .. {}",
+                axis.tag, axis.min, axis.default, axis.max
+            );
+        }
+        println!("  Named instances: {}", report.named_instances);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_faces_when_single_font_then_one_named_face() {
+        let path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../test-fonts/NotoSans-Regular.ttf");
+        let Ok(data) = std::fs::read(&path) else {
+            return;
+        };
+        let faces = list_faces(&data);
+        assert_eq!(faces.len(), 1);
+        assert_eq!(faces[0].index, 0);
+        assert!(faces[0].postscript_name.is_some());
+        assert!(list_faces(b"not a font").is_empty());
+    }
+
+    #[test]
+    fn color_summary_when_tables_present_then_listed() {
+        let color = ColorReport {
+            colr_version: Some(1),
+            cpal_palettes: Some(2),
+            svg: true,
+            ..Default::default()
+        };
+        assert_eq!(color.summary(), "COLRv1, CPAL (2 palettes), SVG");
+        assert_eq!(ColorReport::default().summary(), "none");
+    }
+}
@@ -3,4 +3,6 @@
 
 pub mod batch;
 pub mod info;
+pub mod inspect;
 pub mod render;
+pub mod shape;
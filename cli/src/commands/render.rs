@@ -5,12 +5,13 @@
 //
 // this_file: crates/typf-cli/src/commands/render.rs
 
-use crate::cli::{OutputFormat, RenderArgs};
+use crate::cli::{AaQuality, OutputFormat, RenderArgs};
 use crate::limits::{read_to_string_with_limit, validate_file_size_limit, MAX_FONT_FILE_BYTES};
 use skrifa::bitmap::BitmapStrikes;
 use skrifa::raw::TableProvider;
 use std::fs::File;
 use std::io::{self, Read, Write};
+use std::path::Path;
 use std::sync::Arc;
 use typf::error::{Result, TypfError};
 #[cfg(feature = "linra")]
@@ -18,14 +19,12 @@ use typf_core::linra::{LinraRenderParams, LinraRenderer};
 use typf_core::Pipeline;
 use typf_core::{
     traits::{Exporter, FontRef, Renderer, Shaper},
-    types::{Direction, RenderOutput, VectorFormat},
-    Color, GlyphSource, GlyphSourcePreference, RenderMode, RenderParams, ShapingParams,
-    MAX_FONT_SIZE,
+    types::{BitmapFormat, Direction, RenderOutput, VectorFormat},
+    AntialiasQuality, Color, GlyphSource, GlyphSourcePreference, RenderMode, RenderParams,
+    ShapingParams, SubpixelPositioning, MAX_FONT_SIZE,
 };
 use typf_export::{PngExporter, PnmExporter};
-use typf_fontdb::TypfFontFace;
-use typf_render_opixa::OpixaRenderer;
-use typf_render_svg::SvgRenderer;
+use typf_fontdb::{FontSource, TypfFontFace};
 use typf_shape_none::NoneShaper;
 use typf_unicode::{UnicodeOptions, UnicodeProcessor};
 
@@ -136,6 +135,10 @@ pub fn run(args: &RenderArgs) -> Result<()> {
         features: parse_features(&args.features)?,
         variations: variations.clone(),
         letter_spacing: 0.0,
+        tab_stops: Default::default(),
+        number_substitution: Default::default(),
+        case: Default::default(),
+        cancel: None,
     };
 
     // 7. Create rendering parameters
@@ -152,10 +155,40 @@ pub fn run(args: &RenderArgs) -> Result<()> {
         background: Some(background),
         padding: args.margin,
         antialias: !matches!(args.format, OutputFormat::Pbm | OutputFormat::Png1),
+        antialias_quality: match args.aa_quality {
+            AaQuality::X4 => AntialiasQuality::Oversample4x,
+            AaQuality::X8 => AntialiasQuality::Oversample8x,
+            AaQuality::X16 => AntialiasQuality::Oversample16x,
+            AaQuality::Analytic => AntialiasQuality::Analytic,
+        },
+        hinting: Default::default(),
+        dropout: Default::default(),
+        gamma: 1.0,
         variations,
-        color_palette,
+        color_palette: color_palette.into(),
         glyph_sources,
         output: output_mode,
+        format: BitmapFormat::Rgba8,
+        row_alignment: 1,
+        pixel_layout: Default::default(),
+        effects: Default::default(),
+        auto_padding: true,
+        transform: None,
+        baseline_shift: 0.0,
+        deterministic: args.deterministic,
+        cancel: None,
+        memory_budget: None,
+        strict: args.strict,
+        emoji_presentation: Default::default(),
+        color_spans: Vec::new(),
+        subpixel: if args.subpixel {
+            SubpixelPositioning::Quarter
+        } else {
+            SubpixelPositioning::Off
+        },
+        glyph_source_log: None,
+        diagnostics: None,
+        layout: Default::default(),
     };
 
     let (shaping_cache_on, glyph_cache_on, caching_allowed) = cache_flags(
@@ -180,7 +213,7 @@ pub fn run(args: &RenderArgs) -> Result<()> {
         renderer = select_renderer(renderer_name)?;
     }
 
-    warn_if_vello_gpu_with_color_font(renderer_name, font.data(), args.quiet);
+    warn_if_vello_gpu_with_color_font(renderer_name, font.data(), font.face_index(), args.quiet);
 
     // 9. Build pipeline
     let exporter = create_exporter(args.format)?;
@@ -255,8 +288,8 @@ impl ColorFontSupport {
     }
 }
 
-fn detect_color_font_support(font_data: &[u8]) -> Option<ColorFontSupport> {
-    let font = skrifa::FontRef::new(font_data).ok()?;
+fn detect_color_font_support(font_data: &[u8], face_index: u32) -> Option<ColorFontSupport> {
+    let font = skrifa::FontRef::from_index(font_data, face_index).ok()?;
     let has_colr = font.colr().is_ok();
     let has_svg = font.svg().is_ok();
     let has_bitmap = !BitmapStrikes::new(&font).is_empty();
@@ -267,12 +300,17 @@ fn detect_color_font_support(font_data: &[u8]) -> Option<ColorFontSupport> {
     })
 }
 
-fn warn_if_vello_gpu_with_color_font(renderer_name: &str, font_data: &[u8], quiet: bool) {
+fn warn_if_vello_gpu_with_color_font(
+    renderer_name: &str,
+    font_data: &[u8],
+    face_index: u32,
+    quiet: bool,
+) {
     if quiet || renderer_name != "vello" {
         return;
     }
 
-    let Some(support) = detect_color_font_support(font_data) else {
+    let Some(support) = detect_color_font_support(font_data, face_index) else {
         return;
     };
     if !support.any() {
@@ -287,18 +325,31 @@ fn warn_if_vello_gpu_with_color_font(renderer_name: &str, font_data: &[u8], quie
 }
 
 fn get_input_text(args: &RenderArgs) -> Result<String> {
-    validate_text_sources(args)?;
+    read_input_text(
+        args.text.as_deref(),
+        args.text_arg.as_deref(),
+        args.text_file.as_deref(),
+    )
+}
+
+/// Text from exactly one of the positional argument, `--text`, `--text-file`, or stdin
+pub(crate) fn read_input_text(
+    text: Option<&str>,
+    text_arg: Option<&str>,
+    text_file: Option<&Path>,
+) -> Result<String> {
+    check_text_sources(text.is_some(), text_arg.is_some(), text_file.is_some())?;
 
     // Priority: text positional > --text > --text-file > stdin
-    if let Some(ref text) = args.text {
+    if let Some(text) = text {
         return decode_and_validate_inline_text(text);
     }
 
-    if let Some(ref text) = args.text_arg {
+    if let Some(text) = text_arg {
         return decode_and_validate_inline_text(text);
     }
 
-    if let Some(ref path) = args.text_file {
+    if let Some(path) = text_file {
         let file = File::open(path)?;
         let text = read_text_with_limit(file, "text file input").map_err(TypfError::Other)?;
         return Ok(text);
@@ -309,15 +360,24 @@ fn get_input_text(args: &RenderArgs) -> Result<String> {
     read_text_with_limit(stdin.lock(), "stdin input text").map_err(TypfError::Other)
 }
 
+#[cfg(test)]
 fn validate_text_sources(args: &RenderArgs) -> Result<()> {
+    check_text_sources(
+        args.text.is_some(),
+        args.text_arg.is_some(),
+        args.text_file.is_some(),
+    )
+}
+
+fn check_text_sources(text: bool, text_arg: bool, text_file: bool) -> Result<()> {
     let mut explicit_sources = Vec::new();
-    if args.text.is_some() {
+    if text {
         explicit_sources.push("positional text");
     }
-    if args.text_arg.is_some() {
+    if text_arg {
         explicit_sources.push("--text");
     }
-    if args.text_file.is_some() {
+    if text_file {
         explicit_sources.push("--text-file");
     }
 
@@ -482,7 +542,23 @@ fn parse_hex_u32(chars: &[char]) -> Option<u32> {
 }
 
 fn load_font(args: &RenderArgs) -> Result<Arc<dyn FontRef>> {
-    let font_path = args.font_file.as_ref().ok_or_else(|| {
+    load_font_file(
+        args.font_file.as_deref(),
+        args.face_index,
+        args.face_name.as_deref(),
+        args.verbose,
+    )
+}
+
+/// Load a face of the font at `font_path`, within the size limit: the one
+/// named `face_name` if given, else face `face_index`
+pub(crate) fn load_font_file(
+    font_path: Option<&Path>,
+    face_index: u32,
+    face_name: Option<&str>,
+    verbose: bool,
+) -> Result<Arc<dyn FontRef>> {
+    let font_path = font_path.ok_or_else(|| {
         TypfError::Other(
             "No font file specified. Use -f/--font-file <path> to provide a font.\n\
              Example: typf render -f /path/to/font.ttf 'Hello'"
@@ -490,30 +566,43 @@ fn load_font(args: &RenderArgs) -> Result<Arc<dyn FontRef>> {
         )
     })?;
 
-    if args.verbose {
-        eprintln!(
-            "Loading font from {} (face_index={})",
-            font_path.display(),
-            args.face_index
-        );
+    let (source, face) = match face_name {
+        Some(ps_name) => (
+            FontSource::CollectionMember {
+                path: font_path.to_path_buf(),
+                ps_name: ps_name.to_string(),
+            },
+            format!("face_name={ps_name}"),
+        ),
+        None => (
+            FontSource::CollectionIndex {
+                path: font_path.to_path_buf(),
+                index: face_index,
+            },
+            format!("face_index={face_index}"),
+        ),
+    };
+
+    if verbose {
+        eprintln!("Loading font from {} ({})", font_path.display(), face);
     }
 
     validate_file_size_limit(font_path, MAX_FONT_FILE_BYTES, "font file")
         .map_err(TypfError::Other)?;
 
-    TypfFontFace::from_file_index(font_path, args.face_index)
+    TypfFontFace::from_source(&source)
         .map_err(|error| {
             TypfError::Other(format!(
-                "Failed to load font '{}' with face_index={}: {}",
+                "Failed to load font '{}' with {}: {}",
                 font_path.display(),
-                args.face_index,
+                face,
                 error
             ))
         })
         .map(|font| Arc::new(font) as Arc<dyn FontRef>)
 }
 
-fn parse_font_size(size_str: &str) -> Result<f32> {
+pub(crate) fn parse_font_size(size_str: &str) -> Result<f32> {
     let normalized = size_str.trim();
 
     let parsed: f32 = if normalized.eq_ignore_ascii_case("em") {
@@ -543,7 +632,7 @@ fn parse_font_size(size_str: &str) -> Result<f32> {
     Ok(parsed)
 }
 
-fn resolve_direction(
+pub(crate) fn resolve_direction(
     text: &str,
     dir_str: &str,
     language: Option<&str>,
@@ -601,12 +690,12 @@ fn resolve_direction(
     }
 }
 
-fn parse_language_hint(raw: Option<&str>) -> Result<Option<String>> {
+pub(crate) fn parse_language_hint(raw: Option<&str>) -> Result<Option<String>> {
     crate::language::normalize_language_tag(raw)
         .map_err(|error| TypfError::Other(format!("Invalid language tag: {}", error)))
 }
 
-fn parse_script_hint(raw: &str) -> Result<Option<String>> {
+pub(crate) fn parse_script_hint(raw: &str) -> Result<Option<String>> {
     let normalized = raw.trim();
     if normalized.is_empty() || normalized.eq_ignore_ascii_case("auto") {
         return Ok(None);
@@ -698,7 +787,7 @@ fn parse_color_palette(raw: u32) -> Result<u16> {
     })
 }
 
-fn parse_backend_name(raw: &str, kind: &str) -> Result<String> {
+pub(crate) fn parse_backend_name(raw: &str, kind: &str) -> Result<String> {
     let normalized = raw.trim().to_ascii_lowercase();
     if normalized.chars().any(char::is_whitespace) {
         return Err(TypfError::Other(format!(
@@ -713,7 +802,7 @@ fn parse_backend_name(raw: &str, kind: &str) -> Result<String> {
     }
 }
 
-fn parse_features(features_str: &Option<String>) -> Result<Vec<(String, u32)>> {
+pub(crate) fn parse_features(features_str: &Option<String>) -> Result<Vec<(String, u32)>> {
     let Some(features) = features_str else {
         return Ok(Vec::new());
     };
@@ -775,7 +864,7 @@ fn parse_feature_token(part: &str) -> Result<(String, u32)> {
 /// - "wght:700 wdth:100" - Alternative separator
 ///
 /// Named instances like "Bold" are not supported by this parser.
-fn parse_variations(instance_str: &Option<String>) -> Result<Vec<(String, f32)>> {
+pub(crate) fn parse_variations(instance_str: &Option<String>) -> Result<Vec<(String, f32)>> {
     use std::collections::BTreeMap;
 
     let Some(instance) = instance_str else {
@@ -846,30 +935,53 @@ fn parse_glyph_sources(specs: &[String]) -> Result<GlyphSourcePreference> {
         return Ok(GlyphSourcePreference::default());
     }
 
+    let mut preset = None;
     let mut prefer = Vec::new();
     let mut deny = Vec::new();
 
     for spec in specs {
         let (kind, list) = spec.split_once('=').ok_or_else(|| {
-            TypfError::Other("glyph-source expects prefer= or deny=<list>".into())
+            TypfError::Other("glyph-source expects prefer=, deny=<list>, or preset=<name>".into())
         })?;
-        let kind = kind.trim();
+        let kind = kind.trim().to_ascii_lowercase();
         let list = list.trim();
 
+        if kind == "preset" {
+            preset = Some(GlyphSourcePreference::preset(list).ok_or_else(|| {
+                TypfError::Other(format!(
+                    "unknown glyph-source preset '{}'; expected {}",
+                    list,
+                    GlyphSourcePreference::PRESETS.join(", ")
+                ))
+            })?);
+            continue;
+        }
         let sources = parse_glyph_source_list(list)?;
-        match kind.to_ascii_lowercase().as_str() {
+        match kind.as_str() {
             "prefer" => prefer.extend(sources),
             "deny" => deny.extend(sources),
             other => {
                 return Err(TypfError::Other(format!(
-                    "Invalid glyph-source flag '{}'; use prefer= or deny=",
+                    "Invalid glyph-source flag '{}'; use prefer=, deny=, or preset=",
                     other
                 )))
             },
         }
     }
 
-    Ok(GlyphSourcePreference::from_parts(prefer, deny))
+    // prefer= replaces the preset's order; deny= adds to its denies
+    let Some(preset) = preset else {
+        return Ok(GlyphSourcePreference::from_parts(prefer, deny));
+    };
+    let prefer = if prefer.is_empty() {
+        preset.prefer
+    } else {
+        prefer
+    };
+    Ok(GlyphSourcePreference {
+        size_rules: preset.size_rules,
+        ..GlyphSourcePreference::from_parts(prefer, preset.deny.into_iter().chain(deny))
+    })
 }
 
 fn parse_glyph_source_list(list: &str) -> Result<Vec<GlyphSource>> {
@@ -915,7 +1027,7 @@ fn parse_glyph_source(token: &str) -> Result<GlyphSource> {
     }
 }
 
-fn select_shaper(shaper_name: &str) -> Result<Arc<dyn Shaper + Send + Sync>> {
+pub(crate) fn select_shaper(shaper_name: &str) -> Result<Arc<dyn Shaper + Send + Sync>> {
     match shaper_name {
         "auto" | "none" => Ok(Arc::new(NoneShaper::new())),
 
@@ -936,33 +1048,11 @@ fn select_shaper(shaper_name: &str) -> Result<Arc<dyn Shaper + Send + Sync>> {
 }
 
 fn select_renderer(renderer_name: &str) -> Result<Arc<dyn Renderer + Send + Sync>> {
-    match renderer_name {
-        "auto" | "opixa" => Ok(Arc::new(OpixaRenderer::new())),
-
-        "svg" => Ok(Arc::new(SvgRenderer::new())),
-
-        #[cfg(feature = "render-cg")]
-        "cg" | "coregraphics" | "mac" => Ok(Arc::new(typf_render_cg::CoreGraphicsRenderer::new())),
-
-        #[cfg(feature = "render-skia")]
-        "skia" => Ok(Arc::new(typf_render_skia::SkiaRenderer::new())),
-
-        #[cfg(feature = "render-zeno")]
-        "zeno" => Ok(Arc::new(typf_render_zeno::ZenoRenderer::new())),
-
-        #[cfg(feature = "render-vello-cpu")]
-        "vello-cpu" => Ok(Arc::new(typf_render_vello_cpu::VelloCpuRenderer::new())),
-
-        #[cfg(feature = "render-vello")]
-        "vello" => typf_render_vello::VelloRenderer::new()
-            .map(|r| Arc::new(r) as Arc<dyn Renderer + Send + Sync>)
-            .map_err(|e| TypfError::Other(format!("Failed to create GPU renderer: {}", e))),
-
-        _ => Err(TypfError::Other(format!(
-            "Unknown or unavailable renderer: {}",
-            renderer_name
-        ))),
-    }
+    let name = match renderer_name {
+        "auto" => "opixa",
+        other => other,
+    };
+    typf::renderers().create(name)
 }
 
 /// Check if the renderer name refers to a linra (single-pass) renderer
@@ -1038,7 +1128,10 @@ fn run_linra(args: &RenderArgs, normalized_renderer: &str) -> Result<()> {
         script,
         antialias: !matches!(args.format, OutputFormat::Pbm | OutputFormat::Png1),
         letter_spacing: 0.0,
-        color_palette,
+        color_palette: color_palette.into(),
+        style: typf_core::types::FontStyle::Normal,
+        stretch: typf_core::types::FontStretch::Normal,
+        pixel_layout: Default::default(),
     };
 
     // 5. Select linra renderer
@@ -1223,6 +1316,7 @@ mod tests {
             text: Some("Hello".to_string()),
             font_file: Some(test_font(name)),
             face_index,
+            face_name: None,
             instance: None,
             text_arg: None,
             text_file: None,
@@ -1243,6 +1337,10 @@ mod tests {
             glyph_source: Vec::new(),
             no_shaping_cache: false,
             no_glyph_cache: false,
+            deterministic: false,
+            strict: false,
+            subpixel: false,
+            aa_quality: AaQuality::X4,
             output_file: None,
             format: OutputFormat::Png,
             quiet: true,
@@ -1253,7 +1351,7 @@ mod tests {
     #[test]
     fn test_detect_color_font_support_when_no_color_tables_then_all_false() {
         let bytes = load_font_bytes("NotoSans-Regular.ttf");
-        let support = detect_color_font_support(&bytes).expect("font parse should succeed");
+        let support = detect_color_font_support(&bytes, 0).expect("font parse should succeed");
         assert_eq!(
             support,
             ColorFontSupport {
@@ -1268,7 +1366,7 @@ mod tests {
     #[test]
     fn test_detect_color_font_support_when_colr_font_then_colr_true() {
         let bytes = load_font_bytes("Nabla-Regular-COLR.ttf");
-        let support = detect_color_font_support(&bytes).expect("font parse should succeed");
+        let support = detect_color_font_support(&bytes, 0).expect("font parse should succeed");
         assert!(
             support.has_colr,
             "expected COLR support for Nabla-Regular-COLR.ttf"
@@ -1278,7 +1376,7 @@ mod tests {
     #[test]
     fn test_detect_color_font_support_when_svg_font_then_svg_true() {
         let bytes = load_font_bytes("Nabla-Regular-SVG.ttf");
-        let support = detect_color_font_support(&bytes).expect("font parse should succeed");
+        let support = detect_color_font_support(&bytes, 0).expect("font parse should succeed");
         assert!(
             support.has_svg,
             "expected SVG table for Nabla-Regular-SVG.ttf"
@@ -1288,7 +1386,7 @@ mod tests {
     #[test]
     fn test_detect_color_font_support_when_bitmap_font_then_bitmap_true() {
         let bytes = load_font_bytes("Nabla-Regular-CBDT.ttf");
-        let support = detect_color_font_support(&bytes).expect("font parse should succeed");
+        let support = detect_color_font_support(&bytes, 0).expect("font parse should succeed");
         assert!(
             support.has_bitmap,
             "expected bitmap strikes for Nabla-Regular-CBDT.ttf"
@@ -1360,6 +1458,7 @@ mod tests {
             text: Some("Hello".to_string()),
             font_file: Some(oversized_path.clone()),
             face_index: 0,
+            face_name: None,
             instance: None,
             text_arg: None,
             text_file: None,
@@ -1380,6 +1479,10 @@ mod tests {
             glyph_source: Vec::new(),
             no_shaping_cache: false,
             no_glyph_cache: false,
+            deterministic: false,
+            strict: false,
+            subpixel: false,
+            aa_quality: AaQuality::X4,
             output_file: None,
             format: OutputFormat::Png,
             quiet: true,
@@ -1409,6 +1512,22 @@ mod tests {
         assert!(pref.deny.contains(&GlyphSource::Colr1));
     }
 
+    #[test]
+    fn glyph_source_parsing_starts_from_preset() {
+        let specs = vec![
+            "preset=prefer-bitmap-at-small-sizes".to_string(),
+            "deny=sbix".to_string(),
+        ];
+
+        let pref = parse_glyph_sources(&specs).expect("parsing should succeed");
+        assert_eq!(pref.order_at(16.0)[0], GlyphSource::Cbdt);
+        assert!(!pref.prefer.contains(&GlyphSource::Sbix));
+
+        let err = parse_glyph_sources(&["preset=sepia".to_string()])
+            .expect_err("unknown preset should error");
+        assert!(format!("{err}").contains("vector-only"));
+    }
+
     #[test]
     fn glyph_source_parsing_defaults_when_empty() {
         let pref = parse_glyph_sources(&[]).expect("empty specs should use default");
@@ -0,0 +1,153 @@
+//! Shape command implementation
+//!
+//! Prints the glyphs a shaper produced, without rendering them.
+// this_file: crates/typf-cli/src/commands/shape.rs
+
+use std::fs::File;
+use std::io::{self, Write};
+
+use crate::cli::ShapeArgs;
+use crate::commands::render::{
+    load_font_file, parse_backend_name, parse_features, parse_font_size, parse_language_hint,
+    parse_script_hint, parse_variations, read_input_text, resolve_direction, select_shaper,
+};
+use typf::error::{Result, TypfError};
+use typf_core::{
+    types::{RenderOutput, ShapingResult},
+    RenderParams, ShapingParams,
+};
+
+pub fn run(args: &ShapeArgs) -> Result<()> {
+    let text = read_input_text(
+        args.text.as_deref(),
+        args.text_arg.as_deref(),
+        args.text_file.as_deref(),
+    )?;
+    let font = load_font_file(
+        args.font_file.as_deref(),
+        args.face_index,
+        args.face_name.as_deref(),
+        args.verbose,
+    )?;
+
+    let language = parse_language_hint(args.language.as_deref())?;
+    let script = parse_script_hint(&args.script)?;
+    let direction = resolve_direction(
+        &text,
+        &args.direction,
+        language.as_deref(),
+        script.as_deref(),
+    )?;
+
+    let params = ShapingParams {
+        size: parse_font_size(&args.font_size)?,
+        direction,
+        language,
+        script,
+        features: parse_features(&args.features)?,
+        variations: parse_variations(&args.instance)?,
+        letter_spacing: 0.0,
+        tab_stops: Default::default(),
+        number_substitution: Default::default(),
+        case: Default::default(),
+        cancel: None,
+    };
+
+    let shaper = select_shaper(&parse_backend_name(&args.shaper, "shaper")?)?;
+    if args.verbose {
+        eprintln!("Shaping with {} backend...", shaper.name());
+    }
+    let shaped = shaper.shape(&text, font.clone(), &params)?;
+
+    let output = if args.json {
+        match typf::renderers()
+            .create("json")?
+            .render(&shaped, font, &RenderParams::default())?
+        {
+            RenderOutput::Json(json) => json + "\n",
+            _ => return Err(TypfError::Other("JSON renderer produced no JSON".into())),
+        }
+    } else {
+        format_glyphs(&shaped) + "\n"
+    };
+
+    if let Some(ref path) = args.output_file {
+        File::create(path)?.write_all(output.as_bytes())?;
+    } else {
+        io::stdout().write_all(output.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// `hb-shape` style: `[gid=cluster@dx,dy+advance|...]`
+///
+/// Offsets are relative to the pen position and left out when zero, so
+/// output diffs cleanly against `hb-shape --no-glyph-names`.
+fn format_glyphs(shaped: &ShapingResult) -> String {
+    let mut pen = 0.0;
+    let glyphs: Vec<String> = shaped
+        .glyphs
+        .iter()
+        .map(|glyph| {
+            let (dx, dy) = (glyph.x - pen, glyph.y);
+            pen += glyph.advance;
+            let offset = if dx != 0.0 || dy != 0.0 {
+                format!("@{},{}", number(dx), number(dy))
+            } else {
+                String::new()
+            };
+            format!(
+                "{}={}{}+{}",
+                glyph.id,
+                glyph.cluster,
+                offset,
+                number(glyph.advance)
+            )
+        })
+        .collect();
+    format!("[{}]", glyphs.join("|"))
+}
+
+/// Whole numbers without a fraction, others to two decimals
+fn number(value: f32) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    if rounded.fract() == 0.0 {
+        format!("{}", rounded as i64)
+    } else {
+        format!("{:.2}", rounded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use typf_core::types::{Direction, PositionedGlyph};
+
+    fn glyph(id: u32, x: f32, y: f32, advance: f32, cluster: u32) -> PositionedGlyph {
+        PositionedGlyph {
+            id,
+            x,
+            y,
+            advance,
+            cluster,
+        }
+    }
+
+    #[test]
+    fn format_glyphs_when_offsets_zero_then_omitted() {
+        let shaped = ShapingResult {
+            glyphs: vec![
+                glyph(36, 0.0, 0.0, 600.0, 0),
+                glyph(57, 540.0, 0.0, 580.5, 1),
+                glyph(3, 1180.5, -20.0, 0.0, 1),
+            ],
+            advance_width: 1180.5,
+            advance_height: 1000.0,
+            direction: Direction::LeftToRight,
+        };
+        assert_eq!(
+            format_glyphs(&shaped),
+            "[36=0+600|57=1@-60,0+580.50|3=1@0,-20+0]"
+        );
+    }
+}
@@ -24,6 +24,8 @@ fn main() -> Result<()> {
     match cli.command {
         Commands::Info(args) => commands::info::run(&args),
         Commands::Render(args) => commands::render::run(&args),
+        Commands::Shape(args) => commands::shape::run(&args),
+        Commands::Inspect(args) => commands::inspect::run(&args),
         Commands::Batch(args) => commands::batch::run(&args),
     }
 }
@@ -3,6 +3,8 @@
 //! Integration tests for the typf CLI commands:
 //! - `info`: Display backend information
 //! - `render`: Render text to image
+//! - `shape`: Print shaped glyphs
+//! - `inspect`: Report a font's tables
 //! - `batch`: Process multiple rendering jobs
 //!
 //! Tests cover both success cases and failure cases (bad input, missing fonts).
@@ -646,6 +648,86 @@ fn test_render_corrupted_font_fails() {
     let _ = fs::remove_file(temp_font);
 }
 
+// ============================================================================
+// Shape and Inspect Command Tests
+// ============================================================================
+
+#[test]
+fn test_shape_prints_hb_style_glyphs() {
+    let font = test_font("NotoSans-Regular.ttf");
+    if !font.exists() {
+        eprintln!("Skipping test: font not found at {:?}", font);
+        return;
+    }
+
+    let output = Command::new(typf_binary())
+        .args(["shape", "Hello", "--font", font.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute typf shape");
+
+    assert!(
+        output.status.success(),
+        "shape should succeed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.trim();
+    assert!(line.starts_with('[') && line.ends_with(']'), "{}", line);
+    assert_eq!(line.split('|').count(), 5, "one entry per glyph: {}", line);
+}
+
+#[test]
+fn test_shape_json_lists_glyphs() {
+    let font = test_font("NotoSans-Regular.ttf");
+    if !font.exists() {
+        eprintln!("Skipping test: font not found at {:?}", font);
+        return;
+    }
+
+    let output = Command::new(typf_binary())
+        .args(["shape", "Hello", "-f", font.to_str().unwrap(), "--json"])
+        .output()
+        .expect("Failed to execute typf shape");
+
+    assert!(output.status.success(), "shape --json should succeed");
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("shape --json prints JSON");
+    assert_eq!(json["glyphs"].as_array().map(Vec::len), Some(5));
+}
+
+#[test]
+fn test_inspect_reports_color_tables() {
+    let font = test_font("Nabla-Regular-COLR.ttf");
+    if !font.exists() {
+        eprintln!("Skipping test: font not found at {:?}", font);
+        return;
+    }
+
+    let output = Command::new(typf_binary())
+        .args(["inspect", font.to_str().unwrap(), "--json"])
+        .output()
+        .expect("Failed to execute typf inspect");
+
+    assert!(output.status.success(), "inspect should succeed");
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("inspect --json prints JSON");
+    assert!(json["color"]["colr_version"].is_u64(), "{}", json);
+    assert!(json["color"]["cpal_palettes"].is_u64(), "{}", json);
+}
+
+#[test]
+fn test_inspect_missing_font_fails() {
+    let output = Command::new(typf_binary())
+        .args(["inspect", "/nonexistent/path/to/font.ttf"])
+        .output()
+        .expect("Failed to execute typf inspect");
+
+    assert!(
+        !output.status.success(),
+        "inspect with missing font should fail"
+    );
+}
+
 // ============================================================================
 // Batch Command Tests
 // ============================================================================
@@ -782,6 +864,8 @@ fn test_help() {
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("render"), "Should list render command");
     assert!(stdout.contains("info"), "Should list info command");
+    assert!(stdout.contains("shape"), "Should list shape command");
+    assert!(stdout.contains("inspect"), "Should list inspect command");
     assert!(stdout.contains("batch"), "Should list batch command");
 }
 
@@ -0,0 +1,343 @@
+//! Shaping and pixel comparison between two backends
+//
+// this_file: conformance/src/diff.rs
+
+use typf_core::types::{BitmapData, BitmapFormat, PixelLayout, PositionedGlyph, ShapingResult};
+use typf_core::Color;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// One glyph slot where the two shaping results disagree
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct GlyphMismatch {
+    /// Position in the glyph run
+    pub index: usize,
+    /// Left glyph id, `None` when the left run is shorter
+    pub left_id: Option<u32>,
+    /// Right glyph id, `None` when the right run is shorter
+    pub right_id: Option<u32>,
+    /// Left glyph origin
+    pub left_position: Option<(f32, f32)>,
+    /// Right glyph origin
+    pub right_position: Option<(f32, f32)>,
+}
+
+/// Glyph-level differences between two shaping results
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ShapingDiff {
+    /// Glyph count of the left run
+    pub left_glyphs: usize,
+    /// Glyph count of the right run
+    pub right_glyphs: usize,
+    /// Slots whose glyph ids differ, counting slots only one side has
+    pub id_mismatches: usize,
+    /// Slots with equal ids whose origins are further apart than the tolerance
+    pub position_mismatches: usize,
+    /// Largest origin distance along either axis among equal-id slots, in pixels
+    pub max_position_delta: f32,
+    /// Difference of the total advances, right minus left, in pixels
+    pub advance_delta: f32,
+    /// Every slot counted in `id_mismatches` or `position_mismatches`
+    pub mismatches: Vec<GlyphMismatch>,
+}
+
+impl ShapingDiff {
+    /// Compare two runs slot by slot; origins within `position_tolerance`
+    /// pixels count as equal
+    pub fn between(left: &ShapingResult, right: &ShapingResult, position_tolerance: f32) -> Self {
+        let slots = left.glyphs.len().max(right.glyphs.len());
+        let mut diff = Self {
+            left_glyphs: left.glyphs.len(),
+            right_glyphs: right.glyphs.len(),
+            id_mismatches: 0,
+            position_mismatches: 0,
+            max_position_delta: 0.0,
+            advance_delta: right.advance_width - left.advance_width,
+            mismatches: Vec::new(),
+        };
+
+        let origin = |glyph: &PositionedGlyph| (glyph.x, glyph.y);
+        for index in 0..slots {
+            let l = left.glyphs.get(index);
+            let r = right.glyphs.get(index);
+            let mismatch = match (l, r) {
+                (Some(l), Some(r)) if l.id == r.id => {
+                    let delta = (l.x - r.x).abs().max((l.y - r.y).abs());
+                    diff.max_position_delta = diff.max_position_delta.max(delta);
+                    let moved = delta > position_tolerance;
+                    diff.position_mismatches += usize::from(moved);
+                    moved
+                },
+                _ => {
+                    diff.id_mismatches += 1;
+                    true
+                },
+            };
+            if mismatch {
+                diff.mismatches.push(GlyphMismatch {
+                    index,
+                    left_id: l.map(|g| g.id),
+                    right_id: r.map(|g| g.id),
+                    left_position: l.map(origin),
+                    right_position: r.map(origin),
+                });
+            }
+        }
+        diff
+    }
+
+    /// True when both runs hold the same glyphs at the same places
+    pub fn is_identical(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Smallest rectangle holding every pixel with nonzero alpha
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct InkBounds {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl InkBounds {
+    /// Largest distance between matching edges of two boxes, in pixels
+    pub fn drift(&self, other: &InkBounds) -> u32 {
+        let right = |b: &InkBounds| b.x + b.width;
+        let bottom = |b: &InkBounds| b.y + b.height;
+        self.x
+            .abs_diff(other.x)
+            .max(self.y.abs_diff(other.y))
+            .max(right(self).abs_diff(right(other)))
+            .max(bottom(self).abs_diff(bottom(other)))
+    }
+}
+
+/// Pixel-level differences between two renderings
+///
+/// Both bitmaps are aligned at their top-left corner and compared over the
+/// union of their sizes; area only one side covers counts as transparent on
+/// the other. Values are RGBA channel units, 0–255.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PixelDiff {
+    /// Left bitmap size
+    pub left_size: (u32, u32),
+    /// Right bitmap size
+    pub right_size: (u32, u32),
+    /// Root mean square of the channel differences
+    pub rmse: f64,
+    /// Largest single channel difference
+    pub max_delta: u8,
+    /// Pixels where any channel differs
+    pub differing_pixels: usize,
+    /// Ink box of the left bitmap, `None` when it is blank
+    pub left_ink: Option<InkBounds>,
+    /// Ink box of the right bitmap, `None` when it is blank
+    pub right_ink: Option<InkBounds>,
+}
+
+impl PixelDiff {
+    /// Compare two bitmaps; coverage-only formats take on `foreground`
+    pub fn between(left: &BitmapData, right: &BitmapData, foreground: Color) -> Self {
+        let a = to_rgba(left, foreground);
+        let b = to_rgba(right, foreground);
+        let width = left.width.max(right.width);
+        let height = left.height.max(right.height);
+
+        let mut sum_sq = 0u64;
+        let mut max_delta = 0u8;
+        let mut differing_pixels = 0;
+        for y in 0..height {
+            for x in 0..width {
+                let pa = pixel(&a, left.width, left.height, x, y);
+                let pb = pixel(&b, right.width, right.height, x, y);
+                let mut differs = false;
+                for (ca, cb) in pa.iter().zip(pb) {
+                    let d = ca.abs_diff(cb);
+                    sum_sq += u64::from(d) * u64::from(d);
+                    max_delta = max_delta.max(d);
+                    differs |= d != 0;
+                }
+                differing_pixels += usize::from(differs);
+            }
+        }
+        let channels = u64::from(width) * u64::from(height) * 4;
+        let rmse = if channels == 0 {
+            0.0
+        } else {
+            (sum_sq as f64 / channels as f64).sqrt()
+        };
+
+        Self {
+            left_size: (left.width, left.height),
+            right_size: (right.width, right.height),
+            rmse,
+            max_delta,
+            differing_pixels,
+            left_ink: ink_bounds(&a, left.width, left.height),
+            right_ink: ink_bounds(&b, right.width, right.height),
+        }
+    }
+
+    /// How far the ink boxes moved, in pixels
+    ///
+    /// `Some(0)` when both bitmaps are blank and `None` when only one is,
+    /// since there is nothing to measure the drift against.
+    pub fn bbox_drift(&self) -> Option<u32> {
+        match (&self.left_ink, &self.right_ink) {
+            (Some(l), Some(r)) => Some(l.drift(r)),
+            (None, None) => Some(0),
+            _ => None,
+        }
+    }
+}
+
+/// Straight RGBA, 4 bytes per pixel, rows packed
+fn to_rgba(bitmap: &BitmapData, foreground: Color) -> Vec<u8> {
+    let straight = bitmap.to_layout(PixelLayout::RGBA);
+    let packed = straight.to_packed();
+    let bitmap = packed.as_ref();
+    let pixels = bitmap.width as usize * bitmap.height as usize;
+    let ink = |alpha: u8| [foreground.r, foreground.g, foreground.b, alpha];
+    let mut out = Vec::with_capacity(pixels * 4);
+    match bitmap.format {
+        BitmapFormat::Rgba8 => out.extend(bitmap.data.iter().take(pixels * 4)),
+        BitmapFormat::Rgb8 => {
+            for px in bitmap.data.chunks_exact(3).take(pixels) {
+                out.extend([px[0], px[1], px[2], 255]);
+            }
+        },
+        BitmapFormat::Gray8 => {
+            for &alpha in bitmap.data.iter().take(pixels) {
+                out.extend(ink(alpha));
+            }
+        },
+        BitmapFormat::Gray1 => {
+            let stride = (bitmap.width as usize).div_ceil(8);
+            for y in 0..bitmap.height as usize {
+                for x in 0..bitmap.width as usize {
+                    let byte = bitmap.data.get(y * stride + x / 8).copied().unwrap_or(0);
+                    let on = byte & (0x80 >> (x % 8)) != 0;
+                    out.extend(ink(if on { 255 } else { 0 }));
+                }
+            }
+        },
+    }
+    // Short buffers read as transparent rather than panicking
+    out.resize(pixels * 4, 0);
+    out
+}
+
+fn pixel(rgba: &[u8], width: u32, height: u32, x: u32, y: u32) -> [u8; 4] {
+    if x >= width || y >= height {
+        return [0; 4];
+    }
+    let i = (y as usize * width as usize + x as usize) * 4;
+    [rgba[i], rgba[i + 1], rgba[i + 2], rgba[i + 3]]
+}
+
+fn ink_bounds(rgba: &[u8], width: u32, height: u32) -> Option<InkBounds> {
+    let (mut x0, mut y0, mut x1, mut y1) = (u32::MAX, u32::MAX, 0, 0);
+    for y in 0..height {
+        for x in 0..width {
+            if pixel(rgba, width, height, x, y)[3] != 0 {
+                x0 = x0.min(x);
+                y0 = y0.min(y);
+                x1 = x1.max(x + 1);
+                y1 = y1.max(y + 1);
+            }
+        }
+    }
+    (x0 != u32::MAX).then(|| InkBounds {
+        x: x0,
+        y: y0,
+        width: x1 - x0,
+        height: y1 - y0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use typf_core::types::Direction;
+
+    fn run(glyphs: &[(u32, f32)]) -> ShapingResult {
+        ShapingResult {
+            glyphs: glyphs
+                .iter()
+                .map(|&(id, x)| PositionedGlyph {
+                    id,
+                    x,
+                    y: 0.0,
+                    advance: 10.0,
+                    cluster: 0,
+                })
+                .collect(),
+            advance_width: glyphs.len() as f32 * 10.0,
+            advance_height: 16.0,
+            direction: Direction::LeftToRight,
+        }
+    }
+
+    fn gray(width: u32, height: u32, lit: &[(u32, u32)]) -> BitmapData {
+        let mut data = vec![0; (width * height) as usize];
+        for &(x, y) in lit {
+            data[(y * width + x) as usize] = 255;
+        }
+        BitmapData {
+            width,
+            height,
+            format: BitmapFormat::Gray8,
+            stride: BitmapFormat::Gray8.row_bytes(width),
+            layout: PixelLayout::RGBA,
+            data,
+        }
+    }
+
+    #[test]
+    fn test_shaping_diff_when_ids_and_positions_differ_then_counted() {
+        let left = run(&[(1, 0.0), (2, 10.0), (3, 20.0)]);
+        let right = run(&[(1, 0.0), (2, 12.5), (4, 20.0), (5, 30.0)]);
+
+        let diff = ShapingDiff::between(&left, &right, 0.5);
+        assert_eq!(diff.id_mismatches, 2);
+        assert_eq!(diff.position_mismatches, 1);
+        assert_eq!(diff.max_position_delta, 2.5);
+        assert_eq!(diff.advance_delta, 10.0);
+        let indices: Vec<_> = diff.mismatches.iter().map(|m| m.index).collect();
+        assert_eq!(indices, [1, 2, 3]);
+        assert_eq!(diff.mismatches[2].left_id, None);
+
+        assert!(ShapingDiff::between(&left, &left, 0.0).is_identical());
+    }
+
+    #[test]
+    fn test_pixel_diff_when_ink_shifts_then_rmse_and_drift() {
+        let left = gray(4, 3, &[(1, 1)]);
+        let right = gray(5, 3, &[(2, 1)]);
+
+        let diff = PixelDiff::between(&left, &right, Color::rgba(0, 0, 0, 255));
+        assert_eq!(diff.differing_pixels, 2);
+        assert_eq!(diff.max_delta, 255);
+        // Two pixels differ by 255 in alpha only, over 5x3 pixels of 4 channels
+        let expected = (2.0 * 255.0f64.powi(2) / 60.0).sqrt();
+        assert!((diff.rmse - expected).abs() < 1e-9);
+        assert_eq!(diff.bbox_drift(), Some(1));
+
+        let same = PixelDiff::between(&left, &left, Color::rgba(0, 0, 0, 255));
+        assert_eq!(
+            (same.rmse, same.max_delta, same.differing_pixels),
+            (0.0, 0, 0)
+        );
+
+        let blank = gray(4, 3, &[]);
+        let one_sided = PixelDiff::between(&left, &blank, Color::rgba(0, 0, 0, 255));
+        assert_eq!(one_sided.bbox_drift(), None);
+    }
+}
@@ -0,0 +1,362 @@
+//! Cross-backend conformance checks for Typf.
+//!
+//! Two shaper/renderer pairs should turn the same text into the same glyphs
+//! and, give or take antialiasing, the same pixels. This crate renders a
+//! suite of [`TestCase`]s through both sides of a [`Harness`] and reports
+//! where they part ways:
+//!
+//! - shaping: glyph id mismatches, origin drift, and total advance drift
+//!   ([`ShapingDiff`]);
+//! - pixels: RMSE, largest channel delta, differing pixel count, and ink
+//!   bounding-box drift ([`PixelDiff`]).
+//!
+//! Any backend that implements the core traits plugs in, so the same harness
+//! checks HarfBuzz against DirectWrite in a Windows CI job or HarfRust against
+//! HarfBuzz anywhere:
+//!
+//! ```ignore
+//! use typf_conformance::{default_suite, Backend, Harness, Tolerance};
+//!
+//! let harness = Harness::new(
+//!     Backend::new(Arc::new(HarfBuzzShaper::new()), Arc::new(SkiaRenderer::new())),
+//!     Backend::new(Arc::new(HarfrustShaper::new()), Arc::new(SkiaRenderer::new())),
+//! )
+//! .with_tolerance(Tolerance::default());
+//!
+//! let report = harness.run(&font, &default_suite());
+//! println!("{report}");
+//! assert!(report.passed());
+//! ```
+//!
+//! With the `serde` feature the reports serialize, for CI artifacts.
+
+// this_file: conformance/src/lib.rs
+
+pub mod diff;
+pub mod suite;
+
+use std::fmt;
+use std::sync::Arc;
+
+use typf_core::{
+    traits::{FontRef, Renderer, Shaper},
+    types::{RenderOutput, ShapingResult},
+    RenderParams,
+};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+pub use diff::{GlyphMismatch, InkBounds, PixelDiff, ShapingDiff};
+pub use suite::{default_suite, TestCase};
+
+/// One side of a comparison: a shaper and the renderer that draws its output
+#[derive(Clone)]
+pub struct Backend {
+    label: String,
+    shaper: Arc<dyn Shaper>,
+    renderer: Arc<dyn Renderer>,
+}
+
+impl Backend {
+    /// Pair a shaper with a renderer, labelled `"<shaper>+<renderer>"`
+    pub fn new(shaper: Arc<dyn Shaper>, renderer: Arc<dyn Renderer>) -> Self {
+        Self {
+            label: format!("{}+{}", shaper.name(), renderer.name()),
+            shaper,
+            renderer,
+        }
+    }
+
+    /// Replace the label used in reports
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    /// Name shown in reports
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+impl fmt::Debug for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Backend")
+            .field("label", &self.label)
+            .finish()
+    }
+}
+
+/// How far the two sides may differ before a case fails
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Tolerance {
+    /// Glyph slots allowed to hold different ids
+    pub max_id_mismatches: usize,
+    /// Origins closer than this many pixels count as equal
+    pub position_tolerance: f32,
+    /// Largest allowed RMSE, in channel units
+    pub max_rmse: f64,
+    /// Largest allowed single channel difference
+    pub max_pixel_delta: u8,
+    /// Largest allowed ink bounding-box edge drift, in pixels
+    pub max_bbox_drift: u32,
+}
+
+impl Tolerance {
+    /// Identical glyphs and identical pixels
+    pub fn exact() -> Self {
+        Self {
+            max_id_mismatches: 0,
+            position_tolerance: 0.0,
+            max_rmse: 0.0,
+            max_pixel_delta: 0,
+            max_bbox_drift: 0,
+        }
+    }
+}
+
+impl Default for Tolerance {
+    /// Same glyphs within half a pixel; pixels may differ by antialiasing
+    /// (any single delta, low RMSE) and ink may shift by one pixel
+    fn default() -> Self {
+        Self {
+            max_id_mismatches: 0,
+            position_tolerance: 0.5,
+            max_rmse: 8.0,
+            max_pixel_delta: u8::MAX,
+            max_bbox_drift: 1,
+        }
+    }
+}
+
+/// What happened to one [`TestCase`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CaseReport {
+    /// The case's name
+    pub name: String,
+    /// Present when both sides shaped
+    pub shaping: Option<ShapingDiff>,
+    /// Present when both sides rendered to bitmaps
+    pub pixels: Option<PixelDiff>,
+    /// Backend failures, prefixed with the failing side's label
+    pub errors: Vec<String>,
+    /// Tolerance violations, one line each
+    pub violations: Vec<String>,
+}
+
+impl CaseReport {
+    /// True when nothing failed and everything is within tolerance
+    pub fn passed(&self) -> bool {
+        self.errors.is_empty() && self.violations.is_empty()
+    }
+
+    fn check(&mut self, tolerance: &Tolerance) {
+        if let Some(shaping) = &self.shaping {
+            if shaping.id_mismatches > tolerance.max_id_mismatches {
+                self.violations.push(format!(
+                    "{} glyph id mismatches (allowed {})",
+                    shaping.id_mismatches, tolerance.max_id_mismatches
+                ));
+            }
+            if shaping.position_mismatches > 0 {
+                self.violations.push(format!(
+                    "{} glyphs moved, up to {:.2}px (allowed {:.2}px)",
+                    shaping.position_mismatches,
+                    shaping.max_position_delta,
+                    tolerance.position_tolerance
+                ));
+            }
+        }
+        if let Some(pixels) = &self.pixels {
+            if pixels.rmse > tolerance.max_rmse {
+                self.violations.push(format!(
+                    "RMSE {:.2} (allowed {:.2})",
+                    pixels.rmse, tolerance.max_rmse
+                ));
+            }
+            if pixels.max_delta > tolerance.max_pixel_delta {
+                self.violations.push(format!(
+                    "max pixel delta {} (allowed {})",
+                    pixels.max_delta, tolerance.max_pixel_delta
+                ));
+            }
+            match pixels.bbox_drift() {
+                Some(drift) if drift <= tolerance.max_bbox_drift => {},
+                Some(drift) => self.violations.push(format!(
+                    "ink bounds drift {drift}px (allowed {}px)",
+                    tolerance.max_bbox_drift
+                )),
+                None => self
+                    .violations
+                    .push("only one side left any ink".to_string()),
+            }
+        }
+    }
+}
+
+/// Results of a [`Harness::run`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ConformanceReport {
+    /// Label of the left backend
+    pub left: String,
+    /// Label of the right backend
+    pub right: String,
+    /// Limits the cases were judged against
+    pub tolerance: Tolerance,
+    /// One entry per case, in suite order
+    pub cases: Vec<CaseReport>,
+}
+
+impl ConformanceReport {
+    /// True when every case passed
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(CaseReport::passed)
+    }
+
+    /// Cases that failed
+    pub fn failures(&self) -> impl Iterator<Item = &CaseReport> {
+        self.cases.iter().filter(|case| !case.passed())
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let passed = self.cases.iter().filter(|case| case.passed()).count();
+        writeln!(
+            f,
+            "{} vs {}: {passed}/{} cases passed",
+            self.left,
+            self.right,
+            self.cases.len()
+        )?;
+        for case in &self.cases {
+            write!(
+                f,
+                "{} {:<24}",
+                if case.passed() { "PASS" } else { "FAIL" },
+                case.name
+            )?;
+            if let Some(shaping) = &case.shaping {
+                write!(
+                    f,
+                    " ids {:>2}  moved {:>2} (max {:.2}px)",
+                    shaping.id_mismatches, shaping.position_mismatches, shaping.max_position_delta
+                )?;
+            }
+            if let Some(pixels) = &case.pixels {
+                write!(f, "  rmse {:6.2}  max {:>3}", pixels.rmse, pixels.max_delta)?;
+                match pixels.bbox_drift() {
+                    Some(drift) => write!(f, "  drift {drift}px")?,
+                    None => write!(f, "  drift n/a")?,
+                }
+            }
+            writeln!(f)?;
+            for line in case.errors.iter().chain(&case.violations) {
+                writeln!(f, "     {line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders test cases through two backends and compares the results
+#[derive(Debug, Clone)]
+pub struct Harness {
+    left: Backend,
+    right: Backend,
+    render_params: RenderParams,
+    tolerance: Tolerance,
+}
+
+impl Harness {
+    /// Compare `left` against `right` with default render settings and
+    /// [`Tolerance::default`]
+    pub fn new(left: Backend, right: Backend) -> Self {
+        Self {
+            left,
+            right,
+            render_params: RenderParams::default(),
+            tolerance: Tolerance::default(),
+        }
+    }
+
+    /// Judge cases against `tolerance`
+    pub fn with_tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Render both sides with `params`
+    pub fn with_render_params(mut self, params: RenderParams) -> Self {
+        self.render_params = params;
+        self
+    }
+
+    /// Run every case with `font`
+    pub fn run(&self, font: &Arc<dyn FontRef>, cases: &[TestCase]) -> ConformanceReport {
+        ConformanceReport {
+            left: self.left.label.clone(),
+            right: self.right.label.clone(),
+            tolerance: self.tolerance,
+            cases: cases.iter().map(|case| self.run_case(font, case)).collect(),
+        }
+    }
+
+    /// Run a single case with `font`
+    pub fn run_case(&self, font: &Arc<dyn FontRef>, case: &TestCase) -> CaseReport {
+        let mut report = CaseReport {
+            name: case.name.clone(),
+            shaping: None,
+            pixels: None,
+            errors: Vec::new(),
+            violations: Vec::new(),
+        };
+
+        let shape = |backend: &Backend, errors: &mut Vec<String>| {
+            backend
+                .shaper
+                .shape(&case.text, font.clone(), &case.params)
+                .map_err(|e| errors.push(format!("{}: shaping failed: {e}", backend.label)))
+                .ok()
+        };
+        let left = shape(&self.left, &mut report.errors);
+        let right = shape(&self.right, &mut report.errors);
+        if let (Some(l), Some(r)) = (&left, &right) {
+            report.shaping = Some(ShapingDiff::between(
+                l,
+                r,
+                self.tolerance.position_tolerance,
+            ));
+        }
+
+        let render =
+            |backend: &Backend, shaped: Option<ShapingResult>, errors: &mut Vec<String>| {
+                match backend
+                    .renderer
+                    .render(&shaped?, font.clone(), &self.render_params)
+                {
+                    Ok(RenderOutput::Bitmap(bitmap)) => Some(bitmap),
+                    Ok(_) => {
+                        errors.push(format!("{}: renderer produced no bitmap", backend.label));
+                        None
+                    },
+                    Err(e) => {
+                        errors.push(format!("{}: rendering failed: {e}", backend.label));
+                        None
+                    },
+                }
+            };
+        let left = render(&self.left, left, &mut report.errors);
+        let right = render(&self.right, right, &mut report.errors);
+        if let (Some(l), Some(r)) = (&left, &right) {
+            report.pixels = Some(PixelDiff::between(l, r, self.render_params.foreground));
+        }
+
+        report.check(&self.tolerance);
+        report
+    }
+}
@@ -0,0 +1,104 @@
+//! Text cases to push through both backends
+//
+// this_file: conformance/src/suite.rs
+
+use typf_core::types::Direction;
+use typf_core::ShapingParams;
+
+/// One string plus the shaping settings it is rendered with
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    /// Short identifier shown in reports, e.g. `"arabic-joining"`
+    pub name: String,
+    /// Text to shape and render
+    pub text: String,
+    /// Size, direction, language, script, features, and variations
+    pub params: ShapingParams,
+}
+
+impl TestCase {
+    /// A left-to-right case with default shaping settings at `size` pixels
+    pub fn new(name: impl Into<String>, text: impl Into<String>, size: f32) -> Self {
+        Self {
+            name: name.into(),
+            text: text.into(),
+            params: ShapingParams {
+                size,
+                ..ShapingParams::default()
+            },
+        }
+    }
+
+    /// Shape right to left with the given script and language tags
+    pub fn rtl(mut self, script: &str, language: &str) -> Self {
+        self.params.direction = Direction::RightToLeft;
+        self.with_script(script, language)
+    }
+
+    /// Set the script and language tags
+    pub fn with_script(mut self, script: &str, language: &str) -> Self {
+        self.params.script = Some(script.to_string());
+        self.params.language = Some(language.to_string());
+        self
+    }
+
+    /// Add an OpenType feature setting such as `("liga", 0)`
+    pub fn with_feature(mut self, tag: &str, value: u32) -> Self {
+        self.params.features.push((tag.to_string(), value));
+        self
+    }
+
+    /// Add a variable font axis setting such as `("wght", 700.0)`
+    pub fn with_variation(mut self, tag: &str, value: f32) -> Self {
+        self.params.variations.push((tag.to_string(), value));
+        self
+    }
+}
+
+/// A broad starter suite covering the usual parity trouble spots
+///
+/// Kerning, ligatures, marks, joining and reordering scripts, RTL runs, and
+/// CJK. A font without a script's glyphs still exercises `.notdef` handling,
+/// so run the suite with fonts that cover the scripts you care about.
+pub fn default_suite() -> Vec<TestCase> {
+    vec![
+        TestCase::new("latin-basic", "The quick brown fox", 32.0),
+        TestCase::new("latin-kerning", "AVATAR Toyota WAVE", 48.0),
+        TestCase::new("latin-ligatures", "office affluent fjord", 40.0),
+        TestCase::new("latin-no-ligatures", "office affluent", 40.0).with_feature("liga", 0),
+        TestCase::new("digits-punctuation", "0123456789 ($1,234.56)!?", 28.0),
+        TestCase::new(
+            "combining-marks",
+            "e\u{301} a\u{308} n\u{303} o\u{302}\u{323}",
+            40.0,
+        ),
+        TestCase::new("small-size", "Hamburgefonstiv", 9.0),
+        TestCase::new("large-size", "Ag", 160.0),
+        TestCase::new("arabic-joining", "مرحبا بالعالم", 40.0).rtl("Arab", "ar"),
+        TestCase::new("hebrew", "שלום עולם", 40.0).rtl("Hebr", "he"),
+        TestCase::new("devanagari-reordering", "हिन्दी क्षत्रिय", 40.0).with_script("Deva", "hi"),
+        TestCase::new("thai", "ภาษาไทย", 40.0).with_script("Thai", "th"),
+        TestCase::new("cjk", "漢字かなカナ한글", 40.0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_suite_when_built_then_names_unique_and_rtl_set() {
+        let suite = default_suite();
+        let mut names: Vec<_> = suite.iter().map(|case| case.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), suite.len());
+
+        let arabic = suite.iter().find(|case| case.name == "arabic-joining");
+        assert_eq!(
+            arabic.map(|case| case.params.direction),
+            Some(Direction::RightToLeft)
+        );
+        assert!(suite.iter().all(|case| case.params.size > 0.0));
+    }
+}
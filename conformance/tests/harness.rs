@@ -0,0 +1,95 @@
+//! Runs the harness over real backends and a real font
+
+#![allow(clippy::unwrap_used)]
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use typf_conformance::{default_suite, Backend, Harness, TestCase, Tolerance};
+use typf_core::traits::FontRef;
+use typf_fontdb::TypfFontFace;
+use typf_render_opixa::OpixaRenderer;
+use typf_render_zeno::ZenoRenderer;
+use typf_shape_hr::HarfrustShaper;
+use typf_shape_none::NoneShaper;
+
+fn load_font() -> Option<Arc<dyn FontRef>> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../test-fonts")
+        .join("NotoSans-Regular.ttf");
+    if !path.exists() {
+        eprintln!("skipping: {} not found", path.display());
+        return None;
+    }
+    Some(Arc::new(TypfFontFace::from_file(&path).unwrap()))
+}
+
+fn harfrust_with(renderer: Arc<dyn typf_core::traits::Renderer>) -> Backend {
+    Backend::new(Arc::new(HarfrustShaper::new()), renderer)
+}
+
+#[test]
+fn same_backend_on_both_sides_is_exact() {
+    let Some(font) = load_font() else {
+        return;
+    };
+    let backend = harfrust_with(Arc::new(OpixaRenderer::new()));
+    let harness = Harness::new(backend.clone(), backend.with_label("again"))
+        .with_tolerance(Tolerance::exact());
+
+    let report = harness.run(&font, &default_suite());
+    assert!(report.passed(), "{report}");
+    assert_eq!(report.cases.len(), default_suite().len());
+    for case in &report.cases {
+        let pixels = case.pixels.as_ref().unwrap();
+        assert_eq!(pixels.rmse, 0.0);
+        assert!(case.shaping.as_ref().unwrap().is_identical());
+    }
+}
+
+#[test]
+fn passthrough_shaper_against_harfrust_reports_moved_glyphs() {
+    let Some(font) = load_font() else {
+        return;
+    };
+    let renderer: Arc<dyn typf_core::traits::Renderer> = Arc::new(OpixaRenderer::new());
+    let harness = Harness::new(
+        Backend::new(Arc::new(NoneShaper::new()), renderer.clone()),
+        harfrust_with(renderer),
+    );
+
+    // Same cmap lookups, but the passthrough shaper ignores real advances
+    let case = TestCase::new("advances", "AVAVAV", 64.0);
+    let report = harness.run(&font, &[case]);
+    let shaping = report.cases[0].shaping.as_ref().unwrap();
+    assert_eq!(shaping.id_mismatches, 0);
+    assert!(shaping.position_mismatches > 0);
+    assert_eq!(
+        shaping.mismatches[0].index, 1,
+        "both runs start at the origin"
+    );
+    assert_ne!(shaping.advance_delta, 0.0);
+    assert!(!report.passed());
+    assert!(report.to_string().contains("FAIL advances"));
+}
+
+#[test]
+fn different_rasterizers_share_shaping_and_ink_position() {
+    let Some(font) = load_font() else {
+        return;
+    };
+    let harness = Harness::new(
+        harfrust_with(Arc::new(OpixaRenderer::new())),
+        harfrust_with(Arc::new(ZenoRenderer::new())),
+    );
+
+    let report = harness.run(&font, &[TestCase::new("latin", "Hamburgefonstiv", 48.0)]);
+    let case = &report.cases[0];
+    assert!(case.errors.is_empty(), "{:?}", case.errors);
+    assert!(case.shaping.as_ref().unwrap().is_identical());
+    let pixels = case.pixels.as_ref().unwrap();
+    assert!(
+        pixels.bbox_drift().is_some_and(|drift| drift <= 2),
+        "{report}"
+    );
+}
@@ -0,0 +1,184 @@
+//! Memory budgets for rendering calls.
+//!
+//! The bitmap limits (`TYPF_MAX_BITMAP_*`) bound each canvas, but not what a
+//! call allocates in total: glyph bitmaps pile up before compositing, and
+//! every COLR layer is a pixmap as large as its glyph. A [`MemoryBudget`] in
+//! [`RenderParams::memory_budget`](crate::RenderParams::memory_budget) caps
+//! the bytes a call may hold at once. Renderers take a [`Reservation`] before
+//! allocating canvases, glyph bitmaps, and color layers; when one would pass
+//! the limit the call fails with [`RenderError::BudgetExceeded`].
+//!
+//! Reservations give their bytes back when dropped, so the budget tracks
+//! memory in use, and [`MemoryBudget::peak`] reports the high-water mark.
+//!
+//! ```
+//! use typf_core::budget::MemoryBudget;
+//!
+//! let budget = MemoryBudget::new(1024);
+//! let canvas = budget.reserve(800).expect("fits");
+//! assert!(budget.reserve(800).is_err());
+//! drop(canvas);
+//! assert_eq!(budget.in_use(), 0);
+//! assert_eq!(budget.peak(), 800);
+//! ```
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::error::RenderError;
+
+/// A byte limit shared by every allocation it is asked about
+///
+/// Clones share the count, so one budget can cap a single call or all the
+/// calls a server has in flight.
+#[derive(Debug, Clone)]
+pub struct MemoryBudget {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    limit: usize,
+    in_use: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl MemoryBudget {
+    /// A budget of `limit` bytes
+    pub fn new(limit: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                limit,
+                in_use: AtomicUsize::new(0),
+                peak: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Bytes that may be reserved at once
+    pub fn limit(&self) -> usize {
+        self.inner.limit
+    }
+
+    /// Bytes reserved right now
+    pub fn in_use(&self) -> usize {
+        self.inner.in_use.load(Ordering::Relaxed)
+    }
+
+    /// Most bytes ever reserved at once
+    pub fn peak(&self) -> usize {
+        self.inner.peak.load(Ordering::Relaxed)
+    }
+
+    /// Reserve `bytes`, held until the reservation is dropped
+    pub fn reserve(&self, bytes: usize) -> Result<Reservation, RenderError> {
+        let mut reservation = Reservation {
+            budget: Some(self.clone()),
+            bytes: 0,
+        };
+        reservation.grow(bytes)?;
+        Ok(reservation)
+    }
+}
+
+/// Bytes held against a budget, returned when dropped
+///
+/// The default reservation has no budget: it counts bytes but never fails.
+#[derive(Debug, Default)]
+pub struct Reservation {
+    budget: Option<MemoryBudget>,
+    bytes: usize,
+}
+
+impl Reservation {
+    /// Bytes held so far
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Hold `bytes` more, or fail without holding any of them
+    pub fn grow(&mut self, bytes: usize) -> Result<(), RenderError> {
+        if let Some(budget) = &self.budget {
+            let inner = &budget.inner;
+            let after = inner
+                .in_use
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                    used.checked_add(bytes)
+                        .filter(|&total| total <= inner.limit)
+                })
+                .map_err(|in_use| RenderError::BudgetExceeded {
+                    requested: bytes,
+                    in_use,
+                    limit: inner.limit,
+                })?
+                + bytes;
+            inner.peak.fetch_max(after, Ordering::Relaxed);
+        }
+        self.bytes = self.bytes.saturating_add(bytes);
+        Ok(())
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        if let Some(budget) = &self.budget {
+            budget.inner.in_use.fetch_sub(self.bytes, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Reserve `bytes` against an optional budget; no budget never fails
+pub fn reserve(budget: Option<&MemoryBudget>, bytes: usize) -> Result<Reservation, RenderError> {
+    match budget {
+        Some(budget) => budget.reserve(bytes),
+        None => Ok(Reservation {
+            budget: None,
+            bytes,
+        }),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grow_when_past_limit_then_budget_exceeded_and_nothing_held() {
+        let budget = MemoryBudget::new(100);
+        let mut reservation = budget.reserve(60).expect("fits");
+
+        let err = reservation.grow(50).expect_err("over budget");
+        assert!(matches!(
+            err,
+            RenderError::BudgetExceeded {
+                requested: 50,
+                in_use: 60,
+                limit: 100
+            }
+        ));
+        assert_eq!(reservation.bytes(), 60);
+        assert_eq!(budget.in_use(), 60);
+    }
+
+    #[test]
+    fn test_drop_when_reservations_released_then_peak_kept() {
+        let budget = MemoryBudget::new(100);
+        let first = budget.reserve(30).expect("fits");
+        let shared = budget.clone();
+        let second = shared.reserve(70).expect("fits");
+        drop(first);
+        drop(second);
+
+        assert_eq!(budget.in_use(), 0);
+        assert_eq!(budget.peak(), 100);
+        assert!(budget.reserve(100).is_ok());
+    }
+
+    #[test]
+    fn test_reserve_when_no_budget_then_counts_without_limit() {
+        let mut reservation = reserve(None, usize::MAX / 2).expect("unbudgeted");
+        reservation.grow(10).expect("unbudgeted");
+        assert_eq!(reservation.bytes(), usize::MAX / 2 + 10);
+    }
+}
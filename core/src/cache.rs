@@ -10,15 +10,37 @@
 //!
 //! This prevents unbounded memory growth when processing many unique fonts
 //! (e.g., font matching across hundreds of candidates).
+//!
+//! **Idle trimming**: Long-running daemons pick up entries for fonts used once.
+//! Every entry records when it was last read, so [`TrimIdle::trim_idle`] can
+//! drop what nobody touched recently, and caches built `with_ttl` expire
+//! entries a fixed time after insertion. Hosts reacting to memory pressure
+//! call [`trim_idle_caches`], which trims the global [`CacheManager`] and every
+//! cache handed to [`register_trim_hook`].
+//!
+//! **WebAssembly**: Moka doesn't run on `wasm32`, so there the same caches
+//! sit on a small single-threaded LRU with identical byte accounting but no
+//! TinyLFU admission.
 
 use crate::glyph_cache::GlyphCacheKey;
 use crate::shaping_cache::ShapingCacheKey;
-use moka::sync::Cache;
+#[cfg(not(target_arch = "wasm32"))]
+use moka::{policy::EvictionPolicy, sync::Cache};
 use parking_lot::RwLock;
 use std::hash::Hash;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::OnceLock;
-use std::time::{Duration, Instant};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use wasm::{Cache, EvictionPolicy};
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+mod wasm;
 
 /// Global, lazily initialized instance of the cache manager.
 static CACHE_MANAGER: OnceLock<CacheManager> = OnceLock::new();
@@ -31,6 +53,222 @@ pub fn get_cache_manager() -> &'static CacheManager {
     CACHE_MANAGER.get_or_init(CacheManager::new)
 }
 
+/// Idle time after which Moka evicts an entry on its own: 10 minutes
+const DEFAULT_TIME_TO_IDLE: Duration = Duration::from_secs(600);
+
+/// Caches that can drop entries nobody has read for a while.
+pub trait TrimIdle: Send + Sync {
+    /// Evict every entry not read (or inserted) within `max_age`.
+    ///
+    /// Returns how many entries were evicted.
+    fn trim_idle(&self, max_age: Duration) -> usize;
+}
+
+/// Caches registered for [`trim_idle_caches`]; dropped caches fall out.
+static TRIM_HOOKS: Mutex<Vec<Weak<dyn TrimIdle>>> = Mutex::new(Vec::new());
+
+/// Include `cache` in future [`trim_idle_caches`] calls.
+///
+/// Only a weak reference is kept, so registering never keeps a cache alive.
+pub fn register_trim_hook<T: TrimIdle + 'static>(cache: &Arc<T>) {
+    register_in(&TRIM_HOOKS, cache);
+}
+
+fn register_in<T: TrimIdle + 'static>(hooks: &Mutex<Vec<Weak<dyn TrimIdle>>>, cache: &Arc<T>) {
+    let cache: Arc<dyn TrimIdle> = cache.clone();
+    if let Ok(mut hooks) = hooks.lock() {
+        hooks.push(Arc::downgrade(&cache));
+    }
+}
+
+/// Trim idle entries from the global cache manager and every registered cache.
+///
+/// Meant for a host's memory-pressure callback or a periodic housekeeping
+/// timer. Returns the total number of entries evicted.
+pub fn trim_idle_caches(max_age: Duration) -> usize {
+    let manager = CACHE_MANAGER.get().map(|manager| manager as &dyn TrimIdle);
+    trim_all(manager, &TRIM_HOOKS, max_age)
+}
+
+/// Trim `manager` and every live cache in `hooks`
+fn trim_all(
+    manager: Option<&dyn TrimIdle>,
+    hooks: &Mutex<Vec<Weak<dyn TrimIdle>>>,
+    max_age: Duration,
+) -> usize {
+    let mut evicted = manager.map_or(0, |manager| manager.trim_idle(max_age));
+
+    let live: Vec<Arc<dyn TrimIdle>> = match hooks.lock() {
+        Ok(mut hooks) => {
+            hooks.retain(|hook| hook.strong_count() > 0);
+            hooks.iter().filter_map(Weak::upgrade).collect()
+        },
+        Err(_) => Vec::new(),
+    };
+    for cache in live {
+        evicted += cache.trim_idle(max_age);
+    }
+    evicted
+}
+
+/// Milliseconds since the first cache access in this process
+fn now_millis() -> u64 {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    EPOCH.get_or_init(Instant::now).elapsed().as_millis() as u64
+}
+
+/// Where a cache reads the time for its idle and TTL checks
+///
+/// Caches use the process clock. A [`manual`](Self::manual) clock stands
+/// still until it is [`advance`](Self::advance)d, so tests can age entries
+/// without sleeping.
+#[derive(Debug, Clone, Default)]
+pub struct CacheClock {
+    manual: Option<Arc<AtomicU64>>,
+}
+
+impl CacheClock {
+    /// A clock that only moves when advanced, starting at zero
+    pub fn manual() -> Self {
+        Self {
+            manual: Some(Arc::new(AtomicU64::new(0))),
+        }
+    }
+
+    /// Move a manual clock forward by `by`; the process clock ignores this
+    pub fn advance(&self, by: Duration) {
+        if let Some(millis) = &self.manual {
+            millis.fetch_add(by.as_millis() as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Current time in milliseconds, from an arbitrary epoch
+    pub fn now_millis(&self) -> u64 {
+        match &self.manual {
+            Some(millis) => millis.load(Ordering::Relaxed),
+            None => now_millis(),
+        }
+    }
+}
+
+/// A cached value plus when it was inserted and last read
+#[derive(Clone)]
+pub(crate) struct Stamped<V> {
+    value: V,
+    inserted: u64,
+    last_access: Arc<AtomicU64>,
+}
+
+/// Moka (or, on wasm32, the LRU stand-in) cache of stamped values, with
+/// idle and TTL checks on its own [`CacheClock`]
+pub(crate) struct StampedCache<K: Hash + Eq, V> {
+    cache: Cache<K, Stamped<V>>,
+    ttl: Option<u64>,
+    clock: CacheClock,
+}
+
+impl<K, V> StampedCache<K, V>
+where
+    K: Hash + Eq + Send + Sync + Clone + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    fn new(cache: Cache<K, Stamped<V>>, ttl: Option<Duration>) -> Self {
+        Self {
+            cache,
+            ttl: ttl.map(|ttl| ttl.as_millis() as u64),
+            clock: CacheClock::default(),
+        }
+    }
+
+    pub(crate) fn with_clock(mut self, clock: CacheClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The value for `key`, unless it outlived the TTL; a hit counts as a read
+    pub(crate) fn get(&self, key: &K) -> Option<V> {
+        let entry = self.cache.get(key)?;
+        let now = self.clock.now_millis();
+        if self.expired(&entry, now) {
+            self.cache.invalidate(key);
+            return None;
+        }
+        entry.last_access.store(now, Ordering::Relaxed);
+        Some(entry.value)
+    }
+
+    pub(crate) fn insert(&self, key: K, value: V) {
+        let now = self.clock.now_millis();
+        let entry = Stamped {
+            value,
+            inserted: now,
+            last_access: Arc::new(AtomicU64::new(now)),
+        };
+        self.cache.insert(key, entry);
+    }
+
+    fn expired(&self, entry: &Stamped<V>, now: u64) -> bool {
+        self.ttl
+            .is_some_and(|ttl| now.saturating_sub(entry.inserted) >= ttl)
+    }
+
+    /// Invalidate every entry idle for longer than `max_age`, or past its TTL
+    pub(crate) fn evict_idle(&self, max_age: Duration) -> usize {
+        let now = self.clock.now_millis();
+        let max_age = max_age.as_millis() as u64;
+        self.evict(|_, entry| {
+            now.saturating_sub(entry.last_access.load(Ordering::Relaxed)) > max_age
+                || self.expired(entry, now)
+        })
+    }
+
+    /// Invalidate every entry whose key `stale` picks
+    pub(crate) fn evict_where(&self, stale: impl Fn(&K) -> bool) -> usize {
+        self.evict(|key, _| stale(key))
+    }
+
+    fn evict(&self, stale: impl Fn(&K, &Stamped<V>) -> bool) -> usize {
+        let keys: Vec<Arc<K>> = self
+            .cache
+            .iter()
+            .filter(|(key, entry)| stale(key, entry))
+            .map(|(key, _)| key)
+            .collect();
+        for key in &keys {
+            self.cache.invalidate(key.as_ref());
+        }
+        self.cache.run_pending_tasks();
+        keys.len()
+    }
+
+    pub(crate) fn invalidate_all(&self) {
+        self.cache.invalidate_all();
+    }
+
+    pub(crate) fn run_pending_tasks(&self) {
+        self.cache.run_pending_tasks();
+    }
+
+    pub(crate) fn entry_count(&self) -> u64 {
+        self.cache.entry_count()
+    }
+
+    pub(crate) fn weighted_size(&self) -> u64 {
+        self.cache.weighted_size()
+    }
+}
+
+/// Caches holding entries derived from particular font files.
+///
+/// A font database that reloads or loses a file hands its old bytes to
+/// every subscribed cache, which drops what it made from them.
+pub trait InvalidateFont: Send + Sync {
+    /// Evict every entry made from the font `font_data`.
+    ///
+    /// Returns how many entries were evicted.
+    fn invalidate_font(&self, font_data: &[u8]) -> usize;
+}
+
 /// Default cache byte limit: 512 MB
 ///
 /// Can be overridden via `TYPF_CACHE_MAX_BYTES` environment variable.
@@ -49,7 +287,7 @@ where
     K: Hash + Eq + Send + Sync + Clone + 'static,
     V: Clone + Send + Sync + 'static,
 {
-    cache: Cache<K, V>,
+    cache: StampedCache<K, V>,
     stats: Arc<RwLock<CacheMetrics>>,
 }
 
@@ -77,21 +315,39 @@ where
     /// pre-Moka two-tier design; they are simply summed into one Moka TinyLFU
     /// cache, which manages hot/cold admission internally.
     pub fn new(l1_size: usize, l2_size: usize) -> Self {
+        Self::build(l1_size, l2_size, None)
+    }
+
+    /// Like [`new`](Self::new), but every entry also expires `ttl` after insertion.
+    ///
+    /// Reads don't extend the lifetime, so even hot entries are refreshed
+    /// once per `ttl`.
+    pub fn with_ttl(l1_size: usize, l2_size: usize, ttl: Duration) -> Self {
+        Self::build(l1_size, l2_size, Some(ttl))
+    }
+
+    fn build(l1_size: usize, l2_size: usize, ttl: Option<Duration>) -> Self {
         let total_capacity = (l1_size + l2_size) as u64;
         let cache = Cache::builder()
             .max_capacity(total_capacity)
             // TinyLFU is the default, but be explicit
-            .eviction_policy(moka::policy::EvictionPolicy::tiny_lfu())
+            .eviction_policy(EvictionPolicy::tiny_lfu())
             // Time-to-idle: evict entries not accessed for 10 minutes
-            .time_to_idle(Duration::from_secs(600))
+            .time_to_idle(DEFAULT_TIME_TO_IDLE)
             .build();
 
         Self {
-            cache,
+            cache: StampedCache::new(cache, ttl),
             stats: Arc::new(RwLock::new(CacheMetrics::default())),
         }
     }
 
+    /// Read idle and TTL times from `clock` instead of the process clock
+    pub fn with_clock(mut self, clock: CacheClock) -> Self {
+        self.cache = self.cache.with_clock(clock);
+        self
+    }
+
     /// Look up a cached value
     ///
     /// TinyLFU admission policy means frequently-accessed keys stay cached
@@ -164,6 +420,11 @@ where
         *stats = CacheMetrics::default();
     }
 
+    /// Drop every entry whose key `stale` picks; returns how many went.
+    pub fn invalidate_where(&self, stale: impl Fn(&K) -> bool) -> usize {
+        self.cache.evict_where(stale)
+    }
+
     /// Force pending operations to complete (for testing)
     #[cfg(test)]
     pub fn sync(&self) {
@@ -171,6 +432,16 @@ where
     }
 }
 
+impl<K, V> TrimIdle for MultiLevelCache<K, V>
+where
+    K: Hash + Eq + Send + Sync + Clone + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    fn trim_idle(&self, max_age: Duration) -> usize {
+        self.cache.evict_idle(max_age)
+    }
+}
+
 /// Basic cache statistics
 #[derive(Debug, Clone)]
 pub struct CacheStats {
@@ -219,6 +490,29 @@ pub fn get_cache_max_bytes() -> u64 {
         .unwrap_or(DEFAULT_CACHE_MAX_BYTES)
 }
 
+/// A TinyLFU cache holding at most `max_bytes` as measured by `weight`,
+/// optionally expiring entries `ttl` after insertion
+pub(crate) fn byte_weighted<K, V>(
+    max_bytes: u64,
+    ttl: Option<Duration>,
+    weight: fn(&V) -> usize,
+) -> StampedCache<K, V>
+where
+    K: Hash + Eq + Send + Sync + Clone + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    let cache = Cache::builder()
+        .max_capacity(max_bytes)
+        .weigher(move |_key: &K, entry: &Stamped<V>| {
+            // Weight = byte size, minimum 1 to avoid division issues
+            weight(&entry.value).clamp(1, u32::MAX as usize) as u32
+        })
+        .eviction_policy(EvictionPolicy::tiny_lfu())
+        .time_to_idle(DEFAULT_TIME_TO_IDLE)
+        .build();
+    StampedCache::new(cache, ttl)
+}
+
 /// Byte-weighted cache for RenderOutput values
 ///
 /// Unlike entry-count caches, this tracks actual memory usage.
@@ -227,7 +521,7 @@ pub struct RenderOutputCache<K>
 where
     K: Hash + Eq + Send + Sync + Clone + 'static,
 {
-    cache: Cache<K, crate::types::RenderOutput>,
+    cache: StampedCache<K, crate::types::RenderOutput>,
     stats: Arc<RwLock<CacheMetrics>>,
     max_bytes: u64,
 }
@@ -252,23 +546,28 @@ where
 {
     /// Create a byte-weighted cache with specified maximum bytes.
     pub fn new(max_bytes: u64) -> Self {
-        let cache = Cache::builder()
-            .max_capacity(max_bytes)
-            .weigher(|_key: &K, value: &crate::types::RenderOutput| {
-                // Weight = byte size, minimum 1 to avoid division issues
-                value.byte_size().max(1) as u32
-            })
-            .eviction_policy(moka::policy::EvictionPolicy::tiny_lfu())
-            .time_to_idle(Duration::from_secs(600))
-            .build();
+        Self::build(max_bytes, None)
+    }
+
+    /// Like [`new`](Self::new), but every entry also expires `ttl` after insertion.
+    pub fn with_ttl(max_bytes: u64, ttl: Duration) -> Self {
+        Self::build(max_bytes, Some(ttl))
+    }
 
+    fn build(max_bytes: u64, ttl: Option<Duration>) -> Self {
         Self {
-            cache,
+            cache: byte_weighted(max_bytes, ttl, crate::types::RenderOutput::byte_size),
             stats: Arc::new(RwLock::new(CacheMetrics::default())),
             max_bytes,
         }
     }
 
+    /// Read idle and TTL times from `clock` instead of the process clock
+    pub fn with_clock(mut self, clock: CacheClock) -> Self {
+        self.cache = self.cache.with_clock(clock);
+        self
+    }
+
     /// Create a cache with the default byte limit (512 MB or env override).
     pub fn with_default_limit() -> Self {
         Self::new(get_cache_max_bytes())
@@ -329,6 +628,11 @@ where
         *stats = CacheMetrics::default();
     }
 
+    /// Drop every entry whose key `stale` picks; returns how many went.
+    pub fn invalidate_where(&self, stale: impl Fn(&K) -> bool) -> usize {
+        self.cache.evict_where(stale)
+    }
+
     /// Force pending operations to complete (for testing).
     #[cfg(test)]
     pub fn sync(&self) {
@@ -336,6 +640,15 @@ where
     }
 }
 
+impl<K> TrimIdle for RenderOutputCache<K>
+where
+    K: Hash + Eq + Send + Sync + Clone + 'static,
+{
+    fn trim_idle(&self, max_age: Duration) -> usize {
+        self.cache.evict_idle(max_age)
+    }
+}
+
 /// Centralized cache manager for shaping and glyph caches
 pub struct CacheManager {
     pub shaping_cache: MultiLevelCache<ShapingCacheKey, Arc<Vec<u8>>>,
@@ -406,6 +719,12 @@ impl CacheManager {
     }
 }
 
+impl TrimIdle for CacheManager {
+    fn trim_idle(&self, max_age: Duration) -> usize {
+        self.shaping_cache.trim_idle(max_age) + self.glyph_cache.trim_idle(max_age)
+    }
+}
+
 impl Default for CacheManager {
     fn default() -> Self {
         Self::new()
@@ -484,15 +803,105 @@ mod tests {
         assert!(cache.len() <= 10, "Cache should respect capacity limit");
     }
 
+    #[test]
+    fn test_trim_idle_when_entry_read_recently_then_kept() {
+        let clock = CacheClock::manual();
+        let cache: MultiLevelCache<u32, String> =
+            MultiLevelCache::new(10, 100).with_clock(clock.clone());
+        cache.insert(1, "stale".to_string());
+        cache.insert(2, "fresh".to_string());
+        cache.sync();
+
+        clock.advance(Duration::from_millis(30));
+        assert_eq!(cache.get(&2), Some("fresh".to_string()));
+
+        assert_eq!(cache.trim_idle(Duration::from_millis(15)), 1);
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&2), Some("fresh".to_string()));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_with_ttl_when_expired_then_gone_even_if_read() {
+        let clock = CacheClock::manual();
+        let cache: MultiLevelCache<u32, String> =
+            MultiLevelCache::with_ttl(10, 100, Duration::from_millis(20)).with_clock(clock.clone());
+        cache.insert(1, "short-lived".to_string());
+
+        clock.advance(Duration::from_millis(15));
+        assert!(cache.get(&1).is_some());
+
+        // Read 5 ms ago, but inserted 25 ms ago
+        clock.advance(Duration::from_millis(10));
+        assert_eq!(cache.get(&1), None);
+    }
+
+    #[test]
+    fn test_trim_all_when_hook_registered_then_manager_and_hook_trimmed() {
+        use crate::types::{RenderOutput, VectorData, VectorFormat};
+
+        let clock = CacheClock::manual();
+        let manager = CacheManager {
+            shaping_cache: MultiLevelCache::new(10, 100).with_clock(clock.clone()),
+            glyph_cache: MultiLevelCache::new(10, 100).with_clock(clock.clone()),
+        };
+        manager.shaping_cache.insert(
+            ShapingCacheKey::new(
+                "text",
+                "backend",
+                b"font",
+                16.0,
+                None,
+                None,
+                Vec::new(),
+                Vec::new(),
+            ),
+            Arc::new(vec![1]),
+        );
+        manager.shaping_cache.sync();
+
+        let hooks = Mutex::new(Vec::new());
+        let cache = Arc::new(RenderOutputCache::<u32>::new(1_000_000).with_clock(clock.clone()));
+        register_in(&hooks, &cache);
+        cache.insert(
+            7,
+            RenderOutput::Vector(VectorData {
+                format: VectorFormat::Svg,
+                data: "<svg/>".to_string(),
+            }),
+        );
+        cache.sync();
+
+        let manager: &dyn TrimIdle = &manager;
+        assert_eq!(
+            trim_all(Some(manager), &hooks, Duration::from_millis(30)),
+            0
+        );
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(
+            trim_all(Some(manager), &hooks, Duration::from_millis(30)),
+            2
+        );
+        assert_eq!(cache.entry_count(), 0);
+
+        drop(cache);
+        assert_eq!(trim_all(None, &hooks, Duration::ZERO), 0);
+        assert!(hooks.lock().is_ok_and(|hooks| hooks.is_empty()));
+    }
+
     #[test]
     fn test_render_output_byte_size() {
-        use crate::types::{BitmapData, BitmapFormat, RenderOutput, VectorData, VectorFormat};
+        use crate::types::{
+            BitmapData, BitmapFormat, PixelLayout, RenderOutput, VectorData, VectorFormat,
+        };
 
         // Test bitmap byte size (should be data.len())
         let bitmap = RenderOutput::Bitmap(BitmapData {
             width: 100,
             height: 100,
             format: BitmapFormat::Rgba8,
+            stride: 400,
+            layout: PixelLayout::RGBA,
             data: vec![0u8; 40_000], // 100x100x4 = 40KB
         });
         assert_eq!(bitmap.byte_size(), 40_000);
@@ -511,7 +920,7 @@ mod tests {
 
     #[test]
     fn test_byte_weighted_cache_respects_limit() {
-        use crate::types::{BitmapData, BitmapFormat, RenderOutput};
+        use crate::types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput};
 
         // Create a cache with 100KB limit
         let cache: RenderOutputCache<u32> = RenderOutputCache::new(100_000);
@@ -522,6 +931,8 @@ mod tests {
                 width: 50,
                 height: 50,
                 format: BitmapFormat::Rgba8,
+                stride: 200,
+                layout: PixelLayout::RGBA,
                 data: vec![i as u8; 10_000], // 10KB each
             });
             cache.insert(i, output);
@@ -538,7 +949,7 @@ mod tests {
 
     #[test]
     fn test_byte_weighted_cache_large_item_eviction() {
-        use crate::types::{BitmapData, BitmapFormat, RenderOutput};
+        use crate::types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput};
 
         // Create a cache with 50KB limit
         let cache: RenderOutputCache<u32> = RenderOutputCache::new(50_000);
@@ -548,6 +959,8 @@ mod tests {
             width: 10,
             height: 10,
             format: BitmapFormat::Gray8,
+            stride: 10,
+            layout: PixelLayout::RGBA,
             data: vec![0u8; 100], // 100 bytes
         });
         cache.insert(1, small);
@@ -557,6 +970,8 @@ mod tests {
             width: 100,
             height: 100,
             format: BitmapFormat::Rgba8,
+            stride: 400,
+            layout: PixelLayout::RGBA,
             data: vec![0u8; 40_000], // 40KB
         });
         cache.insert(2, large);
@@ -0,0 +1,177 @@
+//! Single-threaded stand-in for Moka on `wasm32`
+//!
+//! Moka needs threads and a monotonic clock that `wasm32-unknown-unknown`
+//! doesn't provide. This mirrors the slice of `moka::sync::Cache` that
+//! [`super`] uses, backed by a plain LRU list: capacity and weights are
+//! honoured, TinyLFU admission is not, and idle expiry is checked lazily
+//! on read. TTLs are checked by `StampedCache` on every target.
+
+use lru::LruCache;
+use parking_lot::Mutex;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::now_millis;
+
+type Weigher<K, V> = Box<dyn Fn(&K, &V) -> u32 + Send + Sync>;
+
+/// Accepted for API parity with Moka; every policy evicts least recently used
+pub struct EvictionPolicy;
+
+impl EvictionPolicy {
+    pub fn tiny_lfu() -> Self {
+        Self
+    }
+}
+
+struct Slot<V> {
+    value: V,
+    weight: u32,
+    read: u64,
+}
+
+struct Inner<K: Hash + Eq, V> {
+    entries: LruCache<K, Slot<V>>,
+    weighted_size: u64,
+}
+
+pub struct CacheBuilder<K, V> {
+    max_capacity: u64,
+    weigher: Option<Weigher<K, V>>,
+    time_to_idle: Option<Duration>,
+}
+
+impl<K, V> CacheBuilder<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    pub fn max_capacity(mut self, max_capacity: u64) -> Self {
+        self.max_capacity = max_capacity;
+        self
+    }
+
+    pub fn weigher(mut self, weigher: impl Fn(&K, &V) -> u32 + Send + Sync + 'static) -> Self {
+        self.weigher = Some(Box::new(weigher));
+        self
+    }
+
+    pub fn eviction_policy(self, _policy: EvictionPolicy) -> Self {
+        self
+    }
+
+    pub fn time_to_idle(mut self, duration: Duration) -> Self {
+        self.time_to_idle = Some(duration);
+        self
+    }
+
+    pub fn build(self) -> Cache<K, V> {
+        Cache {
+            inner: Mutex::new(Inner {
+                entries: LruCache::unbounded(),
+                weighted_size: 0,
+            }),
+            max_capacity: self.max_capacity,
+            weigher: self.weigher,
+            time_to_idle: self.time_to_idle.map(|d| d.as_millis() as u64),
+        }
+    }
+}
+
+pub struct Cache<K: Hash + Eq, V> {
+    inner: Mutex<Inner<K, V>>,
+    max_capacity: u64,
+    weigher: Option<Weigher<K, V>>,
+    time_to_idle: Option<u64>,
+}
+
+impl<K, V> Cache<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    pub fn builder() -> CacheBuilder<K, V> {
+        CacheBuilder {
+            max_capacity: u64::MAX,
+            weigher: None,
+            time_to_idle: None,
+        }
+    }
+
+    fn expired(&self, slot: &Slot<V>, now: u64) -> bool {
+        self.time_to_idle
+            .is_some_and(|limit| now.saturating_sub(slot.read) > limit)
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let now = now_millis();
+        let mut inner = self.inner.lock();
+        let expired = self.expired(inner.entries.peek(key)?, now);
+        if expired {
+            if let Some(slot) = inner.entries.pop(key) {
+                inner.weighted_size -= u64::from(slot.weight);
+            }
+            return None;
+        }
+        let slot = inner.entries.get_mut(key)?;
+        slot.read = now;
+        Some(slot.value.clone())
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        let weight = self.weigher.as_ref().map_or(1, |weigh| weigh(&key, &value));
+        let now = now_millis();
+        let mut inner = self.inner.lock();
+        let slot = Slot {
+            value,
+            weight,
+            read: now,
+        };
+        if let Some(old) = inner.entries.put(key, slot) {
+            inner.weighted_size -= u64::from(old.weight);
+        }
+        inner.weighted_size += u64::from(weight);
+        while inner.weighted_size > self.max_capacity {
+            match inner.entries.pop_lru() {
+                Some((_, evicted)) => inner.weighted_size -= u64::from(evicted.weight),
+                None => break,
+            }
+        }
+    }
+
+    pub fn invalidate(&self, key: &K) {
+        let mut inner = self.inner.lock();
+        if let Some(slot) = inner.entries.pop(key) {
+            inner.weighted_size -= u64::from(slot.weight);
+        }
+    }
+
+    pub fn invalidate_all(&self) {
+        let mut inner = self.inner.lock();
+        inner.entries.clear();
+        inner.weighted_size = 0;
+    }
+
+    /// Snapshot of the live entries, most recently used first
+    pub fn iter(&self) -> impl Iterator<Item = (Arc<K>, V)> {
+        let inner = self.inner.lock();
+        let entries: Vec<_> = inner
+            .entries
+            .iter()
+            .map(|(key, slot)| (Arc::new(key.clone()), slot.value.clone()))
+            .collect();
+        entries.into_iter()
+    }
+
+    /// Nothing is deferred here; kept so callers read the same on every target
+    pub fn run_pending_tasks(&self) {}
+
+    pub fn entry_count(&self) -> u64 {
+        self.inner.lock().entries.len() as u64
+    }
+
+    pub fn weighted_size(&self) -> u64 {
+        self.inner.lock().weighted_size
+    }
+}
@@ -0,0 +1,137 @@
+//! Cooperative cancellation for long rendering calls.
+//!
+//! A server rendering untrusted requests cannot let one huge text or
+//! pathological font hold a worker indefinitely. Put a [`CancelToken`] in
+//! [`ShapingParams::cancel`](crate::ShapingParams::cancel) and
+//! [`RenderParams::cancel`](crate::RenderParams::cancel); shapers check it
+//! before and after each run, renderers between glyphs, and the opixa
+//! rasterizer between scanlines. Once the token is cancelled, or its deadline
+//! passes, the call returns [`TypfError::Cancelled`] at the next check.
+//!
+//! Checks are cooperative: a single shaping engine call is never interrupted,
+//! so the bound on response time is the cost of the largest uninterruptible
+//! step, not zero.
+//!
+//! ```
+//! use std::time::Duration;
+//! use typf_core::cancel::CancelToken;
+//!
+//! let token = CancelToken::with_timeout(Duration::from_millis(250));
+//! let worker_copy = token.clone();
+//! assert!(worker_copy.check().is_ok());
+//! token.cancel();
+//! assert!(worker_copy.check().is_err());
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::error::{Result, TypfError};
+
+/// A shared flag, with an optional deadline, that aborts rendering calls
+///
+/// Clones share state: cancelling any clone cancels them all.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /// A token that only trips when [`cancel`](Self::cancel) is called
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token that also trips once `deadline` has passed
+    pub fn with_deadline(deadline: Instant) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                cancelled: AtomicBool::new(false),
+                deadline: Some(deadline),
+            }),
+        }
+    }
+
+    /// A token that also trips `budget` from now
+    pub fn with_timeout(budget: Duration) -> Self {
+        Self::with_deadline(Instant::now() + budget)
+    }
+
+    /// Ask every call holding this token to stop
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the token was cancelled or its deadline has passed
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Relaxed)
+            || self.inner.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// `Err(TypfError::Cancelled)` once the token has tripped
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(TypfError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Time left before the deadline; `None` without one
+    pub fn remaining(&self) -> Option<Duration> {
+        self.inner
+            .deadline
+            .map(|d| d.saturating_duration_since(Instant::now()))
+    }
+}
+
+/// Check an optional token; no token never cancels
+pub fn check(token: Option<&CancelToken>) -> Result<()> {
+    token.map_or(Ok(()), CancelToken::check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_when_called_on_clone_then_all_clones_trip() {
+        let token = CancelToken::new();
+        let other = token.clone();
+        assert!(!token.is_cancelled());
+
+        other.cancel();
+        assert!(token.is_cancelled());
+        assert!(matches!(token.check(), Err(TypfError::Cancelled)));
+    }
+
+    #[test]
+    fn test_deadline_when_passed_then_cancelled() {
+        let token = CancelToken::with_deadline(Instant::now());
+        assert!(token.is_cancelled());
+        assert_eq!(token.remaining(), Some(Duration::ZERO));
+
+        let token = CancelToken::with_timeout(Duration::from_secs(3600));
+        assert!(!token.is_cancelled());
+        assert!(CancelToken::new().remaining().is_none());
+    }
+
+    #[test]
+    fn test_check_when_no_token_then_ok() {
+        assert!(check(None).is_ok());
+        let token = CancelToken::new();
+        token.cancel();
+        assert!(check(Some(&token)).is_err());
+    }
+}
@@ -0,0 +1,374 @@
+//! Uppercase, lowercase, and small-caps text.
+//!
+//! [`ShapingParams::case`] asks shapers to change the case of what they
+//! draw without the caller rewriting the text, so clusters still point into
+//! the original string. Shapers call [`apply_case`] first thing:
+//!
+//! ```ignore
+//! if let Some(cased) = apply_case(text, font.as_ref(), params) {
+//!     return cased.shape(params, |text, params| self.shape(text, font, params));
+//! }
+//! ```
+//!
+//! Uppercase and lowercase rewrite the text, following the Turkish and
+//! Azerbaijani dotted and dotless i when [`ShapingParams::language`] says so,
+//! and the Greek final sigma. Small caps turn on the font's `smcp` feature.
+//!
+//! A font without `smcp` has no small-cap glyphs, and one shaped run has one
+//! size, so shapers leave such text alone. Layout synthesizes small caps
+//! instead: [`small_caps_pieces`] splits the text where letters need
+//! shrinking, and those pieces are shaped uppercase at
+//! [`SMALL_CAPS_SCALE`] of the size. `TypfPipeline::render_text` does this.
+
+use std::ops::Range;
+
+use icu_properties::{
+    props::{GeneralCategory, GeneralCategoryGroup},
+    CodePointMapData,
+};
+
+use crate::error::Result;
+use crate::mapped_text::MappedText;
+use crate::sfnt;
+use crate::traits::FontRef;
+use crate::types::ShapingResult;
+use crate::ShapingParams;
+
+/// Size of synthesized small caps, relative to the run's size
+pub const SMALL_CAPS_SCALE: f32 = 0.7;
+
+/// Case to draw text in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TextCase {
+    /// As written. Default.
+    #[default]
+    None,
+    /// All capitals
+    Uppercase,
+    /// All lowercase
+    Lowercase,
+    /// Lowercase letters as small capitals; capitals stay as they are
+    ///
+    /// Shapers use the font's `smcp` feature. For a font without it, only
+    /// `TypfPipeline::render_text` synthesizes small caps; a shaper called
+    /// directly, or through the core `Pipeline`, leaves the text as written.
+    SmallCaps,
+}
+
+/// Whether `font`'s GSUB has the `smcp` feature
+pub fn has_small_caps(font: &dyn FontRef) -> bool {
+    has_gsub_feature(font.data(), font.face_index(), b"smcp")
+}
+
+/// `text` in `case`, with `language` (a BCP 47 tag) choosing the Turkish and
+/// Azerbaijani i. Small caps leave the text alone.
+pub fn to_case(text: &str, case: TextCase, language: Option<&str>) -> String {
+    match map_case(text, case, language) {
+        Some(mapped) => mapped.text().to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// What a shaper does for [`ShapingParams::case`]
+#[derive(Debug, Clone)]
+pub struct CaseMapping<'a> {
+    source: &'a str,
+    mapped: Option<MappedText>,
+    small_caps: bool,
+}
+
+/// How to shape `text` in `params.case`, or `None` when the shaper should
+/// go ahead as usual: no case change, or small caps in a font without them.
+pub fn apply_case<'a>(
+    text: &'a str,
+    font: &dyn FontRef,
+    params: &ShapingParams,
+) -> Option<CaseMapping<'a>> {
+    match params.case {
+        TextCase::None => None,
+        TextCase::SmallCaps => has_small_caps(font).then_some(CaseMapping {
+            source: text,
+            mapped: None,
+            small_caps: true,
+        }),
+        case => Some(CaseMapping {
+            source: text,
+            mapped: Some(map_case(text, case, params.language.as_deref())?),
+            small_caps: false,
+        }),
+    }
+}
+
+impl CaseMapping<'_> {
+    /// The text to shape
+    pub fn text(&self) -> &str {
+        self.mapped.as_ref().map_or(self.source, MappedText::text)
+    }
+
+    /// Point clusters from [`Self::text`] back at the original text
+    pub fn restore_clusters(&self, shaped: &mut ShapingResult) {
+        if let Some(mapped) = &self.mapped {
+            mapped.restore_clusters(shaped);
+        }
+    }
+
+    /// Shape [`Self::text`] with `shape`, which gets `params` without a case
+    /// and with `smcp` on for small caps, and restore the clusters
+    pub fn shape(
+        &self,
+        params: &ShapingParams,
+        shape: impl FnOnce(&str, &ShapingParams) -> Result<ShapingResult>,
+    ) -> Result<ShapingResult> {
+        let mut params = ShapingParams {
+            case: TextCase::None,
+            ..params.clone()
+        };
+        if self.small_caps && !params.features.iter().any(|(tag, _)| tag == "smcp") {
+            params.features.push(("smcp".to_string(), 1));
+        }
+        let mut shaped = shape(self.text(), &params)?;
+        self.restore_clusters(&mut shaped);
+        Ok(shaped)
+    }
+}
+
+/// A stretch of text for synthesized small caps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmallCapsPiece {
+    /// Where the piece sits in the text
+    pub range: Range<usize>,
+    /// Lowercase letters, to shape uppercase at [`SMALL_CAPS_SCALE`]
+    pub synthesized: bool,
+}
+
+/// Split `text` into pieces of lowercase letters, which synthesized small
+/// caps draw as shrunken capitals, and everything else, which stays at full
+/// size. Combining marks go with their base letter.
+pub fn small_caps_pieces(text: &str) -> Vec<SmallCapsPiece> {
+    let categories = CodePointMapData::<GeneralCategory>::new();
+    let mut pieces: Vec<SmallCapsPiece> = Vec::new();
+    for (offset, ch) in text.char_indices() {
+        let end = offset + ch.len_utf8();
+        let mark = GeneralCategoryGroup::Mark.contains(categories.get(ch));
+        let synthesized = ch.is_lowercase() && !ch.to_uppercase().eq(std::iter::once(ch));
+        match pieces.last_mut() {
+            Some(piece) if mark || piece.synthesized == synthesized => piece.range.end = end,
+            _ => pieces.push(SmallCapsPiece {
+                range: offset..end,
+                synthesized,
+            }),
+        }
+    }
+    pieces
+}
+
+/// `text` with its case changed, or `None` if nothing changes
+fn map_case(text: &str, case: TextCase, language: Option<&str>) -> Option<MappedText> {
+    let upper = match case {
+        TextCase::Uppercase => true,
+        TextCase::Lowercase => false,
+        TextCase::None | TextCase::SmallCaps => return None,
+    };
+    let turkic = language
+        .and_then(|tag| tag.split(['-', '_']).next())
+        .is_some_and(|primary| {
+            primary.eq_ignore_ascii_case("tr") || primary.eq_ignore_ascii_case("az")
+        });
+
+    let mut mapped = MappedText::with_capacity(text.len());
+    let mut changed = false;
+    let mut chars = text.char_indices().peekable();
+    let mut previous = None;
+    while let Some((offset, ch)) = chars.next() {
+        let special = match (upper, ch) {
+            (true, 'i') if turkic => Some('İ'),
+            (false, 'I') if turkic => Some('ı'),
+            (false, 'İ') if turkic => Some('i'),
+            // Sigma ending a word takes its final form
+            (false, 'Σ') => {
+                let after_letter = previous.is_some_and(char::is_alphabetic);
+                let before_letter = chars.peek().is_some_and(|&(_, next)| next.is_alphabetic());
+                (after_letter && !before_letter).then_some('ς')
+            },
+            _ => None,
+        };
+        previous = Some(ch);
+        match special {
+            Some(replacement) => mapped.push(offset, std::iter::once(replacement)),
+            None if upper => mapped.push(offset, ch.to_uppercase()),
+            None => mapped.push(offset, ch.to_lowercase()),
+        }
+        changed |= !mapped.text().ends_with(ch) || special.is_some();
+    }
+    changed.then_some(mapped)
+}
+
+/// Whether the GSUB FeatureList of face `face_index` of `data` lists `tag`
+fn has_gsub_feature(data: &[u8], face_index: u32, tag: &[u8; 4]) -> bool {
+    let Some(gsub) = sfnt::table_offset(data, face_index, b"GSUB") else {
+        return false;
+    };
+    let Some(feature_list) = sfnt::u16_at(data, gsub + 6).map(|offset| gsub + offset as usize)
+    else {
+        return false;
+    };
+    let count = sfnt::u16_at(data, feature_list).unwrap_or(0) as usize;
+    (0..count)
+        .map(|i| feature_list + 2 + 6 * i)
+        .any(|record| data.get(record..record + 4) == Some(tag))
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::types::GlyphId;
+
+    /// A font with a GSUB table listing `features`, and nothing else
+    struct GsubFont(Vec<u8>);
+
+    impl GsubFont {
+        fn new(features: &[&[u8; 4]]) -> Self {
+            let mut data = Vec::new();
+            data.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+            data.extend_from_slice(&1u16.to_be_bytes()); // numTables
+            data.extend_from_slice(&[0; 6]);
+            data.extend_from_slice(b"GSUB");
+            data.extend_from_slice(&0u32.to_be_bytes()); // checksum
+            data.extend_from_slice(&28u32.to_be_bytes()); // offset
+            data.extend_from_slice(&0u32.to_be_bytes()); // length
+                                                         // GSUB header: version, ScriptList, FeatureList, LookupList
+            data.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+            data.extend_from_slice(&0u16.to_be_bytes());
+            data.extend_from_slice(&10u16.to_be_bytes());
+            data.extend_from_slice(&0u16.to_be_bytes());
+            data.extend_from_slice(&(features.len() as u16).to_be_bytes());
+            for tag in features {
+                data.extend_from_slice(*tag);
+                data.extend_from_slice(&0u16.to_be_bytes());
+            }
+            Self(data)
+        }
+    }
+
+    impl FontRef for GsubFont {
+        fn data(&self) -> &[u8] {
+            &self.0
+        }
+
+        fn units_per_em(&self) -> u16 {
+            1000
+        }
+
+        fn glyph_id(&self, ch: char) -> Option<GlyphId> {
+            Some(ch as u32)
+        }
+
+        fn advance_width(&self, _glyph_id: GlyphId) -> f32 {
+            500.0
+        }
+    }
+
+    fn params(case: TextCase) -> ShapingParams {
+        ShapingParams {
+            case,
+            ..ShapingParams::default()
+        }
+    }
+
+    #[test]
+    fn test_has_small_caps_reads_gsub_feature_list() {
+        assert!(has_small_caps(&GsubFont::new(&[b"liga", b"smcp"])));
+        assert!(!has_small_caps(&GsubFont::new(&[b"liga", b"c2sc"])));
+        assert!(!has_small_caps(&GsubFont(Vec::new())));
+    }
+
+    #[test]
+    fn test_to_case_follows_language_rules() {
+        assert_eq!(to_case("straße", TextCase::Uppercase, None), "STRASSE");
+        assert_eq!(
+            to_case("istanbul", TextCase::Uppercase, Some("tr")),
+            "İSTANBUL"
+        );
+        assert_eq!(
+            to_case("istanbul", TextCase::Uppercase, Some("en")),
+            "ISTANBUL"
+        );
+        assert_eq!(to_case("DIŞ", TextCase::Lowercase, Some("tr-TR")), "dış");
+        assert_eq!(
+            to_case("ΟΔΟΣ ΣΟΦΟΣ", TextCase::Lowercase, None),
+            "οδος σοφος"
+        );
+        assert_eq!(to_case("Abc", TextCase::SmallCaps, None), "Abc");
+    }
+
+    #[test]
+    fn test_apply_case_when_uppercase_then_clusters_map_back() {
+        let font = GsubFont(Vec::new());
+        let cased = apply_case("ßa", &font, &params(TextCase::Uppercase)).expect("mapped");
+        assert_eq!(cased.text(), "SSA");
+        let shaped = cased
+            .shape(&params(TextCase::Uppercase), |text, params| {
+                assert_eq!(params.case, TextCase::None);
+                let glyphs = text
+                    .char_indices()
+                    .map(|(cluster, ch)| crate::types::PositionedGlyph {
+                        id: ch as u32,
+                        x: 0.0,
+                        y: 0.0,
+                        advance: 0.0,
+                        cluster: cluster as u32,
+                    })
+                    .collect();
+                Ok(ShapingResult {
+                    glyphs,
+                    advance_width: 0.0,
+                    advance_height: params.size,
+                    direction: params.direction,
+                })
+            })
+            .expect("shaped");
+        let clusters: Vec<u32> = shaped.glyphs.iter().map(|g| g.cluster).collect();
+        assert_eq!(clusters, [0, 0, 2]);
+        assert!(apply_case("ABC", &font, &params(TextCase::Uppercase)).is_none());
+    }
+
+    #[test]
+    fn test_apply_case_when_small_caps_then_smcp_only_if_font_has_it() {
+        let plain = GsubFont(Vec::new());
+        assert!(apply_case("abc", &plain, &params(TextCase::SmallCaps)).is_none());
+
+        let font = GsubFont::new(&[b"smcp"]);
+        let cased = apply_case("abc", &font, &params(TextCase::SmallCaps)).expect("smcp");
+        assert_eq!(cased.text(), "abc");
+        cased
+            .shape(&params(TextCase::SmallCaps), |_, params| {
+                assert!(params.features.contains(&("smcp".to_string(), 1)));
+                Ok(ShapingResult {
+                    glyphs: Vec::new(),
+                    advance_width: 0.0,
+                    advance_height: params.size,
+                    direction: params.direction,
+                })
+            })
+            .expect("shaped");
+    }
+
+    #[test]
+    fn test_small_caps_pieces_split_lowercase_letters() {
+        let text = "Hello 2U\u{0301}e\u{0301}";
+        let pieces: Vec<(&str, bool)> = small_caps_pieces(text)
+            .into_iter()
+            .map(|piece| (&text[piece.range], piece.synthesized))
+            .collect();
+        assert_eq!(
+            pieces,
+            [
+                ("H", false),
+                ("ello", true),
+                (" 2U\u{0301}", false),
+                ("e\u{0301}", true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Per-glyph colors for syntax highlighting and other rich text.
+//!
+//! [`RenderParams::foreground`] and [`RenderParams::background`] color a
+//! whole call. [`RenderParams::color_spans`] overrides them for byte ranges
+//! of the source text, so one render can paint keywords, strings, and a
+//! selection highlight without splitting the text into separate runs.
+//!
+//! Spans match glyphs by cluster: a glyph takes the colors of the spans
+//! covering the byte its cluster starts at. Later spans win, and a span that
+//! leaves a color unset keeps whatever the earlier spans or the params chose.
+//! A ligature spanning two colors takes the color of its first character.
+//!
+//! Span backgrounds fill each glyph's advance over the full canvas height.
+//! Runs drawn with a [`transform`](RenderParams::transform) keep their
+//! per-glyph foregrounds but draw no span backgrounds, since the rotated
+//! advance boxes would not line up with the canvas.
+
+use std::ops::Range;
+
+use crate::error::Result;
+use crate::render_target::{self, DirtyRect, RenderTarget};
+use crate::types::ShapingResult;
+use crate::{Color, RenderParams};
+
+/// Colors for one byte range of the source text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ColorSpan {
+    /// Bytes of the source text this span covers.
+    pub range: Range<usize>,
+    /// Text color for glyphs in the range, or the params' own when unset.
+    pub foreground: Option<Color>,
+    /// Fill behind glyphs in the range, or none when unset.
+    pub background: Option<Color>,
+}
+
+impl ColorSpan {
+    /// A span that only changes the text color.
+    pub fn foreground(range: Range<usize>, color: Color) -> Self {
+        Self {
+            range,
+            foreground: Some(color),
+            background: None,
+        }
+    }
+
+    /// A span that only fills behind the text.
+    pub fn background(range: Range<usize>, color: Color) -> Self {
+        Self {
+            range,
+            foreground: None,
+            background: Some(color),
+        }
+    }
+
+    /// This span with a background fill as well.
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    fn covers(&self, cluster: u32) -> bool {
+        self.range.contains(&(cluster as usize))
+    }
+}
+
+/// A span background stretched over consecutive glyphs, in run coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpanBackground {
+    /// Left edge, relative to the run's pen start.
+    pub x: f32,
+    /// Width in pixels.
+    pub width: f32,
+    /// Fill color.
+    pub color: Color,
+}
+
+impl RenderParams {
+    /// Text color for the glyph at `cluster`.
+    pub fn foreground_at(&self, cluster: u32) -> Color {
+        self.color_spans
+            .iter()
+            .rev()
+            .filter(|span| span.covers(cluster))
+            .find_map(|span| span.foreground)
+            .unwrap_or(self.foreground)
+    }
+
+    /// Span background behind the glyph at `cluster`, if any.
+    pub fn background_at(&self, cluster: u32) -> Option<Color> {
+        self.color_spans
+            .iter()
+            .rev()
+            .filter(|span| span.covers(cluster))
+            .find_map(|span| span.background)
+    }
+
+    /// Whether any span changes a glyph's text color.
+    pub fn has_foreground_spans(&self) -> bool {
+        self.color_spans
+            .iter()
+            .any(|span| span.foreground.is_some())
+    }
+}
+
+/// Span backgrounds of `shaped`, merging neighbors that share a color.
+///
+/// Empty for transformed runs; see the [module docs](self).
+pub fn span_backgrounds(shaped: &ShapingResult, params: &RenderParams) -> Vec<SpanBackground> {
+    let mut fills: Vec<SpanBackground> = Vec::new();
+    if params.transform.is_some() {
+        return fills;
+    }
+
+    for glyph in &shaped.glyphs {
+        let Some(color) = params.background_at(glyph.cluster) else {
+            continue;
+        };
+        let (x, width) = if glyph.advance < 0.0 {
+            (glyph.x + glyph.advance, -glyph.advance)
+        } else {
+            (glyph.x, glyph.advance)
+        };
+        match fills.last_mut() {
+            Some(last) if last.color == color && (last.x + last.width - x).abs() < 0.5 => {
+                last.width = x + width - last.x;
+            },
+            _ => fills.push(SpanBackground { x, width, color }),
+        }
+    }
+    fills
+}
+
+/// Fill the span backgrounds of `shaped` into `target`.
+///
+/// `x` is where the run's pen starts on the target; each fill covers rows
+/// `y..y + height`, replacing the pixels underneath.
+pub fn fill_span_backgrounds(
+    target: &mut dyn RenderTarget,
+    shaped: &ShapingResult,
+    params: &RenderParams,
+    x: f32,
+    y: i32,
+    height: u32,
+) -> Result<Option<DirtyRect>> {
+    let mut dirty = None;
+    for fill in span_backgrounds(shaped, params) {
+        let left = (x + fill.x).round();
+        let right = (x + fill.x + fill.width).round();
+        if right <= left {
+            continue;
+        }
+        let touched = render_target::fill_rect(
+            target,
+            left as i32,
+            y,
+            (right - left) as u32,
+            height,
+            fill.color,
+        )?;
+        dirty = DirtyRect::merge(dirty, touched);
+    }
+    Ok(dirty)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::render_target::BufferTarget;
+    use crate::types::{BitmapFormat, Direction, PositionedGlyph};
+
+    const RED: Color = Color::rgba(255, 0, 0, 255);
+    const BLUE: Color = Color::rgba(0, 0, 255, 255);
+
+    fn run(clusters: &[u32]) -> ShapingResult {
+        ShapingResult {
+            glyphs: clusters
+                .iter()
+                .enumerate()
+                .map(|(i, &cluster)| PositionedGlyph {
+                    id: i as u32 + 1,
+                    x: i as f32 * 10.0,
+                    y: 0.0,
+                    advance: 10.0,
+                    cluster,
+                })
+                .collect(),
+            advance_width: clusters.len() as f32 * 10.0,
+            advance_height: 16.0,
+            direction: Direction::LeftToRight,
+        }
+    }
+
+    #[test]
+    fn later_spans_win_and_unset_colors_fall_through() {
+        let params = RenderParams {
+            color_spans: vec![
+                ColorSpan::foreground(0..4, RED).with_background(BLUE),
+                ColorSpan::foreground(2..3, BLUE),
+            ],
+            ..RenderParams::default()
+        };
+
+        assert_eq!(params.foreground_at(0), RED);
+        assert_eq!(params.foreground_at(2), BLUE);
+        assert_eq!(params.background_at(2), Some(BLUE));
+        assert_eq!(params.foreground_at(4), Color::black());
+        assert_eq!(params.background_at(4), None);
+        assert!(params.has_foreground_spans());
+        assert!(!RenderParams::default().has_foreground_spans());
+    }
+
+    #[test]
+    fn backgrounds_merge_adjacent_glyphs_of_one_color() {
+        let params = RenderParams {
+            color_spans: vec![
+                ColorSpan::background(0..2, RED),
+                ColorSpan::background(3..4, BLUE),
+            ],
+            ..RenderParams::default()
+        };
+
+        assert_eq!(
+            span_backgrounds(&run(&[0, 1, 2, 3]), &params),
+            vec![
+                SpanBackground {
+                    x: 0.0,
+                    width: 20.0,
+                    color: RED
+                },
+                SpanBackground {
+                    x: 30.0,
+                    width: 10.0,
+                    color: BLUE
+                },
+            ]
+        );
+
+        let rotated = RenderParams {
+            transform: Some(crate::TextTransform::rotate(90.0)),
+            ..params
+        };
+        assert!(span_backgrounds(&run(&[0, 1]), &rotated).is_empty());
+    }
+
+    #[test]
+    fn fills_span_backgrounds_into_target() {
+        let params = RenderParams {
+            color_spans: vec![ColorSpan::background(1..2, RED)],
+            ..RenderParams::default()
+        };
+        let mut pixels = vec![0u8; 40 * 4 * 2];
+        let mut target =
+            BufferTarget::new(&mut pixels, 40, 2, 160, BitmapFormat::Rgba8).expect("target");
+
+        let dirty =
+            fill_span_backgrounds(&mut target, &run(&[0, 1, 2]), &params, 5.0, 0, 2).expect("fill");
+
+        assert_eq!(
+            dirty,
+            Some(DirtyRect {
+                x: 15,
+                y: 0,
+                width: 10,
+                height: 2
+            })
+        );
+        assert_eq!(&pixels[14 * 4..16 * 4], &[0, 0, 0, 0, 255, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,253 @@
+//! Recoverable problems met while rendering.
+//!
+//! Outside [strict](crate::RenderParams::strict) mode a renderer works around
+//! what it cannot draw: a broken glyph is skipped, oversized bounds are
+//! clamped, a variation value past an axis is pinned to its range. Each of
+//! these goes to `log::warn!`, where font QA tools cannot see it. Put a
+//! [`Diagnostics`] in [`RenderParams::diagnostics`](crate::RenderParams::diagnostics)
+//! and the opixa, skia, zeno, and SVG renderers also record each one as a
+//! [`Diagnostic`] for the caller to read once the render returns.
+//! `TypfPipeline` adds a [`DiagnosticKind::MissingGlyph`] for each character
+//! no font in its fallback chain has.
+//!
+//! ```
+//! use typf_core::diagnostics::{DiagnosticKind, Diagnostics};
+//! use typf_core::RenderParams;
+//!
+//! let diagnostics = Diagnostics::new();
+//! let params = RenderParams {
+//!     diagnostics: Some(diagnostics.clone()),
+//!     ..RenderParams::default()
+//! };
+//! // let output = renderer.render(&shaped, font, &params)?;
+//! # diagnostics.warn(DiagnosticKind::GlyphSkipped, Some(3), "no outline");
+//! for warning in diagnostics.take() {
+//!     println!("{warning}");
+//! }
+//! ```
+//!
+//! Renders answered from a cache record nothing.
+
+use std::fmt;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::traits::FontRef;
+use crate::types::GlyphId;
+
+/// What a renderer worked around
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticKind {
+    /// A glyph failed to draw and was left out
+    GlyphSkipped,
+    /// Glyph bounds passed the malformed-glyph limit and were clamped
+    BoundsClamped,
+    /// A variation value outside its axis was pinned to the axis range
+    AxisClamped,
+    /// A variation named an axis the font does not have and was ignored
+    UnknownAxis,
+    /// The font's variations could not be applied; its default instance drew
+    VariationsIgnored,
+    /// The font could not be read for drawing, so none of its glyphs drew
+    FontUnusable,
+    /// Output fell back to a simpler form than the one asked for
+    Fallback,
+    /// A character no font had a glyph for was drawn as `.notdef`
+    MissingGlyph,
+}
+
+/// One recoverable problem
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// What was worked around
+    pub kind: DiagnosticKind,
+    /// The glyph it concerns, when it concerns one
+    pub glyph_id: Option<GlyphId>,
+    /// What happened, for people
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.glyph_id {
+            Some(glyph_id) => write!(f, "glyph {glyph_id}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// A shared list of [`Diagnostic`]s filled in by renderers
+///
+/// Clones share state, so keep one clone and hand another to the renderer.
+/// Diagnostics accumulate across calls until [`take`](Self::take) or
+/// [`clear`](Self::clear).
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    records: Arc<Mutex<Vec<Diagnostic>>>,
+}
+
+impl Diagnostics {
+    /// An empty collector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log `message` as a warning and record it
+    pub fn warn(
+        &self,
+        kind: DiagnosticKind,
+        glyph_id: Option<GlyphId>,
+        message: impl Into<String>,
+    ) {
+        let diagnostic = Diagnostic {
+            kind,
+            glyph_id,
+            message: message.into(),
+        };
+        log::warn!("{diagnostic}");
+        self.records.lock().push(diagnostic);
+    }
+
+    /// Everything recorded so far, oldest first
+    pub fn records(&self) -> Vec<Diagnostic> {
+        self.records.lock().clone()
+    }
+
+    /// Everything recorded so far, leaving the collector empty
+    pub fn take(&self) -> Vec<Diagnostic> {
+        std::mem::take(&mut *self.records.lock())
+    }
+
+    /// Whether nothing has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.records.lock().is_empty()
+    }
+
+    /// Forget every diagnostic
+    pub fn clear(&self) {
+        self.records.lock().clear();
+    }
+}
+
+/// Log `message` as a warning, and record it when `diagnostics` is set
+///
+/// For renderers, which hold the collector as an `Option`.
+pub fn warn(
+    diagnostics: Option<&Diagnostics>,
+    kind: DiagnosticKind,
+    glyph_id: Option<GlyphId>,
+    message: impl Into<String>,
+) {
+    match diagnostics {
+        Some(diagnostics) => diagnostics.warn(kind, glyph_id, message),
+        None => log::warn!("{}", message.into()),
+    }
+}
+
+/// Report variations the font cannot honor as asked
+///
+/// Values outside an axis's range get [`DiagnosticKind::AxisClamped`], and
+/// tags the font has no axis for get [`DiagnosticKind::UnknownAxis`]; both
+/// are what skrifa does to them silently. Static fonts report nothing.
+pub fn check_variations(
+    diagnostics: Option<&Diagnostics>,
+    font: &dyn FontRef,
+    variations: &[(String, f32)],
+) {
+    let Some(diagnostics) = diagnostics else {
+        return;
+    };
+    if variations.is_empty() {
+        return;
+    }
+    let Some(axes) = font.variation_axes() else {
+        return;
+    };
+    for (tag, value) in variations {
+        match axes.iter().find(|axis| &axis.tag == tag) {
+            Some(axis) if *value < axis.min_value || *value > axis.max_value => {
+                diagnostics.warn(
+                    DiagnosticKind::AxisClamped,
+                    None,
+                    format!(
+                        "{tag}={value} is outside {}..={}; clamped",
+                        axis.min_value, axis.max_value
+                    ),
+                );
+            },
+            Some(_) => {},
+            None => diagnostics.warn(
+                DiagnosticKind::UnknownAxis,
+                None,
+                format!("font has no '{tag}' axis; {tag}={value} ignored"),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::VariationAxis;
+
+    struct VariableFont;
+    impl FontRef for VariableFont {
+        fn data(&self) -> &[u8] {
+            &[]
+        }
+        fn units_per_em(&self) -> u16 {
+            1000
+        }
+        fn glyph_id(&self, _ch: char) -> Option<GlyphId> {
+            None
+        }
+        fn advance_width(&self, _glyph_id: GlyphId) -> f32 {
+            0.0
+        }
+        fn variation_axes(&self) -> Option<Vec<VariationAxis>> {
+            Some(vec![VariationAxis {
+                tag: "wght".into(),
+                name: None,
+                min_value: 100.0,
+                default_value: 400.0,
+                max_value: 900.0,
+                hidden: false,
+            }])
+        }
+    }
+
+    #[test]
+    fn test_check_variations_when_out_of_range_or_unknown_then_reported() {
+        let diagnostics = Diagnostics::new();
+        let variations = vec![
+            ("wght".to_string(), 1200.0),
+            ("wdth".to_string(), 75.0),
+            ("wght".to_string(), 700.0),
+        ];
+        check_variations(Some(&diagnostics), &VariableFont, &variations);
+
+        let kinds: Vec<_> = diagnostics.take().into_iter().map(|d| d.kind).collect();
+        assert_eq!(
+            kinds,
+            [DiagnosticKind::AxisClamped, DiagnosticKind::UnknownAxis]
+        );
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_warn_when_no_collector_then_nothing_recorded() {
+        let diagnostics = Diagnostics::new();
+        warn(None, DiagnosticKind::GlyphSkipped, Some(1), "dropped");
+        assert!(diagnostics.is_empty());
+
+        let shared = diagnostics.clone();
+        warn(
+            Some(&shared),
+            DiagnosticKind::GlyphSkipped,
+            Some(1),
+            "dropped",
+        );
+        assert_eq!(diagnostics.records()[0].to_string(), "glyph 1: dropped");
+    }
+}
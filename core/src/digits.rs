@@ -0,0 +1,306 @@
+//! Locale digits in place of ASCII ones.
+//!
+//! Arabic, Persian, Hindi, Thai, and many other languages write numbers with
+//! their own digits, yet text usually arrives with ASCII `0`–`9`. With
+//! [`ShapingParams::number_substitution`] set, [`substitute_digits`] swaps
+//! ASCII digits for the native digits of [`ShapingParams::language`] before
+//! shaping, as DirectWrite's number substitution does. The shaper then sees
+//! real Arabic-Indic (or Devanagari, Thai, ...) code points, so the font's
+//! own `locl` forms for the language apply, and every backend draws the same
+//! digits.
+//!
+//! Shapers call it first thing:
+//!
+//! ```ignore
+//! if let Some(digits) = substitute_digits(text, params) {
+//!     return digits.shape(params, |text, params| self.shape(text, font, params));
+//! }
+//! ```
+//!
+//! Glyph clusters come back as byte offsets into the caller's text, not the
+//! substituted one.
+
+use icu_properties::{props::Script, CodePointMapData};
+
+use crate::error::Result;
+use crate::mapped_text::MappedText;
+use crate::types::{Direction, ShapingResult};
+use crate::ShapingParams;
+
+/// Whether to draw ASCII digits as the language's native digits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum NumberSubstitution {
+    /// Digits as written. Default.
+    #[default]
+    None,
+    /// Native digits everywhere in the text
+    National,
+    /// Native digits where the number follows a letter of the language's
+    /// script, or has no letter before it in right-to-left text; ASCII
+    /// digits elsewhere, such as after Latin
+    Contextual,
+}
+
+/// A language's digits and the script they belong to
+struct Digits {
+    languages: &'static [&'static str],
+    zero: char,
+    script: Script,
+}
+
+const DIGITS: &[Digits] = &[
+    Digits {
+        languages: &["ar", "ckb", "arz", "apc", "ajp", "acm"],
+        zero: '\u{0660}',
+        script: Script::Arabic,
+    },
+    Digits {
+        languages: &["fa", "ur", "ps", "sd", "ks", "prs"],
+        zero: '\u{06F0}',
+        script: Script::Arabic,
+    },
+    Digits {
+        languages: &["hi", "mr", "ne", "sa", "mai", "bho", "kok"],
+        zero: '\u{0966}',
+        script: Script::Devanagari,
+    },
+    Digits {
+        languages: &["bn", "as", "mni"],
+        zero: '\u{09E6}',
+        script: Script::Bengali,
+    },
+    Digits {
+        languages: &["pa"],
+        zero: '\u{0A66}',
+        script: Script::Gurmukhi,
+    },
+    Digits {
+        languages: &["gu"],
+        zero: '\u{0AE6}',
+        script: Script::Gujarati,
+    },
+    Digits {
+        languages: &["or"],
+        zero: '\u{0B66}',
+        script: Script::Oriya,
+    },
+    Digits {
+        languages: &["ta"],
+        zero: '\u{0BE6}',
+        script: Script::Tamil,
+    },
+    Digits {
+        languages: &["te"],
+        zero: '\u{0C66}',
+        script: Script::Telugu,
+    },
+    Digits {
+        languages: &["kn"],
+        zero: '\u{0CE6}',
+        script: Script::Kannada,
+    },
+    Digits {
+        languages: &["ml"],
+        zero: '\u{0D66}',
+        script: Script::Malayalam,
+    },
+    Digits {
+        languages: &["th"],
+        zero: '\u{0E50}',
+        script: Script::Thai,
+    },
+    Digits {
+        languages: &["lo"],
+        zero: '\u{0ED0}',
+        script: Script::Lao,
+    },
+    Digits {
+        languages: &["bo", "dz"],
+        zero: '\u{0F20}',
+        script: Script::Tibetan,
+    },
+    Digits {
+        languages: &["my"],
+        zero: '\u{1040}',
+        script: Script::Myanmar,
+    },
+    Digits {
+        languages: &["km"],
+        zero: '\u{17E0}',
+        script: Script::Khmer,
+    },
+];
+
+fn digits_for(language: &str) -> Option<&'static Digits> {
+    let primary = language.split(['-', '_']).next()?.to_ascii_lowercase();
+    DIGITS
+        .iter()
+        .find(|d| d.languages.contains(&primary.as_str()))
+}
+
+/// The zero of `language`'s native digits, for a BCP 47 tag such as `"ar"`
+/// or `"fa-IR"`; `None` for languages written with ASCII digits
+pub fn native_zero(language: &str) -> Option<char> {
+    digits_for(language).map(|d| d.zero)
+}
+
+/// Text with its digits swapped, and the way back to the original offsets
+#[derive(Debug, Clone)]
+pub struct DigitSubstitution {
+    mapped: MappedText,
+}
+
+/// Swap the ASCII digits of `text` as `params` asks.
+///
+/// `None` when nothing changes: substitution is off, the language has no
+/// native digits, or no digit qualifies.
+pub fn substitute_digits(text: &str, params: &ShapingParams) -> Option<DigitSubstitution> {
+    if params.number_substitution == NumberSubstitution::None
+        || !text.contains(|c: char| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let digits = digits_for(params.language.as_deref()?)?;
+    let scripts = CodePointMapData::<Script>::new();
+
+    let mut mapped = MappedText::with_capacity(text.len() * 2);
+    let mut changed = false;
+    // The script of the last letter, for contextual substitution
+    let mut context = None;
+    for (offset, ch) in text.char_indices() {
+        if ch.is_ascii_digit() {
+            let native = match params.number_substitution {
+                NumberSubstitution::National => true,
+                NumberSubstitution::Contextual => match context {
+                    Some(script) => script == digits.script,
+                    None => params.direction == Direction::RightToLeft,
+                },
+                NumberSubstitution::None => false,
+            };
+            if native {
+                if let Some(d) = char::from_u32(digits.zero as u32 + (ch as u32 - '0' as u32)) {
+                    mapped.push(offset, std::iter::once(d));
+                    changed = true;
+                    continue;
+                }
+            }
+        } else if ch.is_alphabetic() {
+            context = Some(scripts.get(ch));
+        }
+        mapped.push(offset, std::iter::once(ch));
+    }
+    changed.then_some(DigitSubstitution { mapped })
+}
+
+impl DigitSubstitution {
+    /// The text to shape
+    pub fn text(&self) -> &str {
+        self.mapped.text()
+    }
+
+    /// Point clusters from [`Self::text`] back at the original text
+    pub fn restore_clusters(&self, shaped: &mut ShapingResult) {
+        self.mapped.restore_clusters(shaped);
+    }
+
+    /// Shape [`Self::text`] with `shape`, which gets `params` without number
+    /// substitution, and restore the clusters
+    pub fn shape(
+        &self,
+        params: &ShapingParams,
+        shape: impl FnOnce(&str, &ShapingParams) -> Result<ShapingResult>,
+    ) -> Result<ShapingResult> {
+        let params = ShapingParams {
+            number_substitution: NumberSubstitution::None,
+            ..params.clone()
+        };
+        let mut shaped = shape(self.text(), &params)?;
+        self.restore_clusters(&mut shaped);
+        Ok(shaped)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::types::PositionedGlyph;
+
+    fn params(language: &str, substitution: NumberSubstitution) -> ShapingParams {
+        ShapingParams {
+            language: Some(language.to_string()),
+            number_substitution: substitution,
+            ..ShapingParams::default()
+        }
+    }
+
+    fn substituted(text: &str, params: &ShapingParams) -> Option<String> {
+        substitute_digits(text, params).map(|d| d.text().to_string())
+    }
+
+    #[test]
+    fn test_substitute_when_national_then_all_digits_native() {
+        let arabic = params("ar-EG", NumberSubstitution::National);
+        assert_eq!(substituted("a 2024", &arabic).as_deref(), Some("a ٢٠٢٤"));
+        let persian = params("fa", NumberSubstitution::National);
+        assert_eq!(substituted("19", &persian).as_deref(), Some("۱۹"));
+        let thai = params("th", NumberSubstitution::National);
+        assert_eq!(substituted("5", &thai).as_deref(), Some("๕"));
+    }
+
+    #[test]
+    fn test_substitute_when_nothing_to_do_then_none() {
+        assert!(substituted("12", &params("ar", NumberSubstitution::None)).is_none());
+        assert!(substituted("12", &params("en", NumberSubstitution::National)).is_none());
+        assert!(substituted("abc", &params("ar", NumberSubstitution::National)).is_none());
+        assert!(substitute_digits("12", &ShapingParams::default()).is_none());
+        assert_eq!(native_zero("hi-IN"), Some('\u{0966}'));
+    }
+
+    #[test]
+    fn test_substitute_when_contextual_then_follows_preceding_letters() {
+        let arabic = params("ar", NumberSubstitution::Contextual);
+        assert_eq!(
+            substituted("عدد 12, page 3", &arabic).as_deref(),
+            Some("عدد ١٢, page 3")
+        );
+        // No letter before the number: the run direction decides
+        assert!(substituted("12", &arabic).is_none());
+        let rtl = ShapingParams {
+            direction: Direction::RightToLeft,
+            ..arabic
+        };
+        assert_eq!(substituted("12", &rtl).as_deref(), Some("١٢"));
+    }
+
+    #[test]
+    fn test_shape_when_substituted_then_clusters_point_at_original() {
+        let params = params("ar", NumberSubstitution::National);
+        let digits = substitute_digits("x12", &params).expect("substituted");
+        let shaped = digits
+            .shape(&params, |text, params| {
+                assert_eq!(params.number_substitution, NumberSubstitution::None);
+                let glyphs = text
+                    .char_indices()
+                    .map(|(cluster, ch)| PositionedGlyph {
+                        id: ch as u32,
+                        x: 0.0,
+                        y: 0.0,
+                        advance: 0.0,
+                        cluster: cluster as u32,
+                    })
+                    .collect();
+                Ok(ShapingResult {
+                    glyphs,
+                    advance_width: 0.0,
+                    advance_height: params.size,
+                    direction: params.direction,
+                })
+            })
+            .expect("shaped");
+        let clusters: Vec<u32> = shaped.glyphs.iter().map(|g| g.cluster).collect();
+        let ids: Vec<u32> = shaped.glyphs.iter().map(|g| g.id).collect();
+        assert_eq!(clusters, [0, 1, 2]);
+        assert_eq!(ids, [u32::from('x'), 0x0661, 0x0662]);
+    }
+}
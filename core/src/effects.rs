@@ -0,0 +1,737 @@
+//! Text effects that draw outside the glyph outlines, and the room they need.
+//!
+//! A stroke spreads half its width past every edge; a drop shadow moves the
+//! whole text by its offset and then smears it by its blur radius. Either way
+//! ink ends up outside the box the renderer would otherwise allocate, and the
+//! user is left guessing how much [`RenderParams::padding`](crate::RenderParams::padding)
+//! to add. [`RenderEffects::required_insets`] answers that question from the
+//! effect settings, and [`RenderParams::canvas_insets`](crate::RenderParams::canvas_insets)
+//! combines it with the explicit padding so renderers size the canvas once.
+//!
+//! Renderers draw strokes and shadows with a [`CoverageMask`]: they add each
+//! glyph's coverage to it, then [`draw_effects`](CoverageMask::draw_effects)
+//! spreads it by the stroke, blurs a copy for the shadow, and blends both
+//! under where the glyphs will land. No second image pass is needed. The
+//! z-order is shadow, then stroke, then the glyph fill, which is what
+//! [`RenderEffects::subtitle`] relies on.
+//!
+//! A [`BackgroundBox`] is a badge or subtitle plate: a filled rectangle,
+//! optionally rounded, around the text box grown by its padding. Renderers
+//! draw it per call with [`BackgroundBox::fill`]; `TypfPipeline` draws one
+//! box per line or per run according to its [`BoxScope`].
+
+use crate::error::Result;
+use crate::render_target::{self, DirtyRect, RenderTarget};
+use crate::Color;
+
+/// Space reserved on each side of the text box, in pixels.
+///
+/// The text box's top-left corner lands at `(left, top)` in the output
+/// bitmap, which is the offset to apply when placing the image back onto a
+/// layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Insets {
+    /// Pixels left of the text box.
+    pub left: u32,
+    /// Pixels above the text box.
+    pub top: u32,
+    /// Pixels right of the text box.
+    pub right: u32,
+    /// Pixels below the text box.
+    pub bottom: u32,
+}
+
+impl Insets {
+    /// The same inset on all four sides.
+    pub const fn uniform(value: u32) -> Self {
+        Self {
+            left: value,
+            top: value,
+            right: value,
+            bottom: value,
+        }
+    }
+
+    /// Per-side maximum of `self` and `other`.
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            left: self.left.max(other.left),
+            top: self.top.max(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+
+    /// Per-side sum of `self` and `other`, saturating.
+    pub fn grow(self, other: Self) -> Self {
+        Self {
+            left: self.left.saturating_add(other.left),
+            top: self.top.saturating_add(other.top),
+            right: self.right.saturating_add(other.right),
+            bottom: self.bottom.saturating_add(other.bottom),
+        }
+    }
+
+    /// Total extra width (`left + right`).
+    pub fn horizontal(&self) -> u32 {
+        self.left.saturating_add(self.right)
+    }
+
+    /// Total extra height (`top + bottom`).
+    pub fn vertical(&self) -> u32 {
+        self.top.saturating_add(self.bottom)
+    }
+}
+
+/// An outline drawn around each glyph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stroke {
+    /// Full stroke width in pixels; half of it falls outside the outline.
+    pub width: f32,
+    /// Stroke color.
+    pub color: Color,
+}
+
+/// A blurred copy of the text drawn behind it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DropShadow {
+    /// Horizontal shift in pixels; positive moves right.
+    pub offset_x: f32,
+    /// Vertical shift in pixels; positive moves down.
+    pub offset_y: f32,
+    /// Blur radius in pixels. The shadow reaches this far past its edges.
+    pub blur_radius: f32,
+    /// Shadow color, including its opacity.
+    pub color: Color,
+}
+
+/// Which text a [`BackgroundBox`] surrounds when a line is made of several runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BoxScope {
+    /// One box around the whole line.
+    #[default]
+    Block,
+    /// One box around each shaped run (each font, script, or direction).
+    Run,
+}
+
+/// A filled box behind the text, such as a badge or a subtitle plate.
+///
+/// The box covers the text box, from the line's ascent to its descent and
+/// across the advance width, grown by `padding` on every side. Runs drawn
+/// with a [`transform`](crate::RenderParams::transform) get no box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackgroundBox {
+    /// Fill color.
+    pub color: Color,
+    /// Pixels between the text box and the box edge on each side.
+    pub padding: f32,
+    /// Corner radius in pixels, at most half the box's shorter side.
+    pub corner_radius: f32,
+    /// Whether `TypfPipeline` boxes the whole line or each run.
+    pub scope: BoxScope,
+}
+
+impl BackgroundBox {
+    /// A square-cornered box in `color` hugging the text box.
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            padding: 0.0,
+            corner_radius: 0.0,
+            scope: BoxScope::Block,
+        }
+    }
+
+    /// Edges (left, top, right, bottom) and corner radius of the box around
+    /// the text box `left..right`, `top..bottom`, or none when it is empty.
+    pub fn outline(&self, left: f32, top: f32, right: f32, bottom: f32) -> Option<([f32; 4], f32)> {
+        let pad = finite_or_zero(self.padding);
+        let (x0, y0, x1, y1) = (left - pad, top - pad, right + pad, bottom + pad);
+        if !(x1 > x0 && y1 > y0) {
+            return None;
+        }
+        let radius = finite_or_zero(self.corner_radius)
+            .min((x1 - x0) / 2.0)
+            .min((y1 - y0) / 2.0);
+        Some(([x0, y0, x1, y1], radius))
+    }
+
+    /// Blend the box around the text box `left..right`, `top..bottom` onto `target`.
+    ///
+    /// Coordinates are target pixels. Rounded corners and fractional edges
+    /// are antialiased; the box blends over what is already there.
+    pub fn fill(
+        &self,
+        target: &mut dyn RenderTarget,
+        left: f32,
+        top: f32,
+        right: f32,
+        bottom: f32,
+    ) -> Result<Option<DirtyRect>> {
+        let Some(([x0, y0, x1, y1], radius)) = self.outline(left, top, right, bottom) else {
+            return Ok(None);
+        };
+
+        let (px, py) = (x0.floor() as i32, y0.floor() as i32);
+        let width = (x1.ceil() as i32 - px) as u32;
+        let height = (y1.ceil() as i32 - py) as u32;
+        let (cx, cy) = ((x0 + x1) / 2.0, (y0 + y1) / 2.0);
+        let (hx, hy) = ((x1 - x0) / 2.0 - radius, (y1 - y0) / 2.0 - radius);
+
+        let mut mask = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                // Signed distance from the pixel center to the rounded edge
+                let qx = ((px + x as i32) as f32 + 0.5 - cx).abs() - hx;
+                let qy = ((py + y as i32) as f32 + 0.5 - cy).abs() - hy;
+                let outside = qx.max(0.0).hypot(qy.max(0.0));
+                let distance = outside + qx.max(qy).min(0.0) - radius;
+                mask.push(((0.5 - distance).clamp(0.0, 1.0) * 255.0).round() as u8);
+            }
+        }
+        render_target::blend_mask(target, px, py, width, height, &mask, self.color)
+    }
+}
+
+/// Effects applied around the glyph fill. Default: none.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RenderEffects {
+    /// Outline drawn around each glyph.
+    pub stroke: Option<Stroke>,
+    /// Shadow drawn behind the text.
+    pub shadow: Option<DropShadow>,
+    /// Filled box drawn behind the text and its shadow.
+    pub background_box: Option<BackgroundBox>,
+}
+
+impl RenderEffects {
+    /// Broadcast subtitle style for text `size` pixels tall.
+    ///
+    /// A black border of 8% of the size and a soft, translucent black drop
+    /// shadow down and to the right, both scaled with the size. Pair it with
+    /// a light foreground, as [`RenderParams::subtitle`](crate::RenderParams::subtitle) does.
+    pub fn subtitle(size: f32) -> Self {
+        let size = finite_or_zero(size);
+        Self {
+            stroke: Some(Stroke {
+                width: (size * 0.08).max(1.0),
+                color: Color::black(),
+            }),
+            shadow: Some(DropShadow {
+                offset_x: size * 0.04,
+                offset_y: size * 0.04,
+                blur_radius: size * 0.06,
+                color: Color::rgba(0, 0, 0, 160),
+            }),
+            background_box: None,
+        }
+    }
+
+    /// `true` when no effect is configured.
+    pub fn is_empty(&self) -> bool {
+        self.stroke.is_none() && self.shadow.is_none() && self.background_box.is_none()
+    }
+
+    /// Canvas room each side needs so no effect is clipped.
+    ///
+    /// A shadow of stroked text carries the stroke too, so the stroke
+    /// half-width is added to the shadow's reach before offsetting it.
+    /// Negative and non-finite values count as zero.
+    pub fn required_insets(&self) -> Insets {
+        let half_stroke = self.stroke.map_or(0.0, |s| finite_or_zero(s.width) / 2.0);
+        let mut insets = Insets::uniform(ceil_px(half_stroke));
+
+        if let Some(shadow) = self.shadow {
+            let reach = half_stroke + finite_or_zero(shadow.blur_radius);
+            let dx = finite(shadow.offset_x);
+            let dy = finite(shadow.offset_y);
+            insets = insets.union(Insets {
+                left: ceil_px(reach - dx),
+                top: ceil_px(reach - dy),
+                right: ceil_px(reach + dx),
+                bottom: ceil_px(reach + dy),
+            });
+        }
+        if let Some(background) = self.background_box {
+            insets = insets.union(Insets::uniform(ceil_px(finite_or_zero(background.padding))));
+        }
+
+        insets
+    }
+}
+
+/// Coverage of a whole run, gathered glyph by glyph, to draw effects from.
+#[derive(Debug, Clone)]
+pub struct CoverageMask {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+impl CoverageMask {
+    /// An empty mask the size of the canvas.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0; width as usize * height as usize],
+        }
+    }
+
+    /// Bytes the mask holds, for budgeting.
+    pub fn byte_len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Add a `width` x `height` coverage mask with its top-left at `(x, y)`.
+    ///
+    /// Overlapping coverage combines like source-over alpha.
+    pub fn add(&mut self, x: i32, y: i32, width: u32, height: u32, coverage: &[u8]) {
+        self.add_with(x, y, width, height, |i| coverage[i]);
+    }
+
+    /// Add the alpha channel of `width` x `height` RGBA pixels (color glyphs).
+    pub fn add_rgba(&mut self, x: i32, y: i32, width: u32, height: u32, rgba: &[u8]) {
+        self.add_with(x, y, width, height, |i| rgba[i * 4 + 3]);
+    }
+
+    fn add_with(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        coverage: impl Fn(usize) -> u8,
+    ) {
+        for sy in 0..height {
+            let ty = y as i64 + sy as i64;
+            if ty < 0 || ty >= self.height as i64 {
+                continue;
+            }
+            for sx in 0..width {
+                let tx = x as i64 + sx as i64;
+                if tx < 0 || tx >= self.width as i64 {
+                    continue;
+                }
+                let src = coverage((sy * width + sx) as usize) as u32;
+                let dst = &mut self.data[ty as usize * self.width as usize + tx as usize];
+                *dst = (src + *dst as u32 * (255 - src) / 255) as u8;
+            }
+        }
+    }
+
+    /// Draw the shadow and then the stroke of `effects` onto `target`.
+    ///
+    /// `origin` is where the mask's top-left corner sits on the target. The
+    /// stroke spreads the coverage by half its width, and the shadow is cast
+    /// from the stroked shape, as [`RenderEffects::required_insets`] assumes.
+    /// Draw the glyphs afterwards so their fill lands on top.
+    pub fn draw_effects(
+        mut self,
+        effects: &RenderEffects,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+    ) -> Result<Option<DirtyRect>> {
+        if let Some(stroke) = effects.stroke {
+            let radius = finite_or_zero(stroke.width) / 2.0;
+            self.data = dilate_alpha(&self.data, self.width, self.height, radius);
+        }
+        let mut dirty = None;
+        if let Some(shadow) = effects.shadow {
+            dirty = self.cast(&shadow, target, origin)?;
+        }
+        if let Some(stroke) = effects.stroke {
+            let (x, y) = origin;
+            let (width, height) = (self.width, self.height);
+            let touched =
+                render_target::blend_mask(target, x, y, width, height, &self.data, stroke.color)?;
+            dirty = DirtyRect::merge(dirty, touched);
+        }
+        Ok(dirty)
+    }
+
+    /// Blur a copy of the mask and blend it onto `target` in the shadow's color.
+    ///
+    /// `origin` is where the mask's top-left corner sits on the target; the
+    /// shadow lands there moved by its offset, rounded to whole pixels.
+    pub fn cast(
+        &self,
+        shadow: &DropShadow,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+    ) -> Result<Option<DirtyRect>> {
+        let mut blurred = self.data.clone();
+        blur_alpha(&mut blurred, self.width, self.height, shadow.blur_radius);
+        render_target::blend_mask(
+            target,
+            origin.0 + finite(shadow.offset_x).round() as i32,
+            origin.1 + finite(shadow.offset_y).round() as i32,
+            self.width,
+            self.height,
+            &blurred,
+            shadow.color,
+        )
+    }
+}
+
+/// Spread a `width` x `height` alpha mask by `radius` pixels.
+///
+/// Each pixel takes the strongest coverage within `radius` of it, with the
+/// new edge antialiased over the last pixel. This is the outer half of a
+/// stroke centered on the glyph outlines.
+pub fn dilate_alpha(data: &[u8], width: u32, height: u32, radius: f32) -> Vec<u8> {
+    let radius = finite_or_zero(radius);
+    if radius == 0.0 {
+        return data.to_vec();
+    }
+
+    // A covered pixel's edge lies half a pixel from its center; pushed out
+    // by `radius`, it covers a pixel `distance` away by what is left over
+    let reach = (radius + 1.0).ceil() as i64;
+    let mut kernel = Vec::new();
+    for dy in -reach..=reach {
+        for dx in -reach..=reach {
+            let distance = ((dx * dx + dy * dy) as f32).sqrt();
+            let weight = (radius + 1.0 - distance).clamp(0.0, 1.0);
+            if weight > 0.0 {
+                kernel.push((dx, dy, (weight * 255.0).round() as u32));
+            }
+        }
+    }
+
+    let (w, h) = (width as i64, height as i64);
+    let mut spread = vec![0u8; data.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let mut strongest = 0;
+            for &(dx, dy, weight) in &kernel {
+                let (sx, sy) = (x + dx, y + dy);
+                if sx < 0 || sy < 0 || sx >= w || sy >= h {
+                    continue;
+                }
+                let coverage = data[(sy * w + sx) as usize] as u32 * weight / 255;
+                strongest = strongest.max(coverage);
+            }
+            spread[(y * w + x) as usize] = strongest as u8;
+        }
+    }
+    spread
+}
+
+/// Blur a `width` x `height` alpha mask in place, reaching `radius` pixels.
+///
+/// Three horizontal and vertical box passes approximate a Gaussian with
+/// σ = radius / 3, so the blur fades out within `radius` of every edge and
+/// stays inside [`RenderEffects::required_insets`]. Pixels past the mask's
+/// edges count as transparent.
+pub fn blur_alpha(data: &mut [u8], width: u32, height: u32, radius: f32) {
+    let sigma = finite_or_zero(radius) / 3.0;
+    // Box width whose three passes match the Gaussian's variance
+    let box_width = (12.0 * sigma * sigma / 3.0 + 1.0).sqrt();
+    let half = ((box_width - 1.0) / 2.0).round() as usize;
+    if half == 0 || width == 0 || height == 0 {
+        return;
+    }
+
+    let (width, height) = (width as usize, height as usize);
+    let mut line = Vec::with_capacity(width.max(height));
+    for _ in 0..3 {
+        box_blur_lines(data, height, width, width, 1, half, &mut line);
+        box_blur_lines(data, width, height, 1, width, half, &mut line);
+    }
+}
+
+/// Average every pixel with `half` neighbors each side along `lines` lines.
+///
+/// Line `l` starts at `l * line_step` and steps `px_step` between pixels.
+fn box_blur_lines(
+    data: &mut [u8],
+    lines: usize,
+    len: usize,
+    line_step: usize,
+    px_step: usize,
+    half: usize,
+    line: &mut Vec<u32>,
+) {
+    let window = (2 * half + 1) as u32;
+    for l in 0..lines {
+        let start = l * line_step;
+        line.clear();
+        line.extend((0..len).map(|i| data[start + i * px_step] as u32));
+
+        let mut sum: u32 = line[..len.min(half)].iter().sum();
+        for i in 0..len {
+            if let Some(&entering) = line.get(i + half) {
+                sum += entering;
+            }
+            data[start + i * px_step] = ((sum + window / 2) / window) as u8;
+            if i >= half {
+                sum -= line[i - half];
+            }
+        }
+    }
+}
+
+fn finite(value: f32) -> f32 {
+    if value.is_finite() {
+        value
+    } else {
+        0.0
+    }
+}
+
+fn finite_or_zero(value: f32) -> f32 {
+    if value.is_finite() {
+        value.max(0.0)
+    } else {
+        0.0
+    }
+}
+
+fn ceil_px(value: f32) -> u32 {
+    if value > 0.0 {
+        value.ceil() as u32
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::render_target::BufferTarget;
+    use crate::types::BitmapFormat;
+
+    #[test]
+    fn test_required_insets_when_no_effects_then_zero() {
+        assert_eq!(
+            RenderEffects::default().required_insets(),
+            Insets::default()
+        );
+        assert!(RenderEffects::default().is_empty());
+    }
+
+    #[test]
+    fn test_required_insets_when_stroke_then_half_width_each_side() {
+        let effects = RenderEffects {
+            stroke: Some(Stroke {
+                width: 3.0,
+                color: Color::black(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(effects.required_insets(), Insets::uniform(2));
+    }
+
+    #[test]
+    fn test_required_insets_when_offset_shadow_then_lopsided() {
+        let effects = RenderEffects {
+            shadow: Some(DropShadow {
+                offset_x: 4.0,
+                offset_y: 2.0,
+                blur_radius: 3.0,
+                color: Color::black(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            effects.required_insets(),
+            Insets {
+                left: 0,
+                top: 1,
+                right: 7,
+                bottom: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn test_required_insets_when_stroke_and_shadow_then_shadow_carries_stroke() {
+        let effects = RenderEffects {
+            stroke: Some(Stroke {
+                width: 4.0,
+                color: Color::black(),
+            }),
+            shadow: Some(DropShadow {
+                offset_x: -1.0,
+                offset_y: 0.0,
+                blur_radius: 1.0,
+                color: Color::black(),
+            }),
+            background_box: None,
+        };
+        assert_eq!(
+            effects.required_insets(),
+            Insets {
+                left: 4,
+                top: 3,
+                right: 2,
+                bottom: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_required_insets_when_non_finite_then_ignored() {
+        let effects = RenderEffects {
+            shadow: Some(DropShadow {
+                offset_x: f32::NAN,
+                offset_y: f32::INFINITY,
+                blur_radius: -5.0,
+                color: Color::black(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(effects.required_insets(), Insets::default());
+    }
+
+    #[test]
+    fn test_blur_alpha_when_radius_then_spreads_within_reach() {
+        let mut data = vec![0u8; 21 * 21];
+        data[10 * 21 + 10] = 255;
+        blur_alpha(&mut data, 21, 21, 6.0);
+
+        let at = |x: usize, y: usize| data[y * 21 + x];
+        assert!(at(10, 10) < 255 && at(10, 10) > 0);
+        assert!(at(10, 10) >= at(12, 10) && at(12, 10) >= at(14, 10));
+        assert_eq!(at(12, 10), at(10, 12), "horizontal and vertical alike");
+        assert_eq!(at(17, 10), 0, "nothing past the radius");
+
+        let mut sharp = vec![0, 255, 0];
+        blur_alpha(&mut sharp, 3, 1, 0.0);
+        assert_eq!(sharp, [0, 255, 0]);
+    }
+
+    #[test]
+    fn test_coverage_mask_when_cast_then_offset_in_shadow_color() {
+        let mut mask = CoverageMask::new(4, 4);
+        mask.add(1, 1, 2, 2, &[255; 4]);
+        mask.add_rgba(-1, 0, 2, 1, &[0, 0, 0, 0, 9, 9, 9, 128]);
+        assert_eq!(mask.byte_len(), 16);
+        assert_eq!(mask.data[0], 128);
+
+        let shadow = DropShadow {
+            offset_x: 2.0,
+            offset_y: 1.0,
+            blur_radius: 0.0,
+            color: Color::rgba(0, 0, 255, 255),
+        };
+        let mut pixels = vec![0u8; 8 * 8 * 4];
+        let mut target =
+            BufferTarget::new(&mut pixels, 8, 8, 32, BitmapFormat::Rgba8).expect("target");
+        let dirty = mask.cast(&shadow, &mut target, (1, 1)).expect("cast");
+
+        assert_eq!(
+            dirty,
+            Some(DirtyRect {
+                x: 3,
+                y: 2,
+                width: 4,
+                height: 4
+            })
+        );
+        let px = |x: usize, y: usize| &pixels[(y * 8 + x) * 4..(y * 8 + x) * 4 + 4];
+        assert_eq!(px(4, 3), [0, 0, 255, 255]);
+        assert_eq!(px(3, 2)[3], 128);
+        assert_eq!(px(3, 3), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_required_insets_when_background_box_then_padding_each_side() {
+        let effects = RenderEffects {
+            background_box: Some(BackgroundBox {
+                padding: 2.5,
+                ..BackgroundBox::new(Color::black())
+            }),
+            ..Default::default()
+        };
+        assert!(!effects.is_empty());
+        assert_eq!(effects.required_insets(), Insets::uniform(3));
+    }
+
+    #[test]
+    fn test_background_box_when_rounded_then_corners_antialiased() {
+        let plate = BackgroundBox {
+            padding: 2.0,
+            corner_radius: 4.0,
+            ..BackgroundBox::new(Color::rgba(0, 128, 0, 255))
+        };
+        let mut pixels = vec![0u8; 20 * 12 * 4];
+        let mut target =
+            BufferTarget::new(&mut pixels, 20, 12, 80, BitmapFormat::Rgba8).expect("target");
+        let dirty = plate.fill(&mut target, 3.0, 3.0, 17.0, 9.0).expect("fill");
+
+        assert_eq!(
+            dirty,
+            Some(DirtyRect {
+                x: 1,
+                y: 1,
+                width: 18,
+                height: 10
+            })
+        );
+        let alpha = |x: usize, y: usize| pixels[(y * 20 + x) * 4 + 3];
+        assert_eq!(alpha(0, 0), 0);
+        assert_eq!(alpha(1, 1), 0, "corner cut away");
+        assert!(alpha(2, 2) > 0 && alpha(2, 2) < 255, "corner edge blends");
+        assert_eq!(alpha(1, 6), 255, "straight edge is solid");
+        assert_eq!(alpha(10, 6), 255);
+        assert_eq!(alpha(19, 6), 0);
+    }
+
+    #[test]
+    fn test_dilate_alpha_when_radius_then_spreads_with_soft_edge() {
+        let mut data = vec![0u8; 9];
+        data[4] = 255;
+        assert_eq!(dilate_alpha(&data, 3, 3, 0.0), data);
+
+        let spread = dilate_alpha(&data, 3, 3, 1.0);
+        assert_eq!(spread[4], 255);
+        assert_eq!(spread[1], 255, "edge neighbors are fully inside");
+        assert!(spread[0] > 0 && spread[0] < 255, "corners blend");
+    }
+
+    #[test]
+    fn test_draw_effects_when_subtitle_then_stroke_over_shadow() {
+        let effects = RenderEffects {
+            stroke: Some(Stroke {
+                width: 2.0,
+                color: Color::rgba(255, 0, 0, 255),
+            }),
+            shadow: Some(DropShadow {
+                offset_x: 2.0,
+                offset_y: 0.0,
+                blur_radius: 0.0,
+                color: Color::rgba(0, 0, 255, 255),
+            }),
+            background_box: None,
+        };
+        let mut mask = CoverageMask::new(8, 1);
+        mask.add(2, 0, 1, 1, &[255]);
+        let mut pixels = vec![0u8; 8 * 4];
+        let mut target =
+            BufferTarget::new(&mut pixels, 8, 1, 32, BitmapFormat::Rgba8).expect("target");
+        mask.draw_effects(&effects, &mut target, (0, 0))
+            .expect("draw");
+
+        let px = |x: usize| &pixels[x * 4..x * 4 + 4];
+        assert_eq!(px(0), [0, 0, 0, 0]);
+        assert_eq!(px(1), [255, 0, 0, 255], "stroke spreads a pixel");
+        assert_eq!(px(3), [255, 0, 0, 255], "stroke covers the shadow");
+        assert_eq!(px(4), [0, 0, 255, 255], "shadow of the stroked shape");
+        assert_eq!(px(5), [0, 0, 255, 255]);
+        assert_eq!(px(6), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_subtitle_when_size_then_effects_scale() {
+        let small = RenderEffects::subtitle(10.0);
+        let large = RenderEffects::subtitle(50.0);
+        assert_eq!(small.stroke.map(|s| s.width), Some(1.0));
+        assert_eq!(large.stroke.map(|s| s.width), Some(4.0));
+        assert!(large.required_insets().right > small.required_insets().right);
+        assert!(large.background_box.is_none());
+    }
+}
@@ -0,0 +1,361 @@
+//! Text or emoji presentation (UTS #51).
+//!
+//! Many characters can be drawn two ways: as a plain glyph in the text color
+//! or as a color emoji. Unicode decides per sequence. U+FE0E (VS15) asks for
+//! text and U+FE0F (VS16) for emoji. Flags, skin-tone modifiers, ZWJ and tag
+//! sequences are always emoji. Otherwise the base character's
+//! `Emoji_Presentation` property picks the default: 😀 is emoji, ☺ and © are
+//! text.
+//!
+//! Presentation decides which glyph sources a renderer may use.
+//! [`GlyphSourcePreference::for_presentation`] denies color sources for text
+//! and puts them first for emoji. `TypfPipeline::render_text` resolves it per
+//! sequence; [`RenderParams::emoji_presentation`] forces one presentation on
+//! the whole text, and [`Pipeline`](crate::Pipeline) honors that too.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use icu_properties::{
+    props::{
+        Emoji, EmojiModifier, EmojiPresentation as EmojiPresentationProperty, RegionalIndicator,
+    },
+    CodePointSetData, CodePointSetDataBorrowed,
+};
+
+use crate::sfnt;
+use crate::traits::FontRef;
+use crate::{GlyphSource, GlyphSourcePreference, RenderParams, DEFAULT_GLYPH_SOURCES};
+
+/// VARIATION SELECTOR-15: draw the preceding character as text
+pub const TEXT_PRESENTATION_SELECTOR: char = '\u{FE0E}';
+
+/// VARIATION SELECTOR-16: draw the preceding character as emoji
+pub const EMOJI_PRESENTATION_SELECTOR: char = '\u{FE0F}';
+
+const ZWJ: char = '\u{200D}';
+const KEYCAP: char = '\u{20E3}';
+
+/// How an emoji sequence is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Presentation {
+    /// A plain glyph in the foreground color, from outlines
+    Text,
+    /// A color emoji
+    Emoji,
+}
+
+/// Which presentation [`RenderParams`] asks for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum EmojiPresentation {
+    /// Each sequence's own: its variation selector, or its Unicode default.
+    /// Default.
+    #[default]
+    Auto,
+    /// Everything as text, whatever the selectors say
+    Text,
+    /// Everything as emoji, whatever the selectors say
+    Emoji,
+}
+
+impl EmojiPresentation {
+    /// The presentation forced on the whole text, if any
+    pub fn forced(self) -> Option<Presentation> {
+        match self {
+            Self::Auto => None,
+            Self::Text => Some(Presentation::Text),
+            Self::Emoji => Some(Presentation::Emoji),
+        }
+    }
+}
+
+/// A stretch of text whose sequences share a presentation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresentationRun {
+    /// Where the run sits in the text
+    pub range: Range<usize>,
+    /// How its emoji are drawn; `None` for text that is not emoji at all,
+    /// which keeps the caller's glyph sources
+    pub presentation: Option<Presentation>,
+}
+
+/// Split `text` into runs of emoji sequences with one presentation, and
+/// text that is not emoji.
+///
+/// ASCII digits, `#`, and `*` have the `Emoji` property for keycap
+/// sequences, but on their own they are ordinary text.
+pub fn presentation_runs(text: &str) -> Vec<PresentationRun> {
+    let properties = Properties::new();
+    let mut runs: Vec<PresentationRun> = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, base)) = chars.next() {
+        let mut end = start + base.len_utf8();
+        let mut selector = None;
+        let mut sequence = false;
+
+        // A second regional indicator completes a flag
+        if properties.regional_indicator.contains(base) {
+            if let Some(&(at, ch)) = chars.peek() {
+                if properties.regional_indicator.contains(ch) {
+                    chars.next();
+                    end = at + ch.len_utf8();
+                    sequence = true;
+                }
+            }
+        }
+        while let Some(&(at, ch)) = chars.peek() {
+            match ch {
+                TEXT_PRESENTATION_SELECTOR | EMOJI_PRESENTATION_SELECTOR => {
+                    selector = selector.or(Some(ch));
+                },
+                KEYCAP => {},
+                '\u{E0020}'..='\u{E007F}' => sequence = true,
+                _ if properties.modifier.contains(ch) => sequence = true,
+                // A ZWJ joins the next emoji into this sequence
+                ZWJ if properties.emoji.contains(base) => {
+                    let mut ahead = chars.clone();
+                    ahead.next();
+                    match ahead.next() {
+                        Some((joined, next)) if properties.emoji.contains(next) => {
+                            chars = ahead;
+                            end = joined + next.len_utf8();
+                            sequence = true;
+                            continue;
+                        },
+                        _ => break,
+                    }
+                },
+                _ => break,
+            }
+            chars.next();
+            end = at + ch.len_utf8();
+        }
+
+        let presentation = if !properties.emoji.contains(base) {
+            None
+        } else if base.is_ascii() {
+            // Digits, `#`, and `*` are emoji only as keycaps asking for it
+            (selector == Some(EMOJI_PRESENTATION_SELECTOR)).then_some(Presentation::Emoji)
+        } else {
+            Some(match selector {
+                Some(TEXT_PRESENTATION_SELECTOR) => Presentation::Text,
+                Some(_) => Presentation::Emoji,
+                None if sequence || properties.presentation.contains(base) => Presentation::Emoji,
+                None => Presentation::Text,
+            })
+        };
+
+        match runs.last_mut() {
+            Some(run) if run.presentation == presentation => run.range.end = end,
+            _ => runs.push(PresentationRun {
+                range: start..end,
+                presentation,
+            }),
+        }
+    }
+    runs
+}
+
+/// Whether `font` has color glyph data: COLR, SVG, sbix, or CBDT
+pub fn has_color_glyphs(font: &dyn FontRef) -> bool {
+    [b"COLR", b"SVG ", b"sbix", b"CBDT"]
+        .into_iter()
+        .any(|tag| sfnt::table_offset(font.data(), font.face_index(), tag).is_some())
+}
+
+impl GlyphSourcePreference {
+    /// This preference adjusted for `presentation`: text denies every color
+    /// source, emoji moves the allowed color sources ahead of outlines.
+    /// Sources already denied stay denied.
+    pub fn for_presentation(&self, presentation: Presentation) -> Self {
+        let (color, outline): (Vec<GlyphSource>, Vec<GlyphSource>) = DEFAULT_GLYPH_SOURCES
+            .into_iter()
+            .partition(|source| source.is_color());
+        let (mut prefer, rest): (Vec<GlyphSource>, Vec<GlyphSource>) = self
+            .prefer
+            .iter()
+            .copied()
+            .partition(|source| source.is_color());
+        let adjusted = match presentation {
+            // Outlines stay available even when only color sources were asked for
+            Presentation::Text => Self::from_parts(
+                rest.into_iter().chain(outline).collect(),
+                self.deny.iter().copied().chain(color),
+            ),
+            Presentation::Emoji => {
+                prefer.extend(color);
+                prefer.extend(rest);
+                Self::from_parts(prefer, self.deny.iter().copied())
+            },
+        };
+        // Size rules keep their orders; the new denies still apply to them
+        Self {
+            size_rules: self.size_rules.clone(),
+            ..adjusted
+        }
+    }
+}
+
+impl RenderParams {
+    /// These params with glyph sources for `presentation`
+    pub fn for_presentation(&self, presentation: Presentation) -> Self {
+        Self {
+            glyph_sources: self.glyph_sources.for_presentation(presentation),
+            ..self.clone()
+        }
+    }
+
+    /// These params with [`emoji_presentation`](Self::emoji_presentation)
+    /// applied to the glyph sources, when it forces a presentation
+    pub fn presented(&self) -> Cow<'_, Self> {
+        match self.emoji_presentation.forced() {
+            Some(presentation) => Cow::Owned(self.for_presentation(presentation)),
+            None => Cow::Borrowed(self),
+        }
+    }
+}
+
+/// The emoji properties a sequence is classified by
+struct Properties {
+    emoji: CodePointSetDataBorrowed<'static>,
+    presentation: CodePointSetDataBorrowed<'static>,
+    modifier: CodePointSetDataBorrowed<'static>,
+    regional_indicator: CodePointSetDataBorrowed<'static>,
+}
+
+impl Properties {
+    fn new() -> Self {
+        Self {
+            emoji: CodePointSetData::new::<Emoji>(),
+            presentation: CodePointSetData::new::<EmojiPresentationProperty>(),
+            modifier: CodePointSetData::new::<EmojiModifier>(),
+            regional_indicator: CodePointSetData::new::<RegionalIndicator>(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::GlyphId;
+
+    /// A font whose table directory lists `tag`, and nothing else
+    struct TableFont(Vec<u8>);
+
+    impl TableFont {
+        fn new(tag: &[u8; 4]) -> Self {
+            let mut data = Vec::new();
+            data.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+            data.extend_from_slice(&1u16.to_be_bytes());
+            data.extend_from_slice(&[0; 6]);
+            data.extend_from_slice(tag);
+            data.extend_from_slice(&[0; 4]);
+            data.extend_from_slice(&28u32.to_be_bytes());
+            data.extend_from_slice(&[0; 4]);
+            Self(data)
+        }
+    }
+
+    impl FontRef for TableFont {
+        fn data(&self) -> &[u8] {
+            &self.0
+        }
+
+        fn units_per_em(&self) -> u16 {
+            1000
+        }
+
+        fn glyph_id(&self, _ch: char) -> Option<GlyphId> {
+            None
+        }
+
+        fn advance_width(&self, _glyph_id: GlyphId) -> f32 {
+            0.0
+        }
+    }
+
+    fn runs(text: &str) -> Vec<(&str, Option<Presentation>)> {
+        presentation_runs(text)
+            .into_iter()
+            .map(|run| (&text[run.range], run.presentation))
+            .collect()
+    }
+
+    #[test]
+    fn test_presentation_runs_follow_selectors_and_defaults() {
+        use Presentation::{Emoji as E, Text as T};
+        assert_eq!(
+            runs("a😀\u{263A}\u{263A}\u{FE0F}\u{1F600}\u{FE0E}#"),
+            [
+                ("a", None),
+                ("😀", Some(E)),
+                ("\u{263A}", Some(T)),
+                ("\u{263A}\u{FE0F}", Some(E)),
+                ("\u{1F600}\u{FE0E}", Some(T)),
+                ("#", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_presentation_runs_keep_sequences_whole() {
+        use Presentation::Emoji as E;
+        // Keycap, skin tone, flag, and a ZWJ family
+        let keycap = "1\u{FE0F}\u{20E3}";
+        let thumbs = "\u{1F44D}\u{1F3FD}";
+        let flag = "\u{1F1EF}\u{1F1F5}";
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let text = format!("{keycap}{thumbs}{flag}{family} 1\u{20E3}");
+        assert_eq!(
+            runs(&text),
+            [(&text[..text.len() - 5], Some(E)), (" 1\u{20E3}", None),]
+        );
+        // A ZWJ not followed by an emoji ends the sequence
+        assert_eq!(
+            runs("\u{1F600}\u{200D}a"),
+            [("\u{1F600}", Some(E)), ("\u{200D}a", None)]
+        );
+    }
+
+    #[test]
+    fn test_for_presentation_when_text_then_color_denied_and_outlines_kept() {
+        let text = GlyphSourcePreference::default().for_presentation(Presentation::Text);
+        assert!(!text.allows_color());
+        assert_eq!(text.prefer[0], GlyphSource::Glyf);
+
+        let color_only = GlyphSourcePreference::from_parts(vec![GlyphSource::Colr1], []);
+        let text = color_only.for_presentation(Presentation::Text);
+        assert!(text.effective_order().contains(&GlyphSource::Glyf));
+        assert!(!text.allows_color());
+    }
+
+    #[test]
+    fn test_for_presentation_when_emoji_then_color_first_and_denies_kept() {
+        let emoji = GlyphSourcePreference::default().for_presentation(Presentation::Emoji);
+        assert!(emoji.prefer[0].is_color());
+        assert!(emoji.effective_order().contains(&GlyphSource::Glyf));
+
+        let mono = GlyphSourcePreference::monochrome().for_presentation(Presentation::Emoji);
+        assert!(!mono.allows_color());
+    }
+
+    #[test]
+    fn test_presented_applies_forced_presentation() {
+        let params = RenderParams::default();
+        assert!(matches!(params.presented(), Cow::Borrowed(_)));
+
+        let params = RenderParams {
+            emoji_presentation: EmojiPresentation::Text,
+            ..RenderParams::default()
+        };
+        assert!(!params.presented().glyph_sources.allows_color());
+    }
+
+    #[test]
+    fn test_has_color_glyphs_reads_table_directory() {
+        assert!(has_color_glyphs(&TableFont::new(b"COLR")));
+        assert!(has_color_glyphs(&TableFont::new(b"CBDT")));
+        assert!(!has_color_glyphs(&TableFont::new(b"glyf")));
+        assert!(!has_color_glyphs(&TableFont(Vec::new())));
+    }
+}
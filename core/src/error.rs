@@ -1,9 +1,162 @@
 //! When things go wrong in the pipeline
+//!
+//! Every crate reports failures through [`TypfError`], and every error type
+//! in the workspace implements [`Classify`], so callers can sort a failure
+//! by [`ErrorKind`] without matching each crate's variants:
+//!
+//! ```
+//! use typf_core::error::{Classify, ErrorKind, RenderError, TypfError};
+//!
+//! let err = TypfError::from(RenderError::GlyphNotFound(7));
+//! assert_eq!(err.kind(), ErrorKind::NotFound);
+//! ```
+//!
+//! Errors can carry an [`ErrorContext`] naming the pipeline stage, font, and
+//! glyph they came from; [`TypfError::root`] looks past it to the failure.
+
+use std::fmt;
 
 use thiserror::Error;
 
+use crate::types::GlyphId;
+
 pub type Result<T, E = TypfError> = std::result::Result<T, E>;
 
+/// What sort of failure an error is, whichever crate raised it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// Font data that will not parse or breaks the spec
+    InvalidFont,
+    /// A file, face, table, or glyph that is not there
+    NotFound,
+    /// Text, parameters, or configuration the call cannot use
+    InvalidInput,
+    /// A format, feature, or backend this build or font does not support
+    Unsupported,
+    /// A size, count, or memory limit was passed
+    LimitExceeded,
+    /// A [`CancelToken`](crate::CancelToken) stopped the call
+    Cancelled,
+    /// Reading or writing failed
+    Io,
+    /// A backend failed for its own reasons
+    Backend,
+    /// Anything else
+    Other,
+}
+
+/// Sorts an error into an [`ErrorKind`]
+///
+/// Implemented by [`TypfError`], the error types in this module, and the
+/// error types of the backend crates.
+pub trait Classify {
+    /// The broad kind of this failure
+    fn kind(&self) -> ErrorKind;
+}
+
+/// Pipeline stage an error came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PipelineStage {
+    /// Loading or parsing the font
+    Load,
+    /// [`Shaper::shape`](crate::Shaper::shape)
+    Shape,
+    /// [`Renderer::render`](crate::Renderer::render)
+    Render,
+    /// [`Exporter::export`](crate::Exporter::export)
+    Export,
+}
+
+impl fmt::Display for PipelineStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Load => "load",
+            Self::Shape => "shape",
+            Self::Render => "render",
+            Self::Export => "export",
+        })
+    }
+}
+
+/// Where an error happened: any of the stage, the font, and the glyph
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// The stage that failed
+    pub stage: Option<PipelineStage>,
+    /// The font, as [`GlyphRun::font_id`](crate::GlyphRun::font_id) or a
+    /// caller's own key
+    pub font: Option<String>,
+    /// The glyph being drawn or shaped
+    pub glyph_id: Option<GlyphId>,
+}
+
+impl ErrorContext {
+    /// Context naming only `stage`
+    pub fn stage(stage: PipelineStage) -> Self {
+        Self {
+            stage: Some(stage),
+            ..Self::default()
+        }
+    }
+
+    /// Context naming only `glyph_id`
+    pub fn glyph(glyph_id: GlyphId) -> Self {
+        Self {
+            glyph_id: Some(glyph_id),
+            ..Self::default()
+        }
+    }
+
+    /// This context with the font set to `font`
+    pub fn with_font(mut self, font: impl Into<String>) -> Self {
+        self.font = Some(font.into());
+        self
+    }
+
+    /// This context with the glyph set to `glyph_id`
+    pub fn with_glyph(mut self, glyph_id: GlyphId) -> Self {
+        self.glyph_id = Some(glyph_id);
+        self
+    }
+
+    /// Fields of `self`, with gaps filled from `outer`
+    fn or(self, outer: Self) -> Self {
+        Self {
+            stage: self.stage.or(outer.stage),
+            font: self.font.or(outer.font),
+            glyph_id: self.glyph_id.or(outer.glyph_id),
+        }
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(stage) = self.stage {
+            parts.push(format!("{stage} stage"));
+        }
+        if let Some(font) = &self.font {
+            parts.push(format!("font {font}"));
+        }
+        if let Some(glyph_id) = self.glyph_id {
+            parts.push(format!("glyph {glyph_id}"));
+        }
+        f.write_str(&parts.join(", "))
+    }
+}
+
+/// Attach an [`ErrorContext`] to the error of a result
+pub trait ResultExt<T> {
+    /// The error converted to [`TypfError`] and tagged with `context`
+    fn with_context(self, context: impl FnOnce() -> ErrorContext) -> Result<T>;
+}
+
+impl<T, E: Into<TypfError>> ResultExt<T> for std::result::Result<T, E> {
+    fn with_context(self, context: impl FnOnce() -> ErrorContext) -> Result<T> {
+        self.map_err(|e| e.into().with_context(context()))
+    }
+}
+
 /// Every failure in Typf has a story to tell
 #[derive(Debug, Error)]
 pub enum TypfError {
@@ -28,6 +181,9 @@ pub enum TypfError {
     #[error("Export failed: {0}")]
     ExportFailed(#[from] ExportError),
 
+    #[error("Cancelled before completion")]
+    Cancelled,
+
     #[error("Pipeline error: {0}")]
     Pipeline(String),
 
@@ -39,6 +195,70 @@ pub enum TypfError {
 
     #[error("Other error: {0}")]
     Other(String),
+
+    /// `source`, with where it happened
+    #[error("{source} ({context})")]
+    WithContext {
+        context: ErrorContext,
+        source: Box<TypfError>,
+    },
+}
+
+impl TypfError {
+    /// This error tagged with `context`
+    ///
+    /// Tagging twice keeps one context: fields already set, which came from
+    /// closer to the failure, win over the new ones.
+    pub fn with_context(self, context: ErrorContext) -> Self {
+        match self {
+            Self::WithContext {
+                context: inner,
+                source,
+            } => Self::WithContext {
+                context: inner.or(context),
+                source,
+            },
+            source => Self::WithContext {
+                context,
+                source: Box::new(source),
+            },
+        }
+    }
+
+    /// Where the error happened, if anyone said
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Self::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The failure itself, without its context
+    pub fn root(&self) -> &TypfError {
+        match self {
+            Self::WithContext { source, .. } => source.root(),
+            other => other,
+        }
+    }
+}
+
+impl Classify for TypfError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::NotImplemented(_)
+            | Self::FeatureNotCompiled(_)
+            | Self::UnsupportedBackendCombination(..) => ErrorKind::Unsupported,
+            Self::FontLoad(e) => e.kind(),
+            Self::ShapingFailed(e) => e.kind(),
+            Self::RenderingFailed(e) => e.kind(),
+            Self::ExportFailed(e) => e.kind(),
+            Self::Cancelled => ErrorKind::Cancelled,
+            Self::ConfigError(_) => ErrorKind::InvalidInput,
+            Self::Io(_) => ErrorKind::Io,
+            Self::Pipeline(_) | Self::Other(_) => ErrorKind::Other,
+            Self::WithContext { source, .. } => source.kind(),
+        }
+    }
 }
 
 /// When fonts refuse to load
@@ -55,6 +275,42 @@ pub enum FontLoadError {
 
     #[error("System font not found: {0}")]
     SystemFontNotFound(String),
+
+    #[error("No face with PostScript name '{1}' in {0}")]
+    FaceNotFound(String, String),
+
+    /// No font has the family name; `suggestions` are known families whose
+    /// names are a few typos away
+    #[error("Font family not found: {family}{}", did_you_mean(.suggestions))]
+    FontNotFound {
+        family: String,
+        suggestions: Vec<String>,
+    },
+}
+
+/// The "did you mean" tail of [`FontLoadError::FontNotFound`]
+fn did_you_mean(suggestions: &[String]) -> String {
+    match suggestions {
+        [] => String::new(),
+        [only] => format!(" (did you mean '{only}'?)"),
+        [rest @ .., last] => {
+            let rest: Vec<String> = rest.iter().map(|s| format!("'{s}'")).collect();
+            format!(" (did you mean {} or '{last}'?)", rest.join(", "))
+        },
+    }
+}
+
+impl Classify for FontLoadError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::InvalidData => ErrorKind::InvalidFont,
+            Self::NotSupported(_) => ErrorKind::Unsupported,
+            Self::FileNotFound(_)
+            | Self::SystemFontNotFound(_)
+            | Self::FaceNotFound(..)
+            | Self::FontNotFound { .. } => ErrorKind::NotFound,
+        }
+    }
 }
 
 /// When shaping goes wrong
@@ -79,6 +335,19 @@ pub enum ShapingError {
     BackendError(String),
 }
 
+impl Classify for ShapingError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::InvalidText => ErrorKind::InvalidInput,
+            Self::FontSizeTooLarge(..) => ErrorKind::LimitExceeded,
+            Self::ScriptNotSupported(_)
+            | Self::LanguageNotSupported(_)
+            | Self::FeatureNotSupported(_) => ErrorKind::Unsupported,
+            Self::BackendError(_) => ErrorKind::Backend,
+        }
+    }
+}
+
 /// When rendering fails
 #[derive(Debug, Error)]
 pub enum RenderError {
@@ -111,6 +380,13 @@ pub enum RenderError {
     #[error("Out of memory")]
     OutOfMemory,
 
+    #[error("Memory budget exceeded: {requested} more bytes with {in_use} in use would pass the {limit}-byte limit. Render less text per call or raise the budget.")]
+    BudgetExceeded {
+        requested: usize,
+        in_use: usize,
+        limit: usize,
+    },
+
     #[error("Format not supported: {0}")]
     FormatNotSupported(String),
 
@@ -131,6 +407,51 @@ pub enum RenderError {
 
     #[error("Pixmap creation failed")]
     PixmapCreationFailed,
+
+    #[error("Glyph {glyph_id} reaches {extent}px from its origin, past the {limit}px limit for a {font_size}px font. The font is likely malformed.")]
+    GlyphBoundsTooLarge {
+        glyph_id: u32,
+        extent: f32,
+        limit: f32,
+        font_size: f32,
+    },
+
+    /// A backend's own error, converted with its kind kept
+    #[error("{message}")]
+    Classified { kind: ErrorKind, message: String },
+}
+
+impl RenderError {
+    /// `error` as a [`Classified`](Self::Classified) render error
+    pub fn classified(error: &(impl Classify + fmt::Display)) -> Self {
+        Self::Classified {
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl Classify for RenderError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::ZeroDimensions { .. } => ErrorKind::InvalidInput,
+            Self::DimensionsTooLarge { .. }
+            | Self::TotalPixelsTooLarge { .. }
+            | Self::InvalidDimensions { .. }
+            | Self::GlyphCountTooLarge(..)
+            | Self::OutOfMemory
+            | Self::BudgetExceeded { .. }
+            | Self::GlyphBoundsTooLarge { .. } => ErrorKind::LimitExceeded,
+            Self::FormatNotSupported(_) => ErrorKind::Unsupported,
+            Self::InvalidFont => ErrorKind::InvalidFont,
+            Self::GlyphNotFound(_) => ErrorKind::NotFound,
+            Self::BackendError(_)
+            | Self::OutlineExtractionFailed
+            | Self::PathBuildingFailed
+            | Self::PixmapCreationFailed => ErrorKind::Backend,
+            Self::Classified { kind, .. } => *kind,
+        }
+    }
 }
 
 /// When export can't finish
@@ -145,3 +466,79 @@ pub enum ExportError {
     #[error("Write failed: {0}")]
     WriteFailed(String),
 }
+
+impl Classify for ExportError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::FormatNotSupported(_) => ErrorKind::Unsupported,
+            Self::EncodingFailed(_) => ErrorKind::Backend,
+            Self::WriteFailed(_) => ErrorKind::Io,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_when_nested_then_from_innermost_error() {
+        let budget = RenderError::BudgetExceeded {
+            requested: 10,
+            in_use: 0,
+            limit: 5,
+        };
+        assert_eq!(TypfError::from(budget).kind(), ErrorKind::LimitExceeded);
+        assert_eq!(
+            TypfError::from(FontLoadError::InvalidData).kind(),
+            ErrorKind::InvalidFont
+        );
+        assert_eq!(TypfError::Cancelled.kind(), ErrorKind::Cancelled);
+
+        let classified = RenderError::Classified {
+            kind: ErrorKind::NotFound,
+            message: "glyph not found in SVG table".into(),
+        };
+        let wrapped = TypfError::from(classified).with_context(ErrorContext::glyph(3));
+        assert_eq!(wrapped.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_font_not_found_when_suggestions_then_listed_in_message() {
+        let err = |suggestions: &[&str]| FontLoadError::FontNotFound {
+            family: "Noto Sans Arbic".into(),
+            suggestions: suggestions.iter().map(|s| s.to_string()).collect(),
+        };
+        assert_eq!(
+            err(&[]).to_string(),
+            "Font family not found: Noto Sans Arbic"
+        );
+        assert_eq!(
+            err(&["Noto Sans Arabic", "Noto Sans Armenian", "Noto Sans"]).to_string(),
+            "Font family not found: Noto Sans Arbic (did you mean 'Noto Sans Arabic', \
+             'Noto Sans Armenian' or 'Noto Sans'?)"
+        );
+        assert_eq!(err(&[]).kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_with_context_when_tagged_twice_then_inner_fields_win() {
+        let err = TypfError::Cancelled
+            .with_context(ErrorContext::glyph(7).with_font("inner"))
+            .with_context(ErrorContext::stage(PipelineStage::Render).with_font("outer"));
+
+        assert!(matches!(err.root(), TypfError::Cancelled));
+        assert_eq!(
+            err.context(),
+            Some(&ErrorContext {
+                stage: Some(PipelineStage::Render),
+                font: Some("inner".into()),
+                glyph_id: Some(7),
+            })
+        );
+        assert_eq!(
+            err.to_string(),
+            "Cancelled before completion (render stage, font inner, glyph 7)"
+        );
+    }
+}
@@ -12,8 +12,9 @@
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
-use crate::cache::RenderOutputCache;
+use crate::cache::{InvalidateFont, RenderOutputCache, TrimIdle};
 use crate::cache_config;
 use crate::types::{RenderOutput, ShapingResult};
 use crate::RenderParams;
@@ -25,6 +26,8 @@ pub struct GlyphCacheKey {
     pub renderer: String,
     /// Font identity hash
     pub font_id: u64,
+    /// Face within the font data, for TTC/OTC collections
+    pub face_index: u32,
     /// Hash of shaped glyph sequence (positions + ids + direction)
     pub shaped_hash: u64,
     /// Hash of render parameters (colors, AA, palette, variations, glyph sources)
@@ -46,10 +49,17 @@ impl GlyphCacheKey {
         Self {
             renderer: renderer.into(),
             font_id,
+            face_index: 0,
             shaped_hash,
             render_hash,
         }
     }
+
+    /// Key glyphs of face `face_index` of a collection
+    pub fn with_face_index(mut self, face_index: u32) -> Self {
+        self.face_index = face_index;
+        self
+    }
 }
 
 fn hash_bytes(bytes: &[u8]) -> u64 {
@@ -76,15 +86,44 @@ fn hash_shaping_result(shaped: &ShapingResult) -> u64 {
     hasher.finish()
 }
 
-fn hash_render_params(params: &RenderParams) -> u64 {
+pub(crate) fn hash_render_params(params: &RenderParams) -> u64 {
     let mut hasher = DefaultHasher::new();
 
     params.padding.hash(&mut hasher);
     params.antialias.hash(&mut hasher);
+    params.antialias_quality.hash(&mut hasher);
+    params.hinting.hash(&mut hasher);
+    params.dropout.hash(&mut hasher);
+    params.gamma.to_bits().hash(&mut hasher);
     params.color_palette.hash(&mut hasher);
     params.output.hash(&mut hasher);
     params.foreground.hash(&mut hasher);
     params.background.hash(&mut hasher);
+    params.auto_padding.hash(&mut hasher);
+    params.deterministic.hash(&mut hasher);
+
+    if let Some(stroke) = params.effects.stroke {
+        stroke.width.to_bits().hash(&mut hasher);
+        stroke.color.hash(&mut hasher);
+    }
+    if let Some(shadow) = params.effects.shadow {
+        shadow.offset_x.to_bits().hash(&mut hasher);
+        shadow.offset_y.to_bits().hash(&mut hasher);
+        shadow.blur_radius.to_bits().hash(&mut hasher);
+        shadow.color.hash(&mut hasher);
+    }
+    if let Some(background) = params.effects.background_box {
+        background.color.hash(&mut hasher);
+        background.padding.to_bits().hash(&mut hasher);
+        background.corner_radius.to_bits().hash(&mut hasher);
+        background.scope.hash(&mut hasher);
+    }
+
+    if let Some(t) = params.transform {
+        for entry in [t.xx, t.yx, t.xy, t.yy] {
+            entry.to_bits().hash(&mut hasher);
+        }
+    }
 
     for (tag, value) in &params.variations {
         tag.hash(&mut hasher);
@@ -100,6 +139,27 @@ fn hash_render_params(params: &RenderParams) -> u64 {
     for deny in denied {
         deny.hash(&mut hasher);
     }
+    params.glyph_sources.size_rules.hash(&mut hasher);
+    params.color_spans.hash(&mut hasher);
+    params.subpixel.hash(&mut hasher);
+    params.format.hash(&mut hasher);
+    params.row_alignment.hash(&mut hasher);
+    params.pixel_layout.hash(&mut hasher);
+
+    // Renderers size the canvas by the layout width and overflow policy
+    let layout = &params.layout;
+    layout.max_width.map(f32::to_bits).hash(&mut hasher);
+    layout.wrap.hash(&mut hasher);
+    layout.overflow.hash(&mut hasher);
+    layout.max_height.map(f32::to_bits).hash(&mut hasher);
+
+    // Strict and budgeted calls can fail where a lenient one succeeded
+    params.strict.hash(&mut hasher);
+    params
+        .memory_budget
+        .as_ref()
+        .map(crate::MemoryBudget::limit)
+        .hash(&mut hasher);
 
     hasher.finish()
 }
@@ -127,6 +187,13 @@ impl GlyphCache {
         }
     }
 
+    /// Create a cache with the default byte limit whose entries expire `ttl` after insertion.
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            cache: RenderOutputCache::with_ttl(crate::cache::get_cache_max_bytes(), ttl),
+        }
+    }
+
     /// Get a cached render output.
     ///
     /// Returns `None` if not found or if caching is globally disabled.
@@ -172,6 +239,32 @@ impl GlyphCache {
     }
 }
 
+impl TrimIdle for GlyphCache {
+    fn trim_idle(&self, max_age: Duration) -> usize {
+        self.cache.trim_idle(max_age)
+    }
+}
+
+impl TrimIdle for RwLock<GlyphCache> {
+    fn trim_idle(&self, max_age: Duration) -> usize {
+        self.read().map_or(0, |cache| cache.trim_idle(max_age))
+    }
+}
+
+impl InvalidateFont for GlyphCache {
+    fn invalidate_font(&self, font_data: &[u8]) -> usize {
+        let font_id = hash_bytes(font_data);
+        self.cache.invalidate_where(|key| key.font_id == font_id)
+    }
+}
+
+impl InvalidateFont for RwLock<GlyphCache> {
+    fn invalidate_font(&self, font_data: &[u8]) -> usize {
+        self.read()
+            .map_or(0, |cache| cache.invalidate_font(font_data))
+    }
+}
+
 impl Default for GlyphCache {
     fn default() -> Self {
         Self::new()
@@ -214,6 +307,59 @@ mod tests {
         assert_ne!(k1, k2);
     }
 
+    #[test]
+    fn key_changes_with_effects() {
+        let s = shaped();
+        let plain = render_params();
+        let stroked = RenderParams {
+            effects: crate::RenderEffects {
+                stroke: Some(crate::Stroke {
+                    width: 2.0,
+                    color: crate::Color::black(),
+                }),
+                ..Default::default()
+            },
+            ..render_params()
+        };
+        let k1 = GlyphCacheKey::new("r1", b"font", &s, &plain);
+        let k2 = GlyphCacheKey::new("r1", b"font", &s, &stroked);
+        assert_ne!(k1, k2);
+    }
+
+    #[test]
+    fn key_changes_with_layout() {
+        let s = shaped();
+        let plain = render_params();
+        let clipped = RenderParams {
+            layout: crate::TextLayout::new(5.0).with_overflow(crate::Overflow::Clip),
+            ..render_params()
+        };
+        let narrower = RenderParams {
+            layout: crate::TextLayout::new(4.0).with_overflow(crate::Overflow::Clip),
+            ..render_params()
+        };
+        let keys = [&plain, &clipped, &narrower].map(|p| GlyphCacheKey::new("r1", b"font", &s, p));
+        assert_ne!(keys[0], keys[1]);
+        assert_ne!(keys[1], keys[2]);
+    }
+
+    #[test]
+    fn key_changes_with_strict_and_budget() {
+        let s = shaped();
+        let lenient = render_params();
+        let strict = RenderParams {
+            strict: true,
+            ..render_params()
+        };
+        let budgeted = RenderParams {
+            memory_budget: Some(crate::MemoryBudget::new(1024)),
+            ..render_params()
+        };
+        let keys = [&lenient, &strict, &budgeted].map(|p| GlyphCacheKey::new("r1", b"font", &s, p));
+        assert_ne!(keys[0], keys[1]);
+        assert_ne!(keys[0], keys[2]);
+    }
+
     #[test]
     fn cache_stores_and_retrieves() {
         let _guard = crate::cache_config::scoped_caching_enabled(true);
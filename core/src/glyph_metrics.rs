@@ -0,0 +1,174 @@
+//! Glyph metrics read straight from the font tables.
+//!
+//! Measuring text should not cost a render. [`glyph_metrics`] reads a
+//! glyph's advances and side bearings from `hmtx`/`vmtx` (with `HVAR`/`VVAR`
+//! or `gvar` deltas for variable fonts) and its tight bounding box from
+//! `glyf`, falling back to tracing the outline for CFF, CFF2, and `gvar`
+//! fonts. [`measure_glyphs`] does the same for many glyphs, parsing the font
+//! once.
+//!
+//! Values are in pixels at the requested size. Boxes use font coordinates,
+//! y pointing up from the baseline. Fonts without `vmtx` get the OpenType
+//! fallback for vertical metrics: an advance of ascender minus descender and
+//! a top side bearing measured from the ascender.
+//!
+//! Needs the `glyph-metrics` feature.
+
+use skrifa::{
+    instance::{Location, Size},
+    metrics::GlyphMetrics as SkrifaGlyphMetrics,
+    raw::TableProvider,
+    MetadataProvider,
+};
+
+use crate::error::{RenderError, Result};
+use crate::traits::FontRef;
+use crate::types::GlyphId;
+
+/// Tight box around a glyph's ink, y up from the baseline.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GlyphBounds {
+    /// Left edge.
+    pub x_min: f32,
+    /// Bottom edge; negative for descenders.
+    pub y_min: f32,
+    /// Right edge.
+    pub x_max: f32,
+    /// Top edge.
+    pub y_max: f32,
+}
+
+impl GlyphBounds {
+    /// Horizontal extent of the ink.
+    pub fn width(&self) -> f32 {
+        self.x_max - self.x_min
+    }
+
+    /// Vertical extent of the ink.
+    pub fn height(&self) -> f32 {
+        self.y_max - self.y_min
+    }
+}
+
+/// Advances, side bearings, and ink box of one glyph, in pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GlyphMetrics {
+    /// Horizontal advance.
+    pub advance_width: f32,
+    /// Vertical advance.
+    pub advance_height: f32,
+    /// Distance from the origin to the left edge of the ink.
+    pub left_side_bearing: f32,
+    /// Distance from the top of the vertical em box down to the ink.
+    pub top_side_bearing: f32,
+    /// Ink box; `None` for glyphs without ink, such as spaces, and for
+    /// bitmap-only glyphs.
+    pub bounds: Option<GlyphBounds>,
+}
+
+/// A font parsed at one size and variation instance
+struct Measurer<'a> {
+    font: skrifa::FontRef<'a>,
+    location: Location,
+    size: Size,
+    scale: f32,
+}
+
+impl<'a> Measurer<'a> {
+    fn new(font: &'a dyn FontRef, size: f32, variations: &[(String, f32)]) -> Result<Self> {
+        let font = skrifa::FontRef::from_index(font.data(), font.face_index())
+            .map_err(|_| RenderError::InvalidFont)?;
+        let location = font
+            .axes()
+            .location(variations.iter().map(|(tag, value)| (tag.as_str(), *value)));
+        let upem = font.head().map_or(1000, |head| head.units_per_em()).max(1);
+        Ok(Self {
+            font,
+            location,
+            size: Size::new(size),
+            scale: size / f32::from(upem),
+        })
+    }
+
+    fn measure(&self, horizontal: &SkrifaGlyphMetrics, glyph_id: GlyphId) -> Result<GlyphMetrics> {
+        let gid = skrifa::GlyphId::new(glyph_id);
+        let advance_width = horizontal
+            .advance_width(gid)
+            .ok_or(RenderError::GlyphNotFound(glyph_id))?;
+        let left_side_bearing = horizontal.left_side_bearing(gid).unwrap_or_default();
+        let bounds = horizontal
+            .bounds(gid)
+            .filter(|b| b.x_min < b.x_max && b.y_min < b.y_max)
+            .map(|b| GlyphBounds {
+                x_min: b.x_min,
+                y_min: b.y_min,
+                x_max: b.x_max,
+                y_max: b.y_max,
+            });
+        let (advance_height, top_side_bearing) = self.vertical(gid, bounds);
+        Ok(GlyphMetrics {
+            advance_width,
+            advance_height,
+            left_side_bearing,
+            top_side_bearing,
+            bounds,
+        })
+    }
+
+    /// Advance height and top side bearing from `vmtx` and `VVAR`, or the
+    /// OpenType fallback from the ascender and descender
+    fn vertical(&self, gid: skrifa::GlyphId, bounds: Option<GlyphBounds>) -> (f32, f32) {
+        let coords = self.location.coords();
+        if let Ok(vmtx) = self.font.vmtx() {
+            let vvar = self.font.vvar().ok();
+            let delta = |d: Option<skrifa::raw::types::Fixed>| d.map_or(0.0, |d| d.to_f32());
+            let advance = f32::from(vmtx.advance(gid).unwrap_or_default())
+                + delta(
+                    vvar.as_ref()
+                        .and_then(|v| v.advance_height_delta(gid, coords).ok()),
+                );
+            let tsb = f32::from(vmtx.side_bearing(gid).unwrap_or_default())
+                + delta(vvar.as_ref().and_then(|v| v.tsb_delta(gid, coords).ok()));
+            return (advance * self.scale, tsb * self.scale);
+        }
+        let metrics = self.font.metrics(self.size, &self.location);
+        let advance = metrics.ascent - metrics.descent;
+        let tsb = bounds.map_or(0.0, |b| metrics.ascent - b.y_max);
+        (advance, tsb)
+    }
+}
+
+/// Metrics of `glyph_id` at `size` pixels per em, for the variation
+/// instance `variations` (the `(tag, value)` pairs of
+/// [`ShapingParams::variations`](crate::ShapingParams::variations)).
+///
+/// Fails with [`RenderError::InvalidFont`] when the font data does not parse
+/// and [`RenderError::GlyphNotFound`] when the glyph is past the font's
+/// glyph count.
+pub fn glyph_metrics(
+    font: &dyn FontRef,
+    glyph_id: GlyphId,
+    size: f32,
+    variations: &[(String, f32)],
+) -> Result<GlyphMetrics> {
+    let measurer = Measurer::new(font, size, variations)?;
+    let horizontal = SkrifaGlyphMetrics::new(&measurer.font, measurer.size, &measurer.location);
+    measurer.measure(&horizontal, glyph_id)
+}
+
+/// Metrics of every glyph in `glyph_ids`, in order, parsing the font once.
+///
+/// Fails on the first glyph [`glyph_metrics`] would fail on.
+pub fn measure_glyphs(
+    font: &dyn FontRef,
+    glyph_ids: &[GlyphId],
+    size: f32,
+    variations: &[(String, f32)],
+) -> Result<Vec<GlyphMetrics>> {
+    let measurer = Measurer::new(font, size, variations)?;
+    let horizontal = SkrifaGlyphMetrics::new(&measurer.font, measurer.size, &measurer.location);
+    glyph_ids
+        .iter()
+        .map(|&glyph_id| measurer.measure(&horizontal, glyph_id))
+        .collect()
+}
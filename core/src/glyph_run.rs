@@ -0,0 +1,224 @@
+//! Shaped glyph runs as JSON, for consumers that draw their own glyphs.
+//!
+//! Web front-ends drawing to an HTML canvas, or any other process with its
+//! own glyph cache, often need only typf's shaping: which glyphs, where. A
+//! [`GlyphRun`] captures one shaped run with a stable font id, and
+//! [`glyph_runs_to_json`] writes one or more runs as a small JSON document:
+//!
+//! ```json
+//! {"format":"typf-glyph-runs","version":1,"runs":[
+//!   {"font_id":"9f1c…","font_size":24,"direction":"ltr",
+//!    "advance_width":61.5,"advance_height":24,
+//!    "glyphs":[{"id":43,"x":0,"y":0,"advance":17.3,"cluster":0}, …]}]}
+//! ```
+//!
+//! Positions are in pixels at `font_size`, relative to the run origin on the
+//! baseline, with `y` growing downward like canvas coordinates. Non-finite
+//! numbers are written as `null`.
+
+use std::fmt::Write;
+
+use crate::traits::FontRef;
+use crate::types::{Direction, PositionedGlyph, ShapingResult};
+
+/// Value of the top-level `format` field
+pub const GLYPH_RUN_FORMAT: &str = "typf-glyph-runs";
+
+/// Value of the top-level `version` field; bumped on incompatible changes
+pub const GLYPH_RUN_VERSION: u32 = 1;
+
+/// One shaped run, ready to serialize
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphRun {
+    /// Identifies the font the glyph ids belong to; see [`GlyphRun::font_id`]
+    pub font_id: String,
+    /// Font size in pixels the positions were computed at
+    pub font_size: f32,
+    /// Run direction
+    pub direction: Direction,
+    /// Total horizontal advance of the run
+    pub advance_width: f32,
+    /// Total vertical advance of the run
+    pub advance_height: f32,
+    /// Positioned glyphs in visual order
+    pub glyphs: Vec<PositionedGlyph>,
+}
+
+impl GlyphRun {
+    /// Capture a shaped run. `font_id` is usually [`GlyphRun::font_id`].
+    pub fn new(shaped: &ShapingResult, font_id: impl Into<String>) -> Self {
+        Self {
+            font_id: font_id.into(),
+            font_size: shaped.advance_height,
+            direction: shaped.direction,
+            advance_width: shaped.advance_width,
+            advance_height: shaped.advance_height,
+            glyphs: shaped.glyphs.clone(),
+        }
+    }
+
+    /// Stable id for a font: FNV-1a of its bytes as 16 hex digits.
+    ///
+    /// The hash does not depend on the Rust version or process, so a consumer
+    /// can compute it once per font file and match runs against it.
+    pub fn font_id(font: &dyn FontRef) -> String {
+        let hash = font.data().iter().fold(0xcbf2_9ce4_8422_2325u64, |h, &b| {
+            (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        format!("{:016x}", hash)
+    }
+
+    /// This run as a standalone JSON object
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"font_id\":");
+        push_json_string(out, &self.font_id);
+        out.push_str(",\"font_size\":");
+        push_json_number(out, self.font_size);
+        out.push_str(",\"direction\":\"");
+        out.push_str(direction_name(self.direction));
+        out.push_str("\",\"advance_width\":");
+        push_json_number(out, self.advance_width);
+        out.push_str(",\"advance_height\":");
+        push_json_number(out, self.advance_height);
+        out.push_str(",\"glyphs\":[");
+        for (i, glyph) in self.glyphs.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "{{\"id\":{},\"x\":", glyph.id);
+            push_json_number(out, glyph.x);
+            out.push_str(",\"y\":");
+            push_json_number(out, glyph.y);
+            out.push_str(",\"advance\":");
+            push_json_number(out, glyph.advance);
+            let _ = write!(out, ",\"cluster\":{}}}", glyph.cluster);
+        }
+        out.push_str("]}");
+    }
+}
+
+/// Serialize runs into one document with format and version headers
+pub fn glyph_runs_to_json(runs: &[GlyphRun]) -> String {
+    let mut out = format!(
+        "{{\"format\":\"{}\",\"version\":{},\"runs\":[",
+        GLYPH_RUN_FORMAT, GLYPH_RUN_VERSION
+    );
+    for (i, run) in runs.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        run.write_json(&mut out);
+    }
+    out.push_str("]}");
+    out
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::LeftToRight => "ltr",
+        Direction::RightToLeft => "rtl",
+        Direction::TopToBottom => "ttb",
+        Direction::BottomToTop => "btt",
+    }
+}
+
+fn push_json_number(out: &mut String, value: f32) {
+    if value.is_finite() {
+        let _ = write!(out, "{}", value);
+    } else {
+        out.push_str("null");
+    }
+}
+
+fn push_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            },
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct BytesFont(Vec<u8>);
+
+    impl FontRef for BytesFont {
+        fn data(&self) -> &[u8] {
+            &self.0
+        }
+        fn units_per_em(&self) -> u16 {
+            1000
+        }
+        fn glyph_id(&self, _ch: char) -> Option<u32> {
+            None
+        }
+        fn advance_width(&self, _glyph_id: u32) -> f32 {
+            0.0
+        }
+    }
+
+    fn shaped() -> ShapingResult {
+        ShapingResult {
+            glyphs: vec![
+                PositionedGlyph {
+                    id: 43,
+                    x: 0.0,
+                    y: 0.0,
+                    advance: 17.25,
+                    cluster: 0,
+                },
+                PositionedGlyph {
+                    id: 72,
+                    x: 17.25,
+                    y: -1.5,
+                    advance: f32::NAN,
+                    cluster: 1,
+                },
+            ],
+            advance_width: 30.0,
+            advance_height: 24.0,
+            direction: Direction::RightToLeft,
+        }
+    }
+
+    #[test]
+    fn test_glyph_runs_to_json_when_two_glyphs_then_exact_document() {
+        let run = GlyphRun::new(&shaped(), "f\"1");
+        assert_eq!(
+            glyph_runs_to_json(&[run]),
+            concat!(
+                r#"{"format":"typf-glyph-runs","version":1,"runs":["#,
+                r#"{"font_id":"f\"1","font_size":24,"direction":"rtl","#,
+                r#""advance_width":30,"advance_height":24,"glyphs":["#,
+                r#"{"id":43,"x":0,"y":0,"advance":17.25,"cluster":0},"#,
+                r#"{"id":72,"x":17.25,"y":-1.5,"advance":null,"cluster":1}]}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_font_id_when_same_bytes_then_same_id() {
+        let a = GlyphRun::font_id(&BytesFont(b"abc".to_vec()));
+        let b = GlyphRun::font_id(&BytesFont(b"abc".to_vec()));
+        let c = GlyphRun::font_id(&BytesFont(b"abd".to_vec()));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        // FNV-1a of "abc"
+        assert_eq!(a, "e71fa2190541574b");
+    }
+}
@@ -0,0 +1,258 @@
+//! Caret positions and hit testing for a shaped run.
+//!
+//! Shapers report which input byte each glyph came from (its cluster), but an
+//! editor needs the reverse: where does the caret go for byte offset N, and
+//! which offset does a click at x land on? [`caret_stops`] answers both by
+//! listing one [`CaretStop`] per character boundary of the run.
+//!
+//! Boundaries that fall inside a cluster, such as between the letters of an
+//! "ffi" ligature or inside an Arabic lam-alef, are placed with the font's
+//! ligature carets from the GDEF `LigCaretList` (via
+//! [`FontRef::ligature_carets`]). When the font has no carets for the glyph,
+//! the cluster width is split evenly between its characters.
+//!
+//! Stops sit on `char` boundaries. Callers that want grapheme-cluster caret
+//! movement filter the list with their own segmenter. Only horizontal runs
+//! are handled; vertical directions are treated as left-to-right.
+
+use crate::traits::FontRef;
+use crate::types::{Direction, ShapingResult};
+
+/// A place the caret can sit, and where it is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaretStop {
+    /// Byte offset into the shaped text.
+    pub offset: usize,
+    /// Caret x position in pixels from the run origin.
+    pub x: f32,
+}
+
+/// List the caret stops of `shaped`, in ascending byte-offset order.
+///
+/// `text` must be the string that was shaped and `font_size` the size it was
+/// shaped at, so ligature carets scale to the same pixels as the advances.
+/// The first stop is offset 0 and the last is `text.len()`.
+pub fn caret_stops(
+    text: &str,
+    shaped: &ShapingResult,
+    font: &dyn FontRef,
+    font_size: f32,
+) -> Vec<CaretStop> {
+    let rtl = shaped.direction == Direction::RightToLeft;
+    let glyphs = &shaped.glyphs;
+    if glyphs.is_empty() {
+        let mut stops = vec![CaretStop { offset: 0, x: 0.0 }];
+        if !text.is_empty() {
+            stops.push(CaretStop {
+                offset: text.len(),
+                x: shaped.advance_width,
+            });
+        }
+        return stops;
+    }
+
+    // Pen position of each glyph, ignoring mark offsets.
+    let mut pens = Vec::with_capacity(glyphs.len());
+    let mut pen = 0.0;
+    for glyph in glyphs {
+        pens.push(pen);
+        pen += glyph.advance;
+    }
+
+    // Runs of adjacent glyphs sharing a cluster, as (start byte, first, end).
+    let mut groups: Vec<(usize, usize, usize)> = Vec::new();
+    for (i, glyph) in glyphs.iter().enumerate() {
+        let start = (glyph.cluster as usize).min(text.len());
+        match groups.last_mut() {
+            Some(last) if last.0 == start => last.2 = i + 1,
+            _ => groups.push((start, i, i + 1)),
+        }
+    }
+
+    let mut starts: Vec<usize> = groups.iter().map(|g| g.0).collect();
+    starts.sort_unstable();
+    starts.dedup();
+
+    let scale = font_size / font.units_per_em().max(1) as f32;
+    let mut stops = Vec::new();
+    for &(start, first, end_glyph) in &groups {
+        let end = starts
+            .iter()
+            .find(|&&s| s > start)
+            .copied()
+            .unwrap_or(text.len());
+        let left = pens[first];
+        let right = pens[end_glyph - 1] + glyphs[end_glyph - 1].advance;
+        let (lead, trail) = if rtl { (right, left) } else { (left, right) };
+
+        stops.push(CaretStop {
+            offset: start,
+            x: lead,
+        });
+        if end == text.len() {
+            stops.push(CaretStop {
+                offset: end,
+                x: trail,
+            });
+        }
+
+        let Some(chars) = text.get(start..end) else {
+            continue;
+        };
+        let inner: Vec<usize> = chars
+            .char_indices()
+            .skip(1)
+            .map(|(i, _)| start + i)
+            .collect();
+        if inner.is_empty() {
+            continue;
+        }
+
+        let mut carets = (first..end_glyph)
+            .map(|i| (i, font.ligature_carets(glyphs[i].id)))
+            .find(|(_, carets)| carets.len() >= inner.len())
+            .map(|(i, carets)| {
+                carets
+                    .iter()
+                    .take(inner.len())
+                    .map(|c| pens[i] + c * scale)
+                    .collect::<Vec<f32>>()
+            });
+        if let Some(carets) = carets.as_mut() {
+            carets.sort_by(f32::total_cmp);
+            if rtl {
+                carets.reverse();
+            }
+        }
+
+        let parts = (inner.len() + 1) as f32;
+        for (k, &offset) in inner.iter().enumerate() {
+            let x = match &carets {
+                Some(carets) => carets[k],
+                None => lead + (trail - lead) * (k + 1) as f32 / parts,
+            };
+            stops.push(CaretStop { offset, x });
+        }
+    }
+
+    stops.sort_by_key(|stop| stop.offset);
+    stops.dedup_by_key(|stop| stop.offset);
+    stops
+}
+
+/// Byte offset of the caret stop nearest to `x`, or `None` if there are no stops.
+pub fn hit_test(stops: &[CaretStop], x: f32) -> Option<usize> {
+    stops
+        .iter()
+        .min_by(|a, b| (a.x - x).abs().total_cmp(&(b.x - x).abs()))
+        .map(|stop| stop.offset)
+}
+
+/// Caret x for `offset`, snapping back to the previous stop if `offset` is not one.
+pub fn caret_x(stops: &[CaretStop], offset: usize) -> Option<f32> {
+    stops
+        .iter()
+        .take_while(|stop| stop.offset <= offset)
+        .last()
+        .map(|stop| stop.x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PositionedGlyph;
+
+    /// Glyph 7 is an "ffi" ligature with carets at 250 and 700 units.
+    struct LigatureFont {
+        carets: bool,
+    }
+
+    impl FontRef for LigatureFont {
+        fn data(&self) -> &[u8] {
+            &[]
+        }
+        fn units_per_em(&self) -> u16 {
+            1000
+        }
+        fn glyph_id(&self, _ch: char) -> Option<u32> {
+            None
+        }
+        fn advance_width(&self, _glyph_id: u32) -> f32 {
+            1000.0
+        }
+        fn ligature_carets(&self, glyph_id: u32) -> Vec<f32> {
+            if self.carets && glyph_id == 7 {
+                vec![700.0, 250.0]
+            } else {
+                Vec::new()
+            }
+        }
+    }
+
+    fn glyph(id: u32, cluster: u32, advance: f32) -> PositionedGlyph {
+        PositionedGlyph {
+            id,
+            x: 0.0,
+            y: 0.0,
+            advance,
+            cluster,
+        }
+    }
+
+    /// "xffi": glyph 1 for "x", then the ligature, each 10 px wide.
+    fn ffi_run(direction: Direction) -> ShapingResult {
+        let mut glyphs = vec![glyph(1, 0, 10.0), glyph(7, 1, 10.0)];
+        if direction == Direction::RightToLeft {
+            glyphs.reverse();
+        }
+        ShapingResult {
+            glyphs,
+            advance_width: 20.0,
+            advance_height: 10.0,
+            direction,
+        }
+    }
+
+    fn xs(stops: &[CaretStop]) -> Vec<(usize, f32)> {
+        stops.iter().map(|s| (s.offset, s.x)).collect()
+    }
+
+    #[test]
+    fn test_caret_stops_when_ligature_has_carets_then_uses_them() {
+        let font = LigatureFont { carets: true };
+        let stops = caret_stops("xffi", &ffi_run(Direction::LeftToRight), &font, 10.0);
+        assert_eq!(
+            xs(&stops),
+            vec![(0, 0.0), (1, 10.0), (2, 12.5), (3, 17.0), (4, 20.0)]
+        );
+    }
+
+    #[test]
+    fn test_caret_stops_when_no_carets_then_splits_evenly() {
+        let font = LigatureFont { carets: false };
+        let stops = caret_stops("xffi", &ffi_run(Direction::LeftToRight), &font, 10.0);
+        let inner: Vec<f32> = stops[2..4].iter().map(|s| s.x).collect();
+        assert!((inner[0] - 13.333).abs() < 0.01 && (inner[1] - 16.667).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_caret_stops_when_rtl_then_logical_order_runs_leftward() {
+        let font = LigatureFont { carets: true };
+        let stops = caret_stops("xffi", &ffi_run(Direction::RightToLeft), &font, 10.0);
+        assert_eq!(
+            xs(&stops),
+            vec![(0, 20.0), (1, 10.0), (2, 7.0), (3, 2.5), (4, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_hit_test_and_caret_x_when_inside_ligature_then_round_trip() {
+        let font = LigatureFont { carets: true };
+        let stops = caret_stops("xffi", &ffi_run(Direction::LeftToRight), &font, 10.0);
+
+        assert_eq!(hit_test(&stops, 13.0), Some(2));
+        assert_eq!(hit_test(&stops, 16.0), Some(3));
+        assert_eq!(caret_x(&stops, 3), Some(17.0));
+        assert_eq!(hit_test(&[], 1.0), None);
+    }
+}
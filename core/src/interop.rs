@@ -0,0 +1,182 @@
+//! Bitmaps in and out of other crates' pixel types.
+//!
+//! Applications that already keep a [`tiny_skia::Pixmap`] or an
+//! [`image::RgbaImage`] per frame can hand its buffer to typf and take it
+//! back without copying: each conversion moves the `Vec<u8>` across. A
+//! bitmap in another [`PixelLayout`] is converted in place on the way out;
+//! only padded rows cost a copy.
+//!
+//! ```ignore
+//! let bitmap = BitmapData::from(pixmap);
+//! let pixmap = tiny_skia::Pixmap::try_from(bitmap)?;
+//! ```
+//!
+//! Needs the `tiny-skia` or `image` feature. Only `Rgba8` bitmaps convert.
+
+use crate::error::{RenderError, Result, TypfError};
+use crate::types::{BitmapData, BitmapFormat, PixelLayout};
+
+/// `bitmap` as tightly packed `Rgba8` in `layout`, moved rather than copied
+/// when it already is.
+fn into_packed_rgba(bitmap: BitmapData, layout: PixelLayout) -> Result<BitmapData> {
+    if bitmap.format != BitmapFormat::Rgba8 {
+        return Err(RenderError::FormatNotSupported(format!(
+            "only Rgba8 bitmaps convert to other pixel types, not {:?}",
+            bitmap.format
+        ))
+        .into());
+    }
+    let mut bitmap = if bitmap.is_packed() {
+        bitmap
+    } else {
+        bitmap.to_packed().into_owned()
+    };
+    // Short buffers read as transparent, like `to_packed` fills missing rows
+    bitmap
+        .data
+        .resize(bitmap.stride * bitmap.height as usize, 0);
+    Ok(bitmap.into_layout(layout))
+}
+
+#[cfg(feature = "tiny-skia")]
+impl From<tiny_skia::Pixmap> for BitmapData {
+    /// Takes over the pixmap's premultiplied RGBA buffer.
+    fn from(pixmap: tiny_skia::Pixmap) -> Self {
+        let (width, height) = (pixmap.width(), pixmap.height());
+        BitmapData {
+            width,
+            height,
+            format: BitmapFormat::Rgba8,
+            stride: BitmapFormat::Rgba8.row_bytes(width),
+            layout: PixelLayout::PREMULTIPLIED_RGBA,
+            data: pixmap.take(),
+        }
+    }
+}
+
+#[cfg(feature = "tiny-skia")]
+impl TryFrom<BitmapData> for tiny_skia::Pixmap {
+    type Error = TypfError;
+
+    /// Moves the bitmap's buffer into a pixmap, premultiplying it first
+    /// unless it already is.
+    fn try_from(bitmap: BitmapData) -> Result<Self> {
+        let (width, height) = (bitmap.width, bitmap.height);
+        let bitmap = into_packed_rgba(bitmap, PixelLayout::PREMULTIPLIED_RGBA)?;
+        let size = tiny_skia::IntSize::from_wh(width, height)
+            .ok_or(RenderError::ZeroDimensions { width, height })?;
+        tiny_skia::Pixmap::from_vec(bitmap.data, size)
+            .ok_or_else(|| RenderError::InvalidDimensions { width, height }.into())
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<image::RgbaImage> for BitmapData {
+    /// Takes over the image's straight RGBA buffer.
+    fn from(image: image::RgbaImage) -> Self {
+        let (width, height) = image.dimensions();
+        BitmapData {
+            width,
+            height,
+            format: BitmapFormat::Rgba8,
+            stride: BitmapFormat::Rgba8.row_bytes(width),
+            layout: PixelLayout::RGBA,
+            data: image.into_raw(),
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+impl TryFrom<BitmapData> for image::RgbaImage {
+    type Error = TypfError;
+
+    /// Moves the bitmap's buffer into an image, unpremultiplying or
+    /// reordering it first if needed.
+    fn try_from(bitmap: BitmapData) -> Result<Self> {
+        let (width, height) = (bitmap.width, bitmap.height);
+        let bitmap = into_packed_rgba(bitmap, PixelLayout::RGBA)?;
+        image::RgbaImage::from_raw(width, height, bitmap.data)
+            .ok_or_else(|| RenderError::InvalidDimensions { width, height }.into())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn bitmap(stride: usize, layout: PixelLayout, data: Vec<u8>) -> BitmapData {
+        BitmapData {
+            width: 2,
+            height: 1,
+            format: BitmapFormat::Rgba8,
+            stride,
+            layout,
+            data,
+        }
+    }
+
+    #[cfg(feature = "tiny-skia")]
+    #[test]
+    fn test_pixmap_round_trip_when_packed_then_buffer_moves() {
+        let mut pixmap = tiny_skia::Pixmap::new(2, 1).expect("pixmap");
+        pixmap.data_mut()[..4].copy_from_slice(&[100, 50, 0, 128]);
+        let ptr = pixmap.data().as_ptr();
+
+        let bitmap = BitmapData::from(pixmap);
+        assert_eq!(bitmap.layout, PixelLayout::PREMULTIPLIED_RGBA);
+        assert_eq!(bitmap.data.as_ptr(), ptr);
+
+        let pixmap = tiny_skia::Pixmap::try_from(bitmap).expect("pixmap");
+        assert_eq!(pixmap.data().as_ptr(), ptr);
+        assert_eq!(&pixmap.data()[..4], &[100, 50, 0, 128]);
+    }
+
+    #[cfg(feature = "tiny-skia")]
+    #[test]
+    fn test_pixmap_from_bitmap_when_straight_and_padded_then_premultiplied() {
+        let padded = bitmap(
+            12,
+            PixelLayout::RGBA,
+            vec![200, 100, 0, 128, 0, 0, 0, 0, 9, 9, 9, 9],
+        );
+        let pixmap = tiny_skia::Pixmap::try_from(padded).expect("pixmap");
+        assert_eq!(pixmap.data(), &[100, 50, 0, 128, 0, 0, 0, 0]);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_image_round_trip_when_straight_then_buffer_moves() {
+        let image =
+            image::RgbaImage::from_raw(2, 1, vec![1, 2, 3, 255, 4, 5, 6, 255]).expect("image");
+        let ptr = image.as_raw().as_ptr();
+
+        let bitmap = BitmapData::from(image);
+        assert_eq!(bitmap.layout, PixelLayout::RGBA);
+        assert_eq!(bitmap.data.as_ptr(), ptr);
+
+        let image = image::RgbaImage::try_from(bitmap).expect("image");
+        assert_eq!(image.as_raw().as_ptr(), ptr);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_image_from_bitmap_when_premultiplied_bgra_then_straight_rgba() {
+        let bgra = bitmap(
+            8,
+            PixelLayout::PREMULTIPLIED_BGRA,
+            vec![0, 50, 100, 128, 0, 0, 0, 0],
+        );
+        let image = image::RgbaImage::try_from(bgra).expect("image");
+        assert_eq!(image.as_raw(), &[199, 100, 0, 128, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_into_packed_rgba_when_gray_then_errors() {
+        let gray = BitmapData {
+            format: BitmapFormat::Gray8,
+            ..bitmap(2, PixelLayout::RGBA, vec![0, 255])
+        };
+        assert!(into_packed_rgba(gray, PixelLayout::RGBA).is_err());
+    }
+}
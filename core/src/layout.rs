@@ -0,0 +1,128 @@
+//! Fitting text to a width: where lines may break, and what becomes of a
+//! line that is still too wide.
+//!
+//! A [`TextLayout`] in [`RenderParams::layout`](crate::RenderParams::layout)
+//! sets the width. `TypfPipeline` wraps measured text by its [`WrapMode`]
+//! and cuts rendered lines short with an ellipsis under
+//! [`Overflow::Ellipsis`]. The opixa, skia, zeno, and SVG renderers size
+//! their canvas by [`TextLayout::visible_width`]: under [`Overflow::Clip`]
+//! and [`Overflow::Ellipsis`] the text box ends at the width and ink past it
+//! is cut off; under [`Overflow::Visible`] the canvas grows to the whole line.
+//!
+//! Without a width, nothing wraps and nothing is cut. A height bounds the
+//! lines `TypfPipeline::lay_out_block` places, for flowing text across
+//! pages or columns; renderers, which draw one line, ignore it.
+
+/// Where a line that is too wide may break
+///
+/// Only `TypfPipeline::measure`, `lay_out_block`, and `lay_out_columns`
+/// break lines; `render_text` and the renderers always draw one line, cut
+/// short or clipped by the [`Overflow`] policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum WrapMode {
+    /// Between words, at UAX #14 line break opportunities; a word wider
+    /// than the line overflows it
+    #[default]
+    Word,
+    /// Between any two grapheme clusters
+    Char,
+    /// Between words, and between grapheme clusters inside a word that
+    /// would overflow a line on its own, for CJK mixed with long Latin words
+    WordThenChar,
+    /// Only after line terminators
+    NoWrap,
+}
+
+/// What happens to a line wider than the width
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// The canvas grows to fit the whole line
+    #[default]
+    Visible,
+    /// The canvas ends at the width and cuts off what is past it
+    Clip,
+    /// The line is cut short and ends in "…", or "..." when no font has a
+    /// glyph for it
+    Ellipsis,
+}
+
+/// A width to fit text to, and how
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TextLayout {
+    /// Widest a line may be, in pixels; `None` leaves lines as wide as their
+    /// text. Default: none.
+    pub max_width: Option<f32>,
+    /// Where lines break. Default: [`WrapMode::Word`].
+    pub wrap: WrapMode,
+    /// What happens to lines that are still too wide. Default:
+    /// [`Overflow::Visible`].
+    pub overflow: Overflow,
+    /// Tallest a block of lines may be, in pixels; `None` takes every line.
+    /// Default: none.
+    pub max_height: Option<f32>,
+}
+
+impl TextLayout {
+    /// Fit to `max_width`, wrapping between words and letting what does
+    /// not fit show
+    pub fn new(max_width: f32) -> Self {
+        Self {
+            max_width: Some(max_width),
+            ..Self::default()
+        }
+    }
+
+    /// Break lines by `wrap` instead
+    pub fn with_wrap(mut self, wrap: WrapMode) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Treat lines that are still too wide by `overflow` instead
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Also bound blocks of lines to `max_height`
+    pub fn with_max_height(mut self, max_height: f32) -> Self {
+        self.max_height = Some(max_height);
+        self
+    }
+
+    /// Whether a line `advance` pixels wide is wider than the width
+    pub fn overflows(&self, advance: f32) -> bool {
+        self.max_width.is_some_and(|max| advance > max)
+    }
+
+    /// How much of a line `advance` pixels wide the canvas shows: all of it
+    /// under [`Overflow::Visible`], at most the width otherwise
+    pub fn visible_width(&self, advance: f32) -> f32 {
+        match (self.max_width, self.overflow) {
+            (Some(max), Overflow::Clip | Overflow::Ellipsis) => advance.min(max.max(0.0)),
+            _ => advance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_width_when_clipped_then_capped_at_max_width() {
+        let clip = TextLayout::new(50.0).with_overflow(Overflow::Clip);
+        assert_eq!(clip.visible_width(80.0), 50.0);
+        assert_eq!(clip.visible_width(30.0), 30.0);
+        assert!(clip.overflows(80.0));
+        assert!(!clip.overflows(30.0));
+    }
+
+    #[test]
+    fn test_visible_width_when_visible_or_no_width_then_whole_line() {
+        assert_eq!(TextLayout::new(50.0).visible_width(80.0), 80.0);
+        let unbounded = TextLayout::default().with_overflow(Overflow::Ellipsis);
+        assert_eq!(unbounded.visible_width(80.0), 80.0);
+        assert!(!unbounded.overflows(80.0));
+    }
+}
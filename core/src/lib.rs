@@ -69,21 +69,67 @@ pub fn get_max_bitmap_pixels() -> u64 {
         .unwrap_or(DEFAULT_MAX_BITMAP_PIXELS)
 }
 
+pub mod budget;
 pub mod cache;
 pub mod cache_config;
+pub mod cancel;
+pub mod case;
+pub mod color_span;
 pub mod context;
+pub mod diagnostics;
+pub mod digits;
+pub mod effects;
+pub mod emoji;
 pub mod error;
 pub mod ffi;
 pub mod glyph_cache;
+#[cfg(feature = "glyph-metrics")]
+pub mod glyph_metrics;
+pub mod glyph_run;
+pub mod hit_test;
+#[cfg(any(feature = "tiny-skia", feature = "image"))]
+pub mod interop;
+pub mod layout;
 pub mod linra;
+mod mapped_text;
+#[cfg(feature = "outline")]
+pub mod outline;
+pub mod palette;
 pub mod pipeline;
+pub mod registry;
+pub mod render_cache;
+pub mod render_target;
+pub mod run_offset;
+pub mod sanitize;
+pub mod sdf;
+mod sfnt;
 pub mod shaping_cache;
+pub mod source_log;
+pub mod stats;
 pub mod traits;
-
+pub mod transform;
+pub mod truncate;
+pub mod whitespace;
+
+pub use budget::MemoryBudget;
+pub use cancel::CancelToken;
+pub use case::TextCase;
+pub use color_span::ColorSpan;
 pub use context::PipelineContext;
-pub use error::{Result, TypfError};
+pub use digits::NumberSubstitution;
+pub use effects::{BackgroundBox, BoxScope, DropShadow, Insets, RenderEffects, Stroke};
+pub use emoji::EmojiPresentation;
+pub use error::{Classify, ErrorKind, Result, TypfError};
+pub use glyph_run::{glyph_runs_to_json, GlyphRun};
+pub use layout::{Overflow, TextLayout, WrapMode};
+pub use palette::PaletteSelection;
 pub use pipeline::{Pipeline, PipelineBuilder};
+pub use registry::RendererRegistry;
+pub use run_offset::RunOffset;
+pub use stats::RenderStats;
 pub use traits::{Exporter, Renderer, Shaper, Stage};
+pub use transform::TextTransform;
+pub use whitespace::TabStops;
 
 /// Maximum font size in pixels to prevent DoS attacks.
 ///
@@ -108,7 +154,11 @@ pub mod types {
     /// Minimal, stable font-wide metrics in font units.
     ///
     /// These are intended for layout/baseline decisions by consumers that only have a `FontRef`.
+    /// `ascent`, `descent`, and `line_gap` are the OS/2 typo values, or `hhea`'s
+    /// without an OS/2 table; [`line_metrics`](Self::line_metrics) picks
+    /// between the tables the way a given platform does.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct FontMetrics {
         /// Units per em (typically 1000 or 2048).
         pub units_per_em: u16,
@@ -118,6 +168,241 @@ pub mod types {
         pub descent: i16,
         /// Recommended gap between lines.
         pub line_gap: i16,
+        /// Where the font's alphabetic, ideographic, hanging, and math baselines sit.
+        pub baselines: Baselines,
+        /// Ascender, descender, and line gap from the `hhea` table.
+        pub hhea: LineMetrics,
+        /// `sTypoAscender`, `sTypoDescender`, and `sTypoLineGap` from OS/2.
+        pub typo: Option<LineMetrics>,
+        /// `usWinAscent` and `usWinDescent` from OS/2, descent negated, no line gap.
+        pub win: Option<LineMetrics>,
+        /// Whether OS/2 sets `USE_TYPO_METRICS`, asking for the typo values.
+        pub use_typo_metrics: bool,
+        /// Height of lowercase x from OS/2, when the table version has it.
+        pub x_height: Option<i16>,
+        /// Height of flat capitals from OS/2, when the table version has it.
+        pub cap_height: Option<i16>,
+        /// Recommended subscript size and offset from OS/2.
+        pub subscript: Option<ScriptMetrics>,
+        /// Recommended superscript size and offset from OS/2.
+        pub superscript: Option<ScriptMetrics>,
+    }
+
+    impl FontMetrics {
+        /// Ascent, descent, and line gap chosen the way `policy` says.
+        ///
+        /// Tables the font lacks fall back to `hhea`.
+        pub fn line_metrics(&self, policy: LineMetricsPolicy) -> LineMetrics {
+            match policy {
+                LineMetricsPolicy::Css => match self.typo {
+                    Some(typo) if self.use_typo_metrics => typo,
+                    _ if self.hhea.ascent != 0 || self.hhea.descent != 0 => self.hhea,
+                    _ => self.typo.or(self.win).unwrap_or(self.hhea),
+                },
+                LineMetricsPolicy::Hhea => self.hhea,
+                LineMetricsPolicy::Typo => self.typo.unwrap_or(self.hhea),
+                LineMetricsPolicy::Win => match self.win {
+                    // GDI's external leading: whatever hhea adds beyond the win box
+                    Some(win) => LineMetrics {
+                        line_gap: (self.hhea.height() - win.height()).clamp(0, i16::MAX as i32)
+                            as i16,
+                        ..win
+                    },
+                    None => self.hhea,
+                },
+            }
+        }
+
+        /// Baseline-to-baseline distance in pixels at `size`, as `policy` picks it.
+        pub fn line_height(&self, policy: LineMetricsPolicy, size: f32) -> f32 {
+            if self.units_per_em == 0 {
+                return 0.0;
+            }
+            self.line_metrics(policy).height() as f32 * size / self.units_per_em as f32
+        }
+
+        /// Height of `baseline` above the glyph origin, in pixels at `size`.
+        pub fn baseline(&self, baseline: Baseline, size: f32) -> f32 {
+            if self.units_per_em == 0 {
+                return 0.0;
+            }
+            self.baselines.get(baseline) as f32 * size / self.units_per_em as f32
+        }
+
+        /// Pixels to raise a run set in this font at `size` so its `baseline`
+        /// lines up with the same baseline of a line set in `line` at `line_size`.
+        ///
+        /// Both are measured from a shared glyph origin. Pass the result as
+        /// [`RenderParams::baseline_shift`](crate::RenderParams::baseline_shift)
+        /// when rendering the run; negative values lower it.
+        pub fn baseline_shift(
+            &self,
+            size: f32,
+            line: &FontMetrics,
+            line_size: f32,
+            baseline: Baseline,
+        ) -> f32 {
+            line.baseline(baseline, line_size) - self.baseline(baseline, size)
+        }
+    }
+
+    /// A font's recommended subscript or superscript glyph size and placement,
+    /// in font units, as the OS/2 table states them.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct ScriptMetrics {
+        /// Horizontal size of the scaled glyphs.
+        pub x_size: i16,
+        /// Vertical size of the scaled glyphs.
+        pub y_size: i16,
+        /// Horizontal offset of the scaled run, for italics.
+        pub x_offset: i16,
+        /// Distance from the parent baseline to the scaled run's baseline.
+        ///
+        /// Like OS/2, positive moves subscripts down and superscripts up.
+        pub y_offset: i16,
+    }
+
+    /// Ascent, descent, and line gap as one font table states them, in font units.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct LineMetrics {
+        /// Distance from baseline to the top of the line box.
+        pub ascent: i16,
+        /// Distance from baseline to the bottom of the line box (usually negative).
+        pub descent: i16,
+        /// Extra space between line boxes.
+        pub line_gap: i16,
+    }
+
+    impl LineMetrics {
+        /// Baseline-to-baseline distance: ascent minus descent plus line gap.
+        pub fn height(&self) -> i32 {
+            self.ascent as i32 - self.descent as i32 + self.line_gap as i32
+        }
+    }
+
+    /// Which table's vertical metrics set the line height.
+    ///
+    /// Fonts often disagree with themselves: `hhea`, OS/2 typo, and OS/2 win
+    /// metrics can describe three different line boxes, and each platform
+    /// reads a different one.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub enum LineMetricsPolicy {
+        /// What browsers use for CSS `line-height: normal`: typo metrics when
+        /// the font sets `USE_TYPO_METRICS`, else `hhea`, else typo, else win.
+        #[default]
+        Css,
+        /// `hhea` only, like macOS CoreText.
+        Hhea,
+        /// OS/2 typo metrics, like DirectWrite with `USE_TYPO_METRICS`.
+        Typo,
+        /// OS/2 win metrics plus GDI-style external leading, like classic Windows.
+        Win,
+    }
+
+    /// One of the baselines named by the OpenType `BASE` table.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Baseline {
+        /// Latin, Cyrillic, Greek, and most other scripts sit here (`romn`).
+        Alphabetic,
+        /// Bottom of the ideographic em box, used by Han, kana, and Hangul (`ideo`).
+        Ideographic,
+        /// The headstroke that Devanagari, Bengali, Gurmukhi, and Tibetan hang from (`hang`).
+        Hanging,
+        /// Centerline of math operators such as minus and plus (`math`).
+        Math,
+    }
+
+    impl Baseline {
+        /// The four-letter `BASE` table tag.
+        pub fn tag(self) -> &'static str {
+            match self {
+                Baseline::Alphabetic => "romn",
+                Baseline::Ideographic => "ideo",
+                Baseline::Hanging => "hang",
+                Baseline::Math => "math",
+            }
+        }
+
+        /// Parse a `BASE` table tag such as `"hang"`.
+        pub fn from_tag(tag: &str) -> Option<Self> {
+            match tag {
+                "romn" => Some(Baseline::Alphabetic),
+                "ideo" => Some(Baseline::Ideographic),
+                "hang" => Some(Baseline::Hanging),
+                "math" => Some(Baseline::Math),
+                _ => None,
+            }
+        }
+
+        /// The baseline text in `script` aligns on.
+        ///
+        /// Takes ISO 15924 (`"Deva"`) or OpenType (`"dev2"`) script tags in
+        /// any case. Scripts without a hanging or ideographic baseline, and
+        /// unknown tags, align on the alphabetic baseline.
+        pub fn for_script(script: &str) -> Self {
+            let script = script.trim().to_ascii_lowercase();
+            match script.as_str() {
+                "hani" | "hans" | "hant" | "hira" | "kana" | "hrkt" | "jpan" | "hang" | "kore"
+                | "bopo" | "yi" | "yiii" => Baseline::Ideographic,
+                "deva" | "dev2" | "beng" | "bng2" | "guru" | "gur2" | "tibt" => Baseline::Hanging,
+                _ => Baseline::Alphabetic,
+            }
+        }
+    }
+
+    /// Baseline positions in font units, measured up from the glyph origin.
+    ///
+    /// Read from the font's `BASE` table where it has them. Missing values
+    /// are synthesized from the ascent and descent, see [`Baselines::synthesized`].
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Baselines {
+        /// Usually 0: most fonts put the glyph origin on the alphabetic baseline.
+        pub alphabetic: i16,
+        /// Usually negative, at or near the descent.
+        pub ideographic: i16,
+        /// Usually well above the x-height.
+        pub hanging: i16,
+        /// Usually around half the x-height.
+        pub math: i16,
+    }
+
+    impl Baselines {
+        /// Approximate baselines for a font without a `BASE` table.
+        ///
+        /// Alphabetic sits on the origin, ideographic on the descent, hanging
+        /// at 60% of the ascent, and math at 30% of the ascent.
+        pub fn synthesized(ascent: i16, descent: i16) -> Self {
+            let ascent = ascent.max(0) as f32;
+            Self {
+                alphabetic: 0,
+                ideographic: descent.min(0),
+                hanging: (ascent * 0.6).round() as i16,
+                math: (ascent * 0.3).round() as i16,
+            }
+        }
+
+        /// Position of `baseline` in font units.
+        pub fn get(&self, baseline: Baseline) -> i16 {
+            match baseline {
+                Baseline::Alphabetic => self.alphabetic,
+                Baseline::Ideographic => self.ideographic,
+                Baseline::Hanging => self.hanging,
+                Baseline::Math => self.math,
+            }
+        }
+
+        /// Set `baseline` to `value` font units.
+        pub fn set(&mut self, baseline: Baseline, value: i16) {
+            match baseline {
+                Baseline::Alphabetic => self.alphabetic = value,
+                Baseline::Ideographic => self.ideographic = value,
+                Baseline::Hanging => self.hanging = value,
+                Baseline::Math => self.math = value,
+            }
+        }
     }
 
     /// A variable font axis definition.
@@ -141,6 +426,7 @@ pub mod types {
     }
 
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum Direction {
         /// Standard Latin, Cyrillic, etc.
         LeftToRight,
@@ -152,8 +438,78 @@ pub mod types {
         BottomToTop,
     }
 
+    /// Posture requested from a font family.
+    ///
+    /// Backends that pick faces by style (the platform linra renderers) use it
+    /// to choose an italic or oblique face, and slant the upright face when
+    /// the family has none.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub enum FontStyle {
+        /// Upright.
+        #[default]
+        Normal,
+        /// A cursive italic design, or a slanted upright when there is none.
+        Italic,
+        /// A slanted upright, or an italic face when there is none.
+        Oblique,
+    }
+
+    /// Width requested from a font family, the nine OS/2 `usWidthClass` steps.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub enum FontStretch {
+        /// 50% of normal width.
+        UltraCondensed,
+        /// 62.5% of normal width.
+        ExtraCondensed,
+        /// 75% of normal width.
+        Condensed,
+        /// 87.5% of normal width.
+        SemiCondensed,
+        /// The family's normal width.
+        #[default]
+        Normal,
+        /// 112.5% of normal width.
+        SemiExpanded,
+        /// 125% of normal width.
+        Expanded,
+        /// 150% of normal width.
+        ExtraExpanded,
+        /// 200% of normal width.
+        UltraExpanded,
+    }
+
+    impl FontStretch {
+        const ALL: [FontStretch; 9] = [
+            FontStretch::UltraCondensed,
+            FontStretch::ExtraCondensed,
+            FontStretch::Condensed,
+            FontStretch::SemiCondensed,
+            FontStretch::Normal,
+            FontStretch::SemiExpanded,
+            FontStretch::Expanded,
+            FontStretch::ExtraExpanded,
+            FontStretch::UltraExpanded,
+        ];
+
+        /// The OS/2 `usWidthClass` value, 1 (ultra-condensed) to 9 (ultra-expanded).
+        pub fn width_class(self) -> u16 {
+            self as u16 + 1
+        }
+
+        /// The stretch for an OS/2 `usWidthClass` value, `None` outside 1..=9.
+        pub fn from_width_class(class: u16) -> Option<Self> {
+            Self::ALL.get(usize::from(class).checked_sub(1)?).copied()
+        }
+
+        /// Width as a percentage of normal, the scale of the `wdth` axis.
+        pub fn percent(self) -> f32 {
+            [50.0, 62.5, 75.0, 87.5, 100.0, 112.5, 125.0, 150.0, 200.0][self as usize]
+        }
+    }
+
     /// One shaped glyph with its final position inside the run.
     #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct PositionedGlyph {
         /// The glyph ID in the font.
         pub id: GlyphId,
@@ -169,6 +525,7 @@ pub mod types {
 
     /// Output from the shaping stage, ready for rendering.
     #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct ShapingResult {
         /// The list of positioned glyphs.
         pub glyphs: Vec<PositionedGlyph>,
@@ -180,6 +537,41 @@ pub mod types {
         pub direction: Direction,
     }
 
+    impl ShapingResult {
+        /// Hash of everything that decides how this run looks once rendered.
+        ///
+        /// Covers glyph ids and positions in order, the run's advances and
+        /// direction, and `font_key`, a caller-chosen identity for the font
+        /// and its instance (the shaped glyphs do not record which font they
+        /// came from). Clusters are left out because they don't change pixels.
+        ///
+        /// UI code can keep the previous frame's hash per line and skip
+        /// re-rendering when it matches. The value is stable for the life of
+        /// the process but not across Rust releases, so don't persist it.
+        pub fn content_hash(&self, font_key: u64) -> u64 {
+            use std::hash::{Hash, Hasher};
+
+            // `-0.0 + 0.0` is `+0.0`, so both zeros draw and hash the same
+            fn bits(value: f32) -> u32 {
+                (value + 0.0).to_bits()
+            }
+
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            font_key.hash(&mut hasher);
+            self.direction.hash(&mut hasher);
+            bits(self.advance_width).hash(&mut hasher);
+            bits(self.advance_height).hash(&mut hasher);
+            self.glyphs.len().hash(&mut hasher);
+            for glyph in &self.glyphs {
+                glyph.id.hash(&mut hasher);
+                bits(glyph.x).hash(&mut hasher);
+                bits(glyph.y).hash(&mut hasher);
+                bits(glyph.advance).hash(&mut hasher);
+            }
+            hasher.finish()
+        }
+    }
+
     #[derive(Debug, Clone)]
     pub enum RenderOutput {
         /// Rasterized bitmap (PNG, PBM, etc.).
@@ -212,10 +604,19 @@ pub mod types {
     }
 
     #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct BitmapData {
         pub width: u32,
         pub height: u32,
         pub format: BitmapFormat,
+        /// Bytes from the start of one row to the start of the next.
+        ///
+        /// At least [`BitmapFormat::row_bytes`] of the width; renderers pad
+        /// rows past that for [`RenderParams::row_alignment`](crate::RenderParams::row_alignment).
+        pub stride: usize,
+        /// Channel order and alpha mode of `Rgba8` pixels; other formats
+        /// ignore it.
+        pub layout: PixelLayout,
         pub data: Vec<u8>,
     }
 
@@ -224,16 +625,185 @@ pub mod types {
         pub fn byte_size(&self) -> usize {
             self.data.len()
         }
+
+        /// Whether rows follow each other without padding.
+        pub fn is_packed(&self) -> bool {
+            self.stride == self.format.row_bytes(self.width)
+        }
+
+        /// The pixels of row `y`, without padding; none past the data.
+        pub fn row(&self, y: u32) -> Option<&[u8]> {
+            let start = y as usize * self.stride;
+            self.data
+                .get(start..start + self.format.row_bytes(self.width))
+        }
+
+        /// This bitmap with rows packed, copied only when padded.
+        ///
+        /// For consumers that index pixels as `y * width + x`. Rows missing
+        /// from a short buffer come out as zeros.
+        pub fn to_packed(&self) -> std::borrow::Cow<'_, BitmapData> {
+            if self.is_packed() {
+                return std::borrow::Cow::Borrowed(self);
+            }
+            let row = self.format.row_bytes(self.width);
+            let mut data = Vec::with_capacity(row * self.height as usize);
+            for y in 0..self.height {
+                match self.row(y) {
+                    Some(pixels) => data.extend_from_slice(pixels),
+                    None => data.resize(data.len() + row, 0),
+                }
+            }
+            std::borrow::Cow::Owned(BitmapData {
+                stride: row,
+                data,
+                ..*self
+            })
+        }
+
+        /// This bitmap in `layout`, copied only when it differs.
+        ///
+        /// Exporters and compositors call this with [`PixelLayout::RGBA`]
+        /// instead of guessing what each renderer produced. Formats other
+        /// than `Rgba8` come back unchanged.
+        pub fn to_layout(&self, layout: PixelLayout) -> std::borrow::Cow<'_, BitmapData> {
+            if self.format != BitmapFormat::Rgba8 || self.layout == layout {
+                return std::borrow::Cow::Borrowed(self);
+            }
+            std::borrow::Cow::Owned(self.clone().into_layout(layout))
+        }
+
+        /// Converts this bitmap's pixels to `layout` in place.
+        ///
+        /// Unpremultiplying rounds to nearest and zeroes the color of fully
+        /// transparent pixels. Row padding is left as it is.
+        pub fn into_layout(mut self, layout: PixelLayout) -> Self {
+            if self.format != BitmapFormat::Rgba8 || self.layout == layout {
+                return self;
+            }
+            let swap = self.layout.order != layout.order;
+            let alpha = (self.layout.alpha, layout.alpha);
+            let row = self.format.row_bytes(self.width);
+            for line in self.data.chunks_mut(self.stride.max(1)) {
+                let end = row.min(line.len());
+                for px in line[..end].chunks_exact_mut(4) {
+                    if swap {
+                        px.swap(0, 2);
+                    }
+                    let a = u32::from(px[3]);
+                    match alpha {
+                        (AlphaMode::Straight, AlphaMode::Premultiplied) => {
+                            for c in &mut px[..3] {
+                                *c = ((u32::from(*c) * a + 127) / 255) as u8;
+                            }
+                        },
+                        (AlphaMode::Premultiplied, AlphaMode::Straight) if a == 0 => {
+                            px[..3].fill(0);
+                        },
+                        (AlphaMode::Premultiplied, AlphaMode::Straight) => {
+                            for c in &mut px[..3] {
+                                *c = ((u32::from(*c) * 255 + a / 2) / a).min(255) as u8;
+                            }
+                        },
+                        _ => {},
+                    }
+                }
+            }
+            self.layout = layout;
+            self
+        }
     }
 
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    /// Byte order of the color channels in four-byte pixels.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum ChannelOrder {
+        /// Red, green, blue, alpha
+        #[default]
+        Rgba,
+        /// Blue, green, red, alpha, as Direct2D and most Windows APIs use
+        Bgra,
+    }
+
+    /// How color channels relate to alpha.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum AlphaMode {
+        /// Color channels hold the full color regardless of alpha
+        #[default]
+        Straight,
+        /// Color channels are already scaled by alpha, ready to composite
+        Premultiplied,
+    }
+
+    /// Channel order and alpha mode of four-byte pixels.
+    ///
+    /// The default, straight RGBA, is what the pure-Rust renderers draw and
+    /// what the exporters expect. [`BitmapData::to_layout`] is the one place
+    /// pixels move between layouts.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PixelLayout {
+        pub order: ChannelOrder,
+        pub alpha: AlphaMode,
+    }
+
+    impl PixelLayout {
+        /// Straight-alpha RGBA, the default
+        pub const RGBA: Self = Self {
+            order: ChannelOrder::Rgba,
+            alpha: AlphaMode::Straight,
+        };
+        /// Premultiplied RGBA, as tiny-skia and CoreGraphics draw
+        pub const PREMULTIPLIED_RGBA: Self = Self {
+            order: ChannelOrder::Rgba,
+            alpha: AlphaMode::Premultiplied,
+        };
+        /// Premultiplied BGRA, as Direct2D and WIC draw
+        pub const PREMULTIPLIED_BGRA: Self = Self {
+            order: ChannelOrder::Bgra,
+            alpha: AlphaMode::Premultiplied,
+        };
+    }
+
+    /// Pixel layout of a [`BitmapData`].
+    ///
+    /// Rows take [`row_bytes`](Self::row_bytes) each, padded out to a
+    /// [`BitmapData::stride`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum BitmapFormat {
+        /// Straight-alpha red, green, blue, alpha; four bytes per pixel
         Rgba8,
+        /// Red, green, blue; three bytes per pixel
         Rgb8,
+        /// Coverage (alpha only); one byte per pixel
         Gray8,
+        /// One bit per pixel, set where covered, most significant bit
+        /// leftmost; each row starts on a fresh byte
         Gray1,
     }
 
+    impl BitmapFormat {
+        /// Bytes one `width`-pixel row takes.
+        pub fn row_bytes(self, width: u32) -> usize {
+            let width = width as usize;
+            match self {
+                Self::Rgba8 => width * 4,
+                Self::Rgb8 => width * 3,
+                Self::Gray8 => width,
+                Self::Gray1 => width.div_ceil(8),
+            }
+        }
+
+        /// Row stride for `width` pixels, rounded up to a multiple of
+        /// `alignment` bytes. Alignments of zero and one pack rows.
+        pub fn stride(self, width: u32, alignment: u32) -> usize {
+            self.row_bytes(width)
+                .next_multiple_of(alignment.max(1) as usize)
+        }
+    }
+
     #[derive(Debug, Clone)]
     pub struct VectorData {
         pub format: VectorFormat,
@@ -410,6 +980,7 @@ pub mod types {
     }
 
     #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct TextRun {
         pub text: String,
         pub start: usize,
@@ -486,6 +1057,28 @@ pub struct ShapingParams {
     /// Added after the shaper's advance width for each glyph. Positive values
     /// spread glyphs apart; negative values bring them closer together.
     pub letter_spacing: f32,
+    /// Where tab characters stop. Default: every 8 space advances.
+    ///
+    /// Shapers that lay out tabs themselves use this to size each tab so the
+    /// text after it starts at the next stop.
+    pub tab_stops: whitespace::TabStops,
+    /// Draw ASCII digits as the native digits of `language`. Default: off.
+    ///
+    /// Shapers swap the digits before shaping, so every backend produces
+    /// the same glyphs; see [`digits`].
+    pub number_substitution: digits::NumberSubstitution,
+    /// Draw the text uppercase, lowercase, or in small caps. Default: as
+    /// written.
+    ///
+    /// Shapers map the case before shaping and use the font's `smcp` for
+    /// small caps. Fonts without it get synthesized small caps only from
+    /// `TypfPipeline::render_text`; see [`TextCase::SmallCaps`].
+    pub case: case::TextCase,
+    /// Abort shaping once this token trips. Default: none.
+    ///
+    /// Shapers check it before and after shaping, so a cancelled request
+    /// stops at the next run boundary with [`TypfError::Cancelled`].
+    pub cancel: Option<CancelToken>,
 }
 
 impl Default for ShapingParams {
@@ -498,6 +1091,10 @@ impl Default for ShapingParams {
             features: Vec::new(),
             variations: Vec::new(),
             letter_spacing: 0.0,
+            tab_stops: whitespace::TabStops::default(),
+            number_substitution: digits::NumberSubstitution::None,
+            case: case::TextCase::None,
+            cancel: None,
         }
     }
 }
@@ -540,6 +1137,19 @@ impl ShapingParams {
         }
         Ok(())
     }
+
+    /// Whether kerning is on: true unless `features` sets `kern` to 0.
+    ///
+    /// Shapers that run OpenType features get this from the feature list
+    /// directly; shapers that apply legacy `kern` pairs by hand check it here.
+    /// When `kern` appears more than once, the last setting wins.
+    pub fn kerning(&self) -> bool {
+        self.features
+            .iter()
+            .rev()
+            .find(|(tag, _)| tag == "kern")
+            .is_none_or(|(_, value)| *value != 0)
+    }
 }
 
 /// Validate glyph count against security limits
@@ -578,6 +1188,17 @@ pub enum GlyphSource {
     Ebdt,
 }
 
+impl GlyphSource {
+    /// Whether glyphs from this source carry their own colors (COLR, SVG, and
+    /// the bitmap tables) rather than taking the foreground color.
+    pub fn is_color(self) -> bool {
+        !matches!(
+            self,
+            GlyphSource::Glyf | GlyphSource::Cff | GlyphSource::Cff2
+        )
+    }
+}
+
 const DEFAULT_GLYPH_SOURCES: [GlyphSource; 9] = [
     GlyphSource::Glyf,
     GlyphSource::Cff2,
@@ -590,11 +1211,31 @@ const DEFAULT_GLYPH_SOURCES: [GlyphSource; 9] = [
     GlyphSource::Ebdt,
 ];
 
+/// Below this many pixels per em, [`GlyphSourcePreference::prefer_bitmap_at_small_sizes`]
+/// reaches for bitmap strikes first
+pub const SMALL_GLYPH_PPEM: u16 = 32;
+
+/// A source order for glyphs drawn small
+///
+/// Bitmap strikes look best near the size they were drawn for, while COLR
+/// and SVG art scales cleanly; a rule switches the order below a size.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SizeRule {
+    /// The rule applies to glyphs drawn at fewer pixels per em than this
+    pub below_ppem: u16,
+    /// Order tried instead of [`GlyphSourcePreference::prefer`]; denied
+    /// sources are still skipped
+    pub prefer: Vec<GlyphSource>,
+}
+
 /// Preference ordering and deny list for glyph sources
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GlyphSourcePreference {
     pub prefer: Vec<GlyphSource>,
     pub deny: HashSet<GlyphSource>,
+    /// Orders for small sizes, by ascending `below_ppem`; see
+    /// [`order_at`](Self::order_at)
+    pub size_rules: Vec<SizeRule>,
 }
 
 impl GlyphSourcePreference {
@@ -629,9 +1270,55 @@ impl GlyphSourcePreference {
         Self {
             prefer: normalized,
             deny,
+            size_rules: Vec::new(),
+        }
+    }
+
+    /// Names accepted by [`preset`](Self::preset)
+    pub const PRESETS: [&'static str; 4] = [
+        "default",
+        "monochrome",
+        "vector-only",
+        "prefer-bitmap-at-small-sizes",
+    ];
+
+    /// The preset called `name`, one of [`PRESETS`](Self::PRESETS)
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "monochrome" => Some(Self::monochrome()),
+            "vector-only" => Some(Self::vector_only()),
+            "prefer-bitmap-at-small-sizes" => Some(Self::prefer_bitmap_at_small_sizes()),
+            _ => None,
         }
     }
 
+    /// Below `below_ppem` pixels per em, try sources in `prefer` order
+    /// instead. Where rules overlap, the smallest threshold wins.
+    pub fn with_size_rule(mut self, below_ppem: u16, prefer: Vec<GlyphSource>) -> Self {
+        self.size_rules.push(SizeRule { below_ppem, prefer });
+        self.size_rules.sort_by_key(|rule| rule.below_ppem);
+        self
+    }
+
+    /// Order to try for a glyph drawn at `ppem` pixels per em, with denies
+    /// applied
+    pub fn order_at(&self, ppem: f32) -> Vec<GlyphSource> {
+        let Some(rule) = self
+            .size_rules
+            .iter()
+            .find(|rule| ppem < f32::from(rule.below_ppem))
+        else {
+            return self.effective_order();
+        };
+        let mut seen = HashSet::new();
+        rule.prefer
+            .iter()
+            .copied()
+            .filter(|src| !self.deny.contains(src) && seen.insert(*src))
+            .collect()
+    }
+
     /// Effective order with current denies applied.
     pub fn effective_order(&self) -> Vec<GlyphSource> {
         self.prefer
@@ -640,6 +1327,54 @@ impl GlyphSourcePreference {
             .filter(|src| !self.deny.contains(src))
             .collect()
     }
+
+    /// Outlines only: every color source (COLR, SVG, bitmaps) denied, so
+    /// color fonts render as plain glyphs in the foreground color.
+    pub fn monochrome() -> Self {
+        Self::from_parts(
+            Vec::new(),
+            DEFAULT_GLYPH_SOURCES
+                .into_iter()
+                .filter(|src| src.is_color()),
+        )
+    }
+
+    /// Vector sources only: outlines, COLR, and SVG, with the bitmap tables
+    /// (sbix, CBDT, EBDT) denied so glyphs stay sharp at any size.
+    pub fn vector_only() -> Self {
+        Self::from_parts(
+            Vec::new(),
+            [GlyphSource::Sbix, GlyphSource::Cbdt, GlyphSource::Ebdt],
+        )
+    }
+
+    /// Bitmap strikes first below [`SMALL_GLYPH_PPEM`], where they were drawn
+    /// for the size; the default order, with COLR ahead of bitmaps, above.
+    pub fn prefer_bitmap_at_small_sizes() -> Self {
+        Self::default().with_size_rule(
+            SMALL_GLYPH_PPEM,
+            vec![
+                GlyphSource::Sbix,
+                GlyphSource::Cbdt,
+                GlyphSource::Ebdt,
+                GlyphSource::Colr1,
+                GlyphSource::Colr0,
+                GlyphSource::Svg,
+                GlyphSource::Glyf,
+                GlyphSource::Cff2,
+                GlyphSource::Cff,
+            ],
+        )
+    }
+
+    /// Whether any color source survives the deny list, at some size.
+    pub fn allows_color(&self) -> bool {
+        let sized = self.size_rules.iter().flat_map(|rule| &rule.prefer);
+        self.prefer
+            .iter()
+            .chain(sized)
+            .any(|src| src.is_color() && !self.deny.contains(src))
+    }
 }
 
 impl Default for GlyphSourcePreference {
@@ -673,17 +1408,41 @@ pub struct RenderParams {
     /// Anti-aliasing smooths diagonal and curved edges by computing fractional
     /// pixel coverage. Disable for pixel-art or bitmap-style rendering.
     pub antialias: bool,
+    /// How finely edge coverage is measured. Default: 4x oversampling.
+    ///
+    /// Finer levels smooth edges on low-DPI displays at a cost in time;
+    /// retina targets rarely need more than the default. See
+    /// [`AntialiasQuality`] for which renderers honor it.
+    pub antialias_quality: AntialiasQuality,
+    /// How far the font's hints may move outlines onto the pixel grid.
+    /// Default: none, outlines as designed.
+    ///
+    /// See [`Hinting`] for which renderers honor it.
+    pub hinting: Hinting,
+    /// How strokes thinner than a pixel are kept from vanishing. Default: none.
+    ///
+    /// Matters most with [`antialias`](Self::antialias) off; see
+    /// [`DropoutMode`].
+    pub dropout: DropoutMode,
+    /// Exponent applied to edge coverage. Default: 1.0, coverage as measured.
+    ///
+    /// Each coverage value `c` in 0..=1 becomes `c^(1/gamma)`, so values
+    /// above 1 darken anti-aliased edges and values below 1 lighten them.
+    /// The opixa renderer honors it for outline glyphs.
+    pub gamma: f32,
     /// Variable font axis coordinates applied during rendering, as `(tag, value)` pairs.
     ///
     /// Renderers that read glyph outlines directly (opixa, skia, zeno, vello)
     /// need these coordinates to instantiate the correct outline from the `gvar`
     /// or `CFF2` table. Should match the values used in [`ShapingParams::variations`].
     pub variations: Vec<(String, f32)>,
-    /// CPAL palette index for COLR color glyphs. Default: 0 (the font's default palette).
+    /// CPAL palette for COLR color glyphs. Default: index 0 (the font's default palette).
     ///
     /// Color fonts (COLR format) can carry multiple named palettes (light mode,
     /// dark mode, seasonal themes, etc.). Index 0 is always the default.
-    pub color_palette: u16,
+    /// [`PaletteSelection::AutoForBackground`] picks, per font, the palette
+    /// flagged for a light or dark canvas; see [`palette`].
+    pub color_palette: PaletteSelection,
     /// Which glyph data sources to use, and in what priority order.
     ///
     /// A font can store the same character multiple ways: as a vector outline
@@ -692,11 +1451,121 @@ pub struct RenderParams {
     /// controls which representation the renderer picks when multiple are
     /// available. Default: vector outlines first, bitmaps last.
     pub glyph_sources: GlyphSourcePreference,
-    /// Whether to produce a raster bitmap or a vector document. Default: bitmap.
+    /// Whether to produce a raster bitmap, a distance field, or a vector document.
+    /// Default: bitmap.
     ///
     /// Set to `RenderMode::Vector(VectorFormat::Svg)` to get an SVG string
     /// instead of a pixel grid. Not all renderers support vector output.
     pub output: RenderMode,
+    /// Pixel layout of bitmap output. Default: `Rgba8`.
+    ///
+    /// `Gray8` keeps only coverage, for consumers that tint text themselves.
+    /// `Gray1` packs one bit per pixel, set where coverage reaches half, for
+    /// e-ink panels and other embedded displays. Colors, backgrounds, and
+    /// effects count as coverage in both. `Gray1` thresholds each layer as
+    /// it lands, so two faint edges that overlap do not add up to a set
+    /// pixel. The opixa, zeno, and skia
+    /// renderers composite straight into the requested layout; other
+    /// renderers return `Rgba8`, so check [`BitmapData::format`](types::BitmapData::format).
+    /// `Rgb8` is not a render format.
+    pub format: types::BitmapFormat,
+    /// Byte multiple every bitmap row starts on. Default: 1, packed rows.
+    ///
+    /// GPU texture uploads and Windows DIBs want rows 4 or 256 bytes apart.
+    /// The opixa, zeno, and skia renderers pad rows to it as they draw, so
+    /// [`BitmapData::stride`](types::BitmapData::stride) needs no repacking.
+    /// Distance fields and other renderers' bitmaps stay packed.
+    pub row_alignment: u32,
+    /// Channel order and alpha mode of `Rgba8` output. Default: straight RGBA.
+    ///
+    /// Every bitmap renderer converts to it before returning, whatever it
+    /// draws internally, so Direct2D's premultiplied BGRA and tiny-skia's
+    /// premultiplied RGBA arrive the same way. Ask for premultiplied BGRA to
+    /// hand pixels to a Windows surface without another pass.
+    pub pixel_layout: types::PixelLayout,
+    /// Stroke and shadow settings. Default: none.
+    pub effects: RenderEffects,
+    /// Grow the canvas to fit [`effects`](Self::effects). Default: true.
+    ///
+    /// When on, the room each effect needs is added to `padding`, side by
+    /// side, so strokes and shadows are never clipped. Turn it off to keep
+    /// the canvas size fixed at `padding` regardless of effects.
+    pub auto_padding: bool,
+    /// Rotation, shear, or scale applied to the whole run. Default: none.
+    ///
+    /// Renderers transform glyph outlines and pen positions before
+    /// rasterizing, then size the canvas to the transformed text, so rotated
+    /// text keeps full quality. Color glyphs follow the transformed baseline
+    /// but are drawn upright. The CoreGraphics renderer rejects transforms.
+    pub transform: Option<TextTransform>,
+    /// Pixels to raise the run off the line's baseline. Default: 0.
+    ///
+    /// Lets runs in different scripts or fonts share a line on the right
+    /// baseline, e.g. Devanagari hanging from the same headline as a Latin
+    /// run; [`FontMetrics::baseline_shift`](types::FontMetrics::baseline_shift)
+    /// computes it. The canvas keeps the unshifted line box where the
+    /// renderer sizes to one, and grows to fit the moved ink otherwise.
+    /// Negative values lower the run.
+    pub baseline_shift: f32,
+    /// Produce byte-identical output for identical inputs. Default: false.
+    ///
+    /// Renderers that honor it give up speedups whose results depend on
+    /// thread count or scheduling, round fixed-point coordinates the same
+    /// way everywhere, and write vector output in a canonical form (sorted
+    /// attributes, fixed precision), so outputs can be diffed across runs
+    /// and platforms. Renderers without nondeterminism ignore it.
+    pub deterministic: bool,
+    /// Abort rendering once this token trips. Default: none.
+    ///
+    /// Renderers check it between glyphs (opixa also between scanlines) and
+    /// return [`TypfError::Cancelled`], bounding the time a huge text or a
+    /// pathological font can hold a worker.
+    pub cancel: Option<CancelToken>,
+    /// Cap on the bytes the call may hold at once. Default: none.
+    ///
+    /// Renderers reserve canvases, glyph bitmaps, and COLR layers against it
+    /// and fail with [`RenderError::BudgetExceeded`](error::RenderError::BudgetExceeded)
+    /// instead of allocating past the limit.
+    pub memory_budget: Option<MemoryBudget>,
+    /// Fail on malformed glyphs instead of working around them. Default: false.
+    ///
+    /// Without it renderers skip glyphs they cannot draw and clamp absurd
+    /// bounding boxes; with it the first such glyph fails the call. See
+    /// [`sanitize`].
+    pub strict: bool,
+    /// Draw emoji as text or as color emoji. Default: each sequence's own.
+    ///
+    /// Forcing text denies color glyph sources; forcing emoji puts them
+    /// first. `TypfPipeline` resolves the automatic presentation per
+    /// sequence from variation selectors and Unicode defaults; see [`emoji`].
+    pub emoji_presentation: emoji::EmojiPresentation,
+    /// Colors for byte ranges of the source text. Default: none.
+    ///
+    /// Each glyph takes the foreground and background of the last span
+    /// covering its cluster, falling back to [`foreground`](Self::foreground).
+    /// See [`color_span`].
+    pub color_spans: Vec<ColorSpan>,
+    /// Place glyphs between pixel columns. Default: off.
+    ///
+    /// Off, renderers drop the fractional part of each pen position, so
+    /// rounding jitters the spacing between glyphs. See
+    /// [`SubpixelPositioning`] for which renderers honor it.
+    pub subpixel: SubpixelPositioning,
+    /// Where to note the glyph source each drawn glyph came from. Default: none.
+    ///
+    /// See [`source_log`] for which renderers fill it in.
+    pub glyph_source_log: Option<source_log::GlyphSourceLog>,
+    /// Where to record glyphs skipped, bounds clamped, and other problems
+    /// worked around outside strict mode. Default: none.
+    ///
+    /// See [`diagnostics`] for which renderers fill it in.
+    pub diagnostics: Option<diagnostics::Diagnostics>,
+    /// Width to fit the line to, and what to do when it is wider.
+    /// Default: no width.
+    ///
+    /// The opixa, skia, zeno, and SVG renderers end the canvas at
+    /// [`TextLayout::visible_width`]; see [`layout`].
+    pub layout: layout::TextLayout,
 }
 
 impl Default for RenderParams {
@@ -706,10 +1575,106 @@ impl Default for RenderParams {
             background: None,
             padding: 0,
             antialias: true,
+            antialias_quality: AntialiasQuality::Oversample4x,
+            hinting: Hinting::None,
+            dropout: DropoutMode::None,
+            gamma: 1.0,
             variations: Vec::new(),
-            color_palette: 0,
+            color_palette: PaletteSelection::Index(0),
             glyph_sources: GlyphSourcePreference::default(),
             output: RenderMode::Bitmap,
+            format: types::BitmapFormat::Rgba8,
+            row_alignment: 1,
+            pixel_layout: types::PixelLayout::RGBA,
+            effects: RenderEffects::default(),
+            auto_padding: true,
+            transform: None,
+            baseline_shift: 0.0,
+            deterministic: false,
+            cancel: None,
+            memory_budget: None,
+            strict: false,
+            emoji_presentation: emoji::EmojiPresentation::Auto,
+            color_spans: Vec::new(),
+            subpixel: SubpixelPositioning::Off,
+            glyph_source_log: None,
+            diagnostics: None,
+            layout: layout::TextLayout::default(),
+        }
+    }
+}
+
+impl RenderParams {
+    /// White text with a black border and drop shadow, sized for text
+    /// `size` pixels tall: the broadcast subtitle look. See
+    /// [`RenderEffects::subtitle`].
+    pub fn subtitle(size: f32) -> Self {
+        Self {
+            foreground: Color::white(),
+            effects: RenderEffects::subtitle(size),
+            ..Self::default()
+        }
+    }
+
+    /// Params tuned as a set for one kind of output; colors, effects, and
+    /// the rest keep their defaults. See [`RenderPreset`] for each set.
+    pub fn preset(preset: RenderPreset) -> Self {
+        match preset {
+            RenderPreset::Default => Self::default(),
+            RenderPreset::UiSmallSize => Self {
+                antialias_quality: AntialiasQuality::Oversample16x,
+                hinting: Hinting::Light,
+                dropout: DropoutMode::Simple,
+                gamma: 1.4,
+                subpixel: SubpixelPositioning::Quarter,
+                ..Self::default()
+            },
+            RenderPreset::Print1200Dpi => Self {
+                antialias_quality: AntialiasQuality::Analytic,
+                hinting: Hinting::None,
+                dropout: DropoutMode::None,
+                gamma: 1.0,
+                deterministic: true,
+                ..Self::default()
+            },
+            RenderPreset::EinkMono => Self {
+                antialias: false,
+                hinting: Hinting::Full,
+                dropout: DropoutMode::Smart,
+                gamma: 1.0,
+                format: types::BitmapFormat::Gray1,
+                glyph_sources: GlyphSourcePreference::monochrome(),
+                ..Self::default()
+            },
+        }
+    }
+
+    /// The transform renderers should apply, or `None` when there is nothing to do.
+    ///
+    /// Identity transforms count as none. Non-finite or degenerate matrices
+    /// are rejected with [`TypfError::ConfigError`].
+    pub fn active_transform(&self) -> Result<Option<TextTransform>> {
+        match self.transform {
+            Some(t) if !t.is_identity() => {
+                t.validate()?;
+                Ok(Some(t))
+            },
+            _ => Ok(None),
+        }
+    }
+
+    /// Room to leave around the text box on each side of the canvas.
+    ///
+    /// Starts from the larger of [`padding`](Self::padding) and the
+    /// renderer's own default, then adds whatever the configured effects
+    /// need when [`auto_padding`](Self::auto_padding) is on. The text box's
+    /// top-left corner sits at `(left, top)` in the rendered bitmap.
+    pub fn canvas_insets(&self, renderer_padding: u32) -> Insets {
+        let base = Insets::uniform(self.padding.max(renderer_padding));
+        if self.auto_padding {
+            base.grow(self.effects.required_insets())
+        } else {
+            base
         }
     }
 }
@@ -720,6 +1685,186 @@ pub enum RenderMode {
     Bitmap,
     /// Vector output (currently SVG only)
     Vector(types::VectorFormat),
+    /// Single-channel signed distance field (`Gray8`).
+    ///
+    /// Each glyph outline is converted into a field where 128 marks the edge,
+    /// brighter values lie inside, and darker values lie outside. Distances
+    /// are clamped to `spread` pixels, and the canvas grows by `spread` on
+    /// every side so the falloff is never clipped. Colors and background are
+    /// ignored. GPU text stacks sample this with a threshold at 0.5 to get
+    /// crisp edges at any scale. See [`sdf`] for the conversion itself.
+    DistanceField {
+        /// Maximum encoded distance, in output pixels.
+        spread: u32,
+    },
+}
+
+/// How finely a renderer measures the coverage of edge pixels.
+///
+/// Oversampling tests a grid of points in every pixel, so an edge pixel
+/// takes one of `n * n + 1` gray levels and each step up costs about four
+/// times the scan conversion work. Analytic coverage computes the exact
+/// area the outline covers in each pixel instead: every gray level, at
+/// about the cost of 4x, though overlapping contours count as nonzero
+/// winding whatever the fill rule. The opixa renderer honors it; the
+/// others keep their own anti-aliasing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AntialiasQuality {
+    /// 4x4 samples per pixel (default)
+    #[default]
+    Oversample4x,
+    /// 8x8 samples per pixel
+    Oversample8x,
+    /// 16x16 samples per pixel
+    Oversample16x,
+    /// Exact area coverage
+    Analytic,
+}
+
+impl AntialiasQuality {
+    /// Samples per pixel along each axis; none for analytic coverage.
+    pub fn oversample(self) -> Option<u8> {
+        match self {
+            Self::Oversample4x => Some(4),
+            Self::Oversample8x => Some(8),
+            Self::Oversample16x => Some(16),
+            Self::Analytic => None,
+        }
+    }
+}
+
+/// How far a renderer lets the font's hints move outlines onto the pixel
+/// grid.
+///
+/// Hinting snaps stems and heights to whole pixels, trading the designed
+/// shapes for crisper small text. The opixa renderer runs the font's own
+/// TrueType or CFF hints, or an automatic hinter for fonts without them;
+/// the others draw unhinted outlines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Hinting {
+    /// Outlines as designed (default)
+    #[default]
+    None,
+    /// Vertical snapping only, so advances and spacing stay as shaped
+    Light,
+    /// Snapping on both axes; strongest with anti-aliasing off
+    Full,
+}
+
+/// How a renderer keeps strokes thinner than a pixel from vanishing.
+///
+/// A stem narrower than a pixel can fall between pixel samples and leave
+/// a gap, most visibly in aliased output. Dropout control lights a pixel
+/// for such a stroke anyway. The opixa renderer honors it; the others
+/// ignore it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DropoutMode {
+    /// Do not apply dropout handling (default).
+    #[default]
+    None,
+    /// Light the pixel where the lost stroke starts.
+    Simple,
+    /// Light the pixel under the middle of the lost stroke.
+    Smart,
+}
+
+/// Render settings bundled for one kind of output.
+///
+/// Each preset sets hinting, anti-aliasing, dropout control, gamma, and
+/// glyph placement together, so callers need not know how they interact;
+/// [`RenderParams::preset`] builds the params.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RenderPreset {
+    /// [`RenderParams::default`]
+    #[default]
+    Default,
+    /// Body text on low-DPI screens: 16x anti-aliasing, light hinting,
+    /// simple dropout control, gamma 1.4 for sturdier stems, and
+    /// quarter-pixel glyph placement
+    UiSmallSize,
+    /// Print proofs: analytic coverage of unhinted outlines on whole
+    /// pixels, with deterministic output so proofs match press runs
+    Print1200Dpi,
+    /// One-bit e-ink panels: `Gray1` output without anti-aliasing or color
+    /// glyphs, full hinting, and smart dropout control
+    EinkMono,
+}
+
+impl RenderPreset {
+    pub const ALL: [Self; 4] = [
+        Self::Default,
+        Self::UiSmallSize,
+        Self::Print1200Dpi,
+        Self::EinkMono,
+    ];
+
+    /// The preset's name, e.g. `"eink-mono"`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::UiSmallSize => "ui-smallsize",
+            Self::Print1200Dpi => "print-1200dpi",
+            Self::EinkMono => "eink-mono",
+        }
+    }
+
+    /// The preset called `name`
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| preset.name() == name)
+    }
+}
+
+/// How far between pixel columns a renderer may place a glyph.
+///
+/// Shaping produces fractional pen positions. Compositing each glyph at a
+/// whole pixel rounds every advance on its own, which makes spacing uneven
+/// at small sizes. With a phase count above one, the skia renderer
+/// rasterizes each glyph shifted by its pen's fraction, snapped to the
+/// nearest phase, and composites it at the whole pixel below. Bitmaps are
+/// shared per glyph and phase within a run. Transformed runs and color
+/// glyphs stay on whole pixels, and the other renderers ignore it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SubpixelPositioning {
+    /// Whole pixels only (default)
+    #[default]
+    Off,
+    /// Quarter-pixel steps: four rasterizations per glyph at most
+    Quarter,
+}
+
+impl SubpixelPositioning {
+    /// Distinct horizontal offsets a glyph may be rasterized at.
+    pub fn phases(self) -> u32 {
+        match self {
+            Self::Off => 1,
+            Self::Quarter => 4,
+        }
+    }
+
+    /// Split pen position `x` into a whole pixel and a phase index.
+    ///
+    /// The glyph is composited at the returned pixel and rasterized shifted
+    /// right by `phase / phases()` pixels. A fraction that snaps up to a
+    /// whole pixel moves to the next pixel at phase zero. When off, the
+    /// fraction is dropped.
+    pub fn split(self, x: f32) -> (f32, u32) {
+        let phases = self.phases();
+        let whole = x.floor();
+        if phases == 1 || !x.is_finite() {
+            return (whole, 0);
+        }
+        let phase = ((x - whole) * phases as f32).round() as u32;
+        if phase >= phases {
+            (whole + 1.0, 0)
+        } else {
+            (whole, phase)
+        }
+    }
+
+    /// Pixel offset of `phase`.
+    pub fn offset(self, phase: u32) -> f32 {
+        phase as f32 / self.phases() as f32
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -832,6 +1977,149 @@ mod tests {
         assert!(output.byte_size() > 0);
     }
 
+    fn two_glyph_run() -> ShapingResult {
+        let glyph = |id, x| PositionedGlyph {
+            id,
+            x,
+            y: 0.0,
+            advance: 10.0,
+            cluster: 0,
+        };
+        ShapingResult {
+            glyphs: vec![glyph(1, 0.0), glyph(2, 10.0)],
+            advance_width: 20.0,
+            advance_height: 16.0,
+            direction: Direction::LeftToRight,
+        }
+    }
+
+    #[test]
+    fn test_bitmap_stride_when_aligned_then_rows_padded_and_packable() {
+        assert_eq!(BitmapFormat::Rgba8.stride(3, 1), 12);
+        assert_eq!(BitmapFormat::Rgba8.stride(3, 0), 12);
+        assert_eq!(BitmapFormat::Gray8.stride(3, 4), 4);
+        assert_eq!(BitmapFormat::Gray1.stride(9, 4), 4);
+        assert_eq!(BitmapFormat::Rgba8.stride(3, 256), 256);
+
+        let padded = BitmapData {
+            width: 3,
+            height: 2,
+            format: BitmapFormat::Gray8,
+            stride: 4,
+            layout: PixelLayout::RGBA,
+            data: vec![1, 2, 3, 0, 4, 5, 6, 0],
+        };
+        assert!(!padded.is_packed());
+        assert_eq!(padded.row(1), Some(&[4, 5, 6][..]));
+        assert_eq!(padded.row(2), None);
+
+        let packed = padded.to_packed();
+        assert!(packed.is_packed());
+        assert_eq!(packed.stride, 3);
+        assert_eq!(packed.data, [1, 2, 3, 4, 5, 6]);
+        assert!(matches!(packed.to_packed(), std::borrow::Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_bitmap_layout_when_converted_then_channels_swap_and_alpha_scales() {
+        // One half-transparent orange pixel, one clear pixel, one pad byte pair
+        let straight = BitmapData {
+            width: 2,
+            height: 1,
+            format: BitmapFormat::Rgba8,
+            stride: 10,
+            layout: PixelLayout::RGBA,
+            data: vec![255, 128, 0, 128, 9, 9, 9, 0, 7, 7],
+        };
+        assert!(matches!(
+            straight.to_layout(PixelLayout::RGBA),
+            std::borrow::Cow::Borrowed(_)
+        ));
+
+        let bgra = straight.to_layout(PixelLayout::PREMULTIPLIED_BGRA);
+        assert_eq!(bgra.layout, PixelLayout::PREMULTIPLIED_BGRA);
+        assert_eq!(bgra.data, [0, 64, 128, 128, 0, 0, 0, 0, 7, 7]);
+
+        let back = bgra.into_owned().into_layout(PixelLayout::RGBA);
+        assert_eq!(back.data, [255, 128, 0, 128, 0, 0, 0, 0, 7, 7]);
+
+        let gray = BitmapData {
+            format: BitmapFormat::Gray8,
+            layout: PixelLayout::RGBA,
+            ..back
+        };
+        let unchanged = gray.to_layout(PixelLayout::PREMULTIPLIED_BGRA);
+        assert_eq!(unchanged.layout, PixelLayout::RGBA);
+    }
+
+    #[test]
+    fn test_subpixel_split_when_quarter_then_nearest_phase() {
+        use super::SubpixelPositioning;
+
+        assert_eq!(SubpixelPositioning::Off.split(10.7), (10.0, 0));
+        assert_eq!(SubpixelPositioning::Quarter.split(10.3), (10.0, 1));
+        assert_eq!(SubpixelPositioning::Quarter.split(10.6), (10.0, 2));
+        assert_eq!(SubpixelPositioning::Quarter.split(10.9), (11.0, 0));
+        assert_eq!(SubpixelPositioning::Quarter.split(-0.25), (-1.0, 3));
+        assert_eq!(SubpixelPositioning::Quarter.offset(3), 0.75);
+    }
+
+    #[test]
+    fn test_render_preset_when_named_then_knobs_set_together() {
+        use super::{
+            AntialiasQuality, DropoutMode, Hinting, RenderParams, RenderPreset, SubpixelPositioning,
+        };
+
+        for preset in RenderPreset::ALL {
+            assert_eq!(RenderPreset::parse(preset.name()), Some(preset));
+        }
+        assert_eq!(RenderPreset::parse("sepia"), None);
+
+        let ui = RenderParams::preset(RenderPreset::UiSmallSize);
+        assert_eq!(ui.antialias_quality, AntialiasQuality::Oversample16x);
+        assert_eq!(ui.hinting, Hinting::Light);
+        assert_eq!(ui.dropout, DropoutMode::Simple);
+        assert!(ui.gamma > 1.0);
+        assert_eq!(ui.subpixel, SubpixelPositioning::Quarter);
+        let print = RenderParams::preset(RenderPreset::Print1200Dpi);
+        assert_eq!(print.antialias_quality, AntialiasQuality::Analytic);
+        assert_eq!(print.hinting, Hinting::None);
+        assert_eq!(print.subpixel, SubpixelPositioning::Off);
+        assert!(print.deterministic);
+        let eink = RenderParams::preset(RenderPreset::EinkMono);
+        assert!(!eink.antialias);
+        assert_eq!(eink.hinting, Hinting::Full);
+        assert_eq!(eink.dropout, DropoutMode::Smart);
+        assert_eq!(eink.format, BitmapFormat::Gray1);
+        assert!(!eink.glyph_sources.allows_color());
+    }
+
+    #[test]
+    fn test_content_hash_when_same_run_then_equal() {
+        let run = two_glyph_run();
+        let mut relabeled = run.clone();
+        relabeled.glyphs[1].cluster = 7;
+        relabeled.glyphs[0].y = -0.0;
+
+        assert_eq!(run.content_hash(1), run.clone().content_hash(1));
+        assert_eq!(run.content_hash(1), relabeled.content_hash(1));
+    }
+
+    #[test]
+    fn test_content_hash_when_order_position_or_font_changes_then_differs() {
+        let run = two_glyph_run();
+        let base = run.content_hash(1);
+
+        let mut swapped = run.clone();
+        swapped.glyphs.swap(0, 1);
+        let mut nudged = run.clone();
+        nudged.glyphs[1].x += 0.5;
+
+        assert_ne!(base, swapped.content_hash(1));
+        assert_ne!(base, nudged.content_hash(1));
+        assert_ne!(base, run.content_hash(2));
+    }
+
     #[test]
     fn test_shaping_params_validate_when_non_finite_size_then_error() {
         for size in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
@@ -864,6 +2152,16 @@ mod tests {
             .expect("positive finite size should validate");
     }
 
+    #[test]
+    fn test_shaping_params_kerning_when_kern_feature_off_then_false() {
+        let mut params = ShapingParams::default();
+        assert!(params.kerning());
+        params.features.push(("kern".to_string(), 0));
+        assert!(!params.kerning());
+        params.features.push(("kern".to_string(), 1));
+        assert!(params.kerning());
+    }
+
     #[test]
     fn test_shaping_params_validate_when_size_above_max_then_error() {
         let params = ShapingParams {
@@ -885,4 +2183,122 @@ mod tests {
             other => panic!("expected FontSizeTooLarge, got {:?}", other),
         }
     }
+
+    #[test]
+    fn test_baseline_for_script_when_iso_or_opentype_tag_then_mapped() {
+        assert_eq!(Baseline::for_script("Deva"), Baseline::Hanging);
+        assert_eq!(Baseline::for_script("dev2"), Baseline::Hanging);
+        assert_eq!(Baseline::for_script("Hani"), Baseline::Ideographic);
+        assert_eq!(Baseline::for_script("kana"), Baseline::Ideographic);
+        assert_eq!(Baseline::for_script("Latn"), Baseline::Alphabetic);
+        assert_eq!(Baseline::for_script("Zzzz"), Baseline::Alphabetic);
+        for baseline in [
+            Baseline::Alphabetic,
+            Baseline::Ideographic,
+            Baseline::Hanging,
+            Baseline::Math,
+        ] {
+            assert_eq!(Baseline::from_tag(baseline.tag()), Some(baseline));
+        }
+    }
+
+    #[test]
+    fn test_font_stretch_when_width_class_round_trips_then_same_stretch() {
+        for class in 1..=9 {
+            let stretch = FontStretch::from_width_class(class);
+            assert_eq!(stretch.map(FontStretch::width_class), Some(class));
+        }
+        assert_eq!(FontStretch::from_width_class(0), None);
+        assert_eq!(FontStretch::from_width_class(10), None);
+        assert_eq!(FontStretch::default().width_class(), 5);
+        assert_eq!(FontStretch::Condensed.percent(), 75.0);
+    }
+
+    #[test]
+    fn test_baseline_shift_when_fonts_differ_then_baselines_meet() {
+        let metrics = |units_per_em, ascent, descent| FontMetrics {
+            units_per_em,
+            ascent,
+            descent,
+            line_gap: 0,
+            baselines: Baselines::synthesized(ascent, descent),
+            hhea: LineMetrics::default(),
+            typo: None,
+            win: None,
+            use_typo_metrics: false,
+            x_height: None,
+            cap_height: None,
+            subscript: None,
+            superscript: None,
+        };
+        let latin = metrics(1000, 1000, -250);
+        let mut devanagari = metrics(2048, 2048, -512);
+        devanagari.baselines.hanging = 1024;
+
+        // Latin hangs at 600/1000 em, Devanagari at 1024/2048 em
+        assert_eq!(latin.baseline(Baseline::Hanging, 20.0), 12.0);
+        assert_eq!(devanagari.baseline(Baseline::Hanging, 20.0), 10.0);
+        let shift = devanagari.baseline_shift(20.0, &latin, 20.0, Baseline::Hanging);
+        assert_eq!(shift, 2.0);
+        assert_eq!(
+            latin.baseline_shift(20.0, &latin, 20.0, Baseline::Alphabetic),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_line_metrics_when_policy_varies_then_matching_table() {
+        let hhea = LineMetrics {
+            ascent: 1069,
+            descent: -293,
+            line_gap: 0,
+        };
+        let typo = LineMetrics {
+            ascent: 800,
+            descent: -200,
+            line_gap: 200,
+        };
+        let win = LineMetrics {
+            ascent: 1000,
+            descent: -250,
+            line_gap: 0,
+        };
+        let mut metrics = FontMetrics {
+            units_per_em: 1000,
+            ascent: typo.ascent,
+            descent: typo.descent,
+            line_gap: typo.line_gap,
+            baselines: Baselines::synthesized(typo.ascent, typo.descent),
+            hhea,
+            typo: Some(typo),
+            win: Some(win),
+            use_typo_metrics: false,
+            x_height: Some(536),
+            cap_height: Some(714),
+            subscript: None,
+            superscript: None,
+        };
+
+        assert_eq!(metrics.line_metrics(LineMetricsPolicy::Css), hhea);
+        assert_eq!(metrics.line_metrics(LineMetricsPolicy::Typo), typo);
+        // hhea's 1362 exceeds win's 1250 by 112 units of external leading
+        assert_eq!(
+            metrics.line_metrics(LineMetricsPolicy::Win),
+            LineMetrics {
+                line_gap: 112,
+                ..win
+            }
+        );
+        assert_eq!(metrics.line_height(LineMetricsPolicy::Hhea, 10.0), 13.62);
+
+        metrics.use_typo_metrics = true;
+        assert_eq!(metrics.line_metrics(LineMetricsPolicy::Css), typo);
+        assert_eq!(metrics.line_height(LineMetricsPolicy::Css, 10.0), 12.0);
+
+        // A font without OS/2 falls back to hhea everywhere
+        metrics.typo = None;
+        metrics.win = None;
+        assert_eq!(metrics.line_metrics(LineMetricsPolicy::Win), hhea);
+        assert_eq!(metrics.line_metrics(LineMetricsPolicy::Typo), hhea);
+    }
 }
@@ -16,11 +16,16 @@
 //! - Eliminates glyph extraction overhead
 //! - Allows the OS to optimize internally
 //! - Can leverage hardware acceleration
+//!
+//! A fused backend ties its shaping to its rasterizer. When you want one
+//! without the other, say HarfRust shaping with a platform rasterizer, keep
+//! the two stages apart with [`Shaper`] and [`Renderer`], and use
+//! [`ComposedLinra`] wherever a [`LinraRenderer`] is expected.
 
 use std::sync::Arc;
 
 use crate::error::Result;
-use crate::traits::FontRef;
+use crate::traits::{FontRef, Renderer, Shaper};
 use crate::types::RenderOutput;
 use crate::Color;
 
@@ -52,8 +57,14 @@ pub struct LinraRenderParams {
     pub antialias: bool,
     /// Extra spacing between characters (in points, can be negative)
     pub letter_spacing: f32,
-    /// CPAL color palette index for COLR color glyphs (0 = default palette)
-    pub color_palette: u16,
+    /// CPAL color palette for COLR color glyphs (index 0 = default palette)
+    pub color_palette: crate::PaletteSelection,
+    /// Italic or oblique posture; slanted synthetically when the font has none
+    pub style: crate::types::FontStyle,
+    /// Width class to request from the font family
+    pub stretch: crate::types::FontStretch,
+    /// Channel order and alpha mode of the returned pixels
+    pub pixel_layout: crate::types::PixelLayout,
 }
 
 impl Default for LinraRenderParams {
@@ -70,7 +81,10 @@ impl Default for LinraRenderParams {
             script: None,
             antialias: true,
             letter_spacing: 0.0,
-            color_palette: 0,
+            color_palette: crate::PaletteSelection::Index(0),
+            style: crate::types::FontStyle::Normal,
+            stretch: crate::types::FontStretch::Normal,
+            pixel_layout: crate::types::PixelLayout::RGBA,
         }
     }
 }
@@ -94,6 +108,10 @@ impl LinraRenderParams {
             features: self.features.clone(),
             variations: self.variations.clone(),
             letter_spacing: self.letter_spacing,
+            tab_stops: crate::TabStops::default(),
+            number_substitution: Default::default(),
+            case: Default::default(),
+            cancel: None,
         }
     }
 
@@ -104,10 +122,31 @@ impl LinraRenderParams {
             background: self.background,
             padding: self.padding,
             antialias: self.antialias,
+            antialias_quality: crate::AntialiasQuality::default(),
+            hinting: crate::Hinting::None,
+            dropout: crate::DropoutMode::None,
+            gamma: 1.0,
             variations: self.variations.clone(),
             color_palette: self.color_palette,
             glyph_sources: crate::GlyphSourcePreference::default(),
             output: crate::RenderMode::Bitmap,
+            format: crate::types::BitmapFormat::Rgba8,
+            row_alignment: 1,
+            pixel_layout: self.pixel_layout,
+            effects: crate::RenderEffects::default(),
+            auto_padding: true,
+            transform: None,
+            baseline_shift: 0.0,
+            deterministic: false,
+            cancel: None,
+            memory_budget: None,
+            strict: false,
+            emoji_presentation: crate::EmojiPresentation::Auto,
+            color_spans: Vec::new(),
+            subpixel: crate::SubpixelPositioning::Off,
+            glyph_source_log: None,
+            diagnostics: None,
+            layout: crate::layout::TextLayout::default(),
         }
     }
 }
@@ -167,15 +206,163 @@ pub trait LinraRenderer: Send + Sync {
     /// Clear any internal caches
     fn clear_cache(&self) {}
 
+    /// Drop cached entries not used within `max_age`, returning how many were evicted
+    fn trim_idle(&self, _max_age: std::time::Duration) -> usize {
+        0
+    }
+
     /// Check if this renderer supports a given output format
     fn supports_format(&self, format: &str) -> bool {
         matches!(format, "bitmap" | "rgba")
     }
 }
 
+/// A [`LinraRenderer`] assembled from a separate shaper and renderer
+///
+/// Code written against the linra interface can take any shaper and any
+/// renderer this way, instead of the pairing a platform backend fixes.
+///
+/// ```rust,ignore
+/// let linra = ComposedLinra::new(Arc::new(HarfrustShaper::new()), Arc::new(OpixaRenderer::new()));
+/// let output = linra.render_text("Hello", font, &LinraRenderParams::with_size(24.0))?;
+/// ```
+pub struct ComposedLinra {
+    shaper: Arc<dyn Shaper>,
+    renderer: Arc<dyn Renderer>,
+}
+
+impl ComposedLinra {
+    /// Pair `shaper` with `renderer`
+    pub fn new(shaper: Arc<dyn Shaper>, renderer: Arc<dyn Renderer>) -> Self {
+        Self { shaper, renderer }
+    }
+
+    /// The shaping half
+    pub fn shaper(&self) -> &Arc<dyn Shaper> {
+        &self.shaper
+    }
+
+    /// The rendering half
+    pub fn renderer(&self) -> &Arc<dyn Renderer> {
+        &self.renderer
+    }
+}
+
+impl LinraRenderer for ComposedLinra {
+    fn name(&self) -> &'static str {
+        "composed"
+    }
+
+    fn render_text(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        params: &LinraRenderParams,
+    ) -> Result<RenderOutput> {
+        let shaped = self
+            .shaper
+            .shape(text, font.clone(), &params.to_shaping_params())?;
+        self.renderer
+            .render(&shaped, font, &params.to_render_params())
+    }
+
+    fn clear_cache(&self) {
+        self.shaper.clear_cache();
+        self.renderer.clear_cache();
+    }
+
+    fn trim_idle(&self, max_age: std::time::Duration) -> usize {
+        self.shaper.trim_idle(max_age) + self.renderer.trim_idle(max_age)
+    }
+
+    fn supports_format(&self, format: &str) -> bool {
+        self.renderer.supports_format(format)
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::expect_used, clippy::panic)]
 mod tests {
     use super::*;
+    use crate::types::{
+        BitmapData, BitmapFormat, Direction, PixelLayout, PositionedGlyph, ShapingResult,
+    };
+    use crate::{RenderParams, ShapingParams};
+
+    struct OneGlyphFont;
+
+    impl FontRef for OneGlyphFont {
+        fn data(&self) -> &[u8] {
+            &[]
+        }
+        fn units_per_em(&self) -> u16 {
+            1000
+        }
+        fn glyph_id(&self, _ch: char) -> Option<u32> {
+            Some(1)
+        }
+        fn advance_width(&self, _glyph_id: u32) -> f32 {
+            500.0
+        }
+    }
+
+    struct CountingShaper;
+
+    impl Shaper for CountingShaper {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+        fn shape(
+            &self,
+            text: &str,
+            _font: Arc<dyn FontRef>,
+            params: &ShapingParams,
+        ) -> Result<ShapingResult> {
+            let glyphs = text
+                .char_indices()
+                .map(|(i, _)| PositionedGlyph {
+                    id: 1,
+                    x: i as f32 * params.size,
+                    y: 0.0,
+                    advance: params.size,
+                    cluster: i as u32,
+                })
+                .collect::<Vec<_>>();
+            Ok(ShapingResult {
+                advance_width: glyphs.len() as f32 * params.size,
+                glyphs,
+                advance_height: params.size,
+                direction: params.direction,
+            })
+        }
+    }
+
+    struct WidthRenderer;
+
+    impl Renderer for WidthRenderer {
+        fn name(&self) -> &'static str {
+            "width"
+        }
+        fn render(
+            &self,
+            shaped: &ShapingResult,
+            _font: Arc<dyn FontRef>,
+            params: &RenderParams,
+        ) -> Result<RenderOutput> {
+            let width = shaped.advance_width as u32 + 2 * params.padding;
+            Ok(RenderOutput::Bitmap(BitmapData {
+                width,
+                height: 1,
+                format: BitmapFormat::Gray8,
+                stride: BitmapFormat::Gray8.row_bytes(width),
+                layout: PixelLayout::RGBA,
+                data: vec![0; width as usize],
+            }))
+        }
+        fn supports_format(&self, format: &str) -> bool {
+            format == "bitmap"
+        }
+    }
 
     #[test]
     fn test_linra_params_default() {
@@ -198,7 +385,7 @@ mod tests {
             variations: vec![("wght".to_string(), 700.0)],
             features: vec![("liga".to_string(), 1)],
             language: Some("en".to_string()),
-            color_palette: 2,
+            color_palette: crate::PaletteSelection::Index(2),
             ..Default::default()
         };
 
@@ -209,6 +396,27 @@ mod tests {
 
         let render = linra.to_render_params();
         assert_eq!(render.variations.len(), 1);
-        assert_eq!(render.color_palette, 2);
+        assert_eq!(render.color_palette, crate::PaletteSelection::Index(2));
+    }
+
+    #[test]
+    fn test_composed_linra_when_rendering_then_shapes_before_render() {
+        let linra = ComposedLinra::new(Arc::new(CountingShaper), Arc::new(WidthRenderer));
+        let params = LinraRenderParams {
+            size: 10.0,
+            padding: 2,
+            direction: Direction::LeftToRight,
+            ..Default::default()
+        };
+
+        let output = linra
+            .render_text("abc", Arc::new(OneGlyphFont), &params)
+            .expect("composed render should succeed");
+        match output {
+            RenderOutput::Bitmap(bitmap) => assert_eq!(bitmap.width, 34),
+            other => panic!("expected bitmap, got {:?}", other),
+        }
+        assert!(linra.supports_format("bitmap"));
+        assert!(!linra.supports_format("svg"));
     }
 }
@@ -0,0 +1,46 @@
+//! Text rewritten before shaping, with the way back to the caller's offsets.
+//!
+//! Digit substitution and case mapping replace characters, sometimes with
+//! longer ones ("ß" uppercases to "SS", ASCII digits become two-byte Arabic
+//! ones). Shapers report clusters as offsets into what they shaped, so
+//! [`MappedText`] remembers where each output character came from.
+
+use crate::types::ShapingResult;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MappedText {
+    text: String,
+    /// Start of each pushed piece as (offset in `text`, offset in the source)
+    offsets: Vec<(u32, u32)>,
+}
+
+impl MappedText {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            text: String::with_capacity(capacity),
+            offsets: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Append `replacement` for the source character at `source`
+    pub(crate) fn push(&mut self, source: usize, replacement: impl Iterator<Item = char>) {
+        self.offsets.push((self.text.len() as u32, source as u32));
+        self.text.extend(replacement);
+    }
+
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Point clusters from [`Self::text`] back at the source text
+    pub(crate) fn restore_clusters(&self, shaped: &mut ShapingResult) {
+        for glyph in &mut shaped.glyphs {
+            let i = self
+                .offsets
+                .partition_point(|&(mapped, _)| mapped <= glyph.cluster);
+            if let Some(&(_, source)) = i.checked_sub(1).and_then(|i| self.offsets.get(i)) {
+                glyph.cluster = source;
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Glyph outlines as vector paths.
+//!
+//! The renderers trace outlines privately on their way to pixels. This
+//! module hands the same paths to applications that want to hit-test glyph
+//! shapes, apply their own effects, or tessellate text for a GPU:
+//! [`extract_glyph_outline`] traces one glyph, and [`glyph_outlines`] walks a
+//! whole [`ShapingResult`], placing each outline at its glyph's position.
+//!
+//! Paths are in pixels with y pointing up, as in the font: the glyph origin
+//! is at (0, 0) and the baseline is y = 0. Flip y for a y-down canvas.
+//! Outlines are unhinted, and variable fonts are drawn at the instance given
+//! by `variations`, the same `(tag, value)` pairs as
+//! [`ShapingParams::variations`](crate::ShapingParams::variations).
+//!
+//! Needs the `outline` feature.
+
+use kurbo::{Affine, BezPath};
+use skrifa::{
+    instance::{Location, Size},
+    outline::{DrawSettings, OutlineGlyphCollection, OutlinePen},
+    MetadataProvider,
+};
+
+use crate::error::{RenderError, Result};
+use crate::traits::FontRef;
+use crate::types::{GlyphId, ShapingResult};
+
+/// [`OutlinePen`] that appends to a [`kurbo::BezPath`].
+///
+/// For callers driving skrifa themselves, such as renderers that already
+/// hold a parsed font.
+pub struct BezPathPen<'a> {
+    path: &'a mut BezPath,
+}
+
+impl<'a> BezPathPen<'a> {
+    /// A pen drawing into `path`.
+    pub fn new(path: &'a mut BezPath) -> Self {
+        Self { path }
+    }
+}
+
+impl OutlinePen for BezPathPen<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.path.move_to((x as f64, y as f64));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.path.line_to((x as f64, y as f64));
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.path
+            .quad_to((cx0 as f64, cy0 as f64), (x as f64, y as f64));
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        self.path.curve_to(
+            (cx0 as f64, cy0 as f64),
+            (cx1 as f64, cy1 as f64),
+            (x as f64, y as f64),
+        );
+    }
+
+    fn close(&mut self) {
+        self.path.close_path();
+    }
+}
+
+/// One glyph of a shaped run, with its outline placed in run coordinates.
+#[derive(Debug, Clone)]
+pub struct PositionedOutline {
+    /// The glyph ID in the font.
+    pub glyph_id: GlyphId,
+    /// The cluster the glyph came from, as in the shaped run.
+    pub cluster: u32,
+    /// Outline translated to the glyph's position; empty for glyphs
+    /// without ink, such as spaces.
+    pub path: BezPath,
+}
+
+/// Parsed outlines of one font instance, shared by every glyph traced
+struct Tracer<'a> {
+    outlines: OutlineGlyphCollection<'a>,
+    location: Location,
+    size: Size,
+}
+
+impl<'a> Tracer<'a> {
+    fn new(font: &'a dyn FontRef, size: f32, variations: &[(String, f32)]) -> Result<Self> {
+        let font = skrifa::FontRef::from_index(font.data(), font.face_index())
+            .map_err(|_| RenderError::InvalidFont)?;
+        let location = font
+            .axes()
+            .location(variations.iter().map(|(tag, value)| (tag.as_str(), *value)));
+        Ok(Self {
+            outlines: font.outline_glyphs(),
+            location,
+            size: Size::new(size),
+        })
+    }
+
+    fn trace(&self, glyph_id: GlyphId) -> Result<BezPath> {
+        let glyph = self
+            .outlines
+            .get(skrifa::GlyphId::new(glyph_id))
+            .ok_or(RenderError::GlyphNotFound(glyph_id))?;
+        let mut path = BezPath::new();
+        let settings = DrawSettings::unhinted(self.size, &self.location);
+        glyph
+            .draw(settings, &mut BezPathPen::new(&mut path))
+            .map_err(|_| RenderError::OutlineExtractionFailed)?;
+        Ok(path)
+    }
+}
+
+/// Trace the outline of `glyph_id` at `size` pixels per em.
+///
+/// Fails with [`RenderError::InvalidFont`] when the font data does not
+/// parse and [`RenderError::GlyphNotFound`] when the glyph has no outline,
+/// as in bitmap-only fonts.
+pub fn extract_glyph_outline(
+    font: &dyn FontRef,
+    glyph_id: GlyphId,
+    size: f32,
+    variations: &[(String, f32)],
+) -> Result<BezPath> {
+    Tracer::new(font, size, variations)?.trace(glyph_id)
+}
+
+/// Trace every glyph of `shaped`, in order, each placed at its position.
+///
+/// `size` should be the size the run was shaped at. The font is parsed
+/// once up front (failing with [`RenderError::InvalidFont`]); after that each
+/// glyph is its own `Result`, so one missing outline does not end the run.
+pub fn glyph_outlines<'a>(
+    font: &'a dyn FontRef,
+    shaped: &'a ShapingResult,
+    size: f32,
+    variations: &[(String, f32)],
+) -> Result<impl Iterator<Item = Result<PositionedOutline>> + 'a> {
+    let tracer = Tracer::new(font, size, variations)?;
+    Ok(shaped.glyphs.iter().map(move |glyph| {
+        let mut path = tracer.trace(glyph.id)?;
+        // `glyph.y` is a downward offset; these paths point y up
+        path.apply_affine(Affine::translate((glyph.x as f64, -glyph.y as f64)));
+        Ok(PositionedOutline {
+            glyph_id: glyph.id,
+            cluster: glyph.cluster,
+            path,
+        })
+    }))
+}
@@ -0,0 +1,238 @@
+//! Choosing a CPAL palette for color glyphs.
+//!
+//! COLR fonts draw with colors from a CPAL palette, and a font may carry
+//! several: one for light backgrounds, one for dark, seasonal variants.
+//! CPAL version 1 flags each palette as usable with a light and/or dark
+//! background. [`PaletteSelection::AutoForBackground`] reads those flags and
+//! picks the palette that suits the canvas, so emoji and icon fonts stay
+//! legible in dark mode without the caller knowing the font's palette order.
+
+use crate::sfnt;
+use crate::Color;
+
+/// CPAL v1 `paletteTypes` flag: palette suits a light background
+const USABLE_WITH_LIGHT_BACKGROUND: u32 = 0x0001;
+/// CPAL v1 `paletteTypes` flag: palette suits a dark background
+const USABLE_WITH_DARK_BACKGROUND: u32 = 0x0002;
+
+/// Backgrounds a CPAL palette declares itself usable with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PaletteType {
+    /// Designed for light backgrounds
+    pub light_background: bool,
+    /// Designed for dark backgrounds
+    pub dark_background: bool,
+}
+
+impl PaletteType {
+    fn from_flags(flags: u32) -> Self {
+        Self {
+            light_background: flags & USABLE_WITH_LIGHT_BACKGROUND != 0,
+            dark_background: flags & USABLE_WITH_DARK_BACKGROUND != 0,
+        }
+    }
+}
+
+/// Which CPAL palette color glyphs draw with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PaletteSelection {
+    /// This palette index
+    Index(u16),
+    /// The first palette flagged for this background's lightness
+    ///
+    /// Backgrounds darker than perceptual mid grey (CIE L* 50) count as dark;
+    /// alpha is ignored.
+    /// Falls back to palette 0 when no palette carries the matching flag,
+    /// including every CPAL version 0 font.
+    AutoForBackground(Color),
+}
+
+impl Default for PaletteSelection {
+    fn default() -> Self {
+        Self::Index(0)
+    }
+}
+
+impl From<u16> for PaletteSelection {
+    fn from(index: u16) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl PaletteSelection {
+    /// Palette index to use for face `face_index` of `font_data`
+    pub fn resolve(&self, font_data: &[u8], face_index: u32) -> u16 {
+        match *self {
+            Self::Index(index) => index,
+            Self::AutoForBackground(background) => {
+                let dark = is_dark(background);
+                palette_types(font_data, face_index)
+                    .iter()
+                    .position(|ty| {
+                        if dark {
+                            ty.dark_background
+                        } else {
+                            ty.light_background
+                        }
+                    })
+                    .map_or(0, |index| index as u16)
+            },
+        }
+    }
+}
+
+/// Whether `color` reads as a dark background, by sRGB relative luminance
+fn is_dark(color: Color) -> bool {
+    let linear = |channel: u8| {
+        let c = channel as f32 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let luminance = 0.2126 * linear(color.r) + 0.7152 * linear(color.g) + 0.0722 * linear(color.b);
+    // CIE L* 50, perceptual mid grey (about #777)
+    luminance < 0.184
+}
+
+/// Background flags of each CPAL palette in face `face_index` of
+/// `font_data`, in palette order
+///
+/// Empty without a `CPAL` table. CPAL version 0 palettes, and version 1
+/// fonts without a `paletteTypes` array, report no flags.
+pub fn palette_types(font_data: &[u8], face_index: u32) -> Vec<PaletteType> {
+    let Some(cpal) = sfnt::table_offset(font_data, face_index, b"CPAL") else {
+        return Vec::new();
+    };
+    let (Some(version), Some(count)) = (
+        sfnt::u16_at(font_data, cpal),
+        sfnt::u16_at(font_data, cpal + 4),
+    ) else {
+        return Vec::new();
+    };
+    let count = count as usize;
+    let types_offset = if version >= 1 {
+        sfnt::u32_at(font_data, cpal + 12 + 2 * count).unwrap_or(0) as usize
+    } else {
+        0
+    };
+    (0..count)
+        .map(|i| {
+            if types_offset == 0 {
+                return PaletteType::default();
+            }
+            sfnt::u32_at(font_data, cpal + types_offset + 4 * i)
+                .map_or_else(PaletteType::default, PaletteType::from_flags)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A font holding only a CPAL v1 table with `flags` as its palette types
+    fn font_with_palette_types(flags: &[u32]) -> Vec<u8> {
+        let count = flags.len() as u16;
+        let mut cpal = Vec::new();
+        cpal.extend_from_slice(&1u16.to_be_bytes()); // version
+        cpal.extend_from_slice(&1u16.to_be_bytes()); // numPaletteEntries
+        cpal.extend_from_slice(&count.to_be_bytes()); // numPalettes
+        cpal.extend_from_slice(&count.to_be_bytes()); // numColorRecords
+        let header = 12 + 2 * flags.len() + 12;
+        let records = header + 4 * flags.len();
+        cpal.extend_from_slice(&(records as u32).to_be_bytes());
+        for i in 0..count {
+            cpal.extend_from_slice(&i.to_be_bytes());
+        }
+        cpal.extend_from_slice(&(header as u32).to_be_bytes()); // paletteTypesArrayOffset
+        cpal.extend_from_slice(&0u32.to_be_bytes());
+        cpal.extend_from_slice(&0u32.to_be_bytes());
+        for flag in flags {
+            cpal.extend_from_slice(&flag.to_be_bytes());
+        }
+        for _ in flags {
+            cpal.extend_from_slice(&[0, 0, 0, 255]);
+        }
+
+        let mut font = Vec::new();
+        font.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        font.extend_from_slice(&1u16.to_be_bytes());
+        font.extend_from_slice(&[0; 6]);
+        font.extend_from_slice(b"CPAL");
+        font.extend_from_slice(&0u32.to_be_bytes());
+        font.extend_from_slice(&28u32.to_be_bytes());
+        font.extend_from_slice(&(cpal.len() as u32).to_be_bytes());
+        font.extend_from_slice(&cpal);
+        font
+    }
+
+    #[test]
+    fn test_palette_types_when_cpal_v1_then_flags_read() {
+        let font = font_with_palette_types(&[0, 1, 2, 3]);
+        let types = palette_types(&font, 0);
+
+        assert_eq!(types.len(), 4);
+        assert_eq!(types[0], PaletteType::default());
+        assert!(types[1].light_background && !types[1].dark_background);
+        assert!(!types[2].light_background && types[2].dark_background);
+        assert!(types[3].light_background && types[3].dark_background);
+    }
+
+    #[test]
+    fn test_resolve_when_auto_then_palette_matches_background() {
+        let font = font_with_palette_types(&[0, 1, 2]);
+
+        let on_white = PaletteSelection::AutoForBackground(Color::white());
+        let on_black = PaletteSelection::AutoForBackground(Color::black());
+        assert_eq!(on_white.resolve(&font, 0), 1);
+        assert_eq!(on_black.resolve(&font, 0), 2);
+        assert_eq!(PaletteSelection::Index(2).resolve(&font, 0), 2);
+    }
+
+    #[test]
+    fn test_resolve_when_collection_face_then_reads_that_faces_cpal() {
+        let faces = [
+            font_with_palette_types(&[1, 2]),
+            font_with_palette_types(&[2, 1]),
+        ];
+        let mut collection = Vec::new();
+        collection.extend_from_slice(b"ttcf");
+        collection.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        collection.extend_from_slice(&(faces.len() as u32).to_be_bytes());
+        let mut start = 12 + 4 * faces.len();
+        for face in &faces {
+            collection.extend_from_slice(&(start as u32).to_be_bytes());
+            start += face.len();
+        }
+        for face in &faces {
+            // Table offsets in a collection count from the start of the file
+            let mut face = face.clone();
+            let table = sfnt::u32_at(&face, 20).unwrap_or(0) + collection.len() as u32;
+            face[20..24].copy_from_slice(&table.to_be_bytes());
+            collection.extend_from_slice(&face);
+        }
+
+        let on_black = PaletteSelection::AutoForBackground(Color::black());
+        assert_eq!(on_black.resolve(&collection, 0), 1);
+        assert_eq!(on_black.resolve(&collection, 1), 0);
+        assert!(palette_types(&collection, 2).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_when_no_flags_then_default_palette() {
+        let font = font_with_palette_types(&[0, 0]);
+        let on_black = PaletteSelection::AutoForBackground(Color::black());
+
+        assert_eq!(on_black.resolve(&font, 0), 0);
+        assert_eq!(on_black.resolve(&[], 0), 0);
+        assert!(palette_types(&[], 0).is_empty());
+    }
+
+    #[test]
+    fn test_is_dark_splits_at_mid_grey() {
+        assert!(is_dark(Color::rgba(0x30, 0x30, 0x30, 255)));
+        assert!(!is_dark(Color::rgba(0xd0, 0xd0, 0xd0, 255)));
+    }
+}
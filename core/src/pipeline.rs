@@ -46,12 +46,16 @@
 //! changes here do not affect it and vice versa.
 
 use crate::{
+    cache::CacheMetrics,
     context::PipelineContext,
-    error::{Result, TypfError},
+    error::{ErrorContext, PipelineStage, Result, ResultExt, TypfError},
     glyph_cache::{GlyphCache, GlyphCacheKey, SharedGlyphCache},
+    render_cache::{RenderCache, RenderCacheKey},
     shaping_cache::{ShapingCache, ShapingCacheKey, SharedShapingCache},
+    stats::{span, CacheCounts, RenderStats, RunStats},
     traits::{Exporter, FontRef, Renderer, Shaper, Stage},
-    RenderParams, ShapingParams,
+    types::RenderOutput,
+    GlyphRun, RenderParams, ShapingParams,
 };
 use std::sync::{Arc, RwLock};
 
@@ -85,10 +89,9 @@ pub struct Pipeline {
     exporter: Option<Arc<dyn Exporter>>,
     #[allow(dead_code)]
     cache_policy: CachePolicy,
-    #[allow(dead_code)]
     shaping_cache: Option<SharedShapingCache>,
-    #[allow(dead_code)]
     glyph_cache: Option<SharedGlyphCache>,
+    render_cache: Option<Arc<RenderCache>>,
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -103,6 +106,9 @@ impl Pipeline {
     }
 
     /// Run the configured shaper, renderer, and exporter directly.
+    ///
+    /// With a [render cache](PipelineBuilder::with_render_cache), identical
+    /// calls return the cached bytes without running any of them.
     pub fn process(
         &self,
         text: &str,
@@ -123,13 +129,114 @@ impl Pipeline {
             .as_ref()
             .ok_or_else(|| TypfError::ConfigError("No exporter configured".into()))?;
 
-        let shaped = shaper.shape(text, font.clone(), shaping_params)?;
-        let rendered = renderer.render(&shaped, font, render_params)?;
-        let exported = exporter.export(&rendered)?;
+        let cache_key = self.render_cache.as_ref().map(|_| {
+            let backends = format!("{}/{}/{}", shaper.name(), renderer.name(), exporter.name());
+            RenderCacheKey::new(&backends, text, font.data(), shaping_params, render_params)
+                .with_face_index(font.face_index())
+        });
+        if let (Some(cache), Some(key)) = (&self.render_cache, &cache_key) {
+            if let Some(hit) = cache.get(key) {
+                return Ok(hit.to_vec());
+            }
+        }
 
+        let (shaped, _) = span("shape", || {
+            shaper
+                .shape(text, font.clone(), shaping_params)
+                .with_context(|| stage_context(PipelineStage::Shape, &*font))
+        });
+        let render_context = stage_context(PipelineStage::Render, &*font);
+        let export_context = ErrorContext {
+            stage: Some(PipelineStage::Export),
+            ..render_context.clone()
+        };
+        let (rendered, _) = span("rasterize", || {
+            renderer
+                .render(&shaped?, font, &render_params.presented())
+                .with_context(|| render_context)
+        });
+        let (exported, _) = span("export", || {
+            exporter.export(&rendered?).with_context(|| export_context)
+        });
+
+        let exported = exported?;
+        if let (Some(cache), Some(key)) = (&self.render_cache, cache_key) {
+            cache.insert(key, exported.as_slice());
+        }
         Ok(exported)
     }
 
+    /// Shape and render like [`process`](Self::process), without exporting,
+    /// and report what the call did.
+    ///
+    /// Cache counts come from the pipeline's own shaping and glyph caches.
+    pub fn render_with_stats(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        shaping_params: &ShapingParams,
+        render_params: &RenderParams,
+    ) -> Result<(RenderOutput, RenderStats)> {
+        let shaper = self
+            .shaper
+            .as_ref()
+            .ok_or_else(|| TypfError::ConfigError("No shaper configured".into()))?;
+        let renderer = self
+            .renderer
+            .as_ref()
+            .ok_or_else(|| TypfError::ConfigError("No renderer configured".into()))?;
+
+        let (shaping_before, render_before) = self.cache_metrics();
+        let (shaped, shape) = span("shape", || {
+            shaper
+                .shape(text, font.clone(), shaping_params)
+                .with_context(|| stage_context(PipelineStage::Shape, &*font))
+        });
+        let shaped = shaped?;
+        let render_context = stage_context(PipelineStage::Render, &*font);
+        let (rendered, rasterize) = span("rasterize", || {
+            renderer
+                .render(&shaped, font, &render_params.presented())
+                .with_context(|| render_context)
+        });
+        let rendered = rendered?;
+        let (shaping_after, render_after) = self.cache_metrics();
+
+        let mut stats = RenderStats {
+            runs: vec![RunStats {
+                range: 0..text.len(),
+                font: 0,
+                glyphs: shaped.glyphs.len(),
+                shape,
+            }],
+            glyphs_rendered: shaped.glyphs.len(),
+            shaping_cache: CacheCounts::between(&shaping_before, &shaping_after),
+            render_cache: CacheCounts::between(&render_before, &render_after),
+            bytes_allocated: rendered.byte_size(),
+            ..RenderStats::default()
+        };
+        stats.timings.shape = shape;
+        stats.timings.rasterize = rasterize;
+        log::debug!("{stats}");
+
+        Ok((rendered, stats))
+    }
+
+    /// Current metrics of the shaping and glyph caches, default when absent
+    fn cache_metrics(&self) -> (CacheMetrics, CacheMetrics) {
+        let shaping = self
+            .shaping_cache
+            .as_ref()
+            .and_then(|cache| cache.read().ok().map(|cache| cache.metrics()))
+            .unwrap_or_default();
+        let glyph = self
+            .glyph_cache
+            .as_ref()
+            .and_then(|cache| cache.read().ok().map(|cache| cache.metrics()))
+            .unwrap_or_default();
+        (shaping, glyph)
+    }
+
     pub fn execute(&self, mut context: PipelineContext) -> Result<PipelineContext> {
         if let Some(shaper) = &self.shaper {
             context.set_shaper(shaper.clone());
@@ -180,6 +287,7 @@ pub struct PipelineBuilder {
     cache_policy: CachePolicy,
     shaping_cache: Option<SharedShapingCache>,
     glyph_cache: Option<SharedGlyphCache>,
+    render_cache: Option<Arc<RenderCache>>,
 }
 
 impl PipelineBuilder {
@@ -192,6 +300,7 @@ impl PipelineBuilder {
             cache_policy: CachePolicy::default(),
             shaping_cache: None,
             glyph_cache: None,
+            render_cache: None,
         }
     }
 
@@ -239,6 +348,15 @@ impl PipelineBuilder {
         self
     }
 
+    /// Cache finished, encoded renders in `cache`, keyed by content.
+    ///
+    /// Only [`Pipeline::process`] consults it. Share one cache between
+    /// pipelines to pool their renders; see [`RenderCache`].
+    pub fn with_render_cache(mut self, cache: Arc<RenderCache>) -> Self {
+        self.render_cache = Some(cache);
+        self
+    }
+
     /// Build the pipeline from the configured parts.
     pub fn build(self) -> Result<Pipeline> {
         let stages = if self.stages.is_empty() {
@@ -296,6 +414,7 @@ impl PipelineBuilder {
             cache_policy: self.cache_policy,
             shaping_cache,
             glyph_cache,
+            render_cache: self.render_cache,
         })
     }
 }
@@ -359,6 +478,11 @@ impl Stage for FontSelectionStage {
     }
 }
 
+/// Where an error from `stage` running on `font` happened
+fn stage_context(stage: PipelineStage, font: &dyn FontRef) -> ErrorContext {
+    ErrorContext::stage(stage).with_font(GlyphRun::font_id(font))
+}
+
 struct ShapingStage;
 impl Stage for ShapingStage {
     fn name(&self) -> &'static str {
@@ -378,7 +502,9 @@ impl Stage for ShapingStage {
         let params = context.shaping_params();
 
         log::debug!("Shaping text with backend: {}", shaper.name());
-        let shaped = shaper.shape(text, font, params)?;
+        let shaped = shaper
+            .shape(text, font.clone(), params)
+            .with_context(|| stage_context(PipelineStage::Shape, &*font))?;
 
         context.set_shaped(shaped);
         Ok(context)
@@ -404,10 +530,13 @@ impl Stage for RenderingStage {
             .font()
             .ok_or_else(|| TypfError::Pipeline("No font available".into()))?;
 
-        let params = context.render_params();
+        // A forced emoji presentation picks the glyph sources for the whole run
+        let params = context.render_params().presented();
 
         log::debug!("Rendering with backend: {}", renderer.name());
-        let output = renderer.render(shaped, font, params)?;
+        let output = renderer
+            .render(shaped, font.clone(), &params)
+            .with_context(|| stage_context(PipelineStage::Render, &*font))?;
 
         context.set_output(output);
         Ok(context)
@@ -427,7 +556,9 @@ impl Stage for ExportStage {
                 .ok_or_else(|| TypfError::Pipeline("No render output available".into()))?;
 
             log::debug!("Exporting with backend: {}", exporter.name());
-            let exported = exporter.export(output)?;
+            let exported = exporter
+                .export(output)
+                .with_context(|| ErrorContext::stage(PipelineStage::Export))?;
 
             context.set_exported(exported);
         }
@@ -458,6 +589,13 @@ impl Shaper for CachedShaper {
         font: Arc<dyn FontRef>,
         params: &ShapingParams,
     ) -> Result<crate::types::ShapingResult> {
+        // Map case and digits here so the cache is keyed by the text actually shaped
+        if let Some(cased) = crate::case::apply_case(text, font.as_ref(), params) {
+            return cased.shape(params, |text, params| self.shape(text, font, params));
+        }
+        if let Some(digits) = crate::digits::substitute_digits(text, params) {
+            return digits.shape(params, |text, params| self.shape(text, font, params));
+        }
         let key = ShapingCacheKey::new(
             text,
             self.inner.name(),
@@ -467,7 +605,8 @@ impl Shaper for CachedShaper {
             params.script.clone(),
             params.features.clone(),
             params.variations.clone(),
-        );
+        )
+        .with_face_index(font.face_index());
 
         if let Ok(cache) = self.cache.read() {
             if let Some(hit) = cache.get(&key) {
@@ -507,7 +646,8 @@ impl Renderer for CachedRenderer {
         font: Arc<dyn FontRef>,
         params: &RenderParams,
     ) -> Result<crate::types::RenderOutput> {
-        let key = GlyphCacheKey::new(self.inner.name(), font.data(), shaped, params);
+        let key = GlyphCacheKey::new(self.inner.name(), font.data(), shaped, params)
+            .with_face_index(font.face_index());
 
         if let Ok(cache) = self.cache.read() {
             if let Some(hit) = cache.get(&key) {
@@ -528,8 +668,10 @@ impl Renderer for CachedRenderer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::Classify;
     use crate::types::{
-        BitmapData, BitmapFormat, Direction, PositionedGlyph, RenderOutput, ShapingResult,
+        BitmapData, BitmapFormat, Direction, PixelLayout, PositionedGlyph, RenderOutput,
+        ShapingResult,
     };
     use std::sync::Arc;
 
@@ -581,6 +723,8 @@ mod tests {
                 width,
                 height,
                 format: BitmapFormat::Rgba8,
+                stride: BitmapFormat::Rgba8.row_bytes(width),
+                layout: PixelLayout::RGBA,
                 data: vec![0u8; (width * height * 4) as usize],
             }))
         }
@@ -660,6 +804,121 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pipeline_render_cache_skips_repeat_work() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct CountingExporter(AtomicUsize);
+        impl Exporter for CountingExporter {
+            fn name(&self) -> &'static str {
+                "CountingExporter"
+            }
+            fn export(&self, output: &RenderOutput) -> Result<Vec<u8>> {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                MockExporter.export(output)
+            }
+            fn extension(&self) -> &'static str {
+                "bin"
+            }
+            fn mime_type(&self) -> &'static str {
+                "application/octet-stream"
+            }
+        }
+
+        let _caching = crate::cache_config::scoped_caching_enabled(true);
+        let exporter = Arc::new(CountingExporter(AtomicUsize::new(0)));
+        let cache = Arc::new(RenderCache::new(1 << 20));
+        let pipeline = match Pipeline::builder()
+            .shaper(Arc::new(MockShaper))
+            .renderer(Arc::new(MockRenderer))
+            .exporter(exporter.clone())
+            .with_render_cache(cache.clone())
+            .build()
+        {
+            Ok(pipeline) => pipeline,
+            Err(e) => unreachable!("pipeline build failed: {e}"),
+        };
+        let font = Arc::new(MockFont);
+        let render_params = RenderParams::default();
+        let process = |text: &str, size: f32| {
+            let shaping_params = ShapingParams {
+                size,
+                ..ShapingParams::default()
+            };
+            match pipeline.process(text, font.clone(), &shaping_params, &render_params) {
+                Ok(bytes) => bytes,
+                Err(e) => unreachable!("pipeline process failed: {e}"),
+            }
+        };
+
+        let first = process("Badge", 16.0);
+        assert_eq!(process("Badge", 16.0), first);
+        assert_eq!(exporter.0.load(Ordering::Relaxed), 1);
+
+        process("Badge", 20.0);
+        process("Other", 16.0);
+        assert_eq!(exporter.0.load(Ordering::Relaxed), 3);
+
+        // A reloaded font drops its renders, so the next call runs again
+        assert_eq!(cache.invalidate_font(font.data()), 3);
+        process("Badge", 16.0);
+        assert_eq!(exporter.0.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn test_pipeline_render_cache_when_strict_after_lenient_then_errors() {
+        /// Skips the private-use glyph unless strict, which rejects it
+        struct StrictRenderer;
+        impl Renderer for StrictRenderer {
+            fn name(&self) -> &'static str {
+                "StrictRenderer"
+            }
+            fn render(
+                &self,
+                shaped: &ShapingResult,
+                font: Arc<dyn FontRef>,
+                params: &RenderParams,
+            ) -> Result<RenderOutput> {
+                match shaped.glyphs.iter().find(|glyph| glyph.id == 0xE000) {
+                    Some(glyph) if params.strict => {
+                        Err(crate::error::RenderError::GlyphNotFound(glyph.id).into())
+                    },
+                    _ => MockRenderer.render(shaped, font, params),
+                }
+            }
+            fn supports_format(&self, _format: &str) -> bool {
+                true
+            }
+        }
+
+        let _caching = crate::cache_config::scoped_caching_enabled(true);
+        let pipeline = match Pipeline::builder()
+            .shaper(Arc::new(MockShaper))
+            .renderer(Arc::new(StrictRenderer))
+            .exporter(Arc::new(MockExporter))
+            .with_render_cache(Arc::new(RenderCache::new(1 << 20)))
+            .build()
+        {
+            Ok(pipeline) => pipeline,
+            Err(e) => unreachable!("pipeline build failed: {e}"),
+        };
+        let font = Arc::new(MockFont);
+        let shaping_params = ShapingParams::default();
+        let process = |strict| {
+            let render_params = RenderParams {
+                strict,
+                ..RenderParams::default()
+            };
+            pipeline.process("a\u{E000}", font.clone(), &shaping_params, &render_params)
+        };
+
+        assert!(process(false).is_ok());
+        assert!(
+            process(true).is_err(),
+            "strict call served the lenient render"
+        );
+    }
+
     #[test]
     fn test_pipeline_missing_shaper() {
         let pipeline_result = Pipeline::builder()
@@ -681,6 +940,64 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_pipeline_render_error_names_stage_and_font() {
+        struct FailingRenderer;
+        impl Renderer for FailingRenderer {
+            fn name(&self) -> &'static str {
+                "FailingRenderer"
+            }
+            fn render(
+                &self,
+                _shaped: &ShapingResult,
+                _font: Arc<dyn FontRef>,
+                _params: &RenderParams,
+            ) -> Result<RenderOutput> {
+                Err(
+                    TypfError::from(crate::error::RenderError::GlyphNotFound(72))
+                        .with_context(ErrorContext::glyph(72)),
+                )
+            }
+            fn supports_format(&self, _format: &str) -> bool {
+                true
+            }
+        }
+
+        let pipeline = match Pipeline::builder()
+            .shaper(Arc::new(MockShaper))
+            .renderer(Arc::new(FailingRenderer))
+            .exporter(Arc::new(MockExporter))
+            .build()
+        {
+            Ok(pipeline) => pipeline,
+            Err(e) => unreachable!("pipeline build failed: {e}"),
+        };
+
+        let font: Arc<dyn FontRef> = Arc::new(MockFont);
+        let result = pipeline.process(
+            "Hello",
+            font.clone(),
+            &ShapingParams::default(),
+            &RenderParams::default(),
+        );
+        let Err(err) = result else {
+            unreachable!("failing renderer should fail the pipeline");
+        };
+        assert_eq!(err.kind(), crate::ErrorKind::NotFound);
+        assert!(matches!(
+            err.root(),
+            TypfError::RenderingFailed(crate::error::RenderError::GlyphNotFound(72))
+        ));
+        assert_eq!(
+            err.context(),
+            Some(&ErrorContext {
+                stage: Some(PipelineStage::Render),
+                font: Some(GlyphRun::font_id(&*font)),
+                glyph_id: Some(72),
+            })
+        );
+    }
+
     #[test]
     fn test_pipeline_missing_renderer() {
         let pipeline_result = Pipeline::builder()
@@ -808,4 +1125,36 @@ mod tests {
         let result = pipeline.process("", font, &shaping_params, &render_params);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_render_with_stats_when_shaping_cached_then_second_call_hits() {
+        let _caching = crate::cache_config::scoped_caching_enabled(true);
+        let pipeline = match Pipeline::builder()
+            .shaper(Arc::new(MockShaper))
+            .renderer(Arc::new(MockRenderer))
+            .enable_shaping_cache(true)
+            .build()
+        {
+            Ok(pipeline) => pipeline,
+            Err(e) => unreachable!("pipeline build failed: {e}"),
+        };
+        let font: Arc<dyn FontRef> = Arc::new(MockFont);
+        let shaping_params = ShapingParams::default();
+        let render_params = RenderParams::default();
+
+        let mut calls = (0..2).map(|_| {
+            pipeline.render_with_stats("abc", font.clone(), &shaping_params, &render_params)
+        });
+        let (Some(Ok((_, first))), Some(Ok((output, second)))) = (calls.next(), calls.next())
+        else {
+            unreachable!("render_with_stats failed");
+        };
+
+        assert_eq!(first.shaping_cache, CacheCounts { hits: 0, misses: 1 });
+        assert_eq!(second.shaping_cache, CacheCounts { hits: 1, misses: 0 });
+        assert_eq!(second.render_cache, CacheCounts::default());
+        assert_eq!(second.glyphs_rendered, 3);
+        assert_eq!(second.runs.len(), 1);
+        assert_eq!(second.bytes_allocated, output.byte_size());
+    }
 }
@@ -0,0 +1,219 @@
+//! Renderers by name.
+//!
+//! The CLI and bindings take the renderer as a string (`"skia"`, `"zeno"`,
+//! ...). Instead of each of them matching on every backend it was compiled
+//! with, they ask a [`RendererRegistry`] to build one. Built-in backends are
+//! registered by the `typf` crate for whichever features are enabled; any
+//! other crate can add its own [`Renderer`] under a new name, or replace a
+//! built-in one, with [`RendererRegistry::register`].
+//!
+//! ```
+//! use std::sync::Arc;
+//! use typf_core::registry::RendererRegistry;
+//! # use typf_core::{error::Result, traits::{FontRef, Renderer}, types::*, RenderParams};
+//! # struct Blank;
+//! # impl Renderer for Blank {
+//! #     fn name(&self) -> &'static str { "blank" }
+//! #     fn render(&self, _: &ShapingResult, _: Arc<dyn FontRef>, _: &RenderParams)
+//! #         -> Result<RenderOutput> { unimplemented!() }
+//! # }
+//!
+//! let registry = RendererRegistry::new();
+//! registry.register("blank", || Ok(Arc::new(Blank)));
+//! assert_eq!(registry.create("blank")?.name(), "blank");
+//! # Ok::<(), typf_core::TypfError>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::error::{Result, TypfError};
+use crate::traits::Renderer;
+
+/// A renderer ready to hand to a [`Pipeline`][crate::Pipeline]
+pub type SharedRenderer = Arc<dyn Renderer + Send + Sync>;
+
+/// Builds a fresh renderer each time it is called
+pub type RendererFactory = dyn Fn() -> Result<SharedRenderer> + Send + Sync;
+
+/// Process-wide registry returned by [`RendererRegistry::global`]
+static GLOBAL: OnceLock<RendererRegistry> = OnceLock::new();
+
+/// Named renderer factories, safe to share between threads
+///
+/// Names are matched case-insensitively. Registering a name that is already
+/// taken replaces the earlier factory, so plugins can override built-ins.
+#[derive(Default)]
+pub struct RendererRegistry {
+    factories: RwLock<BTreeMap<String, Arc<RendererFactory>>>,
+}
+
+impl RendererRegistry {
+    /// An empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide registry
+    ///
+    /// Starts empty; `typf::renderers()` fills in the built-in backends the
+    /// first time it is called.
+    pub fn global() -> &'static Self {
+        GLOBAL.get_or_init(Self::new)
+    }
+
+    /// Register `factory` under `name`, replacing any earlier factory
+    ///
+    /// Returns `true` if a factory was replaced.
+    pub fn register<F>(&self, name: &str, factory: F) -> bool
+    where
+        F: Fn() -> Result<SharedRenderer> + Send + Sync + 'static,
+    {
+        self.write()
+            .insert(Self::key(name), Arc::new(factory))
+            .is_some()
+    }
+
+    /// Register `factory` under `name` unless the name is taken
+    ///
+    /// Returns `true` if it was registered. Built-in backends use this so a
+    /// plugin registered earlier keeps its name.
+    pub fn register_default<F>(&self, name: &str, factory: F) -> bool
+    where
+        F: Fn() -> Result<SharedRenderer> + Send + Sync + 'static,
+    {
+        let mut factories = self.write();
+        let key = Self::key(name);
+        if factories.contains_key(&key) {
+            return false;
+        }
+        factories.insert(key, Arc::new(factory));
+        true
+    }
+
+    /// Remove the factory registered under `name`, if any
+    pub fn unregister(&self, name: &str) -> bool {
+        self.write().remove(&Self::key(name)).is_some()
+    }
+
+    /// Whether a factory is registered under `name`
+    pub fn contains(&self, name: &str) -> bool {
+        self.read().contains_key(&Self::key(name))
+    }
+
+    /// Registered names, sorted
+    pub fn names(&self) -> Vec<String> {
+        self.read().keys().cloned().collect()
+    }
+
+    /// Build the renderer registered under `name`
+    ///
+    /// Fails with [`TypfError::ConfigError`] naming the registered renderers
+    /// when `name` is unknown, or with whatever error the factory returns.
+    pub fn create(&self, name: &str) -> Result<SharedRenderer> {
+        // Release the lock before building: factories may be slow (GPU setup)
+        // or may register further renderers themselves
+        let factory = self.read().get(&Self::key(name)).cloned();
+        match factory {
+            Some(factory) => factory(),
+            None => Err(TypfError::ConfigError(format!(
+                "Unknown or unavailable renderer: {} (registered: {})",
+                name,
+                self.names().join(", ")
+            ))),
+        }
+    }
+
+    fn key(name: &str) -> String {
+        name.trim().to_ascii_lowercase()
+    }
+
+    // A factory that panicked mid-registration leaves the map intact, so a
+    // poisoned lock is safe to keep using
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<String, Arc<RendererFactory>>> {
+        self.factories
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, BTreeMap<String, Arc<RendererFactory>>> {
+        self.factories
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl std::fmt::Debug for RendererRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RendererRegistry")
+            .field("names", &self.names())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::traits::FontRef;
+    use crate::types::{RenderOutput, ShapingResult};
+    use crate::RenderParams;
+
+    struct Named(&'static str);
+
+    impl Renderer for Named {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn render(
+            &self,
+            _shaped: &ShapingResult,
+            _font: Arc<dyn FontRef>,
+            _params: &RenderParams,
+        ) -> Result<RenderOutput> {
+            Err(TypfError::NotImplemented("test renderer".into()))
+        }
+    }
+
+    #[test]
+    fn test_create_when_registered_then_built_by_name() {
+        let registry = RendererRegistry::new();
+        assert!(!registry.register("Alpha", || Ok(Arc::new(Named("alpha")))));
+        registry.register("beta", || Ok(Arc::new(Named("beta"))));
+
+        let renderer = registry.create("alpha").expect("alpha is registered");
+        assert_eq!(renderer.name(), "alpha");
+        assert_eq!(registry.names(), ["alpha", "beta"]);
+
+        let Err(error) = registry.create("gamma") else {
+            panic!("gamma is not registered");
+        };
+        assert!(error.to_string().contains("alpha, beta"));
+    }
+
+    #[test]
+    fn test_register_when_name_taken_then_default_yields_to_plugin() {
+        let registry = RendererRegistry::new();
+        registry.register("skia", || Ok(Arc::new(Named("plugin"))));
+        assert!(!registry.register_default("skia", || Ok(Arc::new(Named("builtin")))));
+        let renderer = registry.create("SKIA").expect("skia is registered");
+        assert_eq!(renderer.name(), "plugin");
+
+        assert!(registry.register("skia", || Ok(Arc::new(Named("override")))));
+        assert!(registry.unregister("skia"));
+        assert!(!registry.contains("skia"));
+    }
+
+    #[test]
+    fn test_global_when_shared_across_threads_then_sees_registrations() {
+        let name = "registry-test-thread";
+        std::thread::spawn(move || {
+            RendererRegistry::global().register(name, || Ok(Arc::new(Named("thread"))))
+        })
+        .join()
+        .expect("registering thread finishes");
+        assert!(RendererRegistry::global().contains(name));
+        RendererRegistry::global().unregister(name);
+    }
+}
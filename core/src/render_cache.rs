@@ -0,0 +1,251 @@
+//! Content-addressed cache of finished, encoded renders
+//!
+//! Badge and thumbnail services render the same few strings over and over.
+//! [`RenderCache`] keeps the exported bytes (PNG, SVG, ...) keyed by a hash
+//! of everything that decides them: text, font, shaping and render
+//! parameters, and the backends used. A hit skips shaping, rasterizing, and
+//! encoding. Attach one with
+//! [`PipelineBuilder::with_render_cache`](crate::pipeline::PipelineBuilder::with_render_cache).
+//!
+//! Like the other caches it stays inert until caching is switched on with
+//! [`cache_config::set_caching_enabled`] or `TYPF_CACHE=1`. Entries are
+//! byte-weighted and may expire a fixed time after insertion.
+//! Fonts are identified by their bytes, so a font file replaced on disk
+//! never serves stale renders, but its old entries linger until evicted;
+//! subscribe the cache to a font database's changes (see [`InvalidateFont`])
+//! to free them at once.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::cache::{byte_weighted, CacheClock, InvalidateFont, StampedCache, TrimIdle};
+use crate::cache_config;
+use crate::glyph_cache::hash_render_params;
+use crate::{RenderParams, ShapingParams};
+
+/// What a finished render depends on, hashed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenderCacheKey {
+    /// Hash of the font data, see [`font_id`]
+    pub font_id: u64,
+    /// Face within the font data, for TTC/OTC collections
+    pub face_index: u32,
+    /// Hash of the text, parameters, and backend names
+    pub content: u64,
+}
+
+impl RenderCacheKey {
+    /// Key for rendering `text` in the font `font_data` through `backends`,
+    /// a name for the shaper, renderer, and exporter in use
+    pub fn new(
+        backends: &str,
+        text: &str,
+        font_data: &[u8],
+        shaping: &ShapingParams,
+        render: &RenderParams,
+    ) -> Self {
+        let mut hasher = DefaultHasher::new();
+        backends.hash(&mut hasher);
+        text.hash(&mut hasher);
+
+        shaping.size.to_bits().hash(&mut hasher);
+        shaping.direction.hash(&mut hasher);
+        shaping.language.hash(&mut hasher);
+        shaping.script.hash(&mut hasher);
+        shaping.features.hash(&mut hasher);
+        for (tag, value) in &shaping.variations {
+            tag.hash(&mut hasher);
+            value.to_bits().hash(&mut hasher);
+        }
+        shaping.letter_spacing.to_bits().hash(&mut hasher);
+        shaping.tab_stops.interval.to_bits().hash(&mut hasher);
+        for stop in &shaping.tab_stops.positions {
+            stop.to_bits().hash(&mut hasher);
+        }
+        shaping.number_substitution.hash(&mut hasher);
+        shaping.case.hash(&mut hasher);
+
+        hash_render_params(render).hash(&mut hasher);
+        render.baseline_shift.to_bits().hash(&mut hasher);
+        render.emoji_presentation.hash(&mut hasher);
+
+        Self {
+            font_id: font_id(font_data),
+            face_index: 0,
+            content: hasher.finish(),
+        }
+    }
+
+    /// Key a render of face `face_index` of a collection
+    pub fn with_face_index(mut self, face_index: u32) -> Self {
+        self.face_index = face_index;
+        self
+    }
+}
+
+/// Identity of a font in [`RenderCacheKey`]s: a hash of its bytes
+pub fn font_id(font_data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    font_data.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Byte-weighted cache of encoded renders
+///
+/// Cheap to share: wrap it in an `Arc` and hand it to every pipeline that
+/// should reuse renders.
+pub struct RenderCache {
+    cache: StampedCache<RenderCacheKey, Arc<[u8]>>,
+}
+
+impl RenderCache {
+    /// Create a cache holding at most `max_bytes` of encoded output.
+    pub fn new(max_bytes: u64) -> Self {
+        Self {
+            cache: byte_weighted(max_bytes, None, |bytes: &Arc<[u8]>| bytes.len()),
+        }
+    }
+
+    /// Like [`new`](Self::new), but every entry also expires `ttl` after insertion.
+    pub fn with_ttl(max_bytes: u64, ttl: Duration) -> Self {
+        Self {
+            cache: byte_weighted(max_bytes, Some(ttl), |bytes: &Arc<[u8]>| bytes.len()),
+        }
+    }
+
+    /// Read idle and TTL times from `clock` instead of the process clock
+    pub fn with_clock(mut self, clock: CacheClock) -> Self {
+        self.cache = self.cache.with_clock(clock);
+        self
+    }
+
+    /// The encoded render for `key`, if cached.
+    ///
+    /// Returns `None` if caching is globally disabled.
+    pub fn get(&self, key: &RenderCacheKey) -> Option<Arc<[u8]>> {
+        if !cache_config::is_caching_enabled() {
+            return None;
+        }
+        self.cache.get(key)
+    }
+
+    /// Store the encoded render for `key`.
+    ///
+    /// Does nothing if caching is globally disabled.
+    pub fn insert(&self, key: RenderCacheKey, bytes: impl Into<Arc<[u8]>>) {
+        if !cache_config::is_caching_enabled() {
+            return;
+        }
+        self.cache.insert(key, bytes.into());
+    }
+
+    /// Drop every render made with the font `font_data`.
+    ///
+    /// Returns how many entries were dropped.
+    pub fn invalidate_font(&self, font_data: &[u8]) -> usize {
+        let font = font_id(font_data);
+        self.cache.evict_where(|key| key.font_id == font)
+    }
+
+    /// Drop every entry.
+    pub fn clear(&self) {
+        self.cache.invalidate_all();
+        self.cache.run_pending_tasks();
+    }
+
+    /// Bytes of encoded output held.
+    pub fn weighted_size(&self) -> u64 {
+        self.cache.weighted_size()
+    }
+
+    /// Number of renders held.
+    pub fn entry_count(&self) -> u64 {
+        self.cache.entry_count()
+    }
+}
+
+impl Default for RenderCache {
+    /// A cache with the default byte limit (512 MB or `TYPF_CACHE_MAX_BYTES`).
+    fn default() -> Self {
+        Self::new(crate::cache::get_cache_max_bytes())
+    }
+}
+
+impl InvalidateFont for RenderCache {
+    fn invalidate_font(&self, font_data: &[u8]) -> usize {
+        RenderCache::invalidate_font(self, font_data)
+    }
+}
+
+impl TrimIdle for RenderCache {
+    fn trim_idle(&self, max_age: Duration) -> usize {
+        self.cache.evict_idle(max_age)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(text: &str, font: &[u8], render: &RenderParams) -> RenderCacheKey {
+        RenderCacheKey::new(
+            "none/opixa/png",
+            text,
+            font,
+            &ShapingParams::default(),
+            render,
+        )
+    }
+
+    #[test]
+    fn test_key_when_any_input_changes_then_differs() {
+        let render = RenderParams::default();
+        let base = key("Badge", b"font-a", &render);
+
+        assert_eq!(base, key("Badge", b"font-a", &render));
+        assert_ne!(base, key("Badge!", b"font-a", &render));
+        assert_ne!(base.font_id, key("Badge", b"font-b", &render).font_id);
+        let red = RenderParams {
+            foreground: crate::Color::rgba(255, 0, 0, 255),
+            ..RenderParams::default()
+        };
+        assert_ne!(base, key("Badge", b"font-a", &red));
+        let bigger = ShapingParams {
+            size: 32.0,
+            ..ShapingParams::default()
+        };
+        let resized = RenderCacheKey::new("none/opixa/png", "Badge", b"font-a", &bigger, &render);
+        assert_ne!(base, resized);
+        let svg = RenderCacheKey::new(
+            "none/opixa/svg",
+            "Badge",
+            b"font-a",
+            &ShapingParams::default(),
+            &render,
+        );
+        assert_ne!(base, svg);
+    }
+
+    #[test]
+    fn test_invalidate_font_when_font_reloads_then_only_its_entries_go() {
+        let _guard = crate::cache_config::scoped_caching_enabled(true);
+        let cache = RenderCache::new(1 << 20);
+        let render = RenderParams::default();
+        cache.insert(key("a", b"font-a", &render), vec![1u8, 2, 3]);
+        cache.insert(key("b", b"font-a", &render), vec![4u8]);
+        cache.insert(key("a", b"font-b", &render), vec![5u8]);
+
+        assert_eq!(
+            cache.get(&key("a", b"font-a", &render)).as_deref(),
+            Some(&[1u8, 2, 3][..])
+        );
+        assert_eq!(cache.invalidate_font(b"font-a"), 2);
+        assert!(cache.get(&key("a", b"font-a", &render)).is_none());
+        assert!(cache.get(&key("a", b"font-b", &render)).is_some());
+
+        cache.clear();
+        assert!(cache.get(&key("a", b"font-b", &render)).is_none());
+    }
+}
@@ -0,0 +1,790 @@
+//! Rendering into pixel buffers the caller already owns.
+//!
+//! [`Renderer::render`](crate::traits::Renderer::render) allocates a fresh
+//! canvas per call. Applications with a framebuffer, a window surface, or a
+//! tiny-skia pixmap would rather have text drawn straight into it.
+//! [`Renderer::render_into`](crate::traits::Renderer::render_into) does that:
+//! it draws into any [`RenderTarget`] at a pixel origin and reports the
+//! [`DirtyRect`] it touched.
+//!
+//! A target describes its memory with a width, height, row stride, and pixel
+//! format. [`BufferTarget`] wraps a plain `&mut [u8]`; backends may offer
+//! wrappers for their own surface types. The blend helpers here
+//! ([`fill_rect`], [`blend_mask`], [`blend_premul_rgba`],
+//! [`composite_bitmap`]) do the source-over compositing for any target, so
+//! renderers only decide where glyph pixels land.
+//!
+//! Supported target formats are [`BitmapFormat::Rgba8`], straight or
+//! premultiplied, [`BitmapFormat::Gray8`], which accumulates coverage, and
+//! [`BitmapFormat::Gray1`], which blends like `Gray8` and keeps each pixel
+//! set while its coverage is at least half.
+//!
+//! Editors and terminals that change a few cells per frame can call
+//! [`Renderer::render_region`](crate::traits::Renderer::render_region) with
+//! the rectangle that needs repainting. Only that rectangle is touched
+//! ([`ClipTarget`]), and renderers skip rasterizing glyphs that cannot reach
+//! it ([`GlyphCull`]).
+
+use std::sync::Arc;
+
+use crate::error::{RenderError, Result, TypfError};
+use crate::traits::{FontRef, Renderer};
+use crate::types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput, ShapingResult};
+use crate::{Color, RenderParams};
+
+/// Pixel rectangle of a target that changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirtyRect {
+    /// Left edge.
+    pub x: u32,
+    /// Top edge.
+    pub y: u32,
+    /// Width.
+    pub width: u32,
+    /// Height.
+    pub height: u32,
+}
+
+impl DirtyRect {
+    /// Overlap of both, if any.
+    pub fn intersect(self, other: DirtyRect) -> Option<DirtyRect> {
+        let x0 = self.x.max(other.x);
+        let y0 = self.y.max(other.y);
+        let x1 = (self.x + self.width).min(other.x + other.width);
+        let y1 = (self.y + self.height).min(other.y + other.height);
+        (x1 > x0 && y1 > y0).then(|| DirtyRect {
+            x: x0,
+            y: y0,
+            width: x1 - x0,
+            height: y1 - y0,
+        })
+    }
+
+    /// Smallest rectangle covering both.
+    pub fn union(self, other: DirtyRect) -> DirtyRect {
+        let x0 = self.x.min(other.x);
+        let y0 = self.y.min(other.y);
+        let x1 = (self.x + self.width).max(other.x + other.width);
+        let y1 = (self.y + self.height).max(other.y + other.height);
+        DirtyRect {
+            x: x0,
+            y: y0,
+            width: x1 - x0,
+            height: y1 - y0,
+        }
+    }
+
+    /// Union of two optional rectangles.
+    pub fn merge(a: Option<DirtyRect>, b: Option<DirtyRect>) -> Option<DirtyRect> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(a.union(b)),
+            (a, None) => a,
+            (None, b) => b,
+        }
+    }
+}
+
+/// A caller-owned pixel buffer that renderers can draw into.
+pub trait RenderTarget {
+    /// Width in pixels.
+    fn width(&self) -> u32;
+    /// Height in pixels.
+    fn height(&self) -> u32;
+    /// Bytes from the start of one row to the start of the next.
+    fn stride(&self) -> usize;
+    /// Pixel layout; `Rgba8`, `Gray8`, or `Gray1`.
+    fn format(&self) -> BitmapFormat;
+    /// Whether `Rgba8` color channels are premultiplied by alpha.
+    fn premultiplied(&self) -> bool {
+        false
+    }
+    /// The pixel memory, at least `stride * (height - 1) + row bytes` long.
+    fn pixels_mut(&mut self) -> &mut [u8];
+    /// Called with each region a render changed. The default ignores it.
+    fn mark_dirty(&mut self, _rect: DirtyRect) {}
+    /// Only pixels inside this rectangle may change. The default allows the
+    /// whole target.
+    fn clip_rect(&self) -> Option<DirtyRect> {
+        None
+    }
+}
+
+/// [`RenderTarget`] over a borrowed byte slice.
+///
+/// ```
+/// use typf_core::render_target::{BufferTarget, RenderTarget};
+/// use typf_core::types::BitmapFormat;
+///
+/// let mut frame = vec![0u8; 64 * 4 * 32];
+/// let target = BufferTarget::new(&mut frame, 64, 32, 64 * 4, BitmapFormat::Rgba8).unwrap();
+/// assert_eq!(target.stride(), 256);
+/// ```
+#[derive(Debug)]
+pub struct BufferTarget<'a> {
+    data: &'a mut [u8],
+    width: u32,
+    height: u32,
+    stride: usize,
+    format: BitmapFormat,
+    premultiplied: bool,
+    dirty: Option<DirtyRect>,
+}
+
+impl<'a> BufferTarget<'a> {
+    /// Wrap `data` as a `width` x `height` image with rows `stride` bytes apart.
+    ///
+    /// Fails if the format is `Rgb8`, the stride is shorter than a row, or
+    /// `data` is too small for the last row.
+    pub fn new(
+        data: &'a mut [u8],
+        width: u32,
+        height: u32,
+        stride: usize,
+        format: BitmapFormat,
+    ) -> Result<Self> {
+        check_format(format)?;
+        let row = format.row_bytes(width);
+        if stride < row {
+            return Err(TypfError::ConfigError(format!(
+                "stride {stride} is shorter than a {width}-pixel row ({row} bytes)"
+            )));
+        }
+        let needed = if height == 0 {
+            0
+        } else {
+            stride * (height as usize - 1) + row
+        };
+        if data.len() < needed {
+            return Err(TypfError::ConfigError(format!(
+                "buffer of {} bytes is too small for {width}x{height} at stride {stride} ({needed} bytes)",
+                data.len()
+            )));
+        }
+        Ok(Self {
+            data,
+            width,
+            height,
+            stride,
+            format,
+            premultiplied: false,
+            dirty: None,
+        })
+    }
+
+    /// Treat `Rgba8` pixels as premultiplied alpha.
+    pub fn with_premultiplied(mut self, premultiplied: bool) -> Self {
+        self.premultiplied = premultiplied;
+        self
+    }
+
+    /// Everything drawn since the last [`take_dirty`](Self::take_dirty).
+    pub fn dirty(&self) -> Option<DirtyRect> {
+        self.dirty
+    }
+
+    /// Return and reset the accumulated dirty region.
+    pub fn take_dirty(&mut self) -> Option<DirtyRect> {
+        self.dirty.take()
+    }
+}
+
+impl RenderTarget for BufferTarget<'_> {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn stride(&self) -> usize {
+        self.stride
+    }
+
+    fn format(&self) -> BitmapFormat {
+        self.format
+    }
+
+    fn premultiplied(&self) -> bool {
+        self.premultiplied
+    }
+
+    fn pixels_mut(&mut self) -> &mut [u8] {
+        self.data
+    }
+
+    fn mark_dirty(&mut self, rect: DirtyRect) {
+        self.dirty = DirtyRect::merge(self.dirty, Some(rect));
+    }
+}
+
+/// Restricts drawing on another target to a rectangle.
+///
+/// Used by [`Renderer::render_region`](crate::traits::Renderer::render_region);
+/// everything outside `clip` is left untouched. Dirty regions are forwarded to
+/// the wrapped target.
+pub struct ClipTarget<'a> {
+    inner: &'a mut dyn RenderTarget,
+    clip: DirtyRect,
+}
+
+impl<'a> ClipTarget<'a> {
+    /// Limit drawing on `inner` to `clip`, itself narrowed by any clip `inner` already has.
+    pub fn new(inner: &'a mut dyn RenderTarget, clip: DirtyRect) -> Self {
+        let clip = match inner.clip_rect() {
+            Some(outer) => clip.intersect(outer).unwrap_or(DirtyRect {
+                width: 0,
+                height: 0,
+                ..clip
+            }),
+            None => clip,
+        };
+        Self { inner, clip }
+    }
+}
+
+impl RenderTarget for ClipTarget<'_> {
+    fn width(&self) -> u32 {
+        self.inner.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.inner.height()
+    }
+
+    fn stride(&self) -> usize {
+        self.inner.stride()
+    }
+
+    fn format(&self) -> BitmapFormat {
+        self.inner.format()
+    }
+
+    fn premultiplied(&self) -> bool {
+        self.inner.premultiplied()
+    }
+
+    fn pixels_mut(&mut self) -> &mut [u8] {
+        self.inner.pixels_mut()
+    }
+
+    fn mark_dirty(&mut self, rect: DirtyRect) {
+        self.inner.mark_dirty(rect);
+    }
+
+    fn clip_rect(&self) -> Option<DirtyRect> {
+        Some(self.clip)
+    }
+}
+
+/// Decides which glyphs a clipped redraw must rasterize.
+///
+/// Glyph bitmaps are not known before rasterizing, so the test is
+/// conservative: a glyph is kept when its pen span, widened by `margin` on
+/// both sides, overlaps the clip horizontally. Renderers use the font size as
+/// the margin, which covers overhangs and mark offsets in practice.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphCull {
+    min_x: f32,
+    max_x: f32,
+    margin: f32,
+}
+
+impl GlyphCull {
+    /// Cull against `clip` on a target where the run's canvas starts at `origin`.
+    pub fn new(clip: DirtyRect, origin: (i32, i32), margin: f32) -> Self {
+        let min_x = clip.x as f32 - origin.0 as f32;
+        Self {
+            min_x,
+            max_x: min_x + clip.width as f32,
+            margin: margin.max(0.0),
+        }
+    }
+
+    /// Whether a glyph whose pen sits at canvas column `x` with `advance` may touch the clip.
+    pub fn keeps(&self, x: f32, advance: f32) -> bool {
+        let (start, end) = if advance < 0.0 {
+            (x + advance, x)
+        } else {
+            (x, x + advance)
+        };
+        end + self.margin > self.min_x && start - self.margin < self.max_x
+    }
+
+    /// Whole pixels a skipped glyph's ink reaches above and below its pen,
+    /// as `(top, bottom)` with y up, read from its outline bounds.
+    ///
+    /// Renderers stretch the line box by it so culling never moves the
+    /// baseline. `None` for glyphs without outline bounds, such as spaces
+    /// and bitmap-only emoji.
+    #[cfg(feature = "glyph-metrics")]
+    pub fn skipped_extent(
+        &self,
+        font: &dyn crate::traits::FontRef,
+        glyph_id: crate::types::GlyphId,
+        size: f32,
+        variations: &[(String, f32)],
+    ) -> Option<(f32, f32)> {
+        let bounds = crate::glyph_metrics::glyph_metrics(font, glyph_id, size, variations)
+            .ok()?
+            .bounds?;
+        Some((bounds.y_max.ceil(), bounds.y_min.floor()))
+    }
+}
+
+fn check_format(format: BitmapFormat) -> Result<()> {
+    match format {
+        BitmapFormat::Rgba8 | BitmapFormat::Gray8 | BitmapFormat::Gray1 => Ok(()),
+        other => Err(RenderError::FormatNotSupported(format!(
+            "render targets must be Rgba8, Gray8 or Gray1, not {other:?}"
+        ))
+        .into()),
+    }
+}
+
+/// Clip a `width` x `height` rectangle at `(x, y)` to the target and its clip rectangle.
+fn clip(target: &dyn RenderTarget, x: i32, y: i32, width: u32, height: u32) -> Option<DirtyRect> {
+    let x0 = x.max(0) as i64;
+    let y0 = y.max(0) as i64;
+    let x1 = (x as i64 + width as i64).min(target.width() as i64);
+    let y1 = (y as i64 + height as i64).min(target.height() as i64);
+    let rect = (x1 > x0 && y1 > y0).then(|| DirtyRect {
+        x: x0 as u32,
+        y: y0 as u32,
+        width: (x1 - x0) as u32,
+        height: (y1 - y0) as u32,
+    })?;
+    match target.clip_rect() {
+        Some(limit) => rect.intersect(limit),
+        None => Some(rect),
+    }
+}
+
+/// Source-over one premultiplied `[r, g, b, a]` source pixel onto `dst`.
+#[inline]
+fn blend_pixel(dst: &mut [u8], format: BitmapFormat, premultiplied: bool, src: [u32; 4]) {
+    let inv_a = 255 - src[3];
+    match format {
+        BitmapFormat::Gray8 => {
+            dst[0] = (src[3] + dst[0] as u32 * inv_a / 255).min(255) as u8;
+        },
+        _ if premultiplied => {
+            for c in 0..4 {
+                dst[c] = (src[c] + dst[c] as u32 * inv_a / 255).min(255) as u8;
+            }
+        },
+        _ => {
+            let dst_a = dst[3] as u32;
+            let out_a = (src[3] + dst_a * inv_a / 255).min(255);
+            for c in 0..3 {
+                let dst_c = dst[c] as u32 * dst_a / 255;
+                let out_c = src[c] + dst_c * inv_a / 255;
+                dst[c] = (out_c * 255 + out_a / 2)
+                    .checked_div(out_a)
+                    .map_or(0, |v| v.min(255) as u8);
+            }
+            dst[3] = out_a as u8;
+        },
+    }
+}
+
+/// Run `blend` over every pixel of the clipped rectangle and mark it dirty.
+///
+/// `blend` receives the destination pixel and the offset into the source
+/// rectangle. `Gray1` pixels are handed over as a `Gray8` byte of 0 or 255
+/// and thresholded at half on the way back.
+fn for_each_pixel(
+    target: &mut dyn RenderTarget,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    mut blend: impl FnMut(&mut [u8], BitmapFormat, bool, u32, u32) -> bool,
+) -> Result<Option<DirtyRect>> {
+    let format = target.format();
+    check_format(format)?;
+    let premultiplied = target.premultiplied();
+    let stride = target.stride();
+    let Some(rect) = clip(target, x, y, width, height) else {
+        return Ok(None);
+    };
+
+    let mut touched = false;
+    let pixels = target.pixels_mut();
+    for ty in rect.y..rect.y + rect.height {
+        let row = ty as usize * stride;
+        let sy = (ty as i64 - y as i64) as u32;
+        for tx in rect.x..rect.x + rect.width {
+            let sx = (tx as i64 - x as i64) as u32;
+            if format == BitmapFormat::Gray1 {
+                let (at, bit) = (row + tx as usize / 8, 0x80 >> (tx % 8));
+                let mut cell = [if pixels[at] & bit != 0 { 255 } else { 0 }];
+                if blend(&mut cell, BitmapFormat::Gray8, premultiplied, sx, sy) {
+                    touched = true;
+                    if cell[0] >= 128 {
+                        pixels[at] |= bit;
+                    } else {
+                        pixels[at] &= !bit;
+                    }
+                }
+                continue;
+            }
+            let bpp = format.row_bytes(1);
+            let at = row + tx as usize * bpp;
+            touched |= blend(&mut pixels[at..at + bpp], format, premultiplied, sx, sy);
+        }
+    }
+
+    if !touched {
+        return Ok(None);
+    }
+    target.mark_dirty(rect);
+    Ok(Some(rect))
+}
+
+/// Fill a rectangle with `color`, replacing what was there.
+pub fn fill_rect(
+    target: &mut dyn RenderTarget,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    color: Color,
+) -> Result<Option<DirtyRect>> {
+    let a = color.a as u32;
+    let premul = [
+        color.r as u32 * a / 255,
+        color.g as u32 * a / 255,
+        color.b as u32 * a / 255,
+        a,
+    ];
+    for_each_pixel(
+        target,
+        x,
+        y,
+        width,
+        height,
+        |dst, format, premultiplied, _, _| {
+            match format {
+                BitmapFormat::Gray8 => dst[0] = color.a,
+                _ if premultiplied => {
+                    for c in 0..4 {
+                        dst[c] = premul[c] as u8;
+                    }
+                },
+                _ => dst.copy_from_slice(&[color.r, color.g, color.b, color.a]),
+            }
+            true
+        },
+    )
+}
+
+/// Blend a coverage mask tinted with `color` at `(x, y)`.
+pub fn blend_mask(
+    target: &mut dyn RenderTarget,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    mask: &[u8],
+    color: Color,
+) -> Result<Option<DirtyRect>> {
+    for_each_pixel(
+        target,
+        x,
+        y,
+        width,
+        height,
+        |dst, format, premultiplied, sx, sy| {
+            let coverage = mask[(sy * width + sx) as usize] as u32;
+            if coverage == 0 {
+                return false;
+            }
+            let src_a = coverage * color.a as u32 / 255;
+            let src = [
+                color.r as u32 * src_a / 255,
+                color.g as u32 * src_a / 255,
+                color.b as u32 * src_a / 255,
+                src_a,
+            ];
+            blend_pixel(dst, format, premultiplied, src);
+            true
+        },
+    )
+}
+
+/// Blend premultiplied RGBA pixels (color glyphs) at `(x, y)`.
+pub fn blend_premul_rgba(
+    target: &mut dyn RenderTarget,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+) -> Result<Option<DirtyRect>> {
+    for_each_pixel(
+        target,
+        x,
+        y,
+        width,
+        height,
+        |dst, format, premultiplied, sx, sy| {
+            let at = ((sy * width + sx) * 4) as usize;
+            let src_a = rgba[at + 3] as u32;
+            if src_a == 0 {
+                return false;
+            }
+            let src = [
+                rgba[at] as u32,
+                rgba[at + 1] as u32,
+                rgba[at + 2] as u32,
+                src_a,
+            ];
+            blend_pixel(dst, format, premultiplied, src);
+            true
+        },
+    )
+}
+
+/// Blend a renderer's straight-alpha `Rgba8` or coverage `Gray8`/`Gray1` bitmap at `(x, y)`.
+///
+/// `Gray8` and `Gray1` sources are treated as coverage of opaque black.
+pub fn composite_bitmap(
+    target: &mut dyn RenderTarget,
+    x: i32,
+    y: i32,
+    bitmap: &BitmapData,
+) -> Result<Option<DirtyRect>> {
+    let (width, height) = (bitmap.width, bitmap.height);
+    match bitmap.format {
+        BitmapFormat::Gray8 => {
+            let packed = bitmap.to_packed();
+            blend_mask(target, x, y, width, height, &packed.data, Color::black())
+        },
+        BitmapFormat::Rgba8 => {
+            let straight = bitmap.to_layout(PixelLayout::RGBA);
+            for_each_pixel(
+                target,
+                x,
+                y,
+                width,
+                height,
+                |dst, format, premultiplied, sx, sy| {
+                    let at = sy as usize * straight.stride + sx as usize * 4;
+                    let px = &straight.data[at..at + 4];
+                    let src_a = px[3] as u32;
+                    if src_a == 0 {
+                        return false;
+                    }
+                    let src = [
+                        px[0] as u32 * src_a / 255,
+                        px[1] as u32 * src_a / 255,
+                        px[2] as u32 * src_a / 255,
+                        src_a,
+                    ];
+                    blend_pixel(dst, format, premultiplied, src);
+                    true
+                },
+            )
+        },
+        BitmapFormat::Gray1 => {
+            let stride = bitmap.stride;
+            let mut mask = vec![0u8; (width * height) as usize];
+            for (i, coverage) in mask.iter_mut().enumerate() {
+                let (sx, sy) = (i % width as usize, i / width as usize);
+                let byte = bitmap.data.get(sy * stride + sx / 8).copied().unwrap_or(0);
+                if byte & (0x80 >> (sx % 8)) != 0 {
+                    *coverage = 255;
+                }
+            }
+            blend_mask(target, x, y, width, height, &mask, Color::black())
+        },
+        other => Err(RenderError::FormatNotSupported(format!(
+            "cannot composite {other:?} bitmaps into a render target"
+        ))
+        .into()),
+    }
+}
+
+/// Redraw only the part of a run inside `clip`.
+///
+/// This is the default [`Renderer::render_region`](crate::traits::Renderer::render_region):
+/// it limits drawing to `clip` and otherwise draws like
+/// [`Renderer::render_into`](crate::traits::Renderer::render_into).
+pub fn render_clipped(
+    renderer: &(impl Renderer + ?Sized),
+    shaped: &ShapingResult,
+    font: Arc<dyn FontRef>,
+    params: &RenderParams,
+    target: &mut dyn RenderTarget,
+    origin: (i32, i32),
+    clip: DirtyRect,
+) -> Result<Option<DirtyRect>> {
+    let mut clipped = ClipTarget::new(target, clip);
+    renderer.render_into(shaped, font, params, &mut clipped, origin)
+}
+
+/// Render through [`Renderer::render`] and composite the bitmap into `target`.
+///
+/// This is the default [`Renderer::render_into`]; renderers that override it
+/// can fall back here for modes they do not draw directly.
+pub fn render_via_bitmap(
+    renderer: &(impl Renderer + ?Sized),
+    shaped: &ShapingResult,
+    font: Arc<dyn FontRef>,
+    params: &RenderParams,
+    target: &mut dyn RenderTarget,
+    origin: (i32, i32),
+) -> Result<Option<DirtyRect>> {
+    match renderer.render(shaped, font, params)? {
+        RenderOutput::Bitmap(bitmap) => composite_bitmap(target, origin.0, origin.1, &bitmap),
+        _ => Err(RenderError::FormatNotSupported(format!(
+            "{} produced non-bitmap output, which cannot be drawn into a render target",
+            renderer.name()
+        ))
+        .into()),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_target_when_stride_too_short_then_errors() {
+        let mut data = vec![0u8; 100];
+        assert!(BufferTarget::new(&mut data, 10, 2, 30, BitmapFormat::Rgba8).is_err());
+        assert!(BufferTarget::new(&mut data, 10, 3, 40, BitmapFormat::Rgba8).is_err());
+        assert!(BufferTarget::new(&mut data, 10, 2, 40, BitmapFormat::Rgb8).is_err());
+        assert!(BufferTarget::new(&mut data, 10, 2, 50, BitmapFormat::Rgba8).is_ok());
+    }
+
+    #[test]
+    fn test_blend_mask_when_partly_offscreen_then_clips_and_marks_dirty() {
+        // 4x2 target with 2 bytes of row padding.
+        let mut data = vec![0u8; 18];
+        let mut target =
+            BufferTarget::new(&mut data, 4, 2, 6, BitmapFormat::Gray8).expect("target");
+
+        let mask = [255, 128, 0, 255];
+        // Only the mask's zero texel lands on the target: nothing changes.
+        let dirty = blend_mask(&mut target, 3, -1, 2, 2, &mask, Color::black()).expect("blend");
+        assert_eq!(dirty, None);
+        assert_eq!(target.dirty(), None);
+
+        let dirty = blend_mask(&mut target, 3, 0, 2, 2, &mask, Color::black()).expect("blend");
+        let clipped = DirtyRect {
+            x: 3,
+            y: 0,
+            width: 1,
+            height: 2,
+        };
+        assert_eq!(dirty, Some(clipped));
+        assert_eq!(target.take_dirty(), Some(clipped));
+        assert_eq!(&data[0..6], &[0, 0, 0, 255, 0, 0]);
+        assert_eq!(&data[6..12], &[0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_blend_mask_when_gray1_then_bits_set_from_half_coverage() {
+        // 10 pixels need two bytes per row
+        let mut data = vec![0u8; 4];
+        let mut target =
+            BufferTarget::new(&mut data, 10, 2, 2, BitmapFormat::Gray1).expect("target");
+
+        let mask = [255, 127, 128, 0, 0, 0, 0, 0, 0, 200];
+        blend_mask(&mut target, 0, 1, 10, 1, &mask, Color::black()).expect("blend");
+        fill_rect(&mut target, 6, 0, 3, 1, Color::black()).expect("fill");
+
+        assert_eq!(data, [0b0000_0011, 0b1000_0000, 0b1010_0000, 0b0100_0000]);
+
+        let bitmap = BitmapData {
+            width: 10,
+            height: 2,
+            format: BitmapFormat::Gray1,
+            stride: 2,
+            layout: PixelLayout::RGBA,
+            data,
+        };
+        let mut gray = vec![0u8; 20];
+        let mut target =
+            BufferTarget::new(&mut gray, 10, 2, 10, BitmapFormat::Gray8).expect("target");
+        composite_bitmap(&mut target, 0, 0, &bitmap).expect("composite");
+        assert_eq!(&gray[6..10], &[255, 255, 255, 0]);
+        assert_eq!(&gray[10..13], &[255, 0, 255]);
+    }
+
+    #[test]
+    fn test_clip_target_when_fill_then_only_clip_changes() {
+        let mut data = vec![0u8; 16];
+        let mut target =
+            BufferTarget::new(&mut data, 4, 4, 4, BitmapFormat::Gray8).expect("target");
+        let clip = DirtyRect {
+            x: 1,
+            y: 1,
+            width: 2,
+            height: 5,
+        };
+
+        let dirty = {
+            let mut clipped = ClipTarget::new(&mut target, clip);
+            fill_rect(&mut clipped, 0, 0, 4, 4, Color::black()).expect("fill")
+        };
+        let expected = DirtyRect {
+            x: 1,
+            y: 1,
+            width: 2,
+            height: 3,
+        };
+        assert_eq!(dirty, Some(expected));
+        assert_eq!(target.dirty(), Some(expected));
+        assert_eq!(
+            data,
+            [0, 0, 0, 0, 0, 255, 255, 0, 0, 255, 255, 0, 0, 255, 255, 0]
+        );
+    }
+
+    #[test]
+    fn test_glyph_cull_when_far_from_clip_then_dropped() {
+        let clip = DirtyRect {
+            x: 110,
+            y: 0,
+            width: 20,
+            height: 10,
+        };
+        // Canvas starts at x = 100, so the clip covers canvas columns 10..30.
+        let cull = GlyphCull::new(clip, (100, 0), 4.0);
+        assert!(cull.keeps(12.0, 8.0));
+        assert!(cull.keeps(0.0, 8.0), "margin reaches column 12");
+        assert!(!cull.keeps(-10.0, 5.0));
+        assert!(!cull.keeps(34.0, 8.0));
+        assert!(cull.keeps(40.0, -12.0), "RTL advance spans 28..40");
+    }
+
+    #[test]
+    fn test_blend_when_straight_and_premultiplied_then_agree() {
+        let red = Color::rgba(255, 0, 0, 255);
+        let bg = Color::rgba(0, 0, 255, 128);
+
+        let mut straight = vec![0u8; 4];
+        let mut target =
+            BufferTarget::new(&mut straight, 1, 1, 4, BitmapFormat::Rgba8).expect("target");
+        fill_rect(&mut target, 0, 0, 1, 1, bg).expect("fill");
+        blend_mask(&mut target, 0, 0, 1, 1, &[128], red).expect("blend");
+
+        let mut premul = vec![0u8; 4];
+        let mut target = BufferTarget::new(&mut premul, 1, 1, 4, BitmapFormat::Rgba8)
+            .expect("target")
+            .with_premultiplied(true);
+        fill_rect(&mut target, 0, 0, 1, 1, bg).expect("fill");
+        blend_mask(&mut target, 0, 0, 1, 1, &[128], red).expect("blend");
+
+        assert_eq!(straight[3], premul[3]);
+        let a = premul[3] as i32;
+        for c in 0..3 {
+            let unpremul = premul[c] as i32 * 255 / a;
+            assert!((straight[c] as i32 - unpremul).abs() <= 2, "channel {c}");
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Superscripts, subscripts, and other runs set off the baseline.
+//!
+//! A [`RunOffset`] shrinks a run and raises or lowers it against the text
+//! around it, so footnote markers and chemical formulas come out right
+//! without moving glyphs by hand. Fonts state their preferred sizes and
+//! offsets in the OS/2 table; [`RunOffset::superscript`] and
+//! [`RunOffset::subscript`] read them from [`FontMetrics`], and
+//! [`RunOffset::new`] takes explicit values.
+//!
+//! Apply the offset to the run's parameters, then shape and render as usual:
+//!
+//! ```ignore
+//! let offset = RunOffset::subscript(font.metrics().as_ref());
+//! let (shaping, render) = offset.apply(&shaping, &render);
+//! let shaped = shaper.shape("2", font.clone(), &shaping)?;
+//! let output = renderer.render(&shaped, font, &render)?;
+//! ```
+//!
+//! The shaper sees the smaller size, so advances and kerning match it, and
+//! the renderer moves the run through [`RenderParams::baseline_shift`].
+
+use crate::types::{FontMetrics, ScriptMetrics};
+use crate::{RenderParams, ShapingParams};
+
+/// Size and baseline shift for one run, relative to the text around it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunOffset {
+    /// Run font size as a fraction of the surrounding size
+    pub scale: f32,
+    /// Baseline shift in ems of the surrounding size; positive raises the run
+    pub shift: f32,
+}
+
+impl Default for RunOffset {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+impl RunOffset {
+    /// Same size, on the baseline
+    pub const NONE: Self = Self {
+        scale: 1.0,
+        shift: 0.0,
+    };
+
+    /// Size used when a font states no usable subscript or superscript size
+    const FALLBACK_SCALE: f32 = 0.65;
+    /// Subscript drop used when a font states none
+    const FALLBACK_SUBSCRIPT_SHIFT: f32 = 0.075;
+    /// Superscript rise used when a font states none
+    const FALLBACK_SUPERSCRIPT_SHIFT: f32 = 0.35;
+
+    /// Scale the run by `scale` and raise it by `shift` ems (negative lowers)
+    pub fn new(scale: f32, shift: f32) -> Self {
+        Self { scale, shift }
+    }
+
+    /// The font's superscript placement, or 65% size raised 0.35 em
+    pub fn superscript(metrics: Option<&FontMetrics>) -> Self {
+        metrics
+            .and_then(|m| Self::from_script_metrics(m.superscript?, m.units_per_em, 1.0))
+            .unwrap_or(Self::new(
+                Self::FALLBACK_SCALE,
+                Self::FALLBACK_SUPERSCRIPT_SHIFT,
+            ))
+    }
+
+    /// The font's subscript placement, or 65% size lowered 0.075 em
+    pub fn subscript(metrics: Option<&FontMetrics>) -> Self {
+        metrics
+            .and_then(|m| Self::from_script_metrics(m.subscript?, m.units_per_em, -1.0))
+            .unwrap_or(Self::new(
+                Self::FALLBACK_SCALE,
+                -Self::FALLBACK_SUBSCRIPT_SHIFT,
+            ))
+    }
+
+    /// OS/2 values in font units; `direction` turns OS/2's offset sign into ours
+    fn from_script_metrics(
+        metrics: ScriptMetrics,
+        units_per_em: u16,
+        direction: f32,
+    ) -> Option<Self> {
+        if units_per_em == 0 || metrics.y_size <= 0 {
+            return None;
+        }
+        let em = units_per_em as f32;
+        Some(Self::new(
+            metrics.y_size as f32 / em,
+            direction * metrics.y_offset as f32 / em,
+        ))
+    }
+
+    /// Font size of the run when the surrounding text is `size` pixels
+    pub fn size(&self, size: f32) -> f32 {
+        size * self.scale
+    }
+
+    /// Pixels to raise the run when the surrounding text is `size` pixels
+    pub fn baseline_shift(&self, size: f32) -> f32 {
+        size * self.shift
+    }
+
+    /// Parameters for the run, given those of the surrounding text
+    ///
+    /// Shaping gets the scaled size and letter spacing; rendering gets the
+    /// shift added to any baseline shift it already has.
+    pub fn apply(
+        &self,
+        shaping: &ShapingParams,
+        render: &RenderParams,
+    ) -> (ShapingParams, RenderParams) {
+        let shaping_params = ShapingParams {
+            size: self.size(shaping.size),
+            letter_spacing: shaping.letter_spacing * self.scale,
+            ..shaping.clone()
+        };
+        let render_params = RenderParams {
+            baseline_shift: render.baseline_shift + self.baseline_shift(shaping.size),
+            ..render.clone()
+        };
+        (shaping_params, render_params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Baselines, LineMetrics};
+
+    fn metrics() -> FontMetrics {
+        FontMetrics {
+            units_per_em: 1000,
+            ascent: 1069,
+            descent: -293,
+            line_gap: 0,
+            baselines: Baselines::synthesized(1069, -293),
+            hhea: LineMetrics::default(),
+            typo: None,
+            win: None,
+            use_typo_metrics: false,
+            x_height: None,
+            cap_height: None,
+            subscript: Some(ScriptMetrics {
+                x_size: 650,
+                y_size: 600,
+                x_offset: 0,
+                y_offset: 140,
+            }),
+            superscript: Some(ScriptMetrics {
+                x_size: 650,
+                y_size: 600,
+                x_offset: 0,
+                y_offset: 477,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_script_offsets_when_font_states_them_then_used() {
+        let metrics = metrics();
+        assert_eq!(
+            RunOffset::superscript(Some(&metrics)),
+            RunOffset::new(0.6, 0.477)
+        );
+        assert_eq!(
+            RunOffset::subscript(Some(&metrics)),
+            RunOffset::new(0.6, -0.14)
+        );
+
+        let unusable = FontMetrics {
+            superscript: Some(ScriptMetrics::default()),
+            subscript: None,
+            ..metrics
+        };
+        assert_eq!(
+            RunOffset::superscript(Some(&unusable)),
+            RunOffset::new(0.65, 0.35)
+        );
+        assert_eq!(RunOffset::subscript(None), RunOffset::new(0.65, -0.075));
+    }
+
+    #[test]
+    fn test_apply_when_superscript_then_smaller_and_raised() {
+        let shaping = ShapingParams {
+            size: 20.0,
+            letter_spacing: 2.0,
+            ..ShapingParams::default()
+        };
+        let render = RenderParams {
+            baseline_shift: 1.0,
+            ..RenderParams::default()
+        };
+
+        let (shaping, render) = RunOffset::new(0.5, 0.25).apply(&shaping, &render);
+        assert_eq!(shaping.size, 10.0);
+        assert_eq!(shaping.letter_spacing, 1.0);
+        assert_eq!(render.baseline_shift, 6.0);
+
+        let (same, _) = RunOffset::NONE.apply(&shaping, &render);
+        assert_eq!(same.size, shaping.size);
+    }
+}
@@ -0,0 +1,125 @@
+//! Degrading gracefully on malformed fonts.
+//!
+//! A font file can claim anything: outlines a million ems wide, paint graphs
+//! that nest without end, embedded bitmaps larger than any screen. Renderers
+//! guard against each, and [`RenderParams::strict`](crate::RenderParams::strict)
+//! picks what happens when a guard trips:
+//!
+//! - by default the glyph is worked around: glyphs that cannot be drawn are
+//!   skipped, and bounding boxes past [`MAX_GLYPH_EXTENT_EM`] are clamped so
+//!   one bad glyph cannot blow up the canvas, each noted in the render's
+//!   [`Diagnostics`] when it has them;
+//! - in strict mode the call fails with the error instead, for validators
+//!   and test suites that would rather know.
+//!
+//! ```
+//! use typf_core::sanitize::clamp_glyph_bounds;
+//!
+//! let huge = [-1.0e9, 0.0, 30.0, 40.0];
+//! let clamped = clamp_glyph_bounds(7, huge, 50.0, false, None).expect("clamped");
+//! assert_eq!(clamped, [-400.0, 0.0, 30.0, 40.0]);
+//! assert!(clamp_glyph_bounds(7, huge, 50.0, true, None).is_err());
+//! ```
+
+use crate::diagnostics::{self, DiagnosticKind, Diagnostics};
+use crate::error::RenderError;
+
+/// Farthest, in ems, glyph ink may reach from its origin
+///
+/// Generous enough for stacked marks and long swashes; real glyphs stay well
+/// inside it, so a glyph past it comes from broken or hostile data.
+pub const MAX_GLYPH_EXTENT_EM: f32 = 8.0;
+
+/// [`MAX_GLYPH_EXTENT_EM`] in pixels at `font_size`
+pub fn glyph_extent_limit(font_size: f32) -> f32 {
+    font_size.abs().max(1.0) * MAX_GLYPH_EXTENT_EM
+}
+
+/// Check glyph `bounds` (`[x_min, y_min, x_max, y_max]` in pixels from the
+/// glyph origin) against [`glyph_extent_limit`]
+///
+/// Bounds inside the limit come back unchanged. Bounds past it are clamped to
+/// it, noted in `diagnostics` as [`DiagnosticKind::BoundsClamped`], or fail
+/// with [`RenderError::GlyphBoundsTooLarge`] when `strict`.
+/// Non-finite bounds cannot be clamped meaningfully and always fail with
+/// [`RenderError::PathBuildingFailed`].
+pub fn clamp_glyph_bounds(
+    glyph_id: u32,
+    bounds: [f32; 4],
+    font_size: f32,
+    strict: bool,
+    diagnostics: Option<&Diagnostics>,
+) -> Result<[f32; 4], RenderError> {
+    if bounds.iter().any(|v| !v.is_finite()) {
+        return Err(RenderError::PathBuildingFailed);
+    }
+    let limit = glyph_extent_limit(font_size);
+    let extent = bounds.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+    if extent <= limit {
+        return Ok(bounds);
+    }
+    if strict {
+        return Err(RenderError::GlyphBoundsTooLarge {
+            glyph_id,
+            extent,
+            limit,
+            font_size,
+        });
+    }
+    diagnostics::warn(
+        diagnostics,
+        DiagnosticKind::BoundsClamped,
+        Some(glyph_id),
+        format!("reaches {extent}px from its origin; clamped to {limit}px"),
+    );
+    Ok(bounds.map(|v| v.clamp(-limit, limit)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_glyph_bounds_when_inside_limit_then_unchanged() {
+        let bounds = [-3.5, -10.0, 48.0, 70.0];
+        assert!(matches!(
+            clamp_glyph_bounds(1, bounds, 64.0, true, None),
+            Ok(b) if b == bounds
+        ));
+    }
+
+    #[test]
+    fn test_clamp_glyph_bounds_when_past_limit_then_strict_errors() {
+        let bounds = [0.0, -5000.0, 10.0, 10.0];
+        assert!(matches!(
+            clamp_glyph_bounds(9, bounds, 10.0, false, None),
+            Ok([0.0, -80.0, 10.0, 10.0])
+        ));
+        assert!(matches!(
+            clamp_glyph_bounds(9, bounds, 10.0, true, None),
+            Err(RenderError::GlyphBoundsTooLarge { glyph_id: 9, .. })
+        ));
+    }
+
+    #[test]
+    fn test_clamp_glyph_bounds_when_clamped_then_diagnostic_recorded() {
+        let diagnostics = Diagnostics::new();
+        let bounds = [0.0, -5000.0, 10.0, 10.0];
+        assert!(clamp_glyph_bounds(9, bounds, 10.0, false, Some(&diagnostics)).is_ok());
+        assert!(
+            clamp_glyph_bounds(9, [0.0, 0.0, 1.0, 1.0], 10.0, false, Some(&diagnostics)).is_ok()
+        );
+
+        let recorded = diagnostics.take();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].kind, DiagnosticKind::BoundsClamped);
+        assert_eq!(recorded[0].glyph_id, Some(9));
+    }
+
+    #[test]
+    fn test_clamp_glyph_bounds_when_not_finite_then_error_in_both_modes() {
+        let bounds = [0.0, f32::NAN, 10.0, f32::INFINITY];
+        assert!(clamp_glyph_bounds(1, bounds, 10.0, false, None).is_err());
+        assert!(clamp_glyph_bounds(1, bounds, 10.0, true, None).is_err());
+    }
+}
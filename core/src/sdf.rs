@@ -0,0 +1,223 @@
+//! Signed distance fields built from glyph coverage masks.
+//!
+//! Renderers that support [`RenderMode::DistanceField`](crate::RenderMode::DistanceField)
+//! rasterize each glyph as usual, then hand the anti-aliased coverage mask to
+//! [`coverage_to_distance_field`]. The result is a single-channel field where
+//! the outline sits at 50% grey, ink is brighter, and empty space is darker.
+//!
+//! The transform is the exact Euclidean distance transform of Felzenszwalb and
+//! Huttenlocher, run once for the outside and once for the inside of the
+//! shape. Partially covered pixels seed the transform with a sub-pixel offset
+//! so the field stays smooth even for small glyphs.
+
+/// Squared distance that stands in for "no feature pixel reachable".
+const INF: f32 = 1e20;
+
+/// Convert an 8-bit coverage mask into an 8-bit signed distance field.
+///
+/// `coverage` holds `width * height` alpha values (0 = empty, 255 = solid).
+/// The returned field is `(width + 2 * spread) * (height + 2 * spread)`
+/// bytes: the mask is padded by `spread` pixels on every side so the field
+/// can fall off outside the outline. Distances are clamped to `±spread`
+/// pixels, with 128 marking the edge, 255 deep inside, and 0 far outside.
+///
+/// A `spread` of zero is treated as one pixel.
+pub fn coverage_to_distance_field(
+    coverage: &[u8],
+    width: u32,
+    height: u32,
+    spread: u32,
+) -> Vec<u8> {
+    let spread = spread.max(1);
+    let out_w = (width + 2 * spread) as usize;
+    let out_h = (height + 2 * spread) as usize;
+    let len = out_w * out_h;
+
+    // Squared distance seeds: `outer` is zero on ink, `inner` is zero on air.
+    let mut outer = vec![INF; len];
+    let mut inner = vec![0.0f32; len];
+
+    for y in 0..height as usize {
+        for x in 0..width as usize {
+            let Some(&a) = coverage.get(y * width as usize + x) else {
+                continue;
+            };
+            let idx = (y + spread as usize) * out_w + x + spread as usize;
+            match a {
+                0 => {},
+                255 => {
+                    outer[idx] = 0.0;
+                    inner[idx] = INF;
+                },
+                _ => {
+                    let d = 0.5 - a as f32 / 255.0;
+                    outer[idx] = if d > 0.0 { d * d } else { 0.0 };
+                    inner[idx] = if d < 0.0 { d * d } else { 0.0 };
+                },
+            }
+        }
+    }
+
+    edt_2d(&mut outer, out_w, out_h);
+    edt_2d(&mut inner, out_w, out_h);
+
+    let scale = 1.0 / (2.0 * spread as f32);
+    outer
+        .iter()
+        .zip(inner.iter())
+        .map(|(o, i)| {
+            let dist = o.sqrt() - i.sqrt();
+            (255.0 * (0.5 - dist * scale)).round().clamp(0.0, 255.0) as u8
+        })
+        .collect()
+}
+
+/// Blend a distance field onto a single-channel canvas.
+///
+/// Overlapping fields are merged with `max`, which is the union of the two
+/// shapes in distance-field space. `(x, y)` is the canvas position of the
+/// field's top-left pixel; parts outside the canvas are clipped.
+#[allow(clippy::too_many_arguments)]
+pub fn blit_distance_field(
+    canvas: &mut [u8],
+    canvas_width: u32,
+    canvas_height: u32,
+    field: &[u8],
+    field_width: u32,
+    field_height: u32,
+    x: i32,
+    y: i32,
+) {
+    for fy in 0..field_height as i32 {
+        let py = y + fy;
+        if py < 0 || py >= canvas_height as i32 {
+            continue;
+        }
+        for fx in 0..field_width as i32 {
+            let px = x + fx;
+            if px < 0 || px >= canvas_width as i32 {
+                continue;
+            }
+            let src = field[(fy as u32 * field_width + fx as u32) as usize];
+            let dst = &mut canvas[(py as u32 * canvas_width + px as u32) as usize];
+            *dst = (*dst).max(src);
+        }
+    }
+}
+
+/// In-place 2D squared Euclidean distance transform (columns, then rows).
+fn edt_2d(grid: &mut [f32], width: usize, height: usize) {
+    let n = width.max(height);
+    let mut f = vec![0.0f32; n];
+    let mut d = vec![0.0f32; n];
+    let mut v = vec![0usize; n];
+    let mut z = vec![0.0f32; n + 1];
+
+    for x in 0..width {
+        for y in 0..height {
+            f[y] = grid[y * width + x];
+        }
+        edt_1d(&f[..height], &mut d[..height], &mut v, &mut z);
+        for y in 0..height {
+            grid[y * width + x] = d[y];
+        }
+    }
+
+    for y in 0..height {
+        let row = &mut grid[y * width..(y + 1) * width];
+        f[..width].copy_from_slice(row);
+        edt_1d(&f[..width], &mut d[..width], &mut v, &mut z);
+        row.copy_from_slice(&d[..width]);
+    }
+}
+
+/// 1D squared distance transform via the lower envelope of parabolas.
+fn edt_1d(f: &[f32], d: &mut [f32], v: &mut [usize], z: &mut [f32]) {
+    let n = f.len();
+    if n == 0 {
+        return;
+    }
+
+    let mut k = 0usize;
+    v[0] = 0;
+    z[0] = -INF;
+    z[1] = INF;
+
+    for q in 1..n {
+        let mut s;
+        loop {
+            let r = v[k];
+            s = ((f[q] + (q * q) as f32) - (f[r] + (r * r) as f32)) / (2.0 * (q as f32 - r as f32));
+            if s <= z[k] && k > 0 {
+                k -= 1;
+            } else {
+                break;
+            }
+        }
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = INF;
+    }
+
+    k = 0;
+    for (q, out) in d.iter_mut().enumerate() {
+        while z[k + 1] < q as f32 {
+            k += 1;
+        }
+        let r = v[k];
+        let dq = q as f32 - r as f32;
+        *out = dq * dq + f[r];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_field_when_solid_square_then_centre_bright_and_corners_dark() {
+        let coverage = vec![255u8; 8 * 8];
+        let field = coverage_to_distance_field(&coverage, 8, 8, 4);
+        let w = 16;
+        assert_eq!(field.len(), 16 * 16);
+
+        // Centre of the square is deep inside.
+        assert!(field[8 * w + 8] > 200, "centre = {}", field[8 * w + 8]);
+        // Far corner is outside by more than the spread.
+        assert_eq!(field[0], 0);
+    }
+
+    #[test]
+    fn test_distance_field_when_crossing_edge_then_values_straddle_midpoint() {
+        let coverage = vec![255u8; 8 * 8];
+        let field = coverage_to_distance_field(&coverage, 8, 8, 4);
+        let w = 16;
+
+        // Row through the middle: last outside pixel vs first inside pixel.
+        let outside = field[8 * w + 3];
+        let inside = field[8 * w + 4];
+        assert!(outside < 128, "outside = {}", outside);
+        assert!(inside >= 128, "inside = {}", inside);
+    }
+
+    #[test]
+    fn test_distance_field_when_empty_mask_then_all_zero() {
+        let field = coverage_to_distance_field(&[0u8; 16], 4, 4, 2);
+        assert!(field.iter().all(|&v| v == 0));
+    }
+
+    #[test]
+    fn test_blit_distance_field_when_overlapping_then_takes_max() {
+        let mut canvas = vec![100u8; 4];
+        blit_distance_field(&mut canvas, 2, 2, &[50, 200, 150, 10], 2, 2, 0, 0);
+        assert_eq!(canvas, vec![100, 200, 150, 100]);
+    }
+
+    #[test]
+    fn test_blit_distance_field_when_offset_then_clips_to_canvas() {
+        let mut canvas = vec![0u8; 4];
+        blit_distance_field(&mut canvas, 2, 2, &[1, 2, 3, 4], 2, 2, 1, 1);
+        assert_eq!(canvas, vec![0, 0, 0, 1]);
+    }
+}
@@ -0,0 +1,36 @@
+//! Just enough of the sfnt container to find a table.
+//!
+//! Core has no font parser of its own; the few places that need to know
+//! whether a font carries a table or feature read the bytes directly.
+
+/// Big-endian `u16` at `at`
+pub(crate) fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    data.get(at..at.checked_add(2)?)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+/// Big-endian `u32` at `at`
+pub(crate) fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    data.get(at..at.checked_add(4)?)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Offset of table `tag` in face `face_index` of `data`, a font or collection
+pub(crate) fn table_offset(data: &[u8], face_index: u32, tag: &[u8; 4]) -> Option<usize> {
+    let directory = if data.starts_with(b"ttcf") {
+        if face_index >= u32_at(data, 8)? {
+            return None;
+        }
+        u32_at(data, 12 + 4 * face_index as usize)? as usize
+    } else if face_index == 0 {
+        0
+    } else {
+        return None;
+    };
+    let tables = u16_at(data, directory + 4)? as usize;
+    (0..tables)
+        .map(|i| directory + 12 + 16 * i)
+        .find(|&record| data.get(record..record + 4) == Some(tag))
+        .and_then(|record| u32_at(data, record + 8))
+        .map(|offset| offset as usize)
+}
@@ -11,8 +11,9 @@
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
-use crate::cache::MultiLevelCache;
+use crate::cache::{InvalidateFont, MultiLevelCache, TrimIdle};
 use crate::cache_config;
 use crate::types::ShapingResult;
 
@@ -29,6 +30,8 @@ pub struct ShapingCacheKey {
     pub backend: String,
     /// Font identifier (hash of font data)
     pub font_id: u64,
+    /// Face within the font data, for TTC/OTC collections
+    pub face_index: u32,
     /// Font size in points (stored as u32: size * 100 for hash stability)
     pub size: u32,
     /// Language code (e.g., "en", "ar", "zh")
@@ -75,6 +78,7 @@ impl ShapingCacheKey {
             text: text.into(),
             backend: backend.into(),
             font_id,
+            face_index: 0,
             size: (size * 100.0) as u32, // Store as integer for stability
             language,
             script,
@@ -82,6 +86,12 @@ impl ShapingCacheKey {
             variations: variations_int,
         }
     }
+
+    /// Key shaping of face `face_index` of a collection
+    pub fn with_face_index(mut self, face_index: u32) -> Self {
+        self.face_index = face_index;
+        self
+    }
 }
 
 /// Cache for shaping results
@@ -124,6 +134,13 @@ impl ShapingCache {
         }
     }
 
+    /// Create a shaping cache whose entries expire `ttl` after insertion.
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            cache: MultiLevelCache::with_ttl(100, 500, ttl),
+        }
+    }
+
     /// Get a cached shaping result
     ///
     /// Returns `Some(result)` if the key exists in the Moka TinyLFU cache,
@@ -152,6 +169,11 @@ impl ShapingCache {
         self.cache.hit_rate()
     }
 
+    /// Raw lookup counters and timings
+    pub fn metrics(&self) -> crate::cache::CacheMetrics {
+        self.cache.metrics()
+    }
+
     /// Get cache statistics
     pub fn stats(&self) -> CacheStats {
         let metrics = self.cache.metrics();
@@ -169,6 +191,33 @@ impl ShapingCache {
     }
 }
 
+impl TrimIdle for ShapingCache {
+    fn trim_idle(&self, max_age: Duration) -> usize {
+        self.cache.trim_idle(max_age)
+    }
+}
+
+impl InvalidateFont for ShapingCache {
+    fn invalidate_font(&self, font_data: &[u8]) -> usize {
+        let font_id = crate::render_cache::font_id(font_data);
+        self.cache.invalidate_where(|key| key.font_id == font_id)
+    }
+}
+
+impl InvalidateFont for RwLock<ShapingCache> {
+    fn invalidate_font(&self, font_data: &[u8]) -> usize {
+        self.read()
+            .map_or(0, |cache| cache.invalidate_font(font_data))
+    }
+}
+
+/// Lets shapers register their [`SharedShapingCache`] as a trim hook.
+impl TrimIdle for RwLock<ShapingCache> {
+    fn trim_idle(&self, max_age: Duration) -> usize {
+        self.read().map_or(0, |cache| cache.trim_idle(max_age))
+    }
+}
+
 impl Default for ShapingCache {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,162 @@
+//! Which glyph source actually drew each glyph.
+//!
+//! [`GlyphSourcePreference`](crate::GlyphSourcePreference) says which tables a
+//! renderer may use and in what order, but a glyph missing from the COLR table
+//! falls through to SVG, a bitmap strike, or its outline without a trace. Put
+//! a [`GlyphSourceLog`] in [`RenderParams::glyph_source_log`](crate::RenderParams::glyph_source_log)
+//! and the opixa, skia, zeno, and SVG renderers record one
+//! [`GlyphSourceRecord`] per glyph they draw, so a test can assert the
+//! fallback chain went the way it was configured.
+//!
+//! ```
+//! use typf_core::source_log::GlyphSourceLog;
+//! use typf_core::{GlyphSource, RenderParams};
+//!
+//! let log = GlyphSourceLog::new();
+//! let params = RenderParams {
+//!     glyph_source_log: Some(log.clone()),
+//!     ..RenderParams::default()
+//! };
+//! // renderer.render(&shaped, font, &params)?;
+//! # log.record(0, 36, GlyphSource::Glyf);
+//! assert!(log.records().iter().all(|r| r.source == GlyphSource::Glyf));
+//! ```
+//!
+//! Glyphs that draw nothing (spaces, glyphs skipped after an error) and
+//! renders answered from a cache leave no record.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::sfnt;
+use crate::types::GlyphId;
+use crate::GlyphSource;
+
+/// One drawn glyph and the table it came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlyphSourceRecord {
+    /// Position of the glyph in [`ShapingResult::glyphs`](crate::types::ShapingResult::glyphs)
+    pub index: usize,
+    /// The glyph drawn
+    pub glyph_id: GlyphId,
+    /// The table its image or outline came from
+    pub source: GlyphSource,
+}
+
+/// A shared list of [`GlyphSourceRecord`]s filled in by renderers
+///
+/// Clones share state, so keep one clone and hand another to the renderer.
+/// Records accumulate across calls until [`take`](Self::take) or
+/// [`clear`](Self::clear).
+#[derive(Debug, Clone, Default)]
+pub struct GlyphSourceLog {
+    records: Arc<Mutex<Vec<GlyphSourceRecord>>>,
+}
+
+impl GlyphSourceLog {
+    /// An empty log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note that glyph `index` of the run, `glyph_id`, was drawn from `source`
+    pub fn record(&self, index: usize, glyph_id: GlyphId, source: GlyphSource) {
+        self.records.lock().push(GlyphSourceRecord {
+            index,
+            glyph_id,
+            source,
+        });
+    }
+
+    /// Everything recorded so far, in drawing order
+    pub fn records(&self) -> Vec<GlyphSourceRecord> {
+        self.records.lock().clone()
+    }
+
+    /// Everything recorded so far, leaving the log empty
+    pub fn take(&self) -> Vec<GlyphSourceRecord> {
+        std::mem::take(&mut *self.records.lock())
+    }
+
+    /// Forget every record
+    pub fn clear(&self) {
+        self.records.lock().clear();
+    }
+}
+
+/// The outline table glyphs of face `face_index` of `font_data` are drawn from
+///
+/// Checks `glyf`, then `CFF2`, then `CFF`, the order outline readers try
+/// them in. Fonts with none of them report [`GlyphSource::Glyf`].
+pub fn outline_source(font_data: &[u8], face_index: u32) -> GlyphSource {
+    [
+        (b"glyf", GlyphSource::Glyf),
+        (b"CFF2", GlyphSource::Cff2),
+        (b"CFF ", GlyphSource::Cff),
+    ]
+    .into_iter()
+    .find(|(tag, _)| sfnt::table_offset(font_data, face_index, tag).is_some())
+    .map_or(GlyphSource::Glyf, |(_, source)| source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A table directory naming only `tag`
+    fn font_with_table(tag: &[u8; 4]) -> Vec<u8> {
+        let mut font = Vec::new();
+        font.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        font.extend_from_slice(&1u16.to_be_bytes());
+        font.extend_from_slice(&[0; 6]);
+        font.extend_from_slice(tag);
+        font.extend_from_slice(&[0; 4]);
+        font.extend_from_slice(&28u32.to_be_bytes());
+        font.extend_from_slice(&0u32.to_be_bytes());
+        font
+    }
+
+    #[test]
+    fn test_log_when_cloned_then_records_shared() {
+        let log = GlyphSourceLog::new();
+        let renderer_copy = log.clone();
+        renderer_copy.record(0, 5, GlyphSource::Colr1);
+        renderer_copy.record(1, 9, GlyphSource::Glyf);
+
+        assert_eq!(
+            log.records(),
+            vec![
+                GlyphSourceRecord {
+                    index: 0,
+                    glyph_id: 5,
+                    source: GlyphSource::Colr1
+                },
+                GlyphSourceRecord {
+                    index: 1,
+                    glyph_id: 9,
+                    source: GlyphSource::Glyf
+                },
+            ]
+        );
+        assert_eq!(log.take().len(), 2);
+        assert!(renderer_copy.records().is_empty());
+    }
+
+    #[test]
+    fn test_outline_source_follows_tables() {
+        assert_eq!(
+            outline_source(&font_with_table(b"glyf"), 0),
+            GlyphSource::Glyf
+        );
+        assert_eq!(
+            outline_source(&font_with_table(b"CFF2"), 0),
+            GlyphSource::Cff2
+        );
+        assert_eq!(
+            outline_source(&font_with_table(b"CFF "), 0),
+            GlyphSource::Cff
+        );
+        assert_eq!(outline_source(&[], 0), GlyphSource::Glyf);
+    }
+}
@@ -0,0 +1,211 @@
+//! Where a rendering call spent its time, and what it did.
+//!
+//! Two pieces, usable separately:
+//!
+//! - [`span`] times a closure and reports it under the [`SPAN_TARGET`]
+//!   target. By default that is two `log` records: `> shape` when a stage
+//!   starts, `< shape 1.2ms` when it ends; enable them with
+//!   `RUST_LOG=typf::span=trace` (or your logger's equivalent) to see the
+//!   nesting of segment, shape, rasterize, and composite without attaching
+//!   a profiler. With the `tracing` feature each stage is a trace-level
+//!   `tracing` span instead, so subscribers nest and time it themselves.
+//! - [`RenderStats`] is the same information as numbers, returned next to
+//!   the output by [`Pipeline::render_with_stats`](crate::Pipeline::render_with_stats)
+//!   and `TypfPipeline::render_text_with_stats`.
+//!
+//! Both are cheap: a span costs two `Instant::now` calls and a disabled log
+//! check, so they stay on in release builds.
+
+use std::fmt;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::cache::CacheMetrics;
+
+/// The target stage spans, or their `log` enter/exit records, use
+pub const SPAN_TARGET: &str = "typf::span";
+
+/// Run `f` as the stage called `name`, returning its result and duration
+pub fn span<T>(name: &str, f: impl FnOnce() -> T) -> (T, Duration) {
+    #[cfg(feature = "tracing")]
+    let _entered =
+        tracing::span!(target: SPAN_TARGET, tracing::Level::TRACE, "stage", name).entered();
+    #[cfg(not(feature = "tracing"))]
+    log::trace!(target: SPAN_TARGET, "> {name}");
+    let start = Instant::now();
+    let value = f();
+    let elapsed = start.elapsed();
+    #[cfg(not(feature = "tracing"))]
+    log::trace!(target: SPAN_TARGET, "< {name} {elapsed:?}");
+    (value, elapsed)
+}
+
+/// Time spent in each pipeline stage
+///
+/// Stages a call does not have stay zero: [`Pipeline`](crate::Pipeline)
+/// neither segments nor composites, and only `process` exports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StageTimings {
+    /// Splitting text into script, direction, and font runs
+    pub segment: Duration,
+    /// All shaper calls
+    pub shape: Duration,
+    /// All renderer calls
+    pub rasterize: Duration,
+    /// Stacking rendered layers into one bitmap
+    pub composite: Duration,
+    /// Encoding the output
+    pub export: Duration,
+}
+
+impl StageTimings {
+    /// Sum of every stage
+    pub fn total(&self) -> Duration {
+        self.segment + self.shape + self.rasterize + self.composite + self.export
+    }
+}
+
+/// One shaped run: a stretch of text in one direction and one font
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunStats {
+    /// Byte range of the run in the input text
+    pub range: std::ops::Range<usize>,
+    /// Index of the font the run was shaped with: 0 is the main font
+    pub font: usize,
+    /// Glyphs the shaper produced
+    pub glyphs: usize,
+    /// Time in the shaper
+    pub shape: Duration,
+}
+
+/// Lookups answered by a cache during one call
+///
+/// Counted from the cache's own metrics before and after the call, so a
+/// cache shared with other threads can include their lookups too.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheCounts {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheCounts {
+    /// Lookups between two snapshots of one cache's metrics
+    pub fn between(before: &CacheMetrics, after: &CacheMetrics) -> Self {
+        let hits = |m: &CacheMetrics| m.l1_hits + m.l2_hits;
+        Self {
+            hits: hits(after).saturating_sub(hits(before)),
+            misses: after.misses.saturating_sub(before.misses),
+        }
+    }
+}
+
+/// Counters and timings for one rendering call
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Every shaped run, in the order it was shaped
+    pub runs: Vec<RunStats>,
+    /// Glyphs handed to the renderer
+    pub glyphs_rendered: usize,
+    /// Shaping cache lookups; zero when the pipeline has no shaping cache
+    pub shaping_cache: CacheCounts,
+    /// Render cache lookups; zero when the pipeline has no glyph cache
+    pub render_cache: CacheCounts,
+    /// Bytes of output buffers the call allocated: rendered layers,
+    /// the composited bitmap, and exported bytes
+    pub bytes_allocated: usize,
+    pub timings: StageTimings,
+}
+
+impl RenderStats {
+    /// Renderer time divided by the glyphs rendered; zero when none were
+    ///
+    /// A mean over the whole call, not a per-glyph measurement: canvas setup
+    /// and compositing are spread over every glyph.
+    pub fn mean_rasterize_per_glyph(&self) -> Duration {
+        match u32::try_from(self.glyphs_rendered) {
+            Ok(0) | Err(_) => Duration::ZERO,
+            Ok(glyphs) => self.timings.rasterize / glyphs,
+        }
+    }
+}
+
+impl fmt::Display for RenderStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = &self.timings;
+        writeln!(
+            f,
+            "{} glyphs in {} runs, {} bytes allocated",
+            self.glyphs_rendered,
+            self.runs.len(),
+            self.bytes_allocated
+        )?;
+        writeln!(
+            f,
+            "segment {:?}, shape {:?}, rasterize {:?} (mean {:?}/glyph), composite {:?}, export {:?}",
+            t.segment,
+            t.shape,
+            t.rasterize,
+            self.mean_rasterize_per_glyph(),
+            t.composite,
+            t.export
+        )?;
+        write!(
+            f,
+            "shaping cache {}/{} hits, render cache {}/{} hits",
+            self.shaping_cache.hits,
+            self.shaping_cache.hits + self.shaping_cache.misses,
+            self.render_cache.hits,
+            self.render_cache.hits + self.render_cache.misses
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span_when_run_then_value_and_duration_returned() {
+        let (value, elapsed) = span("work", || {
+            std::thread::sleep(Duration::from_millis(2));
+            7
+        });
+        assert_eq!(value, 7);
+        assert!(elapsed >= Duration::from_millis(2));
+    }
+
+    #[test]
+    fn test_cache_counts_between_when_metrics_grow_then_delta() {
+        let before = CacheMetrics {
+            l1_hits: 3,
+            misses: 1,
+            ..CacheMetrics::default()
+        };
+        let after = CacheMetrics {
+            l1_hits: 5,
+            l2_hits: 1,
+            misses: 2,
+            ..CacheMetrics::default()
+        };
+        assert_eq!(
+            CacheCounts::between(&before, &after),
+            CacheCounts { hits: 3, misses: 1 }
+        );
+    }
+
+    #[test]
+    fn test_mean_rasterize_per_glyph_when_no_glyphs_then_zero() {
+        let mut stats = RenderStats::default();
+        stats.timings.rasterize = Duration::from_millis(10);
+        assert_eq!(stats.mean_rasterize_per_glyph(), Duration::ZERO);
+
+        stats.glyphs_rendered = 4;
+        assert_eq!(
+            stats.mean_rasterize_per_glyph(),
+            Duration::from_micros(2500)
+        );
+    }
+}
@@ -45,6 +45,7 @@
 
 // this_file: crates/typf-core/src/traits.rs
 
+use crate::render_target::{self, DirtyRect, RenderTarget};
 use crate::{error::Result, types::*, PipelineContext, RenderParams, ShapingParams};
 use std::sync::Arc;
 
@@ -136,6 +137,15 @@ pub trait FontRef: Send + Sync {
         None
     }
 
+    /// Which face of [`data`](Self::data) this font is, for TTC/OTC collections.
+    ///
+    /// Backends pass it along with the bytes when they parse the font
+    /// (`FontRef::from_index`, `Face::from_bytes`, ...), so a collection
+    /// member is not silently replaced by face 0. Default returns 0.
+    fn face_index(&self) -> u32 {
+        0
+    }
+
     /// The font's internal coordinate scale (units per em).
     ///
     /// Divide any font-unit measurement by this value to get a fraction of one
@@ -152,6 +162,28 @@ pub trait FontRef: Send + Sync {
         None
     }
 
+    /// Font-wide metrics at the variation instance `variations`, the
+    /// `(tag, value)` pairs of [`ShapingParams::variations`](crate::ShapingParams::variations).
+    ///
+    /// Variable fonts move their ascender, descender, x-height, and the like
+    /// through the `MVAR` table. Implementations that read it SHOULD override
+    /// this; the default ignores `variations` and returns [`metrics`](Self::metrics).
+    fn metrics_at(&self, variations: &[(String, f32)]) -> Option<FontMetrics> {
+        let _ = variations;
+        self.metrics()
+    }
+
+    /// Baseline positions for text in `script`, in font units.
+    ///
+    /// The `BASE` table can place baselines differently per script; pass an
+    /// ISO 15924 or OpenType script tag to get that script's values, or
+    /// `None` for the font-wide ones in [`FontMetrics::baselines`]. The
+    /// default implementation ignores `script`.
+    fn baselines(&self, script: Option<&str>) -> Option<Baselines> {
+        let _ = script;
+        self.metrics().map(|m| m.baselines)
+    }
+
     /// Map a Unicode character to the font-specific glyph ID that represents it.
     ///
     /// This is a cmap lookup. Returns `None` when the font does not contain
@@ -175,6 +207,29 @@ pub trait FontRef: Send + Sync {
         None
     }
 
+    /// Ligature caret positions of `glyph_id`, in font units from its origin.
+    ///
+    /// These come from the GDEF `LigCaretList` and mark where the caret goes
+    /// between the characters a ligature replaced (one value fewer than the
+    /// number of components). [`hit_test`][crate::hit_test] uses them instead
+    /// of splitting the ligature evenly. Returns an empty list when the glyph
+    /// has no carets or the implementation does not read GDEF.
+    fn ligature_carets(&self, _glyph_id: GlyphId) -> Vec<f32> {
+        Vec::new()
+    }
+
+    /// Legacy pair kerning between `left` and `right`, in the units of
+    /// [`advance_width`][Self::advance_width].
+    ///
+    /// This is the old `kern` table, which some TrueType fonts carry instead
+    /// of GPOS kerning. Shapers that run GPOS (or apply `kern` themselves, as
+    /// HarfBuzz does) ignore it; simpler paths add it to the left glyph's
+    /// advance. Returns 0.0 when the pair has no entry or the implementation
+    /// does not read `kern`.
+    fn kerning(&self, _left: GlyphId, _right: GlyphId) -> f32 {
+        0.0
+    }
+
     /// Variable font axes from the `fvar` table.
     ///
     /// A variable font encodes a continuous design space along named axes such
@@ -220,6 +275,7 @@ pub trait FontRef: Send + Sync {
 /// | `typf-shape-none` | `"none"` | Simple LTR passthrough — no OpenType features |
 /// | `typf-shape-hb` | `"harfbuzz"` | HarfBuzz C library via FFI — industry standard |
 /// | `typf-shape-hr` | `"harfrust"` | Pure Rust HarfBuzz port — zero C deps |
+/// | `typf-shape-rustybuzz` | `"rustybuzz"` | Pure Rust, `ttf-parser` based — wasm and musl friendly |
 /// | `typf-shape-icu-hb` | `"icu-hb"` | ICU normalization + HarfBuzz — full Unicode pipeline |
 /// | `typf-shape-ct` | `"coretext"` | macOS CoreText — native Apple typography |
 pub trait Shaper: Send + Sync {
@@ -239,6 +295,22 @@ pub trait Shaper: Send + Sync {
         params: &ShapingParams,
     ) -> Result<ShapingResult>;
 
+    /// Shape every run of a paragraph with `font`, results in run order.
+    ///
+    /// Runs are independent, so shapers may spread them across threads;
+    /// the HarfBuzz shaper does with its `parallel` feature. The default
+    /// shapes them one after another. The first failing run, in order,
+    /// fails the call.
+    fn shape_all(
+        &self,
+        runs: &[(&str, ShapingParams)],
+        font: Arc<dyn FontRef>,
+    ) -> Result<Vec<ShapingResult>> {
+        runs.iter()
+            .map(|(text, params)| self.shape(text, font.clone(), params))
+            .collect()
+    }
+
     /// Returns `true` if this shaper can correctly handle the named script.
     ///
     /// Script names follow ISO 15924 four-letter codes: `"Arab"`, `"Deva"`,
@@ -250,6 +322,13 @@ pub trait Shaper: Send + Sync {
 
     /// Flush any internally cached shaping results.
     fn clear_cache(&self) {}
+
+    /// Drop cached shaping results not used within `max_age`.
+    ///
+    /// Returns how many entries were evicted; shapers without a cache evict none.
+    fn trim_idle(&self, _max_age: std::time::Duration) -> usize {
+        0
+    }
 }
 
 /// Step 2 of the pipeline: draw positioned glyphs into pixels or vector paths.
@@ -298,8 +377,60 @@ pub trait Renderer: Send + Sync {
         false
     }
 
+    /// Draw `shaped` into a caller-owned buffer instead of a new canvas.
+    ///
+    /// `origin` is where the top-left corner of the bitmap [`render`](Self::render)
+    /// would return lands on `target`, so both methods place ink identically.
+    /// Pixels outside the target are clipped. Returns the region that changed,
+    /// which is also reported through
+    /// [`RenderTarget::mark_dirty`].
+    ///
+    /// The default renders a bitmap and composites it; renderers that can
+    /// blend glyphs straight into the target override this to skip that
+    /// allocation and copy.
+    fn render_into(
+        &self,
+        shaped: &ShapingResult,
+        font: Arc<dyn FontRef>,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+    ) -> Result<Option<DirtyRect>> {
+        render_target::render_via_bitmap(self, shaped, font, params, target, origin)
+    }
+
+    /// Redraw only the part of `shaped` that falls inside `clip`.
+    ///
+    /// `origin` means the same as in [`render_into`](Self::render_into), and
+    /// `clip` is in target pixels. Pixels outside `clip` are left alone, so a
+    /// caller can repaint a few changed cells of a line it drew earlier.
+    /// Returns the region that changed, which never extends past `clip`.
+    ///
+    /// The default clips [`render_into`](Self::render_into); renderers that
+    /// rasterize per glyph override this to skip glyphs that cannot reach
+    /// `clip`. Skipped glyphs still stretch the line by their outline
+    /// bounds, so the baseline sits where a full render puts it.
+    fn render_region(
+        &self,
+        shaped: &ShapingResult,
+        font: Arc<dyn FontRef>,
+        params: &RenderParams,
+        target: &mut dyn RenderTarget,
+        origin: (i32, i32),
+        clip: DirtyRect,
+    ) -> Result<Option<DirtyRect>> {
+        render_target::render_clipped(self, shaped, font, params, target, origin, clip)
+    }
+
     /// Free up any internally cached glyph bitmaps or path data.
     fn clear_cache(&self) {}
+
+    /// Drop cached glyph data not used within `max_age`.
+    ///
+    /// Returns how many entries were evicted; renderers without a cache evict none.
+    fn trim_idle(&self, _max_age: std::time::Duration) -> usize {
+        0
+    }
 }
 
 /// Step 3 of the pipeline: encode rendered output as bytes ready to write to disk.
@@ -0,0 +1,179 @@
+//! Linear transforms applied to a whole run of text.
+//!
+//! A [`TextTransform`] rotates, shears, or scales every glyph outline and
+//! every pen position of a run, so renderers rasterize rotated or slanted
+//! text directly instead of resampling a finished bitmap. The matrix works in
+//! y-up text space around the run's origin on the baseline. Translation is
+//! left out on purpose: renderers fit the canvas to the transformed ink, so a
+//! constant offset would not change the output.
+
+use crate::error::{Result, TypfError};
+
+/// 2x2 matrix mapping `(x, y)` to `(xx * x + xy * y, yx * x + yy * y)`.
+///
+/// Coordinates are y-up, so positive rotation turns text counterclockwise on
+/// screen.
+///
+/// ```
+/// use typf_core::TextTransform;
+///
+/// let t = TextTransform::rotate(90.0);
+/// let (x, y) = t.apply(10.0, 0.0);
+/// assert!(x.abs() < 1e-4 && (y - 10.0).abs() < 1e-4);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextTransform {
+    /// Contribution of x to x.
+    pub xx: f32,
+    /// Contribution of x to y.
+    pub yx: f32,
+    /// Contribution of y to x.
+    pub xy: f32,
+    /// Contribution of y to y.
+    pub yy: f32,
+}
+
+impl Default for TextTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl TextTransform {
+    /// Leaves everything in place.
+    pub const IDENTITY: Self = Self {
+        xx: 1.0,
+        yx: 0.0,
+        xy: 0.0,
+        yy: 1.0,
+    };
+
+    /// Counterclockwise rotation by `degrees`.
+    pub fn rotate(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self {
+            xx: cos,
+            yx: sin,
+            xy: -sin,
+            yy: cos,
+        }
+    }
+
+    /// Shear by the given angles. Positive `x_degrees` leans glyphs to the
+    /// right like an oblique; `y_degrees` tilts the baseline upward.
+    pub fn skew(x_degrees: f32, y_degrees: f32) -> Self {
+        Self {
+            xx: 1.0,
+            yx: y_degrees.to_radians().tan(),
+            xy: x_degrees.to_radians().tan(),
+            yy: 1.0,
+        }
+    }
+
+    /// Independent horizontal and vertical scale.
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Self {
+            xx: sx,
+            yx: 0.0,
+            xy: 0.0,
+            yy: sy,
+        }
+    }
+
+    /// `self` followed by `next`.
+    pub fn then(self, next: Self) -> Self {
+        Self {
+            xx: next.xx * self.xx + next.xy * self.yx,
+            yx: next.yx * self.xx + next.yy * self.yx,
+            xy: next.xx * self.xy + next.xy * self.yy,
+            yy: next.yx * self.xy + next.yy * self.yy,
+        }
+    }
+
+    /// Map one point.
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.xx * x + self.xy * y, self.yx * x + self.yy * y)
+    }
+
+    /// `true` when applying the transform changes nothing.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    /// Axis-aligned bounds `(min_x, min_y, max_x, max_y)` of the rectangle
+    /// `x0..x1` by `y0..y1` after transforming it.
+    pub fn bounds(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> (f32, f32, f32, f32) {
+        let corners = [
+            self.apply(x0, y0),
+            self.apply(x1, y0),
+            self.apply(x0, y1),
+            self.apply(x1, y1),
+        ];
+        corners.iter().fold(
+            (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            |(min_x, min_y, max_x, max_y), &(x, y)| {
+                (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+            },
+        )
+    }
+
+    /// Errors for non-finite entries or a matrix that collapses text to a line.
+    pub fn validate(&self) -> Result<()> {
+        let entries = [self.xx, self.yx, self.xy, self.yy];
+        if entries.iter().any(|v| !v.is_finite()) {
+            return Err(TypfError::ConfigError(format!(
+                "text transform has non-finite entries: {self:?}"
+            )));
+        }
+        let det = self.xx * self.yy - self.xy * self.yx;
+        if det.abs() < 1e-6 {
+            return Err(TypfError::ConfigError(format!(
+                "text transform is degenerate (determinant {det}): {self:?}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: (f32, f32), b: (f32, f32)) -> bool {
+        (a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_then_when_two_quarter_turns_then_half_turn() {
+        let half = TextTransform::rotate(90.0).then(TextTransform::rotate(90.0));
+        assert!(close(half.apply(1.0, 2.0), (-1.0, -2.0)));
+    }
+
+    #[test]
+    fn test_then_applies_self_first() {
+        let t = TextTransform::scale(2.0, 1.0).then(TextTransform::rotate(90.0));
+        // Scale (1, 0) to (2, 0), then rotate to (0, 2).
+        assert!(close(t.apply(1.0, 0.0), (0.0, 2.0)));
+    }
+
+    #[test]
+    fn test_skew_when_positive_x_then_tops_lean_right() {
+        let t = TextTransform::skew(45.0, 0.0);
+        assert!(close(t.apply(0.0, 10.0), (10.0, 10.0)));
+        assert!(close(t.apply(5.0, 0.0), (5.0, 0.0)));
+    }
+
+    #[test]
+    fn test_bounds_when_rotated_then_covers_corners() {
+        let (x0, y0, x1, y1) = TextTransform::rotate(90.0).bounds(0.0, -2.0, 10.0, 8.0);
+        assert!(close((x0, y0), (-8.0, 0.0)));
+        assert!(close((x1, y1), (2.0, 10.0)));
+    }
+
+    #[test]
+    fn test_validate_when_degenerate_or_nan_then_errors() {
+        assert!(TextTransform::rotate(30.0).validate().is_ok());
+        assert!(TextTransform::scale(1.0, 0.0).validate().is_err());
+        assert!(TextTransform::scale(f32::NAN, 1.0).validate().is_err());
+    }
+}
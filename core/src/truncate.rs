@@ -0,0 +1,401 @@
+//! Fitting a line into a width with an ellipsis.
+//!
+//! [`truncate`] shapes the text, and if it is wider than
+//! [`Truncation::max_width`] drops characters from one end and puts an
+//! ellipsis (or any other string) in their place, reshaping until the result
+//! fits. The kept text always ends on a boundary that is both a glyph
+//! cluster boundary of the full shaping and an extended grapheme boundary,
+//! so a ligature, a base letter and its combining marks, or an emoji ZWJ
+//! sequence is either kept whole or dropped whole.
+//!
+//! Truncation works in logical order: [`TruncateAt::End`] drops the end of
+//! the text, which is the visual left of a right-to-left line. The ellipsis
+//! is shaped together with the kept text, in the same direction, so the
+//! shaper places it where the reading order puts it and contextual forms
+//! (Arabic joining, kerning) are recomputed at the cut.
+//!
+//! Finding the cut takes a binary search over the candidate boundaries, one
+//! shaping call per step.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use icu_properties::{props::GraphemeClusterBreak, CodePointMapData};
+
+use crate::error::Result;
+use crate::traits::{FontRef, Shaper};
+use crate::types::{Direction, ShapingResult};
+use crate::ShapingParams;
+
+/// Which end of the text gives way
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TruncateAt {
+    /// Keep the start, drop the end: "Long tit…"
+    #[default]
+    End,
+    /// Keep the end, drop the start: "…ng title"
+    Start,
+}
+
+/// How to truncate a line that does not fit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Truncation {
+    /// Widest the result may be, in pixels (height for vertical runs)
+    pub max_width: f32,
+    /// Which end to drop
+    pub at: TruncateAt,
+    /// Marks the cut. Default: "…"; when the font has no glyph for it,
+    /// "..." is used instead
+    pub ellipsis: String,
+}
+
+impl Truncation {
+    /// Truncate at the end with "…" once wider than `max_width`
+    pub fn new(max_width: f32) -> Self {
+        Self {
+            max_width,
+            at: TruncateAt::End,
+            ellipsis: "\u{2026}".to_string(),
+        }
+    }
+}
+
+/// A line after [`truncate`]
+#[derive(Debug, Clone)]
+pub struct Truncated {
+    /// What to show: the kept text plus the ellipsis, or the input as is
+    pub text: String,
+    /// `text`, shaped; clusters are byte offsets into `text`
+    pub shaped: ShapingResult,
+    /// The part of the input that `text` keeps
+    pub kept: Range<usize>,
+    /// Whether anything was dropped
+    pub truncated: bool,
+}
+
+/// Shape `text`, truncating it with an ellipsis if it is wider than
+/// `truncation.max_width`.
+///
+/// When not even the ellipsis alone fits, the result is the bare ellipsis,
+/// so the reader can still tell text was there.
+pub fn truncate(
+    shaper: &dyn Shaper,
+    text: &str,
+    font: &Arc<dyn FontRef>,
+    params: &ShapingParams,
+    truncation: &Truncation,
+) -> Result<Truncated> {
+    let extent = |shaped: &ShapingResult| match shaped.direction {
+        Direction::TopToBottom | Direction::BottomToTop => shaped.advance_height,
+        Direction::LeftToRight | Direction::RightToLeft => shaped.advance_width,
+    };
+
+    let full = shaper.shape(text, font.clone(), params)?;
+    if extent(&full) <= truncation.max_width {
+        return Ok(Truncated {
+            text: text.to_string(),
+            shaped: full,
+            kept: 0..text.len(),
+            truncated: false,
+        });
+    }
+
+    let ellipsis = if truncation.ellipsis == "\u{2026}" && font.glyph_id('\u{2026}').is_none() {
+        "..."
+    } else {
+        truncation.ellipsis.as_str()
+    };
+
+    let mut cuts: Vec<usize> = full
+        .glyphs
+        .iter()
+        .map(|g| g.cluster as usize)
+        .chain([0, text.len()])
+        .filter(|&offset| is_cut_point(text, offset))
+        .collect();
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    // Kept range for a cut, trimmed so no space sits next to the ellipsis
+    let kept = |cut: usize| -> Range<usize> {
+        match truncation.at {
+            TruncateAt::End => 0..text[..cut].trim_end().len(),
+            TruncateAt::Start => {
+                let rest = &text[cut..];
+                text.len() - rest.trim_start().len()..text.len()
+            },
+        }
+    };
+    let attempt = |cut: usize| -> Result<Truncated> {
+        let kept = kept(cut);
+        let shown = match truncation.at {
+            TruncateAt::End => format!("{}{ellipsis}", &text[kept.clone()]),
+            TruncateAt::Start => format!("{ellipsis}{}", &text[kept.clone()]),
+        };
+        let shaped = shaper.shape(&shown, font.clone(), params)?;
+        Ok(Truncated {
+            text: shown,
+            shaped,
+            kept,
+            truncated: true,
+        })
+    };
+
+    // Order the cuts from keeping least to keeping most, then find the last
+    // that fits; fitting only gets harder as more is kept
+    if truncation.at == TruncateAt::Start {
+        cuts.reverse();
+    }
+    // The first cut keeps nothing and the last keeps everything, which is
+    // known not to fit
+    let (mut lo, mut hi) = (0, cuts.len().saturating_sub(1));
+    let mut best = attempt(cuts.first().copied().unwrap_or_default())?;
+    while lo + 1 < hi {
+        let mid = (lo + hi) / 2;
+        let candidate = attempt(cuts[mid])?;
+        if extent(&candidate.shaped) <= truncation.max_width {
+            best = candidate;
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Ok(best)
+}
+
+/// Whether the text may be cut at byte `offset` without splitting an
+/// extended grapheme cluster
+fn is_cut_point(text: &str, offset: usize) -> bool {
+    if offset == 0 || offset >= text.len() {
+        return offset <= text.len();
+    }
+    if !text.is_char_boundary(offset) {
+        return false;
+    }
+    let gcb = CodePointMapData::<GraphemeClusterBreak>::new();
+    let before = text[..offset].chars().next_back().map(|c| gcb.get(c));
+    let after = text[offset..].chars().next().map(|c| gcb.get(c));
+
+    // Marks and joiners attach to what precedes them; prepended marks and
+    // joiners attach to what follows
+    if matches!(
+        after,
+        Some(
+            GraphemeClusterBreak::Extend
+                | GraphemeClusterBreak::ZWJ
+                | GraphemeClusterBreak::SpacingMark
+        )
+    ) || matches!(
+        before,
+        Some(GraphemeClusterBreak::Prepend | GraphemeClusterBreak::ZWJ)
+    ) {
+        return false;
+    }
+    if matches!(before, Some(GraphemeClusterBreak::CR))
+        && matches!(after, Some(GraphemeClusterBreak::LF))
+    {
+        return false;
+    }
+
+    // Regional indicators pair up into flags; only cut between pairs
+    if before == Some(GraphemeClusterBreak::RegionalIndicator)
+        && after == Some(GraphemeClusterBreak::RegionalIndicator)
+    {
+        let run = text[..offset]
+            .chars()
+            .rev()
+            .take_while(|&c| gcb.get(c) == GraphemeClusterBreak::RegionalIndicator)
+            .count();
+        return run % 2 == 0;
+    }
+    true
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::types::{GlyphId, PositionedGlyph};
+
+    /// Every character maps except U+2026, so "…" falls back to "..."
+    struct TestFont {
+        has_ellipsis: bool,
+    }
+
+    impl FontRef for TestFont {
+        fn data(&self) -> &[u8] {
+            &[]
+        }
+
+        fn units_per_em(&self) -> u16 {
+            1000
+        }
+
+        fn glyph_id(&self, ch: char) -> Option<GlyphId> {
+            (ch != '\u{2026}' || self.has_ellipsis).then_some(ch as u32)
+        }
+
+        fn advance_width(&self, _glyph_id: GlyphId) -> f32 {
+            10.0
+        }
+    }
+
+    /// 10px per character, marks take no room, and "fi" forms one
+    /// 10px ligature glyph
+    struct TestShaper;
+
+    impl Shaper for TestShaper {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn shape(
+            &self,
+            text: &str,
+            _font: Arc<dyn FontRef>,
+            params: &ShapingParams,
+        ) -> Result<ShapingResult> {
+            let mut glyphs: Vec<PositionedGlyph> = Vec::new();
+            let mut x = 0.0;
+            let mut chars = text.char_indices().peekable();
+            while let Some((offset, ch)) = chars.next() {
+                if ch == 'f' && chars.peek().map(|&(_, c)| c) == Some('i') {
+                    chars.next();
+                }
+                let advance = if ('\u{0300}'..='\u{036F}').contains(&ch) {
+                    0.0
+                } else {
+                    10.0
+                };
+                glyphs.push(PositionedGlyph {
+                    id: ch as u32,
+                    x,
+                    y: 0.0,
+                    advance,
+                    cluster: offset as u32,
+                });
+                x += advance;
+            }
+            if params.direction == Direction::RightToLeft {
+                glyphs.reverse();
+            }
+            Ok(ShapingResult {
+                glyphs,
+                advance_width: x,
+                advance_height: params.size,
+                direction: params.direction,
+            })
+        }
+    }
+
+    fn run(text: &str, truncation: &Truncation, direction: Direction) -> Truncated {
+        let params = ShapingParams {
+            direction,
+            ..Default::default()
+        };
+        let font: Arc<dyn FontRef> = Arc::new(TestFont { has_ellipsis: true });
+        truncate(&TestShaper, text, &font, &params, truncation).expect("truncate")
+    }
+
+    #[test]
+    fn test_truncate_when_text_fits_then_unchanged() {
+        let out = run("hello", &Truncation::new(50.0), Direction::LeftToRight);
+        assert!(!out.truncated);
+        assert_eq!(out.text, "hello");
+        assert_eq!(out.kept, 0..5);
+    }
+
+    #[test]
+    fn test_truncate_when_too_wide_then_keeps_longest_prefix_that_fits() {
+        let out = run(
+            "hello world",
+            &Truncation::new(60.0),
+            Direction::LeftToRight,
+        );
+        assert!(out.truncated);
+        // "hello" + "…" is 60px; the space before the cut is trimmed
+        assert_eq!(out.text, "hello\u{2026}");
+        assert_eq!(out.kept, 0..5);
+        assert!(out.shaped.advance_width <= 60.0);
+    }
+
+    #[test]
+    fn test_truncate_when_at_start_then_keeps_suffix() {
+        let truncation = Truncation {
+            at: TruncateAt::Start,
+            ..Truncation::new(40.0)
+        };
+        let out = run("abcdefgh", &truncation, Direction::LeftToRight);
+        assert_eq!(out.text, "\u{2026}fgh");
+        assert_eq!(out.kept, 5..8);
+    }
+
+    #[test]
+    fn test_truncate_when_cut_falls_in_ligature_or_mark_then_moves_before_it() {
+        // "fi" is one glyph: cutting between f and i would split it
+        let out = run("xfiz", &Truncation::new(25.0), Direction::LeftToRight);
+        assert_eq!(out.text, "x\u{2026}");
+
+        // A combining acute stays with its e, or both go
+        let out = run(
+            "abe\u{0301}cd",
+            &Truncation::new(40.0),
+            Direction::LeftToRight,
+        );
+        assert_eq!(out.text, "abe\u{0301}\u{2026}");
+        let out = run(
+            "abe\u{0301}cd",
+            &Truncation::new(30.0),
+            Direction::LeftToRight,
+        );
+        assert_eq!(out.text, "ab\u{2026}");
+    }
+
+    #[test]
+    fn test_truncate_when_rtl_then_drops_logical_end() {
+        let out = run(
+            "\u{05D0}\u{05D1}\u{05D2}\u{05D3}",
+            &Truncation::new(30.0),
+            Direction::RightToLeft,
+        );
+        assert_eq!(out.text, "\u{05D0}\u{05D1}\u{2026}");
+        assert_eq!(out.shaped.direction, Direction::RightToLeft);
+    }
+
+    #[test]
+    fn test_truncate_when_nothing_fits_then_bare_ellipsis() {
+        let out = run("abcdef", &Truncation::new(5.0), Direction::LeftToRight);
+        assert!(out.truncated);
+        assert_eq!(out.text, "\u{2026}");
+        assert_eq!(out.kept, 0..0);
+    }
+
+    #[test]
+    fn test_truncate_when_font_lacks_ellipsis_then_three_dots() {
+        let font: Arc<dyn FontRef> = Arc::new(TestFont {
+            has_ellipsis: false,
+        });
+        let out = truncate(
+            &TestShaper,
+            "abcdefgh",
+            &font,
+            &ShapingParams::default(),
+            &Truncation::new(50.0),
+        );
+        assert!(matches!(out, Ok(out) if out.text == "ab..."));
+    }
+
+    #[test]
+    fn test_is_cut_point_when_inside_flag_or_zwj_sequence_then_false() {
+        // Two flags: cut between them, never inside one
+        let flags = "\u{1F1FA}\u{1F1F8}\u{1F1EC}\u{1F1E7}";
+        assert!(!is_cut_point(flags, 4));
+        assert!(is_cut_point(flags, 8));
+        assert!(!is_cut_point(flags, 12));
+        // Woman + ZWJ + laptop
+        let zwj = "\u{1F469}\u{200D}\u{1F4BB}";
+        assert!(!is_cut_point(zwj, 4));
+        assert!(!is_cut_point(zwj, 7));
+        assert!(!is_cut_point("\r\n", 1));
+    }
+}
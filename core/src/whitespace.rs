@@ -0,0 +1,346 @@
+//! Tabs and special-purpose spaces in shaped text.
+//!
+//! Fonts rarely map `\t`, so shapers hand back `.notdef` for it, and many
+//! fonts lack the typographic spaces too. [`expand_whitespace`] runs after
+//! shaping and fixes both:
+//!
+//! - a tab becomes a blank glyph that carries the pen to the next of the
+//!   run's [`TabStops`];
+//! - a fixed-width space the font does not map (no-break, thin, hair, em,
+//!   figure, ...) becomes the space glyph at the width Unicode suggests;
+//! - a zero-width character the font does not map (zero-width space, word
+//!   joiner, byte order mark, soft hyphen, ...) becomes an empty glyph that
+//!   takes no room.
+//!
+//! Glyphs that follow a widened tab slide along with it. Spaces the font does
+//! map are left alone, since the font's own widths are better than ours.
+
+use crate::traits::FontRef;
+use crate::types::{Direction, GlyphId, ShapingResult};
+use crate::ShapingParams;
+
+/// Where tabs in a run stop, measured from the run's logical start
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabStops {
+    /// Distance between the repeating default stops, in space advances.
+    /// Default: 8, like CSS `tab-size`.
+    pub interval: f32,
+    /// Explicit stops in pixels, used before the default ones
+    pub positions: Vec<f32>,
+}
+
+impl Default for TabStops {
+    fn default() -> Self {
+        Self::every(8.0)
+    }
+}
+
+impl TabStops {
+    /// Default stops every `spaces` space advances, and no explicit ones
+    pub fn every(spaces: f32) -> Self {
+        Self {
+            interval: spaces,
+            positions: Vec::new(),
+        }
+    }
+
+    /// Add an explicit stop `position` pixels from the run start
+    pub fn with_stop(mut self, position: f32) -> Self {
+        self.positions.push(position);
+        self
+    }
+
+    /// Where a tab with the pen at `pen` ends, given the run's space advance
+    ///
+    /// A stop closer than half a space is skipped, so a tab never collapses
+    /// to nothing. Explicit stops come first; past the last one the default
+    /// grid takes over.
+    pub fn next_stop(&self, pen: f32, space_advance: f32) -> f32 {
+        let min = pen + space_advance.max(0.0) * 0.5;
+        let explicit = self
+            .positions
+            .iter()
+            .copied()
+            .filter(|stop| stop.is_finite() && *stop > min)
+            .min_by(f32::total_cmp);
+        if let Some(stop) = explicit {
+            return stop;
+        }
+        let interval = self.interval * space_advance;
+        if !interval.is_finite() || interval <= 0.0 {
+            return pen + space_advance.max(0.0);
+        }
+        ((min / interval).floor() + 1.0) * interval
+    }
+}
+
+/// Width of a fixed-width space in ems, for spaces whose width Unicode suggests
+///
+/// `None` for characters that are not such spaces. No-break, figure, and
+/// punctuation spaces take their width from the font and are handled by
+/// [`expand_whitespace`] directly.
+pub fn space_width_em(ch: char) -> Option<f32> {
+    let em = match ch {
+        '\u{2000}' | '\u{2002}' => 1.0 / 2.0, // en quad, en space
+        '\u{2001}' | '\u{2003}' | '\u{3000}' => 1.0, // em quad, em space, ideographic space
+        '\u{2004}' => 1.0 / 3.0,              // three-per-em space
+        '\u{2005}' => 1.0 / 4.0,              // four-per-em space
+        '\u{2006}' => 1.0 / 6.0,              // six-per-em space
+        '\u{2009}' | '\u{202F}' => 1.0 / 5.0, // thin space, narrow no-break space
+        '\u{200A}' => 1.0 / 10.0,             // hair space
+        '\u{205F}' => 4.0 / 18.0,             // medium mathematical space
+        _ => return None,
+    };
+    Some(em)
+}
+
+/// Whether `ch` is an invisible character that should take no room
+pub fn is_zero_width(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{00AD}' // soft hyphen
+            | '\u{180E}' // Mongolian vowel separator
+            | '\u{200B}'..='\u{200F}' // zero-width space, ZWNJ, ZWJ, LRM, RLM
+            | '\u{202A}'..='\u{202E}' // bidi embeddings and overrides
+            | '\u{2060}'..='\u{2064}' // word joiner, invisible operators
+            | '\u{2066}'..='\u{2069}' // bidi isolates
+            | '\u{FEFF}' // byte order mark, zero-width no-break space
+    )
+}
+
+/// Give tabs, unmapped spaces, and unmapped zero-width characters their room
+///
+/// `text` must be the text `shaped` came from, with glyph clusters as byte
+/// offsets into it. See the [module docs](self) for what changes.
+pub fn expand_whitespace(
+    text: &str,
+    shaped: &mut ShapingResult,
+    font: &dyn FontRef,
+    params: &ShapingParams,
+) {
+    let char_at = |cluster: u32| text.get(cluster as usize..)?.chars().next();
+    let needs_work = shaped
+        .glyphs
+        .iter()
+        .any(|g| char_at(g.cluster).is_some_and(|ch| ch == '\t' || g.id == 0 && is_special(ch)));
+    if !needs_work {
+        return;
+    }
+
+    let size = params.size;
+    let em = |fraction: f32| fraction * size;
+    let space = font.glyph_id(' ');
+    let font_advance = |ch: char| {
+        let id = font.glyph_id(ch)?;
+        let upem = font.units_per_em();
+        (upem > 0).then(|| font.advance_width(id) * size / upem as f32)
+    };
+    // A shaped space tells us exactly what the shaper thinks a space is worth
+    let space_advance = shaped
+        .glyphs
+        .iter()
+        .find(|g| char_at(g.cluster) == Some(' '))
+        .map(|g| g.advance - params.letter_spacing)
+        .or_else(|| font_advance(' '))
+        .filter(|advance| advance.is_finite() && *advance > 0.0)
+        .unwrap_or(em(0.25));
+
+    // Walk in logical order so each tab measures from the run start
+    let count = shaped.glyphs.len();
+    let logical: Vec<usize> = match shaped.direction {
+        Direction::RightToLeft => (0..count).rev().collect(),
+        _ => (0..count).collect(),
+    };
+    let mut deltas = vec![0.0f32; count];
+    let mut pen = 0.0;
+    for index in logical {
+        let glyph = &mut shaped.glyphs[index];
+        let Some(ch) = char_at(glyph.cluster) else {
+            pen += glyph.advance;
+            continue;
+        };
+        let blank = |id: GlyphId| space.unwrap_or(id);
+        let advance = if ch == '\t' {
+            glyph.id = blank(glyph.id);
+            Some(params.tab_stops.next_stop(pen, space_advance) - pen)
+        } else if glyph.id != 0 {
+            None
+        } else if is_zero_width(ch) {
+            glyph.id = blank(glyph.id);
+            Some(0.0)
+        } else {
+            let width = match ch {
+                '\u{00A0}' => Some(space_advance),
+                '\u{2007}' => font_advance('0').or(Some(em(0.5))),
+                '\u{2008}' => font_advance('.').or(Some(em(0.25))),
+                _ => space_width_em(ch).map(em),
+            };
+            if width.is_some() {
+                glyph.id = blank(glyph.id);
+            }
+            width.map(|w| w + params.letter_spacing)
+        };
+        if let Some(advance) = advance {
+            deltas[index] = advance - glyph.advance;
+            glyph.advance = advance;
+        }
+        pen += glyph.advance;
+    }
+
+    // Everything right of a widened glyph moves right by its growth
+    let mut shift = 0.0;
+    for (glyph, delta) in shaped.glyphs.iter_mut().zip(deltas) {
+        glyph.x += shift;
+        shift += delta;
+    }
+    if matches!(
+        shaped.direction,
+        Direction::LeftToRight | Direction::RightToLeft
+    ) {
+        shaped.advance_width += shift;
+    }
+}
+
+/// Characters [`expand_whitespace`] replaces when the font leaves them unmapped
+fn is_special(ch: char) -> bool {
+    is_zero_width(ch)
+        || matches!(ch, '\u{00A0}' | '\u{2007}' | '\u{2008}')
+        || space_width_em(ch).is_some()
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::types::PositionedGlyph;
+
+    /// 1000 units per em; maps ASCII only, every glyph 500 units wide
+    struct AsciiFont;
+
+    impl FontRef for AsciiFont {
+        fn data(&self) -> &[u8] {
+            &[]
+        }
+
+        fn units_per_em(&self) -> u16 {
+            1000
+        }
+
+        fn glyph_id(&self, ch: char) -> Option<GlyphId> {
+            (ch.is_ascii_graphic() || ch == ' ').then_some(ch as GlyphId)
+        }
+
+        fn advance_width(&self, _glyph_id: GlyphId) -> f32 {
+            500.0
+        }
+    }
+
+    /// Shape like a shaper that knows nothing about tabs or special spaces
+    fn shape(text: &str, direction: Direction) -> ShapingResult {
+        let mut glyphs: Vec<PositionedGlyph> = text
+            .char_indices()
+            .map(|(cluster, ch)| PositionedGlyph {
+                id: AsciiFont.glyph_id(ch).unwrap_or(0),
+                x: 0.0,
+                y: 0.0,
+                advance: 10.0,
+                cluster: cluster as u32,
+            })
+            .collect();
+        if direction == Direction::RightToLeft {
+            glyphs.reverse();
+        }
+        let mut x = 0.0;
+        for glyph in &mut glyphs {
+            glyph.x = x;
+            x += glyph.advance;
+        }
+        ShapingResult {
+            glyphs,
+            advance_width: x,
+            advance_height: 0.0,
+            direction,
+        }
+    }
+
+    #[test]
+    fn test_next_stop_when_explicit_and_default_then_explicit_first() {
+        let stops = TabStops::every(4.0).with_stop(25.0);
+        assert_eq!(stops.next_stop(0.0, 10.0), 25.0);
+        // Too close to 25, so the default grid (every 40px) takes over
+        assert_eq!(stops.next_stop(22.0, 10.0), 40.0);
+        assert_eq!(stops.next_stop(40.0, 10.0), 80.0);
+        assert_eq!(TabStops::every(0.0).next_stop(7.0, 10.0), 17.0);
+    }
+
+    #[test]
+    fn test_expand_whitespace_when_tab_then_pen_reaches_stop() {
+        let params = ShapingParams {
+            size: 20.0,
+            tab_stops: TabStops::every(4.0),
+            ..ShapingParams::default()
+        };
+        let mut shaped = shape("a b\tc", Direction::LeftToRight);
+        expand_whitespace("a b\tc", &mut shaped, &AsciiFont, &params);
+
+        // The shaped space is 10px wide, so stops fall every 40px
+        let tab = &shaped.glyphs[3];
+        assert_eq!(tab.id, ' ' as GlyphId);
+        assert_eq!((tab.x, tab.advance), (30.0, 10.0));
+        let mut shaped = shape("ab\tc", Direction::LeftToRight);
+        expand_whitespace("ab\tc", &mut shaped, &AsciiFont, &params);
+        assert_eq!(shaped.glyphs[2].advance, 20.0);
+        assert_eq!(shaped.glyphs[3].x, 40.0);
+        assert_eq!(shaped.advance_width, 50.0);
+    }
+
+    #[test]
+    fn test_expand_whitespace_when_rtl_then_stops_measured_from_right() {
+        let params = ShapingParams {
+            size: 20.0,
+            tab_stops: TabStops::every(4.0),
+            ..ShapingParams::default()
+        };
+        let mut shaped = shape("ab\tc", Direction::RightToLeft);
+        expand_whitespace("ab\tc", &mut shaped, &AsciiFont, &params);
+
+        // Visual order is c, tab, b, a; the tab ends 40px from the right edge
+        let x: Vec<f32> = shaped.glyphs.iter().map(|g| g.x).collect();
+        assert_eq!(x, [0.0, 10.0, 30.0, 40.0]);
+        assert_eq!(shaped.advance_width, 50.0);
+        assert_eq!(shaped.advance_width - shaped.glyphs[1].x, 40.0);
+    }
+
+    #[test]
+    fn test_expand_whitespace_when_unmapped_spaces_then_sized_or_hidden() {
+        let text = "a\u{2009}b\u{200B}c\u{00A0}d";
+        let mut shaped = shape(text, Direction::LeftToRight);
+        let params = ShapingParams {
+            size: 20.0,
+            ..ShapingParams::default()
+        };
+        expand_whitespace(text, &mut shaped, &AsciiFont, &params);
+
+        let advances: Vec<f32> = shaped.glyphs.iter().map(|g| g.advance).collect();
+        // Thin space is a fifth of 20px; no space in the run, so the font's
+        // 500/1000 em space sets the no-break width
+        assert_eq!(advances, [10.0, 4.0, 10.0, 0.0, 10.0, 10.0, 10.0]);
+        assert!(shaped.glyphs.iter().all(|g| g.id != 0));
+        assert_eq!(shaped.glyphs[6].x, 44.0);
+        assert_eq!(shaped.advance_width, 54.0);
+    }
+
+    #[test]
+    fn test_expand_whitespace_when_plain_text_then_untouched() {
+        let mut shaped = shape("plain text", Direction::LeftToRight);
+        let before = shaped.clone();
+        expand_whitespace(
+            "plain text",
+            &mut shaped,
+            &AsciiFont,
+            &ShapingParams::default(),
+        );
+        assert_eq!(shaped.glyphs, before.glyphs);
+        assert_eq!(shaped.advance_width, before.advance_width);
+    }
+}
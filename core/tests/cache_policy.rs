@@ -7,7 +7,7 @@ use std::sync::{
 
 use typf_core::traits::{FontRef, Renderer, Shaper, Stage};
 use typf_core::{
-    types::{BitmapData, BitmapFormat, PositionedGlyph, RenderOutput, ShapingResult},
+    types::{BitmapData, BitmapFormat, PixelLayout, PositionedGlyph, RenderOutput, ShapingResult},
     Pipeline, RenderParams, ShapingParams,
 };
 
@@ -122,6 +122,8 @@ impl Renderer for CountingRenderer {
             width: 1,
             height: 1,
             format: BitmapFormat::Gray8,
+            stride: 1,
+            layout: PixelLayout::RGBA,
             data: vec![shaped.glyphs.len() as u8],
         }))
     }
@@ -0,0 +1,95 @@
+#![cfg(feature = "glyph-metrics")]
+#![allow(clippy::expect_used, clippy::panic)]
+
+use skrifa::MetadataProvider;
+use typf_core::{
+    error::{RenderError, TypfError},
+    glyph_metrics::{glyph_metrics, measure_glyphs},
+    traits::FontRef,
+    types::GlyphId,
+};
+
+const FONT_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../test-fonts/NotoSans-Regular.ttf"
+);
+
+/// Raw font bytes; the metrics API reads nothing else
+struct BytesFont(Vec<u8>);
+
+impl FontRef for BytesFont {
+    fn data(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn units_per_em(&self) -> u16 {
+        1000
+    }
+
+    fn glyph_id(&self, ch: char) -> Option<GlyphId> {
+        let font = skrifa::FontRef::new(&self.0).ok()?;
+        font.charmap().map(ch).map(|g| g.to_u32())
+    }
+
+    fn advance_width(&self, _glyph_id: GlyphId) -> f32 {
+        0.0
+    }
+}
+
+fn font() -> BytesFont {
+    BytesFont(std::fs::read(FONT_PATH).expect("test font"))
+}
+
+#[test]
+fn glyph_metrics_scales_advance_and_ink_box_with_size() {
+    let font = font();
+    let h = font.glyph_id('H').expect("H is mapped");
+
+    let small = glyph_metrics(&font, h, 10.0, &[]).expect("metrics");
+    let large = glyph_metrics(&font, h, 100.0, &[]).expect("metrics");
+    assert!(small.advance_width > 0.0);
+    assert!((large.advance_width - small.advance_width * 10.0).abs() < 0.01);
+
+    let bounds = large.bounds.expect("H has ink");
+    assert!(bounds.width() > 0.0 && bounds.height() > 0.0);
+    assert!(bounds.x_max <= large.advance_width + 1.0);
+    assert!((bounds.x_min - large.left_side_bearing).abs() < 0.01);
+    // Without vmtx the vertical advance is the ascender-to-descender span
+    assert!(large.advance_height > bounds.height());
+    assert!(large.top_side_bearing >= 0.0);
+}
+
+#[test]
+fn glyph_metrics_reports_no_box_for_blank_glyphs() {
+    let font = font();
+    let space = font.glyph_id(' ').expect("space is mapped");
+
+    let metrics = glyph_metrics(&font, space, 16.0, &[]).expect("metrics");
+    assert!(metrics.advance_width > 0.0);
+    assert_eq!(metrics.bounds, None);
+}
+
+#[test]
+fn measure_glyphs_matches_single_lookups_and_rejects_bad_ids() {
+    let font = font();
+    let ids: Vec<GlyphId> = "Hi!".chars().filter_map(|c| font.glyph_id(c)).collect();
+
+    let all = measure_glyphs(&font, &ids, 24.0, &[]).expect("metrics");
+    for (&id, metrics) in ids.iter().zip(&all) {
+        assert_eq!(
+            *metrics,
+            glyph_metrics(&font, id, 24.0, &[]).expect("metrics")
+        );
+    }
+
+    assert!(matches!(
+        measure_glyphs(&font, &[ids[0], u32::MAX], 24.0, &[]),
+        Err(TypfError::RenderingFailed(RenderError::GlyphNotFound(
+            u32::MAX
+        )))
+    ));
+    assert!(matches!(
+        glyph_metrics(&BytesFont(vec![0; 8]), 0, 24.0, &[]),
+        Err(TypfError::RenderingFailed(RenderError::InvalidFont))
+    ));
+}
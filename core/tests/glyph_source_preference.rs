@@ -63,3 +63,68 @@ fn empty_prefer_falls_back_to_default_minus_denies() {
     assert!(pref.deny.contains(&GlyphSource::Colr1));
     assert!(pref.deny.contains(&GlyphSource::Svg));
 }
+
+#[test]
+fn monochrome_denies_every_color_source() {
+    let pref = GlyphSourcePreference::monochrome();
+
+    assert_eq!(
+        pref.prefer,
+        vec![GlyphSource::Glyf, GlyphSource::Cff2, GlyphSource::Cff]
+    );
+    assert!(!pref.allows_color());
+    assert!(GlyphSourcePreference::default().allows_color());
+    assert!(
+        GlyphSourcePreference::from_parts(vec![GlyphSource::Glyf, GlyphSource::Sbix], [])
+            .allows_color()
+    );
+}
+
+#[test]
+fn size_rules_pick_order_below_threshold() {
+    let pref = GlyphSourcePreference::from_parts(vec![GlyphSource::Colr1], [GlyphSource::Sbix])
+        .with_size_rule(
+            32,
+            vec![GlyphSource::Sbix, GlyphSource::Cbdt, GlyphSource::Colr1],
+        )
+        .with_size_rule(12, vec![GlyphSource::Glyf]);
+
+    assert_eq!(pref.order_at(8.0), vec![GlyphSource::Glyf]);
+    assert_eq!(
+        pref.order_at(16.0),
+        vec![GlyphSource::Cbdt, GlyphSource::Colr1],
+        "denied sources stay out of size rules"
+    );
+    assert_eq!(pref.order_at(32.0), vec![GlyphSource::Colr1]);
+}
+
+#[test]
+fn presets_by_name() {
+    for name in GlyphSourcePreference::PRESETS {
+        assert!(GlyphSourcePreference::preset(name).is_some(), "{name}");
+    }
+    assert!(GlyphSourcePreference::preset("sepia").is_none());
+
+    let vector = GlyphSourcePreference::preset("vector-only").unwrap_or_default();
+    assert!(vector.allows_color());
+    assert!(!vector.effective_order().contains(&GlyphSource::Cbdt));
+
+    let small = GlyphSourcePreference::prefer_bitmap_at_small_sizes();
+    assert_eq!(small.order_at(16.0)[0], GlyphSource::Sbix);
+    assert_eq!(small.order_at(48.0)[3], GlyphSource::Colr1);
+    assert_eq!(
+        GlyphSourcePreference::preset("monochrome"),
+        Some(GlyphSourcePreference::monochrome())
+    );
+}
+
+#[test]
+fn allows_color_counts_size_rules() {
+    let pref = GlyphSourcePreference::from_parts(vec![GlyphSource::Glyf], [])
+        .with_size_rule(20, vec![GlyphSource::Cbdt]);
+    assert!(pref.allows_color());
+
+    let denied = GlyphSourcePreference::from_parts(vec![GlyphSource::Glyf], [GlyphSource::Cbdt])
+        .with_size_rule(20, vec![GlyphSource::Cbdt]);
+    assert!(!denied.allows_color());
+}
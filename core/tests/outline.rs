@@ -0,0 +1,113 @@
+#![cfg(feature = "outline")]
+#![allow(clippy::expect_used, clippy::panic)]
+
+use kurbo::Shape;
+use skrifa::MetadataProvider;
+use typf_core::{
+    error::{RenderError, TypfError},
+    outline::{extract_glyph_outline, glyph_outlines},
+    traits::FontRef,
+    types::{Direction, GlyphId, PositionedGlyph, ShapingResult},
+};
+
+const FONT_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../test-fonts/NotoSans-Regular.ttf"
+);
+
+/// Raw font bytes; the outline API reads nothing else
+struct BytesFont(Vec<u8>);
+
+impl FontRef for BytesFont {
+    fn data(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn units_per_em(&self) -> u16 {
+        1000
+    }
+
+    fn glyph_id(&self, ch: char) -> Option<GlyphId> {
+        let font = skrifa::FontRef::new(&self.0).ok()?;
+        font.charmap().map(ch).map(|g| g.to_u32())
+    }
+
+    fn advance_width(&self, _glyph_id: GlyphId) -> f32 {
+        0.0
+    }
+}
+
+fn font() -> BytesFont {
+    BytesFont(std::fs::read(FONT_PATH).expect("test font"))
+}
+
+#[test]
+fn extract_glyph_outline_traces_ink_above_baseline() {
+    let font = font();
+    let h = font.glyph_id('H').expect("H is mapped");
+
+    let path = extract_glyph_outline(&font, h, 100.0, &[]).expect("outline");
+    let bounds = path.bounding_box();
+    assert!(bounds.width() > 10.0 && bounds.height() > 50.0);
+    // y points up: the cap height is above the baseline
+    assert!(bounds.y1 > 50.0 && bounds.y0 >= -1.0);
+
+    let space = font.glyph_id(' ').expect("space is mapped");
+    let path = extract_glyph_outline(&font, space, 100.0, &[]).expect("empty outline");
+    assert!(path.elements().is_empty());
+}
+
+#[test]
+fn extract_glyph_outline_rejects_bad_fonts_and_glyphs() {
+    let bogus = BytesFont(vec![0; 16]);
+    assert!(matches!(
+        extract_glyph_outline(&bogus, 1, 16.0, &[]),
+        Err(TypfError::RenderingFailed(RenderError::InvalidFont))
+    ));
+
+    let font = font();
+    assert!(matches!(
+        extract_glyph_outline(&font, u32::MAX, 16.0, &[]),
+        Err(TypfError::RenderingFailed(RenderError::GlyphNotFound(_)))
+    ));
+}
+
+#[test]
+fn glyph_outlines_places_each_glyph_at_its_position() {
+    let font = font();
+    let h = font.glyph_id('H').expect("H is mapped");
+    let shaped = ShapingResult {
+        glyphs: vec![
+            PositionedGlyph {
+                id: h,
+                x: 0.0,
+                y: 0.0,
+                advance: 50.0,
+                cluster: 0,
+            },
+            PositionedGlyph {
+                id: h,
+                x: 200.0,
+                y: 10.0,
+                advance: 50.0,
+                cluster: 1,
+            },
+        ],
+        advance_width: 250.0,
+        advance_height: 0.0,
+        direction: Direction::LeftToRight,
+    };
+
+    let outlines: Vec<_> = glyph_outlines(&font, &shaped, 100.0, &[])
+        .expect("font parses")
+        .collect::<Result<_, _>>()
+        .expect("outlines");
+    assert_eq!(outlines.len(), 2);
+    assert_eq!(outlines[1].cluster, 1);
+
+    let first = outlines[0].path.bounding_box();
+    let second = outlines[1].path.bounding_box();
+    assert!((second.x0 - first.x0 - 200.0).abs() < 1e-6);
+    // A positive `y` lowers the glyph, which is down in these y-up paths
+    assert!((first.y0 - second.y0 - 10.0).abs() < 1e-6);
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "serde")]
+#![allow(clippy::expect_used)]
+
+use icu_properties::props::Script;
+use typf_core::types::{
+    BitmapData, BitmapFormat, Direction, FontMetrics, LineMetrics, PixelLayout, PositionedGlyph,
+    ShapingResult, TextRun,
+};
+
+fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> T {
+    let json = serde_json::to_string(value).expect("serialize");
+    serde_json::from_str(&json).expect("deserialize")
+}
+
+#[test]
+fn shaping_result_replays_without_reshaping() {
+    let shaped = ShapingResult {
+        glyphs: vec![
+            PositionedGlyph {
+                id: 36,
+                x: 0.0,
+                y: 0.0,
+                advance: 11.5,
+                cluster: 0,
+            },
+            PositionedGlyph {
+                id: 72,
+                x: 11.5,
+                y: -0.25,
+                advance: 8.0,
+                cluster: 1,
+            },
+        ],
+        advance_width: 19.5,
+        advance_height: 16.0,
+        direction: Direction::RightToLeft,
+    };
+
+    let back = round_trip(&shaped);
+    assert_eq!(back.glyphs, shaped.glyphs);
+    assert_eq!(back.advance_width, shaped.advance_width);
+    assert_eq!(back.advance_height, shaped.advance_height);
+    assert_eq!(back.direction, shaped.direction);
+}
+
+#[test]
+fn bitmap_keeps_layout_and_padding() {
+    let bitmap = BitmapData {
+        width: 2,
+        height: 2,
+        format: BitmapFormat::Rgba8,
+        stride: 12,
+        layout: PixelLayout::PREMULTIPLIED_BGRA,
+        data: (0..24).collect(),
+    };
+
+    let back = round_trip(&bitmap);
+    assert_eq!((back.width, back.height), (2, 2));
+    assert_eq!(back.format, BitmapFormat::Rgba8);
+    assert_eq!(back.stride, 12);
+    assert_eq!(back.layout, PixelLayout::PREMULTIPLIED_BGRA);
+    assert_eq!(back.data, bitmap.data);
+}
+
+#[test]
+fn metrics_and_runs_round_trip() {
+    let hhea = LineMetrics {
+        ascent: 1069,
+        descent: -293,
+        line_gap: 0,
+    };
+    let metrics = FontMetrics {
+        units_per_em: 1000,
+        ascent: 1069,
+        descent: -293,
+        line_gap: 0,
+        baselines: Default::default(),
+        hhea,
+        typo: Some(hhea),
+        win: None,
+        use_typo_metrics: true,
+        x_height: Some(536),
+        cap_height: Some(714),
+        subscript: None,
+        superscript: None,
+    };
+    assert_eq!(round_trip(&metrics), metrics);
+
+    let run = TextRun {
+        text: "مرحبا".to_string(),
+        start: 6,
+        end: 16,
+        script: Script::Arabic,
+        language: "ar".to_string(),
+        direction: Direction::RightToLeft,
+    };
+    let back = round_trip(&run);
+    assert_eq!(back.text, run.text);
+    assert_eq!((back.start, back.end), (6, 16));
+    assert_eq!(back.script, Script::Arabic);
+    assert_eq!(back.language, "ar");
+    assert_eq!(back.direction, Direction::RightToLeft);
+}
@@ -10,7 +10,7 @@ use std::fs;
 use typf_core::{
     context::PipelineContext,
     pipeline::PipelineBuilder,
-    types::{BitmapData, BitmapFormat, Direction, RenderOutput, ShapingParams, ShapingResult},
+    types::{BitmapData, BitmapFormat, PixelLayout, Direction, RenderOutput, ShapingParams, ShapingResult},
 };
 use typf_export::{JsonExporter, PngExporter, PnmExporter, SvgExporter};
 use typf_render_opixa::OpixaRenderer;
@@ -74,6 +74,8 @@ fn render_simple_png(text: &str) -> Result<(), Box<dyn std::error::Error>> {
         width: 200,                    // Compact dimensions
         height: 50,
         format: BitmapFormat::Rgba8,   // Full color with alpha
+        stride: 800,
+        layout: PixelLayout::RGBA,
         data: create_simple_bitmap(200, 50),
     };
 
@@ -97,6 +99,8 @@ fn render_harfbuzz_png(text: &str) -> Result<(), Box<dyn std::error::Error>> {
         width: 300,                    // More space for shaped text
         height: 60,
         format: BitmapFormat::Rgba8,   // Full color depth
+        stride: 1200,
+        layout: PixelLayout::RGBA,
         data: create_simple_bitmap(300, 60),
     };
 
@@ -172,6 +176,8 @@ fn render_svg_export(text: &str) -> Result<(), Box<dyn std::error::Error>> {
         width: 250,                    // Balanced dimensions
         height: 60,
         format: BitmapFormat::Rgba8,   // Full color with transparency
+        stride: 1000,
+        layout: PixelLayout::RGBA,
         data: create_colored_bitmap(250, 60),
     };
 
@@ -196,6 +202,8 @@ fn render_pnm_formats(text: &str) -> Result<(), Box<dyn std::error::Error>> {
         width: 200,
         height: 50,
         format: BitmapFormat::Rgb8,   // 3 bytes per pixel: R, G, B
+        stride: 600,
+        layout: PixelLayout::RGBA,
         data: create_colored_bitmap_rgb(200, 50),
     };
 
@@ -211,6 +219,8 @@ fn render_pnm_formats(text: &str) -> Result<(), Box<dyn std::error::Error>> {
         width: 200,
         height: 50,
         format: BitmapFormat::Gray8,  // 1 byte per pixel: intensity only
+        stride: 200,
+        layout: PixelLayout::RGBA,
         data: create_grayscale_bitmap(200, 50),
     };
 
@@ -0,0 +1,115 @@
+//! Gallery: glyph atlas generation
+//!
+//! Rasterizes a few runs into small atlas pages, writes each page as a PNG,
+//! then redraws the first run from its quads alone, the way a GPU would
+//! sample the texture. If the redrawn line looks right, the UVs are right.
+//!
+//! Font: NotoSans-Regular.ttf
+//!
+//! Run with:
+//!   cargo run -p typf --example gallery_atlas --features shaping-hb
+
+mod common;
+
+use common::GalleryResult;
+use typf_atlas::{AtlasConfig, AtlasRenderer, AtlasText, GlyphAtlas};
+use typf_core::traits::Shaper;
+use typf_core::types::{BitmapData, BitmapFormat, Direction, PixelLayout, RenderOutput};
+use typf_core::{RenderParams, ShapingParams};
+use typf_shape_hb::HarfBuzzShaper;
+
+const RUNS: [&str; 3] = [
+    "The quick brown fox jumps over the lazy dog",
+    "SPHINX OF BLACK QUARTZ, JUDGE MY VOW",
+    "0123456789 !?&@#%()[]{}",
+];
+
+fn main() -> GalleryResult {
+    println!("Gallery: glyph atlas");
+    let out = common::output_dir("atlas")?;
+    let font = common::load_font("NotoSans-Regular.ttf")?;
+    let shaper = HarfBuzzShaper::new();
+
+    let mut renderer = AtlasRenderer::new(AtlasConfig {
+        page_width: 256,
+        page_height: 256,
+        outset: 1,
+        ..Default::default()
+    });
+
+    let mut texts = Vec::new();
+    for text in RUNS {
+        let shaped = shaper.shape(
+            text,
+            font.clone(),
+            &ShapingParams {
+                size: 32.0,
+                direction: Direction::LeftToRight,
+                ..Default::default()
+            },
+        )?;
+        texts.push(renderer.render(&shaped, font.clone(), &RenderParams::default())?);
+    }
+
+    let atlas = renderer.atlas();
+    println!(
+        "  {} glyphs on {} page(s)",
+        atlas.len(),
+        atlas.pages().len()
+    );
+    for (i, page) in atlas.pages().iter().enumerate() {
+        let bitmap = BitmapData {
+            width: page.width(),
+            height: page.height(),
+            format: page.format(),
+            stride: page.format().row_bytes(page.width()),
+            layout: PixelLayout::RGBA,
+            data: page.data().to_vec(),
+        };
+        common::write_png(
+            &out,
+            &format!("page-{i}.png"),
+            &RenderOutput::Bitmap(bitmap),
+        )?;
+    }
+
+    let line = redraw(atlas, &texts[0], 32);
+    common::write_png(&out, "redrawn.png", &RenderOutput::Bitmap(line))?;
+    Ok(())
+}
+
+/// Copy each quad's texels from its page into a fresh coverage bitmap.
+fn redraw(atlas: &GlyphAtlas, text: &AtlasText, ascent: u32) -> BitmapData {
+    let width = text.advance_width.ceil() as u32 + 8;
+    let height = ascent * 2;
+    let mut data = vec![0u8; (width * height) as usize];
+
+    for quad in &text.quads {
+        let page = &atlas.pages()[quad.page];
+        let src_x = (quad.uv.u0 * page.width() as f32).round() as u32;
+        let src_y = (quad.uv.v0 * page.height() as f32).round() as u32;
+        let dst_x = quad.x.round() as i32 + 4;
+        let dst_y = quad.y.round() as i32 + ascent as i32;
+
+        for y in 0..quad.height {
+            for x in 0..quad.width {
+                let (px, py) = (dst_x + x as i32, dst_y + y as i32);
+                if px < 0 || py < 0 || px as u32 >= width || py as u32 >= height {
+                    continue;
+                }
+                let texel = page.data()[((src_y + y) * page.width() + src_x + x) as usize];
+                let dst = &mut data[(py as u32 * width + px as u32) as usize];
+                *dst = (*dst).max(texel);
+            }
+        }
+    }
+
+    BitmapData {
+        width,
+        height,
+        format: BitmapFormat::Gray8,
+        stride: BitmapFormat::Gray8.row_bytes(width),
+        layout: PixelLayout::RGBA,
+        data,
+    }
+}
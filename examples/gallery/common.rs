@@ -0,0 +1,82 @@
+//! Shared plumbing for the gallery examples: finding fonts, picking an output
+//! directory, and writing images.
+//!
+//! Fonts are looked up in `$TYPF_FONT_DIR` first, then in the repository's
+//! `test-fonts/` directory. Images go to `$TYPF_GALLERY_DIR/<example>/`, or
+//! `examples/output/gallery/<example>/` when the variable is unset.
+
+#![allow(dead_code)] // Each example uses a different subset of these helpers
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use typf_core::traits::{Exporter, FontRef};
+use typf_core::types::RenderOutput;
+use typf_export::PngExporter;
+use typf_fontdb::TypfFontFace;
+
+pub type GalleryResult<T = ()> = Result<T, Box<dyn Error>>;
+
+fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Resolve `name` against the font search path.
+pub fn font_path(name: &str) -> GalleryResult<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = std::env::var_os("TYPF_FONT_DIR") {
+        dirs.push(PathBuf::from(dir));
+    }
+    dirs.push(repo_root().join("test-fonts"));
+
+    dirs.iter()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| {
+            format!(
+                "font {name} not found in {}; see test-fonts/README.md or set TYPF_FONT_DIR",
+                dirs.iter()
+                    .map(|d| d.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .into()
+        })
+}
+
+/// Load `name` from the font search path.
+pub fn load_font(name: &str) -> GalleryResult<Arc<dyn FontRef>> {
+    let face = TypfFontFace::from_file(font_path(name)?)?;
+    Ok(Arc::new(face))
+}
+
+/// Output directory for one example, created if missing.
+pub fn output_dir(example: &str) -> GalleryResult<PathBuf> {
+    let base = std::env::var_os("TYPF_GALLERY_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| repo_root().join("examples/output/gallery"));
+    let dir = base.join(example);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Encode a bitmap render as PNG and write it to `dir/file`.
+pub fn write_png(dir: &Path, file: &str, output: &RenderOutput) -> GalleryResult<PathBuf> {
+    let bytes = PngExporter::new().export(output)?;
+    let path = dir.join(file);
+    std::fs::write(&path, bytes)?;
+    println!("  wrote {}", path.display());
+    Ok(path)
+}
+
+/// Write text output (SVG, JSON) to `dir/file`.
+pub fn write_text(dir: &Path, file: &str, text: &str) -> GalleryResult<PathBuf> {
+    let path = dir.join(file);
+    std::fs::write(&path, text)?;
+    println!("  wrote {}", path.display());
+    Ok(path)
+}
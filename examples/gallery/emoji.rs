@@ -0,0 +1,65 @@
+//! Gallery: color glyphs in every palette
+//!
+//! Renders the same word from a COLR font once per CPAL palette. The palette
+//! count comes from `typf_render_color::ColorFont`, which parses the font's
+//! color tables once and answers palette lookups from its cache.
+//!
+//! Font: Nabla-Regular-COLR.ttf
+//!
+//! Run with:
+//!   cargo run -p typf --example gallery_emoji --features shaping-hb
+
+mod common;
+
+use common::GalleryResult;
+use typf_core::traits::{Renderer, Shaper};
+use typf_core::types::Direction;
+use typf_core::{GlyphSource, GlyphSourcePreference, RenderParams, ShapingParams};
+use typf_render_color::ColorFont;
+use typf_render_skia::SkiaRenderer;
+use typf_shape_hb::HarfBuzzShaper;
+
+/// Stop after this many palettes even if the font has more.
+const MAX_PALETTES: u16 = 8;
+
+fn main() -> GalleryResult {
+    println!("Gallery: color palettes");
+    let out = common::output_dir("emoji")?;
+    let font = common::load_font("Nabla-Regular-COLR.ttf")?;
+
+    let color = ColorFont::new(font.data())?;
+    println!("  color formats: {:?}", color.color_types());
+    let palettes = (0..MAX_PALETTES)
+        .take_while(|&i| color.palette_colors(i).is_some())
+        .count() as u16;
+    if palettes == 0 {
+        return Err("font has no CPAL palettes".into());
+    }
+
+    let shaper = HarfBuzzShaper::new();
+    let shaped = shaper.shape(
+        "Typf",
+        font.clone(),
+        &ShapingParams {
+            size: 96.0,
+            direction: Direction::LeftToRight,
+            ..Default::default()
+        },
+    )?;
+
+    let renderer = SkiaRenderer::new();
+    for palette in 0..palettes {
+        let params = RenderParams {
+            padding: 8,
+            color_palette: palette.into(),
+            glyph_sources: GlyphSourcePreference::from_parts(
+                vec![GlyphSource::Colr1, GlyphSource::Colr0],
+                [],
+            ),
+            ..Default::default()
+        };
+        let output = renderer.render(&shaped, font.clone(), &params)?;
+        common::write_png(&out, &format!("palette-{palette}.png"), &output)?;
+    }
+    Ok(())
+}
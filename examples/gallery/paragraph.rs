@@ -0,0 +1,125 @@
+//! Gallery: a multi-script paragraph
+//!
+//! Shapes Latin, Greek, Cyrillic, and Arabic lines with HarfBuzz, renders
+//! each with Skia, and stacks them into one image. Right-to-left lines are
+//! right-aligned, the way a paragraph layout would place them.
+//!
+//! Fonts: NotoSans-Regular.ttf, NotoNaskhArabic-Regular.ttf
+//!
+//! Run with:
+//!   cargo run -p typf --example gallery_paragraph --features shaping-hb
+
+mod common;
+
+use common::GalleryResult;
+use typf_core::traits::{Renderer, Shaper};
+use typf_core::types::{BitmapData, BitmapFormat, Direction, PixelLayout, RenderOutput};
+use typf_core::{Color, RenderParams, ShapingParams};
+use typf_render_skia::SkiaRenderer;
+use typf_shape_hb::HarfBuzzShaper;
+
+const SIZE: f32 = 40.0;
+const MARGIN: u32 = 16;
+
+/// (font file, text, direction, ISO 15924 script, BCP 47 language)
+const LINES: [(&str, &str, Direction, &str, &str); 4] = [
+    (
+        "NotoSans-Regular.ttf",
+        "Typography: office “affine” fjord",
+        Direction::LeftToRight,
+        "Latn",
+        "en",
+    ),
+    (
+        "NotoSans-Regular.ttf",
+        "Τυπογραφία και γλυφές",
+        Direction::LeftToRight,
+        "Grek",
+        "el",
+    ),
+    (
+        "NotoSans-Regular.ttf",
+        "Типографика и шрифты",
+        Direction::LeftToRight,
+        "Cyrl",
+        "ru",
+    ),
+    (
+        "NotoNaskhArabic-Regular.ttf",
+        "الطباعة فن جميل",
+        Direction::RightToLeft,
+        "Arab",
+        "ar",
+    ),
+];
+
+fn main() -> GalleryResult {
+    println!("Gallery: multi-script paragraph");
+    let out = common::output_dir("paragraph")?;
+    let shaper = HarfBuzzShaper::new();
+    let renderer = SkiaRenderer::new();
+
+    let mut lines = Vec::new();
+    for (font_name, text, direction, script, language) in LINES {
+        let font = common::load_font(font_name)?;
+        let shaping = ShapingParams {
+            size: SIZE,
+            direction,
+            script: Some(script.to_string()),
+            language: Some(language.to_string()),
+            ..Default::default()
+        };
+        let shaped = shaper.shape(text, font.clone(), &shaping)?;
+        println!(
+            "  {script}: {} glyphs, {:.1}px wide",
+            shaped.glyphs.len(),
+            shaped.advance_width
+        );
+
+        let params = RenderParams {
+            background: Some(Color::white()),
+            padding: 4,
+            ..Default::default()
+        };
+        match renderer.render(&shaped, font, &params)? {
+            RenderOutput::Bitmap(bitmap) => lines.push((bitmap, direction)),
+            _ => return Err("Skia returned a non-bitmap output".into()),
+        }
+    }
+
+    let paragraph = stack_lines(&lines);
+    common::write_png(&out, "paragraph.png", &RenderOutput::Bitmap(paragraph))?;
+    Ok(())
+}
+
+/// Stack RGBA line bitmaps on a white page, aligning each by its direction.
+fn stack_lines(lines: &[(BitmapData, Direction)]) -> BitmapData {
+    let width = lines.iter().map(|(b, _)| b.width).max().unwrap_or(0) + MARGIN * 2;
+    let height = lines.iter().map(|(b, _)| b.height).sum::<u32>() + MARGIN * 2;
+    let mut data = vec![255u8; (width * height * 4) as usize];
+
+    let mut top = MARGIN;
+    for (line, direction) in lines {
+        debug_assert_eq!(line.format, BitmapFormat::Rgba8);
+        let left = match direction {
+            Direction::RightToLeft => width - MARGIN - line.width,
+            _ => MARGIN,
+        };
+        let row_bytes = (line.width * 4) as usize;
+        for y in 0..line.height {
+            let src = (y * line.width * 4) as usize;
+            let dst = (((top + y) * width + left) * 4) as usize;
+            data[dst..dst + row_bytes].copy_from_slice(&line.data[src..src + row_bytes]);
+        }
+        top += line.height;
+    }
+
+    BitmapData {
+        width,
+        height,
+        format: BitmapFormat::Rgba8,
+        stride: BitmapFormat::Rgba8.row_bytes(width),
+        layout: PixelLayout::RGBA,
+        data,
+    }
+}
@@ -0,0 +1,82 @@
+//! Gallery: SVG export
+//!
+//! Renders a Latin and an Arabic line as vector paths, plus a PNG of the same
+//! Latin line for comparison. SVG output has no width limit and scales to
+//! any resolution, which makes it the natural choice for print and the web.
+//!
+//! Fonts: NotoSans-Regular.ttf, NotoNaskhArabic-Regular.ttf
+//!
+//! Run with:
+//!   cargo run -p typf --example gallery_svg --features shaping-hb
+
+mod common;
+
+use common::GalleryResult;
+use typf_core::traits::{Renderer, Shaper};
+use typf_core::types::{Direction, RenderOutput, VectorFormat};
+use typf_core::{Color, RenderMode, RenderParams, ShapingParams};
+use typf_render_skia::SkiaRenderer;
+use typf_render_svg::SvgRenderer;
+use typf_shape_hb::HarfBuzzShaper;
+
+fn main() -> GalleryResult {
+    println!("Gallery: SVG export");
+    let out = common::output_dir("svg")?;
+    let shaper = HarfBuzzShaper::new();
+    let svg = SvgRenderer::new();
+
+    let samples = [
+        (
+            "latin",
+            "NotoSans-Regular.ttf",
+            "Vectors never pixelate",
+            Direction::LeftToRight,
+        ),
+        (
+            "arabic",
+            "NotoNaskhArabic-Regular.ttf",
+            "مرحبا بالعالم",
+            Direction::RightToLeft,
+        ),
+    ];
+
+    let params = RenderParams {
+        foreground: Color::rgba(20, 40, 120, 255),
+        padding: 10,
+        output: RenderMode::Vector(VectorFormat::Svg),
+        ..Default::default()
+    };
+
+    for (name, font_name, text, direction) in samples {
+        let font = common::load_font(font_name)?;
+        let shaped = shaper.shape(
+            text,
+            font.clone(),
+            &ShapingParams {
+                size: 48.0,
+                direction,
+                ..Default::default()
+            },
+        )?;
+
+        match svg.render(&shaped, font.clone(), &params)? {
+            RenderOutput::Vector(vector) => {
+                common::write_text(&out, &format!("{name}.svg"), &vector.data)?;
+            },
+            _ => return Err("SVG renderer returned a non-vector output".into()),
+        }
+
+        if name == "latin" {
+            let bitmap = SkiaRenderer::new().render(
+                &shaped,
+                font,
+                &RenderParams {
+                    output: RenderMode::Bitmap,
+                    ..params.clone()
+                },
+            )?;
+            common::write_png(&out, "latin.png", &bitmap)?;
+        }
+    }
+    Ok(())
+}
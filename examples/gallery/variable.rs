@@ -0,0 +1,65 @@
+//! Gallery: variable font animation frames
+//!
+//! Sweeps the weight axis across the font's full range and writes one frame
+//! per step. The same coordinates go to the shaper (advances change with
+//! weight) and the renderer (outlines change with weight); passing them to
+//! only one of the two is the most common variable-font mistake.
+//!
+//! Font: Kalnia[wdth,wght].ttf
+//!
+//! Run with:
+//!   cargo run -p typf --example gallery_variable --features shaping-hb
+
+mod common;
+
+use common::GalleryResult;
+use typf_core::traits::{Renderer, Shaper};
+use typf_core::types::Direction;
+use typf_core::{Color, RenderParams, ShapingParams};
+use typf_fontdb::TypfFontFace;
+use typf_render_skia::SkiaRenderer;
+use typf_shape_hb::HarfBuzzShaper;
+
+const FRAMES: u32 = 12;
+
+fn main() -> GalleryResult {
+    println!("Gallery: variable font frames");
+    let out = common::output_dir("variable")?;
+
+    let face = TypfFontFace::from_file(common::font_path("Kalnia[wdth,wght].ttf")?)?;
+    let (min, max) = face
+        .variation_axes()
+        .unwrap_or_default()
+        .iter()
+        .find(|axis| axis.tag == "wght")
+        .map(|axis| (axis.min_value, axis.max_value))
+        .ok_or("font has no wght axis")?;
+    let font = std::sync::Arc::new(face);
+
+    let shaper = HarfBuzzShaper::new();
+    let renderer = SkiaRenderer::new();
+    for frame in 0..FRAMES {
+        let t = frame as f32 / (FRAMES - 1) as f32;
+        let variations = vec![("wght".to_string(), min + (max - min) * t)];
+
+        let shaped = shaper.shape(
+            "Kalnia",
+            font.clone(),
+            &ShapingParams {
+                size: 72.0,
+                direction: Direction::LeftToRight,
+                variations: variations.clone(),
+                ..Default::default()
+            },
+        )?;
+        let params = RenderParams {
+            background: Some(Color::white()),
+            padding: 8,
+            variations,
+            ..Default::default()
+        };
+        let output = renderer.render(&shaped, font.clone(), &params)?;
+        common::write_png(&out, &format!("frame-{frame:02}.png"), &output)?;
+    }
+    Ok(())
+}
@@ -56,6 +56,10 @@ fn main() {
             features: vec![],
             variations: vec![("wght".to_string(), *weight)], // Weight axis only
             letter_spacing: 0.0,
+            tab_stops: Default::default(),
+            number_substitution: Default::default(),
+            case: Default::default(),
+            cancel: None,
         };
 
         match shaper.shape("Hello", font.clone(), &params) {
@@ -85,6 +89,10 @@ fn main() {
             features: vec![],
             variations: vec![("wdth".to_string(), *width)], // Width axis only
             letter_spacing: 0.0,
+            tab_stops: Default::default(),
+            number_substitution: Default::default(),
+            case: Default::default(),
+            cancel: None,
         };
 
         match shaper.shape("Variable", font.clone(), &params) {
@@ -116,6 +124,10 @@ fn main() {
             ("wdth".to_string(), 125.0), // Extended width
         ],
         letter_spacing: 0.0,
+        tab_stops: Default::default(),
+        number_substitution: Default::default(),
+        case: Default::default(),
+        cancel: None,
     };
 
     match shaper.shape("Bold Extended", font.clone(), &params) {
@@ -150,6 +162,10 @@ fn main() {
             features: vec![],
             variations: vec![("opsz".to_string(), *opsz)],
             letter_spacing: 0.0,
+            tab_stops: Default::default(),
+            number_substitution: Default::default(),
+            case: Default::default(),
+            cancel: None,
         };
 
         match shaper.shape("Optical", font.clone(), &params) {
@@ -179,6 +195,10 @@ fn main() {
             features: vec![],
             variations: vec![("slnt".to_string(), *slant)],
             letter_spacing: 0.0,
+            tab_stops: Default::default(),
+            number_substitution: Default::default(),
+            case: Default::default(),
+            cancel: None,
         };
 
         match shaper.shape("Italic", font.clone(), &params) {
@@ -207,7 +207,7 @@ impl SvgExporter {
         scale: f32,
     ) -> Result<GlyphOutline> {
         let font_data = font.data();
-        let font_ref = skrifa::FontRef::new(font_data)
+        let font_ref = skrifa::FontRef::from_index(font_data, font.face_index())
             .map_err(|_| ExportError::EncodingFailed("Invalid font".to_string()))?;
 
         let outlines = font_ref.outline_glyphs();
@@ -294,17 +294,19 @@ impl SvgExporter {
         x: f32,
         y: f32,
     ) -> Result<Option<String>> {
-        use typf_render_color::bitmap::{has_bitmap_glyphs, render_bitmap_glyph};
+        use typf_render_color::bitmap::render_font_bitmap_glyph;
 
-        let font_data = font.data();
+        let Ok(font_ref) = skrifa::FontRef::from_index(font.data(), font.face_index()) else {
+            return Ok(None);
+        };
 
         // Check if font has bitmap glyphs
-        if !has_bitmap_glyphs(font_data) {
+        if font_ref.bitmap_strikes().is_empty() {
             return Ok(None);
         }
 
         // Try to render the bitmap glyph
-        let pixmap = match render_bitmap_glyph(font_data, glyph_id, font_size) {
+        let pixmap = match render_font_bitmap_glyph(&font_ref, glyph_id, font_size) {
             Ok(p) => p,
             Err(_) => return Ok(None), // No bitmap for this glyph
         };
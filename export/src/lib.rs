@@ -7,7 +7,7 @@ use std::io::Write;
 use typf_core::{
     error::{ExportError, Result},
     traits::Exporter,
-    types::{BitmapData, BitmapFormat, RenderOutput},
+    types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput},
 };
 
 pub mod json;
@@ -52,6 +52,9 @@ impl PnmExporter {
 
     /// Converts bitmap data into PNM's simple text format
     fn export_bitmap(&self, bitmap: &BitmapData) -> Result<Vec<u8>> {
+        let straight = bitmap.to_layout(PixelLayout::RGBA);
+        let packed = straight.to_packed();
+        let bitmap = packed.as_ref();
         let mut output = Vec::new();
 
         match self.format {
@@ -108,10 +111,11 @@ impl PnmExporter {
                     },
                     BitmapFormat::Gray1 => {
                         // Expand 1-bit to full RGB
+                        let stride = BitmapFormat::Gray1.row_bytes(bitmap.width);
                         for y in 0..bitmap.height {
                             for x in 0..bitmap.width {
-                                let byte_idx = ((y * bitmap.width + x) / 8) as usize;
-                                let bit_idx = ((y * bitmap.width + x) % 8) as usize;
+                                let byte_idx = y as usize * stride + x as usize / 8;
+                                let bit_idx = (x % 8) as usize;
                                 let bit = (bitmap.data[byte_idx] >> (7 - bit_idx)) & 1;
                                 let value = if bit == 1 { 255 } else { 0 };
                                 write!(&mut output, "{} {} {} ", value, value, value)?;
@@ -170,10 +174,11 @@ impl PnmExporter {
                     },
                     BitmapFormat::Gray1 => {
                         // Expand 1-bit to 8-bit grayscale
+                        let stride = BitmapFormat::Gray1.row_bytes(bitmap.width);
                         for y in 0..bitmap.height {
                             for x in 0..bitmap.width {
-                                let byte_idx = ((y * bitmap.width + x) / 8) as usize;
-                                let bit_idx = ((y * bitmap.width + x) % 8) as usize;
+                                let byte_idx = y as usize * stride + x as usize / 8;
+                                let bit_idx = (x % 8) as usize;
                                 let bit = (bitmap.data[byte_idx] >> (7 - bit_idx)) & 1;
                                 let value = if bit == 1 { 255 } else { 0 };
                                 write!(&mut output, "{} ", value)?;
@@ -192,10 +197,11 @@ impl PnmExporter {
                 match bitmap.format {
                     BitmapFormat::Gray1 => {
                         // Already 1-bit, just copy
+                        let stride = BitmapFormat::Gray1.row_bytes(bitmap.width);
                         for y in 0..bitmap.height {
                             for x in 0..bitmap.width {
-                                let byte_idx = ((y * bitmap.width + x) / 8) as usize;
-                                let bit_idx = ((y * bitmap.width + x) % 8) as usize;
+                                let byte_idx = y as usize * stride + x as usize / 8;
+                                let bit_idx = (x % 8) as usize;
                                 let bit = (bitmap.data[byte_idx] >> (7 - bit_idx)) & 1;
                                 write!(&mut output, "{} ", bit)?;
                             }
@@ -296,6 +302,8 @@ mod tests {
             width: 2,
             height: 2,
             format: BitmapFormat::Rgba8,
+            stride: 8,
+            layout: PixelLayout::RGBA,
             data: vec![
                 255, 0, 0, 255, // Red pixel
                 0, 255, 0, 255, // Green pixel
@@ -321,6 +329,8 @@ mod tests {
             width: 2,
             height: 1,
             format: BitmapFormat::Gray8,
+            stride: 2,
+            layout: PixelLayout::RGBA,
             data: vec![128, 255],
         };
 
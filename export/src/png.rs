@@ -8,7 +8,7 @@ use image::{ImageBuffer, ImageEncoder, RgbaImage};
 use typf_core::{
     error::{ExportError, Result},
     traits::Exporter,
-    types::{BitmapData, BitmapFormat, RenderOutput},
+    types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput},
 };
 
 /// Encode bitmap data to PNG format.
@@ -18,12 +18,17 @@ use typf_core::{
 ///
 /// Returns a valid PNG with proper IHDR, IDAT, and IEND chunks.
 pub fn encode_bitmap_to_png(bitmap: &BitmapData) -> Result<Vec<u8>> {
+    // Pixels are made straight RGBA and padded rows packed first; the
+    // encoders below expect both
+    let straight = bitmap.to_layout(PixelLayout::RGBA);
+    let packed = straight.to_packed();
+    let bitmap = packed.as_ref();
     // Validate buffer size before processing
     let expected_size = match bitmap.format {
         BitmapFormat::Rgba8 => (bitmap.width * bitmap.height * 4) as usize,
         BitmapFormat::Rgb8 => (bitmap.width * bitmap.height * 3) as usize,
         BitmapFormat::Gray8 => (bitmap.width * bitmap.height) as usize,
-        BitmapFormat::Gray1 => (bitmap.width * bitmap.height).div_ceil(8) as usize,
+        BitmapFormat::Gray1 => BitmapFormat::Gray1.row_bytes(bitmap.width) * bitmap.height as usize,
     };
 
     if bitmap.data.len() < expected_size {
@@ -83,11 +88,12 @@ pub fn encode_bitmap_to_png(bitmap: &BitmapData) -> Result<Vec<u8>> {
         },
         BitmapFormat::Gray1 => {
             // Convert 1-bit to RGBA
+            let stride = BitmapFormat::Gray1.row_bytes(bitmap.width);
             let mut rgba_data = Vec::with_capacity((bitmap.width * bitmap.height * 4) as usize);
             for y in 0..bitmap.height {
                 for x in 0..bitmap.width {
-                    let byte_idx = ((y * bitmap.width + x) / 8) as usize;
-                    let bit_idx = ((y * bitmap.width + x) % 8) as usize;
+                    let byte_idx = y as usize * stride + x as usize / 8;
+                    let bit_idx = (x % 8) as usize;
                     if byte_idx >= bitmap.data.len() {
                         // Guard against out-of-bounds access
                         rgba_data.extend_from_slice(&[0, 0, 0, 255]);
@@ -202,6 +208,8 @@ mod tests {
             width: 2,
             height: 2,
             format: BitmapFormat::Rgba8,
+            stride: 8,
+            layout: PixelLayout::RGBA,
             data: vec![
                 255, 0, 0, 255, // Red
                 0, 255, 0, 255, // Green
@@ -229,6 +237,8 @@ mod tests {
             width: 2,
             height: 2,
             format: BitmapFormat::Gray8,
+            stride: 2,
+            layout: PixelLayout::RGBA,
             data: vec![0, 128, 192, 255],
         };
 
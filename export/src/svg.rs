@@ -113,7 +113,7 @@ impl Exporter for SvgExporter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use typf_core::types::BitmapFormat;
+    use typf_core::types::{BitmapFormat, PixelLayout};
 
     #[test]
     fn test_svg_exporter_creation() {
@@ -127,6 +127,8 @@ mod tests {
             width: 10,
             height: 10,
             format: BitmapFormat::Rgba8,
+            stride: 40,
+            layout: PixelLayout::RGBA,
             data: vec![255u8; 10 * 10 * 4],
         };
 
@@ -147,6 +149,8 @@ mod tests {
             width: 2,
             height: 2,
             format: BitmapFormat::Rgba8,
+            stride: 8,
+            layout: PixelLayout::RGBA,
             data: vec![
                 255, 0, 0, 255, // Red
                 0, 255, 0, 255, // Green
@@ -186,6 +190,8 @@ mod tests {
             width: 8,
             height: 8,
             format: BitmapFormat::Gray1,
+            stride: 1,
+            layout: PixelLayout::RGBA,
             data: vec![0xAA; 8], // Alternating pattern
         };
 
@@ -203,6 +209,8 @@ mod tests {
             width: 4,
             height: 4,
             format: BitmapFormat::Gray8,
+            stride: 4,
+            layout: PixelLayout::RGBA,
             data: vec![
                 0, 64, 128, 192, 255, 200, 100, 50, 0, 64, 128, 192, 255, 200, 100, 50,
             ],
@@ -220,6 +228,8 @@ mod tests {
             width: 10,
             height: 10,
             format: BitmapFormat::Rgba8,
+            stride: 40,
+            layout: PixelLayout::RGBA,
             data: vec![255u8; 10], // Should be 10*10*4 = 400 bytes
         };
 
@@ -242,6 +252,8 @@ mod tests {
             width: 10,
             height: 10,
             format: BitmapFormat::Rgba8,
+            stride: 40,
+            layout: PixelLayout::RGBA,
             data: vec![255u8; 10 * 10 * 4],
         };
 
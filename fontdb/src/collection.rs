@@ -0,0 +1,112 @@
+//! Faces inside font collections (TTC/OTC), by index or PostScript name
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use read_fonts::FontRef as ReadFontRef;
+use typf_core::error::{FontLoadError, Result};
+
+use crate::{FaceInfo, TypfFontFace};
+
+/// Where to find a face on disk.
+///
+/// Face indices shift when a collection is rebuilt with faces added or
+/// reordered; PostScript names stay put, so prefer
+/// [`CollectionMember`](Self::CollectionMember) for anything persisted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FontSource {
+    /// The first face of a font file
+    File(PathBuf),
+    /// Face `index` of a collection
+    CollectionIndex { path: PathBuf, index: u32 },
+    /// The face of a collection whose PostScript name is `ps_name`
+    CollectionMember { path: PathBuf, ps_name: String },
+}
+
+impl FontSource {
+    /// The font file this source points into.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::File(path)
+            | Self::CollectionIndex { path, .. }
+            | Self::CollectionMember { path, .. } => path,
+        }
+    }
+}
+
+impl From<PathBuf> for FontSource {
+    fn from(path: PathBuf) -> Self {
+        Self::File(path)
+    }
+}
+
+/// Every face in `data`, in index order; a plain font file has one.
+pub(crate) fn faces(
+    data: &Arc<Vec<u8>>,
+    path: Option<&Path>,
+) -> impl Iterator<Item = TypfFontFace> {
+    let data = data.clone();
+    let path = path.map(Path::to_path_buf);
+    let count = (0..)
+        .take_while(|&index| ReadFontRef::from_index(data.as_slice(), index).is_ok())
+        .count() as u32;
+    (0..count)
+        .filter_map(move |index| TypfFontFace::from_shared(data.clone(), index, path.clone()).ok())
+}
+
+/// The face in `data` named `ps_name`
+pub(crate) fn find_member(
+    data: &Arc<Vec<u8>>,
+    path: Option<&Path>,
+    ps_name: &str,
+) -> Option<TypfFontFace> {
+    faces(data, path).find(|face| face.face_info().postscript_name.as_deref() == Some(ps_name))
+}
+
+impl TypfFontFace {
+    /// Describe every face in the font file at `path`, in index order.
+    ///
+    /// A plain font file describes its one face; a collection lists each
+    /// member with its family, style, and PostScript name.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn collection_faces(path: impl AsRef<Path>) -> Result<Vec<FaceInfo>> {
+        let path = path.as_ref();
+        let data = Arc::new(read_file(path)?);
+        let faces: Vec<FaceInfo> = faces(&data, Some(path))
+            .map(|face| face.face_info())
+            .collect();
+        if faces.is_empty() {
+            return Err(FontLoadError::InvalidData.into());
+        }
+        Ok(faces)
+    }
+
+    /// Load the face named `ps_name` from a font file or collection.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_file_ps_name(path: impl AsRef<Path>, ps_name: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let data = Arc::new(read_file(path)?);
+        find_member(&data, Some(path), ps_name).ok_or_else(|| {
+            FontLoadError::FaceNotFound(path.display().to_string(), ps_name.to_string()).into()
+        })
+    }
+
+    /// Load the face `source` points to.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_source(source: &FontSource) -> Result<Self> {
+        match source {
+            FontSource::File(path) => Self::from_file(path),
+            FontSource::CollectionIndex { path, index } => Self::from_file_index(path, *index),
+            FontSource::CollectionMember { path, ps_name } => {
+                Self::from_file_ps_name(path, ps_name)
+            },
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn read_file(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|_| FontLoadError::FileNotFound(path.display().to_string()).into())
+}
@@ -0,0 +1,253 @@
+//! Which characters a face has glyphs for, as a bitset.
+//!
+//! Choosing a fallback font asks one question per character: which fonts
+//! can draw it? [`Coverage`] answers it for one face in constant time, from
+//! a bitset built once from the face's `cmap`. [`TypfFontFace::coverage`]
+//! builds it on first use and keeps it, so [`FontDatabase::fonts_covering`]
+//! and [`FontDatabase::fallback_fonts`] cost one bit test per loaded face.
+//! With the `system-fonts` feature they also search installed faces that
+//! were never loaded, whose coverage is read from their files on first use
+//! and kept by path and face index.
+
+use std::collections::HashMap;
+#[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+use std::path::PathBuf;
+use std::sync::Arc;
+#[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use skrifa::MetadataProvider;
+
+use crate::{FontDatabase, TypfFontFace};
+
+/// Codepoints per page of the bitset
+const PAGE_BITS: u32 = 256;
+
+/// The characters one face maps to a glyph other than `.notdef`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Coverage {
+    /// Pages of 256 codepoints, keyed by codepoint / 256; absent pages are
+    /// empty
+    pages: HashMap<u32, [u64; 4]>,
+    len: usize,
+}
+
+impl Coverage {
+    /// Read the coverage of face `face_index` of `data` from its best
+    /// Unicode `cmap` subtable; empty if the face can't be parsed
+    pub(crate) fn read(data: &[u8], face_index: u32) -> Self {
+        let Ok(font) = skrifa::FontRef::from_index(data, face_index) else {
+            return Self::default();
+        };
+        font.charmap()
+            .mappings()
+            .filter(|(_, glyph)| glyph.to_u32() != 0)
+            .map(|(codepoint, _)| codepoint)
+            .collect()
+    }
+
+    /// Whether the face has a glyph for `ch`
+    pub fn contains(&self, ch: char) -> bool {
+        let codepoint = ch as u32;
+        self.pages
+            .get(&(codepoint / PAGE_BITS))
+            .is_some_and(|page| {
+                let bit = codepoint % PAGE_BITS;
+                page[(bit / 64) as usize] & (1 << (bit % 64)) != 0
+            })
+    }
+
+    /// Whether the face has a glyph for every character of `text`
+    pub fn contains_all(&self, text: &str) -> bool {
+        text.chars().all(|ch| self.contains(ch))
+    }
+
+    /// How many characters the face covers
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the face covers no characters at all
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn insert(&mut self, codepoint: u32) {
+        let page = self.pages.entry(codepoint / PAGE_BITS).or_default();
+        let bit = codepoint % PAGE_BITS;
+        let word = &mut page[(bit / 64) as usize];
+        let mask = 1 << (bit % 64);
+        if *word & mask == 0 {
+            *word |= mask;
+            self.len += 1;
+        }
+    }
+}
+
+impl FromIterator<u32> for Coverage {
+    fn from_iter<I: IntoIterator<Item = u32>>(codepoints: I) -> Self {
+        let mut coverage = Self::default();
+        for codepoint in codepoints {
+            coverage.insert(codepoint);
+        }
+        coverage
+    }
+}
+
+/// Installed faces and their coverage, each read on first use and kept
+#[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+#[derive(Default)]
+pub(crate) struct InstalledCoverage {
+    /// Every installed face as its canonical path and face index
+    faces: OnceLock<Vec<(PathBuf, u32)>>,
+    coverage: Mutex<HashMap<(PathBuf, u32), Arc<Coverage>>>,
+}
+
+#[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+impl InstalledCoverage {
+    fn faces(&self) -> &[(PathBuf, u32)] {
+        self.faces.get_or_init(|| {
+            let mut faces: Vec<(PathBuf, u32)> = crate::system::installed_faces()
+                .iter()
+                .filter_map(|face| {
+                    let index = match face.index {
+                        Some(index) => index,
+                        // CoreText names faces; open the file to find which one
+                        None => crate::load_system_face(face)?.source().face_index(),
+                    };
+                    let path = face
+                        .path
+                        .canonicalize()
+                        .unwrap_or_else(|_| face.path.clone());
+                    Some((path, index))
+                })
+                .collect();
+            faces.sort();
+            faces.dedup();
+            faces
+        })
+    }
+
+    /// Coverage of one installed face, read from its file the first time
+    fn coverage(&self, key: &(PathBuf, u32)) -> Arc<Coverage> {
+        let cached = self
+            .coverage
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .cloned();
+        if let Some(coverage) = cached {
+            return coverage;
+        }
+        // Read outside the lock; a racing reader computes the same bitset
+        let coverage = Arc::new(
+            crate::collection::read_file(&key.0)
+                .map(|data| Coverage::read(&data, key.1))
+                .unwrap_or_default(),
+        );
+        self.coverage
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key.clone())
+            .or_insert(coverage)
+            .clone()
+    }
+}
+
+impl FontDatabase {
+    /// Faces with a glyph for `ch`: loaded faces in load order, then, with
+    /// the `system-fonts` feature, installed faces that were never loaded
+    ///
+    /// Each face's [`coverage`](TypfFontFace::coverage) is read from its
+    /// `cmap` the first time it is asked for and kept, so later queries only
+    /// test one bit per face. The first query reads every installed font
+    /// file once; matching installed faces are read again to return them.
+    pub fn fonts_covering(&self, ch: char) -> Vec<Arc<TypfFontFace>> {
+        let loaded = self
+            .fonts
+            .iter()
+            .filter(|font| font.coverage().contains(ch))
+            .cloned();
+        #[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+        let loaded = loaded.chain(self.installed_covering(ch).filter_map(|key| {
+            TypfFontFace::from_file_index(&key.0, key.1)
+                .ok()
+                .map(Arc::new)
+        }));
+        loaded.collect()
+    }
+
+    /// Faces to fall back on for the characters of `text` that `primary`
+    /// lacks, ready for `TextOptions::fallback_fonts`
+    ///
+    /// Goes through `text` in order and, for each character neither
+    /// `primary` nor a face already chosen covers, adds the first face
+    /// [`fonts_covering`](Self::fonts_covering) would list. Characters no
+    /// face covers are skipped, as are control characters.
+    pub fn fallback_fonts(&self, text: &str, primary: &TypfFontFace) -> Vec<Arc<TypfFontFace>> {
+        let mut chosen: Vec<Arc<TypfFontFace>> = Vec::new();
+        for ch in text.chars() {
+            if ch.is_control()
+                || primary.coverage().contains(ch)
+                || chosen.iter().any(|font| font.coverage().contains(ch))
+            {
+                continue;
+            }
+            let found = self
+                .fonts
+                .iter()
+                .find(|font| font.coverage().contains(ch))
+                .cloned();
+            #[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+            let found = found.or_else(|| {
+                self.installed_covering(ch).find_map(|key| {
+                    TypfFontFace::from_file_index(&key.0, key.1)
+                        .ok()
+                        .map(Arc::new)
+                })
+            });
+            if let Some(font) = found {
+                chosen.push(font);
+            }
+        }
+        chosen
+    }
+
+    /// Installed faces, not loaded into this database, with a glyph for `ch`
+    #[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+    fn installed_covering(&self, ch: char) -> impl Iterator<Item = &(PathBuf, u32)> + '_ {
+        let installed = &self.installed_coverage;
+        installed
+            .faces()
+            .iter()
+            .filter(move |key| !self.path_cache.contains_key(*key))
+            .filter(move |key| installed.coverage(key).contains(ch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contains_when_built_from_codepoints_then_only_those() {
+        let coverage: Coverage = ['a', 'z', '\u{1F600}', 'a']
+            .into_iter()
+            .map(u32::from)
+            .collect();
+
+        assert_eq!(coverage.len(), 3);
+        assert!(coverage.contains('a') && coverage.contains('\u{1F600}'));
+        assert!(!coverage.contains('b') && !coverage.contains('\u{1F601}'));
+        assert!(coverage.contains_all("az"));
+        assert!(!coverage.contains_all("abz"));
+    }
+
+    #[test]
+    fn test_contains_when_empty_then_nothing() {
+        let coverage = Coverage::default();
+        assert!(coverage.is_empty());
+        assert!(!coverage.contains('a'));
+        assert!(coverage.contains_all(""));
+    }
+}
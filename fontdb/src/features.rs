@@ -0,0 +1,235 @@
+//! OpenType feature introspection.
+//!
+//! [`TypfFontFace::features`] lists the feature tags a font's `GSUB` and
+//! `GPOS` tables define, once each, with the names a UI can show. Stylistic
+//! sets (`ss01`–`ss20`) and character variants (`cv01`–`cv99`) carry their
+//! designer-given names in the `name` table through `FeatureParams`; the
+//! character variants also list the characters they affect. Other features
+//! get their name from the OpenType feature registry.
+
+use read_fonts::{
+    tables::layout::{FeatureList, FeatureParams},
+    types::NameId,
+    FontRef as ReadFontRef, TableProvider,
+};
+
+use crate::{read_name, TypfFontFace};
+
+/// One OpenType feature a font defines.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FontFeature {
+    /// Four-character feature tag, such as `"liga"` or `"ss01"`.
+    pub tag: String,
+    /// Name to show: the font's own for stylistic sets and character
+    /// variants, the registered name otherwise, `None` for unknown tags.
+    pub name: Option<String>,
+    /// Character variant tooltip text from the font.
+    pub tooltip: Option<String>,
+    /// Character variant sample text from the font.
+    pub sample_text: Option<String>,
+    /// Names of a character variant's alternates, in feature-value order
+    /// starting at 1.
+    pub parameter_names: Vec<String>,
+    /// Characters a character variant affects; empty when the font does not
+    /// say, and always empty for other features.
+    pub characters: Vec<char>,
+    /// Defined in `GSUB` (substitution).
+    pub in_gsub: bool,
+    /// Defined in `GPOS` (positioning).
+    pub in_gpos: bool,
+}
+
+impl TypfFontFace {
+    /// Features defined in the font's `GSUB` and `GPOS` tables, sorted by
+    /// tag, one entry per tag however many scripts and languages use it.
+    pub fn features(&self) -> Vec<FontFeature> {
+        let Some(font) = self.font_ref() else {
+            return Vec::new();
+        };
+        let mut features: Vec<FontFeature> = Vec::new();
+        let gsub = font.gsub().ok().and_then(|t| t.feature_list().ok());
+        let gpos = font.gpos().ok().and_then(|t| t.feature_list().ok());
+        for (list, is_gsub) in [(gsub, true), (gpos, false)] {
+            if let Some(list) = list {
+                collect(&font, &list, is_gsub, &mut features);
+            }
+        }
+        features.sort_by(|a, b| a.tag.cmp(&b.tag));
+        features
+    }
+}
+
+/// Merge the features of one table's feature list into `features`
+fn collect(font: &ReadFontRef, list: &FeatureList, is_gsub: bool, features: &mut Vec<FontFeature>) {
+    for record in list.feature_records() {
+        let tag = record.feature_tag().to_string();
+        let index = match features.iter().position(|f| f.tag == tag) {
+            Some(index) => index,
+            None => {
+                features.push(FontFeature {
+                    name: registered_name(&tag),
+                    tag,
+                    ..FontFeature::default()
+                });
+                features.len() - 1
+            },
+        };
+        let feature = &mut features[index];
+        feature.in_gsub |= is_gsub;
+        feature.in_gpos |= !is_gsub;
+
+        let params = record
+            .feature(list.offset_data())
+            .ok()
+            .and_then(|f| f.feature_params())
+            .and_then(Result::ok);
+        let name = |id: NameId| (id.to_u16() != 0).then(|| read_name(font, &[id])).flatten();
+        match params {
+            Some(FeatureParams::StylisticSet(params)) => {
+                if let Some(ui_name) = name(params.ui_name_id()) {
+                    feature.name = Some(ui_name);
+                }
+            },
+            Some(FeatureParams::CharacterVariant(params)) => {
+                if let Some(label) = name(params.feat_ui_label_name_id()) {
+                    feature.name = Some(label);
+                }
+                feature.tooltip = name(params.feat_ui_tooltip_text_name_id());
+                feature.sample_text = name(params.sample_text_name_id());
+                let first = params.first_param_ui_label_name_id().to_u16();
+                feature.parameter_names = (0..params.num_named_parameters())
+                    .filter_map(|i| name(NameId::new(first.checked_add(i)?)))
+                    .collect();
+                feature.characters = params
+                    .character()
+                    .iter()
+                    .filter_map(|c| char::from_u32(c.get().to_u32()))
+                    .collect();
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Name from the OpenType feature registry, for the features UIs commonly
+/// expose; stylistic sets and character variants get numbered names
+fn registered_name(tag: &str) -> Option<String> {
+    let number = |prefix: &str| {
+        let digits = tag.strip_prefix(prefix)?;
+        digits
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| digits.parse::<u8>().ok())
+            .flatten()
+    };
+    match (number("ss"), number("cv")) {
+        (Some(n @ 1..=20), _) => return Some(format!("Stylistic Set {n}")),
+        (_, Some(n @ 1..=99)) => return Some(format!("Character Variant {n}")),
+        (Some(_), _) | (_, Some(_)) => return None,
+        _ => {},
+    }
+    let name = match tag {
+        "aalt" => "Access All Alternates",
+        "abvm" => "Above-base Mark Positioning",
+        "afrc" => "Alternative Fractions",
+        "blwm" => "Below-base Mark Positioning",
+        "c2pc" => "Petite Capitals From Capitals",
+        "c2sc" => "Small Capitals From Capitals",
+        "calt" => "Contextual Alternates",
+        "case" => "Case-Sensitive Forms",
+        "ccmp" => "Glyph Composition / Decomposition",
+        "clig" => "Contextual Ligatures",
+        "cpsp" => "Capital Spacing",
+        "cswh" => "Contextual Swash",
+        "curs" => "Cursive Positioning",
+        "dlig" => "Discretionary Ligatures",
+        "dnom" => "Denominators",
+        "expt" => "Expert Forms",
+        "falt" => "Final Glyph on Line Alternates",
+        "fina" => "Terminal Forms",
+        "frac" => "Fractions",
+        "fwid" => "Full Widths",
+        "halt" => "Alternate Half Widths",
+        "hist" => "Historical Forms",
+        "hlig" => "Historical Ligatures",
+        "hwid" => "Half Widths",
+        "init" => "Initial Forms",
+        "isol" => "Isolated Forms",
+        "ital" => "Italics",
+        "jalt" => "Justification Alternates",
+        "jp78" => "JIS78 Forms",
+        "jp83" => "JIS83 Forms",
+        "jp90" => "JIS90 Forms",
+        "jp04" => "JIS2004 Forms",
+        "kern" => "Kerning",
+        "liga" => "Standard Ligatures",
+        "lnum" => "Lining Figures",
+        "locl" => "Localized Forms",
+        "mark" => "Mark Positioning",
+        "medi" => "Medial Forms",
+        "mgrk" => "Mathematical Greek",
+        "mkmk" => "Mark to Mark Positioning",
+        "nalt" => "Alternate Annotation Forms",
+        "numr" => "Numerators",
+        "onum" => "Oldstyle Figures",
+        "ordn" => "Ordinals",
+        "ornm" => "Ornaments",
+        "palt" => "Proportional Alternate Widths",
+        "pcap" => "Petite Capitals",
+        "pkna" => "Proportional Kana",
+        "pnum" => "Proportional Figures",
+        "pwid" => "Proportional Widths",
+        "rand" => "Randomize",
+        "rlig" => "Required Ligatures",
+        "ruby" => "Ruby Notation Forms",
+        "salt" => "Stylistic Alternates",
+        "sinf" => "Scientific Inferiors",
+        "smcp" => "Small Capitals",
+        "smpl" => "Simplified Forms",
+        "subs" => "Subscript",
+        "sups" => "Superscript",
+        "swsh" => "Swash",
+        "titl" => "Titling",
+        "tnum" => "Tabular Figures",
+        "trad" => "Traditional Forms",
+        "twid" => "Third Widths",
+        "unic" => "Unicase",
+        "valt" => "Alternate Vertical Metrics",
+        "vert" => "Vertical Alternates",
+        "vhal" => "Alternate Vertical Half Metrics",
+        "vkna" => "Vertical Kana Alternates",
+        "vkrn" => "Vertical Kerning",
+        "vpal" => "Proportional Alternate Vertical Metrics",
+        "vrt2" => "Vertical Alternates and Rotation",
+        "zero" => "Slashed Zero",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_name_when_known_tag_then_named() {
+        assert_eq!(
+            registered_name("liga").as_deref(),
+            Some("Standard Ligatures")
+        );
+        assert_eq!(registered_name("ss01").as_deref(), Some("Stylistic Set 1"));
+        assert_eq!(registered_name("ss20").as_deref(), Some("Stylistic Set 20"));
+        assert_eq!(
+            registered_name("cv42").as_deref(),
+            Some("Character Variant 42")
+        );
+    }
+
+    #[test]
+    fn test_registered_name_when_unknown_or_out_of_range_then_none() {
+        assert_eq!(registered_name("ss00"), None);
+        assert_eq!(registered_name("ss21"), None);
+        assert_eq!(registered_name("cvXY"), None);
+        assert_eq!(registered_name("zzzz"), None);
+    }
+}
@@ -0,0 +1,183 @@
+//! CSS generic families: `serif`, `sans-serif`, `monospace`, and `emoji`.
+//!
+//! A generic name stands for whatever font the platform uses for that role.
+//! [`FamilyAliases`] lists, per generic family, the concrete families to try
+//! in order; [`FontDatabase::query`] takes the first one that resolves. The
+//! defaults name each platform's stock fonts (DejaVu and Noto on Linux,
+//! Segoe and Consolas on Windows, SF and Helvetica on macOS), and
+//! [`FontDatabase::set_family_aliases`] replaces them, for instance with the
+//! fonts an application bundles.
+
+use std::collections::HashMap;
+
+use crate::query::family_key;
+use crate::FontDatabase;
+
+/// A CSS generic font family
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GenericFamily {
+    Serif,
+    SansSerif,
+    Monospace,
+    Emoji,
+}
+
+impl GenericFamily {
+    pub const ALL: [Self; 4] = [Self::Serif, Self::SansSerif, Self::Monospace, Self::Emoji];
+
+    /// The generic family `name` spells, ignoring case, spaces, and hyphens
+    /// as family names do
+    pub fn parse(name: &str) -> Option<Self> {
+        match family_key(name).as_str() {
+            "serif" => Some(Self::Serif),
+            "sansserif" => Some(Self::SansSerif),
+            "monospace" => Some(Self::Monospace),
+            "emoji" => Some(Self::Emoji),
+            _ => None,
+        }
+    }
+
+    /// The CSS keyword
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Serif => "serif",
+            Self::SansSerif => "sans-serif",
+            Self::Monospace => "monospace",
+            Self::Emoji => "emoji",
+        }
+    }
+
+    /// This platform's stock families for the role, most preferred first
+    #[cfg(windows)]
+    fn platform_defaults(self) -> &'static [&'static str] {
+        match self {
+            Self::Serif => &["Cambria", "Times New Roman"],
+            Self::SansSerif => &["Segoe UI", "Arial"],
+            Self::Monospace => &["Cascadia Mono", "Consolas", "Courier New"],
+            Self::Emoji => &["Segoe UI Emoji"],
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn platform_defaults(self) -> &'static [&'static str] {
+        match self {
+            Self::Serif => &["New York", "Times New Roman", "Times"],
+            Self::SansSerif => &["SF Pro", "Helvetica Neue", "Helvetica"],
+            Self::Monospace => &["SF Mono", "Menlo", "Courier New"],
+            Self::Emoji => &["Apple Color Emoji"],
+        }
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    fn platform_defaults(self) -> &'static [&'static str] {
+        match self {
+            Self::Serif => &["DejaVu Serif", "Noto Serif", "Liberation Serif"],
+            Self::SansSerif => &["DejaVu Sans", "Noto Sans", "Liberation Sans"],
+            Self::Monospace => &["DejaVu Sans Mono", "Noto Sans Mono", "Liberation Mono"],
+            Self::Emoji => &["Noto Color Emoji", "Twemoji"],
+        }
+    }
+}
+
+/// Concrete families to try for each [`GenericFamily`], in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FamilyAliases {
+    families: HashMap<GenericFamily, Vec<String>>,
+}
+
+impl FamilyAliases {
+    /// This platform's stock fonts for every generic family
+    pub fn platform_defaults() -> Self {
+        Self {
+            families: GenericFamily::ALL
+                .into_iter()
+                .map(|generic| {
+                    let families = generic.platform_defaults().iter().map(|f| f.to_string());
+                    (generic, families.collect())
+                })
+                .collect(),
+        }
+    }
+
+    /// No aliases at all; generic names then only match fonts that are
+    /// literally called "serif" and so on
+    pub fn empty() -> Self {
+        Self {
+            families: HashMap::new(),
+        }
+    }
+
+    /// The families tried for `generic`, most preferred first
+    pub fn get(&self, generic: GenericFamily) -> &[String] {
+        self.families.get(&generic).map_or(&[], Vec::as_slice)
+    }
+
+    /// Try `families` for `generic`, in order, instead of the current list
+    pub fn set(
+        &mut self,
+        generic: GenericFamily,
+        families: impl IntoIterator<Item = impl Into<String>>,
+    ) {
+        self.families
+            .insert(generic, families.into_iter().map(Into::into).collect());
+    }
+}
+
+impl Default for FamilyAliases {
+    fn default() -> Self {
+        Self::platform_defaults()
+    }
+}
+
+impl FontDatabase {
+    /// The families generic names such as `"monospace"` stand for.
+    pub fn family_aliases(&self) -> &FamilyAliases {
+        &self.aliases
+    }
+
+    /// Try `families` for `generic`, in order, instead of the platform's
+    /// stock fonts.
+    pub fn set_family_aliases(
+        &mut self,
+        generic: GenericFamily,
+        families: impl IntoIterator<Item = impl Into<String>>,
+    ) {
+        self.aliases.set(generic, families);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_when_css_keyword_then_generic_family() {
+        assert_eq!(
+            GenericFamily::parse("Sans-Serif"),
+            Some(GenericFamily::SansSerif)
+        );
+        assert_eq!(
+            GenericFamily::parse("monospace"),
+            Some(GenericFamily::Monospace)
+        );
+        assert_eq!(GenericFamily::parse("Noto Sans"), None);
+        for generic in GenericFamily::ALL {
+            assert_eq!(GenericFamily::parse(generic.name()), Some(generic));
+        }
+    }
+
+    #[test]
+    fn test_aliases_when_set_then_replace_platform_defaults() {
+        let mut aliases = FamilyAliases::default();
+        assert!(GenericFamily::ALL
+            .into_iter()
+            .all(|generic| !aliases.get(generic).is_empty()));
+
+        aliases.set(GenericFamily::Monospace, ["Fira Code", "Iosevka"]);
+        assert_eq!(
+            aliases.get(GenericFamily::Monospace),
+            ["Fira Code", "Iosevka"]
+        );
+        assert!(FamilyAliases::empty().get(GenericFamily::Serif).is_empty());
+    }
+}
@@ -7,18 +7,85 @@
 //! - it avoids leaking long-lived parser objects,
 //! - it supports collection files such as TTCs, where one file contains several
 //!   faces and each face needs its own index.
+//!
+//! A face inside a collection is addressed by index or, more robustly across
+//! font updates, by PostScript name through [`FontSource`];
+//! [`TypfFontFace::collection_faces`] lists what a collection holds.
+//!
+//! On `wasm32` the crate runs in bytes-only mode: there is no filesystem, so
+//! only the `from_data*` and `load_font_data` entry points exist.
+//!
+//! With the `system-fonts` feature, [`FontDatabase::list_families`] and
+//! [`FontDatabase::list_faces`] also cover installed fonts, enumerated with
+//! fontdb on Linux, DirectWrite on Windows, and CoreText on macOS.
+//!
+//! Long-running processes can keep directories of fonts current: fonts in
+//! [`FontDatabase::add_font_dir`] directories are added, dropped, and
+//! reloaded by [`FontDatabase::rescan`], and with the `watch` feature
+//! [`FontDatabase::watch`] rescans whenever a file there changes.
+//! Subscribers, such as caches passed to [`FontDatabase::subscribe_cache`],
+//! hear about every change.
+//!
+//! Fallback selection asks which faces can draw a character:
+//! [`FontDatabase::fonts_covering`] answers from each face's cached
+//! [`Coverage`] bitset instead of trial-shaping, over the loaded faces and,
+//! with the `system-fonts` feature, the installed ones.
+//!
+//! Queries accept the CSS generic families `serif`, `sans-serif`,
+//! `monospace`, and `emoji`, resolved through per-platform
+//! [`FamilyAliases`] that [`FontDatabase::set_family_aliases`] can change.
+//!
+//! Before embedding a face in a PDF or SVG, check
+//! [`TypfFontFace::embedding_permissions`] for what its license allows.
+
+mod collection;
+mod coverage;
+mod features;
+mod generic;
+mod license;
+mod query;
+#[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+mod system;
+#[cfg(all(feature = "watch", not(target_arch = "wasm32")))]
+mod watch;
+
+pub use collection::FontSource;
+pub use coverage::Coverage;
+pub use features::FontFeature;
+pub use generic::{FamilyAliases, GenericFamily};
+pub use license::{EmbeddingLevel, EmbeddingPermissions};
+pub use query::{FontMatch, FontQuery};
+#[cfg(all(feature = "watch", not(target_arch = "wasm32")))]
+pub use watch::FontWatcher;
 
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-
-use read_fonts::{FontRef as ReadFontRef, TableProvider};
+use std::sync::{Arc, OnceLock};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::SystemTime;
+
+use read_fonts::{
+    tables::{
+        base::Base,
+        gdef::Gdef,
+        head::MacStyle,
+        kern::{Kern, Subtable2ClassTable},
+        os2::SelectionFlags,
+    },
+    types::NameId,
+    FontRef as ReadFontRef, TableProvider,
+};
 
 use typf_core::{
+    cache::InvalidateFont,
     error::{FontLoadError, Result},
     traits::FontRef as TypfFontRef,
-    types::{FontMetrics, VariationAxis},
+    types::{
+        Baseline, Baselines, FontMetrics, FontStretch, FontStyle, LineMetrics, ScriptMetrics,
+        VariationAxis,
+    },
 };
 
 /// Source descriptor for one loaded font face.
@@ -42,6 +109,39 @@ impl TypfFontSource {
     }
 }
 
+/// What a font picker needs to know about one face.
+///
+/// Built from the font's own `name`, `OS/2`, and `fvar` tables, so the same
+/// file describes itself the same way on every platform.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaceInfo {
+    /// Family name (typographic family when the font has one).
+    pub family: String,
+    /// Style within the family, such as "Bold Italic".
+    pub style_name: String,
+    /// PostScript name, when the font has one.
+    pub postscript_name: Option<String>,
+    /// File the face came from; `None` for fonts loaded from bytes.
+    pub path: Option<PathBuf>,
+    /// Face index inside a collection file.
+    pub face_index: u32,
+    /// OS/2 weight class, 100 (thin) to 900 (black).
+    pub weight: u16,
+    /// Upright, italic, or oblique.
+    pub style: FontStyle,
+    /// OS/2 width class.
+    pub stretch: FontStretch,
+    /// Variation axes; empty for static fonts.
+    pub axes: Vec<VariationAxis>,
+}
+
+impl FaceInfo {
+    /// Whether the face is a variable font.
+    pub fn is_variable(&self) -> bool {
+        !self.axes.is_empty()
+    }
+}
+
 /// In-memory font face ready for shaping and rendering.
 ///
 /// The face keeps the original font bytes and recreates parser views on demand.
@@ -52,15 +152,19 @@ pub struct TypfFontFace {
     source: TypfFontSource,
     units_per_em: u16,
     metrics: FontMetrics,
+    coverage: OnceLock<Coverage>,
+    kern: OnceLock<KernTable>,
 }
 
 impl TypfFontFace {
     /// Load the first face from a font file on disk.
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
         Self::from_file_index(path, 0)
     }
 
     /// Load a specific face from a font file or collection.
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn from_file_index(path: impl AsRef<Path>, face_index: u32) -> Result<Self> {
         let data = fs::read(path.as_ref())
             .map_err(|_| FontLoadError::FileNotFound(path.as_ref().display().to_string()))?;
@@ -83,6 +187,12 @@ impl TypfFontFace {
         face_index: u32,
         path: Option<PathBuf>,
     ) -> Result<Self> {
+        Self::from_shared(Arc::new(data), face_index, path)
+    }
+
+    /// Load face `face_index` of `data` without copying the bytes, so the
+    /// faces of one collection can share them.
+    fn from_shared(data: Arc<Vec<u8>>, face_index: u32, path: Option<PathBuf>) -> Result<Self> {
         let font_ref = ReadFontRef::from_index(data.as_slice(), face_index)
             .map_err(|_| FontLoadError::InvalidData)?;
 
@@ -91,29 +201,52 @@ impl TypfFontFace {
             .map(|head| head.units_per_em())
             .unwrap_or(1000);
 
-        let (ascent, descent, line_gap) = font_ref
-            .os2()
-            .ok()
-            .map(|os2| {
-                (
-                    os2.s_typo_ascender(),
-                    os2.s_typo_descender(),
-                    os2.s_typo_line_gap(),
-                )
-            })
-            .or_else(|| {
-                font_ref.hhea().ok().map(|hhea| {
-                    (
-                        hhea.ascender().to_i16(),
-                        hhea.descender().to_i16(),
-                        hhea.line_gap().to_i16(),
-                    )
-                })
+        let hhea = font_ref
+            .hhea()
+            .map(|hhea| LineMetrics {
+                ascent: hhea.ascender().to_i16(),
+                descent: hhea.descender().to_i16(),
+                line_gap: hhea.line_gap().to_i16(),
             })
-            .unwrap_or((0, 0, 0));
+            .unwrap_or_default();
+        let os2 = font_ref.os2().ok();
+        let typo = os2.as_ref().map(|os2| LineMetrics {
+            ascent: os2.s_typo_ascender(),
+            descent: os2.s_typo_descender(),
+            line_gap: os2.s_typo_line_gap(),
+        });
+        let win = os2.as_ref().map(|os2| LineMetrics {
+            ascent: os2.us_win_ascent().min(i16::MAX as u16) as i16,
+            descent: -(os2.us_win_descent().min(i16::MAX as u16) as i16),
+            line_gap: 0,
+        });
+        let LineMetrics {
+            ascent,
+            descent,
+            line_gap,
+        } = typo.unwrap_or(hhea);
+        let baselines = read_baselines(&font_ref, None, ascent, descent);
+        let use_typo_metrics = os2.as_ref().is_some_and(|os2| {
+            os2.fs_selection()
+                .contains(SelectionFlags::USE_TYPO_METRICS)
+        });
+        let x_height = os2.as_ref().and_then(|os2| os2.sx_height());
+        let cap_height = os2.as_ref().and_then(|os2| os2.s_cap_height());
+        let subscript = os2.as_ref().map(|os2| ScriptMetrics {
+            x_size: os2.y_subscript_x_size(),
+            y_size: os2.y_subscript_y_size(),
+            x_offset: os2.y_subscript_x_offset(),
+            y_offset: os2.y_subscript_y_offset(),
+        });
+        let superscript = os2.as_ref().map(|os2| ScriptMetrics {
+            x_size: os2.y_superscript_x_size(),
+            y_size: os2.y_superscript_y_size(),
+            x_offset: os2.y_superscript_x_offset(),
+            y_offset: os2.y_superscript_y_offset(),
+        });
 
         Ok(TypfFontFace {
-            data: Arc::new(data),
+            data,
             source: TypfFontSource::new(path, face_index),
             units_per_em,
             metrics: FontMetrics {
@@ -121,7 +254,18 @@ impl TypfFontFace {
                 ascent,
                 descent,
                 line_gap,
+                baselines,
+                hhea,
+                typo,
+                win,
+                use_typo_metrics,
+                x_height,
+                cap_height,
+                subscript,
+                superscript,
             },
+            coverage: OnceLock::new(),
+            kern: OnceLock::new(),
         })
     }
 
@@ -141,6 +285,101 @@ impl TypfFontFace {
         ReadFontRef::from_index(self.data.as_slice(), self.source.face_index).ok()
     }
 
+    /// Font-wide metrics at the variation instance `variations`, the
+    /// `(tag, value)` pairs of [`ShapingParams::variations`].
+    ///
+    /// Applies the `MVAR` deltas to the line metrics of every table, the x-
+    /// and cap heights, and the sub- and superscript recommendations. Without
+    /// variations or an `MVAR` table this is the static [`FontMetrics`].
+    ///
+    /// [`ShapingParams::variations`]: typf_core::ShapingParams::variations
+    pub fn metrics_at(&self, variations: &[(String, f32)]) -> FontMetrics {
+        let mut metrics = self.metrics;
+        if variations.is_empty() {
+            return metrics;
+        }
+        let Ok(font) = skrifa::FontRef::from_index(self.data.as_slice(), self.face_index()) else {
+            return metrics;
+        };
+        let Ok(mvar) = skrifa::raw::TableProvider::mvar(&font) else {
+            return metrics;
+        };
+        let location = skrifa::MetadataProvider::axes(&font)
+            .location(variations.iter().map(|(tag, value)| (tag.as_str(), *value)));
+        let delta = |tag: &[u8; 4]| {
+            mvar.metric_delta(skrifa::Tag::new(tag), location.coords())
+                .map_or(0, |delta| delta.to_f64().round() as i16)
+        };
+        let shift = |value: &mut i16, tag: &[u8; 4]| *value = value.saturating_add(delta(tag));
+
+        shift(&mut metrics.hhea.ascent, b"hasc");
+        shift(&mut metrics.hhea.descent, b"hdsc");
+        shift(&mut metrics.hhea.line_gap, b"hlgp");
+        if let Some(typo) = metrics.typo.as_mut() {
+            shift(&mut typo.ascent, b"tasc");
+            shift(&mut typo.descent, b"tdsc");
+            shift(&mut typo.line_gap, b"tlgp");
+        }
+        // The main line metrics come from the typo table when there is one,
+        // as in `from_shared`, and follow its deltas
+        let line = metrics.typo.unwrap_or(metrics.hhea);
+        metrics.ascent = line.ascent;
+        metrics.descent = line.descent;
+        metrics.line_gap = line.line_gap;
+        if let Some(win) = metrics.win.as_mut() {
+            shift(&mut win.ascent, b"hcla");
+            // usWinDescent is positive; the stored descent is negated
+            win.descent = win.descent.saturating_sub(delta(b"hcld"));
+        }
+        if let Some(x_height) = metrics.x_height.as_mut() {
+            shift(x_height, b"xhgt");
+        }
+        if let Some(cap_height) = metrics.cap_height.as_mut() {
+            shift(cap_height, b"cpht");
+        }
+        for (script, tags) in [
+            (
+                metrics.subscript.as_mut(),
+                [b"sbxs", b"sbys", b"sbxo", b"sbyo"],
+            ),
+            (
+                metrics.superscript.as_mut(),
+                [b"spxs", b"spys", b"spxo", b"spyo"],
+            ),
+        ] {
+            if let Some(script) = script {
+                shift(&mut script.x_size, tags[0]);
+                shift(&mut script.y_size, tags[1]);
+                shift(&mut script.x_offset, tags[2]);
+                shift(&mut script.y_offset, tags[3]);
+            }
+        }
+        metrics
+    }
+
+    /// Baselines for text in `script`, from the `BASE` table where it has them.
+    ///
+    /// `script` is an ISO 15924 or OpenType script tag; `None` gives the
+    /// font-wide baselines also found in [`FontMetrics::baselines`].
+    pub fn baselines(&self, script: Option<&str>) -> Baselines {
+        match (script, self.font_ref()) {
+            (Some(script), Some(font)) => read_baselines(
+                &font,
+                Some(script),
+                self.metrics.ascent,
+                self.metrics.descent,
+            ),
+            _ => self.metrics.baselines,
+        }
+    }
+
+    /// The characters this face has glyphs for, read from its `cmap` on
+    /// first use and kept for the life of the face.
+    pub fn coverage(&self) -> &Coverage {
+        self.coverage
+            .get_or_init(|| Coverage::read(self.data.as_slice(), self.face_index()))
+    }
+
     pub fn glyph_id(&self, ch: char) -> Option<u32> {
         self.font_ref()
             .and_then(|font| font.cmap().ok()?.map_codepoint(ch).map(|gid| gid.to_u32()))
@@ -170,6 +409,73 @@ impl TypfFontFace {
             .and_then(|font| font.maxp().ok().map(|maxp| maxp.num_glyphs() as u32))
     }
 
+    /// Ligature caret positions from the GDEF `LigCaretList`, in font units.
+    ///
+    /// Coordinate carets (formats 1 and 3) are returned as stored. Contour
+    /// point carets (format 2) need the hinted outline, so a glyph that uses
+    /// them returns an empty list and callers fall back to even spacing.
+    pub fn ligature_carets(&self, glyph_id: u32) -> Vec<f32> {
+        self.font_ref()
+            .and_then(|font| font.gdef().ok())
+            .map(|gdef| lig_carets(&gdef, glyph_id))
+            .unwrap_or_default()
+    }
+
+    /// Legacy `kern` table adjustment between two glyphs, normalized to a
+    /// 1000-unit em like [`advance_width`][Self::advance_width].
+    ///
+    /// Returns 0.0 for fonts without a `kern` table; GPOS kerning is left to
+    /// the shapers that run it.
+    ///
+    /// The table is read on the first call and kept with the face.
+    pub fn kerning(&self, left: u32, right: u32) -> f32 {
+        let kern = self.kern.get_or_init(|| {
+            self.font_ref()
+                .and_then(|font| font.kern().ok())
+                .map(|kern| KernTable::new(&kern))
+                .unwrap_or_default()
+        });
+        kern.pair(left, right) as f32 / self.units_per_em as f32 * 1000.0
+    }
+
+    /// Describe this face for font pickers: names, weight, style, stretch, axes.
+    pub fn face_info(&self) -> FaceInfo {
+        let font = self.font_ref();
+        let name = |ids: &[NameId]| font.as_ref().and_then(|f| read_name(f, ids));
+        let os2 = font.as_ref().and_then(|f| f.os2().ok());
+        let selection = os2.as_ref().map(|os2| os2.fs_selection());
+        let mac_italic = font
+            .as_ref()
+            .and_then(|f| f.head().ok())
+            .is_some_and(|head| head.mac_style().contains(MacStyle::ITALIC));
+
+        let style = match selection {
+            Some(flags) if flags.contains(SelectionFlags::OBLIQUE) => FontStyle::Oblique,
+            Some(flags) if flags.contains(SelectionFlags::ITALIC) => FontStyle::Italic,
+            _ if mac_italic => FontStyle::Italic,
+            _ => FontStyle::Normal,
+        };
+
+        FaceInfo {
+            family: name(&[NameId::TYPOGRAPHIC_FAMILY_NAME, NameId::FAMILY_NAME])
+                .unwrap_or_default(),
+            style_name: name(&[NameId::TYPOGRAPHIC_SUBFAMILY_NAME, NameId::SUBFAMILY_NAME])
+                .unwrap_or_else(|| "Regular".to_string()),
+            postscript_name: name(&[NameId::POSTSCRIPT_NAME]),
+            path: self.source.path().map(Path::to_path_buf),
+            face_index: self.source.face_index(),
+            weight: os2
+                .as_ref()
+                .map_or(400, |os2| os2.us_weight_class().clamp(1, 1000)),
+            style,
+            stretch: os2
+                .as_ref()
+                .and_then(|os2| FontStretch::from_width_class(os2.us_width_class()))
+                .unwrap_or_default(),
+            axes: self.variation_axes().unwrap_or_default(),
+        }
+    }
+
     /// Returns variable font axes from the fvar table.
     pub fn variation_axes(&self) -> Option<Vec<VariationAxis>> {
         let font = self.font_ref()?;
@@ -209,6 +515,224 @@ impl TypfFontFace {
     }
 }
 
+/// Horizontal baselines for `script`: the `BASE` table's values where it has
+/// them, synthesized ones elsewhere. Math falls back to half the x-height.
+fn read_baselines(
+    font: &ReadFontRef<'_>,
+    script: Option<&str>,
+    ascent: i16,
+    descent: i16,
+) -> Baselines {
+    let mut baselines = Baselines::synthesized(ascent, descent);
+    if let Some(x_height) = font.os2().ok().and_then(|os2| os2.sx_height()) {
+        if x_height > 0 {
+            baselines.math = x_height / 2;
+        }
+    }
+
+    if let Ok(base) = font.base() {
+        apply_base(&base, script, &mut baselines);
+    }
+    baselines
+}
+
+/// Overwrite `baselines` with the horizontal-axis values `base` gives `script`.
+///
+/// Without a script, or when the table has no record for it, the `DFLT`
+/// record is used, then `latn`, then whichever comes first.
+fn apply_base(base: &Base<'_>, script: Option<&str>, baselines: &mut Baselines) {
+    let Some(axis) = base.horiz_axis().and_then(|axis| axis.ok()) else {
+        return;
+    };
+    let Some(tags) = axis.base_tag_list().and_then(|list| list.ok()) else {
+        return;
+    };
+    let Ok(scripts) = axis.base_script_list() else {
+        return;
+    };
+    let records = scripts.base_script_records();
+    let find = |tag: &str| {
+        records.iter().find(|record| {
+            record
+                .base_script_tag()
+                .to_string()
+                .eq_ignore_ascii_case(tag)
+        })
+    };
+    let record = script
+        .and_then(|script| find(script.trim()))
+        .or_else(|| find("DFLT"))
+        .or_else(|| find("latn"))
+        .or_else(|| records.first());
+    let Some(values) = record
+        .and_then(|record| record.base_script(scripts.offset_data()).ok())
+        .and_then(|script| script.base_values()?.ok())
+    else {
+        return;
+    };
+
+    for (tag, coord) in tags.baseline_tags().iter().zip(values.base_coords().iter()) {
+        if let (Some(baseline), Ok(coord)) = (Baseline::from_tag(&tag.get().to_string()), coord) {
+            baselines.set(baseline, coord.coordinate());
+        }
+    }
+}
+
+/// Coordinate carets of a ligature glyph in a GDEF `LigCaretList`, in font
+/// units; empty when the glyph has no entry or any caret is a contour point
+fn lig_carets(gdef: &Gdef<'_>, glyph_id: u32) -> Vec<f32> {
+    use read_fonts::tables::gdef::CaretValue;
+    use read_fonts::types::GlyphId;
+
+    let carets = || -> Option<Vec<f32>> {
+        let list = gdef.lig_caret_list()?.ok()?;
+        let index = list.coverage().ok()?.get(GlyphId::new(glyph_id))?;
+        let lig_glyph = list.lig_glyphs().get(index as usize).ok()?;
+        lig_glyph
+            .caret_values()
+            .iter()
+            .map(|caret| match caret.ok()? {
+                CaretValue::Format1(c) => Some(c.coordinate() as f32),
+                CaretValue::Format3(c) => Some(c.coordinate() as f32),
+                CaretValue::Format2(_) => None,
+            })
+            .collect()
+    };
+    carets().unwrap_or_default()
+}
+
+/// The horizontal `kern` subtables of a face, copied out of the font data
+///
+/// State-machine (format 1), cross-stream, and variation subtables are
+/// skipped; they need more context than a single pair.
+#[derive(Debug, Default)]
+struct KernTable {
+    /// Format 0 pairs, summed across subtables
+    pairs: HashMap<(u32, u32), i32>,
+    /// Class-based subtables (formats 2 and 3)
+    classes: Vec<KernClasses>,
+}
+
+impl KernTable {
+    fn new(kern: &Kern<'_>) -> Self {
+        use read_fonts::tables::kern::SubtableKind;
+        use read_fonts::types::GlyphId;
+
+        let mut table = Self::default();
+        let subtables = kern
+            .subtables()
+            .filter_map(|subtable| subtable.ok())
+            .filter(|s| s.is_horizontal() && !s.is_cross_stream() && !s.is_variable());
+        for subtable in subtables {
+            match subtable.kind() {
+                Ok(SubtableKind::Format0(sub)) => {
+                    for pair in sub.pairs() {
+                        let key = (pair.left().to_u32(), pair.right().to_u32());
+                        *table.pairs.entry(key).or_default() += pair.value() as i32;
+                    }
+                },
+                Ok(SubtableKind::Format2(sub)) => {
+                    let offsets = |classes: &Subtable2ClassTable<'_>| {
+                        let keys = classes.offsets().iter().map(|offset| offset.get());
+                        (classes.first_glyph().to_u32(), keys.collect::<Vec<_>>())
+                    };
+                    table.classes.push(KernClasses::new(
+                        offsets(&sub.left_offset_table),
+                        offsets(&sub.right_offset_table),
+                        |left, right| sub.kerning(GlyphId::new(left), GlyphId::new(right)),
+                    ));
+                },
+                Ok(SubtableKind::Format3(sub)) => {
+                    let classes = |classes: &[u8]| (0, classes.iter().map(|&c| c as u16).collect());
+                    table.classes.push(KernClasses::new(
+                        classes(sub.left_class()),
+                        classes(sub.right_class()),
+                        |left, right| sub.kerning(GlyphId::new(left), GlyphId::new(right)),
+                    ));
+                },
+                Ok(SubtableKind::Format1(_)) | Err(_) => {},
+            }
+        }
+        table
+    }
+
+    /// Sum of the subtable values for a glyph pair, in font units
+    fn pair(&self, left: u32, right: u32) -> i32 {
+        let pair = self.pairs.get(&(left, right)).copied().unwrap_or(0);
+        let classes: i32 = self
+            .classes
+            .iter()
+            .filter_map(|classes| classes.pair(left, right))
+            .sum();
+        pair + classes
+    }
+}
+
+/// A class-based `kern` subtable as a left-class by right-class matrix
+#[derive(Debug)]
+struct KernClasses {
+    left_first: u32,
+    left: Vec<u16>,
+    right_first: u32,
+    right: Vec<u16>,
+    /// Row-major, one row per left class
+    values: Vec<i32>,
+    columns: usize,
+}
+
+impl KernClasses {
+    /// Group the glyphs starting at each `first` by their subtable class
+    /// key and read one value per pair of classes through `kerning`
+    fn new(
+        (left_first, left_keys): (u32, Vec<u16>),
+        (right_first, right_keys): (u32, Vec<u16>),
+        kerning: impl Fn(u32, u32) -> Option<i32>,
+    ) -> Self {
+        let classify = |first: u32, keys: Vec<u16>| {
+            let mut index = HashMap::new();
+            // The first glyph seen in each class stands in for the class
+            let mut members = Vec::new();
+            let classes = keys
+                .iter()
+                .enumerate()
+                .map(|(i, key)| {
+                    *index.entry(key).or_insert_with(|| {
+                        members.push(first + i as u32);
+                        (members.len() - 1) as u16
+                    })
+                })
+                .collect();
+            (classes, members)
+        };
+        let (left, left_members) = classify(left_first, left_keys);
+        let (right, right_members) = classify(right_first, right_keys);
+        let values = left_members
+            .iter()
+            .flat_map(|&l| right_members.iter().map(move |&r| (l, r)))
+            .map(|(l, r)| kerning(l, r).unwrap_or(0))
+            .collect();
+        Self {
+            left_first,
+            left,
+            right_first,
+            right,
+            values,
+            columns: right_members.len(),
+        }
+    }
+
+    fn pair(&self, left: u32, right: u32) -> Option<i32> {
+        let class = |first: u32, classes: &[u16], glyph: u32| {
+            classes
+                .get(glyph.checked_sub(first)? as usize)
+                .map(|&c| c as usize)
+        };
+        let l = class(self.left_first, &self.left, left)?;
+        let r = class(self.right_first, &self.right, right)?;
+        self.values.get(l * self.columns + r).copied()
+    }
+}
+
 impl TypfFontRef for TypfFontFace {
     fn data(&self) -> &[u8] {
         self.data.as_slice()
@@ -218,6 +742,10 @@ impl TypfFontRef for TypfFontFace {
         Some(self.data.clone())
     }
 
+    fn face_index(&self) -> u32 {
+        self.source.face_index
+    }
+
     fn units_per_em(&self) -> u16 {
         self.units_per_em
     }
@@ -226,6 +754,14 @@ impl TypfFontRef for TypfFontFace {
         Some(self.metrics)
     }
 
+    fn metrics_at(&self, variations: &[(String, f32)]) -> Option<FontMetrics> {
+        Some(self.metrics_at(variations))
+    }
+
+    fn baselines(&self, script: Option<&str>) -> Option<Baselines> {
+        Some(self.baselines(script))
+    }
+
     fn glyph_id(&self, ch: char) -> Option<u32> {
         self.glyph_id(ch)
     }
@@ -238,17 +774,79 @@ impl TypfFontRef for TypfFontFace {
         self.glyph_count()
     }
 
+    fn ligature_carets(&self, glyph_id: u32) -> Vec<f32> {
+        self.ligature_carets(glyph_id)
+    }
+
+    fn kerning(&self, left: u32, right: u32) -> f32 {
+        self.kerning(left, right)
+    }
+
     fn variation_axes(&self) -> Option<Vec<VariationAxis>> {
         self.variation_axes()
     }
 }
 
+/// First name-table string for any of `ids`, in order, preferring Windows
+/// English records over other platforms and languages.
+fn read_name(font: &ReadFontRef, ids: &[NameId]) -> Option<String> {
+    let name = font.name().ok()?;
+    let records = name.name_record();
+    ids.iter().find_map(|&id| {
+        let mut matching: Vec<_> = records.iter().filter(|r| r.name_id() == id).collect();
+        matching.sort_by_key(|r| (r.platform_id() != 3, r.language_id() != 0x409));
+        matching
+            .into_iter()
+            .find_map(|r| r.string(name.string_data()).ok())
+            .map(|s| s.to_string())
+            .filter(|s| !s.is_empty())
+    })
+}
+
+/// What changed in a [`FontDatabase`], as told to its subscribers.
+#[derive(Clone)]
+pub enum FontEvent {
+    /// A font file appeared in a watched directory and was loaded
+    Added(Arc<TypfFontFace>),
+    /// A font's file disappeared from a watched directory
+    Removed(Arc<TypfFontFace>),
+    /// A font's file changed on disk, and `old` was swapped for `new`
+    Changed {
+        old: Arc<TypfFontFace>,
+        new: Arc<TypfFontFace>,
+    },
+}
+
+impl FontEvent {
+    /// The face that stopped being current, whose cached work is now stale
+    pub fn stale(&self) -> Option<&Arc<TypfFontFace>> {
+        match self {
+            Self::Added(_) => None,
+            Self::Removed(old) | Self::Changed { old, .. } => Some(old),
+        }
+    }
+}
+
+/// A font file's modification time and size, as last seen by a rescan
+#[cfg(not(target_arch = "wasm32"))]
+type FileStamp = (Option<SystemTime>, u64);
+
+/// Callback run with every [`FontEvent`] of a database.
+pub type FontEventHook = Box<dyn Fn(&FontEvent) + Send + Sync>;
+
 /// Collection of loaded font faces and their source metadata.
 pub struct FontDatabase {
     fonts: Vec<Arc<TypfFontFace>>,
     sources: Vec<TypfFontSource>,
     path_cache: HashMap<(PathBuf, u32), Arc<TypfFontFace>>,
     default_font: Option<Arc<TypfFontFace>>,
+    font_dirs: Vec<PathBuf>,
+    #[cfg(not(target_arch = "wasm32"))]
+    file_stamps: HashMap<PathBuf, FileStamp>,
+    subscribers: Vec<FontEventHook>,
+    aliases: FamilyAliases,
+    #[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+    installed_coverage: coverage::InstalledCoverage,
 }
 
 impl FontDatabase {
@@ -259,31 +857,266 @@ impl FontDatabase {
             sources: Vec::new(),
             path_cache: HashMap::new(),
             default_font: None,
+            font_dirs: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            file_stamps: HashMap::new(),
+            subscribers: Vec::new(),
+            aliases: FamilyAliases::platform_defaults(),
+            #[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+            installed_coverage: coverage::InstalledCoverage::default(),
         }
     }
 
-    /// Load the first face from a file and reuse a cached copy when possible.
-    pub fn load_font(&mut self, path: impl AsRef<Path>) -> Result<Arc<TypfFontFace>> {
-        let path = path.as_ref();
+    /// Run `hook` with every later [`FontEvent`].
+    ///
+    /// Hooks run while the database is being updated, so they must not
+    /// call back into it.
+    pub fn subscribe(&mut self, hook: impl Fn(&FontEvent) + Send + Sync + 'static) {
+        self.subscribers.push(Box::new(hook));
+    }
 
-        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
-        let face_index = 0;
-        let cache_key = (canonical.clone(), face_index);
+    /// Run `hook` with the old face whenever [`reload`](Self::reload)
+    /// replaces one, e.g. to drop a render cache's entries for it.
+    pub fn on_reload(&mut self, hook: impl Fn(&TypfFontFace) + Send + Sync + 'static) {
+        self.subscribe(move |event| {
+            if let FontEvent::Changed { old, .. } = event {
+                hook(old);
+            }
+        });
+    }
 
-        if let Some(font) = self.path_cache.get(&cache_key) {
-            return Ok(font.clone());
+    /// Drop `cache`'s entries for every font that is replaced or removed.
+    pub fn subscribe_cache(&mut self, cache: Arc<dyn InvalidateFont>) {
+        self.subscribe(move |event| {
+            if let Some(old) = event.stale() {
+                cache.invalidate_font(old.data.as_slice());
+            }
+        });
+    }
+
+    fn emit(&self, event: &FontEvent) {
+        for hook in &self.subscribers {
+            hook(event);
+        }
+    }
+
+    /// Re-read every font loaded from a file, picking up files changed on disk.
+    ///
+    /// Faces whose bytes changed are swapped in place and reported as
+    /// [`FontEvent::Changed`]. Fonts loaded from memory, and files that no
+    /// longer load, keep their current face. Returns how many faces were
+    /// replaced.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reload(&mut self) -> usize {
+        let mut replaced = 0;
+        for slot in 0..self.fonts.len() {
+            let old = self.fonts[slot].clone();
+            let Some(path) = old.path() else {
+                continue;
+            };
+            let new = match TypfFontFace::from_file_index(path, old.face_index()) {
+                Ok(font) if font.data != old.data => Arc::new(font),
+                Ok(_) => continue,
+                Err(e) => {
+                    log::warn!("Keeping {} after failed reload: {e}", path.display());
+                    continue;
+                },
+            };
+            self.replace_face(slot, new);
+            replaced += 1;
         }
+        replaced
+    }
 
-        let font = Arc::new(TypfFontFace::from_file(path)?);
-        self.path_cache.insert(cache_key, font.clone());
+    /// Swap the face in `slot` for `new` wherever the old one is held and
+    /// tell the subscribers.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn replace_face(&mut self, slot: usize, new: Arc<TypfFontFace>) -> FontEvent {
+        let old = std::mem::replace(&mut self.fonts[slot], new.clone());
+        for cached in self.path_cache.values_mut() {
+            if Arc::ptr_eq(cached, &old) {
+                *cached = new.clone();
+            }
+        }
+        if self
+            .default_font
+            .as_ref()
+            .is_some_and(|font| Arc::ptr_eq(font, &old))
+        {
+            self.default_font = Some(new.clone());
+        }
+        let event = FontEvent::Changed { old, new };
+        self.emit(&event);
+        event
+    }
+
+    /// Add a face read from the file at `canonical`.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn push_file_face(&mut self, canonical: PathBuf, font: Arc<TypfFontFace>) {
+        let face_index = font.face_index();
+        self.path_cache
+            .insert((canonical.clone(), face_index), font.clone());
         self.fonts.push(font.clone());
         self.sources
             .push(TypfFontSource::new(Some(canonical), face_index));
 
         if self.default_font.is_none() {
-            self.default_font = Some(font.clone());
+            self.default_font = Some(font);
+        }
+    }
+
+    /// Keep the fonts in `dir` and its subdirectories loaded.
+    ///
+    /// The directory's font files are loaded now, and each
+    /// [`rescan`](Self::rescan) (or, with the `watch` feature, the watcher
+    /// from [`watch`](Self::watch)) picks up files added, removed, or changed
+    /// since.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_font_dir(&mut self, dir: impl AsRef<Path>) -> Vec<FontEvent> {
+        let dir = dir.as_ref();
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        if !self.font_dirs.contains(&dir) {
+            self.font_dirs.push(dir);
+        }
+        self.rescan()
+    }
+
+    /// Directories added with [`add_font_dir`](Self::add_font_dir).
+    pub fn font_dirs(&self) -> &[PathBuf] {
+        &self.font_dirs
+    }
+
+    /// Bring the database in line with the font directories on disk.
+    ///
+    /// New font files are loaded with all their faces, fonts whose files
+    /// are gone are dropped, and files whose modification time or size
+    /// changed since the last rescan are read again; other files are only
+    /// looked at, not read. Fonts outside the font directories are left
+    /// alone. Subscribers hear about each change, which is also returned.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn rescan(&mut self) -> Vec<FontEvent> {
+        let mut on_disk = Vec::new();
+        for dir in &self.font_dirs {
+            collect_font_files(dir, &mut on_disk);
+        }
+        let on_disk: Vec<(PathBuf, FileStamp)> = on_disk
+            .into_iter()
+            .filter_map(|path| {
+                let metadata = fs::metadata(&path).ok()?;
+                let canonical = path.canonicalize().unwrap_or(path);
+                Some((canonical, (metadata.modified().ok(), metadata.len())))
+            })
+            .collect();
+        let mut events = Vec::new();
+
+        // Gone from a font directory: forget the faces
+        let mut slot = 0;
+        while slot < self.fonts.len() {
+            let gone = self.sources[slot].path().is_some_and(|path| {
+                self.font_dirs.iter().any(|dir| path.starts_with(dir)) && !path.exists()
+            });
+            if !gone {
+                slot += 1;
+                continue;
+            }
+            let old = self.fonts.remove(slot);
+            self.sources.remove(slot);
+            self.path_cache.retain(|_, font| !Arc::ptr_eq(font, &old));
+            if self
+                .default_font
+                .as_ref()
+                .is_some_and(|font| Arc::ptr_eq(font, &old))
+            {
+                self.default_font = self.fonts.first().cloned();
+            }
+            let event = FontEvent::Removed(old);
+            self.emit(&event);
+            events.push(event);
+        }
+        self.file_stamps.retain(|path, _| path.exists());
+
+        // New or changed files: read them, skip the rest
+        for (path, stamp) in on_disk {
+            if self.file_stamps.get(&path) == Some(&stamp) {
+                continue;
+            }
+            events.extend(self.sync_file(&path));
+            self.file_stamps.insert(path, stamp);
+        }
+        events
+    }
+
+    /// Read every face of the font file at `canonical` once, swapping in
+    /// faces whose bytes changed and adding faces not loaded yet.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn sync_file(&mut self, canonical: &Path) -> Vec<FontEvent> {
+        let data = match collection::read_file(canonical) {
+            Ok(data) => Arc::new(data),
+            Err(e) => {
+                log::debug!("Skipping {}: {e}", canonical.display());
+                return Vec::new();
+            },
+        };
+        let mut events = Vec::new();
+        for font in collection::faces(&data, Some(canonical)) {
+            let key = (canonical.to_path_buf(), font.face_index());
+            let event = match self.path_cache.get(&key) {
+                Some(old) if old.data == font.data => continue,
+                Some(old) => {
+                    let old = old.clone();
+                    let Some(slot) = self.fonts.iter().position(|f| Arc::ptr_eq(f, &old)) else {
+                        continue;
+                    };
+                    self.replace_face(slot, Arc::new(font))
+                },
+                None => {
+                    let font = Arc::new(font);
+                    self.push_file_face(key.0, font.clone());
+                    let event = FontEvent::Added(font);
+                    self.emit(&event);
+                    event
+                },
+            };
+            events.push(event);
         }
+        events
+    }
+
+    /// Load the first face from a file and reuse a cached copy when possible.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_font(&mut self, path: impl AsRef<Path>) -> Result<Arc<TypfFontFace>> {
+        self.load_source(&FontSource::File(path.as_ref().to_path_buf()))
+    }
+
+    /// Load the face `source` points to and reuse a cached copy when possible.
+    ///
+    /// A collection member is looked up among the faces already loaded from
+    /// its file before the file is read again.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_source(&mut self, source: &FontSource) -> Result<Arc<TypfFontFace>> {
+        let path = source.path();
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
 
+        let cached = match source {
+            FontSource::File(_) => self.path_cache.get(&(canonical.clone(), 0)),
+            FontSource::CollectionIndex { index, .. } => {
+                self.path_cache.get(&(canonical.clone(), *index))
+            },
+            FontSource::CollectionMember { ps_name, .. } => self
+                .path_cache
+                .iter()
+                .find(|((cached_path, _), font)| {
+                    *cached_path == canonical
+                        && font.face_info().postscript_name.as_deref() == Some(ps_name)
+                })
+                .map(|(_, font)| font),
+        };
+        if let Some(font) = cached {
+            return Ok(font.clone());
+        }
+
+        let font = Arc::new(TypfFontFace::from_source(source)?);
+        self.push_file_face(canonical, font.clone());
         Ok(font)
     }
 
@@ -312,12 +1145,82 @@ impl FontDatabase {
         &self.sources
     }
 
-    /// Temporary lookup stub.
+    /// The regular face of the family `name`, matched like
+    /// [`query`][Self::query] with default descriptors.
+    pub fn find_font(&self, name: &str) -> Option<Arc<TypfFontFace>> {
+        self.query(&FontQuery::new(name)).map(|found| found.font)
+    }
+
+    /// Family names of the loaded fonts and, with the `system-fonts` feature,
+    /// the installed ones; sorted and without duplicates.
+    pub fn list_families(&self) -> Vec<String> {
+        let mut families: Vec<String> = self
+            .fonts
+            .iter()
+            .map(|font| font.face_info().family)
+            .filter(|family| !family.is_empty())
+            .collect();
+        #[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+        families.extend(system::family_names());
+
+        families.sort_by_key(|family| family.to_lowercase());
+        families.dedup_by(|a, b| query::family_key(a) == query::family_key(b));
+        families
+    }
+
+    /// Every face of `family` among the loaded fonts and, with the
+    /// `system-fonts` feature, the installed ones; the family matches as in
+    /// [`FontQuery::family`].
     ///
-    /// This currently returns the default font instead of performing a real
-    /// family-name search.
-    pub fn find_font(&self, _name: &str) -> Option<Arc<TypfFontFace>> {
-        self.default_font.clone()
+    /// Installed faces are read from their files, so this touches the disk
+    /// once per face; list families first and describe only the one picked.
+    pub fn list_faces(&self, family: &str) -> Vec<FaceInfo> {
+        let mut faces: Vec<FaceInfo> = self
+            .fonts
+            .iter()
+            .filter(|font| {
+                font.font_ref()
+                    .is_some_and(|f| query::has_family(&f, family))
+            })
+            .map(|font| font.face_info())
+            .collect();
+        #[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+        for face in system::family_faces(
+            system::installed_family(family)
+                .as_deref()
+                .unwrap_or(family),
+        ) {
+            let known = faces.iter().any(|info| {
+                info.path.as_deref() == Some(face.path.as_path())
+                    && (face.index.is_none() || face.index == Some(info.face_index))
+                    && (face.postscript_name.is_none()
+                        || face.postscript_name == info.postscript_name)
+            });
+            if known {
+                continue;
+            }
+            if let Some(mut info) = load_system_face(&face).map(|font| font.face_info()) {
+                // Group under the platform's name even if the file disagrees
+                info.family = face.family;
+                faces.push(info);
+            }
+        }
+
+        faces.sort_by(|a, b| {
+            (
+                a.stretch,
+                a.style != FontStyle::Normal,
+                a.weight,
+                &a.style_name,
+            )
+                .cmp(&(
+                    b.stretch,
+                    b.style != FontStyle::Normal,
+                    b.weight,
+                    &b.style_name,
+                ))
+        });
+        faces
     }
 
     pub fn clear(&mut self) {
@@ -332,6 +1235,49 @@ impl FontDatabase {
     }
 }
 
+/// Font files under `dir`, recursively, appended to `found`
+#[cfg(not(target_arch = "wasm32"))]
+fn collect_font_files(dir: &Path, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_font_files(&path, found);
+        } else if is_font_file(&path) {
+            found.push(path);
+        }
+    }
+}
+
+/// Whether `path` has a font file extension
+#[cfg(not(target_arch = "wasm32"))]
+fn is_font_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            ["ttf", "otf", "ttc", "otc"]
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// Read an installed face's file; collections without an index are searched
+/// for the face with the platform's PostScript name.
+#[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+fn load_system_face(face: &system::SystemFace) -> Option<TypfFontFace> {
+    if let Some(index) = face.index {
+        return TypfFontFace::from_file_index(&face.path, index).ok();
+    }
+
+    let data = Arc::new(fs::read(&face.path).ok()?);
+    match &face.postscript_name {
+        Some(name) => collection::find_member(&data, Some(&face.path), name),
+        None => collection::faces(&data, Some(&face.path)).next(),
+    }
+}
+
 impl Default for FontDatabase {
     fn default() -> Self {
         Self::new()
@@ -413,4 +1359,176 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_ligature_carets_when_not_a_ligature_then_empty() {
+        let font_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../test-fonts/NotoSans-Regular.ttf"
+        );
+        let Ok(font) = TypfFontFace::from_file(font_path) else {
+            return;
+        };
+
+        let glyph = font.glyph_id('a').unwrap_or(0);
+        assert!(font.ligature_carets(glyph).is_empty());
+        assert!(font.ligature_carets(u32::MAX >> 8).is_empty());
+    }
+
+    #[test]
+    fn test_lig_carets_when_gdef_lists_glyph_then_coordinates() {
+        use read_fonts::{FontData, FontRead};
+
+        let mut t = Vec::new();
+        // Header: version 1.0, LigCaretList at 12, no other subtables
+        // LigCaretList (12): coverage at +8, two LigGlyphs at +16 and +32
+        // Coverage: format 1, glyphs 100 and 101
+        // LigGlyph 100: format 1 at 300 and format 3 at 600 (no device)
+        // LigGlyph 101: format 1 at 250 and a format 2 contour point
+        for v in [1u16, 0, 0, 0, 12, 0]
+            .into_iter()
+            .chain([8, 2, 16, 32])
+            .chain([1, 2, 100, 101])
+            .chain([2, 6, 10, 1, 300, 3, 600, 0])
+            .chain([2, 6, 10, 1, 250, 2, 4])
+        {
+            t.extend(v.to_be_bytes());
+        }
+        let Ok(gdef) = Gdef::read(FontData::new(&t)) else {
+            panic!("hand-built GDEF table parses");
+        };
+
+        assert_eq!(lig_carets(&gdef, 100), [300.0, 600.0]);
+        assert!(
+            lig_carets(&gdef, 101).is_empty(),
+            "contour points need hinting"
+        );
+        assert!(lig_carets(&gdef, 36).is_empty());
+    }
+
+    /// A horizontal `BASE` axis with `hang` and `romn` values for two scripts
+    fn base_table() -> Vec<u8> {
+        fn u16s(t: &mut Vec<u8>, values: &[u16]) {
+            for v in values {
+                t.extend(v.to_be_bytes());
+            }
+        }
+        let mut t = Vec::new();
+        // Header: version 1.0, horizAxis at 8, no vertAxis
+        u16s(&mut t, &[1, 0, 8, 0]);
+        // Axis (8): tag list at +4, script list at +14
+        u16s(&mut t, &[4, 14]);
+        // BaseTagList (12): two tags
+        u16s(&mut t, &[2]);
+        t.extend(b"hangromn");
+        // BaseScriptList (22): deva at +14, latn at +36
+        u16s(&mut t, &[2]);
+        t.extend(b"deva");
+        u16s(&mut t, &[14]);
+        t.extend(b"latn");
+        u16s(&mut t, &[36]);
+        for (hang, romn) in [(600i16, 0i16), (560, -10)] {
+            // BaseScript: values at +6, no min/max, no lang systems
+            u16s(&mut t, &[6, 0, 0]);
+            // BaseValues: default index 0, two format 1 coords at +8 and +12
+            u16s(&mut t, &[0, 2, 8, 12]);
+            u16s(&mut t, &[1, hang as u16, 1, romn as u16]);
+        }
+        t
+    }
+
+    #[test]
+    fn test_apply_base_when_script_known_then_its_values() {
+        use read_fonts::{FontData, FontRead};
+
+        let data = base_table();
+        let Ok(base) = Base::read(FontData::new(&data)) else {
+            panic!("hand-built BASE table parses");
+        };
+        let synthesized = Baselines::synthesized(1000, -250);
+
+        let mut deva = synthesized;
+        apply_base(&base, Some("Deva"), &mut deva);
+        assert_eq!((deva.hanging, deva.alphabetic), (600, 0));
+        assert_eq!(deva.ideographic, synthesized.ideographic);
+
+        // No DFLT record and no `cyrl`, so `latn` stands in
+        let mut other = synthesized;
+        apply_base(&base, Some("cyrl"), &mut other);
+        assert_eq!((other.hanging, other.alphabetic), (560, -10));
+        assert_eq!(other.math, synthesized.math);
+    }
+
+    #[test]
+    fn test_kern_table_when_format0_then_pair_value() {
+        use read_fonts::{FontData, FontRead};
+
+        let mut t = Vec::new();
+        // Header: version 0, one subtable
+        // Subtable: version 0, length 26, format 0 horizontal; two pairs
+        for v in [0u16, 1, 0, 26, 0x0001, 2, 12, 1, 0] {
+            t.extend(v.to_be_bytes());
+        }
+        for (left, right, value) in [(36u16, 57u16, -80i16), (57, 36, -70)] {
+            t.extend(left.to_be_bytes());
+            t.extend(right.to_be_bytes());
+            t.extend(value.to_be_bytes());
+        }
+        let Ok(kern) = Kern::read(FontData::new(&t)) else {
+            panic!("hand-built kern table parses");
+        };
+
+        let table = KernTable::new(&kern);
+        assert_eq!(table.pair(36, 57), -80);
+        assert_eq!(table.pair(57, 36), -70);
+        assert_eq!(table.pair(36, 36), 0);
+    }
+
+    #[test]
+    fn test_kern_table_when_format3_then_class_value() {
+        use read_fonts::{FontData, FontRead};
+
+        let mut t = Vec::new();
+        // Header: version 0, one subtable
+        // Subtable: version 0, length 28, format 3 horizontal; four glyphs
+        for v in [0u16, 1, 0, 28, 0x0301, 4] {
+            t.extend(v.to_be_bytes());
+        }
+        // Two values, two left classes, two right classes, no flags
+        t.extend([2u8, 2, 2, 0]);
+        for value in [0i16, -50] {
+            t.extend(value.to_be_bytes());
+        }
+        // Glyph 1 is left class 1, glyph 2 right class 1; only (1, 1) kerns
+        t.extend([0u8, 1, 0, 0]);
+        t.extend([0u8, 0, 1, 0]);
+        t.extend([0u8, 0, 0, 1]);
+        let Ok(kern) = Kern::read(FontData::new(&t)) else {
+            panic!("hand-built kern table parses");
+        };
+
+        let table = KernTable::new(&kern);
+        assert_eq!(table.pair(1, 2), -50);
+        assert_eq!(table.pair(2, 1), 0);
+        assert_eq!(table.pair(1, 9), 0);
+    }
+
+    #[test]
+    fn test_baselines_when_no_base_table_then_synthesized() {
+        let font_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../test-fonts/NotoSans-Regular.ttf"
+        );
+        let Ok(font) = TypfFontFace::from_file(font_path) else {
+            return;
+        };
+        let metrics = font.metrics;
+        let baselines = metrics.baselines;
+        assert_eq!(baselines.alphabetic, 0);
+        assert_eq!(baselines.ideographic, metrics.descent.min(0));
+        assert!(baselines.hanging > 0 && baselines.hanging < metrics.ascent);
+        assert!(baselines.math > 0 && baselines.math < baselines.hanging);
+        assert_eq!(font.baselines(Some("Deva")), baselines);
+        assert_eq!(TypfFontRef::baselines(&font, None), Some(baselines));
+    }
 }
@@ -0,0 +1,138 @@
+//! What a font's license lets you do with it when embedding it in a
+//! document.
+//!
+//! The `OS/2` table's `fsType` field says whether the font may travel
+//! inside a PDF or SVG, and on what terms: installed on the reader's system,
+//! only for viewing and printing, or for editing too, whether it may be
+//! subset, and whether only its bitmaps may go. The `name` table carries the
+//! license text, its URL, and the copyright notice. Exporters that embed
+//! subsets can check [`TypfFontFace::embedding_permissions`] before
+//! writing.
+
+use read_fonts::{types::NameId, TableProvider};
+
+use crate::{read_name, TypfFontFace};
+
+/// How far a font may be embedded, from `fsType` bits 0–3
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmbeddingLevel {
+    /// May be embedded and installed permanently on the reader's system
+    Installable,
+    /// May not be embedded at all without the legal owner's permission
+    Restricted,
+    /// May be embedded to view and print the document, not to edit it
+    PreviewAndPrint,
+    /// May be embedded to view, print, and edit the document
+    Editable,
+}
+
+/// A font's embedding terms and license notices
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddingPermissions {
+    /// The raw `OS/2` `fsType` field; 0 when the font has no `OS/2` table
+    pub fs_type: u16,
+    /// What the usage bits allow
+    pub level: EmbeddingLevel,
+    /// Bit 8: the whole font must be embedded, not a subset
+    pub no_subsetting: bool,
+    /// Bit 9: only bitmap strikes may be embedded, not outlines
+    pub bitmap_only: bool,
+    /// License description (name ID 13)
+    pub license: Option<String>,
+    /// License URL (name ID 14)
+    pub license_url: Option<String>,
+    /// Copyright notice (name ID 0)
+    pub copyright: Option<String>,
+}
+
+impl EmbeddingPermissions {
+    /// Read the terms from `fsType`.
+    ///
+    /// Fonts from before `OS/2` version 3 may set several usage bits; as
+    /// the specification asks, the least restrictive one wins.
+    pub fn from_fs_type(fs_type: u16) -> Self {
+        let level = if fs_type & 0x0008 != 0 {
+            EmbeddingLevel::Editable
+        } else if fs_type & 0x0004 != 0 {
+            EmbeddingLevel::PreviewAndPrint
+        } else if fs_type & 0x0002 != 0 {
+            EmbeddingLevel::Restricted
+        } else {
+            EmbeddingLevel::Installable
+        };
+        Self {
+            fs_type,
+            level,
+            no_subsetting: fs_type & 0x0100 != 0,
+            bitmap_only: fs_type & 0x0200 != 0,
+            license: None,
+            license_url: None,
+            copyright: None,
+        }
+    }
+
+    /// Whether the font's outlines may be embedded in a document at all
+    pub fn allows_embedding(&self) -> bool {
+        self.level != EmbeddingLevel::Restricted && !self.bitmap_only
+    }
+
+    /// Whether a subset of the font, such as one from `typf-subset`, may be
+    /// embedded
+    pub fn allows_subsetting(&self) -> bool {
+        self.allows_embedding() && !self.no_subsetting
+    }
+}
+
+impl TypfFontFace {
+    /// The face's embedding terms from `OS/2` `fsType`, with the license
+    /// and copyright notices from its `name` table.
+    ///
+    /// A face without an `OS/2` table reads as installable: nothing
+    /// restricts it.
+    pub fn embedding_permissions(&self) -> EmbeddingPermissions {
+        let font = self.font_ref();
+        let fs_type = font
+            .as_ref()
+            .and_then(|f| f.os2().ok())
+            .map_or(0, |os2| os2.fs_type());
+        let name = |id: NameId| font.as_ref().and_then(|f| read_name(f, &[id]));
+        EmbeddingPermissions {
+            license: name(NameId::LICENSE_DESCRIPTION),
+            license_url: name(NameId::LICENSE_URL),
+            copyright: name(NameId::COPYRIGHT_NOTICE),
+            ..EmbeddingPermissions::from_fs_type(fs_type)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_fs_type_when_usage_bits_then_level() {
+        let installable = EmbeddingPermissions::from_fs_type(0);
+        assert_eq!(installable.level, EmbeddingLevel::Installable);
+        assert!(installable.allows_subsetting());
+
+        let restricted = EmbeddingPermissions::from_fs_type(0x0002);
+        assert_eq!(restricted.level, EmbeddingLevel::Restricted);
+        assert!(!restricted.allows_embedding());
+
+        // Least restrictive of several bits wins
+        let mixed = EmbeddingPermissions::from_fs_type(0x0002 | 0x0004 | 0x0008);
+        assert_eq!(mixed.level, EmbeddingLevel::Editable);
+    }
+
+    #[test]
+    fn test_from_fs_type_when_subset_or_bitmap_bits_then_flags() {
+        let whole = EmbeddingPermissions::from_fs_type(0x0004 | 0x0100);
+        assert_eq!(whole.level, EmbeddingLevel::PreviewAndPrint);
+        assert!(whole.allows_embedding());
+        assert!(!whole.allows_subsetting());
+
+        let bitmaps = EmbeddingPermissions::from_fs_type(0x0200);
+        assert!(bitmaps.bitmap_only);
+        assert!(!bitmaps.allows_embedding());
+    }
+}
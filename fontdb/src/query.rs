@@ -0,0 +1,483 @@
+//! Face selection by CSS-like descriptors.
+//!
+//! [`FontDatabase::query`] picks one face of a family the way browsers do
+//! (CSS Fonts Level 4, §5.2 "Matching font styles"): the family's faces are
+//! narrowed by stretch, then by style, then by weight, each step keeping only
+//! the faces nearest the request in the direction CSS prefers. Variable faces
+//! match any weight or width inside their `wght`/`wdth` axis ranges, and the
+//! result carries the axis values that realize the request.
+//!
+//! Family names match loosely: case, spaces, hyphens, and underscores are
+//! ignored, and every family name in the `name` table counts, in any
+//! language, so "Noto Naskh Arabic", "NotoNaskhArabic", and a face's
+//! Japanese family name all find it. [`FontDatabase::resolve`] turns a miss
+//! into [`FontLoadError::FontNotFound`], suggesting known families a few
+//! typos away. Generic families such as `"monospace"` go through the
+//! database's [`FamilyAliases`](crate::FamilyAliases).
+
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use read_fonts::{
+    tables::meta::{Metadata, DLNG, SLNG},
+    types::NameId,
+    FontRef as ReadFontRef, TableProvider,
+};
+use typf_core::{
+    error::{FontLoadError, Result},
+    types::{FontStretch, FontStyle},
+};
+
+use crate::{FaceInfo, FontDatabase, GenericFamily, TypfFontFace};
+
+/// What to look for in [`FontDatabase::query`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontQuery {
+    /// Family name, matched against every localized family name in the
+    /// font, ignoring case, spaces, hyphens, and underscores.
+    pub family: String,
+    /// Desired weight, 1 to 1000 (400 is regular, 700 bold).
+    pub weight: u16,
+    /// Desired width.
+    pub stretch: FontStretch,
+    /// Desired posture.
+    pub style: FontStyle,
+    /// BCP 47 language of the text, such as `"ja"`; among faces that match
+    /// equally well, ones declaring support in their `meta` table win.
+    pub language: Option<String>,
+}
+
+impl FontQuery {
+    /// Regular, normal-width, upright face of `family`.
+    pub fn new(family: impl Into<String>) -> Self {
+        Self {
+            family: family.into(),
+            weight: 400,
+            stretch: FontStretch::Normal,
+            style: FontStyle::Normal,
+            language: None,
+        }
+    }
+}
+
+/// The face [`FontDatabase::query`] settled on.
+#[derive(Clone)]
+pub struct FontMatch {
+    /// The chosen face.
+    pub font: Arc<TypfFontFace>,
+    /// Its description.
+    pub info: FaceInfo,
+    /// `wght`/`wdth` values to apply when the face is variable, clamped to
+    /// the axis ranges; empty for static faces.
+    pub variations: Vec<(String, f32)>,
+}
+
+/// Closed range a face covers on one descriptor.
+#[derive(Debug, Clone, Copy)]
+struct Range {
+    min: f32,
+    max: f32,
+}
+
+impl Range {
+    fn point(value: f32) -> Self {
+        Self {
+            min: value,
+            max: value,
+        }
+    }
+
+    /// The face's static value, widened by its variation axis if any
+    fn of(info: &FaceInfo, tag: &str, value: f32) -> Self {
+        info.axes
+            .iter()
+            .find(|axis| axis.tag == tag)
+            .map_or(Self::point(value), |axis| Self {
+                min: axis.min_value,
+                max: axis.max_value,
+            })
+    }
+
+    fn clamp(self, value: f32) -> f32 {
+        value.clamp(self.min, self.max)
+    }
+
+    /// CSS preference for this range: `(tier, distance)`, lower is better.
+    ///
+    /// Tier 0 contains the value; `prefer_lower` decides whether ranges
+    /// below or above the value come next.
+    fn rank(self, value: f32, prefer_lower: bool) -> (u8, f32) {
+        if self.min <= value && value <= self.max {
+            (0, 0.0)
+        } else if self.max < value {
+            (if prefer_lower { 1 } else { 2 }, value - self.max)
+        } else {
+            (if prefer_lower { 2 } else { 1 }, self.min - value)
+        }
+    }
+}
+
+/// Stretch preference: narrower first for normal and narrower requests
+fn stretch_rank(info: &FaceInfo, desired: FontStretch) -> (u8, f32) {
+    let desired = desired.percent();
+    Range::of(info, "wdth", info.stretch.percent()).rank(desired, desired <= 100.0)
+}
+
+/// Style preference order from the CSS fallback lists
+fn style_rank(face: FontStyle, desired: FontStyle) -> u8 {
+    use FontStyle::{Italic, Normal, Oblique};
+    let order = match desired {
+        Italic => [Italic, Oblique, Normal],
+        Oblique => [Oblique, Italic, Normal],
+        Normal => [Normal, Oblique, Italic],
+    };
+    order.iter().position(|&s| s == face).unwrap_or(order.len()) as u8
+}
+
+/// Weight preference; between 400 and 500 heavier faces up to 500 come
+/// first, then lighter ones, then anything heavier than 500
+fn weight_rank(info: &FaceInfo, desired: u16) -> (u8, f32) {
+    let range = Range::of(info, "wght", f32::from(info.weight));
+    let desired = f32::from(desired);
+    if (400.0..=500.0).contains(&desired) && range.min > desired {
+        return if range.min <= 500.0 {
+            (1, range.min - desired)
+        } else {
+            (3, range.min - desired)
+        };
+    }
+    range.rank(desired, desired < 400.0)
+}
+
+/// CSS order between two faces for `query`: stretch, then style, then weight
+fn compare(a: &FaceInfo, b: &FaceInfo, query: &FontQuery) -> Ordering {
+    let by_stretch = stretch_rank(a, query.stretch)
+        .partial_cmp(&stretch_rank(b, query.stretch))
+        .unwrap_or(Ordering::Equal);
+    let by_style = style_rank(a.style, query.style).cmp(&style_rank(b.style, query.style));
+    let by_weight = weight_rank(a, query.weight)
+        .partial_cmp(&weight_rank(b, query.weight))
+        .unwrap_or(Ordering::Equal);
+    by_stretch.then(by_style).then(by_weight)
+}
+
+/// `family` reduced to what family matching compares: lowercase, without
+/// whitespace, hyphens, or underscores
+pub(crate) fn family_key(family: &str) -> String {
+    family
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Whether any family name record (IDs 1, 16, and 21, any platform or
+/// language) matches `family`
+pub(crate) fn has_family(font: &ReadFontRef, family: &str) -> bool {
+    let Ok(name) = font.name() else {
+        return false;
+    };
+    let key = family_key(family);
+    name.name_record()
+        .iter()
+        .filter(|r| {
+            [
+                NameId::FAMILY_NAME,
+                NameId::TYPOGRAPHIC_FAMILY_NAME,
+                NameId::WWS_FAMILY_NAME,
+            ]
+            .contains(&r.name_id())
+        })
+        .filter_map(|r| r.string(name.string_data()).ok())
+        .any(|s| family_key(&s.to_string()) == key)
+}
+
+/// Levenshtein distance between `a` and `b`, in chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Whether the `meta` table lists `language` among the design or supported
+/// languages; a listed `ja` also covers `ja-JP`, and the reverse
+fn supports_language(font: &ReadFontRef, language: &str) -> bool {
+    let primary = |tag: &str| {
+        tag.split(['-', '_'])
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase()
+    };
+    let wanted = primary(language);
+    let Ok(meta) = font.meta() else {
+        return false;
+    };
+    meta.data_maps()
+        .iter()
+        .filter(|map| [DLNG, SLNG].contains(&map.tag()))
+        .filter_map(|map| map.data(meta.offset_data()).ok())
+        .any(|data| match data {
+            Metadata::ScriptLangTags(tags) => tags
+                .iter()
+                .filter_map(|tag| tag.ok())
+                .any(|tag| primary(tag.as_str()) == wanted),
+            Metadata::Other(_) => false,
+        })
+}
+
+impl FontDatabase {
+    /// Pick the face of `query.family` that best matches the requested
+    /// stretch, style, and weight, by the CSS font-matching rules.
+    ///
+    /// Loaded fonts are searched first; with the `system-fonts` feature the
+    /// installed faces of the family join them, read from disk on each call.
+    /// A generic family such as `"monospace"` that no font is literally
+    /// called resolves to the first of its
+    /// [`family_aliases`](Self::family_aliases) with a face. Returns `None`
+    /// when no face belongs to the family.
+    pub fn query(&self, query: &FontQuery) -> Option<FontMatch> {
+        self.query_family(query).or_else(|| {
+            let generic = GenericFamily::parse(&query.family)?;
+            self.aliases.get(generic).iter().find_map(|family| {
+                self.query_family(&FontQuery {
+                    family: family.clone(),
+                    ..query.clone()
+                })
+            })
+        })
+    }
+
+    /// [`query`](Self::query) without generic family aliases
+    fn query_family(&self, query: &FontQuery) -> Option<FontMatch> {
+        let candidates = self
+            .fonts
+            .iter()
+            .filter(|font| {
+                font.font_ref()
+                    .is_some_and(|f| has_family(&f, &query.family))
+            })
+            .cloned();
+        #[cfg(all(feature = "system-fonts", not(target_arch = "wasm32")))]
+        let candidates = candidates.chain(
+            crate::system::family_faces(
+                crate::system::installed_family(&query.family)
+                    .as_deref()
+                    .unwrap_or(&query.family),
+            )
+            .iter()
+            .filter_map(crate::load_system_face)
+            .map(Arc::new)
+            .collect::<Vec<_>>(),
+        );
+
+        let language = |font: &TypfFontFace| {
+            query.language.as_deref().is_some_and(|language| {
+                font.font_ref()
+                    .is_some_and(|f| supports_language(&f, language))
+            })
+        };
+        // Ties go to faces supporting the language, then to the earliest
+        let (font, info, _) = candidates
+            .map(|font| {
+                let info = font.face_info();
+                let speaks = language(&font);
+                (font, info, speaks)
+            })
+            .reduce(|best, next| {
+                let order = compare(&next.1, &best.1, query).then(best.2.cmp(&next.2));
+                if order == Ordering::Less {
+                    next
+                } else {
+                    best
+                }
+            })?;
+
+        let mut variations = Vec::new();
+        for axis in &info.axes {
+            let value = match axis.tag.as_str() {
+                "wght" => f32::from(query.weight),
+                "wdth" => query.stretch.percent(),
+                _ => continue,
+            };
+            let range = Range {
+                min: axis.min_value,
+                max: axis.max_value,
+            };
+            variations.push((axis.tag.clone(), range.clamp(value)));
+        }
+
+        Some(FontMatch {
+            font,
+            info,
+            variations,
+        })
+    }
+
+    /// [`query`](Self::query), failing with
+    /// [`FontLoadError::FontNotFound`] and the
+    /// [`suggest_families`](Self::suggest_families) for `query.family` when
+    /// no face belongs to the family.
+    pub fn resolve(&self, query: &FontQuery) -> Result<FontMatch> {
+        self.query(query).ok_or_else(|| {
+            FontLoadError::FontNotFound {
+                family: query.family.clone(),
+                suggestions: self.suggest_families(&query.family),
+            }
+            .into()
+        })
+    }
+
+    /// Up to three [`list_families`](Self::list_families) names close to
+    /// `family`, nearest first, for "did you mean" messages.
+    ///
+    /// Names are compared like family matching compares them, and count as
+    /// close within one edit per four characters, but at least two.
+    pub fn suggest_families(&self, family: &str) -> Vec<String> {
+        let key = family_key(family);
+        let limit = (key.chars().count() / 4).max(2);
+        let mut close: Vec<(usize, String)> = self
+            .list_families()
+            .into_iter()
+            .map(|name| (edit_distance(&key, &family_key(&name)), name))
+            .filter(|(distance, _)| *distance <= limit)
+            .collect();
+        close.sort();
+        close.into_iter().take(3).map(|(_, name)| name).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use typf_core::types::VariationAxis;
+
+    fn face(weight: u16, style: FontStyle, stretch: FontStretch) -> FaceInfo {
+        FaceInfo {
+            family: "Test".to_string(),
+            style_name: String::new(),
+            postscript_name: None,
+            path: None,
+            face_index: 0,
+            weight,
+            style,
+            stretch,
+            axes: Vec::new(),
+        }
+    }
+
+    /// Index of the best face, earliest first on ties
+    fn best(faces: &[FaceInfo], weight: u16, style: FontStyle, stretch: FontStretch) -> usize {
+        let query = FontQuery {
+            weight,
+            style,
+            stretch,
+            ..FontQuery::new("Test")
+        };
+        (0..faces.len())
+            .reduce(|best, i| {
+                if compare(&faces[i], &faces[best], &query) == Ordering::Less {
+                    i
+                } else {
+                    best
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_weight_when_between_400_and_500_then_prefers_up_to_500() {
+        let faces = [
+            face(300, FontStyle::Normal, FontStretch::Normal),
+            face(500, FontStyle::Normal, FontStretch::Normal),
+            face(700, FontStyle::Normal, FontStretch::Normal),
+        ];
+        assert_eq!(best(&faces, 400, FontStyle::Normal, FontStretch::Normal), 1);
+        // Without a 500, lighter beats heavier
+        let no_medium = [faces[0].clone(), faces[2].clone()];
+        assert_eq!(
+            best(&no_medium, 450, FontStyle::Normal, FontStretch::Normal),
+            0
+        );
+    }
+
+    #[test]
+    fn test_weight_when_bold_or_light_then_searches_outward() {
+        let faces = [
+            face(100, FontStyle::Normal, FontStretch::Normal),
+            face(400, FontStyle::Normal, FontStretch::Normal),
+            face(900, FontStyle::Normal, FontStretch::Normal),
+        ];
+        assert_eq!(best(&faces, 700, FontStyle::Normal, FontStretch::Normal), 2);
+        assert_eq!(best(&faces, 300, FontStyle::Normal, FontStretch::Normal), 0);
+    }
+
+    #[test]
+    fn test_style_when_italic_missing_then_oblique_before_normal() {
+        let faces = [
+            face(400, FontStyle::Normal, FontStretch::Normal),
+            face(400, FontStyle::Oblique, FontStretch::Normal),
+        ];
+        assert_eq!(best(&faces, 400, FontStyle::Italic, FontStretch::Normal), 1);
+        assert_eq!(best(&faces, 400, FontStyle::Normal, FontStretch::Normal), 0);
+    }
+
+    #[test]
+    fn test_stretch_when_ranked_before_style_and_weight() {
+        let faces = [
+            face(700, FontStyle::Italic, FontStretch::Normal),
+            face(400, FontStyle::Normal, FontStretch::Condensed),
+        ];
+        // Stretch narrows first, even though face 0 matches weight and style
+        assert_eq!(
+            best(&faces, 700, FontStyle::Italic, FontStretch::Condensed),
+            1
+        );
+        // Normal width prefers narrower over wider
+        let faces = [
+            face(400, FontStyle::Normal, FontStretch::Expanded),
+            face(400, FontStyle::Normal, FontStretch::Condensed),
+        ];
+        assert_eq!(best(&faces, 400, FontStyle::Normal, FontStretch::Normal), 1);
+    }
+
+    #[test]
+    fn test_family_key_when_spacing_or_case_differs_then_equal() {
+        let key = family_key("Noto Naskh Arabic");
+        assert_eq!(key, family_key("NotoNaskhArabic"));
+        assert_eq!(key, family_key("noto-naskh_arabic"));
+        assert_ne!(key, family_key("Noto Naskh"));
+    }
+
+    #[test]
+    fn test_edit_distance_when_typos_then_counts_edits() {
+        assert_eq!(edit_distance("notosans", "notosans"), 0);
+        assert_eq!(edit_distance("notosnas", "notosans"), 2);
+        assert_eq!(edit_distance("notosansarbic", "notosansarabic"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_weight_when_variable_axis_covers_request_then_exact() {
+        let mut variable = face(400, FontStyle::Normal, FontStretch::Normal);
+        variable.axes.push(VariationAxis {
+            tag: "wght".to_string(),
+            name: None,
+            min_value: 100.0,
+            default_value: 400.0,
+            max_value: 900.0,
+            hidden: false,
+        });
+        let faces = [face(700, FontStyle::Normal, FontStretch::Normal), variable];
+        assert_eq!(weight_rank(&faces[1], 650), (0, 0.0));
+        assert_eq!(best(&faces, 650, FontStyle::Normal, FontStretch::Normal), 1);
+    }
+}
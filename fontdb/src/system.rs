@@ -0,0 +1,300 @@
+//! Installed fonts, found through each platform's own font API.
+//!
+//! Every platform answers two questions: which families are installed, and
+//! which font files (and faces inside them) make up a family. fontdb answers
+//! them on Linux and other Unixes, DirectWrite on Windows, and CoreText on
+//! macOS, so the list matches what native apps show. Weight, style, stretch,
+//! and axes then come from the files themselves through
+//! [`TypfFontFace::face_info`](crate::TypfFontFace::face_info), so a face
+//! reads the same on every platform.
+
+use std::path::PathBuf;
+
+/// One installed face as the platform reports it.
+pub(crate) struct SystemFace {
+    /// Family the platform lists the face under
+    pub family: String,
+    pub path: PathBuf,
+    /// Face inside a collection; `None` when the platform names the face
+    /// by PostScript name instead
+    pub index: Option<u32>,
+    pub postscript_name: Option<String>,
+}
+
+/// The installed family named `family` up to case, spaces, hyphens, and
+/// underscores, spelled the way the platform lists it
+pub(crate) fn installed_family(family: &str) -> Option<String> {
+    let key = crate::query::family_key(family);
+    family_names()
+        .into_iter()
+        .find(|name| crate::query::family_key(name) == key)
+}
+
+/// Every installed face, each listed once
+pub(crate) fn installed_faces() -> Vec<SystemFace> {
+    let mut families = family_names();
+    families.sort_by_key(|name| crate::query::family_key(name));
+    families.dedup_by(|a, b| crate::query::family_key(a) == crate::query::family_key(b));
+
+    let mut faces: Vec<SystemFace> = families
+        .iter()
+        .flat_map(|family| family_faces(family))
+        .collect();
+    faces.sort_by(|a, b| {
+        (&a.path, a.index, &a.postscript_name).cmp(&(&b.path, b.index, &b.postscript_name))
+    });
+    faces.dedup_by(|a, b| {
+        (&a.path, a.index, &a.postscript_name) == (&b.path, b.index, &b.postscript_name)
+    });
+    faces
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) use self::coretext::{family_faces, family_names};
+#[cfg(windows)]
+pub(crate) use self::directwrite::{family_faces, family_names};
+#[cfg(not(any(windows, target_os = "macos")))]
+pub(crate) use self::fontdb_source::{family_faces, family_names};
+
+#[cfg(not(any(windows, target_os = "macos")))]
+mod fontdb_source {
+    use std::sync::OnceLock;
+
+    use super::SystemFace;
+
+    /// Scanned once per process; fontconfig directories rarely change under us
+    fn database() -> &'static fontdb::Database {
+        static DATABASE: OnceLock<fontdb::Database> = OnceLock::new();
+        DATABASE.get_or_init(|| {
+            let mut db = fontdb::Database::new();
+            db.load_system_fonts();
+            db
+        })
+    }
+
+    /// First (English, when present) family name of a face
+    fn family_of(face: &fontdb::FaceInfo) -> Option<&str> {
+        face.families.first().map(|(name, _)| name.as_str())
+    }
+
+    pub(crate) fn family_names() -> Vec<String> {
+        database()
+            .faces()
+            .filter_map(family_of)
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub(crate) fn family_faces(family: &str) -> Vec<SystemFace> {
+        let key = crate::query::family_key(family);
+        database()
+            .faces()
+            .filter_map(|face| {
+                let name = family_of(face)?;
+                // Any localized name of the face selects it
+                if !face
+                    .families
+                    .iter()
+                    .any(|(name, _)| crate::query::family_key(name) == key)
+                {
+                    return None;
+                }
+                let fontdb::Source::File(path) = &face.source else {
+                    return None;
+                };
+                Some(SystemFace {
+                    family: name.to_string(),
+                    path: path.clone(),
+                    index: Some(face.index),
+                    postscript_name: Some(face.post_script_name.clone()),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(windows)]
+mod directwrite {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use std::path::PathBuf;
+
+    use windows::core::{w, Interface, PCWSTR};
+    use windows::Win32::Foundation::{BOOL, FALSE};
+    use windows::Win32::Graphics::DirectWrite::{
+        DWriteCreateFactory, IDWriteFactory, IDWriteFont, IDWriteFontCollection,
+        IDWriteLocalFontFileLoader, IDWriteLocalizedStrings, DWRITE_FACTORY_TYPE_SHARED,
+        DWRITE_FONT_SIMULATIONS_NONE,
+    };
+
+    use super::SystemFace;
+
+    fn collection() -> Option<IDWriteFontCollection> {
+        unsafe {
+            let factory: IDWriteFactory = DWriteCreateFactory(DWRITE_FACTORY_TYPE_SHARED).ok()?;
+            let mut collection = None;
+            factory
+                .GetSystemFontCollection(&mut collection, FALSE)
+                .ok()?;
+            collection
+        }
+    }
+
+    /// The en-us string, or the first one when there is none
+    fn localized(strings: &IDWriteLocalizedStrings) -> Option<String> {
+        unsafe {
+            let mut index = 0;
+            let mut exists = BOOL::default();
+            strings
+                .FindLocaleName(w!("en-us"), &mut index, &mut exists)
+                .ok()?;
+            if !exists.as_bool() {
+                index = 0;
+            }
+            let len = strings.GetStringLength(index).ok()?;
+            let mut buf = vec![0u16; len as usize + 1];
+            strings.GetString(index, &mut buf).ok()?;
+            Some(String::from_utf16_lossy(&buf[..len as usize]))
+        }
+    }
+
+    /// File path and face index behind a font, for fonts on local files
+    fn font_file(font: &IDWriteFont) -> Option<(PathBuf, u32)> {
+        unsafe {
+            let face = font.CreateFontFace().ok()?;
+            let mut count = 0;
+            face.GetFiles(&mut count, None).ok()?;
+            let mut files = vec![None; count as usize];
+            face.GetFiles(&mut count, Some(files.as_mut_ptr())).ok()?;
+            let file = files.into_iter().next()??;
+
+            let mut key = std::ptr::null_mut();
+            let mut key_size = 0;
+            file.GetReferenceKey(&mut key, &mut key_size).ok()?;
+            let loader: IDWriteLocalFontFileLoader = file.GetLoader().ok()?.cast().ok()?;
+            let len = loader.GetFilePathLengthFromKey(key, key_size).ok()?;
+            let mut buf = vec![0u16; len as usize + 1];
+            loader.GetFilePathFromKey(key, key_size, &mut buf).ok()?;
+            let path = OsString::from_wide(&buf[..len as usize]);
+            Some((PathBuf::from(path), face.GetIndex()))
+        }
+    }
+
+    pub(crate) fn family_names() -> Vec<String> {
+        let Some(collection) = collection() else {
+            return Vec::new();
+        };
+        unsafe {
+            (0..collection.GetFontFamilyCount())
+                .filter_map(|i| {
+                    let family = collection.GetFontFamily(i).ok()?;
+                    localized(&family.GetFamilyNames().ok()?)
+                })
+                .collect()
+        }
+    }
+
+    pub(crate) fn family_faces(family: &str) -> Vec<SystemFace> {
+        let Some(collection) = collection() else {
+            return Vec::new();
+        };
+        let wide: Vec<u16> = family.encode_utf16().chain([0]).collect();
+        unsafe {
+            let mut index = 0;
+            let mut exists = BOOL::default();
+            if collection
+                .FindFamilyName(PCWSTR(wide.as_ptr()), &mut index, &mut exists)
+                .is_err()
+                || !exists.as_bool()
+            {
+                return Vec::new();
+            }
+            let Ok(font_family) = collection.GetFontFamily(index) else {
+                return Vec::new();
+            };
+            let name = font_family
+                .GetFamilyNames()
+                .ok()
+                .and_then(|names| localized(&names))
+                .unwrap_or_else(|| family.to_string());
+
+            (0..font_family.GetFontCount())
+                .filter_map(|i| {
+                    let font = font_family.GetFont(i).ok()?;
+                    // Bold and oblique simulations are not faces of their own
+                    if font.GetSimulations() != DWRITE_FONT_SIMULATIONS_NONE {
+                        return None;
+                    }
+                    let (path, index) = font_file(&font)?;
+                    Some(SystemFace {
+                        family: name.clone(),
+                        path,
+                        index: Some(index),
+                        postscript_name: None,
+                    })
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod coretext {
+    use std::path::PathBuf;
+
+    use objc2_core_foundation::{CFArray, CFDictionary, CFRetained, CFString, CFType, CFURL};
+    use objc2_core_text::{
+        kCTFontFamilyNameAttribute, kCTFontNameAttribute, kCTFontURLAttribute, CTFontDescriptor,
+        CTFontManagerCopyAvailableFontFamilyNames,
+    };
+
+    use super::SystemFace;
+
+    pub(crate) fn family_names() -> Vec<String> {
+        let names = unsafe { CTFontManagerCopyAvailableFontFamilyNames() };
+        let names = unsafe { CFRetained::cast_unchecked::<CFArray<CFString>>(names) };
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    /// A descriptor attribute, if present and of type `T`
+    fn attribute<T: objc2_core_foundation::Type>(
+        desc: &CTFontDescriptor,
+        key: &CFString,
+    ) -> Option<CFRetained<T>> {
+        let value: CFRetained<CFType> = unsafe { desc.attribute(key) }?;
+        value.downcast::<T>().ok()
+    }
+
+    pub(crate) fn family_faces(family: &str) -> Vec<SystemFace> {
+        let name = CFString::from_str(family);
+        let family_key: &CFString = unsafe { kCTFontFamilyNameAttribute };
+        let attrs = CFDictionary::<CFString, CFString>::from_slices(&[family_key], &[&*name]);
+        let attrs = unsafe { CFRetained::cast_unchecked::<CFDictionary>(attrs) };
+        let query = unsafe { CTFontDescriptor::with_attributes(&attrs) };
+
+        let Some(matches) = (unsafe { query.matching_font_descriptors(None) }) else {
+            return Vec::new();
+        };
+        let matches = unsafe { CFRetained::cast_unchecked::<CFArray<CTFontDescriptor>>(matches) };
+
+        matches
+            .iter()
+            .filter_map(|desc| {
+                let url: CFRetained<CFURL> = attribute(&desc, unsafe { kCTFontURLAttribute })?;
+                let path: PathBuf = url.to_file_path()?;
+                let family = attribute::<CFString>(&desc, family_key)
+                    .map(|f| f.to_string())
+                    .unwrap_or_else(|| family.to_string());
+                let postscript_name = attribute::<CFString>(&desc, unsafe { kCTFontNameAttribute })
+                    .map(|n| n.to_string());
+                // CoreText names faces, not collection indices
+                Some(SystemFace {
+                    family,
+                    path,
+                    index: None,
+                    postscript_name,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,60 @@
+//! Rescanning font directories as their files change.
+
+use std::sync::{Arc, RwLock, Weak};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use typf_core::error::{Result, TypfError};
+
+use crate::FontDatabase;
+
+/// Keeps a [`FontDatabase`] in step with its font directories.
+///
+/// Watching stops when this is dropped.
+pub struct FontWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl FontDatabase {
+    /// Rescan `db` whenever a file in one of its
+    /// [font directories](Self::add_font_dir) is created, removed, or
+    /// modified.
+    ///
+    /// Each rescan holds the write lock for the whole update, so readers
+    /// see the fonts either before or after it, never halfway; it only
+    /// reads the files whose modification time or size changed. The watcher
+    /// only holds a weak reference and goes quiet once `db` is dropped.
+    /// Directories added after this call are not watched.
+    pub fn watch(db: &Arc<RwLock<FontDatabase>>) -> Result<FontWatcher> {
+        let weak: Weak<RwLock<FontDatabase>> = Arc::downgrade(db);
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let relevant = event.is_ok_and(|event| {
+                    matches!(
+                        event.kind,
+                        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(_)
+                    )
+                });
+                if !relevant {
+                    return;
+                }
+                if let Some(db) = weak.upgrade() {
+                    if let Ok(mut db) = db.write() {
+                        db.rescan();
+                    }
+                }
+            })
+            .map_err(|e| TypfError::Other(format!("Cannot watch font directories: {e}")))?;
+
+        let dirs = db
+            .read()
+            .map_err(|_| TypfError::Other("Font database lock poisoned".into()))?
+            .font_dirs()
+            .to_vec();
+        for dir in &dirs {
+            watcher
+                .watch(dir, RecursiveMode::Recursive)
+                .map_err(|e| TypfError::Other(format!("Cannot watch {}: {e}", dir.display())))?;
+        }
+        Ok(FontWatcher { _watcher: watcher })
+    }
+}
@@ -4,8 +4,12 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use read_fonts::{FontRef as ReadFontRef, TableProvider};
-use typf_core::traits::FontRef;
-use typf_fontdb::TypfFontFace;
+use typf_core::{
+    error::{FontLoadError, TypfError},
+    traits::FontRef,
+    types::{FontStretch, FontStyle, LineMetricsPolicy},
+};
+use typf_fontdb::{EmbeddingLevel, FontDatabase, FontQuery, GenericFamily, TypfFontFace};
 
 fn repo_test_font_path(name: &str) -> PathBuf {
     // `CARGO_MANIFEST_DIR` is `<repo>/fontdb`; fonts live at `<repo>/test-fonts`.
@@ -71,6 +75,43 @@ fn test_metrics_when_loading_real_font_then_matches_read_fonts_tables() {
     );
 }
 
+#[test]
+fn test_metrics_when_loading_real_font_then_every_table_exposed() {
+    let font_path = repo_test_font_path("NotoSans-Regular.ttf");
+    let font = TypfFontFace::from_file(&font_path).expect("load test font");
+    let metrics = font.metrics().expect("TypfFontFace should expose metrics");
+
+    let data = std::fs::read(&font_path).expect("read test font bytes");
+    let read_font = ReadFontRef::from_index(&data, 0).expect("parse test font");
+    let hhea = read_font.hhea().expect("test font has hhea");
+    let os2 = read_font.os2().expect("test font has OS/2");
+
+    assert_eq!(metrics.hhea.ascent, hhea.ascender().to_i16());
+    assert_eq!(metrics.hhea.descent, hhea.descender().to_i16());
+    assert_eq!(metrics.hhea.line_gap, hhea.line_gap().to_i16());
+    let typo = metrics.typo.expect("typo metrics from OS/2");
+    assert_eq!(typo.ascent, os2.s_typo_ascender());
+    assert_eq!(typo.line_gap, os2.s_typo_line_gap());
+    let win = metrics.win.expect("win metrics from OS/2");
+    assert_eq!(win.ascent as u16, os2.us_win_ascent());
+    assert_eq!(-win.descent as u16, os2.us_win_descent());
+    assert_eq!(metrics.x_height, os2.sx_height());
+    assert_eq!(metrics.cap_height, os2.s_cap_height());
+    let superscript = metrics.superscript.expect("superscript metrics from OS/2");
+    assert_eq!(superscript.y_size, os2.y_superscript_y_size());
+    assert_eq!(superscript.y_offset, os2.y_superscript_y_offset());
+    let subscript = metrics.subscript.expect("subscript metrics from OS/2");
+    assert_eq!(subscript.y_offset, os2.y_subscript_y_offset());
+
+    let expected = if metrics.use_typo_metrics {
+        typo
+    } else {
+        metrics.hhea
+    };
+    assert_eq!(metrics.line_metrics(LineMetricsPolicy::Css), expected);
+    assert!(metrics.line_height(LineMetricsPolicy::Win, 16.0) > 0.0);
+}
+
 #[test]
 fn test_variation_axes_when_loading_variable_font_then_returns_axes() {
     let font_path = repo_test_font_path("Kalnia[wdth,wght].ttf");
@@ -124,3 +165,596 @@ fn test_is_variable_when_loading_static_font_then_returns_false() {
 
     assert!(!font_ref.is_variable(), "NotoSans should not be variable");
 }
+
+#[test]
+fn test_face_info_when_loading_variable_font_then_reports_axes() {
+    let font_path = repo_test_font_path("Kalnia[wdth,wght].ttf");
+    let font = TypfFontFace::from_file(&font_path).expect("load variable font");
+
+    let info = font.face_info();
+    assert!(!info.family.is_empty(), "family name should be read");
+    assert_eq!(info.path.as_deref(), Some(font_path.as_path()));
+    assert!(info.is_variable());
+    assert!(info.axes.iter().any(|axis| axis.tag == "wght"));
+    assert!((1..=1000).contains(&info.weight));
+}
+
+#[test]
+fn test_face_info_when_loading_static_font_then_upright_without_axes() {
+    let font_path = repo_test_font_path("NotoSans-Regular.ttf");
+    let font = TypfFontFace::from_file(&font_path).expect("load static font");
+
+    let info = font.face_info();
+    assert!(!info.is_variable());
+    assert_eq!(info.style, FontStyle::Normal);
+    assert_eq!(info.weight, 400);
+    assert_eq!(info.face_index, 0);
+}
+
+#[test]
+fn test_list_faces_when_fonts_loaded_then_grouped_by_family() {
+    let mut db = FontDatabase::new();
+    let static_font = db
+        .load_font(repo_test_font_path("NotoSans-Regular.ttf"))
+        .expect("load static font");
+    let family = static_font.face_info().family;
+
+    let families = db.list_families();
+    assert!(families.contains(&family));
+    let mut sorted = families.clone();
+    sorted.sort_by_key(|f| f.to_lowercase());
+    assert_eq!(families, sorted, "families should be sorted");
+
+    let faces = db.list_faces(&family.to_uppercase());
+    assert!(!faces.is_empty(), "family lookup should ignore case");
+    assert!(faces
+        .iter()
+        .all(|face| face.family.eq_ignore_ascii_case(&family)));
+    assert!(faces.iter().all(|face| !face.is_variable()));
+}
+
+#[test]
+fn test_list_faces_when_family_unknown_then_empty() {
+    let db = FontDatabase::new();
+    assert!(db.list_faces("No Such Family 7f3a").is_empty());
+}
+
+#[test]
+fn test_query_when_family_loaded_then_nearest_face_wins() {
+    let mut db = FontDatabase::new();
+    let font = db
+        .load_font(repo_test_font_path("NotoSans-Regular.ttf"))
+        .expect("load static font");
+    let family = font.face_info().family;
+
+    // Only a regular upright face exists, so a bold condensed italic
+    // request falls back to it
+    let query = FontQuery {
+        weight: 700,
+        stretch: FontStretch::Condensed,
+        style: FontStyle::Italic,
+        language: Some("en".to_string()),
+        ..FontQuery::new(family.to_lowercase())
+    };
+    let found = db.query(&query).expect("family is loaded");
+    assert!(Arc::ptr_eq(&found.font, &font));
+    assert!(
+        found.variations.is_empty(),
+        "static faces need no variations"
+    );
+
+    let regular = db.find_font(&family).expect("find by family name");
+    assert!(Arc::ptr_eq(&regular, &font));
+}
+
+#[test]
+fn test_query_when_family_unknown_then_none() {
+    let mut db = FontDatabase::new();
+    db.load_font(repo_test_font_path("NotoSans-Regular.ttf"))
+        .expect("load static font");
+
+    assert!(db.query(&FontQuery::new("No Such Family 7f3a")).is_none());
+    assert!(db.find_font("No Such Family 7f3a").is_none());
+}
+
+#[test]
+fn test_resolve_when_family_spelled_loosely_or_mistyped() {
+    let mut db = FontDatabase::new();
+    let font = db
+        .load_font(repo_test_font_path("NotoSans-Regular.ttf"))
+        .expect("load static font");
+    let family = font.face_info().family;
+
+    let squashed: String = family.split_whitespace().collect();
+    let found = db
+        .resolve(&FontQuery::new(squashed.to_uppercase()))
+        .expect("spacing and case are ignored");
+    assert!(Arc::ptr_eq(&found.font, &font));
+    assert!(!db.list_faces(&family.replace(' ', "-")).is_empty());
+
+    let typo = format!("{family}s");
+    match db.resolve(&FontQuery::new(typo.clone())) {
+        Err(TypfError::FontLoad(FontLoadError::FontNotFound {
+            family: missing,
+            suggestions,
+        })) => {
+            assert_eq!(missing, typo);
+            assert_eq!(suggestions.first(), Some(&family));
+        },
+        other => panic!("expected FontNotFound, got {:?}", other.map(|m| m.info)),
+    }
+    assert!(db.suggest_families("Wingdings").is_empty());
+}
+
+#[test]
+fn test_query_when_generic_family_then_first_alias_with_a_face() {
+    let mut db = FontDatabase::new();
+    let font = db
+        .load_font(repo_test_font_path("NotoSans-Regular.ttf"))
+        .expect("load static font");
+    let family = font.face_info().family;
+
+    db.set_family_aliases(
+        GenericFamily::Monospace,
+        ["No Such Mono 7f3a", family.as_str()],
+    );
+    let found = db.find_font("monospace").expect("second alias is loaded");
+    assert!(Arc::ptr_eq(&found, &font));
+
+    db.set_family_aliases(GenericFamily::Monospace, Vec::<String>::new());
+    assert!(db.find_font("monospace").is_none());
+}
+
+#[test]
+fn test_embedding_permissions_when_ofl_font_then_subsettable_with_license() {
+    let font = TypfFontFace::from_file(repo_test_font_path("NotoSans-Regular.ttf"))
+        .expect("load static font");
+    let permissions = font.embedding_permissions();
+
+    assert_eq!(permissions.level, EmbeddingLevel::Installable);
+    assert!(permissions.allows_subsetting());
+    let license = permissions.license.expect("Noto names its license");
+    assert!(license.contains("SIL Open Font License"), "{license}");
+    assert!(permissions.copyright.is_some());
+}
+
+#[test]
+fn test_coverage_when_font_loaded_then_agrees_with_cmap() {
+    let mut db = FontDatabase::new();
+    let font = db
+        .load_font(repo_test_font_path("NotoSans-Regular.ttf"))
+        .expect("load static font");
+
+    let coverage = font.coverage();
+    assert!(!coverage.is_empty());
+    for ch in ['A', 'z', '\u{00E9}', '\u{1F600}', '\u{0627}'] {
+        let mapped = font.glyph_id(ch).is_some_and(|gid| gid != 0);
+        assert_eq!(coverage.contains(ch), mapped, "coverage of {ch:?}");
+    }
+    assert!(std::ptr::eq(coverage, font.coverage()), "built once");
+
+    let covering = db.fonts_covering('A');
+    assert!(covering.iter().any(|face| Arc::ptr_eq(face, &font)));
+    assert!(db.fallback_fonts("Hello", &font).is_empty());
+}
+
+#[cfg(feature = "system-fonts")]
+#[test]
+fn test_fonts_covering_when_nothing_loaded_then_installed_faces_searched() {
+    let db = FontDatabase::new();
+    if db.list_families().is_empty() {
+        eprintln!("Skipping test: no installed fonts");
+        return;
+    }
+
+    let covering = db.fonts_covering('A');
+    assert!(!covering.is_empty(), "some installed face draws 'A'");
+    assert!(covering.iter().all(|face| face.coverage().contains('A')));
+    assert_eq!(
+        db.fonts_covering('A').len(),
+        covering.len(),
+        "cached coverage agrees"
+    );
+}
+
+#[test]
+fn test_features_when_loading_font_then_lists_layout_features_once() {
+    let font_path = repo_test_font_path("NotoSans-Regular.ttf");
+    let font = TypfFontFace::from_file(&font_path).expect("load static font");
+
+    let features = font.features();
+    assert!(!features.is_empty(), "font has GSUB/GPOS features");
+    let tags: Vec<&str> = features.iter().map(|f| f.tag.as_str()).collect();
+    let mut sorted = tags.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+    assert_eq!(tags, sorted, "one sorted entry per tag");
+
+    let kern = features.iter().find(|f| f.tag == "kern");
+    if let Some(kern) = kern {
+        assert!(kern.in_gpos);
+        assert_eq!(kern.name.as_deref(), Some("Kerning"));
+    }
+    assert!(features.iter().all(|f| f.in_gsub || f.in_gpos));
+}
+
+#[test]
+fn test_reload_when_file_changes_then_face_replaced_and_hooks_see_old() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let path = std::env::temp_dir().join(format!("typf-reload-{}.ttf", std::process::id()));
+    let original = std::fs::read(repo_test_font_path("NotoSans-Regular.ttf")).expect("read font");
+    let replacement =
+        std::fs::read(repo_test_font_path("Nabla-Regular-COLR.ttf")).expect("read font");
+    std::fs::write(&path, &original).expect("write font");
+
+    let mut db = FontDatabase::new();
+    let memory = db.load_font_data(replacement.clone()).expect("load bytes");
+    let loaded = db.load_font(&path).expect("load file");
+    let seen = Arc::new(AtomicUsize::new(0));
+    let hook_seen = seen.clone();
+    let expected = original.len();
+    db.on_reload(move |old| {
+        assert_eq!(old.data().len(), expected);
+        hook_seen.fetch_add(1, Ordering::Relaxed);
+    });
+
+    assert_eq!(db.reload(), 0, "unchanged files stay");
+    std::fs::write(&path, &replacement).expect("rewrite font");
+    assert_eq!(db.reload(), 1);
+
+    assert_eq!(seen.load(Ordering::Relaxed), 1);
+    assert!(!Arc::ptr_eq(&db.fonts()[1], &loaded));
+    assert_eq!(db.fonts()[1].data(), replacement.as_slice());
+    assert!(Arc::ptr_eq(&db.fonts()[0], &memory));
+    assert_eq!(
+        db.load_font(&path).map(|font| font.data().len()).ok(),
+        Some(replacement.len()),
+        "the path cache hands out the new face"
+    );
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_rescan_when_dir_changes_then_events_reach_caches() {
+    use std::sync::Mutex;
+    use typf_core::cache::InvalidateFont;
+    use typf_fontdb::FontEvent;
+
+    struct Dropped(Mutex<Vec<usize>>);
+    impl InvalidateFont for Dropped {
+        fn invalidate_font(&self, font_data: &[u8]) -> usize {
+            self.0.lock().expect("lock").push(font_data.len());
+            1
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!("typf-rescan-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("nested")).expect("create dir");
+    let original = std::fs::read(repo_test_font_path("NotoSans-Regular.ttf")).expect("read font");
+    let replacement =
+        std::fs::read(repo_test_font_path("Nabla-Regular-COLR.ttf")).expect("read font");
+    std::fs::write(dir.join("a.ttf"), &original).expect("write font");
+    std::fs::write(dir.join("notes.txt"), b"not a font").expect("write text");
+
+    let mut db = FontDatabase::new();
+    let dropped = Arc::new(Dropped(Mutex::new(Vec::new())));
+    db.subscribe_cache(dropped.clone());
+
+    let events = db.add_font_dir(&dir);
+    assert!(matches!(events.as_slice(), [FontEvent::Added(_)]));
+    assert_eq!(db.fonts().len(), 1);
+    assert!(db.rescan().is_empty(), "nothing changed");
+
+    std::fs::write(dir.join("nested/b.ttf"), &original).expect("write font");
+    assert!(matches!(db.rescan().as_slice(), [FontEvent::Added(_)]));
+    assert_eq!(db.fonts().len(), 2);
+
+    std::fs::write(dir.join("a.ttf"), &replacement).expect("rewrite font");
+    let events = db.rescan();
+    assert!(
+        matches!(events.as_slice(), [FontEvent::Changed { old, new }]
+            if old.data() == original.as_slice() && new.data() == replacement.as_slice())
+    );
+
+    std::fs::remove_file(dir.join("nested/b.ttf")).expect("remove font");
+    assert!(matches!(db.rescan().as_slice(), [FontEvent::Removed(_)]));
+    assert_eq!(db.fonts().len(), 1);
+    assert_eq!(db.fonts()[0].data(), replacement.as_slice());
+
+    assert_eq!(
+        *dropped.0.lock().expect("lock"),
+        vec![original.len(), original.len()],
+        "caches drop the replaced and the removed face"
+    );
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_rescan_when_collection_added_then_every_face_loaded() {
+    use typf_fontdb::FontEvent;
+
+    let first = std::fs::read(repo_test_font_path("NotoSans-Regular.ttf")).expect("read font");
+    let second = std::fs::read(repo_test_font_path("Nabla-Regular-COLR.ttf")).expect("read font");
+    let dir = std::env::temp_dir().join(format!("typf-rescan-ttc-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create dir");
+
+    let mut db = FontDatabase::new();
+    assert!(db.add_font_dir(&dir).is_empty());
+    std::fs::write(dir.join("pair.ttc"), build_collection(&[&first, &second]))
+        .expect("write collection");
+
+    let events = db.rescan();
+    assert!(
+        matches!(events.as_slice(), [FontEvent::Added(a), FontEvent::Added(b)]
+        if a.face_index() == 0 && b.face_index() == 1)
+    );
+    assert_eq!(db.fonts().len(), 2);
+    assert!(
+        db.rescan().is_empty(),
+        "an unchanged file is not read again"
+    );
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[cfg(feature = "watch")]
+#[test]
+fn test_watch_when_font_written_then_database_rescanned() {
+    use std::sync::{mpsc, RwLock};
+    use std::time::Duration;
+    use typf_fontdb::FontEvent;
+
+    let dir = std::env::temp_dir().join(format!("typf-watch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create dir");
+    let font = std::fs::read(repo_test_font_path("NotoSans-Regular.ttf")).expect("read font");
+
+    let db = Arc::new(RwLock::new(FontDatabase::new()));
+    let (added, events) = mpsc::channel();
+    {
+        let mut db = db.write().expect("lock");
+        db.add_font_dir(&dir);
+        db.subscribe(move |event| {
+            if let FontEvent::Added(font) = event {
+                added.send(font.data().len()).ok();
+            }
+        });
+    }
+    let _watcher = FontDatabase::watch(&db).expect("watch");
+
+    std::fs::write(dir.join("late.ttf"), &font).expect("write font");
+    assert_eq!(
+        events.recv_timeout(Duration::from_secs(10)).ok(),
+        Some(font.len()),
+        "the watcher loads the new file"
+    );
+    assert_eq!(db.read().expect("lock").fonts().len(), 1);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// A TTC holding `fonts` in order, each keeping its tables as they were
+fn build_collection(fonts: &[&[u8]]) -> Vec<u8> {
+    let num_tables = |font: &[u8]| u16::from_be_bytes([font[4], font[5]]) as usize;
+    let dir_len = |font: &[u8]| 12 + 16 * num_tables(font);
+
+    let mut out = b"ttcf".to_vec();
+    out.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    out.extend_from_slice(&(fonts.len() as u32).to_be_bytes());
+    let mut dir_offset = 12 + 4 * fonts.len();
+    for font in fonts {
+        out.extend_from_slice(&(dir_offset as u32).to_be_bytes());
+        dir_offset += dir_len(font);
+    }
+
+    let mut base = dir_offset;
+    let mut bodies = Vec::new();
+    for font in fonts {
+        let mut dir = font[..dir_len(font)].to_vec();
+        for table in 0..num_tables(font) {
+            let at = 12 + 16 * table + 8;
+            let offset = u32::from_be_bytes(dir[at..at + 4].try_into().unwrap());
+            dir[at..at + 4].copy_from_slice(&(offset + base as u32).to_be_bytes());
+        }
+        out.extend_from_slice(&dir);
+        bodies.extend_from_slice(font);
+        bodies.resize(bodies.len().next_multiple_of(4), 0);
+        base = dir_offset + bodies.len();
+    }
+    out.extend_from_slice(&bodies);
+    out
+}
+
+#[test]
+fn test_collection_member_when_selected_by_ps_name_then_resolved_and_cached() {
+    use typf_core::traits::{Renderer, Shaper};
+    use typf_core::types::RenderOutput;
+    use typf_core::{RenderParams, ShapingParams};
+    use typf_fontdb::FontSource;
+    use typf_render_opixa::OpixaRenderer;
+    use typf_shape_hr::HarfrustShaper;
+
+    let first = std::fs::read(repo_test_font_path("NotoSans-Regular.ttf")).expect("read font");
+    let second = std::fs::read(repo_test_font_path("Nabla-Regular-COLR.ttf")).expect("read font");
+    let ps_name = |data: Vec<u8>| {
+        TypfFontFace::from_data(data)
+            .expect("load font")
+            .face_info()
+            .postscript_name
+            .expect("PostScript name")
+    };
+    let (first_name, second_name) = (ps_name(first.clone()), ps_name(second.clone()));
+    let path = std::env::temp_dir().join(format!("typf-collection-{}.ttc", std::process::id()));
+    std::fs::write(&path, build_collection(&[&first, &second])).expect("write collection");
+
+    let faces = TypfFontFace::collection_faces(&path).expect("enumerate");
+    let names: Vec<_> = faces
+        .iter()
+        .map(|info| (info.face_index, info.postscript_name.clone()))
+        .collect();
+    assert_eq!(
+        names,
+        vec![(0, Some(first_name)), (1, Some(second_name.clone()))]
+    );
+    assert!(faces
+        .iter()
+        .all(|info| info.path.as_deref() == Some(path.as_path())));
+
+    let member = FontSource::CollectionMember {
+        path: path.clone(),
+        ps_name: second_name.clone(),
+    };
+    let face = TypfFontFace::from_source(&member).expect("resolve member");
+    assert_eq!(face.face_index(), 1);
+    assert_eq!(
+        face.face_info().family,
+        TypfFontFace::from_data(second.clone())
+            .expect("load font")
+            .face_info()
+            .family
+    );
+
+    let mut db = FontDatabase::new();
+    let by_name = db.load_source(&member).expect("load member");
+    let by_index = db
+        .load_source(&FontSource::CollectionIndex {
+            path: path.clone(),
+            index: 1,
+        })
+        .expect("load index");
+    assert!(Arc::ptr_eq(&by_name, &by_index));
+    assert!(Arc::ptr_eq(
+        &by_name,
+        &db.load_source(&member).expect("cached")
+    ));
+    assert_eq!(db.font_count(), 1);
+
+    // Backends read face 1 of the shared bytes, not the collection's first face
+    let shaper = HarfrustShaper::with_cache();
+    let renderer = OpixaRenderer::with_cache();
+    let params = ShapingParams {
+        size: 48.0,
+        ..ShapingParams::default()
+    };
+    let draw = |font: Arc<dyn FontRef>| {
+        let shaped = shaper.shape("Nabla", font.clone(), &params).expect("shape");
+        let ids: Vec<u32> = shaped.glyphs.iter().map(|glyph| glyph.id).collect();
+        match renderer
+            .render(&shaped, font, &RenderParams::default())
+            .expect("render")
+        {
+            RenderOutput::Bitmap(bitmap) => (ids, bitmap.width, bitmap.height, bitmap.data),
+            _ => panic!("expected a bitmap"),
+        }
+    };
+    let standalone = draw(Arc::new(
+        TypfFontFace::from_data(second).expect("load font"),
+    ));
+    assert_eq!(draw(by_name.clone()), standalone);
+    let first_face = TypfFontFace::from_source(&FontSource::CollectionIndex {
+        path: path.clone(),
+        index: 0,
+    })
+    .expect("load index 0");
+    assert_ne!(draw(Arc::new(first_face)).0, standalone.0);
+
+    let missing = FontSource::CollectionMember {
+        path: path.clone(),
+        ps_name: "NoSuchFace".to_string(),
+    };
+    assert!(matches!(
+        TypfFontFace::from_source(&missing),
+        Err(TypfError::FontLoad(FontLoadError::FaceNotFound(_, name))) if name == "NoSuchFace"
+    ));
+    std::fs::remove_file(&path).ok();
+}
+
+/// `font` with `extra` tables added, in a fresh table directory
+fn with_tables(font: &[u8], extra: Vec<([u8; 4], Vec<u8>)>) -> Vec<u8> {
+    let be32 = |at: usize| u32::from_be_bytes(font[at..at + 4].try_into().unwrap()) as usize;
+    let count = u16::from_be_bytes([font[4], font[5]]) as usize;
+    let mut tables: Vec<([u8; 4], Vec<u8>)> = (0..count)
+        .map(|i| {
+            let record = 12 + 16 * i;
+            let tag = font[record..record + 4].try_into().unwrap();
+            let (offset, len) = (be32(record + 8), be32(record + 12));
+            (tag, font[offset..offset + len].to_vec())
+        })
+        .collect();
+    tables.extend(extra);
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    let mut out = font[..4].to_vec();
+    out.extend_from_slice(&(tables.len() as u16).to_be_bytes());
+    out.extend_from_slice(&[0; 6]);
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, data) in &tables {
+        out.extend_from_slice(tag);
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in &tables {
+        out.extend_from_slice(data);
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+    out
+}
+
+/// A `wght` 100..400..900 axis, and `MVAR` raising `hasc` by 100, `tasc`
+/// by 60, and `xhgt` by 20 units at the heaviest weight
+fn weight_axis_tables() -> Vec<([u8; 4], Vec<u8>)> {
+    let words =
+        |values: &[u16]| -> Vec<u8> { values.iter().flat_map(|v| v.to_be_bytes()).collect() };
+
+    let mut fvar = words(&[1, 0, 16, 2, 1, 20, 0, 8]);
+    fvar.extend_from_slice(b"wght");
+    for value in [100u32, 400, 900] {
+        fvar.extend_from_slice(&(value << 16).to_be_bytes());
+    }
+    fvar.extend_from_slice(&words(&[0, 256]));
+
+    let mut mvar = words(&[1, 0, 0, 8, 3, 36]);
+    for (inner, tag) in [b"hasc", b"tasc", b"xhgt"].into_iter().enumerate() {
+        mvar.extend_from_slice(tag);
+        mvar.extend_from_slice(&words(&[0, inner as u16]));
+    }
+    // Item variation store: one region peaking at wght=max, three word deltas
+    mvar.extend_from_slice(&words(&[1, 0, 12, 1, 0, 22]));
+    mvar.extend_from_slice(&words(&[1, 1, 0, 0x4000, 0x4000]));
+    mvar.extend_from_slice(&words(&[3, 1, 1, 0, 100, 60, 20]));
+
+    vec![(*b"fvar", fvar), (*b"MVAR", mvar)]
+}
+
+#[test]
+fn test_metrics_at_when_mvar_present_then_deltas_applied() {
+    let data = std::fs::read(repo_test_font_path("NotoSans-Regular.ttf")).expect("read font");
+    let face = TypfFontFace::from_data(with_tables(&data, weight_axis_tables())).expect("load");
+    let base = face.metrics().expect("metrics");
+    let at = |weight: f32| face.metrics_at(&[("wght".to_string(), weight)]);
+
+    assert_eq!(at(400.0), base, "the default instance has no deltas");
+    assert_eq!(face.metrics_at(&[]), base);
+
+    let heavy = at(900.0);
+    assert_eq!(heavy.hhea.ascent, base.hhea.ascent + 100);
+    assert_eq!(
+        heavy.typo.map(|t| t.ascent),
+        base.typo.map(|t| t.ascent + 60)
+    );
+    assert!(base.typo.is_some(), "NotoSans has OS/2 typo metrics");
+    assert_eq!(heavy.ascent, base.ascent + 60, "follows the typo table");
+    assert_eq!(heavy.x_height, base.x_height.map(|h| h + 20));
+    assert_eq!(
+        (heavy.descent, heavy.line_gap),
+        (base.descent, base.line_gap)
+    );
+
+    assert_eq!(at(650.0).ascent, base.ascent + 30, "halfway to the peak");
+
+    let font_ref: &dyn FontRef = &face;
+    assert_eq!(
+        font_ref.metrics_at(&[("wght".to_string(), 900.0)]),
+        Some(heavy)
+    );
+}
@@ -16,7 +16,7 @@
 
 use libfuzzer_sys::fuzz_target;
 use std::sync::Arc;
-use typf_core::{Pipeline, RenderParams, ShapingParams, traits::{FontRef, Shaper, Renderer, Exporter}, types::{BitmapData, BitmapFormat, GlyphId, RenderOutput, ShapingResult}};
+use typf_core::{Pipeline, RenderParams, ShapingParams, traits::{FontRef, Shaper, Renderer, Exporter}, types::{BitmapData, BitmapFormat, PixelLayout, GlyphId, RenderOutput, ShapingResult}};
 
 /// Minimal shaper that never crashes but exercises pipeline logic
 struct FuzzShaper;
@@ -55,6 +55,8 @@ impl Renderer for FuzzRenderer {
             width: 100,
             height: 100,
             format: BitmapFormat::Rgba8,
+            stride: 400,
+            layout: PixelLayout::RGBA,
             data: vec![0; 100 * 100 * 4], // RGBA bitmap
         }))
     }
@@ -0,0 +1,92 @@
+//! Render text with an arbitrary font file
+//!
+//! `fuzz_font_parse` stops at table access. This target takes the bytes all
+//! the way through shaping and rasterization, where malformed outlines,
+//! absurd bounding boxes, recursive COLR paint graphs, and oversized bitmap
+//! strikes have to be survived, not just parsed.
+//!
+//! Every renderer runs twice: lenient (the default), which must degrade
+//! gracefully, and strict, which may fail but must not panic. A memory
+//! budget and a deadline keep a hostile font from exhausting the fuzzer
+//! instead of finding a bug.
+//!
+//! What we test:
+//! - Outline extraction and scan conversion in opixa, skia, and zeno
+//! - COLR v0/v1 painting, CBDT/sbix decoding, and SVG glyphs via the color path
+//! - Bounding-box clamping and glyph skipping in lenient mode
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::sync::Arc;
+use std::time::Duration;
+use typf_core::{
+    traits::{FontRef, Renderer, Shaper},
+    types::{Direction, PositionedGlyph, ShapingResult},
+    CancelToken, MemoryBudget, RenderParams, ShapingParams,
+};
+use typf_fontdb::TypfFontFace;
+use typf_render_opixa::OpixaRenderer;
+use typf_render_skia::SkiaRenderer;
+use typf_render_zeno::ZenoRenderer;
+use typf_shape_none::NoneShaper;
+
+/// Text covering Latin, marks, and emoji, so cmap lookups hit several subtables
+const TEXT: &str = "Hg\u{301}\u{1F600}";
+
+fuzz_target!(|data: &[u8]| {
+    // Skip inputs that can't be fonts, or would only slow the fuzzer down
+    if data.len() < 12 || data.len() > 1_000_000 {
+        return;
+    }
+    let Ok(face) = TypfFontFace::from_data(data.to_vec()) else {
+        return;
+    };
+    let font: Arc<dyn FontRef> = Arc::new(face);
+
+    let size = 48.0;
+    let shaped = NoneShaper::new()
+        .shape(
+            TEXT,
+            font.clone(),
+            &ShapingParams {
+                size,
+                ..Default::default()
+            },
+        )
+        .ok();
+    // Raw glyph ids too, so fonts without a usable cmap still reach the renderers
+    let raw = ShapingResult {
+        glyphs: (0..8)
+            .map(|id| PositionedGlyph {
+                id,
+                x: id as f32 * size,
+                y: 0.0,
+                advance: size,
+                cluster: 0,
+            })
+            .collect(),
+        advance_width: 8.0 * size,
+        advance_height: size,
+        direction: Direction::LeftToRight,
+    };
+
+    let renderers: [&dyn Renderer; 3] = [
+        &OpixaRenderer::new(),
+        &SkiaRenderer::new(),
+        &ZenoRenderer::new(),
+    ];
+    for strict in [false, true] {
+        let params = RenderParams {
+            memory_budget: Some(MemoryBudget::new(64 << 20)),
+            cancel: Some(CancelToken::with_timeout(Duration::from_secs(2))),
+            strict,
+            ..Default::default()
+        };
+        for renderer in renderers {
+            for run in shaped.iter().chain([&raw]) {
+                let _ = renderer.render(run, font.clone(), &params);
+            }
+        }
+    }
+});
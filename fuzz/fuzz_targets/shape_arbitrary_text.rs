@@ -0,0 +1,87 @@
+//! Shape arbitrary text with a real font
+//!
+//! `fuzz_harfbuzz_shape` feeds text to a mock font with no tables, so the
+//! shaper bails out before any lookup runs. This target shapes the same kind
+//! of hostile text with NotoSans, driving GSUB/GPOS, cluster mapping, and
+//! run splitting in the pure-Rust shapers.
+//!
+//! The first input byte picks direction and size, the rest is the text.
+//!
+//! What we test:
+//! - Cluster bookkeeping on invalid, combining-heavy, and mixed-script text
+//! - Direction handling for LTR, RTL, TTB, and BTT runs
+//! - Degenerate sizes (zero, tiny, huge) that reach the scaling math
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::sync::{Arc, OnceLock};
+use typf_core::{
+    traits::{FontRef, Shaper},
+    types::Direction,
+    ShapingParams,
+};
+use typf_fontdb::TypfFontFace;
+use typf_shape_hr::HarfrustShaper;
+use typf_shape_none::NoneShaper;
+
+const FONT_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../test-fonts/NotoSans-Regular.ttf"
+);
+
+/// Loaded once per process; `None` when the test fonts are not checked out
+fn font() -> Option<Arc<dyn FontRef>> {
+    static FONT: OnceLock<Option<Arc<TypfFontFace>>> = OnceLock::new();
+    FONT.get_or_init(|| TypfFontFace::from_file(FONT_PATH).ok().map(Arc::new))
+        .clone()
+        .map(|f| f as Arc<dyn FontRef>)
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, rest)) = data.split_first() else {
+        return;
+    };
+    let text = String::from_utf8_lossy(rest);
+
+    // Reject inputs that would waste time or cause timeouts
+    if text.is_empty() || text.len() > 4_000 {
+        return;
+    }
+    let Some(font) = font() else {
+        return;
+    };
+
+    let direction = match selector & 0b11 {
+        0 => Direction::LeftToRight,
+        1 => Direction::RightToLeft,
+        2 => Direction::TopToBottom,
+        _ => Direction::BottomToTop,
+    };
+    let size = match selector >> 2 {
+        0 => 0.0,
+        1 => 0.01,
+        63 => 100_000.0,
+        n => f32::from(n) * 4.0,
+    };
+    let params = ShapingParams {
+        size,
+        direction,
+        ..Default::default()
+    };
+
+    for shaper in [&HarfrustShaper::new() as &dyn Shaper, &NoneShaper::new()] {
+        if let Ok(shaped) = shaper.shape(&text, font.clone(), &params) {
+            // Clusters index into the text the shaper was given
+            for glyph in &shaped.glyphs {
+                assert!(
+                    (glyph.cluster as usize) <= text.len(),
+                    "{}: cluster {} past text of {} bytes",
+                    shaper.name(),
+                    glyph.cluster,
+                    text.len()
+                );
+            }
+        }
+    }
+});
@@ -0,0 +1,143 @@
+//! Pixel comparison against a golden.
+
+use std::fmt;
+
+use typf_core::types::{BitmapData, BitmapFormat, PixelLayout};
+
+use crate::Tolerance;
+
+/// How an `Rgba8` rendering differs from its same-sized golden
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    /// Width and height shared by both images
+    pub size: (u32, u32),
+    /// Largest single channel difference, 0–255
+    pub max_delta: u8,
+    /// Pixels with a channel further off than the tolerance allows
+    pub differing_pixels: usize,
+}
+
+impl Comparison {
+    /// Compare two `Rgba8` bitmaps of the same size
+    pub(crate) fn between(
+        expected: &BitmapData,
+        actual: &BitmapData,
+        tolerance: Tolerance,
+    ) -> Self {
+        let mut max_delta = 0;
+        let mut differing_pixels = 0;
+        for (e, a) in expected
+            .data
+            .chunks_exact(4)
+            .zip(actual.data.chunks_exact(4))
+        {
+            let delta = pixel_delta(e, a);
+            max_delta = max_delta.max(delta);
+            differing_pixels += usize::from(delta > tolerance.max_pixel_delta);
+        }
+        Self {
+            size: (expected.width, expected.height),
+            max_delta,
+            differing_pixels,
+        }
+    }
+
+    /// Share of pixels that differ, 0.0–1.0
+    pub fn diff_fraction(&self) -> f64 {
+        let total = self.size.0 as usize * self.size.1 as usize;
+        if total == 0 {
+            0.0
+        } else {
+            self.differing_pixels as f64 / total as f64
+        }
+    }
+
+    /// Whether the differences fit in `tolerance`
+    pub fn passes(&self, tolerance: Tolerance) -> bool {
+        self.diff_fraction() <= tolerance.max_diff_fraction
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} pixels differ ({:.4}%), max channel delta {}",
+            self.differing_pixels,
+            self.size.0 as usize * self.size.1 as usize,
+            self.diff_fraction() * 100.0,
+            self.max_delta
+        )
+    }
+}
+
+/// The golden faded to a grey ghost, with out-of-tolerance pixels in red
+pub(crate) fn diff_image(
+    expected: &BitmapData,
+    actual: &BitmapData,
+    tolerance: Tolerance,
+) -> BitmapData {
+    let mut data = Vec::with_capacity(expected.data.len());
+    for (e, a) in expected
+        .data
+        .chunks_exact(4)
+        .zip(actual.data.chunks_exact(4))
+    {
+        if pixel_delta(e, a) > tolerance.max_pixel_delta {
+            data.extend([255, 0, 0, 255]);
+        } else {
+            let ghost = 255 - e[3] / 4;
+            data.extend([ghost, ghost, ghost, 255]);
+        }
+    }
+    BitmapData {
+        width: expected.width,
+        height: expected.height,
+        format: BitmapFormat::Rgba8,
+        stride: BitmapFormat::Rgba8.row_bytes(expected.width),
+        layout: PixelLayout::RGBA,
+        data,
+    }
+}
+
+fn pixel_delta(expected: &[u8], actual: &[u8]) -> u8 {
+    expected
+        .iter()
+        .zip(actual)
+        .map(|(e, a)| e.abs_diff(*a))
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, px: [u8; 4]) -> BitmapData {
+        BitmapData {
+            width,
+            height,
+            format: BitmapFormat::Rgba8,
+            stride: BitmapFormat::Rgba8.row_bytes(width),
+            layout: PixelLayout::RGBA,
+            data: px.repeat((width * height) as usize),
+        }
+    }
+
+    #[test]
+    fn test_between_when_deltas_within_tolerance_then_not_counted() {
+        let expected = solid(10, 10, [0, 0, 0, 200]);
+        let mut actual = solid(10, 10, [0, 0, 0, 202]);
+        actual.data[3] = 250; // one pixel well off
+
+        let tolerance = Tolerance {
+            max_pixel_delta: 2,
+            max_diff_fraction: 0.01,
+        };
+        let comparison = Comparison::between(&expected, &actual, tolerance);
+        assert_eq!(comparison.differing_pixels, 1);
+        assert_eq!(comparison.max_delta, 50);
+        assert!(comparison.passes(tolerance));
+        assert!(!comparison.passes(Tolerance::exact()));
+    }
+}
@@ -0,0 +1,111 @@
+//! Reading and writing goldens as straight-alpha RGBA PNGs.
+
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::Path;
+
+use typf_core::types::{BitmapData, BitmapFormat, PixelLayout};
+
+use crate::GoldenError;
+
+/// `bitmap` as `Rgba8`; coverage formats become black ink
+pub(crate) fn to_rgba(bitmap: &BitmapData) -> BitmapData {
+    let straight = bitmap.to_layout(PixelLayout::RGBA);
+    let packed = straight.to_packed();
+    let bitmap = packed.as_ref();
+    let pixels = bitmap.width as usize * bitmap.height as usize;
+    let mut data = Vec::with_capacity(pixels * 4);
+    match bitmap.format {
+        BitmapFormat::Rgba8 => data.extend(bitmap.data.iter().take(pixels * 4)),
+        BitmapFormat::Rgb8 => {
+            for px in bitmap.data.chunks_exact(3).take(pixels) {
+                data.extend([px[0], px[1], px[2], 255]);
+            }
+        },
+        BitmapFormat::Gray8 => {
+            for &alpha in bitmap.data.iter().take(pixels) {
+                data.extend([0, 0, 0, alpha]);
+            }
+        },
+        BitmapFormat::Gray1 => {
+            let stride = (bitmap.width as usize).div_ceil(8);
+            for y in 0..bitmap.height as usize {
+                for x in 0..bitmap.width as usize {
+                    let byte = bitmap.data.get(y * stride + x / 8).copied().unwrap_or(0);
+                    let on = byte & (0x80 >> (x % 8)) != 0;
+                    data.extend([0, 0, 0, if on { 255 } else { 0 }]);
+                }
+            }
+        },
+    }
+    // Short buffers read as transparent rather than panicking
+    data.resize(pixels * 4, 0);
+    BitmapData {
+        width: bitmap.width,
+        height: bitmap.height,
+        format: BitmapFormat::Rgba8,
+        stride: BitmapFormat::Rgba8.row_bytes(bitmap.width),
+        layout: PixelLayout::RGBA,
+        data,
+    }
+}
+
+pub(crate) fn read_png(path: &Path) -> Result<BitmapData, GoldenError> {
+    let png_error = |e: png::DecodingError| GoldenError::Png {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    };
+    let file = File::open(path).map_err(|source| GoldenError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    let mut decoder = png::Decoder::new(std::io::BufReader::new(file));
+    decoder.set_transformations(png::Transformations::ALPHA | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(png_error)?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(png_error)?;
+    buf.truncate(info.buffer_size());
+
+    let format = match info.color_type {
+        png::ColorType::Rgba => BitmapFormat::Rgba8,
+        png::ColorType::Rgb => BitmapFormat::Rgb8,
+        other => {
+            return Err(GoldenError::Png {
+                path: path.to_path_buf(),
+                message: format!("unsupported color type {other:?}"),
+            })
+        },
+    };
+    Ok(to_rgba(&BitmapData {
+        width: info.width,
+        height: info.height,
+        format,
+        stride: format.row_bytes(info.width),
+        layout: PixelLayout::RGBA,
+        data: buf,
+    }))
+}
+
+/// Write an `Rgba8` bitmap, creating parent directories
+pub(crate) fn write_png(path: &Path, bitmap: &BitmapData) -> Result<(), GoldenError> {
+    let io_error = |source| GoldenError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let png_error = |e: png::EncodingError| GoldenError::Png {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    let file = File::create(path).map_err(io_error)?;
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), bitmap.width, bitmap.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(png_error)?;
+    writer.write_image_data(&bitmap.data).map_err(png_error)?;
+    writer.finish().map_err(png_error)
+}
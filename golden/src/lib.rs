@@ -0,0 +1,457 @@
+//! Golden-image regression tests for Typf renderers.
+//!
+//! A smoke test that checks "some pixels are set" passes while a renderer
+//! drops a glyph, shifts the baseline, or inverts a gradient. This crate
+//! compares renderings against reviewed PNGs committed next to the tests
+//! instead:
+//!
+//! - a [`GoldenCase`] names a piece of text, its shaping and render
+//!   parameters, and the [`Tolerance`] its comparison allows;
+//! - [`Goldens`] renders cases through any shaper and renderer, compares the
+//!   result with `<dir>/<name>.png`, and on failure writes the actual and a
+//!   diff image where the error message says;
+//! - with `TYPF_UPDATE_GOLDENS=1` set, it rewrites the goldens instead, so
+//!   an intended change is one test run plus a review of the PNG diff.
+//!
+//! ```ignore
+//! use typf_golden::{GoldenCase, Goldens, Tolerance};
+//!
+//! let goldens = Goldens::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/goldens"));
+//! let case = GoldenCase::new("latin-48", "Hamburgefonstiv", 48.0)
+//!     .with_tolerance(Tolerance::exact());
+//! goldens.assert_case(&case, &shaper, &renderer, &font);
+//! ```
+
+// this_file: golden/src/lib.rs
+
+mod compare;
+mod image;
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use thiserror::Error;
+use typf_core::{
+    error::TypfError,
+    traits::{FontRef, Renderer, Shaper},
+    types::{BitmapData, RenderOutput},
+    RenderParams, ShapingParams,
+};
+
+pub use compare::Comparison;
+
+/// Set to `1` to write goldens instead of comparing against them
+pub const UPDATE_ENV: &str = "TYPF_UPDATE_GOLDENS";
+
+/// Where actual and diff images of failed cases go; a temp dir by default
+pub const OUTPUT_ENV: &str = "TYPF_GOLDEN_OUT";
+
+/// How far a rendering may stray from its golden
+///
+/// A pixel differs when any of its RGBA channels is more than
+/// `max_pixel_delta` away from the golden. A case passes when at most
+/// `max_diff_fraction` of its pixels differ. Sizes must always match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// Largest channel difference that still counts as equal, 0–255
+    pub max_pixel_delta: u8,
+    /// Share of pixels, 0.0–1.0, allowed to differ
+    pub max_diff_fraction: f64,
+}
+
+impl Tolerance {
+    /// Every pixel identical
+    pub fn exact() -> Self {
+        Self {
+            max_pixel_delta: 0,
+            max_diff_fraction: 0.0,
+        }
+    }
+}
+
+impl Default for Tolerance {
+    /// Antialiasing noise between platforms: channels within 2, and one
+    /// pixel in a thousand free to differ further
+    fn default() -> Self {
+        Self {
+            max_pixel_delta: 2,
+            max_diff_fraction: 0.001,
+        }
+    }
+}
+
+/// A named rendering to keep stable
+#[derive(Debug, Clone)]
+pub struct GoldenCase {
+    /// File stem of the golden; ASCII letters, digits, `-`, and `_`
+    pub name: String,
+    pub text: String,
+    pub shaping: ShapingParams,
+    pub render: RenderParams,
+    pub tolerance: Tolerance,
+}
+
+impl GoldenCase {
+    /// `text` at `size` pixels with default parameters and [`Tolerance::default`]
+    pub fn new(name: impl Into<String>, text: impl Into<String>, size: f32) -> Self {
+        Self {
+            name: name.into(),
+            text: text.into(),
+            shaping: ShapingParams {
+                size,
+                ..ShapingParams::default()
+            },
+            render: RenderParams::default(),
+            tolerance: Tolerance::default(),
+        }
+    }
+
+    pub fn with_shaping(mut self, shaping: ShapingParams) -> Self {
+        self.shaping = shaping;
+        self
+    }
+
+    pub fn with_render(mut self, render: RenderParams) -> Self {
+        self.render = render;
+        self
+    }
+
+    pub fn with_tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+}
+
+/// Why a case did not match its golden
+#[derive(Debug, Error)]
+pub enum GoldenError {
+    #[error("invalid case name {0:?}: use ASCII letters, digits, '-' and '_'")]
+    InvalidName(String),
+
+    #[error("no golden for {name} at {}; run with {UPDATE_ENV}=1 to create it", path.display())]
+    Missing { name: String, path: PathBuf },
+
+    #[error(
+        "{name}: size {}x{} differs from golden {}x{}; actual written to {}",
+        actual.0, actual.1, expected.0, expected.1, actual_path.display()
+    )]
+    SizeMismatch {
+        name: String,
+        expected: (u32, u32),
+        actual: (u32, u32),
+        actual_path: PathBuf,
+    },
+
+    #[error(
+        "{name}: {comparison}; actual written to {}, diff to {}",
+        actual_path.display(), diff_path.display()
+    )]
+    Mismatch {
+        name: String,
+        comparison: Comparison,
+        actual_path: PathBuf,
+        diff_path: PathBuf,
+    },
+
+    #[error("{name}: renderer returned {kind}, not a bitmap")]
+    NotBitmap { name: String, kind: &'static str },
+
+    #[error("rendering failed: {0}")]
+    Render(#[from] TypfError),
+
+    #[error("PNG error in {}: {message}", path.display())]
+    Png { path: PathBuf, message: String },
+
+    #[error("IO error on {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// What [`Goldens::check`] did
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The rendering is within tolerance of the golden
+    Matched(Comparison),
+    /// Update mode: the golden was (re)written from the rendering
+    Written(PathBuf),
+}
+
+/// A directory of golden PNGs
+#[derive(Debug, Clone)]
+pub struct Goldens {
+    dir: PathBuf,
+    update: bool,
+    output: PathBuf,
+}
+
+impl Goldens {
+    /// Goldens in `dir`; update mode and output dir come from the environment
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let update = std::env::var(UPDATE_ENV).is_ok_and(|v| v == "1" || v == "true");
+        let output = std::env::var_os(OUTPUT_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| std::env::temp_dir().join("typf-golden"));
+        Self {
+            dir: dir.into(),
+            update,
+            output,
+        }
+    }
+
+    /// Force update mode on or off, whatever the environment says
+    pub fn with_update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// Write actual and diff images of failed cases under `dir`
+    pub fn with_output_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.output = dir.into();
+        self
+    }
+
+    /// Where the golden for `name` lives
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.png"))
+    }
+
+    /// Shape and render `case`, then [`check`](Self::check) the bitmap
+    pub fn check_case(
+        &self,
+        case: &GoldenCase,
+        shaper: &dyn Shaper,
+        renderer: &dyn Renderer,
+        font: &Arc<dyn FontRef>,
+    ) -> Result<Outcome, GoldenError> {
+        let shaped = shaper.shape(&case.text, font.clone(), &case.shaping)?;
+        match renderer.render(&shaped, font.clone(), &case.render)? {
+            RenderOutput::Bitmap(bitmap) => self.check(&case.name, &bitmap, case.tolerance),
+            other => Err(GoldenError::NotBitmap {
+                name: case.name.clone(),
+                kind: output_kind(&other),
+            }),
+        }
+    }
+
+    /// Compare `bitmap` with the golden called `name`, or write it in update mode
+    pub fn check(
+        &self,
+        name: &str,
+        bitmap: &BitmapData,
+        tolerance: Tolerance,
+    ) -> Result<Outcome, GoldenError> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(GoldenError::InvalidName(name.to_string()));
+        }
+
+        let path = self.path(name);
+        let actual = image::to_rgba(bitmap);
+        if self.update {
+            image::write_png(&path, &actual)?;
+            return Ok(Outcome::Written(path));
+        }
+        if !path.exists() {
+            return Err(GoldenError::Missing {
+                name: name.to_string(),
+                path,
+            });
+        }
+
+        let expected = image::read_png(&path)?;
+        let expected_size = (expected.width, expected.height);
+        let actual_size = (actual.width, actual.height);
+        if expected_size != actual_size {
+            let actual_path = self.output.join(format!("{name}.actual.png"));
+            image::write_png(&actual_path, &actual)?;
+            return Err(GoldenError::SizeMismatch {
+                name: name.to_string(),
+                expected: expected_size,
+                actual: actual_size,
+                actual_path,
+            });
+        }
+
+        let comparison = Comparison::between(&expected, &actual, tolerance);
+        if comparison.passes(tolerance) {
+            return Ok(Outcome::Matched(comparison));
+        }
+
+        let actual_path = self.output.join(format!("{name}.actual.png"));
+        let diff_path = self.output.join(format!("{name}.diff.png"));
+        image::write_png(&actual_path, &actual)?;
+        image::write_png(
+            &diff_path,
+            &compare::diff_image(&expected, &actual, tolerance),
+        )?;
+        Err(GoldenError::Mismatch {
+            name: name.to_string(),
+            comparison,
+            actual_path,
+            diff_path,
+        })
+    }
+
+    /// [`check_case`](Self::check_case), panicking with the error on failure
+    #[allow(clippy::panic)] // this is the test assertion
+    pub fn assert_case(
+        &self,
+        case: &GoldenCase,
+        shaper: &dyn Shaper,
+        renderer: &dyn Renderer,
+        font: &Arc<dyn FontRef>,
+    ) {
+        if let Err(error) = self.check_case(case, shaper, renderer, font) {
+            panic!("golden check failed: {error}");
+        }
+    }
+
+    /// [`check`](Self::check), panicking with the error on failure
+    #[allow(clippy::panic)] // this is the test assertion
+    pub fn assert_matches(&self, name: &str, bitmap: &BitmapData, tolerance: Tolerance) {
+        if let Err(error) = self.check(name, bitmap, tolerance) {
+            panic!("golden check failed: {error}");
+        }
+    }
+
+    /// The directory goldens are read from and written to
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+fn output_kind(output: &RenderOutput) -> &'static str {
+    match output {
+        RenderOutput::Bitmap(_) => "a bitmap",
+        RenderOutput::Vector(_) => "vector data",
+        RenderOutput::Json(_) => "JSON",
+        RenderOutput::Geometry(_) => "geometry",
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Matched(comparison) => write!(f, "matched ({comparison})"),
+            Outcome::Written(path) => write!(f, "wrote {}", path.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use typf_core::types::{BitmapFormat, PixelLayout};
+
+    fn scratch(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("typf-golden-{}-{test}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn goldens(dir: &Path) -> Goldens {
+        Goldens::new(dir.join("goldens"))
+            .with_update(false)
+            .with_output_dir(dir.join("out"))
+    }
+
+    fn gray(width: u32, height: u32, value: u8) -> BitmapData {
+        BitmapData {
+            width,
+            height,
+            format: BitmapFormat::Gray8,
+            stride: BitmapFormat::Gray8.row_bytes(width),
+            layout: PixelLayout::RGBA,
+            data: vec![value; (width * height) as usize],
+        }
+    }
+
+    #[test]
+    fn test_check_when_update_mode_then_golden_written_and_then_matched() {
+        let dir = scratch("update");
+        let bitmap = gray(4, 3, 128);
+
+        let written = goldens(&dir)
+            .with_update(true)
+            .check("grey", &bitmap, Tolerance::exact())
+            .expect("update writes");
+        assert_eq!(written, Outcome::Written(dir.join("goldens/grey.png")));
+
+        match goldens(&dir).check("grey", &bitmap, Tolerance::exact()) {
+            Ok(Outcome::Matched(comparison)) => assert_eq!(comparison.differing_pixels, 0),
+            other => panic!("expected a match, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_check_when_golden_absent_then_missing() {
+        let dir = scratch("missing");
+        let result = goldens(&dir).check("absent", &gray(2, 2, 0), Tolerance::default());
+        assert!(matches!(result, Err(GoldenError::Missing { .. })));
+    }
+
+    #[test]
+    fn test_check_when_beyond_tolerance_then_actual_and_diff_written() {
+        let dir = scratch("mismatch");
+        goldens(&dir)
+            .with_update(true)
+            .check("dark", &gray(4, 4, 200), Tolerance::exact())
+            .expect("update writes");
+
+        let mut lighter = gray(4, 4, 200);
+        lighter.data[5] = 100;
+        match goldens(&dir).check("dark", &lighter, Tolerance::exact()) {
+            Err(GoldenError::Mismatch {
+                comparison,
+                actual_path,
+                diff_path,
+                ..
+            }) => {
+                assert_eq!(comparison.differing_pixels, 1);
+                assert!(actual_path.exists());
+                assert!(diff_path.exists());
+            },
+            other => panic!("expected a mismatch, got {other:?}"),
+        }
+
+        let loose = Tolerance {
+            max_pixel_delta: 0,
+            max_diff_fraction: 1.0 / 16.0,
+        };
+        assert!(goldens(&dir).check("dark", &lighter, loose).is_ok());
+    }
+
+    #[test]
+    fn test_check_when_size_differs_then_size_mismatch() {
+        let dir = scratch("size");
+        goldens(&dir)
+            .with_update(true)
+            .check("box", &gray(4, 4, 0), Tolerance::default())
+            .expect("update writes");
+
+        let result = goldens(&dir).check("box", &gray(5, 4, 0), Tolerance::default());
+        match result {
+            Err(GoldenError::SizeMismatch {
+                expected, actual, ..
+            }) => {
+                assert_eq!(expected, (4, 4));
+                assert_eq!(actual, (5, 4));
+            },
+            other => panic!("expected a size mismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_check_when_name_has_path_separator_then_rejected() {
+        let dir = scratch("name");
+        let result = goldens(&dir).check("../escape", &gray(1, 1, 0), Tolerance::default());
+        assert!(matches!(result, Err(GoldenError::InvalidName(_))));
+    }
+}
@@ -0,0 +1,61 @@
+//! Goldens for the raster backends, rendered with a real font
+//!
+//! Each case shapes through `NoneShaper` so only the renderer can move a
+//! pixel. The tests skip when `test-fonts/NotoSans-Regular.ttf` is absent;
+//! record or refresh the PNGs with `TYPF_UPDATE_GOLDENS=1`.
+
+#![allow(clippy::expect_used)]
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use typf_core::traits::{FontRef, Renderer};
+use typf_fontdb::TypfFontFace;
+use typf_golden::{GoldenCase, Goldens, Tolerance};
+use typf_render_opixa::OpixaRenderer;
+use typf_render_skia::SkiaRenderer;
+use typf_render_zeno::ZenoRenderer;
+use typf_shape_none::NoneShaper;
+
+fn goldens() -> Goldens {
+    Goldens::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/goldens"))
+}
+
+fn noto_sans() -> Option<Arc<dyn FontRef>> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../test-fonts")
+        .join("NotoSans-Regular.ttf");
+    if !path.exists() {
+        eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+        return None;
+    }
+    let face = TypfFontFace::from_file(&path).expect("NotoSans-Regular.ttf should load");
+    Some(Arc::new(face))
+}
+
+/// Renders "Hamburgefonstiv" at 32 px and checks it against `<backend>-latin-32`
+fn assert_backend(renderer: &dyn Renderer) {
+    let Some(font) = noto_sans() else {
+        return;
+    };
+    let name = format!("{}-latin-32", renderer.name());
+    let mut case =
+        GoldenCase::new(name, "Hamburgefonstiv", 32.0).with_tolerance(Tolerance::default());
+    case.render.padding = 4;
+    goldens().assert_case(&case, &NoneShaper::new(), renderer, &font);
+}
+
+#[test]
+fn test_assert_case_when_opixa_renders_latin_then_matches_golden() {
+    assert_backend(&OpixaRenderer::new());
+}
+
+#[test]
+fn test_assert_case_when_skia_renders_latin_then_matches_golden() {
+    assert_backend(&SkiaRenderer::new());
+}
+
+#[test]
+fn test_assert_case_when_zeno_renders_latin_then_matches_golden() {
+    assert_backend(&ZenoRenderer::new());
+}
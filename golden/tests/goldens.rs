@@ -0,0 +1,113 @@
+//! The golden workflow end to end on a deterministic synthetic renderer
+//!
+//! Backend crates use the same calls with a real font; goldens live in
+//! `tests/goldens` and are refreshed with `TYPF_UPDATE_GOLDENS=1`.
+
+use std::sync::Arc;
+
+use typf_core::{
+    error::Result,
+    traits::{FontRef, Renderer},
+    types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput, ShapingResult},
+    RenderParams,
+};
+use typf_golden::{GoldenCase, GoldenError, Goldens, Tolerance};
+use typf_shape_none::NoneShaper;
+
+/// Every char maps to a glyph; advances grow with the glyph id
+struct StepFont;
+
+impl FontRef for StepFont {
+    fn data(&self) -> &[u8] {
+        &[]
+    }
+
+    fn units_per_em(&self) -> u16 {
+        1000
+    }
+
+    fn glyph_id(&self, ch: char) -> Option<u32> {
+        Some(ch as u32 % 8)
+    }
+
+    fn advance_width(&self, glyph_id: u32) -> f32 {
+        400.0 + 100.0 * glyph_id as f32
+    }
+}
+
+/// Paints each glyph as a coverage bar as tall as its id, a ramp in x
+struct BarRenderer;
+
+impl Renderer for BarRenderer {
+    fn name(&self) -> &'static str {
+        "bar"
+    }
+
+    fn render(
+        &self,
+        shaped: &ShapingResult,
+        _font: Arc<dyn FontRef>,
+        params: &RenderParams,
+    ) -> Result<RenderOutput> {
+        let pad = params.padding;
+        let width = shaped.advance_width.ceil() as u32 + 2 * pad;
+        let height = 16 + 2 * pad;
+        let mut data = vec![0u8; (width * height) as usize];
+        for glyph in &shaped.glyphs {
+            let x0 = glyph.x as u32 + pad;
+            let advance = glyph.advance as u32;
+            for y in pad + 16 - 2 * glyph.id.min(8)..pad + 16 {
+                for dx in 0..advance.saturating_sub(1) {
+                    data[(y * width + x0 + dx) as usize] = (64 + dx * 191 / advance) as u8;
+                }
+            }
+        }
+        Ok(RenderOutput::Bitmap(BitmapData {
+            width,
+            height,
+            format: BitmapFormat::Gray8,
+            stride: BitmapFormat::Gray8.row_bytes(width),
+            layout: PixelLayout::RGBA,
+            data,
+        }))
+    }
+}
+
+fn goldens() -> Goldens {
+    Goldens::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/goldens"))
+}
+
+fn font() -> Arc<dyn FontRef> {
+    Arc::new(StepFont)
+}
+
+fn case() -> GoldenCase {
+    let mut case = GoldenCase::new("bars", "Typf", 20.0).with_tolerance(Tolerance::exact());
+    case.render.padding = 2;
+    case
+}
+
+#[test]
+fn test_assert_case_when_rendering_unchanged_then_matches_committed_golden() {
+    goldens().assert_case(&case(), &NoneShaper::new(), &BarRenderer, &font());
+}
+
+#[test]
+fn test_check_case_when_size_changes_then_mismatch_reported() {
+    let goldens = goldens();
+    if goldens.path("bars").exists() && std::env::var(typf_golden::UPDATE_ENV).is_err() {
+        let mut bigger = case();
+        bigger.shaping.size = 24.0;
+        let out = std::env::temp_dir().join(format!("typf-golden-it-{}", std::process::id()));
+        let result = goldens.with_output_dir(out).check_case(
+            &bigger,
+            &NoneShaper::new(),
+            &BarRenderer,
+            &font(),
+        );
+        assert!(
+            matches!(result, Err(GoldenError::SizeMismatch { .. })),
+            "got {result:?}"
+        );
+    }
+}
@@ -46,6 +46,14 @@
 //! let bytes = pipeline.process("Hello", font, &ShapingParams::default(), &RenderParams::default())?;
 //! ```
 //!
+//! For mixed scripts, bidi text, and fallback fonts, [`TypfPipeline`] picks
+//! backends by name and renders a whole string into one bitmap:
+//!
+//! ```ignore
+//! let pipeline = typf::TypfPipeline::builder().shaper("harfbuzz").renderer("zeno").build()?;
+//! let bitmap = pipeline.render_text("Hello, مرحبا", font, &typf::TextOptions::default())?;
+//! ```
+//!
 //! ## Feature flags
 //!
 //! | Flag | What you get |
@@ -55,19 +63,25 @@
 //! | `full` | Every shaper, renderer, and exporter |
 //! | `shaping-hb` | HarfBuzz C library — industry-standard, all scripts |
 //! | `shaping-hr` | Pure Rust HarfBuzz port — zero C deps |
+//! | `shaping-rustybuzz` | rustybuzz — the older pure Rust HarfBuzz port |
 //! | `shaping-icu-hb` | ICU normalization + HarfBuzz — full Unicode pipeline |
 //! | `shaping-ct` / `shaping-mac` | macOS CoreText (macOS only) |
 //! | `render-skia` | tiny-skia — full color font support |
+//! | `render-svg` | SVG renderer — vector paths, no rasterizing |
 //! | `render-zeno` | Pure Rust zeno rasterizer — 256-level AA, color fonts |
 //! | `render-vello-cpu` | Vello CPU — pure Rust, COLR/bitmap support |
 //! | `render-vello` | Vello GPU — Metal/Vulkan/DX12, outline-only today |
 //! | `render-cg` / `render-mac` | macOS CoreGraphics (macOS only) |
 //! | `export-png` | PNG output |
 //! | `export-svg` | SVG output (23× faster than PNG) |
+//! | `conformance` | Harness diffing shaping and pixels between two backends |
+//! | `wasm` | JavaScript API via `wasm-bindgen`, fonts from bytes |
+//! | `wasm-browser` | `wasm` + HarfRust + Zeno, all of which build for `wasm32` |
 
 pub use typf_core::{
-    cache_config, error, traits, validate_glyph_count, Color, GlyphSource, GlyphSourcePreference,
-    Pipeline, RenderMode, RenderParams, ShapingParams, MAX_FONT_SIZE, MAX_GLYPH_COUNT,
+    budget, cache_config, cancel, error, stats, traits, validate_glyph_count, CancelToken, Color,
+    GlyphSource, GlyphSourcePreference, MemoryBudget, Pipeline, RenderMode, RenderParams,
+    RenderStats, RunOffset, ShapingParams, MAX_FONT_SIZE, MAX_GLYPH_COUNT,
 };
 
 #[cfg(feature = "input")]
@@ -82,9 +96,25 @@ pub use typf_fontdb as fontdb;
 #[cfg(feature = "export-pnm")]
 pub use typf_export as export;
 
+#[cfg(feature = "conformance")]
+pub use typf_conformance as conformance;
+
+#[cfg(feature = "subset")]
+pub use typf_subset as subset;
+
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
+mod registry;
+pub use registry::{register_builtin_renderers, renderers};
+pub use typf_core::registry::RendererRegistry;
+
+mod text_pipeline;
+pub use text_pipeline::{
+    BlockLine, Column, ColumnBlock, Columns, GlyphSourceOverride, MissingGlyph, TextBlock,
+    TextMetrics, TextOptions, TypfPipeline, TypfPipelineBuilder,
+};
+
 // ── Shaping backends ────────────────────────────────────────────────────────
 //
 // Each shaper implements `typf_core::traits::Shaper`. Pick one based on your
@@ -107,6 +137,20 @@ pub use typf_shape_none as shape_none;
 #[cfg(feature = "shaping-hb")]
 pub use typf_shape_hb as shape_hb;
 
+/// HarfRust shaper: a pure Rust port of HarfBuzz.
+///
+/// Same shaping model and script coverage as `shape_hb` without the C
+/// toolchain, which makes it the shaper of choice for WebAssembly builds.
+#[cfg(feature = "shaping-hr")]
+pub use typf_shape_hr as shape_hr;
+
+/// rustybuzz shaper: the long-standing pure Rust HarfBuzz port.
+///
+/// Built on `ttf-parser` with no build script. Complex-script output matches
+/// `shape_hr` glyph for glyph.
+#[cfg(feature = "shaping-rustybuzz")]
+pub use typf_shape_rustybuzz as shape_rustybuzz;
+
 /// macOS CoreText native shaper (macOS only).
 ///
 /// Delegates to Apple's text engine — the same one used by Cocoa, Safari, and
@@ -165,6 +209,13 @@ pub use typf_render_cg as render_cg;
 #[cfg(feature = "render-skia")]
 pub use typf_render_skia as render_skia;
 
+/// SVG renderer: glyph outlines as vector paths.
+///
+/// Emits an SVG document instead of a bitmap, so output stays sharp at any
+/// zoom and is far faster to produce than PNG. Supports COLR color glyphs.
+#[cfg(feature = "render-svg")]
+pub use typf_render_svg as render_svg;
+
 /// Vello CPU renderer: modern 2D rendering without a GPU.
 ///
 /// Uses the `vello_cpu` crate for high-quality rendering with 256-level
@@ -0,0 +1,83 @@
+//! Built-in renderers in the global [`RendererRegistry`].
+
+use std::sync::Once;
+
+// Which of these get used depends on the renderer features enabled
+#[allow(unused_imports)]
+use std::sync::Arc;
+#[allow(unused_imports)]
+use typf_core::{error::TypfError, registry::SharedRenderer};
+
+use typf_core::registry::RendererRegistry;
+
+/// The global renderer registry, with every built-in backend of this build
+///
+/// Built-ins are added the first time this is called, under the names the CLI
+/// accepts: `opixa`, `svg`, `json`, `skia`, `zeno`, `vello-cpu`, `vello`,
+/// `gpu`, and `cg` (also `coregraphics` and `mac`), as enabled by features.
+/// Names a plugin registered before that first call are left alone.
+///
+/// ```ignore
+/// let renderer = typf::renderers().create("zeno")?;
+/// ```
+pub fn renderers() -> &'static RendererRegistry {
+    static BUILTINS: Once = Once::new();
+    let registry = RendererRegistry::global();
+    BUILTINS.call_once(|| register_builtin_renderers(registry));
+    registry
+}
+
+/// Add this build's backends to `registry`, keeping names already taken
+#[allow(unused_variables)] // with no renderer features there is nothing to add
+pub fn register_builtin_renderers(registry: &RendererRegistry) {
+    #[cfg(feature = "render-opixa")]
+    registry.register_default("opixa", || {
+        Ok(Arc::new(typf_render_opixa::OpixaRenderer::new()) as SharedRenderer)
+    });
+
+    #[cfg(feature = "render-svg")]
+    registry.register_default("svg", || {
+        Ok(Arc::new(typf_render_svg::SvgRenderer::new()) as SharedRenderer)
+    });
+
+    #[cfg(feature = "render-json")]
+    registry.register_default("json", || {
+        Ok(Arc::new(typf_render_json::JsonRenderer::new()) as SharedRenderer)
+    });
+
+    #[cfg(feature = "render-skia")]
+    registry.register_default("skia", || {
+        Ok(Arc::new(typf_render_skia::SkiaRenderer::new()) as SharedRenderer)
+    });
+
+    #[cfg(feature = "render-zeno")]
+    registry.register_default("zeno", || {
+        Ok(Arc::new(typf_render_zeno::ZenoRenderer::new()) as SharedRenderer)
+    });
+
+    #[cfg(feature = "render-vello-cpu")]
+    registry.register_default("vello-cpu", || {
+        Ok(Arc::new(typf_render_vello_cpu::VelloCpuRenderer::new()) as SharedRenderer)
+    });
+
+    #[cfg(feature = "render-vello")]
+    registry.register_default("vello", || {
+        typf_render_vello::VelloRenderer::new()
+            .map(|r| Arc::new(r) as SharedRenderer)
+            .map_err(|e| TypfError::Other(format!("Failed to create GPU renderer: {}", e)))
+    });
+
+    #[cfg(feature = "render-gpu")]
+    registry.register_default("gpu", || {
+        typf_render_gpu::GpuRenderer::new()
+            .map(|r| Arc::new(r) as SharedRenderer)
+            .map_err(|e| TypfError::Other(format!("Failed to create GPU renderer: {}", e)))
+    });
+
+    #[cfg(feature = "render-cg")]
+    for name in ["cg", "coregraphics", "mac"] {
+        registry.register_default(name, || {
+            Ok(Arc::new(typf_render_cg::CoreGraphicsRenderer::new()) as SharedRenderer)
+        });
+    }
+}
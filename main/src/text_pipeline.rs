@@ -0,0 +1,1527 @@
+//! From a string to one finished bitmap in a single call.
+//!
+//! [`Pipeline`](crate::Pipeline) shapes and renders exactly what it is given:
+//! one run, one font, backends the caller constructed. Real text needs more
+//! glue than that. It mixes scripts and directions, it contains characters
+//! the chosen font lacks, and every resulting run must land on the same
+//! baseline. [`TypfPipeline`] does that glue work:
+//!
+//! 1. Splits the text into script and direction runs (with the `unicode` feature).
+//! 2. Gives each character to the first font that has it: the main font, then
+//!    [`TextOptions::fallback_fonts`] in order.
+//! 3. Shapes every piece, then lays the pieces out left to right in visual order.
+//! 4. Renders one layer per font, size, and emoji presentation and composites
+//!    the layers into one bitmap. Small caps in a font without `smcp` are
+//!    synthesized as capitals at [`SMALL_CAPS_SCALE`] of the size; in color
+//!    fonts, emoji drawn as text and as emoji get their own glyph sources.
+//!
+//! With a width in [`RenderParams::layout`], a line too wide for it is cut
+//! short with an ellipsis before rendering under [`Overflow::Ellipsis`].
+//! [`TypfPipeline::measure`] stops after step 3 and reports the size the text
+//! would take, wrapped by the layout's [`WrapMode`], for layout passes that
+//! must not pay for rasterizing. [`TypfPipeline::lay_out_block`] places the
+//! wrapped lines in a box of the layout's width and height and reports where
+//! the text that did not fit starts, so documents can flow it on;
+//! [`TypfPipeline::lay_out_columns`] does the same across columns.
+//!
+//! ```ignore
+//! use typf::{TextOptions, TypfPipeline};
+//!
+//! let pipeline = TypfPipeline::builder()
+//!     .shaper("harfbuzz")
+//!     .renderer("zeno")
+//!     .build()?;
+//! let bitmap = pipeline.render_text("Hello, مرحبا", font, &TextOptions::default())?;
+//! ```
+
+use std::borrow::Cow;
+use std::ops::Range;
+use std::sync::Arc;
+
+use typf_core::{
+    cancel,
+    case::{has_small_caps, small_caps_pieces, TextCase, SMALL_CAPS_SCALE},
+    diagnostics::DiagnosticKind,
+    emoji::{has_color_glyphs, presentation_runs, EmojiPresentation, Presentation},
+    error::{Result, TypfError},
+    glyph_metrics::{measure_glyphs, GlyphBounds},
+    render_target::{composite_bitmap, fill_rect, BufferTarget},
+    stats::{span, RenderStats, RunStats},
+    traits::{FontRef, Renderer, Shaper},
+    types::{BitmapData, Direction, PixelLayout, PositionedGlyph, RenderOutput, ShapingResult},
+    whitespace::is_zero_width,
+    BackgroundBox, BoxScope, GlyphSourcePreference, Overflow, RenderEffects, RenderParams,
+    ShapingParams, WrapMode,
+};
+
+/// Per-call settings for [`TypfPipeline::render_text`]
+#[derive(Clone, Default)]
+pub struct TextOptions {
+    /// Size, language, features, and variations for every run.
+    ///
+    /// `direction` is the paragraph direction: it decides the order of runs
+    /// on the line. Each run is shaped in its own resolved direction, and
+    /// `script` is left for the shaper to detect per run.
+    pub shaping: ShapingParams,
+    /// Colors, padding, and effects for the whole line
+    pub render: RenderParams,
+    /// Fonts tried, in order, for characters the main font has no glyph for
+    pub fallback_fonts: Vec<Arc<dyn FontRef>>,
+    /// Glyph sources for parts of the text, in place of
+    /// [`render.glyph_sources`](RenderParams::glyph_sources). Where ranges
+    /// overlap, the last one wins.
+    pub glyph_source_overrides: Vec<GlyphSourceOverride>,
+}
+
+impl TextOptions {
+    /// Broadcast subtitles at `size` pixels: white text with a black border
+    /// and a soft drop shadow, drawn shadow first, then border, then fill.
+    /// See [`RenderParams::subtitle`].
+    pub fn subtitle(size: f32) -> Self {
+        Self {
+            shaping: ShapingParams {
+                size,
+                ..ShapingParams::default()
+            },
+            render: RenderParams::subtitle(size),
+            ..Self::default()
+        }
+    }
+}
+
+/// Glyph sources for one range of the text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlyphSourceOverride {
+    /// Byte range of the text it applies to
+    pub range: Range<usize>,
+    /// Sources to draw that range from
+    pub glyph_sources: GlyphSourcePreference,
+}
+
+impl std::fmt::Debug for TextOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextOptions")
+            .field("shaping", &self.shaping)
+            .field("render", &self.render)
+            .field("fallback_fonts", &self.fallback_fonts.len())
+            .field("glyph_source_overrides", &self.glyph_source_overrides)
+            .finish()
+    }
+}
+
+/// Size of text as [`TypfPipeline::measure`] lays it out, in pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TextMetrics {
+    /// Width of the widest line, trailing spaces of wrapped lines excluded
+    pub advance: f32,
+    /// Highest ascent of the fonts used, above each baseline
+    pub ascent: f32,
+    /// Deepest descent of the fonts used, below each baseline
+    pub descent: f32,
+    /// Lines the text takes; 0 for empty text
+    pub line_count: usize,
+    /// Box around the ink of every line, y up from the first baseline and x
+    /// from the start of the line. `None` when no glyph has ink or the
+    /// fonts' outlines cannot be read.
+    pub tight_bbox: Option<GlyphBounds>,
+}
+
+impl TextMetrics {
+    /// Height of all lines, stacked `ascent + descent` apart
+    pub fn height(&self) -> f32 {
+        self.line_count as f32 * (self.ascent + self.descent)
+    }
+}
+
+/// A paragraph laid out into a box by [`TypfPipeline::lay_out_block`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextBlock {
+    /// Lines that fit, top to bottom
+    pub lines: Vec<BlockLine>,
+    /// Byte offset of the first text that did not fit; `None` when all of
+    /// it did
+    pub overflow: Option<usize>,
+    /// Highest ascent of the fonts used, above each baseline
+    pub ascent: f32,
+    /// Deepest descent of the fonts used, below each baseline
+    pub descent: f32,
+}
+
+impl TextBlock {
+    /// Height the lines take, stacked `ascent + descent` apart
+    pub fn height(&self) -> f32 {
+        self.lines.len() as f32 * (self.ascent + self.descent)
+    }
+}
+
+/// One line of a [`TextBlock`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockLine {
+    /// The part of the text on this line, trailing spaces and line
+    /// terminators excluded
+    pub range: Range<usize>,
+    /// Advance of the line, in pixels
+    pub width: f32,
+    /// Baseline, in pixels down from the top of the box
+    pub baseline: f32,
+}
+
+/// A character of the text that no font in the chain has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingGlyph {
+    /// Byte offset of the character in the text
+    pub offset: usize,
+    /// The character
+    pub ch: char,
+}
+
+/// How [`TypfPipeline::lay_out_columns`] divides the layout width
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Columns {
+    /// How many columns, side by side
+    pub count: usize,
+    /// Space between neighboring columns, in pixels
+    pub gutter: f32,
+    /// Share the lines out evenly instead of filling each column in turn
+    pub balance: bool,
+}
+
+impl Columns {
+    /// `count` columns `gutter` pixels apart, filled in turn
+    pub fn new(count: usize, gutter: f32) -> Self {
+        Self {
+            count,
+            gutter,
+            balance: false,
+        }
+    }
+
+    /// Share the lines out evenly
+    pub fn balanced(mut self) -> Self {
+        self.balance = true;
+        self
+    }
+}
+
+/// A paragraph laid out into columns by [`TypfPipeline::lay_out_columns`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnBlock {
+    /// Every column, left to right, including empty ones
+    pub columns: Vec<Column>,
+    /// Byte offset of the first text that did not fit; `None` when all of
+    /// it did
+    pub overflow: Option<usize>,
+    /// Highest ascent of the fonts used, above each baseline
+    pub ascent: f32,
+    /// Deepest descent of the fonts used, below each baseline
+    pub descent: f32,
+}
+
+/// One column of a [`ColumnBlock`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    /// Left edge, in pixels from the left of the block
+    pub x: f32,
+    /// Width the lines wrapped at
+    pub width: f32,
+    /// Lines in the column, top to bottom, baselines from the column top
+    pub lines: Vec<BlockLine>,
+}
+
+/// A shaper and renderer chosen by name, driven from plain text
+///
+/// Built with [`TypfPipeline::builder`]; cheap to share between threads.
+#[derive(Clone)]
+pub struct TypfPipeline {
+    shaper: Arc<dyn Shaper>,
+    renderer: Arc<dyn Renderer>,
+}
+
+impl TypfPipeline {
+    /// Start choosing backends
+    pub fn builder() -> TypfPipelineBuilder {
+        TypfPipelineBuilder::new()
+    }
+
+    /// The shaper every run goes through
+    pub fn shaper(&self) -> &Arc<dyn Shaper> {
+        &self.shaper
+    }
+
+    /// The renderer every layer goes through
+    pub fn renderer(&self) -> &Arc<dyn Renderer> {
+        &self.renderer
+    }
+
+    /// Shape and render `text` with `font`, falling back as needed, into one bitmap
+    ///
+    /// Text set in a single font is rendered in one call and comes back
+    /// exactly as the renderer draws it. When the text needs fallback fonts,
+    /// synthesized small caps, or both text and emoji presentation from a
+    /// color font, each font, size, and presentation is rendered as its own
+    /// layer and the layers are aligned on their ascents, following the
+    /// baseline contract all bitmap renderers share. Ink that rises above its
+    /// font's ascent, or a [`transform`](RenderParams::transform), can
+    /// misalign the layers slightly.
+    ///
+    /// The text is drawn on one line. When it is wider than the
+    /// [`layout`](RenderParams::layout) width under [`Overflow::Ellipsis`],
+    /// grapheme clusters are dropped from its logical end until it fits with
+    /// "…" after it; color spans past the cut do not reach the ellipsis.
+    ///
+    /// Fails if the renderer does not produce bitmaps.
+    pub fn render_text(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        options: &TextOptions,
+    ) -> Result<BitmapData> {
+        self.render_text_with_stats(text, font, options)
+            .map(|(bitmap, _)| bitmap)
+    }
+
+    /// [`render_text`](Self::render_text), also reporting runs, glyph
+    /// counts, allocations, and time spent segmenting, shaping, rasterizing,
+    /// and compositing
+    pub fn render_text_with_stats(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        options: &TextOptions,
+    ) -> Result<(BitmapData, RenderStats)> {
+        let mut stats = RenderStats::default();
+        let fonts = chain(font, options);
+        let mut line = self.lay_out(text, &fonts, options, &mut stats)?;
+        if let Some(diagnostics) = &options.render.diagnostics {
+            for missing in missing_glyphs(text, &fonts, &line) {
+                diagnostics.warn(
+                    DiagnosticKind::MissingGlyph,
+                    Some(0),
+                    format!(
+                        "no font has U+{:04X} at byte {}",
+                        missing.ch as u32, missing.offset
+                    ),
+                );
+            }
+        }
+        let layout = &options.render.layout;
+        if let (Some(max_width), Overflow::Ellipsis) = (layout.max_width, layout.overflow) {
+            if line.pen > max_width {
+                line = self.ellipsize(text, &fonts, options, &line, max_width, &mut stats)?;
+            }
+        }
+        let Line { layers, runs, pen } = line;
+
+        // A renderer boxes its whole call, so boxes per run need compositing
+        let boxes = match options.render.effects.background_box {
+            Some(plate) if plate.scope == BoxScope::Run => runs,
+            _ => vec![(0.0, pen)],
+        };
+        let bitmap = match layers.as_slice() {
+            [] => {
+                let empty = LayerKey::default().empty(options);
+                let params = options.render.presented();
+                self.render_layer(&empty, &fonts[0], &params, &mut stats)
+            },
+            [(key, layer)] if boxes.len() == 1 => {
+                let params = key.render_params(&options.render, options);
+                self.render_layer(layer, &fonts[key.font], &params, &mut stats)
+            },
+            _ => self.composite(&layers, &fonts, &boxes, options, &mut stats),
+        }?;
+        log::debug!("{stats}");
+        Ok((bitmap, stats))
+    }
+
+    /// Size `text` as [`render_text`](Self::render_text) would lay it out,
+    /// without rasterizing anything
+    ///
+    /// Segments and shapes exactly as rendering does, so the advance matches
+    /// the rendered line. With a [`layout`](RenderParams::layout) width, the
+    /// text is also wrapped by its [`WrapMode`] and measured as a paragraph
+    /// whose lines are `ascent + descent` apart; under [`Overflow::Clip`]
+    /// and [`Overflow::Ellipsis`] no line counts as wider than the width.
+    /// The ink box comes from the fonts' outline tables and covers every
+    /// glyph, clipped or not.
+    pub fn measure(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        options: &TextOptions,
+    ) -> Result<TextMetrics> {
+        let fonts = chain(font, options);
+        let Paragraph {
+            line,
+            lines,
+            ascent: line_ascent,
+            descent: line_descent,
+        } = self.paragraph(text, &fonts, options, options.render.layout.max_width)?;
+        let variations = &options.render.variations;
+
+        let layout = &options.render.layout;
+        let advance = lines
+            .iter()
+            .map(|(_, width)| layout.visible_width(*width))
+            .fold(0.0, f32::max);
+
+        // Each glyph sits on the line holding its cluster, moved to that
+        // line's left edge and down by the lines above it
+        let mut origins: Vec<f32> = vec![f32::INFINITY; lines.len()];
+        let line_of = |cluster: u32| {
+            lines
+                .iter()
+                .position(|(range, _)| range.contains(&(cluster as usize)))
+        };
+        if lines.len() > 1 {
+            for glyph in line.layers.iter().flat_map(|(_, layer)| &layer.glyphs) {
+                if let Some(i) = line_of(glyph.cluster) {
+                    origins[i] = origins[i].min(glyph.x);
+                }
+            }
+        } else {
+            origins.fill(0.0);
+        }
+        let pitch = line_ascent + line_descent;
+        let mut tight_bbox: Option<GlyphBounds> = None;
+        for (key, layer) in &line.layers {
+            let ids: Vec<_> = layer.glyphs.iter().map(|g| g.id).collect();
+            let font = fonts[key.font].as_ref();
+            let metrics = match measure_glyphs(font, &ids, layer.advance_height, variations) {
+                Ok(metrics) => metrics,
+                Err(e) => {
+                    log::debug!("No ink bounds for font {}: {}", key.font, e);
+                    continue;
+                },
+            };
+            for (glyph, metrics) in layer.glyphs.iter().zip(metrics) {
+                let (Some(ink), Some(i)) = (metrics.bounds, line_of(glyph.cluster)) else {
+                    continue;
+                };
+                // `glyph.y` points down; the box points up
+                let x = glyph.x - origins[i];
+                let y = -glyph.y - i as f32 * pitch;
+                let placed = GlyphBounds {
+                    x_min: ink.x_min + x,
+                    y_min: ink.y_min + y,
+                    x_max: ink.x_max + x,
+                    y_max: ink.y_max + y,
+                };
+                tight_bbox = Some(match tight_bbox {
+                    Some(b) => GlyphBounds {
+                        x_min: b.x_min.min(placed.x_min),
+                        y_min: b.y_min.min(placed.y_min),
+                        x_max: b.x_max.max(placed.x_max),
+                        y_max: b.y_max.max(placed.y_max),
+                    },
+                    None => placed,
+                });
+            }
+        }
+
+        Ok(TextMetrics {
+            advance,
+            ascent: line_ascent,
+            descent: line_descent,
+            line_count: lines.len(),
+            tight_bbox,
+        })
+    }
+
+    /// Characters of `text` that would be drawn as `.notdef` (tofu): those no
+    /// font in the chain, `font` then [`TextOptions::fallback_fonts`], has
+    /// a glyph for, in text order
+    ///
+    /// Shapes as [`render_text`](Self::render_text) does, so a character is
+    /// only reported when shaping really produced `.notdef` for it. Control
+    /// characters, which are never drawn, are left out. Rendering with
+    /// [`RenderParams::diagnostics`] set also records each one there.
+    pub fn missing_glyphs(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        options: &TextOptions,
+    ) -> Result<Vec<MissingGlyph>> {
+        let fonts = chain(font, options);
+        let line = self.lay_out(text, &fonts, options, &mut RenderStats::default())?;
+        Ok(missing_glyphs(text, &fonts, &line))
+    }
+
+    /// Lay `text` out into the box of the [`layout`](RenderParams::layout)
+    /// width and height, as far as it fits
+    ///
+    /// Wraps as [`measure`](Self::measure) does and keeps the lines whose
+    /// full `ascent + descent` fits in the layout's
+    /// [`max_height`](typf_core::TextLayout::max_height). The rest is
+    /// reported as [`TextBlock::overflow`], a byte offset to lay out into the
+    /// next box or page from. Without a width the text is one line; without
+    /// a height every line fits. A box too short for one line takes none,
+    /// and the whole text overflows.
+    pub fn lay_out_block(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        options: &TextOptions,
+    ) -> Result<TextBlock> {
+        let fonts = chain(font, options);
+        let layout = &options.render.layout;
+        let paragraph = self.paragraph(text, &fonts, options, layout.max_width)?;
+        let fit = paragraph.fit(layout.max_height);
+        let overflow = paragraph.overflow(fit);
+        Ok(TextBlock {
+            lines: paragraph.block_lines(0..fit),
+            overflow,
+            ascent: paragraph.ascent,
+            descent: paragraph.descent,
+        })
+    }
+
+    /// Lay `text` out into side-by-side columns that share the
+    /// [`layout`](RenderParams::layout) width, as far as it fits
+    ///
+    /// Each column is `columns.gutter` from the next and as wide as an equal
+    /// share of what the gutters leave. Lines wrap at that width and fill
+    /// the columns in order, each up to the layout's
+    /// [`max_height`](typf_core::TextLayout::max_height); with
+    /// [`Columns::balance`], the lines are shared out so that column
+    /// lengths differ by at most one, the longer columns first, and the text
+    /// ends level across the columns rather than filling the first. Text
+    /// that does not fit even balanced fills each column in turn. Text past
+    /// the last column is reported as [`ColumnBlock::overflow`].
+    ///
+    /// Fails with [`TypfError::ConfigError`] without a layout width, for
+    /// zero columns, or when the gutters leave the columns no width.
+    pub fn lay_out_columns(
+        &self,
+        text: &str,
+        font: Arc<dyn FontRef>,
+        options: &TextOptions,
+        columns: &Columns,
+    ) -> Result<ColumnBlock> {
+        let layout = &options.render.layout;
+        let Some(total) = layout.max_width else {
+            return Err(TypfError::ConfigError(
+                "Columns need a layout width to share".to_string(),
+            ));
+        };
+        if columns.count == 0 {
+            return Err(TypfError::ConfigError(
+                "Column count must be at least 1".to_string(),
+            ));
+        }
+        let gutters = columns.gutter.max(0.0) * (columns.count - 1) as f32;
+        let width = (total - gutters) / columns.count as f32;
+        if !width.is_finite() || width <= 0.0 {
+            return Err(TypfError::ConfigError(format!(
+                "{} columns with {} px gutters do not fit in {} px",
+                columns.count, columns.gutter, total
+            )));
+        }
+
+        let fonts = chain(font, options);
+        let paragraph = self.paragraph(text, &fonts, options, Some(width))?;
+        let fit = paragraph.fit(layout.max_height);
+        let lines = paragraph.lines.len();
+        // Balancing only applies when everything fits; otherwise fill up
+        let balance = columns.balance && lines <= fit * columns.count;
+
+        let mut placed = 0;
+        let mut laid = Vec::with_capacity(columns.count);
+        for i in 0..columns.count {
+            let take = if balance {
+                lines / columns.count + usize::from(i < lines % columns.count)
+            } else {
+                fit
+            };
+            let end = (placed + take).min(lines);
+            laid.push(Column {
+                x: i as f32 * (width + columns.gutter.max(0.0)),
+                width,
+                lines: paragraph.block_lines(placed..end),
+            });
+            placed = end;
+        }
+        Ok(ColumnBlock {
+            columns: laid,
+            overflow: paragraph.overflow(placed),
+            ascent: paragraph.ascent,
+            descent: paragraph.descent,
+        })
+    }
+
+    /// Lay `text` out on one line, then wrap it at `max_width`
+    fn paragraph(
+        &self,
+        text: &str,
+        fonts: &[Arc<dyn FontRef>],
+        options: &TextOptions,
+        max_width: Option<f32>,
+    ) -> Result<Paragraph> {
+        let mut stats = RenderStats::default();
+        let line = self.lay_out(text, fonts, options, &mut stats)?;
+        let variations = &options.render.variations;
+
+        let (mut line_ascent, mut line_descent) = (0.0_f32, 0.0_f32);
+        if line.layers.is_empty() {
+            let size = options.shaping.size;
+            line_ascent = ascent(fonts[0].as_ref(), size, variations);
+            line_descent = descent(fonts[0].as_ref(), size, variations);
+        }
+        for (key, layer) in &line.layers {
+            let (font, size) = (fonts[key.font].as_ref(), layer.advance_height);
+            line_ascent = line_ascent.max(ascent(font, size, variations));
+            line_descent = line_descent.max(descent(font, size, variations));
+        }
+        // Fonts without vertical metrics still get lines an em apart
+        if line_ascent + line_descent <= 0.0 {
+            line_ascent = options.shaping.size;
+        }
+
+        let lines = match max_width {
+            _ if text.is_empty() => Vec::new(),
+            Some(max_width) => wrap(text, &line, options.render.layout.wrap, max_width),
+            None => vec![(0..text.len(), line.pen)],
+        };
+        Ok(Paragraph {
+            line,
+            lines,
+            ascent: line_ascent,
+            descent: line_descent,
+        })
+    }
+
+    /// `text` cut short at a cluster boundary and ended with an ellipsis,
+    /// laid out, so that it fits `max_width`
+    ///
+    /// Binary search over the cuts, laying out each candidate; the bare
+    /// ellipsis when not even one cluster fits. The line's runs in `stats`
+    /// are replaced by the shown line's.
+    fn ellipsize(
+        &self,
+        text: &str,
+        fonts: &[Arc<dyn FontRef>],
+        options: &TextOptions,
+        line: &Line,
+        max_width: f32,
+        stats: &mut RenderStats,
+    ) -> Result<Line> {
+        let ellipsis = match covering_font(fonts, '\u{2026}') {
+            Some(_) => "\u{2026}",
+            None => "...",
+        };
+        let mut cuts: Vec<usize> = line
+            .layers
+            .iter()
+            .flat_map(|(_, layer)| &layer.glyphs)
+            .map(|g| g.cluster as usize)
+            .filter(|&cut| cut > 0 && cut < text.len() && text.is_char_boundary(cut))
+            .collect();
+        cuts.sort_unstable();
+        cuts.dedup();
+        let shown = |cut: usize| format!("{}{ellipsis}", text[..cut].trim_end());
+
+        // Widest cut that fits; 0 keeps nothing but the ellipsis
+        let mut scratch = RenderStats::default();
+        let (mut best, mut lo, mut hi) = (0, 0, cuts.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let trial = self.lay_out(&shown(cuts[mid]), fonts, options, &mut scratch)?;
+            if trial.pen <= max_width {
+                best = cuts[mid];
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        stats.timings.shape += scratch.timings.shape;
+        stats.runs.clear();
+        self.lay_out(&shown(best), fonts, options, stats)
+    }
+
+    /// Segment and shape `text` into layers sharing one pen, as one line
+    fn lay_out(
+        &self,
+        text: &str,
+        fonts: &[Arc<dyn FontRef>],
+        options: &TextOptions,
+        stats: &mut RenderStats,
+    ) -> Result<Line> {
+        let mut layers: Vec<(LayerKey, ShapingResult)> = Vec::new();
+        let (pieces, segment) = span("segment", || pieces(text, fonts, options));
+        stats.timings.segment = segment;
+
+        let mut pen = 0.0;
+        // Pen span of every shaped run, for per-run background boxes
+        let mut runs: Vec<(f32, f32)> = Vec::new();
+        for piece in pieces {
+            // Shapers check too, but one that ignores the token still stops here
+            cancel::check(options.shaping.cancel.as_ref())?;
+            let mut params = options.shaping.clone();
+            params.direction = piece.direction;
+            params.script = None;
+            if piece.small_caps {
+                params.case = TextCase::Uppercase;
+                params.size *= SMALL_CAPS_SCALE;
+            }
+            let (shaped, shape) = span("shape", || {
+                self.shaper.shape(
+                    &text[piece.start..piece.end],
+                    fonts[piece.font].clone(),
+                    &params,
+                )
+            });
+            let shaped = shaped?;
+            stats.timings.shape += shape;
+            stats.runs.push(RunStats {
+                range: piece.start..piece.end,
+                font: piece.font,
+                glyphs: shaped.glyphs.len(),
+                shape,
+            });
+
+            // Clusters stay byte offsets, now into the whole text
+            let key = piece.layer();
+            let at = match layers.iter().position(|(k, _)| *k == key) {
+                Some(at) => at,
+                None => {
+                    layers.push((key, key.empty(options)));
+                    layers.len() - 1
+                },
+            };
+            let layer = &mut layers[at].1;
+            layer
+                .glyphs
+                .extend(shaped.glyphs.iter().map(|g| PositionedGlyph {
+                    x: g.x + pen,
+                    cluster: g.cluster + piece.start as u32,
+                    ..*g
+                }));
+            layer.advance_height = layer.advance_height.max(shaped.advance_height);
+            runs.push((pen, pen + shaped.advance_width));
+            pen += shaped.advance_width;
+        }
+
+        // Every layer spans the whole line, so they share a left edge
+        layers.retain(|(_, layer)| !layer.glyphs.is_empty());
+        for (_, layer) in &mut layers {
+            layer.advance_width = pen;
+        }
+        Ok(Line { layers, runs, pen })
+    }
+
+    fn render_layer(
+        &self,
+        layer: &ShapingResult,
+        font: &Arc<dyn FontRef>,
+        params: &RenderParams,
+        stats: &mut RenderStats,
+    ) -> Result<BitmapData> {
+        let (output, rasterize) = span("rasterize", || {
+            self.renderer.render(layer, font.clone(), params)
+        });
+        stats.timings.rasterize += rasterize;
+        stats.glyphs_rendered += layer.glyphs.len();
+        match output? {
+            RenderOutput::Bitmap(bitmap) => {
+                stats.bytes_allocated += bitmap.data.len();
+                Ok(bitmap)
+            },
+            _ => Err(TypfError::ConfigError(format!(
+                "Renderer {} does not produce bitmaps",
+                self.renderer.name()
+            ))),
+        }
+    }
+
+    /// Render each used layer bare, then stack them on one baseline
+    ///
+    /// `boxes` are the pen spans to draw the background box behind.
+    fn composite(
+        &self,
+        layers: &[(LayerKey, ShapingResult)],
+        fonts: &[Arc<dyn FontRef>],
+        boxes: &[(f32, f32)],
+        options: &TextOptions,
+        stats: &mut RenderStats,
+    ) -> Result<BitmapData> {
+        let mut bare = options.render.clone();
+        bare.padding = 0;
+        bare.background = None;
+        bare.effects.background_box = None;
+
+        let mut rendered = Vec::with_capacity(layers.len());
+        let mut line_descent: f32 = 0.0;
+        for (key, layer) in layers {
+            let font = &fonts[key.font];
+            let params = key.render_params(&bare, options);
+            let bitmap = self.render_layer(layer, font, &params, stats)?;
+            let (font, size) = (font.as_ref(), layer.advance_height);
+            rendered.push((bitmap, ascent(font, size, &params.variations)));
+            line_descent = line_descent.max(descent(font, size, &params.variations));
+        }
+
+        // Layers put their pen origin this far into their bitmaps
+        let insets = bare.canvas_insets(0);
+        let plate = options.render.effects.background_box.map(|plate| Plate {
+            plate,
+            spans: boxes,
+            origin: (insets.left as f32, insets.top as f32),
+            descent: line_descent,
+        });
+        let (bitmap, composite) = span("composite", || stack(&rendered, plate, options));
+        stats.timings.composite = composite;
+        let bitmap = bitmap?;
+        stats.bytes_allocated += bitmap.data.len();
+        Ok(bitmap)
+    }
+}
+
+/// Text shaped onto one line, before rendering
+struct Line {
+    /// One shaped run per layer, all spanning the whole line
+    layers: Vec<(LayerKey, ShapingResult)>,
+    /// Pen span of every shaped run, for per-run background boxes
+    runs: Vec<(f32, f32)>,
+    /// Advance of the whole line
+    pen: f32,
+}
+
+/// Text laid out on one line and broken into lines
+struct Paragraph {
+    line: Line,
+    /// Byte range and width of every line
+    lines: Vec<(Range<usize>, f32)>,
+    /// Highest ascent and deepest descent of the fonts used
+    ascent: f32,
+    descent: f32,
+}
+
+impl Paragraph {
+    /// How many lines fit in `max_height`; all of them without one
+    fn fit(&self, max_height: Option<f32>) -> usize {
+        let pitch = self.ascent + self.descent;
+        match max_height {
+            Some(max_height) if pitch > 0.0 => {
+                ((max_height.max(0.0) / pitch).floor() as usize).min(self.lines.len())
+            },
+            _ => self.lines.len(),
+        }
+    }
+
+    /// Where the text left after the first `placed` lines starts
+    fn overflow(&self, placed: usize) -> Option<usize> {
+        self.lines.get(placed).map(|(range, _)| range.start)
+    }
+
+    /// Lines `range` as a box's lines, the first at the top of the box
+    fn block_lines(&self, range: Range<usize>) -> Vec<BlockLine> {
+        let pitch = self.ascent + self.descent;
+        self.lines[range]
+            .iter()
+            .enumerate()
+            .map(|(i, (range, width))| BlockLine {
+                range: range.clone(),
+                width: *width,
+                baseline: self.ascent + i as f32 * pitch,
+            })
+            .collect()
+    }
+}
+
+/// A background box to draw behind stacked layers
+struct Plate<'a> {
+    plate: BackgroundBox,
+    /// Pen spans to box, each from its start to its end
+    spans: &'a [(f32, f32)],
+    /// Pen origin and line top within each layer's bitmap
+    origin: (f32, f32),
+    /// Deepest descent of the layers' fonts
+    descent: f32,
+}
+
+/// Stack bitmaps, each with its font's ascent, so the ascents line up
+fn stack(
+    rendered: &[(BitmapData, f32)],
+    plate: Option<Plate<'_>>,
+    options: &TextOptions,
+) -> Result<BitmapData> {
+    let line_ascent = rendered.iter().map(|(_, a)| *a).fold(0.0, f32::max);
+    // Auto padding makes room for the box, as the renderers would have
+    let room = match &plate {
+        Some(p) if options.render.auto_padding => {
+            RenderEffects {
+                background_box: Some(p.plate),
+                ..RenderEffects::default()
+            }
+            .required_insets()
+            .left
+        },
+        _ => 0,
+    };
+    let padding = options.render.padding + room;
+    let tops: Vec<u32> = rendered
+        .iter()
+        .map(|(_, a)| (line_ascent - a).round() as u32)
+        .collect();
+    let width = rendered.iter().map(|(b, _)| b.width).max().unwrap_or(0) + 2 * padding;
+    let height = rendered
+        .iter()
+        .zip(&tops)
+        .map(|((b, _), top)| top + b.height)
+        .max()
+        .unwrap_or(0)
+        + 2 * padding;
+
+    let format = options.render.format;
+    let stride = format.stride(width, options.render.row_alignment);
+    let mut data = vec![0u8; stride * height as usize];
+    let mut target = BufferTarget::new(&mut data, width, height, stride, format)?;
+    if let Some(background) = options.render.background {
+        fill_rect(&mut target, 0, 0, width, height, background)?;
+    }
+    if let Some(Plate {
+        plate,
+        spans,
+        origin,
+        descent,
+    }) = plate
+    {
+        let x = padding as f32 + origin.0;
+        let top = padding as f32 + origin.1;
+        let bottom = top + line_ascent + descent;
+        for &(start, end) in spans {
+            plate.fill(&mut target, x + start, top, x + end, bottom)?;
+        }
+    }
+    for ((bitmap, _), top) in rendered.iter().zip(&tops) {
+        composite_bitmap(&mut target, padding as i32, (padding + top) as i32, bitmap)?;
+    }
+
+    Ok(BitmapData {
+        width,
+        height,
+        format,
+        stride,
+        layout: PixelLayout::RGBA,
+        data,
+    }
+    .into_layout(options.render.pixel_layout))
+}
+
+impl std::fmt::Debug for TypfPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TypfPipeline")
+            .field("shaper", &self.shaper.name())
+            .field("renderer", &self.renderer.name())
+            .finish()
+    }
+}
+
+/// Picks the backends of a [`TypfPipeline`]
+///
+/// Shapers and renderers can be given by name or as instances. Both default
+/// to `"auto"`: the best shaper this build has (HarfBuzz, then HarfRust,
+/// then rustybuzz, then `none`) and the `opixa` renderer.
+#[derive(Default)]
+pub struct TypfPipelineBuilder {
+    shaper: Option<Backend<dyn Shaper>>,
+    renderer: Option<Backend<dyn Renderer>>,
+}
+
+enum Backend<T: ?Sized> {
+    Named(String),
+    Instance(Arc<T>),
+}
+
+impl TypfPipelineBuilder {
+    /// Both backends on `"auto"`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shape with the backend called `name`
+    ///
+    /// Accepts `auto`, `none`, `hb`/`harfbuzz`, `hr`/`harfrust`, `rustybuzz`,
+    /// `icu-hb`/`icu-harfbuzz`, and `ct`/`coretext`/`mac`, as enabled by features.
+    pub fn shaper(mut self, name: &str) -> Self {
+        self.shaper = Some(Backend::Named(name.to_string()));
+        self
+    }
+
+    /// Shape with `shaper`
+    pub fn with_shaper(mut self, shaper: Arc<dyn Shaper>) -> Self {
+        self.shaper = Some(Backend::Instance(shaper));
+        self
+    }
+
+    /// Render with the backend registered in [`renderers`](crate::renderers) as `name`
+    ///
+    /// `auto` means `opixa`.
+    pub fn renderer(mut self, name: &str) -> Self {
+        self.renderer = Some(Backend::Named(name.to_string()));
+        self
+    }
+
+    /// Render with `renderer`
+    pub fn with_renderer(mut self, renderer: Arc<dyn Renderer>) -> Self {
+        self.renderer = Some(Backend::Instance(renderer));
+        self
+    }
+
+    /// Create the backends
+    ///
+    /// Fails with [`TypfError::ConfigError`] if a name is unknown or its
+    /// feature is not enabled, or with the error a backend hit starting up.
+    pub fn build(self) -> Result<TypfPipeline> {
+        let shaper = match self.shaper {
+            Some(Backend::Instance(shaper)) => shaper,
+            Some(Backend::Named(name)) => create_shaper(&name)?,
+            None => create_shaper("auto")?,
+        };
+        let renderer = match self.renderer {
+            Some(Backend::Instance(renderer)) => renderer,
+            Some(Backend::Named(name)) => create_renderer(&name)?,
+            None => create_renderer("auto")?,
+        };
+        Ok(TypfPipeline { shaper, renderer })
+    }
+}
+
+/// A stretch of text shaped in one go
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Piece {
+    start: usize,
+    end: usize,
+    font: usize,
+    direction: Direction,
+    /// Lowercase letters drawn as synthesized small caps
+    small_caps: bool,
+    /// Emoji drawn as text or as emoji, when that was resolved per sequence
+    presentation: Option<Presentation>,
+    /// Index of the glyph source override covering the piece
+    sources: Option<usize>,
+}
+
+impl Piece {
+    /// The layer this piece is drawn on
+    fn layer(&self) -> LayerKey {
+        LayerKey {
+            font: self.font,
+            small_caps: self.small_caps,
+            presentation: self.presentation,
+            sources: self.sources,
+        }
+    }
+}
+
+/// What sets a layer apart: its font, its size, and its glyph sources
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct LayerKey {
+    font: usize,
+    small_caps: bool,
+    presentation: Option<Presentation>,
+    sources: Option<usize>,
+}
+
+impl LayerKey {
+    /// A layer with no glyphs yet, at this key's size
+    fn empty(&self, options: &TextOptions) -> ShapingResult {
+        let scale = if self.small_caps {
+            SMALL_CAPS_SCALE
+        } else {
+            1.0
+        };
+        ShapingResult {
+            glyphs: Vec::new(),
+            advance_width: 0.0,
+            advance_height: options.shaping.size * scale,
+            direction: options.shaping.direction,
+        }
+    }
+
+    /// `params` with this layer's glyph sources
+    fn render_params<'a>(
+        &self,
+        params: &'a RenderParams,
+        options: &TextOptions,
+    ) -> Cow<'a, RenderParams> {
+        let overridden = self
+            .sources
+            .and_then(|i| options.glyph_source_overrides.get(i));
+        let Some(sources) = overridden else {
+            return match self.presentation {
+                Some(presentation) => Cow::Owned(params.for_presentation(presentation)),
+                None => params.presented(),
+            };
+        };
+        let params = RenderParams {
+            glyph_sources: sources.glyph_sources.clone(),
+            ..params.clone()
+        };
+        Cow::Owned(match self.presentation {
+            Some(presentation) => params.for_presentation(presentation),
+            None => params.presented().into_owned(),
+        })
+    }
+}
+
+/// The main font followed by the fallback fonts
+fn chain(font: Arc<dyn FontRef>, options: &TextOptions) -> Vec<Arc<dyn FontRef>> {
+    std::iter::once(font)
+        .chain(options.fallback_fonts.iter().cloned())
+        .collect()
+}
+
+/// Break laid-out text into lines no wider than `max_width`, as byte ranges
+/// with their widths
+///
+/// Lines end where `mode` allows, and always after a line terminator.
+/// Widths are summed from the advances of the line's clusters, without
+/// reshaping; trailing spaces hang past the edge. What cannot break, such as
+/// a word wider than `max_width` in [`WrapMode::Word`], overflows its line.
+#[cfg(feature = "unicode")]
+fn wrap(text: &str, line: &Line, mode: WrapMode, max_width: f32) -> Vec<(Range<usize>, f32)> {
+    use typf_unicode::{grapheme_boundaries, line_breaks, LineBreak};
+
+    let mut advances: Vec<(usize, f32)> = line
+        .layers
+        .iter()
+        .flat_map(|(_, layer)| &layer.glyphs)
+        .map(|g| (g.cluster as usize, g.advance))
+        .collect();
+    advances.sort_by_key(|&(cluster, _)| cluster);
+    // Advances summed up to each cluster, for widths in two lookups
+    let sums: Vec<f32> = std::iter::once(0.0)
+        .chain(advances.iter().scan(0.0, |sum, &(_, advance)| {
+            *sum += advance;
+            Some(*sum)
+        }))
+        .collect();
+    let width = |range: &Range<usize>| {
+        let first = advances.partition_point(|&(c, _)| c < range.start);
+        let last = advances.partition_point(|&(c, _)| c < range.end);
+        sums[last] - sums[first]
+    };
+    // Trailing spaces and line terminators are not part of a line
+    let line_to = |start: usize, offset: usize| {
+        let kept = text[start..offset].trim_end_matches(char::is_whitespace);
+        start..start + kept.len()
+    };
+
+    let opportunities: Vec<LineBreak> = match mode {
+        WrapMode::Word | WrapMode::WordThenChar => line_breaks(text).collect(),
+        WrapMode::Char => {
+            let mandatory: Vec<usize> = line_breaks(text)
+                .filter(|b| b.mandatory)
+                .map(|b| b.offset)
+                .collect();
+            grapheme_boundaries(text)
+                .filter(|&offset| offset > 0)
+                .map(|offset| LineBreak {
+                    offset,
+                    mandatory: mandatory.contains(&offset),
+                })
+                .collect()
+        },
+        WrapMode::NoWrap => line_breaks(text)
+            .filter(|b| b.mandatory || b.offset == text.len())
+            .collect(),
+    };
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    // Furthest opportunity on the current line that still fits
+    let mut fits: Option<usize> = None;
+    for line_break in opportunities {
+        let range = line_to(start, line_break.offset);
+        if width(&range) > max_width {
+            if let Some(at) = fits.take() {
+                let range = line_to(start, at);
+                lines.push((range.clone(), width(&range)));
+                start = at;
+            }
+            // A word too wide for a line of its own breaks between clusters
+            if mode == WrapMode::WordThenChar {
+                let end = line_break.offset;
+                let inside: Vec<usize> = grapheme_boundaries(&text[start..end])
+                    .map(|offset| start + offset)
+                    .filter(|&at| at > start && at < end)
+                    .collect();
+                for at in inside {
+                    if width(&line_to(start, at)) > max_width {
+                        if let Some(fit) = fits.take() {
+                            let range = line_to(start, fit);
+                            lines.push((range.clone(), width(&range)));
+                            start = fit;
+                        }
+                    }
+                    fits = Some(at);
+                }
+                // Whatever is left of the word ends the line if it is
+                // still too wide with the next cluster
+                if width(&line_to(start, end)) > max_width {
+                    if let Some(fit) = fits.take() {
+                        let range = line_to(start, fit);
+                        lines.push((range.clone(), width(&range)));
+                        start = fit;
+                    }
+                }
+            }
+        }
+        if line_break.mandatory {
+            let range = line_to(start, line_break.offset);
+            lines.push((range.clone(), width(&range)));
+            start = line_break.offset;
+            fits = None;
+        } else {
+            fits = Some(line_break.offset);
+        }
+    }
+    if start < text.len() {
+        let range = line_to(start, text.len());
+        lines.push((range.clone(), width(&range)));
+    }
+    lines
+}
+
+/// Without the `unicode` feature the text stays on one line
+#[cfg(not(feature = "unicode"))]
+fn wrap(text: &str, line: &Line, _mode: WrapMode, _max_width: f32) -> Vec<(Range<usize>, f32)> {
+    vec![(0..text.len(), line.pen)]
+}
+
+/// Split `text` into pieces of one direction and one font, in visual order
+fn pieces(text: &str, fonts: &[Arc<dyn FontRef>], options: &TextOptions) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    for (start, end, direction) in runs(text, options) {
+        let mut current: Option<Piece> = None;
+        for (offset, ch) in text[start..end].char_indices() {
+            let at = start + offset;
+            let font = match &current {
+                // Spaces and joiners never switch fonts on their own
+                Some(piece) if ch.is_whitespace() || is_zero_width(ch) => piece.font,
+                Some(piece) => covering_font(fonts, ch).unwrap_or(piece.font),
+                None => covering_font(fonts, ch).unwrap_or(0),
+            };
+            match &mut current {
+                Some(piece) if piece.font == font => piece.end = at + ch.len_utf8(),
+                _ => {
+                    pieces.extend(current.take());
+                    current = Some(Piece {
+                        start: at,
+                        end: at + ch.len_utf8(),
+                        font,
+                        direction,
+                        small_caps: false,
+                        presentation: None,
+                        sources: None,
+                    });
+                },
+            }
+        }
+        pieces.extend(current);
+    }
+    if !options.glyph_source_overrides.is_empty() {
+        pieces = apply_overrides(text, pieces, &options.glyph_source_overrides);
+    }
+    if options.shaping.case == TextCase::SmallCaps {
+        pieces = synthesize_small_caps(text, pieces, fonts);
+    }
+    if options.render.emoji_presentation == EmojiPresentation::Auto {
+        pieces = resolve_presentation(text, pieces, fonts);
+    }
+    visual_order(&mut pieces, options.shaping.direction);
+    pieces
+}
+
+/// Split pieces where glyph source overrides start and end, and note the
+/// override covering each part
+fn apply_overrides(
+    text: &str,
+    pieces: Vec<Piece>,
+    overrides: &[GlyphSourceOverride],
+) -> Vec<Piece> {
+    let mut split = Vec::with_capacity(pieces.len());
+    for piece in pieces {
+        let mut cuts: Vec<usize> = overrides
+            .iter()
+            .flat_map(|o| [o.range.start, o.range.end])
+            .filter(|&cut| cut > piece.start && cut < piece.end && text.is_char_boundary(cut))
+            .collect();
+        cuts.sort_unstable();
+        cuts.dedup();
+        let mut start = piece.start;
+        for end in cuts.into_iter().chain([piece.end]) {
+            let sources = overrides
+                .iter()
+                .rposition(|o| o.range.start <= start && end <= o.range.end);
+            split.push(Piece {
+                start,
+                end,
+                sources,
+                ..piece
+            });
+            start = end;
+        }
+    }
+    split
+}
+
+/// Split the pieces of fonts without `smcp` where lowercase letters start and
+/// stop, so the letters can be shaped as shrunken capitals
+fn synthesize_small_caps(text: &str, pieces: Vec<Piece>, fonts: &[Arc<dyn FontRef>]) -> Vec<Piece> {
+    let lacking: Vec<bool> = fonts
+        .iter()
+        .map(|font| !has_small_caps(font.as_ref()))
+        .collect();
+    let mut split = Vec::with_capacity(pieces.len());
+    for piece in pieces {
+        if !lacking[piece.font] {
+            split.push(piece);
+            continue;
+        }
+        split.extend(
+            small_caps_pieces(&text[piece.start..piece.end])
+                .into_iter()
+                .map(|small| Piece {
+                    start: piece.start + small.range.start,
+                    end: piece.start + small.range.end,
+                    small_caps: small.synthesized,
+                    ..piece
+                }),
+        );
+    }
+    split
+}
+
+/// Split the pieces of color fonts where emoji presentation changes, so text
+/// and emoji sequences get their own glyph sources
+fn resolve_presentation(text: &str, pieces: Vec<Piece>, fonts: &[Arc<dyn FontRef>]) -> Vec<Piece> {
+    let color: Vec<bool> = fonts
+        .iter()
+        .map(|font| has_color_glyphs(font.as_ref()))
+        .collect();
+    let mut split = Vec::with_capacity(pieces.len());
+    for piece in pieces {
+        if !color[piece.font] {
+            split.push(piece);
+            continue;
+        }
+        split.extend(
+            presentation_runs(&text[piece.start..piece.end])
+                .into_iter()
+                .map(|run| Piece {
+                    start: piece.start + run.range.start,
+                    end: piece.start + run.range.end,
+                    presentation: run.presentation,
+                    ..piece
+                }),
+        );
+    }
+    split
+}
+
+/// Script and direction runs as `(start, end, direction)`, in logical order
+#[cfg(feature = "unicode")]
+fn runs(text: &str, options: &TextOptions) -> Vec<(usize, usize, Direction)> {
+    let unicode = typf_unicode::UnicodeOptions {
+        detect_scripts: true,
+        normalize: false, // offsets must point into the caller's text
+        bidi_resolve: true,
+        language: options.shaping.language.clone(),
+    };
+    match typf_unicode::UnicodeProcessor::new().process(text, &unicode) {
+        Ok(runs) => runs
+            .into_iter()
+            .map(|run| (run.start, run.end, run.direction))
+            .collect(),
+        Err(e) => {
+            log::warn!("Unicode segmentation failed, shaping as one run: {}", e);
+            vec![(0, text.len(), options.shaping.direction)]
+        },
+    }
+}
+
+/// Without the `unicode` feature the whole text is one run
+#[cfg(not(feature = "unicode"))]
+fn runs(text: &str, options: &TextOptions) -> Vec<(usize, usize, Direction)> {
+    vec![(0, text.len(), options.shaping.direction)]
+}
+
+/// Characters of the clusters that shaped to `.notdef` which no font maps
+///
+/// A cluster can hold characters the font does have, such as a base letter
+/// with an unsupported mark; only the unmapped ones are reported, or all of
+/// the cluster when every character maps but shaping still gave `.notdef`.
+fn missing_glyphs(text: &str, fonts: &[Arc<dyn FontRef>], line: &Line) -> Vec<MissingGlyph> {
+    let glyphs = || line.layers.iter().flat_map(|(_, layer)| &layer.glyphs);
+    let mut starts: Vec<usize> = glyphs().map(|g| g.cluster as usize).collect();
+    starts.sort_unstable();
+    starts.dedup();
+    let mut notdef: Vec<usize> = glyphs()
+        .filter(|g| g.id == 0)
+        .map(|g| g.cluster as usize)
+        .collect();
+    notdef.sort_unstable();
+    notdef.dedup();
+
+    let mut missing = Vec::new();
+    for start in notdef {
+        let end = starts
+            .iter()
+            .find(|&&next| next > start)
+            .copied()
+            .unwrap_or(text.len());
+        let Some(cluster) = text.get(start..end) else {
+            continue;
+        };
+        let drawn = cluster
+            .char_indices()
+            .filter(|(_, ch)| !ch.is_control())
+            .map(|(offset, ch)| MissingGlyph {
+                offset: start + offset,
+                ch,
+            });
+        let unmapped: Vec<_> = drawn
+            .clone()
+            .filter(|m| covering_font(fonts, m.ch).is_none())
+            .collect();
+        if unmapped.is_empty() {
+            missing.extend(drawn);
+        } else {
+            missing.extend(unmapped);
+        }
+    }
+    missing
+}
+
+/// Index of the first font with a glyph for `ch`
+fn covering_font(fonts: &[Arc<dyn FontRef>], ch: char) -> Option<usize> {
+    fonts
+        .iter()
+        .position(|font| font.glyph_id(ch).is_some_and(|id| id != 0))
+}
+
+/// Reorder logical `pieces` for display on a line read in `base` direction
+///
+/// A simplified form of bidi rule L2 with two levels: consecutive pieces
+/// against the base direction are reversed in place, and on a right-to-left
+/// line the whole sequence is reversed first.
+fn visual_order(pieces: &mut [Piece], base: Direction) {
+    let base_rtl = base == Direction::RightToLeft;
+    if base_rtl {
+        pieces.reverse();
+    }
+    let against = |piece: &Piece| (piece.direction == Direction::RightToLeft) != base_rtl;
+    let mut i = 0;
+    while i < pieces.len() {
+        if against(&pieces[i]) {
+            let start = i;
+            while i < pieces.len() && against(&pieces[i]) {
+                i += 1;
+            }
+            pieces[start..i].reverse();
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Where renderers put the baseline below the top of a bare layer, in pixels
+fn ascent(font: &dyn FontRef, size: f32, variations: &[(String, f32)]) -> f32 {
+    font.metrics_at(variations)
+        .filter(|m| m.units_per_em > 0 && (m.ascent != 0 || m.descent != 0))
+        .map(|m| (m.ascent as f32).max(0.0) * size / m.units_per_em as f32)
+        .unwrap_or(0.0)
+}
+
+fn descent(font: &dyn FontRef, size: f32, variations: &[(String, f32)]) -> f32 {
+    font.metrics_at(variations)
+        .filter(|m| m.units_per_em > 0 && (m.ascent != 0 || m.descent != 0))
+        .map(|m| (m.descent as f32).abs() * size / m.units_per_em as f32)
+        .unwrap_or(0.0)
+}
+
+fn create_renderer(name: &str) -> Result<Arc<dyn Renderer>> {
+    let name = match name.trim() {
+        "auto" => "opixa",
+        other => other,
+    };
+    let renderer: Arc<dyn Renderer> = crate::renderers().create(name)?;
+    Ok(renderer)
+}
+
+fn create_shaper(name: &str) -> Result<Arc<dyn Shaper>> {
+    let shaper: Option<Arc<dyn Shaper>> = match name.trim().to_ascii_lowercase().as_str() {
+        "auto" => best_shaper(),
+
+        #[cfg(feature = "shaping-none")]
+        "none" => Some(Arc::new(typf_shape_none::NoneShaper::new())),
+
+        #[cfg(feature = "shaping-hb")]
+        "hb" | "harfbuzz" => Some(Arc::new(typf_shape_hb::HarfBuzzShaper::new())),
+
+        #[cfg(feature = "shaping-hr")]
+        "hr" | "harfrust" => Some(Arc::new(typf_shape_hr::HarfrustShaper::new())),
+
+        #[cfg(feature = "shaping-rustybuzz")]
+        "rustybuzz" => Some(Arc::new(typf_shape_rustybuzz::RustybuzzShaper::new())),
+
+        #[cfg(feature = "shaping-icu-hb")]
+        "icu-hb" | "icu-harfbuzz" => Some(Arc::new(typf_shape_icu_hb::IcuHarfBuzzShaper::new())),
+
+        #[cfg(feature = "shaping-ct")]
+        "ct" | "coretext" | "mac" => Some(Arc::new(typf_shape_ct::CoreTextShaper::new())),
+
+        _ => None,
+    };
+    shaper.ok_or_else(|| TypfError::ConfigError(format!("Unknown or unavailable shaper: {}", name)))
+}
+
+/// The most capable shaper compiled in
+#[allow(unreachable_code)] // every arm after the first enabled one
+fn best_shaper() -> Option<Arc<dyn Shaper>> {
+    #[cfg(feature = "shaping-hb")]
+    return Some(Arc::new(typf_shape_hb::HarfBuzzShaper::new()));
+    #[cfg(feature = "shaping-hr")]
+    return Some(Arc::new(typf_shape_hr::HarfrustShaper::new()));
+    #[cfg(feature = "shaping-rustybuzz")]
+    return Some(Arc::new(typf_shape_rustybuzz::RustybuzzShaper::new()));
+    #[cfg(feature = "shaping-none")]
+    return Some(Arc::new(typf_shape_none::NoneShaper::new()));
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn piece(font: usize, direction: Direction) -> Piece {
+        Piece {
+            start: font,
+            end: font + 1,
+            font,
+            direction,
+            small_caps: false,
+            presentation: None,
+            sources: None,
+        }
+    }
+
+    #[test]
+    fn test_visual_order_when_ltr_line_then_rtl_pieces_reversed() {
+        use Direction::{LeftToRight as L, RightToLeft as R};
+        let mut pieces = [piece(0, L), piece(1, R), piece(2, R), piece(3, L)];
+        visual_order(&mut pieces, L);
+        let fonts: Vec<usize> = pieces.iter().map(|p| p.font).collect();
+        assert_eq!(fonts, [0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn test_visual_order_when_rtl_line_then_ltr_pieces_keep_order() {
+        use Direction::{LeftToRight as L, RightToLeft as R};
+        let mut pieces = [piece(0, R), piece(1, L), piece(2, L), piece(3, R)];
+        visual_order(&mut pieces, R);
+        let fonts: Vec<usize> = pieces.iter().map(|p| p.font).collect();
+        assert_eq!(fonts, [3, 1, 2, 0]);
+    }
+}
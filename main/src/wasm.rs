@@ -1,10 +1,26 @@
 //! Typf meets the web: Text rendering in your browser
 //!
-//! JavaScript calls, Rust speed. Perfect for canvas rendering,
-//! text measurement, and dynamic typography on the web.
+//! JavaScript hands over font bytes and text, and gets back straight RGBA
+//! pixels sized for `ImageData`, ready for `putImageData` on a canvas:
+//!
+//! ```js
+//! const font = new WasmFont(new Uint8Array(await (await fetch(url)).arrayBuffer()));
+//! const renderer = new WasmRenderer();          // best backends in this build
+//! const image = renderer.render_text(font, "Hello", 48);
+//! ctx.putImageData(new ImageData(image.data, image.width, image.height), 0, 0);
+//! ```
+//!
+//! There is no filesystem in the browser, so fonts only ever come from
+//! bytes. Build with the `wasm-browser` feature for the pure Rust HarfRust
+//! shaper and Zeno renderer.
+
+use std::sync::Arc;
 
+use crate::fontdb::TypfFontFace;
 use crate::prelude::*;
+use typf_core::{error::RenderError, types::BitmapFormat};
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::Clamped;
 
 /// Better panic messages make better debugging experiences
 #[wasm_bindgen(start)]
@@ -13,115 +29,294 @@ pub fn init_panic_hook() {
     console_error_panic_hook::set_once();
 }
 
+/// Shapers compiled into this build, preferred first
+const SHAPERS: &[&str] = &[
+    #[cfg(feature = "shaping-hr")]
+    "harfrust",
+    #[cfg(feature = "shaping-rustybuzz")]
+    "rustybuzz",
+    #[cfg(feature = "shaping-hb")]
+    "harfbuzz",
+    #[cfg(feature = "shaping-none")]
+    "none",
+];
+
+/// Renderers compiled into this build, preferred first
+const RENDERERS: &[&str] = &[
+    #[cfg(feature = "render-zeno")]
+    "zeno",
+    #[cfg(feature = "render-skia")]
+    "skia",
+    #[cfg(feature = "render-opixa")]
+    "opixa",
+];
+
+fn make_shaper(name: &str) -> Option<Arc<dyn Shaper>> {
+    let shaper: Arc<dyn Shaper> = match name {
+        #[cfg(feature = "shaping-hr")]
+        "harfrust" | "hr" => Arc::new(crate::shape_hr::HarfrustShaper::new()),
+        #[cfg(feature = "shaping-rustybuzz")]
+        "rustybuzz" | "rb" => Arc::new(crate::shape_rustybuzz::RustybuzzShaper::new()),
+        #[cfg(feature = "shaping-hb")]
+        "harfbuzz" | "hb" => Arc::new(crate::shape_hb::HarfBuzzShaper::new()),
+        #[cfg(feature = "shaping-none")]
+        "none" => Arc::new(crate::shape_none::NoneShaper::new()),
+        _ => return None,
+    };
+    Some(shaper)
+}
+
+fn make_renderer(name: &str) -> Option<Arc<dyn Renderer>> {
+    let renderer: Arc<dyn Renderer> = match name {
+        #[cfg(feature = "render-zeno")]
+        "zeno" => Arc::new(crate::render_zeno::ZenoRenderer::new()),
+        #[cfg(feature = "render-skia")]
+        "skia" => Arc::new(crate::render_skia::SkiaRenderer::new()),
+        #[cfg(feature = "render-opixa")]
+        "opixa" => Arc::new(crate::render_opixa::OpixaRenderer::new()),
+        _ => return None,
+    };
+    Some(renderer)
+}
+
+/// Names accepted by `new WasmRenderer(shaper, ...)`, best first
+#[wasm_bindgen]
+pub fn available_shapers() -> Vec<String> {
+    SHAPERS.iter().map(|name| name.to_string()).collect()
+}
+
+/// Names accepted by `new WasmRenderer(..., renderer)`, best first
+#[wasm_bindgen]
+pub fn available_renderers() -> Vec<String> {
+    RENDERERS.iter().map(|name| name.to_string()).collect()
+}
+
+/// A font parsed from bytes fetched by JavaScript
+#[wasm_bindgen]
+pub struct WasmFont {
+    face: Arc<TypfFontFace>,
+}
+
+#[wasm_bindgen]
+impl WasmFont {
+    /// Parse face `face_index` (default 0) of a font file's bytes
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &[u8], face_index: Option<u32>) -> Result<WasmFont, JsValue> {
+        let face = TypfFontFace::from_data_index(data.to_vec(), face_index.unwrap_or(0))
+            .map_err(|e| JsValue::from_str(&format!("Font error: {}", e)))?;
+        Ok(WasmFont {
+            face: Arc::new(face),
+        })
+    }
+
+    /// Design units per em
+    #[wasm_bindgen(getter)]
+    pub fn units_per_em(&self) -> u16 {
+        self.face.units_per_em()
+    }
+}
+
+/// Straight (not premultiplied) RGBA pixels, top row first
+#[wasm_bindgen]
+pub struct RenderedText {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl RenderedText {
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// A copy of the pixels as a `Uint8ClampedArray`, as `ImageData` expects
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Clamped<Vec<u8>> {
+        Clamped(self.data.clone())
+    }
+}
+
 /// Your browser's new best friend for text rendering
 #[wasm_bindgen]
 pub struct WasmRenderer {
-    #[cfg(feature = "shaping-none")]
-    shaper: Box<dyn Shaper>,
-    #[cfg(feature = "render-opixa")]
-    renderer: Box<dyn Renderer>,
+    shaper: Arc<dyn Shaper>,
+    renderer: Arc<dyn Renderer>,
+    foreground: Color,
+    background: Option<Color>,
 }
 
 #[wasm_bindgen]
 impl WasmRenderer {
-    /// Ready to render with the minimal, web-friendly backends
+    /// Pick backends by name, or leave either out for the best one built in
+    ///
+    /// See `available_shapers()` and `available_renderers()` for the names.
     #[wasm_bindgen(constructor)]
-    pub fn new() -> Result<WasmRenderer, JsValue> {
-        #[cfg(not(all(feature = "shaping-none", feature = "render-opixa")))]
-        return Err(JsValue::from_str(
-            "WASM needs shaping-none and render-opixa features",
-        ));
-
-        #[cfg(all(feature = "shaping-none", feature = "render-opixa"))]
-        {
-            use crate::render_opixa::OpixaRenderer;
-            use crate::shape_none::NoneShaper;
-
-            Ok(WasmRenderer {
-                shaper: Box::new(NoneShaper::new()),
-                renderer: Box::new(OpixaRenderer::new()),
-            })
-        }
+    pub fn new(shaper: Option<String>, renderer: Option<String>) -> Result<WasmRenderer, JsValue> {
+        Self::with_backends(shaper.as_deref(), renderer.as_deref())
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Name of the shaping backend in use
+    #[wasm_bindgen(getter)]
+    pub fn shaper(&self) -> String {
+        self.shaper.name().to_string()
+    }
+
+    /// Name of the rendering backend in use
+    #[wasm_bindgen(getter)]
+    pub fn renderer(&self) -> String {
+        self.renderer.name().to_string()
+    }
+
+    /// Text color; black unless changed
+    pub fn set_color(&mut self, r: u8, g: u8, b: u8, a: u8) {
+        self.foreground = Color::rgba(r, g, b, a);
+    }
+
+    /// Canvas color; transparent unless changed, and alpha 0 makes it so again
+    pub fn set_background(&mut self, r: u8, g: u8, b: u8, a: u8) {
+        self.background = (a > 0).then(|| Color::rgba(r, g, b, a));
     }
 
     /// Turn text into pixels, right in your browser
     ///
-    /// Returns RGBA bytes ready for canvas or image data
-    #[wasm_bindgen]
+    /// The bitmap is sized to fit the text, so set the canvas (or the
+    /// `putImageData` offset) from its `width` and `height`.
     pub fn render_text(
         &self,
+        font: &WasmFont,
         text: &str,
         font_size: f32,
-        _width: Option<u32>,
-        _height: Option<u32>,
-    ) -> Result<Vec<u8>, JsValue> {
-        #[cfg(not(all(feature = "shaping-none", feature = "render-opixa")))]
-        return Err(JsValue::from_str("Rendering not available in this build"));
-
-        #[cfg(all(feature = "shaping-none", feature = "render-opixa"))]
-        {
-            log::warn!("WASM rendering uses a MockFont stub - output will be incorrect");
-            // TODO: Replace with real font loading
-            struct MockFont {
-                font_size: f32,
-            }
-            impl FontRef for MockFont {
-                fn data(&self) -> &[u8] {
-                    &[]
-                }
-                fn units_per_em(&self) -> u16 {
-                    1000
-                }
-                fn glyph_id(&self, ch: char) -> Option<u32> {
-                    Some(ch as u32)
-                }
-                fn advance_width(&self, _glyph_id: u32) -> f32 {
-                    self.font_size * 0.6
-                }
-            }
-
-            let font = std::sync::Arc::new(MockFont { font_size });
-
-            // Shape those characters
-            let shaping_params = ShapingParams {
-                size: font_size,
-                ..Default::default()
-            };
-
-            let shaped = self
-                .shaper
-                .shape(text, font.clone(), &shaping_params)
-                .map_err(|e| JsValue::from_str(&format!("Shaping error: {}", e)))?;
-
-            // Render to bitmap
-            let render_params = RenderParams {
-                foreground: Color::rgba(0, 0, 0, 255),
-                background: Some(Color::rgba(255, 255, 255, 255)),
-                ..Default::default()
-            };
-
-            let output = self
-                .renderer
-                .render(&shaped, font, &render_params)
-                .map_err(|e| JsValue::from_str(&format!("Render error: {}", e)))?;
-
-            match output {
-                RenderOutput::Bitmap(bitmap) => Ok(bitmap.data),
-                _ => Err(JsValue::from_str("Unexpected output format")),
-            }
-        }
+    ) -> Result<RenderedText, JsValue> {
+        self.rasterize(font, text, font_size)
+            .map_err(|e| JsValue::from_str(&format!("Render error: {}", e)))
+    }
+
+    /// Advance width of the shaped text in pixels
+    pub fn measure_text(
+        &self,
+        font: &WasmFont,
+        text: &str,
+        font_size: f32,
+    ) -> Result<f32, JsValue> {
+        self.shape(font, text, font_size)
+            .map(|shaped| shaped.advance_width)
+            .map_err(|e| JsValue::from_str(&format!("Shaping error: {}", e)))
     }
 
     /// Which version of Typf are you running?
-    #[wasm_bindgen]
     pub fn version(&self) -> String {
         env!("CARGO_PKG_VERSION").to_string()
     }
 }
 
-/// Quick text width measurement (approximation for now)
-#[wasm_bindgen]
-pub fn measure_text(text: &str, font_size: f32) -> f32 {
-    // TODO: Use proper text measurement
-    text.len() as f32 * font_size * 0.6
+impl WasmRenderer {
+    fn with_backends(shaper: Option<&str>, renderer: Option<&str>) -> Result<Self, String> {
+        let shaper_name = shaper
+            .or_else(|| SHAPERS.first().copied())
+            .ok_or("This build has no shaping backend")?;
+        let renderer_name = renderer
+            .or_else(|| RENDERERS.first().copied())
+            .ok_or("This build has no bitmap renderer")?;
+        Ok(Self {
+            shaper: make_shaper(shaper_name)
+                .ok_or_else(|| format!("Shaper `{shaper_name}` is not available in this build"))?,
+            renderer: make_renderer(renderer_name).ok_or_else(|| {
+                format!("Renderer `{renderer_name}` is not available in this build")
+            })?,
+            foreground: Color::rgba(0, 0, 0, 255),
+            background: None,
+        })
+    }
+
+    fn shape(&self, font: &WasmFont, text: &str, font_size: f32) -> Result<ShapingResult> {
+        let params = ShapingParams {
+            size: font_size,
+            ..Default::default()
+        };
+        self.shaper.shape(text, font.face.clone(), &params)
+    }
+
+    fn rasterize(&self, font: &WasmFont, text: &str, font_size: f32) -> Result<RenderedText> {
+        let shaped = self.shape(font, text, font_size)?;
+        let params = RenderParams {
+            foreground: self.foreground,
+            background: self.background,
+            ..Default::default()
+        };
+        match self.renderer.render(&shaped, font.face.clone(), &params)? {
+            RenderOutput::Bitmap(bitmap) if bitmap.format == BitmapFormat::Rgba8 => {
+                Ok(RenderedText {
+                    width: bitmap.width,
+                    height: bitmap.height,
+                    data: bitmap.data,
+                })
+            },
+            _ => Err(RenderError::FormatNotSupported(format!(
+                "{} did not produce an RGBA bitmap",
+                self.renderer.name()
+            ))
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn load_font() -> Option<WasmFont> {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../test-fonts/NotoSans-Regular.ttf"
+        );
+        let Ok(data) = std::fs::read(path) else {
+            eprintln!("skipping: {path} not found");
+            return None;
+        };
+        let face = TypfFontFace::from_data(data).expect("test font parses");
+        Some(WasmFont {
+            face: Arc::new(face),
+        })
+    }
+
+    #[test]
+    fn test_with_backends_when_unnamed_then_best_available() {
+        let renderer = WasmRenderer::with_backends(None, None).expect("default backends");
+        assert_eq!(available_shapers()[0], SHAPERS[0]);
+        assert_eq!(available_renderers()[0], RENDERERS[0]);
+        assert!(!renderer.shaper().is_empty());
+
+        let Err(message) = WasmRenderer::with_backends(Some("no-such-shaper"), None) else {
+            panic!("unknown shaper accepted");
+        };
+        assert!(message.contains("no-such-shaper"));
+    }
+
+    #[test]
+    fn test_rasterize_when_text_then_rgba_sized_for_image_data() {
+        let Some(font) = load_font() else {
+            return;
+        };
+        let mut renderer = WasmRenderer::with_backends(None, None).expect("default backends");
+        renderer.set_color(200, 0, 0, 255);
+        let image = renderer
+            .rasterize(&font, "Typf", 32.0)
+            .expect("text renders");
+
+        assert!(image.width > 0 && image.height > 0);
+        assert_eq!(image.data.len(), (image.width * image.height * 4) as usize);
+        // Straight alpha: fully covered pixels carry the exact text color
+        assert!(image.data.chunks_exact(4).any(|px| px == [200, 0, 0, 255]));
+        // Transparent background unless asked otherwise
+        assert_eq!(&image.data[..4], &[0, 0, 0, 0]);
+    }
 }
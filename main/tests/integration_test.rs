@@ -75,6 +75,10 @@ fn test_full_pipeline() {
         features: Vec::new(),
         variations: Vec::new(),
         letter_spacing: 0.0,
+        tab_stops: Default::default(),
+        number_substitution: Default::default(),
+        case: Default::default(),
+        cancel: None,
     };
 
     // Create shaper
@@ -0,0 +1,44 @@
+//! The global renderer registry: built-ins by name, plugins alongside them
+
+use std::sync::Arc;
+
+use typf::renderers;
+use typf_core::{
+    error::{Result, TypfError},
+    traits::{FontRef, Renderer},
+    types::{RenderOutput, ShapingResult},
+    RenderParams,
+};
+
+/// Stands in for a renderer from a third-party crate
+struct PluginRenderer;
+
+impl Renderer for PluginRenderer {
+    fn name(&self) -> &'static str {
+        "plugin"
+    }
+
+    fn render(
+        &self,
+        _shaped: &ShapingResult,
+        _font: Arc<dyn FontRef>,
+        _params: &RenderParams,
+    ) -> Result<RenderOutput> {
+        Err(TypfError::NotImplemented("plugin renderer".into()))
+    }
+}
+
+#[test]
+fn test_renderers_when_default_features_then_opixa_built_in() {
+    let renderer = renderers().create("opixa").unwrap();
+    assert_eq!(renderer.name(), "opixa");
+    assert!(renderers().create("no-such-renderer").is_err());
+}
+
+#[test]
+fn test_renderers_when_plugin_registered_then_created_by_name() {
+    renderers().register("plugin-test", || Ok(Arc::new(PluginRenderer)));
+    let renderer = renderers().create("plugin-test").unwrap();
+    assert_eq!(renderer.name(), "plugin");
+    assert!(renderers().names().contains(&"opixa".to_string()));
+}
@@ -0,0 +1,625 @@
+//! `TypfPipeline`: backends by name, font fallback, and one composited bitmap
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use typf::{Columns, GlyphSourceOverride, MissingGlyph, TextOptions, TypfPipeline};
+use typf_core::{
+    diagnostics::{DiagnosticKind, Diagnostics},
+    error::{Result, TypfError},
+    traits::{FontRef, Renderer},
+    types::{BitmapData, BitmapFormat, PixelLayout, RenderOutput, ShapingResult},
+    BackgroundBox, BoxScope, CancelToken, Color, EmojiPresentation, GlyphSourcePreference,
+    Overflow, RenderParams, TextCase, TextLayout, WrapMode,
+};
+use typf_fontdb::TypfFontFace;
+
+/// Has glyphs for `first..=last`, numbered from `base`
+struct RangeFont {
+    first: char,
+    last: char,
+    base: u32,
+}
+
+impl FontRef for RangeFont {
+    fn data(&self) -> &[u8] {
+        &[]
+    }
+
+    fn units_per_em(&self) -> u16 {
+        1000
+    }
+
+    fn glyph_id(&self, ch: char) -> Option<u32> {
+        (self.first..=self.last)
+            .contains(&ch)
+            .then(|| self.base + ch as u32)
+    }
+
+    fn advance_width(&self, _glyph_id: u32) -> f32 {
+        500.0
+    }
+}
+
+/// A `RangeFont` with a COLR table, so its emoji have two presentations
+struct ColorRangeFont {
+    range: RangeFont,
+    data: Vec<u8>,
+}
+
+impl ColorRangeFont {
+    fn new(range: RangeFont) -> Self {
+        // An sfnt table directory listing COLR and nothing else
+        let mut data = Vec::new();
+        data.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend_from_slice(&[0; 6]);
+        data.extend_from_slice(b"COLR");
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&28u32.to_be_bytes());
+        data.extend_from_slice(&[0; 4]);
+        Self { range, data }
+    }
+}
+
+impl FontRef for ColorRangeFont {
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn units_per_em(&self) -> u16 {
+        1000
+    }
+
+    fn glyph_id(&self, ch: char) -> Option<u32> {
+        self.range.glyph_id(ch)
+    }
+
+    fn advance_width(&self, glyph_id: u32) -> f32 {
+        self.range.advance_width(glyph_id)
+    }
+}
+
+/// Paints each glyph's advance solid: red below glyph 1000, blue from there,
+/// green when color glyph sources are denied
+struct SpanRenderer;
+
+impl Renderer for SpanRenderer {
+    fn name(&self) -> &'static str {
+        "span"
+    }
+
+    fn render(
+        &self,
+        shaped: &ShapingResult,
+        _font: Arc<dyn FontRef>,
+        params: &RenderParams,
+    ) -> Result<RenderOutput> {
+        let pad = params.padding;
+        let width = shaped.advance_width.ceil() as u32 + 2 * pad;
+        let height = 10 + 2 * pad;
+        let mut data = vec![0u8; (width * height * 4) as usize];
+        for glyph in &shaped.glyphs {
+            let color = if !params.glyph_sources.allows_color() {
+                [0, 255, 0, 255]
+            } else if glyph.id < 1000 {
+                [255, 0, 0, 255]
+            } else {
+                [0, 0, 255, 255]
+            };
+            let x0 = glyph.x as u32 + pad;
+            for y in pad..pad + 10 {
+                for x in x0..x0 + glyph.advance as u32 {
+                    let at = ((y * width + x) * 4) as usize;
+                    data[at..at + 4].copy_from_slice(&color);
+                }
+            }
+        }
+        Ok(RenderOutput::Bitmap(BitmapData {
+            width,
+            height,
+            format: BitmapFormat::Rgba8,
+            stride: BitmapFormat::Rgba8.row_bytes(width),
+            layout: PixelLayout::RGBA,
+            data,
+        }))
+    }
+}
+
+fn pixel(bitmap: &BitmapData, x: u32, y: u32) -> [u8; 4] {
+    let at = ((y * bitmap.width + x) * 4) as usize;
+    [
+        bitmap.data[at],
+        bitmap.data[at + 1],
+        bitmap.data[at + 2],
+        bitmap.data[at + 3],
+    ]
+}
+
+fn pipeline() -> TypfPipeline {
+    TypfPipeline::builder()
+        .shaper("none")
+        .with_renderer(Arc::new(SpanRenderer))
+        .build()
+        .expect("none shaper is built in")
+}
+
+fn options() -> TextOptions {
+    let mut options = TextOptions::default();
+    options.shaping.size = 20.0;
+    options
+}
+
+#[test]
+fn test_render_text_when_main_font_lacks_char_then_fallback_composited() {
+    let main: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: 'a',
+        last: 'm',
+        base: 0,
+    });
+    let mut options = options();
+    options.fallback_fonts = vec![Arc::new(RangeFont {
+        first: 'a',
+        last: 'z',
+        base: 1000,
+    })];
+    options.render.padding = 2;
+    options.render.background = Some(Color::white());
+
+    let bitmap = pipeline()
+        .render_text("anna", main, &options)
+        .expect("renders");
+
+    // Four 10 px glyphs plus padding, drawn red, blue, blue, red
+    assert_eq!((bitmap.width, bitmap.height), (44, 14));
+    assert_eq!(pixel(&bitmap, 0, 0), [255, 255, 255, 255]);
+    assert_eq!(pixel(&bitmap, 7, 7), [255, 0, 0, 255]);
+    assert_eq!(pixel(&bitmap, 17, 7), [0, 0, 255, 255]);
+    assert_eq!(pixel(&bitmap, 27, 7), [0, 0, 255, 255]);
+    assert_eq!(pixel(&bitmap, 37, 7), [255, 0, 0, 255]);
+}
+
+#[test]
+fn test_render_text_when_one_font_covers_all_then_renderer_output_unchanged() {
+    let font: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: 'a',
+        last: 'z',
+        base: 0,
+    });
+    let mut options = options();
+    options.render.padding = 3;
+
+    let bitmap = pipeline()
+        .render_text("abc", font, &options)
+        .expect("renders");
+
+    assert_eq!((bitmap.width, bitmap.height), (36, 16));
+    assert_eq!(pixel(&bitmap, 1, 1), [0, 0, 0, 0]);
+    assert_eq!(pixel(&bitmap, 20, 8), [255, 0, 0, 255]);
+}
+
+#[test]
+fn test_build_when_shaper_unknown_then_config_error() {
+    let Err(error) = TypfPipeline::builder().shaper("no-such-shaper").build() else {
+        panic!("no-such-shaper is not a shaper");
+    };
+    assert!(error.to_string().contains("no-such-shaper"));
+}
+
+#[test]
+fn test_render_text_with_stats_when_fallback_used_then_runs_and_layers_counted() {
+    let main: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: 'a',
+        last: 'm',
+        base: 0,
+    });
+    let mut options = options();
+    options.fallback_fonts = vec![Arc::new(RangeFont {
+        first: 'a',
+        last: 'z',
+        base: 1000,
+    })];
+
+    let (bitmap, stats) = pipeline()
+        .render_text_with_stats("anna", main, &options)
+        .expect("renders");
+
+    let runs: Vec<_> = stats
+        .runs
+        .iter()
+        .map(|r| (r.range.clone(), r.font))
+        .collect();
+    assert_eq!(runs, vec![(0..1, 0), (1..3, 1), (3..4, 0)]);
+    assert_eq!(stats.glyphs_rendered, 4);
+    // Two bare 40x10 layers, then the composited line
+    assert_eq!(stats.bytes_allocated, 2 * 40 * 10 * 4 + bitmap.data.len());
+    assert!(stats.timings.total() >= stats.timings.shape);
+}
+
+#[test]
+fn test_render_text_when_small_caps_without_smcp_then_lowercase_shrunk() {
+    let font: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: 'A',
+        last: 'z',
+        base: 0,
+    });
+    let mut options = options();
+    options.shaping.case = TextCase::SmallCaps;
+
+    let (bitmap, stats) = pipeline()
+        .render_text_with_stats("Abc", font, &options)
+        .expect("renders");
+
+    let runs: Vec<_> = stats
+        .runs
+        .iter()
+        .map(|r| (r.range.clone(), r.font))
+        .collect();
+    assert_eq!(runs, vec![(0..1, 0), (1..3, 0)]);
+    // "A" is 10 px wide; "BC" at 70% of the size is 7 px a glyph
+    assert_eq!(bitmap.width, 24);
+    assert_eq!(pixel(&bitmap, 5, 5), [255, 0, 0, 255]);
+    assert_eq!(pixel(&bitmap, 20, 5), [255, 0, 0, 255]);
+    // The full-size and small-caps layers, then the composited line
+    assert_eq!(stats.bytes_allocated, 2 * 24 * 10 * 4 + bitmap.data.len());
+}
+
+#[test]
+fn test_render_text_when_color_font_then_presentation_per_sequence() {
+    let font: Arc<dyn FontRef> = Arc::new(ColorRangeFont::new(RangeFont {
+        first: '\0',
+        last: char::MAX,
+        base: 0,
+    }));
+    let mut options = options();
+
+    // Smiley with VS16 is emoji; the bare smiley defaults to text
+    let text = "\u{263A}\u{FE0F}\u{263A}";
+    let bitmap = pipeline()
+        .render_text(text, font.clone(), &options)
+        .expect("renders");
+
+    assert_eq!(bitmap.width, 30);
+    assert_eq!(pixel(&bitmap, 5, 5), [0, 0, 255, 255]);
+    assert_eq!(pixel(&bitmap, 15, 5), [0, 0, 255, 255]);
+    assert_eq!(pixel(&bitmap, 25, 5), [0, 255, 0, 255]);
+
+    // Forcing text overrides the selector
+    options.render.emoji_presentation = EmojiPresentation::Text;
+    let bitmap = pipeline()
+        .render_text(text, font, &options)
+        .expect("renders");
+    assert_eq!(pixel(&bitmap, 5, 5), [0, 255, 0, 255]);
+}
+
+#[test]
+fn test_render_text_when_glyph_source_override_then_range_drawn_apart() {
+    let font: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: 'a',
+        last: 'z',
+        base: 0,
+    });
+    let mut options = options();
+    options.glyph_source_overrides = vec![GlyphSourceOverride {
+        range: 1..3,
+        glyph_sources: GlyphSourcePreference::monochrome(),
+    }];
+
+    let (bitmap, stats) = pipeline()
+        .render_text_with_stats("abcd", font, &options)
+        .expect("renders");
+
+    let runs: Vec<_> = stats.runs.iter().map(|r| r.range.clone()).collect();
+    assert_eq!(runs, vec![0..1, 1..3, 3..4]);
+    assert_eq!(pixel(&bitmap, 5, 5), [255, 0, 0, 255]);
+    assert_eq!(pixel(&bitmap, 15, 5), [0, 255, 0, 255]);
+    assert_eq!(pixel(&bitmap, 25, 5), [0, 255, 0, 255]);
+    assert_eq!(pixel(&bitmap, 35, 5), [255, 0, 0, 255]);
+}
+
+#[test]
+fn test_render_text_when_token_cancelled_then_cancelled_error() {
+    let font: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: 'a',
+        last: 'z',
+        base: 0,
+    });
+    let token = CancelToken::new();
+    token.cancel();
+    let mut options = options();
+    options.shaping.cancel = Some(token);
+
+    let result = pipeline().render_text("abc", font, &options);
+
+    assert!(matches!(result, Err(TypfError::Cancelled)));
+}
+
+#[test]
+fn test_render_text_when_background_box_then_line_or_each_run_boxed() {
+    let main: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: 'a',
+        last: 'm',
+        base: 0,
+    });
+    let mut options = options();
+    options.fallback_fonts = vec![Arc::new(RangeFont {
+        first: 'a',
+        last: 'z',
+        base: 1000,
+    })];
+    let plate = BackgroundBox {
+        padding: 1.0,
+        ..BackgroundBox::new(Color::rgba(0, 0, 0, 128))
+    };
+    let mut alpha = |scope| {
+        options.render.effects.background_box = Some(BackgroundBox { scope, ..plate });
+        let bitmap = pipeline()
+            .render_text("anna", main.clone(), &options)
+            .expect("renders");
+        assert_eq!((bitmap.width, bitmap.height), (42, 12), "room for the box");
+        [5, 11, 20, 41].map(|x| pixel(&bitmap, x, 0)[3])
+    };
+
+    // The runs a, nn, a are boxed apart, so their padding overlaps
+    assert_eq!(alpha(BoxScope::Block), [128, 128, 128, 128]);
+    assert_eq!(alpha(BoxScope::Run), [128, 191, 128, 128]);
+}
+
+#[test]
+fn test_measure_when_one_line_then_advance_matches_rendered_width() {
+    let font: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: ' ',
+        last: 'z',
+        base: 0,
+    });
+    let options = options();
+
+    let metrics = pipeline()
+        .measure("abc def", font.clone(), &options)
+        .expect("measures");
+    let bitmap = pipeline()
+        .render_text("abc def", font, &options)
+        .expect("renders");
+
+    assert_eq!(metrics.advance, 70.0);
+    assert_eq!(metrics.advance.ceil() as u32, bitmap.width);
+    assert_eq!(metrics.line_count, 1);
+    // No font tables, so no ink box
+    assert_eq!(metrics.tight_bbox, None);
+}
+
+#[test]
+fn test_measure_when_max_width_then_wrapped_at_spaces_and_newlines() {
+    let font: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: '\n',
+        last: 'z',
+        base: 0,
+    });
+    let mut options = options();
+    options.render.layout = TextLayout::new(45.0);
+
+    let measure = |text| pipeline().measure(text, font.clone(), &options);
+
+    let wrapped = measure("abc def gh").expect("measures");
+    assert_eq!(wrapped.line_count, 3);
+    assert_eq!(wrapped.advance, 30.0);
+
+    let broken = measure("ab\nc").expect("measures");
+    assert_eq!(broken.line_count, 2);
+    assert_eq!(broken.advance, 20.0);
+
+    let long = measure("abcdefgh").expect("measures");
+    assert_eq!(long.line_count, 1);
+    assert_eq!(long.advance, 80.0);
+
+    assert_eq!(measure("").expect("measures").line_count, 0);
+}
+
+#[test]
+fn test_measure_when_real_font_then_ink_box_per_line() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("test-fonts")
+        .join("NotoSans-Regular.ttf");
+    if !path.exists() {
+        eprintln!("Skipping test: font not found at {:?}", path);
+        return;
+    }
+    let font: Arc<dyn FontRef> = Arc::new(TypfFontFace::from_file(&path).expect("loads"));
+    let mut options = options();
+    options.shaping.size = 100.0;
+
+    let one = pipeline()
+        .measure("H", font.clone(), &options)
+        .expect("measures");
+    let ink = one.tight_bbox.expect("H has ink");
+    assert!(ink.x_min > 0.0 && ink.x_max < one.advance);
+    assert!(ink.y_min.abs() < 0.5, "H sits on the baseline: {ink:?}");
+    assert!(ink.y_max > 60.0 && ink.y_max < one.ascent);
+
+    options.render.layout = TextLayout::new(1000.0);
+    let two = pipeline()
+        .measure("H\nH", font, &options)
+        .expect("measures");
+    let ink = two.tight_bbox.expect("H has ink");
+    assert_eq!(two.line_count, 2);
+    assert!((ink.y_min + two.ascent + two.descent).abs() < 0.5);
+    assert!((two.height() - 2.0 * (one.ascent + one.descent)).abs() < 1e-3);
+}
+
+#[test]
+fn test_measure_when_wrap_modes_then_long_word_broken_as_asked() {
+    let font: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: ' ',
+        last: 'z',
+        base: 0,
+    });
+    let mut options = options();
+    let mut lines = |wrap| {
+        options.render.layout = TextLayout::new(45.0).with_wrap(wrap);
+        pipeline()
+            .measure("ab cdefghij", font.clone(), &options)
+            .expect("measures")
+            .line_count
+    };
+
+    // "ab" | "cdefghij", which overflows
+    assert_eq!(lines(WrapMode::Word), 2);
+    // "ab c" | "defg" | "hij"
+    assert_eq!(lines(WrapMode::Char), 3);
+    // "ab" | "cdef" | "ghij"
+    assert_eq!(lines(WrapMode::WordThenChar), 3);
+    assert_eq!(lines(WrapMode::NoWrap), 1);
+}
+
+#[test]
+fn test_render_text_when_ellipsis_overflow_then_cut_to_fit() {
+    let font: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: ' ',
+        last: 'z',
+        base: 0,
+    });
+    let mut options = options();
+    options.render.layout = TextLayout::new(45.0).with_overflow(Overflow::Ellipsis);
+
+    // The font has no "…", so "a..." is drawn
+    let bitmap = pipeline()
+        .render_text("abcdefgh", font.clone(), &options)
+        .expect("renders");
+    assert_eq!(bitmap.width, 40);
+
+    let metrics = pipeline()
+        .measure("abcdefgh", font, &options)
+        .expect("measures");
+    assert_eq!(metrics.advance, 45.0);
+}
+
+#[test]
+fn test_lay_out_block_when_taller_than_box_then_overflow_offset_continues_flow() {
+    let font: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: ' ',
+        last: 'z',
+        base: 0,
+    });
+    let mut options = options();
+    // Lines are one 20 px em apart in a font without vertical metrics
+    options.render.layout = TextLayout::new(45.0).with_max_height(50.0);
+    let text = "ab cd ef gh";
+
+    let first = pipeline()
+        .lay_out_block(text, font.clone(), &options)
+        .expect("lays out");
+    let ranges: Vec<_> = first.lines.iter().map(|line| line.range.clone()).collect();
+    assert_eq!(ranges, [0..2, 3..5]);
+    assert_eq!(first.lines[1].baseline, 40.0);
+    assert_eq!(first.overflow, Some(6));
+    assert_eq!(first.height(), 40.0);
+
+    let rest = &text[first.overflow.unwrap_or(text.len())..];
+    let second = pipeline()
+        .lay_out_block(rest, font, &options)
+        .expect("lays out");
+    assert_eq!(second.lines.len(), 2);
+    assert_eq!(second.overflow, None);
+}
+
+#[test]
+fn test_lay_out_columns_when_balanced_then_lines_shared_evenly() {
+    let font: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: ' ',
+        last: 'z',
+        base: 0,
+    });
+    let mut options = options();
+    // Two 45 px columns: one two-letter word per line
+    options.render.layout = TextLayout::new(100.0);
+    let text = "ab cd ef gh ij";
+    let lay_out = |options: &TextOptions, columns: Columns| {
+        pipeline()
+            .lay_out_columns(text, font.clone(), options, &columns)
+            .expect("lays out")
+    };
+    let counts = |block: &typf::ColumnBlock| -> Vec<usize> {
+        block.columns.iter().map(|c| c.lines.len()).collect()
+    };
+
+    let filled = lay_out(&options, Columns::new(2, 10.0));
+    assert_eq!(counts(&filled), [5, 0]);
+
+    let balanced = lay_out(&options, Columns::new(2, 10.0).balanced());
+    assert_eq!(counts(&balanced), [3, 2]);
+    let second = &balanced.columns[1];
+    assert_eq!((second.x, second.width), (55.0, 45.0));
+    assert_eq!(second.lines[0].range, 9..11);
+    assert_eq!(second.lines[0].baseline, 20.0);
+    assert_eq!(balanced.overflow, None);
+
+    options.render.layout = options.render.layout.with_max_height(40.0);
+    let bounded = lay_out(&options, Columns::new(2, 10.0).balanced());
+    assert_eq!(counts(&bounded), [2, 2]);
+    assert_eq!(bounded.overflow, Some(12));
+
+    let result = pipeline().lay_out_columns(text, font, &options, &Columns::new(0, 10.0));
+    assert!(matches!(result, Err(TypfError::ConfigError(_))));
+}
+
+#[test]
+fn test_lay_out_columns_when_four_lines_in_three_columns_then_none_left_empty() {
+    let font: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: ' ',
+        last: 'z',
+        base: 0,
+    });
+    let mut options = options();
+    // Three 30 px columns: one two-letter word per line
+    options.render.layout = TextLayout::new(100.0);
+    let block = pipeline()
+        .lay_out_columns(
+            "ab cd ef gh",
+            font,
+            &options,
+            &Columns::new(3, 5.0).balanced(),
+        )
+        .expect("lays out");
+
+    let counts: Vec<usize> = block.columns.iter().map(|c| c.lines.len()).collect();
+    assert_eq!(counts, [2, 1, 1]);
+    assert_eq!(block.columns[2].lines[0].range, 9..11);
+    assert_eq!(block.overflow, None);
+}
+
+#[test]
+fn test_missing_glyphs_when_no_font_covers_char_then_reported_and_diagnosed() {
+    let main: Arc<dyn FontRef> = Arc::new(RangeFont {
+        first: 'a',
+        last: 'm',
+        base: 0,
+    });
+    let mut options = options();
+    options.fallback_fonts = vec![Arc::new(RangeFont {
+        first: 'a',
+        last: 'z',
+        base: 1000,
+    })];
+
+    // "z" falls back; the snowman has no font
+    let missing = pipeline()
+        .missing_glyphs("az\u{2603}b", main.clone(), &options)
+        .expect("shapes");
+    assert_eq!(
+        missing,
+        [MissingGlyph {
+            offset: 2,
+            ch: '\u{2603}',
+        }]
+    );
+
+    let diagnostics = Diagnostics::new();
+    options.render.diagnostics = Some(diagnostics.clone());
+    pipeline()
+        .render_text("az\u{2603}b", main, &options)
+        .expect("renders");
+    let kinds: Vec<_> = diagnostics.take().into_iter().map(|d| d.kind).collect();
+    assert_eq!(kinds, [DiagnosticKind::MissingGlyph]);
+}
@@ -16,12 +16,11 @@ rustup target add wasm32-unknown-unknown 2>/dev/null || true
 
 # Build with wasm-pack
 echo "Building WASM package..."
-cd crates/typf
+cd "$(dirname "$0")/../bindings/wasm"
 wasm-pack build \
     --target web \
     --out-dir ../../pkg \
-    --features wasm,wasm-minimal \
-    --no-default-features
+    --out-name typf
 
 echo "WASM build complete! Output in pkg/"
 
@@ -43,61 +42,55 @@ cat > ../../pkg/example.html << 'EOF'
     <h1>Typf WebAssembly Demo</h1>
 
     <div id="controls">
+        <input type="file" id="font" accept=".ttf,.otf,.ttc">
         <input type="text" id="text" value="Hello WASM!" placeholder="Enter text...">
-        <input type="number" id="fontSize" value="24" min="8" max="72">
-        <button id="render">Render</button>
+        <input type="number" id="fontSize" value="48" min="8" max="200">
+        <select id="renderer"></select>
     </div>
 
     <canvas id="canvas"></canvas>
 
     <script type="module">
-        import init, { WasmRenderer, measure_text } from './typf.js';
+        import init, { WasmFont, WasmRenderer, available_renderers } from './typf.js';
 
         async function run() {
             await init();
 
-            const renderer = new WasmRenderer();
+            const select = document.getElementById('renderer');
+            for (const name of available_renderers()) {
+                select.add(new Option(name, name));
+            }
+
             const canvas = document.getElementById('canvas');
             const ctx = canvas.getContext('2d');
+            let font = null;
+            let renderer = new WasmRenderer();
 
             function render() {
+                if (!font) return;
                 const text = document.getElementById('text').value;
                 const fontSize = parseFloat(document.getElementById('fontSize').value);
-
-                // Measure text
-                const width = Math.ceil(measure_text(text, fontSize)) + 20;
-                const height = Math.ceil(fontSize * 1.5) + 20;
-
-                // Set canvas size
-                canvas.width = width;
-                canvas.height = height;
-
-                // Render text
                 try {
-                    const pixels = renderer.render_text(text, fontSize);
-
-                    // Convert to ImageData
-                    const imageData = new ImageData(
-                        new Uint8ClampedArray(pixels),
-                        width,
-                        height
-                    );
-
-                    // Draw to canvas
-                    ctx.putImageData(imageData, 0, 0);
+                    const image = renderer.render_text(font, text, fontSize);
+                    canvas.width = image.width;
+                    canvas.height = image.height;
+                    ctx.putImageData(new ImageData(image.data, image.width, image.height), 0, 0);
                 } catch (e) {
                     console.error('Render error:', e);
-                    ctx.fillStyle = 'red';
-                    ctx.fillText('Error: ' + e, 10, 20);
                 }
             }
 
-            document.getElementById('render').addEventListener('click', render);
+            document.getElementById('font').addEventListener('change', async (event) => {
+                const file = event.target.files[0];
+                font = new WasmFont(new Uint8Array(await file.arrayBuffer()));
+                render();
+            });
+            select.addEventListener('change', () => {
+                renderer = new WasmRenderer(undefined, select.value);
+                render();
+            });
             document.getElementById('text').addEventListener('input', render);
             document.getElementById('fontSize').addEventListener('input', render);
-
-            // Initial render
-            render();
         }
 
         run();
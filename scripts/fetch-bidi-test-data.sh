@@ -0,0 +1,16 @@
+#!/bin/bash
+# Download the Unicode bidi conformance data run by unicode/tests/bidi_conformance.rs
+#
+# The file keeps its own header, with the UCD version and the Unicode
+# License v3 notice (https://www.unicode.org/license.txt) it is published under.
+
+set -e
+
+UCD_VERSION="${UCD_VERSION:-16.0.0}"
+URL="https://www.unicode.org/Public/${UCD_VERSION}/ucd/BidiCharacterTest.txt"
+OUT="$(dirname "$0")/../unicode/tests/data/BidiCharacterTest.txt"
+
+echo "Fetching BidiCharacterTest.txt from UCD ${UCD_VERSION}..."
+curl -sSfL "$URL" -o "$OUT"
+head -n 3 "$OUT"
+echo "Saved to unicode/tests/data/BidiCharacterTest.txt"
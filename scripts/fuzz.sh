@@ -23,6 +23,8 @@ TARGETS=(
     "fuzz_unicode_process"
     "fuzz_harfbuzz_shape"
     "fuzz_pipeline"
+    "shape_arbitrary_text"
+    "render_arbitrary_font"
 )
 
 # Validate target
@@ -0,0 +1,40 @@
+#!/bin/bash
+# Run every gallery example as a smoke test
+# Usage: ./scripts/gallery.sh [output_dir]
+#
+# Fonts come from test-fonts/ (see test-fonts/README.md) or $TYPF_FONT_DIR.
+# Images land in output_dir, default examples/output/gallery.
+
+set -e
+
+cd "$(dirname "$0")/.."
+
+if [ -n "$1" ]; then
+    export TYPF_GALLERY_DIR="$1"
+fi
+
+EXAMPLES=(
+    "gallery_paragraph"
+    "gallery_emoji"
+    "gallery_variable"
+    "gallery_svg"
+    "gallery_atlas"
+)
+
+echo "=== Typf Example Gallery ==="
+
+FAILED=()
+for example in "${EXAMPLES[@]}"; do
+    echo ""
+    echo "--- $example ---"
+    if ! cargo run -q -p typf --example "$example" --features fontdb,shaping-hb; then
+        FAILED+=("$example")
+    fi
+done
+
+echo ""
+if [ ${#FAILED[@]} -ne 0 ]; then
+    echo "FAILED: ${FAILED[*]}"
+    exit 1
+fi
+echo "All ${#EXAMPLES[@]} gallery examples passed"
@@ -0,0 +1,148 @@
+//! Character map for the subset: format 4 for the BMP, format 12 beyond it
+
+/// Runs of consecutive characters mapped to consecutive glyphs
+fn runs(mappings: &[(u32, u16)]) -> Vec<(u32, u32, u16)> {
+    let mut runs: Vec<(u32, u32, u16)> = Vec::new();
+    for &(ch, gid) in mappings {
+        match runs.last_mut() {
+            Some((start, end, first))
+                if ch == *end + 1 && u32::from(gid) == u32::from(*first) + (ch - *start) =>
+            {
+                *end = ch;
+            },
+            _ => runs.push((ch, ch, gid)),
+        }
+    }
+    runs
+}
+
+/// Format 4 subtable, or `None` if it would outgrow its 16-bit length
+fn format4(runs: &[(u32, u32, u16)]) -> Option<Vec<u8>> {
+    let mut segments: Vec<(u16, u16, u16)> = runs
+        .iter()
+        .filter(|(start, _, _)| *start < 0xFFFF)
+        .map(|&(start, end, gid)| {
+            let (start, end) = (start as u16, end.min(0xFFFE) as u16);
+            (start, end, gid.wrapping_sub(start))
+        })
+        .collect();
+    segments.push((0xFFFF, 0xFFFF, 1));
+
+    let count = segments.len();
+    let length = u16::try_from(16 + 8 * count).ok()?;
+    let entry_selector = 15 - (count as u16).leading_zeros() as u16;
+    let search_range = 2 * (1u16 << entry_selector);
+
+    let mut table = Vec::with_capacity(length as usize);
+    for value in [4, length, 0, 2 * count as u16, search_range, entry_selector] {
+        table.extend_from_slice(&value.to_be_bytes());
+    }
+    table.extend_from_slice(&(2 * count as u16 - search_range).to_be_bytes());
+    for (_, end, _) in &segments {
+        table.extend_from_slice(&end.to_be_bytes());
+    }
+    table.extend_from_slice(&[0, 0]);
+    for (start, _, _) in &segments {
+        table.extend_from_slice(&start.to_be_bytes());
+    }
+    for (_, _, delta) in &segments {
+        table.extend_from_slice(&delta.to_be_bytes());
+    }
+    table.resize(length as usize, 0);
+    Some(table)
+}
+
+/// Format 12 subtable
+fn format12(runs: &[(u32, u32, u16)]) -> Vec<u8> {
+    let mut table = vec![0, 12, 0, 0];
+    table.extend_from_slice(&((16 + 12 * runs.len()) as u32).to_be_bytes());
+    table.extend_from_slice(&0u32.to_be_bytes());
+    table.extend_from_slice(&(runs.len() as u32).to_be_bytes());
+    for &(start, end, gid) in runs {
+        table.extend_from_slice(&start.to_be_bytes());
+        table.extend_from_slice(&end.to_be_bytes());
+        table.extend_from_slice(&u32::from(gid).to_be_bytes());
+    }
+    table
+}
+
+/// A `cmap` table for `mappings`, sorted by character.
+///
+/// BMP characters go in a Windows format 4 subtable, under the symbol
+/// encoding if `symbol` is set. A Windows full-repertoire format 12
+/// subtable is added when characters lie beyond the BMP or the BMP alone
+/// is too large for format 4.
+pub(crate) fn write_cmap(mappings: &[(u32, u16)], symbol: bool) -> Vec<u8> {
+    let runs = runs(mappings);
+    let bmp = format4(&runs);
+    let wide = (bmp.is_none() || runs.last().is_some_and(|(_, end, _)| *end > 0xFFFF))
+        .then(|| format12(&runs));
+
+    let mut subtables = Vec::new();
+    if let Some(bmp) = bmp {
+        subtables.push((if symbol { 0u16 } else { 1 }, bmp));
+    }
+    if let Some(wide) = wide {
+        subtables.push((10, wide));
+    }
+
+    let mut table = Vec::new();
+    table.extend_from_slice(&0u16.to_be_bytes());
+    table.extend_from_slice(&(subtables.len() as u16).to_be_bytes());
+    let mut offset = 4 + 8 * subtables.len();
+    for (encoding, data) in &subtables {
+        table.extend_from_slice(&3u16.to_be_bytes());
+        table.extend_from_slice(&encoding.to_be_bytes());
+        table.extend_from_slice(&(offset as u32).to_be_bytes());
+        offset += data.len();
+    }
+    for (_, data) in &subtables {
+        table.extend_from_slice(data);
+    }
+    table
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use read_fonts::tables::cmap::Cmap;
+    use read_fonts::FontRead;
+
+    use super::*;
+
+    fn read_back(table: &[u8]) -> Vec<(u32, u32)> {
+        let cmap = Cmap::read(table.into()).expect("parse cmap");
+        let (_, _, subtable) = cmap.best_subtable().expect("subtable");
+        subtable
+            .iter()
+            .map(|(ch, gid)| (ch, gid.to_u32()))
+            .filter(|&(_, gid)| gid != 0)
+            .collect()
+    }
+
+    #[test]
+    fn test_write_cmap_when_bmp_only_then_format4_round_trips() {
+        let mappings = [(0x41, 1), (0x42, 2), (0x43, 3), (0x61, 9), (0x20AC, 4)];
+        let table = write_cmap(&mappings, false);
+
+        assert_eq!(table[7], 1, "Windows Unicode BMP encoding");
+        let expected: Vec<_> = mappings
+            .iter()
+            .map(|&(ch, gid)| (ch, u32::from(gid)))
+            .collect();
+        assert_eq!(read_back(&table), expected);
+    }
+
+    #[test]
+    fn test_write_cmap_when_astral_then_format12_added() {
+        let mappings = [(0x41, 1), (0x1F600, 2), (0x1F601, 3)];
+        let table = write_cmap(&mappings, false);
+
+        assert_eq!(table[3], 2, "format 4 and format 12");
+        let expected: Vec<_> = mappings
+            .iter()
+            .map(|&(ch, gid)| (ch, u32::from(gid)))
+            .collect();
+        assert_eq!(read_back(&table), expected);
+    }
+}
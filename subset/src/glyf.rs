@@ -0,0 +1,188 @@
+//! TrueType outlines: composite closure and the `glyf`, `loca`, and `hmtx` rewrite
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::sfnt::{read_u16, read_u32, write_u16};
+use crate::{Result, SubsetError};
+
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+/// The `glyf` table, sliced into glyphs by `loca`
+pub(crate) struct Outlines<'a> {
+    glyf: &'a [u8],
+    offsets: Vec<u32>,
+}
+
+impl<'a> Outlines<'a> {
+    /// `long_offsets` is `head.indexToLocFormat == 1`.
+    pub(crate) fn new(
+        glyf: &'a [u8],
+        loca: &[u8],
+        long_offsets: bool,
+        num_glyphs: u16,
+    ) -> Result<Self> {
+        let offsets = (0..=num_glyphs as usize)
+            .map(|i| {
+                if long_offsets {
+                    read_u32(loca, i * 4, "loca")
+                } else {
+                    read_u16(loca, i * 2, "loca").map(|half| u32::from(half) * 2)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { glyf, offsets })
+    }
+
+    /// Raw data of glyph `gid`, empty for glyphs without outlines
+    pub(crate) fn glyph(&self, gid: u16) -> Result<&'a [u8]> {
+        let start = self.offsets[gid as usize] as usize;
+        let end = self.offsets[gid as usize + 1] as usize;
+        if start >= end {
+            return Ok(&[]);
+        }
+        self.glyf
+            .get(start..end)
+            .ok_or(SubsetError::Malformed("glyf"))
+    }
+}
+
+/// Components of a composite glyph as (offset of the glyph index, glyph index)
+fn components(glyph: &[u8]) -> Result<Vec<(usize, u16)>> {
+    if glyph.len() < 10 || (read_u16(glyph, 0, "glyf")? as i16) >= 0 {
+        return Ok(Vec::new());
+    }
+    let mut found = Vec::new();
+    let mut pos = 10;
+    loop {
+        let flags = read_u16(glyph, pos, "glyf")?;
+        found.push((pos + 2, read_u16(glyph, pos + 2, "glyf")?));
+        pos += 4;
+        pos += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            pos += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            pos += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            pos += 8;
+        }
+        if flags & MORE_COMPONENTS == 0 {
+            return Ok(found);
+        }
+    }
+}
+
+/// `glyphs` plus every glyph their composites are built from
+pub(crate) fn closure(outlines: &Outlines, glyphs: &BTreeSet<u16>) -> Result<BTreeSet<u16>> {
+    let mut closed = glyphs.clone();
+    let mut pending: Vec<u16> = glyphs.iter().copied().collect();
+    while let Some(gid) = pending.pop() {
+        for (_, component) in components(outlines.glyph(gid)?)? {
+            if (component as usize) + 1 >= outlines.offsets.len() {
+                return Err(SubsetError::Malformed("glyf"));
+            }
+            if closed.insert(component) {
+                pending.push(component);
+            }
+        }
+    }
+    Ok(closed)
+}
+
+/// New `glyf` and long-format `loca` for the glyphs in `map` (old to new ID)
+pub(crate) fn write_glyf(
+    outlines: &Outlines,
+    map: &BTreeMap<u16, u16>,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    let mut glyf = Vec::new();
+    let mut loca = Vec::with_capacity((map.len() + 1) * 4);
+    for &old in map.keys() {
+        loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+        let mut glyph = outlines.glyph(old)?.to_vec();
+        for (offset, component) in components(&glyph)? {
+            let new = map.get(&component).ok_or(SubsetError::Malformed("glyf"))?;
+            write_u16(&mut glyph, offset, *new);
+        }
+        glyf.extend_from_slice(&glyph);
+        glyf.resize(glyf.len().next_multiple_of(4), 0);
+    }
+    loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+    Ok((glyf, loca))
+}
+
+/// New `hmtx` with one full metric per glyph in `map`
+pub(crate) fn write_hmtx(
+    hmtx: &[u8],
+    num_h_metrics: u16,
+    map: &BTreeMap<u16, u16>,
+) -> Result<Vec<u8>> {
+    let long = num_h_metrics.max(1) as usize;
+    let mut out = Vec::with_capacity(map.len() * 4);
+    for &old in map.keys() {
+        let old = old as usize;
+        let (advance, lsb) = if old < long {
+            (
+                read_u16(hmtx, old * 4, "hmtx")?,
+                read_u16(hmtx, old * 4 + 2, "hmtx")?,
+            )
+        } else {
+            (
+                read_u16(hmtx, (long - 1) * 4, "hmtx")?,
+                read_u16(hmtx, long * 4 + (old - long) * 2, "hmtx")?,
+            )
+        };
+        out.extend_from_slice(&advance.to_be_bytes());
+        out.extend_from_slice(&lsb.to_be_bytes());
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn composite(parts: &[(u16, u16)]) -> Vec<u8> {
+        let mut glyph = vec![0xFF, 0xFF, 0, 0, 0, 0, 0, 0, 0, 0];
+        for (i, &(gid, extra)) in parts.iter().enumerate() {
+            let mut flags = extra | ARG_1_AND_2_ARE_WORDS;
+            if i + 1 < parts.len() {
+                flags |= MORE_COMPONENTS;
+            }
+            glyph.extend_from_slice(&flags.to_be_bytes());
+            glyph.extend_from_slice(&gid.to_be_bytes());
+            glyph.extend_from_slice(&[0; 4]);
+            if extra == WE_HAVE_AN_X_AND_Y_SCALE {
+                glyph.extend_from_slice(&[0x40, 0, 0x40, 0]);
+            }
+        }
+        glyph
+    }
+
+    #[test]
+    fn test_components_when_scaled_parts_then_all_found() {
+        let glyph = composite(&[(3, WE_HAVE_AN_X_AND_Y_SCALE), (7, 0)]);
+
+        let found = components(&glyph).ok();
+        assert_eq!(found, Some(vec![(12, 3), (24, 7)]));
+        assert_eq!(components(&[0, 1, 0, 0]).ok(), Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_components_when_truncated_then_malformed() {
+        let mut glyph = composite(&[(3, 0), (7, 0)]);
+        glyph.truncate(20);
+
+        assert!(matches!(
+            components(&glyph),
+            Err(SubsetError::Malformed("glyf"))
+        ));
+    }
+}
@@ -0,0 +1,278 @@
+//! Font subsetting for embedding shaped text in SVG and PDF output.
+//!
+//! A vector file that carries its own font should not carry all of it.
+//! [`Subsetter`] cuts a TrueType font down to the glyphs a [`ShapingResult`]
+//! uses, plus the parts its composite glyphs are built from, and numbers
+//! them densely from zero. [`Subset::remap`] rewrites the shaped glyph IDs
+//! to match, so the run can be drawn with the embedded font.
+//!
+//! The subset keeps outlines, horizontal metrics, the character map,
+//! hinting, and the `name`, `OS/2`, and `post` tables (without glyph
+//! names). Layout tables are dropped since the text is already shaped, as
+//! are variation, color, and vertical tables, so a variable font subsets to
+//! its default instance. Fonts with CFF outlines are not supported yet.
+//!
+//! ```no_run
+//! use typf_core::types::ShapingResult;
+//! use typf_subset::Subsetter;
+//!
+//! fn embed(font: &[u8], shaped: &ShapingResult) -> typf_subset::Result<(Vec<u8>, ShapingResult)> {
+//!     let subset = Subsetter::new(font)?.with_shaped(shaped).build()?;
+//!     let glyphs = subset.remap(shaped);
+//!     Ok((subset.data, glyphs))
+//! }
+//! ```
+
+// this_file: subset/src/lib.rs
+
+mod cmap;
+mod glyf;
+mod sfnt;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use read_fonts::tables::cmap::CmapIterLimits;
+use read_fonts::types::Tag;
+use read_fonts::{FontRef, TableProvider};
+use typf_core::error::{Classify, ErrorKind};
+use typf_core::types::{GlyphId, ShapingResult};
+
+use crate::glyf::Outlines;
+use crate::sfnt::{read_u16, write_u16};
+
+/// Errors from subsetting a font.
+#[derive(Debug, thiserror::Error)]
+pub enum SubsetError {
+    #[error("Cannot parse font: {0}")]
+    Parse(String),
+
+    #[error("Font has no '{0}' table")]
+    MissingTable(&'static str),
+
+    #[error("Font has CFF outlines; only TrueType outlines can be subset")]
+    UnsupportedOutlines,
+
+    #[error("Glyph {glyph} is out of range for a font with {count} glyphs")]
+    GlyphOutOfRange { glyph: GlyphId, count: u16 },
+
+    #[error("Malformed '{0}' table")]
+    Malformed(&'static str),
+}
+
+impl Classify for SubsetError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            SubsetError::Parse(_) | SubsetError::Malformed(_) => ErrorKind::InvalidFont,
+            SubsetError::MissingTable(_) => ErrorKind::NotFound,
+            SubsetError::UnsupportedOutlines => ErrorKind::Unsupported,
+            SubsetError::GlyphOutOfRange { .. } => ErrorKind::InvalidInput,
+        }
+    }
+}
+
+/// Result type for subsetting.
+pub type Result<T, E = SubsetError> = std::result::Result<T, E>;
+
+/// Tables copied into the subset unchanged
+const PASSTHROUGH: [&[u8; 4]; 6] = [b"OS/2", b"cvt ", b"fpgm", b"gasp", b"name", b"prep"];
+
+/// Offset of `indexToLocFormat` in `head`
+const HEAD_INDEX_TO_LOC_FORMAT: usize = 50;
+/// Offset of `numGlyphs` in `maxp`
+const MAXP_NUM_GLYPHS: usize = 4;
+/// Offset of `numberOfHMetrics` in `hhea`
+const HHEA_NUM_H_METRICS: usize = 34;
+/// Length of the `post` header shared by all versions
+const POST_HEADER_LEN: usize = 32;
+
+/// Collects glyphs and cuts a font down to them.
+pub struct Subsetter<'a> {
+    font: FontRef<'a>,
+    glyphs: BTreeSet<GlyphId>,
+}
+
+impl<'a> Subsetter<'a> {
+    /// Subset the first face in `font_data`.
+    pub fn new(font_data: &'a [u8]) -> Result<Self> {
+        Self::with_face_index(font_data, 0)
+    }
+
+    /// Subset face `index` of a font collection.
+    pub fn with_face_index(font_data: &'a [u8], index: u32) -> Result<Self> {
+        let font =
+            FontRef::from_index(font_data, index).map_err(|e| SubsetError::Parse(e.to_string()))?;
+        Ok(Self {
+            font,
+            glyphs: BTreeSet::new(),
+        })
+    }
+
+    /// Keep `glyphs`.
+    pub fn with_glyphs(mut self, glyphs: impl IntoIterator<Item = GlyphId>) -> Self {
+        self.glyphs.extend(glyphs);
+        self
+    }
+
+    /// Keep every glyph in `shaped`.
+    pub fn with_shaped(self, shaped: &ShapingResult) -> Self {
+        self.with_glyphs(shaped.glyphs.iter().map(|glyph| glyph.id))
+    }
+
+    /// Build the subset font.
+    ///
+    /// Glyph 0 (`.notdef`) is always kept and stays glyph 0; the rest keep
+    /// their relative order.
+    pub fn build(&self) -> Result<Subset> {
+        let font = &self.font;
+        let table = |tag: &'static str| {
+            Tag::new_checked(tag.as_bytes())
+                .ok()
+                .and_then(|tag| font.table_data(tag))
+                .map(|data| data.as_bytes())
+                .ok_or(SubsetError::MissingTable(tag))
+        };
+        if table("glyf").is_err() && (table("CFF ").is_ok() || table("CFF2").is_ok()) {
+            return Err(SubsetError::UnsupportedOutlines);
+        }
+        let glyf = table("glyf")?;
+        let loca = table("loca")?;
+        let head = table("head")?;
+        let hhea = table("hhea")?;
+        let hmtx = table("hmtx")?;
+        let maxp = table("maxp")?;
+
+        let count = read_u16(maxp, MAXP_NUM_GLYPHS, "maxp")?;
+        let long_offsets = read_u16(head, HEAD_INDEX_TO_LOC_FORMAT, "head")? == 1;
+        let outlines = Outlines::new(glyf, loca, long_offsets, count)?;
+
+        let mut wanted = BTreeSet::from([0u16]);
+        for &glyph in &self.glyphs {
+            match u16::try_from(glyph) {
+                Ok(gid) if gid < count => wanted.insert(gid),
+                _ => return Err(SubsetError::GlyphOutOfRange { glyph, count }),
+            };
+        }
+        let kept = glyf::closure(&outlines, &wanted)?;
+        let map: BTreeMap<u16, u16> = kept
+            .iter()
+            .zip(0u16..)
+            .map(|(&old, new)| (old, new))
+            .collect();
+        let new_count = map.len() as u16;
+
+        let (new_glyf, new_loca) = glyf::write_glyf(&outlines, &map)?;
+        let new_hmtx = glyf::write_hmtx(hmtx, read_u16(hhea, HHEA_NUM_H_METRICS, "hhea")?, &map)?;
+
+        let mut new_head = head.to_vec();
+        if new_head.len() < 54 {
+            return Err(SubsetError::Malformed("head"));
+        }
+        write_u16(&mut new_head, HEAD_INDEX_TO_LOC_FORMAT, 1);
+        new_head[8..12].fill(0);
+        let mut new_hhea = hhea.to_vec();
+        write_u16(&mut new_hhea, HHEA_NUM_H_METRICS, new_count);
+        let mut new_maxp = maxp.to_vec();
+        write_u16(&mut new_maxp, MAXP_NUM_GLYPHS, new_count);
+
+        let mut tables = vec![
+            (*b"glyf", new_glyf),
+            (*b"head", new_head),
+            (*b"hhea", new_hhea),
+            (*b"hmtx", new_hmtx),
+            (*b"loca", new_loca),
+            (*b"maxp", new_maxp),
+        ];
+        if let Some(cmap) = self.cmap(&map, count)? {
+            tables.push((*b"cmap", cmap));
+        }
+        if let Ok(post) = table("post") {
+            let mut post = post
+                .get(..POST_HEADER_LEN)
+                .ok_or(SubsetError::Malformed("post"))?
+                .to_vec();
+            post[..4].copy_from_slice(&0x0003_0000u32.to_be_bytes());
+            tables.push((*b"post", post));
+        }
+        for tag in PASSTHROUGH {
+            if let Some(data) = font.table_data(Tag::new(tag)) {
+                tables.push((*tag, data.as_bytes().to_vec()));
+            }
+        }
+
+        Ok(Subset {
+            data: sfnt::assemble(tables),
+            glyph_map: map
+                .into_iter()
+                .map(|(old, new)| (GlyphId::from(old), GlyphId::from(new)))
+                .collect(),
+        })
+    }
+
+    /// The font's best character map, restricted and renumbered to `map`
+    fn cmap(&self, map: &BTreeMap<u16, u16>, count: u16) -> Result<Option<Vec<u8>>> {
+        let Ok(cmap) = self.font.cmap() else {
+            return Ok(None);
+        };
+        let Some((_, record, subtable)) = cmap.best_subtable() else {
+            return Ok(None);
+        };
+        let limits = CmapIterLimits {
+            max_char: char::MAX as u32,
+            glyph_count: u32::from(count),
+        };
+        let mut mappings = BTreeMap::new();
+        for (ch, gid) in subtable.iter_with_limits(limits) {
+            let new = u16::try_from(gid.to_u32())
+                .ok()
+                .and_then(|gid| map.get(&gid));
+            if let Some(&new) = new {
+                mappings.entry(ch).or_insert(new);
+            }
+        }
+        let mappings: Vec<(u32, u16)> = mappings.into_iter().collect();
+        let symbol = record.platform_id() == read_fonts::tables::cmap::PlatformId::Windows
+            && record.encoding_id() == 0;
+        Ok(Some(cmap::write_cmap(&mappings, symbol)))
+    }
+}
+
+/// A subset font and the glyph renumbering that produced it.
+#[derive(Debug, Clone)]
+pub struct Subset {
+    /// The subset font file (TrueType)
+    pub data: Vec<u8>,
+    glyph_map: BTreeMap<GlyphId, GlyphId>,
+}
+
+impl Subset {
+    /// The subset's ID for `original`, if the glyph was kept.
+    pub fn glyph_id(&self, original: GlyphId) -> Option<GlyphId> {
+        self.glyph_map.get(&original).copied()
+    }
+
+    /// Number of glyphs in the subset, `.notdef` and composite parts included.
+    pub fn glyph_count(&self) -> usize {
+        self.glyph_map.len()
+    }
+
+    /// (original, subset) ID pairs in ascending order.
+    pub fn glyph_map(&self) -> impl Iterator<Item = (GlyphId, GlyphId)> + '_ {
+        self.glyph_map.iter().map(|(&old, &new)| (old, new))
+    }
+
+    /// `shaped` with its glyph IDs renumbered for this subset.
+    ///
+    /// Glyphs the subset does not hold become `.notdef`.
+    pub fn remap(&self, shaped: &ShapingResult) -> ShapingResult {
+        let mut remapped = shaped.clone();
+        for glyph in &mut remapped.glyphs {
+            glyph.id = self.glyph_id(glyph.id).unwrap_or(0);
+        }
+        remapped
+    }
+}
+
+/// Subset `font_data` to the glyphs in `shaped`.
+pub fn subset_shaped(font_data: &[u8], shaped: &ShapingResult) -> Result<Subset> {
+    Subsetter::new(font_data)?.with_shaped(shaped).build()
+}
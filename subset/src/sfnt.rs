@@ -0,0 +1,112 @@
+//! Reading and assembling sfnt font files
+
+use crate::{Result, SubsetError};
+
+/// Big-endian `u16` at `offset` in `table`
+pub(crate) fn read_u16(data: &[u8], offset: usize, table: &'static str) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or(SubsetError::Malformed(table))
+}
+
+/// Big-endian `u32` at `offset` in `table`
+pub(crate) fn read_u32(data: &[u8], offset: usize, table: &'static str) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or(SubsetError::Malformed(table))
+}
+
+/// Overwrite the big-endian `u16` at `offset`
+pub(crate) fn write_u16(data: &mut [u8], offset: usize, value: u16) {
+    data[offset..offset + 2].copy_from_slice(&value.to_be_bytes());
+}
+
+/// Overwrite the big-endian `u32` at `offset`
+pub(crate) fn write_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+}
+
+/// Sum of `data` as big-endian `u32`s, zero-padded
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Offset of `checksumAdjustment` in `head`
+const HEAD_CHECKSUM_ADJUSTMENT: usize = 8;
+
+/// Write a TrueType font file holding `tables`.
+///
+/// Sorts the tables by tag, pads each to four bytes, and fills in the
+/// directory checksums and `head.checksumAdjustment`.
+pub(crate) fn assemble(mut tables: Vec<([u8; 4], Vec<u8>)>) -> Vec<u8> {
+    tables.sort_by_key(|(tag, _)| *tag);
+    let count = tables.len() as u16;
+    let entry_selector = if count == 0 {
+        0
+    } else {
+        15 - count.leading_zeros() as u16
+    };
+    let search_range = (1u16 << entry_selector) * 16;
+
+    let mut font = Vec::new();
+    font.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    font.extend_from_slice(&count.to_be_bytes());
+    font.extend_from_slice(&search_range.to_be_bytes());
+    font.extend_from_slice(&entry_selector.to_be_bytes());
+    font.extend_from_slice(&(count * 16 - search_range).to_be_bytes());
+
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, data) in &tables {
+        font.extend_from_slice(tag);
+        font.extend_from_slice(&checksum(data).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+
+    let mut head_offset = None;
+    for (tag, data) in &tables {
+        if tag == b"head" {
+            head_offset = Some(font.len());
+        }
+        font.extend_from_slice(data);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+    if let Some(head) = head_offset {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&font));
+        write_u32(&mut font, head + HEAD_CHECKSUM_ADJUSTMENT, adjustment);
+    }
+    font
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assemble_when_tables_unsorted_then_directory_sorted_and_padded() {
+        let font = assemble(vec![(*b"post", vec![1, 2, 3]), (*b"cmap", vec![4; 8])]);
+
+        assert_eq!(read_u16(&font, 4, "").ok(), Some(2));
+        assert_eq!(&font[12..16], b"cmap");
+        assert_eq!(&font[28..32], b"post");
+        assert_eq!(read_u32(&font, 12 + 8, "").ok(), Some(44));
+        assert_eq!(read_u32(&font, 28 + 8, "").ok(), Some(52));
+        assert_eq!(read_u32(&font, 28 + 12, "").ok(), Some(3));
+        assert_eq!(font.len(), 56);
+    }
+
+    #[test]
+    fn test_checksum_when_whole_font_summed_then_matches_magic() {
+        let mut head = vec![0u8; 54];
+        head[12..16].copy_from_slice(&0x5F0F_3CF5u32.to_be_bytes());
+        let font = assemble(vec![(*b"head", head), (*b"name", vec![7; 5])]);
+
+        assert_eq!(checksum(&font), 0xB1B0_AFBA);
+    }
+}
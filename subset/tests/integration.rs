@@ -0,0 +1,143 @@
+//! Integration tests for subsetting a real font.
+
+use std::fs;
+use std::path::PathBuf;
+
+use read_fonts::types::GlyphId;
+use read_fonts::{FontRef, TableProvider};
+use typf_core::types::{Direction, PositionedGlyph, ShapingResult};
+use typf_subset::{subset_shaped, SubsetError, Subsetter};
+
+fn load_font(name: &str) -> Option<Vec<u8>> {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.pop(); // root
+    path.push("test-fonts");
+    path.push(name);
+    fs::read(&path).ok()
+}
+
+/// `text` mapped through the font's cmap, one glyph per character
+fn shaped(font: &FontRef, text: &str) -> ShapingResult {
+    let cmap = font.cmap().expect("cmap");
+    let glyphs: Vec<_> = text
+        .chars()
+        .enumerate()
+        .map(|(i, ch)| PositionedGlyph {
+            id: cmap.map_codepoint(ch).map_or(0, |gid| gid.to_u32()),
+            x: i as f32 * 10.0,
+            y: 0.0,
+            advance: 10.0,
+            cluster: i as u32,
+        })
+        .collect();
+    ShapingResult {
+        advance_width: glyphs.len() as f32 * 10.0,
+        advance_height: 0.0,
+        glyphs,
+        direction: Direction::LeftToRight,
+    }
+}
+
+#[test]
+fn test_subset_when_shaped_text_then_font_holds_only_its_glyphs() {
+    let Some(data) = load_font("NotoSans-Regular.ttf") else {
+        eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+        return;
+    };
+    let font = FontRef::new(&data).expect("parse font");
+    let run = shaped(&font, "Typf typf");
+
+    let subset = subset_shaped(&data, &run).expect("subset");
+    let small = FontRef::new(&subset.data).expect("parse subset");
+
+    // .notdef plus T, y, p, f, space, t
+    assert_eq!(subset.glyph_count(), 7);
+    assert_eq!(small.maxp().expect("maxp").num_glyphs(), 7);
+    assert!(subset.data.len() < data.len() / 4);
+    assert!(small.gsub().is_err(), "layout tables are dropped");
+
+    let remapped = subset.remap(&run);
+    let small_cmap = small.cmap().expect("cmap");
+    let (old_hmtx, new_hmtx) = (font.hmtx().expect("hmtx"), small.hmtx().expect("hmtx"));
+    for ((ch, before), after) in "Typf typf".chars().zip(&run.glyphs).zip(&remapped.glyphs) {
+        assert_eq!(
+            small_cmap.map_codepoint(ch).map(|gid| gid.to_u32()),
+            Some(after.id)
+        );
+        assert_eq!(
+            old_hmtx.advance(GlyphId::new(before.id)),
+            new_hmtx.advance(GlyphId::new(after.id)),
+            "advance of {ch:?}"
+        );
+    }
+    assert_eq!(small_cmap.map_codepoint('x'), None);
+
+    let (old_glyf, old_loca) = (font.glyf().expect("glyf"), font.loca(None).expect("loca"));
+    let (new_glyf, new_loca) = (small.glyf().expect("glyf"), small.loca(None).expect("loca"));
+    let outline = |glyf, loca: &read_fonts::tables::loca::Loca, gid: u32| {
+        loca.get_glyf(GlyphId::new(gid), glyf)
+            .expect("glyph")
+            .map(|glyph| {
+                (
+                    glyph.number_of_contours(),
+                    glyph.x_min(),
+                    glyph.y_min(),
+                    glyph.x_max(),
+                    glyph.y_max(),
+                )
+            })
+    };
+    assert_eq!(
+        outline(&old_glyf, &old_loca, run.glyphs[0].id),
+        outline(&new_glyf, &new_loca, remapped.glyphs[0].id)
+    );
+}
+
+#[test]
+fn test_subset_when_composite_glyph_then_components_kept() {
+    let Some(data) = load_font("NotoSans-Regular.ttf") else {
+        eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+        return;
+    };
+    let font = FontRef::new(&data).expect("parse font");
+    let run = shaped(&font, "é");
+    let subset = subset_shaped(&data, &run).expect("subset");
+    let small = FontRef::new(&subset.data).expect("parse subset");
+
+    let glyf = small.glyf().expect("glyf");
+    let loca = small.loca(None).expect("loca");
+    let id = subset.glyph_id(run.glyphs[0].id).expect("kept");
+    let Some(read_fonts::tables::glyf::Glyph::Composite(composite)) =
+        loca.get_glyf(GlyphId::new(id), &glyf).expect("glyph")
+    else {
+        eprintln!("Skipping test: é is not a composite glyph in this font");
+        return;
+    };
+    for component in composite.components() {
+        let part = component.glyph.to_u32();
+        assert!(part < subset.glyph_count() as u32);
+        assert!(subset.glyph_map().any(|(_, new)| new == part));
+    }
+    assert!(subset.glyph_count() > 2);
+}
+
+#[test]
+fn test_subset_when_glyph_out_of_range_then_error() {
+    let Some(data) = load_font("NotoSans-Regular.ttf") else {
+        eprintln!("Skipping test: NotoSans-Regular.ttf not found");
+        return;
+    };
+    let result = Subsetter::new(&data)
+        .expect("parse font")
+        .with_glyphs([1, 65_000])
+        .build();
+
+    assert!(matches!(
+        result,
+        Err(SubsetError::GlyphOutOfRange { glyph: 65_000, .. })
+    ));
+    assert!(matches!(
+        Subsetter::new(b"not a font"),
+        Err(SubsetError::Parse(_))
+    ));
+}
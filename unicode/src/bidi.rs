@@ -0,0 +1,200 @@
+//! One paragraph of bidirectional text, per UAX #9.
+//!
+//! [`BidiParagraph`] resolves embedding levels for a paragraph: its base
+//! direction, from the first strong character outside isolates when not
+//! given (rules P2–P3), the explicit embeddings, overrides, and isolates
+//! (LRE/RLE/LRO/RLO/PDF, LRI/RLI/FSI/PDI), and the weak, neutral, and
+//! bracket-pair rules. Layout then asks for each line in display order with
+//! [`BidiParagraph::visual_runs`] or [`BidiParagraph::visual_order`], which
+//! apply the line rules L1–L2.
+//!
+//! Offsets are bytes into the paragraph's text. Text holding several
+//! paragraphs is split with [`BidiParagraph::split`].
+
+use std::ops::Range;
+
+use unicode_bidi::{BidiClass, BidiInfo, Level, ParagraphBidiInfo};
+
+use typf_core::types::Direction;
+
+/// The resolved levels of one paragraph
+#[derive(Debug)]
+pub struct BidiParagraph<'a> {
+    info: ParagraphBidiInfo<'a>,
+    start: usize,
+}
+
+/// A run of one level within a line, part of its display order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BidiRun {
+    /// Bytes of the paragraph's text in this run, in logical order
+    pub range: Range<usize>,
+    /// Embedding level; odd levels read right to left
+    pub level: u8,
+}
+
+impl BidiRun {
+    /// Which way the run reads, and so should be shaped
+    pub fn direction(&self) -> Direction {
+        if self.level % 2 == 1 {
+            Direction::RightToLeft
+        } else {
+            Direction::LeftToRight
+        }
+    }
+}
+
+impl<'a> BidiParagraph<'a> {
+    /// Resolve `text` as one paragraph.
+    ///
+    /// `base` is the paragraph direction; `None` takes it from the first
+    /// strong character outside isolates, left to right if there is none.
+    /// Vertical directions count as left to right.
+    pub fn new(text: &'a str, base: Option<Direction>) -> Self {
+        Self {
+            info: ParagraphBidiInfo::new(text, base.map(base_level)),
+            start: 0,
+        }
+    }
+
+    /// Split `text` at paragraph separators (rule P1) and resolve each
+    /// paragraph, detecting each one's direction when `base` is `None`
+    pub fn split(text: &'a str, base: Option<Direction>) -> Vec<Self> {
+        let paragraphs = BidiInfo::new(text, base.map(base_level)).paragraphs;
+        paragraphs
+            .into_iter()
+            .map(|paragraph| Self {
+                info: ParagraphBidiInfo::new(&text[paragraph.range.clone()], Some(paragraph.level)),
+                start: paragraph.range.start,
+            })
+            .collect()
+    }
+
+    /// The paragraph's text, separator included
+    pub fn text(&self) -> &'a str {
+        self.info.text
+    }
+
+    /// Where the paragraph sits in the text given to [`Self::split`]
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.start + self.info.text.len()
+    }
+
+    /// The paragraph embedding level: 0 left to right, 1 right to left
+    pub fn base_level(&self) -> u8 {
+        self.info.paragraph_level.number()
+    }
+
+    /// The paragraph direction
+    pub fn direction(&self) -> Direction {
+        if self.info.paragraph_level.is_rtl() {
+            Direction::RightToLeft
+        } else {
+            Direction::LeftToRight
+        }
+    }
+
+    /// Whether anything in the paragraph reads right to left
+    pub fn has_rtl(&self) -> bool {
+        self.info.has_rtl()
+    }
+
+    /// Level of each character of `line`, after the line rule L1 resets
+    /// trailing whitespace and separators to the paragraph level.
+    ///
+    /// Characters removed by rule X9 (embedding and override controls and
+    /// boundary neutrals) are `None`: they take no part in display.
+    pub fn levels(&self, line: Range<usize>) -> Vec<Option<u8>> {
+        let levels = self.info.reordered_levels(line.clone());
+        self.info.text[line.clone()]
+            .char_indices()
+            .map(|(offset, _)| {
+                let offset = line.start + offset;
+                (!self.is_removed(offset)).then(|| levels[offset].number())
+            })
+            .collect()
+    }
+
+    /// Byte offsets of the characters of `line` in display order, left to
+    /// right, leaving out those removed by rule X9
+    pub fn visual_order(&self, line: Range<usize>) -> Vec<usize> {
+        let levels = self.info.reordered_levels(line.clone());
+        let offsets: Vec<usize> = self.info.text[line.clone()]
+            .char_indices()
+            .map(|(offset, _)| line.start + offset)
+            .collect();
+        let char_levels: Vec<Level> = offsets.iter().map(|&offset| levels[offset]).collect();
+        BidiInfo::reorder_visual(&char_levels)
+            .into_iter()
+            .map(|i| offsets[i])
+            .filter(|&offset| !self.is_removed(offset))
+            .collect()
+    }
+
+    /// The level runs of `line` in display order, left to right; shape
+    /// each run in its own [`BidiRun::direction`]
+    pub fn visual_runs(&self, line: Range<usize>) -> Vec<BidiRun> {
+        if line.is_empty() {
+            return Vec::new();
+        }
+        let (levels, runs) = self.info.visual_runs(line);
+        runs.into_iter()
+            .map(|range| BidiRun {
+                level: levels[range.start].number(),
+                range,
+            })
+            .collect()
+    }
+
+    fn is_removed(&self, offset: usize) -> bool {
+        use BidiClass::*;
+        matches!(
+            self.info.original_classes[offset],
+            LRE | RLE | LRO | RLO | PDF | BN
+        )
+    }
+}
+
+fn base_level(direction: Direction) -> Level {
+    match direction {
+        Direction::RightToLeft => Level::rtl(),
+        _ => Level::ltr(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_when_paragraphs_differ_then_each_detects_direction() {
+        let text = "abc\n\u{05D0}\u{05D1}";
+        let paragraphs = BidiParagraph::split(text, None);
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(paragraphs[0].text(), "abc\n");
+        assert_eq!(paragraphs[0].direction(), Direction::LeftToRight);
+        assert_eq!(paragraphs[1].range(), 4..text.len());
+        assert_eq!(paragraphs[1].direction(), Direction::RightToLeft);
+    }
+
+    #[test]
+    fn test_visual_runs_when_mixed_then_runs_in_display_order() {
+        // Hebrew, a number, then Latin in a right-to-left paragraph
+        let text = "\u{05D0}\u{05D1} 12 ab";
+        let paragraph = BidiParagraph::new(text, Some(Direction::RightToLeft));
+        let runs = paragraph.visual_runs(0..text.len());
+        let texts: Vec<&str> = runs.iter().map(|run| &text[run.range.clone()]).collect();
+        assert_eq!(texts, ["ab", " ", "12", "\u{05D0}\u{05D1} "]);
+        assert_eq!(runs[0].direction(), Direction::LeftToRight);
+        assert_eq!(runs[3].direction(), Direction::RightToLeft);
+    }
+
+    #[test]
+    fn test_visual_order_when_line_is_part_of_paragraph_then_only_that_line() {
+        let text = "ab \u{05D0}\u{05D1}";
+        let paragraph = BidiParagraph::new(text, None);
+        assert_eq!(paragraph.base_level(), 0);
+        assert_eq!(paragraph.visual_order(3..text.len()), [5, 3]);
+        assert!(paragraph.visual_runs(0..0).is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+//! Text boundaries for editing: graphemes, words, and line breaks.
+//!
+//! The same UAX #29 and UAX #14 segmentation that builds runs, exposed on
+//! its own so applications can move a caret by grapheme or word, select a
+//! word on double-click, or find where lines may wrap, without a second
+//! copy of the Unicode data.
+//!
+//! Offsets are bytes into the text and always fall on char boundaries.
+
+use std::ops::Range;
+
+use icu_segmenter::{
+    options::{LineBreakOptions, WordBreakInvariantOptions, WordType},
+    GraphemeClusterSegmenter, LineSegmenter, WordSegmenter,
+};
+
+/// What a [`WordSegment`] holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordKind {
+    /// Letters, including CJK ideographs and kana
+    Letter,
+    /// Digits, with any separators inside the number
+    Number,
+    /// Spaces, punctuation, and symbols
+    Other,
+}
+
+/// One segment between UAX #29 word boundaries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordSegment {
+    /// Where the segment sits in the text
+    pub range: Range<usize>,
+    /// What it holds
+    pub kind: WordKind,
+}
+
+impl WordSegment {
+    /// Whether this is a word a user would select: letters or a number
+    pub fn is_word(&self) -> bool {
+        self.kind != WordKind::Other
+    }
+}
+
+/// A place a line may end (UAX #14)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineBreak {
+    /// The next line would start here
+    pub offset: usize,
+    /// The line must end here, after a line terminator
+    pub mandatory: bool,
+}
+
+/// Grapheme cluster boundaries of `text`, from 0 to `text.len()`: the places
+/// a caret may stop
+pub fn grapheme_boundaries(text: &str) -> impl Iterator<Item = usize> + '_ {
+    GraphemeClusterSegmenter::new().segment_str(text)
+}
+
+/// The segments of `text` between word boundaries, in order; together they
+/// cover the whole text
+pub fn words(text: &str) -> impl Iterator<Item = WordSegment> + '_ {
+    let mut start = 0;
+    WordSegmenter::new_auto(WordBreakInvariantOptions::default())
+        .segment_str(text)
+        .iter_with_word_type()
+        .filter_map(move |(offset, word_type)| {
+            let range = start..offset;
+            start = offset;
+            let kind = match word_type {
+                WordType::Letter => WordKind::Letter,
+                WordType::Number => WordKind::Number,
+                _ => WordKind::Other,
+            };
+            (!range.is_empty()).then_some(WordSegment { range, kind })
+        })
+}
+
+/// Line break opportunities of `text`, in order, ending with `text.len()`
+pub fn line_breaks(text: &str) -> impl Iterator<Item = LineBreak> + '_ {
+    LineSegmenter::new_auto(LineBreakOptions::default())
+        .segment_str(text)
+        .filter(|&offset| offset > 0)
+        .map(|offset| LineBreak {
+            offset,
+            mandatory: text[..offset]
+                .chars()
+                .next_back()
+                .is_some_and(is_line_terminator),
+        })
+}
+
+/// The caret stop after `offset`, or `text.len()` at the end
+pub fn next_grapheme(text: &str, offset: usize) -> usize {
+    grapheme_boundaries(text)
+        .find(|&boundary| boundary > offset)
+        .unwrap_or(text.len())
+}
+
+/// The caret stop before `offset`, or 0 at the start
+pub fn prev_grapheme(text: &str, offset: usize) -> usize {
+    grapheme_boundaries(text)
+        .take_while(|&boundary| boundary < offset)
+        .last()
+        .unwrap_or(0)
+}
+
+/// The segment a double-click at `offset` selects: the word segment holding
+/// it, or the one ending there when `offset` is the end of the text
+pub fn word_at(text: &str, offset: usize) -> Range<usize> {
+    let mut last = 0..0;
+    for segment in words(text) {
+        if segment.range.contains(&offset) {
+            return segment.range;
+        }
+        last = segment.range;
+    }
+    last
+}
+
+/// Where a caret moving right by word stops: the end of the next word after
+/// `offset`, or `text.len()`
+pub fn next_word_end(text: &str, offset: usize) -> usize {
+    words(text)
+        .find(|segment| segment.is_word() && segment.range.end > offset)
+        .map_or(text.len(), |segment| segment.range.end)
+}
+
+/// Where a caret moving left by word stops: the start of the previous word
+/// before `offset`, or 0
+pub fn prev_word_start(text: &str, offset: usize) -> usize {
+    words(text)
+        .take_while(|segment| segment.range.start < offset)
+        .filter(WordSegment::is_word)
+        .last()
+        .map_or(0, |segment| segment.range.start)
+}
+
+/// Whether `ch` ends a line: the mandatory break classes BK, CR, LF, NL
+pub(crate) fn is_line_terminator(ch: char) -> bool {
+    matches!(
+        ch,
+        '\n' | '\r' | '\u{000B}' | '\u{000C}' | '\u{0085}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grapheme_boundaries_keep_clusters_whole() {
+        // e + combining acute, then a flag made of two regional indicators
+        let text = "e\u{0301}\u{1F1EF}\u{1F1F5}x";
+        let boundaries: Vec<usize> = grapheme_boundaries(text).collect();
+        assert_eq!(boundaries, [0, 3, 11, 12]);
+        assert_eq!(next_grapheme(text, 0), 3);
+        assert_eq!(next_grapheme(text, 3), 11);
+        assert_eq!(prev_grapheme(text, 11), 3);
+        assert_eq!(prev_grapheme(text, 0), 0);
+        assert_eq!(next_grapheme(text, 12), 12);
+    }
+
+    #[test]
+    fn test_words_classify_segments() {
+        let text = "Hi, 42 ok";
+        let segments: Vec<(&str, WordKind)> = words(text)
+            .map(|segment| (&text[segment.range], segment.kind))
+            .collect();
+        assert_eq!(
+            segments,
+            [
+                ("Hi", WordKind::Letter),
+                (",", WordKind::Other),
+                (" ", WordKind::Other),
+                ("42", WordKind::Number),
+                (" ", WordKind::Other),
+                ("ok", WordKind::Letter),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_word_navigation_for_selection_and_caret() {
+        let text = "one, two three";
+        assert_eq!(word_at(text, 6), 5..8);
+        assert_eq!(word_at(text, 3), 3..4);
+        assert_eq!(word_at(text, text.len()), 9..14);
+        assert_eq!(next_word_end(text, 0), 3);
+        assert_eq!(next_word_end(text, 3), 8);
+        assert_eq!(next_word_end(text, 14), 14);
+        assert_eq!(prev_word_start(text, 14), 9);
+        assert_eq!(prev_word_start(text, 9), 5);
+        assert_eq!(prev_word_start(text, 2), 0);
+    }
+
+    #[test]
+    fn test_line_breaks_mark_mandatory_ones() {
+        let breaks: Vec<LineBreak> = line_breaks("a b\nc").collect();
+        assert_eq!(
+            breaks,
+            [
+                LineBreak {
+                    offset: 2,
+                    mandatory: false
+                },
+                LineBreak {
+                    offset: 4,
+                    mandatory: true
+                },
+                LineBreak {
+                    offset: 5,
+                    mandatory: false
+                },
+            ]
+        );
+        assert_eq!(line_breaks("").count(), 0);
+    }
+}
@@ -0,0 +1,164 @@
+//! Where a word may be hyphenated.
+//!
+//! [`Hyphenator`] is the hook [`wrap_text`](crate::wrap_text) calls for
+//! words that might not fit on a line. [`TexHyphenator`] implements it with
+//! Frank Liang's algorithm, the one TeX uses, over the pattern files TeX
+//! distributions ship for each language (`hyph-en-us.pat.txt`,
+//! `hyph-de-1996.pat.txt`, ...). No patterns are bundled; load the ones for
+//! the text's language.
+
+use std::collections::HashMap;
+
+/// Finds the places inside a word where it may break with a hyphen
+pub trait Hyphenator: Send + Sync {
+    /// Byte offsets into `word`, in ascending order, before which a hyphen
+    /// break is allowed. Never 0 or `word.len()`.
+    fn hyphenate(&self, word: &str) -> Vec<usize>;
+}
+
+/// Liang's pattern hyphenation over TeX patterns
+#[derive(Debug, Clone, Default)]
+pub struct TexHyphenator {
+    /// Letters of a pattern, to the priority before each letter and after
+    /// the last
+    patterns: HashMap<String, Vec<u8>>,
+    /// Whole words with fixed break points, as char indices
+    exceptions: HashMap<String, Vec<usize>>,
+    /// Longest pattern, in chars
+    longest: usize,
+    /// Fewest chars left before a break. Default: 2
+    pub left_min: usize,
+    /// Fewest chars left after a break. Default: 3
+    pub right_min: usize,
+}
+
+impl TexHyphenator {
+    /// Build from TeX patterns (`hy3ph he2n 1na ...`, whitespace separated,
+    /// `%` comments) and exceptions (`as-so-ciate ...`); either may be empty
+    pub fn new(patterns: &str, exceptions: &str) -> Self {
+        let mut hyphenator = Self {
+            left_min: 2,
+            right_min: 3,
+            ..Self::default()
+        };
+        for pattern in tokens(patterns) {
+            let mut letters = String::new();
+            let mut values = vec![0u8];
+            for ch in pattern.chars() {
+                match ch.to_digit(10) {
+                    Some(digit) => {
+                        if let Some(last) = values.last_mut() {
+                            *last = digit as u8;
+                        }
+                    },
+                    None => {
+                        letters.push(ch);
+                        values.push(0);
+                    },
+                }
+            }
+            hyphenator.longest = hyphenator.longest.max(letters.chars().count());
+            hyphenator.patterns.insert(letters, values);
+        }
+        for exception in tokens(exceptions) {
+            let mut word = String::new();
+            let mut breaks = Vec::new();
+            for ch in exception.chars() {
+                if ch == '-' {
+                    breaks.push(word.chars().count());
+                } else {
+                    word.push(ch);
+                }
+            }
+            hyphenator.exceptions.insert(word.to_lowercase(), breaks);
+        }
+        hyphenator
+    }
+
+    /// Char indices before which `word` may break
+    fn break_indices(&self, word: &str) -> Vec<usize> {
+        let lower = word.to_lowercase();
+        let len = lower.chars().count();
+        // Case mapping that changes the length would misplace the breaks
+        if len != word.chars().count() || len < self.left_min + self.right_min {
+            return Vec::new();
+        }
+        if let Some(breaks) = self.exceptions.get(&lower) {
+            return breaks.clone();
+        }
+
+        let dotted: Vec<char> = std::iter::once('.')
+            .chain(lower.chars())
+            .chain(std::iter::once('.'))
+            .collect();
+        let mut points = vec![0u8; dotted.len() + 1];
+        for start in 0..dotted.len() {
+            let end_max = dotted.len().min(start + self.longest);
+            let mut key = String::new();
+            for &ch in &dotted[start..end_max] {
+                key.push(ch);
+                if let Some(values) = self.patterns.get(&key) {
+                    for (k, &value) in values.iter().enumerate() {
+                        let point = &mut points[start + k];
+                        *point = (*point).max(value);
+                    }
+                }
+            }
+        }
+
+        // `points[i + 1]` sits before the word's char `i`
+        (self.left_min.max(1)..=len.saturating_sub(self.right_min.max(1)))
+            .filter(|&i| points[i + 1] % 2 == 1)
+            .collect()
+    }
+}
+
+impl Hyphenator for TexHyphenator {
+    fn hyphenate(&self, word: &str) -> Vec<usize> {
+        let offsets: Vec<usize> = word.char_indices().map(|(offset, _)| offset).collect();
+        self.break_indices(word)
+            .into_iter()
+            .filter_map(|i| offsets.get(i).copied())
+            .filter(|&offset| offset > 0)
+            .collect()
+    }
+}
+
+/// Whitespace-separated words of a TeX pattern file, without `%` comments
+fn tokens(source: &str) -> impl Iterator<Item = &str> {
+    source
+        .lines()
+        .map(|line| line.split('%').next().unwrap_or_default())
+        .flat_map(str::split_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The patterns from Liang's thesis that hyphenate "hyphenation"
+    const PATTERNS: &str = "hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n % from Liang (1983)";
+
+    #[test]
+    fn test_hyphenate_when_patterns_match_then_liang_breaks() {
+        let hyphenator = TexHyphenator::new(PATTERNS, "");
+        // hy-phen-ation
+        assert_eq!(hyphenator.hyphenate("hyphenation"), vec![2, 6]);
+        assert_eq!(hyphenator.hyphenate("Hyphenation"), vec![2, 6]);
+    }
+
+    #[test]
+    fn test_hyphenate_when_exception_listed_then_exception_wins() {
+        let hyphenator = TexHyphenator::new(PATTERNS, "hyphen-ation\nta-ble");
+        assert_eq!(hyphenator.hyphenate("hyphenation"), vec![6]);
+        assert_eq!(hyphenator.hyphenate("Table"), vec![2]);
+    }
+
+    #[test]
+    fn test_hyphenate_when_margins_exclude_break_then_none() {
+        let mut hyphenator = TexHyphenator::new(PATTERNS, "");
+        hyphenator.left_min = 3;
+        assert_eq!(hyphenator.hyphenate("hyphenation"), vec![6]);
+        assert!(hyphenator.hyphenate("hy").is_empty());
+    }
+}
@@ -12,8 +12,22 @@ use unicode_normalization::UnicodeNormalization;
 use typf_core::{
     error::Result,
     types::{Direction, TextRun},
+    whitespace::is_zero_width,
 };
 
+mod bidi;
+mod boundaries;
+mod hyphenate;
+mod wrap;
+
+pub use bidi::{BidiParagraph, BidiRun};
+pub use boundaries::{
+    grapheme_boundaries, line_breaks, next_grapheme, next_word_end, prev_grapheme, prev_word_start,
+    word_at, words, LineBreak, WordKind, WordSegment,
+};
+pub use hyphenate::{Hyphenator, TexHyphenator};
+pub use wrap::{wrap_text, Line, WrapOptions};
+
 /// Configuration for how deeply we analyze your text
 #[derive(Debug, Clone, Default)]
 pub struct UnicodeOptions {
@@ -68,23 +82,61 @@ impl UnicodeProcessor {
     }
 
     /// Maps out when and where writing systems change in your text
+    ///
+    /// Follows UAX #24: Common and Inherited characters (spaces, punctuation,
+    /// combining marks) join the run around them, and those at the start
+    /// join the first real script. A character whose Script_Extensions
+    /// include the run's script stays in the run. A closing bracket takes
+    /// the script of its opening bracket, so "(مرحبا)" is one Arabic run
+    /// while "abc (مرحبا) def" keeps both brackets with the Latin.
     fn detect_scripts(&self, text: &str) -> Result<Vec<(Script, usize, usize)>> {
-        use icu_properties::script::ScriptWithExtensions;
+        use icu_properties::props::{BidiMirroringGlyph, BidiPairedBracketType};
+        use icu_properties::{script::ScriptWithExtensions, CodePointMapData};
         let script_data = ScriptWithExtensions::new();
+        let brackets = CodePointMapData::<BidiMirroringGlyph>::new();
         let mut scripts = Vec::new();
         let mut current_script = Script::Common;
         let mut start = 0;
+        // Open brackets: the closing bracket they wait for, and their script
+        let mut open: Vec<(char, Script)> = Vec::new();
 
         for (i, ch) in text.char_indices() {
-            let script = script_data.get_script_val(ch);
+            let mut script = script_data.get_script_val(ch);
+
+            let bracket = brackets.get(ch);
+            match bracket.paired_bracket_type {
+                BidiPairedBracketType::Open => {
+                    if let Some(close) = bracket.mirroring_glyph {
+                        open.push((close, current_script));
+                    }
+                },
+                BidiPairedBracketType::Close => {
+                    if let Some(depth) = open.iter().rposition(|&(close, _)| close == ch) {
+                        script = open[depth].1;
+                        open.truncate(depth);
+                    }
+                },
+                _ => {},
+            }
 
-            if script != current_script && script != Script::Common && script != Script::Inherited {
-                if i > start {
-                    scripts.push((current_script, start, i));
+            if script == Script::Common
+                || script == Script::Inherited
+                || script == current_script
+                || (current_script != Script::Common && script_data.has_script(ch, current_script))
+            {
+                continue;
+            }
+            if current_script == Script::Common {
+                // The leading Common text, open brackets included, joins
+                // the first real script
+                for entry in open.iter_mut().filter(|(_, s)| *s == Script::Common) {
+                    entry.1 = script;
                 }
-                current_script = script;
+            } else {
+                scripts.push((current_script, start, i));
                 start = i;
             }
+            current_script = script;
         }
 
         if start < text.len() {
@@ -194,7 +246,8 @@ impl UnicodeProcessor {
         for boundary in segmenter.segment_str(text) {
             if boundary > last {
                 let word = &text[last..boundary];
-                if !word.trim().is_empty() {
+                // Spaces, tabs, and invisible joiners separate words, they aren't words
+                if !word.chars().all(|c| c.is_whitespace() || is_zero_width(c)) {
                     words.push(word.to_string());
                 }
             }
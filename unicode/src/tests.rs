@@ -32,6 +32,15 @@ fn test_word_segmentation() {
     assert!(words.contains(&"123".to_string()));
 }
 
+#[test]
+fn test_word_segmentation_skips_tabs_and_invisible_spaces() {
+    let processor = UnicodeProcessor::new();
+    let words = processor
+        .segment_words("one\ttwo\u{00A0}three\u{200B}four\u{2009}five")
+        .unwrap();
+    assert_eq!(words, ["one", "two", "three", "four", "five"]);
+}
+
 #[test]
 fn test_line_breaks() {
     let processor = UnicodeProcessor::new();
@@ -354,3 +363,61 @@ fn test_line_breaking_long_text() {
     // Should have multiple break opportunities (spaces, punctuation)
     assert!(breaks.len() > 10);
 }
+
+fn script_runs(text: &str) -> Vec<(Script, &str)> {
+    let processor = UnicodeProcessor::new();
+    let options = UnicodeOptions {
+        detect_scripts: true,
+        ..Default::default()
+    };
+    processor
+        .process(text, &options)
+        .unwrap()
+        .into_iter()
+        .map(|run| (run.script, &text[run.start..run.end]))
+        .collect()
+}
+
+#[test]
+fn test_scripts_bracketed_arabic_is_one_run() {
+    assert_eq!(script_runs("(مرحبا)"), [(Script::Arabic, "(مرحبا)")]);
+}
+
+#[test]
+fn test_scripts_brackets_match_their_pair() {
+    assert_eq!(
+        script_runs("abc (مرحبا) def"),
+        [
+            (Script::Latin, "abc ("),
+            (Script::Arabic, "مرحبا"),
+            (Script::Latin, ") def"),
+        ]
+    );
+    // An unmatched closing bracket stays with the run it follows
+    assert_eq!(
+        script_runs("abc مرحبا) def"),
+        [
+            (Script::Latin, "abc "),
+            (Script::Arabic, "مرحبا) "),
+            (Script::Latin, "def")
+        ]
+    );
+}
+
+#[test]
+fn test_scripts_leading_common_and_marks_join_next_script() {
+    // Leading punctuation and a stray combining mark take the first script
+    assert_eq!(
+        script_runs("\"\u{0301}Привет"),
+        [(Script::Cyrillic, "\"\u{0301}Привет")]
+    );
+    assert_eq!(script_runs("12 日本"), [(Script::Han, "12 日本")]);
+}
+
+#[test]
+fn test_scripts_extensions_keep_run() {
+    // ARABIC TATWEEL is Common with Arabic among its script extensions;
+    // the Devanagari danda is shared by several Indic scripts
+    assert_eq!(script_runs("عـربي"), [(Script::Arabic, "عـربي")]);
+    assert_eq!(script_runs("नमस्ते।"), [(Script::Devanagari, "नमस्ते।")]);
+}
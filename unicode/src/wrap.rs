@@ -0,0 +1,382 @@
+//! Breaking a paragraph into lines that fit a width.
+//!
+//! [`wrap_text`] fills lines greedily: each line takes the most text that
+//! fits, ending at a UAX #14 line break opportunity or, when
+//! [`WrapOptions::hyphenator`] is set, at a hyphenation point inside a word.
+//! A line broken inside a word, or after a soft hyphen (U+00AD) in the text,
+//! is shaped with [`WrapOptions::hyphen`] appended, so the hyphen glyph
+//! counts toward the line's width.
+//!
+//! The paragraph is shaped once to estimate widths from glyph advances;
+//! each line is then reshaped on its own, which both measures it exactly and
+//! gives the shaped glyphs to draw. A line that turns out too wide is backed
+//! off to the previous opportunity. A word longer than the whole width that
+//! cannot be hyphenated overflows its line rather than being cut.
+//!
+//! Lines are logical ranges of the text, each shaped in
+//! [`ShapingParams::direction`]. For mixed-direction text, put each line in
+//! display order with [`BidiParagraph::visual_runs`](crate::BidiParagraph::visual_runs)
+//! and shape the runs separately.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use typf_core::{
+    error::Result,
+    traits::{FontRef, Shaper},
+    types::ShapingResult,
+    ShapingParams,
+};
+
+use crate::boundaries::line_breaks;
+use crate::hyphenate::Hyphenator;
+
+const SOFT_HYPHEN: char = '\u{00AD}';
+
+/// How [`wrap_text`] breaks lines
+#[derive(Clone)]
+pub struct WrapOptions {
+    /// Widest a line may be, in pixels
+    pub max_width: f32,
+    /// Finds hyphenation points in words; `None` breaks only between words
+    /// and at soft hyphens
+    pub hyphenator: Option<Arc<dyn Hyphenator>>,
+    /// Shown at the end of a line broken inside a word. Default: "-"
+    pub hyphen: String,
+}
+
+impl WrapOptions {
+    /// Wrap at `max_width` without hyphenation
+    pub fn new(max_width: f32) -> Self {
+        Self {
+            max_width,
+            hyphenator: None,
+            hyphen: "-".to_string(),
+        }
+    }
+
+    /// Also break inside words where `hyphenator` allows
+    pub fn with_hyphenator(mut self, hyphenator: Arc<dyn Hyphenator>) -> Self {
+        self.hyphenator = Some(hyphenator);
+        self
+    }
+}
+
+impl std::fmt::Debug for WrapOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WrapOptions")
+            .field("max_width", &self.max_width)
+            .field("hyphenator", &self.hyphenator.is_some())
+            .field("hyphen", &self.hyphen)
+            .finish()
+    }
+}
+
+/// One line of a wrapped paragraph
+#[derive(Debug, Clone)]
+pub struct Line {
+    /// The part of the paragraph on this line, trailing spaces and line
+    /// terminators excluded
+    pub range: Range<usize>,
+    /// What was shaped: the range's text, plus the hyphen if `hyphenated`
+    pub text: String,
+    /// `text`, shaped; clusters are byte offsets into `text`
+    pub shaped: ShapingResult,
+    /// Whether the line ends inside a word and shows a hyphen
+    pub hyphenated: bool,
+}
+
+/// A place a line may end
+#[derive(Debug, Clone, Copy)]
+struct Opportunity {
+    offset: usize,
+    /// The next line must start here, after a line terminator
+    mandatory: bool,
+    /// Breaking here shows a hyphen
+    hyphen: bool,
+}
+
+/// Break `text` into lines no wider than `options.max_width`.
+///
+/// Empty text gives no lines; a paragraph ending in a line terminator gives
+/// no empty line after it.
+pub fn wrap_text(
+    shaper: &dyn Shaper,
+    text: &str,
+    font: &Arc<dyn FontRef>,
+    params: &ShapingParams,
+    options: &WrapOptions,
+) -> Result<Vec<Line>> {
+    if text.is_empty() {
+        return Ok(Vec::new());
+    }
+    let opportunities = opportunities(text, options.hyphenator.as_deref());
+    let widths = Advances::new(&shaper.shape(text, font.clone(), params)?);
+    let hyphen_width = shaper
+        .shape(&options.hyphen, font.clone(), params)?
+        .advance_width;
+
+    let shape_line = |start: usize, end: &Opportunity| -> Result<Line> {
+        let range = start..line_end(text, start, end.offset);
+        let hyphenated = end.hyphen && !end.mandatory;
+        let mut shown = text[range.clone()].to_string();
+        if hyphenated {
+            shown = shown.trim_end_matches(SOFT_HYPHEN).to_string();
+            shown.push_str(&options.hyphen);
+        }
+        let shaped = shaper.shape(&shown, font.clone(), params)?;
+        Ok(Line {
+            range,
+            text: shown,
+            shaped,
+            hyphenated,
+        })
+    };
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut next = 0;
+    while start < text.len() {
+        // Opportunities after `start`, up to and including the first
+        // mandatory one
+        let mut candidates = Vec::new();
+        for (i, opportunity) in opportunities.iter().enumerate().skip(next) {
+            if opportunity.offset <= start {
+                continue;
+            }
+            candidates.push(i);
+            if opportunity.mandatory {
+                break;
+            }
+        }
+        if candidates.is_empty() {
+            break;
+        }
+
+        // Furthest candidate whose estimate fits, then back off until the
+        // reshaped line really does
+        let estimate = |i: usize| {
+            let o = &opportunities[i];
+            let end = line_end(text, start, o.offset);
+            widths.between(start, end) + if o.hyphen { hyphen_width } else { 0.0 }
+        };
+        let mut pick = candidates
+            .iter()
+            .rposition(|&i| estimate(i) <= options.max_width)
+            .unwrap_or(0);
+        let line = loop {
+            let line = shape_line(start, &opportunities[candidates[pick]])?;
+            if pick == 0 || line.shaped.advance_width <= options.max_width {
+                break line;
+            }
+            pick -= 1;
+        };
+        let chosen = candidates[pick];
+        next = chosen + 1;
+        start = opportunities[chosen].offset;
+        lines.push(line);
+    }
+    Ok(lines)
+}
+
+/// End of a line that breaks at `offset`: trailing spaces and line
+/// terminators hang past the edge and are not part of it
+fn line_end(text: &str, start: usize, offset: usize) -> usize {
+    start
+        + text[start..offset]
+            .trim_end_matches(|c: char| c.is_whitespace())
+            .len()
+}
+
+/// UAX #14 opportunities merged with hyphenation points, in text order
+fn opportunities(text: &str, hyphenator: Option<&dyn Hyphenator>) -> Vec<Opportunity> {
+    let mut all = Vec::new();
+    let mut word_start = 0;
+    for line_break in line_breaks(text) {
+        let offset = line_break.offset;
+        if let Some(hyphenator) = hyphenator {
+            // The word is the segment without what hangs after it
+            let segment = &text[word_start..offset];
+            let word = segment.trim_end_matches(|c: char| !c.is_alphabetic());
+            let lead = word.len() - word.trim_start_matches(|c: char| !c.is_alphabetic()).len();
+            let word = &word[lead..];
+            if word.chars().all(char::is_alphabetic) {
+                for point in hyphenator.hyphenate(word) {
+                    if point > 0 && point < word.len() && word.is_char_boundary(point) {
+                        all.push(Opportunity {
+                            offset: word_start + lead + point,
+                            mandatory: false,
+                            hyphen: true,
+                        });
+                    }
+                }
+            }
+        }
+        let before = text[..offset].chars().next_back();
+        all.push(Opportunity {
+            offset,
+            mandatory: line_break.mandatory && offset < text.len(),
+            hyphen: before == Some(SOFT_HYPHEN),
+        });
+        word_start = offset;
+    }
+    all
+}
+
+/// Advance of each cluster of a shaped paragraph, for width estimates
+struct Advances {
+    /// Cluster offsets in ascending order, with advances summed up to each
+    clusters: Vec<usize>,
+    sums: Vec<f32>,
+}
+
+impl Advances {
+    fn new(shaped: &ShapingResult) -> Self {
+        let mut per_cluster: Vec<(usize, f32)> = shaped
+            .glyphs
+            .iter()
+            .map(|g| (g.cluster as usize, g.advance))
+            .collect();
+        per_cluster.sort_by_key(|&(cluster, _)| cluster);
+        let mut clusters = Vec::with_capacity(per_cluster.len());
+        let mut sums = vec![0.0];
+        for (cluster, advance) in per_cluster {
+            clusters.push(cluster);
+            sums.push(sums.last().copied().unwrap_or_default() + advance);
+        }
+        Self { clusters, sums }
+    }
+
+    /// Summed advances of the clusters starting in `start..end`
+    fn between(&self, start: usize, end: usize) -> f32 {
+        let first = self.clusters.partition_point(|&c| c < start);
+        let last = self.clusters.partition_point(|&c| c < end);
+        self.sums[last] - self.sums[first]
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::boundaries::is_line_terminator;
+    use crate::hyphenate::TexHyphenator;
+    use typf_core::types::{Direction, GlyphId, PositionedGlyph};
+
+    struct TestFont;
+
+    impl FontRef for TestFont {
+        fn data(&self) -> &[u8] {
+            &[]
+        }
+
+        fn units_per_em(&self) -> u16 {
+            1000
+        }
+
+        fn glyph_id(&self, ch: char) -> Option<GlyphId> {
+            Some(ch as u32)
+        }
+
+        fn advance_width(&self, _glyph_id: GlyphId) -> f32 {
+            10.0
+        }
+    }
+
+    /// 10px per character; soft hyphens and line terminators take no room
+    struct TestShaper;
+
+    impl Shaper for TestShaper {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn shape(
+            &self,
+            text: &str,
+            _font: Arc<dyn FontRef>,
+            params: &ShapingParams,
+        ) -> Result<ShapingResult> {
+            let mut x = 0.0;
+            let glyphs = text
+                .char_indices()
+                .map(|(offset, ch)| {
+                    let advance = if ch == SOFT_HYPHEN || is_line_terminator(ch) {
+                        0.0
+                    } else {
+                        10.0
+                    };
+                    let glyph = PositionedGlyph {
+                        id: ch as u32,
+                        x,
+                        y: 0.0,
+                        advance,
+                        cluster: offset as u32,
+                    };
+                    x += advance;
+                    glyph
+                })
+                .collect();
+            Ok(ShapingResult {
+                glyphs,
+                advance_width: x,
+                advance_height: params.size,
+                direction: Direction::LeftToRight,
+            })
+        }
+    }
+
+    fn wrap(text: &str, options: &WrapOptions) -> Vec<String> {
+        let font: Arc<dyn FontRef> = Arc::new(TestFont);
+        wrap_text(&TestShaper, text, &font, &ShapingParams::default(), options)
+            .expect("wrap")
+            .into_iter()
+            .map(|line| line.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_wrap_when_words_fill_lines_then_breaks_between_words() {
+        let lines = wrap("the quick brown fox", &WrapOptions::new(100.0));
+        assert_eq!(lines, ["the quick", "brown fox"]);
+    }
+
+    #[test]
+    fn test_wrap_when_newline_then_breaks_there() {
+        let lines = wrap("a b\nc d\n", &WrapOptions::new(1000.0));
+        assert_eq!(lines, ["a b", "c d"]);
+    }
+
+    #[test]
+    fn test_wrap_when_word_too_long_without_hyphenator_then_overflows() {
+        let lines = wrap("a hyphenation b", &WrapOptions::new(60.0));
+        assert_eq!(lines, ["a", "hyphenation", "b"]);
+    }
+
+    #[test]
+    fn test_wrap_when_hyphenator_set_then_breaks_inside_word_with_hyphen() {
+        let hyphenator = TexHyphenator::new("hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n", "");
+        let options = WrapOptions::new(80.0).with_hyphenator(Arc::new(hyphenator));
+        let font: Arc<dyn FontRef> = Arc::new(TestFont);
+        let lines = wrap_text(
+            &TestShaper,
+            "a hyphenation",
+            &font,
+            &ShapingParams::default(),
+            &options,
+        )
+        .expect("wrap");
+        let texts: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+        // "a hyphen-" is 90px with its hyphen, so only "a hy-" fits
+        assert_eq!(texts, ["a hy-", "phen-", "ation"]);
+        assert!(lines[0].hyphenated && !lines[2].hyphenated);
+        assert!(lines.iter().all(|l| l.shaped.advance_width <= 80.0));
+        assert_eq!(lines[1].range, 4..8);
+    }
+
+    #[test]
+    fn test_wrap_when_soft_hyphen_used_then_visible_hyphen() {
+        let lines = wrap("extra\u{00AD}ordinary", &WrapOptions::new(80.0));
+        assert_eq!(lines, ["extra-", "ordinary"]);
+    }
+}
@@ -0,0 +1,98 @@
+//! UAX #9 conformance against the Unicode Character Database's
+//! BidiCharacterTest.txt
+//!
+//! The UCD file is not checked in yet; `scripts/fetch-bidi-test-data.sh`
+//! downloads the pinned version to `tests/data/BidiCharacterTest.txt`, and
+//! `test_bidi_character_test` runs every case in it. Until then it skips,
+//! and only the hand-written smoke cases in `data/bidi_character_cases.txt`
+//! run. Those share the UCD file's format but prove nothing about
+//! conformance.
+
+#![allow(clippy::expect_used, clippy::panic)]
+
+use std::path::Path;
+
+use typf_core::types::Direction;
+use typf_unicode::BidiParagraph;
+
+const SMOKE_CASES: &str = include_str!("data/bidi_character_cases.txt");
+
+fn parse_list<T>(field: &str, parse: impl Fn(&str) -> T) -> Vec<T> {
+    field.split_whitespace().map(parse).collect()
+}
+
+/// Check every case in `data`, a file in BidiCharacterTest.txt format, and
+/// return how many ran
+///
+/// Each non-comment line has five fields separated by semicolons: the code
+/// points of the paragraph; its direction (0 left to right, 1 right to
+/// left, 2 auto); the resolved paragraph embedding level; the resolved
+/// level of each character, x for those removed by X9; and the visual
+/// order, as character indices left to right without the removed ones.
+fn run_cases(name: &str, data: &str) -> usize {
+    let mut count = 0;
+    for (number, line) in data.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let context = format!("{name} line {}: {line}", number + 1);
+        let fields: Vec<&str> = line.split(';').collect();
+        assert_eq!(fields.len(), 5, "{context}");
+
+        let text: String = parse_list(fields[0], |cp| {
+            char::from_u32(u32::from_str_radix(cp, 16).expect("hex code point"))
+                .expect("scalar value")
+        })
+        .into_iter()
+        .collect();
+        let base = match fields[1] {
+            "0" => Some(Direction::LeftToRight),
+            "1" => Some(Direction::RightToLeft),
+            "2" => None,
+            other => panic!("{context}: paragraph direction {other}"),
+        };
+        let expected_level = fields[2].parse::<u8>().expect("paragraph level");
+        let expected_levels = parse_list(fields[3], |level| match level {
+            "x" => None,
+            level => Some(level.parse::<u8>().expect("level")),
+        });
+        let expected_order = parse_list(fields[4], |i| i.parse::<usize>().expect("index"));
+
+        let paragraph = BidiParagraph::new(&text, base);
+        let offsets: Vec<usize> = text.char_indices().map(|(offset, _)| offset).collect();
+        let order: Vec<usize> = paragraph
+            .visual_order(0..text.len())
+            .into_iter()
+            .map(|offset| offsets.binary_search(&offset).expect("char offset"))
+            .collect();
+
+        assert_eq!(paragraph.base_level(), expected_level, "{context}");
+        assert_eq!(
+            paragraph.levels(0..text.len()),
+            expected_levels,
+            "{context}"
+        );
+        assert_eq!(order, expected_order, "{context}");
+        count += 1;
+    }
+    count
+}
+
+#[test]
+fn test_bidi_character_test() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/BidiCharacterTest.txt");
+    let Ok(data) = std::fs::read_to_string(&path) else {
+        eprintln!(
+            "Skipping test: run scripts/fetch-bidi-test-data.sh to get BidiCharacterTest.txt"
+        );
+        return;
+    };
+    // The UCD file has about 90,000 cases; a short file is a wrong download
+    assert!(run_cases("BidiCharacterTest.txt", &data) > 10_000);
+}
+
+#[test]
+fn test_bidi_smoke_cases() {
+    assert!(run_cases("bidi_character_cases.txt", SMOKE_CASES) > 0);
+}